@@ -2,33 +2,87 @@
 //!
 //! Implements a subset of the A2A spec:
 //! - Agent Card at `GET /.well-known/agent.json`
-//! - `POST /a2a` for `SendMessage` / `GetTask` / `ListTasks`
+//! - `POST /a2a` for `SendMessage` / `GetTask` / `ListTasks` / `CancelTask`
+//!
+//! Requests are parsed into [`A2aRequest`], whose [`A2aMethod`] carries the
+//! typed params of each method, and answered with an [`A2aResponse`].
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+use crate::schema::{self, Migration};
+use crate::skills::SkillRegistry;
+use crate::tool::ToolRegistry;
+
+/// Schema history of the `tasks` table.
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create tasks",
+    "CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        status TEXT NOT NULL,
+        message TEXT NOT NULL,
+        result TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );",
+)];
+
+/// Version of the `tasks` schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
 // ---------------------------------------------------------------------------
 // Agent Card
 // ---------------------------------------------------------------------------
 
-/// Returns the static Agent Card for Y-GN.
-pub fn agent_card() -> Value {
+/// Returns the Agent Card for Y-GN, advertising each tool in `tools` and
+/// each skill in `skills` as a skill with the input schema it accepts.
+///
+/// Tools are tagged `tool` and take their own parameters.  Skills keep
+/// their tags plus `skill`; they are run by name through the `run_skill`
+/// tool and take no input.
+pub fn agent_card(tools: &ToolRegistry, skills: &SkillRegistry) -> Value {
+    let mut skill_defs = skills.list();
+    skill_defs.sort_by(|a, b| a.name.cmp(&b.name));
+    let card_skills: Vec<Value> = tools
+        .list()
+        .into_iter()
+        .map(|spec| {
+            json!({
+                "id": spec.name,
+                "name": spec.name,
+                "description": spec.description,
+                "tags": ["tool"],
+                "inputSchema": spec.parameters_schema,
+            })
+        })
+        .chain(skill_defs.into_iter().map(|skill| {
+            let mut tags = skill.tags.clone();
+            tags.push("skill".to_string());
+            json!({
+                "id": format!("skill:{}", skill.name),
+                "name": skill.name,
+                "description": skill.description,
+                "tags": tags,
+                "inputSchema": {"type": "object", "properties": {}},
+            })
+        }))
+        .collect();
     json!({
         "name": "Y-GN",
         "description": "Distributed multi-agent runtime with governance and audit",
         "version": env!("CARGO_PKG_VERSION"),
         "provider": {"organization": "Y-GN Project"},
         "capabilities": {"streaming": false, "pushNotifications": false},
-        "skills": [
-            {"id": "orchestrate", "name": "HiveMind Pipeline", "description": "7-phase cognitive execution"},
-            {"id": "guard", "name": "Security Guard", "description": "Prompt injection detection"},
-            {"id": "evidence", "name": "Evidence Export", "description": "Tamper-evident audit trail"}
+        "skills": card_skills,
+        "interfaces": [
+            {"protocol": "jsonrpc", "url": "/mcp"},
+            {"protocol": "a2a", "url": "/a2a"}
         ],
-        "interfaces": [{"protocol": "jsonrpc", "url": "/mcp"}],
         "securitySchemes": {}
     })
 }
@@ -37,14 +91,49 @@ pub fn agent_card() -> Value {
 // A2A Task store
 // ---------------------------------------------------------------------------
 
-/// Status of an A2A task.
+/// Status of an A2A task.  The `pending` and `running` names used by
+/// earlier releases are still read, as `Submitted` and `Working`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskStatus {
-    Pending,
-    Running,
+    #[serde(alias = "pending")]
+    Submitted,
+    #[serde(alias = "running")]
+    Working,
     Completed,
     Failed,
+    Canceled,
+}
+
+impl TaskStatus {
+    /// Whether the task has finished and can no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Canceled
+        )
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Submitted => "submitted",
+            TaskStatus::Working => "working",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Canceled => "canceled",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "submitted" | "pending" => Some(TaskStatus::Submitted),
+            "working" | "running" => Some(TaskStatus::Working),
+            "completed" => Some(TaskStatus::Completed),
+            "failed" => Some(TaskStatus::Failed),
+            "canceled" => Some(TaskStatus::Canceled),
+            _ => None,
+        }
+    }
 }
 
 /// An A2A task.
@@ -56,54 +145,125 @@ pub struct A2aTask {
     pub result: Option<String>,
 }
 
-/// In-memory A2A task store.
-#[derive(Debug, Clone, Default)]
+/// Outcome of [`TaskStore::cancel_task`].
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    /// The task was submitted or working and is now canceled.
+    Canceled(A2aTask),
+    /// The task had already finished; it is returned unchanged.
+    NotCancelable(A2aTask),
+    NotFound,
+}
+
+#[derive(Debug)]
+enum Backend {
+    Memory(Mutex<HashMap<String, A2aTask>>),
+    Sqlite(SqliteTaskStore),
+}
+
+/// A2A task store, in memory or persisted to SQLite.
+///
+/// Clones share the same tasks, so one store can be kept in the gateway
+/// state and used by every request.
+#[derive(Debug, Clone)]
 pub struct TaskStore {
-    tasks: Arc<Mutex<HashMap<String, A2aTask>>>,
+    backend: Arc<Backend>,
+}
+
+impl Default for TaskStore {
+    fn default() -> Self {
+        Self {
+            backend: Arc::new(Backend::Memory(Mutex::new(HashMap::new()))),
+        }
+    }
 }
 
 impl TaskStore {
+    /// An in-memory store.
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Create a new pending task from a message. Returns the task.
-    pub fn create_task(&self, message: &str) -> A2aTask {
+    /// A store persisted to the SQLite database at `path`.
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend: Arc::new(Backend::Sqlite(SqliteTaskStore::new(path)?)),
+        })
+    }
+
+    /// Create a task from a message. Returns the task.
+    pub fn create_task(&self, message: &str) -> anyhow::Result<A2aTask> {
         let task = A2aTask {
             id: Uuid::new_v4().to_string(),
             status: TaskStatus::Completed,
             message: message.to_string(),
             result: Some(format!("Processed: {message}")),
         };
-        self.tasks
-            .lock()
-            .unwrap()
-            .insert(task.id.clone(), task.clone());
-        task
+        self.save(&task)?;
+        Ok(task)
     }
 
     /// Get a task by ID.
-    pub fn get_task(&self, id: &str) -> Option<A2aTask> {
-        self.tasks.lock().unwrap().get(id).cloned()
+    pub fn get_task(&self, id: &str) -> anyhow::Result<Option<A2aTask>> {
+        match &*self.backend {
+            Backend::Memory(tasks) => Ok(tasks.lock().unwrap().get(id).cloned()),
+            Backend::Sqlite(db) => db.load(id),
+        }
     }
 
     /// List recent tasks (up to `limit`).
-    pub fn list_tasks(&self, limit: usize) -> Vec<A2aTask> {
-        self.tasks
-            .lock()
-            .unwrap()
-            .values()
-            .take(limit)
-            .cloned()
-            .collect()
+    pub fn list_tasks(&self, limit: usize) -> anyhow::Result<Vec<A2aTask>> {
+        match &*self.backend {
+            Backend::Memory(tasks) => Ok(tasks
+                .lock()
+                .unwrap()
+                .values()
+                .take(limit)
+                .cloned()
+                .collect()),
+            Backend::Sqlite(db) => db.load_recent(limit),
+        }
+    }
+
+    /// Cancel a task that has not finished yet.
+    pub fn cancel_task(&self, id: &str) -> anyhow::Result<CancelOutcome> {
+        let Some(mut task) = self.get_task(id)? else {
+            return Ok(CancelOutcome::NotFound);
+        };
+        if task.status.is_terminal() {
+            return Ok(CancelOutcome::NotCancelable(task));
+        }
+        task.status = TaskStatus::Canceled;
+        self.save(&task)?;
+        Ok(CancelOutcome::Canceled(task))
+    }
+
+    /// Insert or replace a task.
+    fn save(&self, task: &A2aTask) -> anyhow::Result<()> {
+        match &*self.backend {
+            Backend::Memory(tasks) => {
+                tasks.lock().unwrap().insert(task.id.clone(), task.clone());
+                Ok(())
+            }
+            Backend::Sqlite(db) => db.save(task),
+        }
     }
 }
 
+/// Default on-disk location of the A2A task database (`~/.ygn/a2a_tasks.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/a2a_tasks.db")
+}
+
 // ---------------------------------------------------------------------------
 // Persistent (SQLite) task store
 // ---------------------------------------------------------------------------
 
 /// Persistent A2A task store backed by SQLite.
+#[derive(Debug)]
 pub struct SqliteTaskStore {
     conn: Mutex<Connection>,
 }
@@ -112,19 +272,9 @@ impl SqliteTaskStore {
     /// Open (or create) a SQLite-backed task store at `path`.
     /// Pass `":memory:"` for an ephemeral in-memory database.
     pub fn new(path: &str) -> anyhow::Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch(
-            "PRAGMA journal_mode=WAL;
-             PRAGMA synchronous=NORMAL;
-             CREATE TABLE IF NOT EXISTS tasks (
-                 id TEXT PRIMARY KEY,
-                 status TEXT NOT NULL,
-                 message TEXT NOT NULL,
-                 result TEXT,
-                 created_at TEXT NOT NULL,
-                 updated_at TEXT NOT NULL
-             );",
-        )?;
+        let mut conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
         Ok(Self {
             conn: Mutex::new(conn),
         })
@@ -136,22 +286,22 @@ impl SqliteTaskStore {
         let id = Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
         let result_text = format!("Processed: {message}");
+        let status = TaskStatus::Completed.as_str();
         let conn = self.conn.lock().unwrap();
         conn.execute(
             "INSERT INTO tasks (id, status, message, result, created_at, updated_at) \
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![id, "Completed", message, result_text, now, now],
+            rusqlite::params![id, status, message, result_text, now, now],
         )
         .unwrap();
         json!({
             "id": id,
-            "status": "Completed",
+            "status": status,
             "message": message,
             "result": result_text,
             "created_at": now,
         })
     }
-
     /// Retrieve a task by its ID.
     pub fn get_task(&self, id: &str) -> Option<Value> {
         let conn = self.conn.lock().unwrap();
@@ -193,65 +343,302 @@ impl SqliteTaskStore {
         .filter_map(|r| r.ok())
         .collect()
     }
+
+    /// Insert or replace `task`, keeping its original creation time.
+    fn save(&self, task: &A2aTask) -> anyhow::Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO tasks (id, status, message, result, created_at, updated_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5) \
+             ON CONFLICT(id) DO UPDATE SET status = ?2, result = ?4, updated_at = ?5",
+            rusqlite::params![
+                task.id,
+                task.status.as_str(),
+                task.message,
+                task.result,
+                now
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn load(&self, id: &str) -> anyhow::Result<Option<A2aTask>> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn
+            .query_row(
+                "SELECT id, status, message, result FROM tasks WHERE id = ?1",
+                rusqlite::params![id],
+                row_to_task,
+            )
+            .optional()?)
+    }
+
+    fn load_recent(&self, limit: usize) -> anyhow::Result<Vec<A2aTask>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, status, message, result FROM tasks \
+             ORDER BY created_at DESC LIMIT ?1",
+        )?;
+        let tasks = stmt
+            .query_map(rusqlite::params![limit as i64], row_to_task)?
+            .collect::<Result<_, _>>()?;
+        Ok(tasks)
+    }
+}
+
+fn row_to_task(row: &rusqlite::Row<'_>) -> rusqlite::Result<A2aTask> {
+    let status: String = row.get(1)?;
+    Ok(A2aTask {
+        id: row.get(0)?,
+        status: TaskStatus::parse(&status).unwrap_or(TaskStatus::Failed),
+        message: row.get(2)?,
+        result: row.get(3)?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Protocol types
+// ---------------------------------------------------------------------------
+
+/// One part of a [`Message`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Part {
+    Text { text: String },
+}
+
+/// A message sent to the agent.  Also read from a bare string, which
+/// becomes a single text part from the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "MessageRepr")]
+pub struct Message {
+    pub role: String,
+    pub parts: Vec<Part>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageRepr {
+    Text(String),
+    Full { role: String, parts: Vec<Part> },
+}
+
+impl From<MessageRepr> for Message {
+    fn from(repr: MessageRepr) -> Self {
+        match repr {
+            MessageRepr::Text(text) => Message::user(text),
+            MessageRepr::Full { role, parts } => Message { role, parts },
+        }
+    }
+}
+
+impl Message {
+    /// A single-part text message from the user.
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            role: "user".into(),
+            parts: vec![Part::Text { text: text.into() }],
+        }
+    }
+
+    /// The text parts, joined by newlines.
+    pub fn text(&self) -> String {
+        self.parts
+            .iter()
+            .map(|Part::Text { text }| text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Params of `SendMessage`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendMessageParams {
+    pub message: Message,
+}
+
+/// Params of `GetTask` and `CancelTask`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskIdParams {
+    pub task_id: String,
+}
+
+/// Params of `ListTasks`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListTasksParams {
+    pub limit: usize,
+}
+
+impl Default for ListTasksParams {
+    fn default() -> Self {
+        Self { limit: 10 }
+    }
+}
+
+/// An A2A method with its params.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params")]
+pub enum A2aMethod {
+    SendMessage(SendMessageParams),
+    GetTask(TaskIdParams),
+    ListTasks(ListTasksParams),
+    CancelTask(TaskIdParams),
+}
+
+impl A2aMethod {
+    /// Names of the supported methods.
+    pub const NAMES: [&'static str; 4] = ["SendMessage", "GetTask", "ListTasks", "CancelTask"];
+}
+
+/// An A2A JSON-RPC request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct A2aRequest {
+    #[serde(default = "jsonrpc_version")]
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    #[serde(flatten)]
+    pub method: A2aMethod,
+}
+
+fn jsonrpc_version() -> String {
+    "2.0".to_string()
+}
+
+/// The `result` of a successful [`A2aResponse`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum A2aResult {
+    Task { task: A2aTask },
+    Tasks { tasks: Vec<A2aTask> },
+}
+
+/// The `error` of a failed [`A2aResponse`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct A2aError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl A2aError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// An A2A JSON-RPC response: a `result` or an `error`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A2aResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<A2aResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<A2aError>,
+}
+
+impl A2aResponse {
+    fn new(id: Value, outcome: Result<A2aResult, A2aError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: jsonrpc_version(),
+            id,
+            result,
+            error,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // A2A message handler
 // ---------------------------------------------------------------------------
 
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// A2A error code for a task that has already finished.
+const TASK_NOT_CANCELABLE: i64 = -32002;
+
+/// Parse a raw request, telling a malformed envelope (`-32600`), an
+/// unknown method (`-32601`) and bad params (`-32602`) apart.
+pub fn parse_request(request: &Value) -> Result<A2aRequest, A2aError> {
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Err(A2aError::new(
+            INVALID_REQUEST,
+            "Invalid request: expected an object with a string `method`",
+        ));
+    };
+    if !A2aMethod::NAMES.contains(&method) {
+        return Err(A2aError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown A2A method: {method}"),
+        ));
+    }
+    let mut request = request.clone();
+    if request.get("params").is_none_or(Value::is_null) {
+        request["params"] = json!({});
+    }
+    serde_json::from_value(request)
+        .map_err(|e| A2aError::new(INVALID_PARAMS, format!("Invalid params for {method}: {e}")))
+}
+
 /// Handle an A2A JSON-RPC request.
 ///
-/// Supports: `SendMessage`, `GetTask`, `ListTasks`.
+/// Supports: `SendMessage`, `GetTask`, `ListTasks`, `CancelTask`.
 pub fn handle_a2a(request: &Value, store: &TaskStore) -> Value {
-    let method = request.get("method").and_then(|v| v.as_str()).unwrap_or("");
-    let id = request.get("id").cloned().unwrap_or(Value::Null);
-    let params = request.get("params").cloned().unwrap_or(json!({}));
-
-    match method {
-        "SendMessage" => {
-            let message = params
-                .get("message")
-                .and_then(|v| v.as_str())
-                .unwrap_or("(empty)");
-            let task = store.create_task(message);
-            json!({
-                "jsonrpc": "2.0",
-                "id": id,
-                "result": {
-                    "task": task
-                }
+    let response = match parse_request(request) {
+        Ok(request) => handle_request(request, store),
+        Err(error) => A2aResponse::new(
+            request.get("id").cloned().unwrap_or(Value::Null),
+            Err(error),
+        ),
+    };
+    serde_json::to_value(response).expect("A2A responses serialize")
+}
+
+/// Answer a parsed A2A request.
+pub fn handle_request(request: A2aRequest, store: &TaskStore) -> A2aResponse {
+    let not_found =
+        |task_id: &str| A2aError::new(INVALID_PARAMS, format!("Task not found: {task_id}"));
+    let outcome = match request.method {
+        A2aMethod::SendMessage(params) => store
+            .create_task(&params.message.text())
+            .map(|task| Ok(A2aResult::Task { task })),
+        A2aMethod::GetTask(TaskIdParams { task_id }) => {
+            store.get_task(&task_id).map(|task| match task {
+                Some(task) => Ok(A2aResult::Task { task }),
+                None => Err(not_found(&task_id)),
             })
         }
-        "GetTask" => {
-            let task_id = params.get("task_id").and_then(|v| v.as_str()).unwrap_or("");
-            match store.get_task(task_id) {
-                Some(task) => json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {"task": task}
-                }),
-                None => json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {"code": -32602, "message": format!("Task not found: {task_id}")}
-                }),
-            }
-        }
-        "ListTasks" => {
-            let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
-            let tasks = store.list_tasks(limit);
-            json!({
-                "jsonrpc": "2.0",
-                "id": id,
-                "result": {"tasks": tasks}
+        A2aMethod::ListTasks(ListTasksParams { limit }) => store
+            .list_tasks(limit)
+            .map(|tasks| Ok(A2aResult::Tasks { tasks })),
+        A2aMethod::CancelTask(TaskIdParams { task_id }) => {
+            store.cancel_task(&task_id).map(|outcome| match outcome {
+                CancelOutcome::Canceled(task) => Ok(A2aResult::Task { task }),
+                CancelOutcome::NotCancelable(task) => Err(A2aError::new(
+                    TASK_NOT_CANCELABLE,
+                    format!("Task {task_id} is already {}", task.status.as_str()),
+                )),
+                CancelOutcome::NotFound => Err(not_found(&task_id)),
             })
         }
-        _ => json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "error": {"code": -32601, "message": format!("Unknown A2A method: {method}")}
-        }),
-    }
+    };
+    let outcome = outcome.unwrap_or_else(|e| {
+        Err(A2aError::new(
+            INTERNAL_ERROR,
+            format!("Task store error: {e}"),
+        ))
+    });
+    A2aResponse::new(request.id, outcome)
 }
 
 // ---------------------------------------------------------------------------
@@ -264,13 +651,51 @@ mod tests {
 
     #[test]
     fn agent_card_discovery() {
-        let card = agent_card();
+        let skills = crate::skills::builtin_skills().unwrap();
+        let card = agent_card(&crate::mcp::McpServer::default_registry(), &skills);
         assert_eq!(card["name"], "Y-GN");
         assert!(card["skills"].as_array().unwrap().len() >= 3);
         assert_eq!(card["capabilities"]["streaming"], false);
         let interfaces = card["interfaces"].as_array().unwrap();
         assert_eq!(interfaces[0]["protocol"], "jsonrpc");
         assert_eq!(interfaces[0]["url"], "/mcp");
+        assert_eq!(interfaces[1]["protocol"], "a2a");
+        assert_eq!(interfaces[1]["url"], "/a2a");
+    }
+
+    #[test]
+    fn agent_card_skills_carry_input_schemas() {
+        let mut skills = SkillRegistry::new();
+        skills
+            .register(crate::skills::SkillDefinition {
+                name: "ping".to_string(),
+                description: "Echo a ping".to_string(),
+                version: "1.0.0".to_string(),
+                author: "test".to_string(),
+                steps: vec![],
+                tags: vec!["health".to_string()],
+                created_at: chrono::Utc::now(),
+            })
+            .unwrap();
+        let tools = crate::mcp::McpServer::default_registry();
+        let card = agent_card(&tools, &skills);
+        let card_skills = card["skills"].as_array().unwrap();
+
+        let echo = card_skills.iter().find(|s| s["id"] == "echo").unwrap();
+        assert_eq!(echo["tags"], json!(["tool"]));
+        assert_eq!(
+            echo["inputSchema"],
+            crate::tool::Tool::parameters_schema(&crate::tool::EchoTool)
+        );
+        assert_eq!(echo["inputSchema"]["properties"]["input"]["type"], "string");
+
+        let ping = card_skills
+            .iter()
+            .find(|s| s["id"] == "skill:ping")
+            .unwrap();
+        assert_eq!(ping["name"], "ping");
+        assert_eq!(ping["tags"], json!(["health", "skill"]));
+        assert_eq!(ping["inputSchema"]["type"], "object");
     }
 
     #[test]
@@ -294,7 +719,7 @@ mod tests {
     fn a2a_get_task() {
         let store = TaskStore::new();
         // Create a task first
-        let task = store.create_task("test message");
+        let task = store.create_task("test message").unwrap();
 
         let req = json!({
             "jsonrpc": "2.0",
@@ -308,6 +733,208 @@ mod tests {
         assert_eq!(resp["result"]["task"]["status"], "completed");
     }
 
+    #[test]
+    fn a2a_cancel_task() {
+        let store = TaskStore::new();
+        let pending = A2aTask {
+            id: "t-1".into(),
+            status: TaskStatus::Submitted,
+            message: "long job".into(),
+            result: None,
+        };
+        store.save(&pending).unwrap();
+        let cancel = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "CancelTask",
+            "params": {"task_id": "t-1"}
+        });
+        let resp = handle_a2a(&cancel, &store);
+        assert_eq!(resp["result"]["task"]["status"], "canceled");
+        assert_eq!(
+            store.get_task("t-1").unwrap().unwrap().status,
+            TaskStatus::Canceled
+        );
+
+        // Canceling again fails: the task is finished.
+        let resp = handle_a2a(&cancel, &store);
+        assert_eq!(resp["error"]["code"], TASK_NOT_CANCELABLE);
+
+        let missing = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "CancelTask",
+            "params": {"task_id": "nope"}
+        });
+        assert_eq!(
+            handle_a2a(&missing, &store)["error"]["code"],
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn malformed_requests_get_distinct_errors() {
+        let store = TaskStore::new();
+        let code = |req: Value| handle_a2a(&req, &store)["error"]["code"].clone();
+        assert_eq!(code(json!(["not", "an", "object"])), INVALID_REQUEST);
+        assert_eq!(code(json!({ "id": 1, "method": 7 })), INVALID_REQUEST);
+        assert_eq!(
+            code(json!({ "id": 1, "method": "Explode" })),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            code(json!({ "id": 1, "method": "GetTask" })),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(json!({ "id": 1, "method": "SendMessage", "params": { "message": 5 } })),
+            INVALID_PARAMS
+        );
+
+        let resp = handle_a2a(
+            &json!({ "id": 9, "method": "GetTask", "params": {} }),
+            &store,
+        );
+        assert_eq!(resp["id"], 9);
+        assert!(resp.get("result").is_none());
+        assert!(resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid params for GetTask: missing field `task_id`"));
+
+        // ListTasks needs no params.
+        let resp = handle_a2a(&json!({ "id": 2, "method": "ListTasks" }), &store);
+        assert_eq!(resp["result"]["tasks"], json!([]));
+    }
+
+    #[test]
+    fn send_message_accepts_a2a_message_objects() {
+        let store = TaskStore::new();
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "SendMessage",
+            "params": { "message": {
+                "role": "user",
+                "parts": [{ "kind": "text", "text": "line one" }, { "kind": "text", "text": "line two" }]
+            }}
+        });
+        let resp = handle_a2a(&req, &store);
+        assert_eq!(resp["result"]["task"]["message"], "line one\nline two");
+    }
+
+    #[test]
+    fn protocol_types_round_trip() {
+        let requests = [
+            A2aMethod::SendMessage(SendMessageParams {
+                message: Message::user("hi"),
+            }),
+            A2aMethod::GetTask(TaskIdParams {
+                task_id: "t-1".into(),
+            }),
+            A2aMethod::ListTasks(ListTasksParams { limit: 3 }),
+            A2aMethod::CancelTask(TaskIdParams {
+                task_id: "t-2".into(),
+            }),
+        ];
+        for method in requests {
+            let request = A2aRequest {
+                jsonrpc: "2.0".into(),
+                id: json!(7),
+                method,
+            };
+            let value = serde_json::to_value(&request).unwrap();
+            assert!(value["method"].is_string() && value["params"].is_object());
+            assert_eq!(
+                serde_json::from_value::<A2aRequest>(value).unwrap(),
+                request
+            );
+        }
+
+        let message = Message {
+            role: "agent".into(),
+            parts: vec![Part::Text {
+                text: "done".into(),
+            }],
+        };
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            value,
+            json!({ "role": "agent", "parts": [{ "kind": "text", "text": "done" }] })
+        );
+        assert_eq!(serde_json::from_value::<Message>(value).unwrap(), message);
+        assert_eq!(
+            serde_json::from_value::<Message>(json!("hi")).unwrap(),
+            Message::user("hi")
+        );
+
+        for status in [
+            TaskStatus::Submitted,
+            TaskStatus::Working,
+            TaskStatus::Completed,
+            TaskStatus::Failed,
+            TaskStatus::Canceled,
+        ] {
+            let value = serde_json::to_value(&status).unwrap();
+            assert_eq!(value, status.as_str());
+            assert_eq!(TaskStatus::parse(status.as_str()), Some(status.clone()));
+            assert_eq!(serde_json::from_value::<TaskStatus>(value).unwrap(), status);
+        }
+        // Statuses written by earlier releases still read.
+        assert_eq!(
+            serde_json::from_value::<TaskStatus>(json!("running")).unwrap(),
+            TaskStatus::Working
+        );
+        assert_eq!(TaskStatus::parse("pending"), Some(TaskStatus::Submitted));
+
+        let task = A2aTask {
+            id: "t-1".into(),
+            status: TaskStatus::Working,
+            message: "job".into(),
+            result: None,
+        };
+        let ok = A2aResponse::new(json!(1), Ok(A2aResult::Task { task }));
+        let value = serde_json::to_value(&ok).unwrap();
+        assert!(value.get("error").is_none());
+        let back: A2aResponse = serde_json::from_value(value).unwrap();
+        let Some(A2aResult::Task { task }) = back.result else {
+            panic!("expected a task result");
+        };
+        assert_eq!(task.status, TaskStatus::Working);
+
+        let listed = A2aResponse::new(json!(2), Ok(A2aResult::Tasks { tasks: vec![] }));
+        let back: A2aResponse =
+            serde_json::from_value(serde_json::to_value(&listed).unwrap()).unwrap();
+        assert!(matches!(back.result, Some(A2aResult::Tasks { tasks }) if tasks.is_empty()));
+
+        let failed = A2aResponse::new(json!("x"), Err(A2aError::new(METHOD_NOT_FOUND, "nope")));
+        let back: A2aResponse =
+            serde_json::from_value(serde_json::to_value(&failed).unwrap()).unwrap();
+        assert!(back.result.is_none());
+        assert_eq!(back.error, Some(A2aError::new(METHOD_NOT_FOUND, "nope")));
+    }
+
+    #[test]
+    fn sqlite_backed_store_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("ygn-a2a-{}.db", Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        let id = TaskStore::open(path)
+            .unwrap()
+            .create_task("remember me")
+            .unwrap()
+            .id;
+
+        let store = TaskStore::open(path).unwrap();
+        let task = store.get_task(&id).unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.message, "remember me");
+        assert_eq!(store.list_tasks(10).unwrap().len(), 1);
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{path}{suffix}")).ok();
+        }
+    }
+
     // -- SqliteTaskStore tests ------------------------------------------------
 
     #[test]
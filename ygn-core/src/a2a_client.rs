@@ -0,0 +1,751 @@
+//! A2A client: delegate tasks to other agents.
+//!
+//! An [`A2aClient`] fetches a remote agent's card from
+//! `/.well-known/agent.json`, sends it a `SendMessage` request and waits for
+//! the task to finish, either by subscribing to task updates over SSE when
+//! the card advertises streaming or by polling `GetTask`.  The finished
+//! [`A2aTask`] carries the agent's result.
+//!
+//! [`AskAgentTool`] exposes this as the `ask_agent` tool.  The agent's host,
+//! and the host of the endpoint its card names, are checked against a
+//! [`SandboxChecker`] before any request is made.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::{json, Value};
+
+use crate::a2a::{A2aTask, TaskStatus};
+use crate::sandbox::{AccessKind, AccessRequest, SandboxChecker};
+use crate::tool::{Tool, ToolContent, ToolResult};
+
+/// Default time to wait for a delegated task to finish.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default interval between `GetTask` polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Path of the agent card, relative to the agent's base URL.
+const AGENT_CARD_PATH: &str = ".well-known/agent.json";
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Why talking to a remote agent failed.
+#[derive(Debug, thiserror::Error)]
+pub enum A2aClientError {
+    #[error("invalid agent URL '{0}'")]
+    InvalidUrl(String),
+    #[error("request to {url} failed: {source}")]
+    Http {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("agent card at {url} is not valid: {reason}")]
+    InvalidCard { url: String, reason: String },
+    #[error("agent returned JSON-RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("malformed response from agent: {0}")]
+    InvalidResponse(String),
+    #[error("task {task_id} did not finish within {timeout:?}")]
+    Timeout { task_id: String, timeout: Duration },
+}
+
+// ---------------------------------------------------------------------------
+// Agent card
+// ---------------------------------------------------------------------------
+
+/// The parts of a remote agent card the client relies on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteAgentCard {
+    pub name: String,
+    /// Where A2A requests are sent.
+    pub endpoint: Url,
+    /// Whether the agent streams task updates over SSE.
+    pub streaming: bool,
+    /// Ids of the skills the agent advertises.
+    pub skills: Vec<String>,
+}
+
+impl RemoteAgentCard {
+    /// Validate a card fetched from `card_url`.
+    ///
+    /// The endpoint is the `url` of an `a2a` entry in `interfaces`, else the
+    /// card's top-level `url`; relative URLs resolve against `card_url`.
+    pub fn parse(card: &Value, card_url: &Url) -> Result<Self, A2aClientError> {
+        let invalid = |reason: &str| A2aClientError::InvalidCard {
+            url: card_url.to_string(),
+            reason: reason.to_string(),
+        };
+        if !card.is_object() {
+            return Err(invalid("not a JSON object"));
+        }
+        let name = card
+            .get("name")
+            .and_then(Value::as_str)
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| invalid("missing name"))?;
+        let endpoint = card
+            .get("interfaces")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .find(|i| i.get("protocol").and_then(Value::as_str) == Some("a2a"))
+            .and_then(|i| i.get("url"))
+            .or_else(|| card.get("url"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("no A2A endpoint"))?;
+        let endpoint = card_url
+            .join(endpoint)
+            .map_err(|e| invalid(&format!("bad endpoint '{endpoint}': {e}")))?;
+        let streaming = card
+            .pointer("/capabilities/streaming")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let skills = card
+            .get("skills")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.get("id").and_then(Value::as_str).map(String::from))
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            endpoint,
+            streaming,
+            skills,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// A2aClient
+// ---------------------------------------------------------------------------
+
+/// Client for one remote agent.
+#[derive(Debug)]
+pub struct A2aClient {
+    base_url: Url,
+    client: reqwest::Client,
+    timeout: Duration,
+    poll_interval: Duration,
+    next_id: AtomicU64,
+}
+
+impl A2aClient {
+    /// A client for the agent served at `base_url`, which may include a
+    /// path prefix.
+    pub fn new(base_url: &str) -> Result<Self, A2aClientError> {
+        let mut base_url =
+            Url::parse(base_url).map_err(|_| A2aClientError::InvalidUrl(base_url.to_string()))?;
+        // Keep any path prefix when joining the card path.
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        if !matches!(base_url.scheme(), "http" | "https") || base_url.host_str().is_none() {
+            return Err(A2aClientError::InvalidUrl(base_url.to_string()));
+        }
+        // Redirects are not followed: their targets have not been vetted.
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("failed to build HTTP client");
+        Ok(Self {
+            base_url,
+            client,
+            timeout: DEFAULT_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Give up on a task that has not finished after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Poll unfinished tasks every `interval`.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The agent's base URL.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Fetch and validate the agent card.
+    pub async fn fetch_card(&self) -> Result<RemoteAgentCard, A2aClientError> {
+        let url = self
+            .base_url
+            .join(AGENT_CARD_PATH)
+            .map_err(|_| A2aClientError::InvalidUrl(self.base_url.to_string()))?;
+        let response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|source| A2aClientError::Http {
+                url: url.to_string(),
+                source,
+            })?;
+        if !response.status().is_success() {
+            return Err(A2aClientError::InvalidCard {
+                url: url.to_string(),
+                reason: format!("HTTP {}", response.status()),
+            });
+        }
+        let card: Value = response
+            .json()
+            .await
+            .map_err(|e| A2aClientError::InvalidCard {
+                url: url.to_string(),
+                reason: e.to_string(),
+            })?;
+        RemoteAgentCard::parse(&card, &url)
+    }
+
+    /// Send `message` to the agent, creating a task.
+    pub async fn send_message(
+        &self,
+        card: &RemoteAgentCard,
+        message: &str,
+    ) -> Result<A2aTask, A2aClientError> {
+        let result = self
+            .call(card, "SendMessage", json!({ "message": message }))
+            .await?;
+        task_of(&result)
+    }
+
+    /// Fetch the current state of a task.
+    pub async fn get_task(
+        &self,
+        card: &RemoteAgentCard,
+        task_id: &str,
+    ) -> Result<A2aTask, A2aClientError> {
+        let result = self
+            .call(card, "GetTask", json!({ "task_id": task_id }))
+            .await?;
+        task_of(&result)
+    }
+
+    /// Send `message` and wait until the task it creates finishes.
+    pub async fn send_and_wait(
+        &self,
+        card: &RemoteAgentCard,
+        message: &str,
+    ) -> Result<A2aTask, A2aClientError> {
+        let task = self.send_message(card, message).await?;
+        if task.status.is_terminal() {
+            return Ok(task);
+        }
+        let task_id = task.id.clone();
+        tokio::time::timeout(self.timeout, self.wait(card, task))
+            .await
+            .map_err(|_| A2aClientError::Timeout {
+                task_id,
+                timeout: self.timeout,
+            })?
+    }
+
+    /// Fetch the card, send `message` and return the finished task.
+    pub async fn ask(&self, message: &str) -> Result<A2aTask, A2aClientError> {
+        let card = self.fetch_card().await?;
+        self.send_and_wait(&card, message).await
+    }
+
+    // -- internals ---------------------------------------------------------
+
+    /// Wait for `task` to finish, over SSE when the agent streams and by
+    /// polling otherwise or if the stream ends early.
+    async fn wait(&self, card: &RemoteAgentCard, task: A2aTask) -> Result<A2aTask, A2aClientError> {
+        if card.streaming {
+            if let Some(done) = self.subscribe(card, &task.id).await? {
+                return Ok(done);
+            }
+        }
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+            let task = self.get_task(card, &task.id).await?;
+            if task.status.is_terminal() {
+                return Ok(task);
+            }
+        }
+    }
+
+    /// Follow `SubscribeToTask` events until the task finishes.  Returns
+    /// `None` if the agent does not answer with an event stream or the
+    /// stream ends first.
+    async fn subscribe(
+        &self,
+        card: &RemoteAgentCard,
+        task_id: &str,
+    ) -> Result<Option<A2aTask>, A2aClientError> {
+        let http_error = |source| A2aClientError::Http {
+            url: card.endpoint.to_string(),
+            source,
+        };
+        let mut response = self
+            .client
+            .post(card.endpoint.clone())
+            .header("accept", "text/event-stream")
+            .json(&self.request("SubscribeToTask", json!({ "task_id": task_id })))
+            .send()
+            .await
+            .map_err(http_error)?;
+        let is_stream = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if !response.status().is_success() || !is_stream {
+            return Ok(None);
+        }
+
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(http_error)? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim_end().strip_prefix("data:") else {
+                    continue;
+                };
+                let event: Value = serde_json::from_str(data.trim())
+                    .map_err(|e| A2aClientError::InvalidResponse(format!("bad event: {e}")))?;
+                let task = task_of(&rpc_result(event)?)?;
+                if task.status.is_terminal() {
+                    return Ok(Some(task));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn request(&self, method: &str, params: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        })
+    }
+
+    /// Send a JSON-RPC request to the agent's endpoint and return its
+    /// `result`.
+    async fn call(
+        &self,
+        card: &RemoteAgentCard,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, A2aClientError> {
+        let response = self
+            .client
+            .post(card.endpoint.clone())
+            .json(&self.request(method, params))
+            .send()
+            .await
+            .map_err(|source| A2aClientError::Http {
+                url: card.endpoint.to_string(),
+                source,
+            })?;
+        let status = response.status();
+        let body: Value = response.json().await.map_err(|e| {
+            A2aClientError::InvalidResponse(format!("HTTP {status} with unreadable body: {e}"))
+        })?;
+        rpc_result(body)
+    }
+}
+
+/// The `result` of a JSON-RPC response, or its `error` as
+/// [`A2aClientError::Rpc`].
+fn rpc_result(mut response: Value) -> Result<Value, A2aClientError> {
+    if let Some(error) = response.get("error") {
+        return Err(A2aClientError::Rpc {
+            code: error.get("code").and_then(Value::as_i64).unwrap_or(-32603),
+            message: error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+                .to_string(),
+        });
+    }
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(A2aClientError::InvalidResponse(
+            "response has neither result nor error".to_string(),
+        )),
+    }
+}
+
+/// The task in a `{"task": ...}` result.
+fn task_of(result: &Value) -> Result<A2aTask, A2aClientError> {
+    let task = result
+        .get("task")
+        .ok_or_else(|| A2aClientError::InvalidResponse("result has no task".to_string()))?;
+    serde_json::from_value(task.clone())
+        .map_err(|e| A2aClientError::InvalidResponse(format!("bad task: {e}")))
+}
+
+// ---------------------------------------------------------------------------
+// AskAgentTool
+// ---------------------------------------------------------------------------
+
+/// Delegates a message to a peer agent over A2A and returns its result.
+pub struct AskAgentTool {
+    sandbox: Box<dyn SandboxChecker>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl std::fmt::Debug for AskAgentTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AskAgentTool")
+            .field("sandbox", &self.sandbox.profile_name())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl AskAgentTool {
+    /// Only agents on hosts `sandbox` allows network access to can be asked.
+    pub fn new(sandbox: Box<dyn SandboxChecker>) -> Self {
+        Self {
+            sandbox,
+            timeout: DEFAULT_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Default time to wait for the agent, unless a call sets `timeout_secs`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Poll unfinished tasks every `interval`.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// `Err` with the reason if the sandbox denies access to `url`'s host.
+    fn check_host(&self, url: &Url) -> Result<(), String> {
+        let host = url.host_str().unwrap_or_default();
+        let access = self.sandbox.check_access(&AccessRequest {
+            kind: AccessKind::Network,
+            target: host.to_string(),
+        });
+        if access.allowed {
+            Ok(())
+        } else {
+            Err(format!(
+                "sandbox ({}) denied access to '{host}': {}",
+                access.profile, access.reason
+            ))
+        }
+    }
+
+    async fn ask(
+        &self,
+        client: &A2aClient,
+        message: &str,
+    ) -> Result<(RemoteAgentCard, A2aTask), String> {
+        let card = client.fetch_card().await.map_err(|e| e.to_string())?;
+        // The card may point requests at another host.
+        self.check_host(&card.endpoint)?;
+        let task = client
+            .send_and_wait(&card, message)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((card, task))
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            content: ToolContent::default(),
+            error: Some(error),
+            truncated: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for AskAgentTool {
+    fn name(&self) -> &str {
+        "ask_agent"
+    }
+
+    fn description(&self) -> &str {
+        "Delegate a task to a peer agent over A2A and return its result"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "Base URL of the agent, serving /.well-known/agent.json"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "What to ask the agent"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "How long to wait for the task to finish"
+                }
+            },
+            "required": ["url", "message"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let url = args
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: url"))?;
+        let message = args
+            .get("message")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: message"))?;
+        let timeout = args
+            .get("timeout_secs")
+            .and_then(Value::as_u64)
+            .map(Duration::from_secs)
+            .unwrap_or(self.timeout);
+
+        let client = A2aClient::new(url)?
+            .with_timeout(timeout)
+            .with_poll_interval(self.poll_interval);
+        if let Err(reason) = self.check_host(client.base_url()) {
+            return Ok(Self::failure(reason));
+        }
+        let (card, task) = match self.ask(&client, message).await {
+            Ok(done) => done,
+            Err(e) => return Ok(Self::failure(e)),
+        };
+
+        let success = task.status == TaskStatus::Completed;
+        Ok(ToolResult {
+            success,
+            content: ToolContent::Json(json!({
+                "agent": card.name,
+                "task_id": task.id,
+                "status": task.status,
+                "result": task.result,
+            })),
+            error: (!success).then(|| format!("task {} ended {:?}", task.id, task.status)),
+            truncated: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::{ProcessSandbox, SandboxProfile};
+    use axum::{response::IntoResponse, routing::get, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    /// An agent whose tasks stay running for `polls` GetTask calls, and
+    /// that streams updates when `streaming` is set.
+    async fn slow_agent(polls: usize, streaming: bool) -> String {
+        let remaining = Arc::new(Mutex::new(polls));
+        let task = |status: &str| {
+            json!({ "id": "t1", "status": status, "message": "hi", "result":
+                (status == "completed").then_some("done") })
+        };
+        let app = Router::new()
+            .route(
+                "/.well-known/agent.json",
+                get(move || async move {
+                    Json(json!({
+                        "name": "slow",
+                        "capabilities": { "streaming": streaming },
+                        "interfaces": [{ "protocol": "a2a", "url": "/rpc" }]
+                    }))
+                }),
+            )
+            .route(
+                "/rpc",
+                post(move |Json(req): Json<Value>| async move {
+                    let id = req["id"].clone();
+                    let ok = |task: Value| json!({ "jsonrpc": "2.0", "id": id, "result": { "task": task } });
+                    match req["method"].as_str().unwrap_or_default() {
+                        "SendMessage" => Json(ok(task("running"))).into_response(),
+                        "GetTask" => {
+                            let mut left = remaining.lock().unwrap();
+                            let status = if *left == 0 { "completed" } else { "running" };
+                            *left = left.saturating_sub(1);
+                            Json(ok(task(status))).into_response()
+                        }
+                        "SubscribeToTask" => {
+                            let body = format!(
+                                "event: message\ndata: {}\n\nevent: message\ndata: {}\n\n",
+                                ok(task("running")),
+                                ok(task("completed"))
+                            );
+                            ([("content-type", "text/event-stream")], body).into_response()
+                        }
+                        _ => Json(json!({
+                            "jsonrpc": "2.0", "id": id,
+                            "error": { "code": -32601, "message": "Unknown A2A method" }
+                        }))
+                        .into_response(),
+                    }
+                }),
+            )
+            .route("/bad/.well-known/agent.json", get(|| async { Json(json!(["not", "a", "card"])) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        base_url
+    }
+
+    fn fast(client: A2aClient) -> A2aClient {
+        client.with_poll_interval(Duration::from_millis(10))
+    }
+
+    #[test]
+    fn card_endpoint_resolves_against_card_url() {
+        let card_url = Url::parse("http://peer:3000/.well-known/agent.json").unwrap();
+        let card = RemoteAgentCard::parse(
+            &json!({
+                "name": "peer",
+                "skills": [{ "id": "guard" }],
+                "interfaces": [
+                    { "protocol": "jsonrpc", "url": "/mcp" },
+                    { "protocol": "a2a", "url": "/a2a" }
+                ]
+            }),
+            &card_url,
+        )
+        .unwrap();
+        assert_eq!(card.endpoint.as_str(), "http://peer:3000/a2a");
+        assert_eq!(card.skills, ["guard"]);
+        assert!(!card.streaming);
+
+        let top_level = RemoteAgentCard::parse(
+            &json!({ "name": "x", "url": "https://other/agent" }),
+            &card_url,
+        )
+        .unwrap();
+        assert_eq!(top_level.endpoint.as_str(), "https://other/agent");
+    }
+
+    #[test]
+    fn nonconforming_cards_are_rejected() {
+        let card_url = Url::parse("http://peer/.well-known/agent.json").unwrap();
+        for (card, reason) in [
+            (json!([]), "not a JSON object"),
+            (json!({ "url": "/a2a" }), "missing name"),
+            (json!({ "name": "peer" }), "no A2A endpoint"),
+        ] {
+            match RemoteAgentCard::parse(&card, &card_url) {
+                Err(A2aClientError::InvalidCard { reason: r, .. }) => assert_eq!(r, reason),
+                other => panic!("expected InvalidCard for {card}, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ask_polls_until_task_completes() {
+        let base = slow_agent(2, false).await;
+        let task = fast(A2aClient::new(&base).unwrap())
+            .ask("hi")
+            .await
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.result.as_deref(), Some("done"));
+    }
+
+    #[tokio::test]
+    async fn ask_follows_sse_when_streaming() {
+        // GetTask would keep the task running far past the timeout.
+        let base = slow_agent(usize::MAX, true).await;
+        let client = fast(A2aClient::new(&base).unwrap()).with_timeout(Duration::from_secs(5));
+        let task = client.ask("hi").await.unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn unfinished_task_times_out() {
+        let base = slow_agent(usize::MAX, false).await;
+        let client = fast(A2aClient::new(&base).unwrap()).with_timeout(Duration::from_millis(100));
+        match client.ask("hi").await {
+            Err(A2aClientError::Timeout { task_id, .. }) => assert_eq!(task_id, "t1"),
+            other => panic!("expected Timeout, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rpc_errors_and_bad_cards_are_typed() {
+        let base = slow_agent(0, false).await;
+        let client = A2aClient::new(&base).unwrap();
+        let card = client.fetch_card().await.unwrap();
+        match client.call(&card, "Nope", json!({})).await {
+            Err(A2aClientError::Rpc { code, message }) => {
+                assert_eq!(code, -32601);
+                assert!(message.contains("Unknown"));
+            }
+            other => panic!("expected Rpc, got {other:?}"),
+        }
+
+        let bad = A2aClient::new(&format!("{base}/bad")).unwrap();
+        assert!(matches!(
+            bad.fetch_card().await,
+            Err(A2aClientError::InvalidCard { .. })
+        ));
+        assert!(matches!(
+            A2aClient::new("ftp://peer"),
+            Err(A2aClientError::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn ask_agent_tool_reports_result() {
+        let base = slow_agent(0, false).await;
+        let tool = AskAgentTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)))
+            .with_poll_interval(Duration::from_millis(10));
+        let result = tool
+            .execute(json!({ "url": base, "message": "hi" }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let output: Value = serde_json::from_str(&result.output()).unwrap();
+        assert_eq!(output["agent"], "slow");
+        assert_eq!(output["status"], "completed");
+        assert_eq!(output["result"], "done");
+    }
+
+    #[tokio::test]
+    async fn ask_agent_tool_respects_domain_allowlist() {
+        let base = slow_agent(0, false).await;
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        sandbox.allow_domain("peer.example.com");
+        let result = AskAgentTool::new(Box::new(sandbox))
+            .execute(json!({ "url": base, "message": "hi" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("not in the domain allowlist"));
+    }
+}
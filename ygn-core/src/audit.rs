@@ -1,7 +1,11 @@
 //! Security audit trail.
 //!
 //! Records security-relevant events (tool-call attempts, access decisions,
-//! policy violations) for later inspection and compliance.
+//! policy violations) for later inspection and compliance.  Logs export as
+//! JSON Lines or as CEF (ArcSight Common Event Format) for SIEM ingestion,
+//! optionally narrowed by an [`AuditFilter`].
+
+use std::io::{self, BufRead, Write};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -22,10 +26,49 @@ pub enum AuditEventType {
     AccessGranted,
     /// Approval was required before execution.
     ApprovalRequired,
+    /// A call that required approval was approved.
+    ApprovalGranted,
     /// A policy violation was detected.
     PolicyViolation,
 }
 
+impl AuditEventType {
+    pub const ALL: [AuditEventType; 6] = [
+        AuditEventType::ToolCallAttempt,
+        AuditEventType::AccessDenied,
+        AuditEventType::AccessGranted,
+        AuditEventType::ApprovalRequired,
+        AuditEventType::ApprovalGranted,
+        AuditEventType::PolicyViolation,
+    ];
+
+    /// The variant name, as serialized.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEventType::ToolCallAttempt => "ToolCallAttempt",
+            AuditEventType::AccessDenied => "AccessDenied",
+            AuditEventType::AccessGranted => "AccessGranted",
+            AuditEventType::ApprovalRequired => "ApprovalRequired",
+            AuditEventType::ApprovalGranted => "ApprovalGranted",
+            AuditEventType::PolicyViolation => "PolicyViolation",
+        }
+    }
+}
+
+impl std::str::FromStr for AuditEventType {
+    type Err = anyhow::Error;
+
+    /// Parse a variant name, ignoring case and underscores, so
+    /// `access_denied` reads as `AccessDenied`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let wanted = s.replace('_', "").to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str().to_lowercase() == wanted)
+            .ok_or_else(|| anyhow::anyhow!("unknown audit event type: {s}"))
+    }
+}
+
 /// A single entry in the audit log.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
@@ -41,6 +84,14 @@ pub struct AuditEntry {
     pub risk_level: String,
     /// Arbitrary JSON details (arguments, reasons, etc.).
     pub details: Value,
+    /// Id of the policy decision behind this entry, shared by the attempt
+    /// and the entry recording its outcome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision_id: Option<String>,
+    /// Correlation id of the request (an HTTP `x-request-id`, or one
+    /// generated per stdio message) the event happened in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl AuditEntry {
@@ -59,8 +110,126 @@ impl AuditEntry {
             decision: decision.into(),
             risk_level: risk_level.into(),
             details,
+            decision_id: None,
+            request_id: None,
         }
     }
+
+    /// Tag this entry with the id of the policy decision it records.
+    pub fn with_decision_id(mut self, decision_id: impl Into<String>) -> Self {
+        self.decision_id = Some(decision_id.into());
+        self
+    }
+
+    /// Tag this entry with the correlation id of the request it belongs to.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// Format written by [`AuditLog::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditExportFormat {
+    /// One JSON object per line, as read back by [`AuditLog::read_jsonl`].
+    Jsonl,
+    /// One CEF line per entry, see [`AuditEntry::to_cef`].
+    Cef,
+}
+
+impl std::str::FromStr for AuditExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "json-lines" => Ok(Self::Jsonl),
+            "cef" => Ok(Self::Cef),
+            other => anyhow::bail!("unknown audit export format: {other} (expected jsonl or cef)"),
+        }
+    }
+}
+
+/// Selects the entries an export includes.  Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Earliest timestamp included.
+    pub since: Option<DateTime<Utc>>,
+    /// Timestamp entries must be before.
+    pub until: Option<DateTime<Utc>>,
+    /// Event types included; all when empty.
+    pub event_types: Vec<AuditEventType>,
+    /// Tool the entries must concern.
+    pub tool_name: Option<String>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+            && (self.event_types.is_empty() || self.event_types.contains(&entry.event_type))
+            && self
+                .tool_name
+                .as_ref()
+                .is_none_or(|tool| entry.tool_name == *tool)
+    }
+}
+
+/// CEF severity (0-10) for a risk level, 0 when unknown.
+fn cef_severity(risk_level: &str) -> u8 {
+    match risk_level.to_lowercase().as_str() {
+        "low" => 3,
+        "medium" => 5,
+        "high" => 8,
+        "critical" => 10,
+        _ => 0,
+    }
+}
+
+/// Escape a CEF header field: backslashes and pipes.
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escape a CEF extension value: backslashes, equals signs and line breaks.
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+impl AuditEntry {
+    /// Render this entry as one CEF line.  The event type is the signature
+    /// id, the decision the name, and the risk level the severity; the
+    /// tool, decision id and request id go in custom string extensions
+    /// `cs1` to `cs3`, and `details.reason`, when a string, in `reason`.
+    pub fn to_cef(&self) -> String {
+        let mut line = format!(
+            "CEF:0|Y-GN|ygn-core|{}|{}|{}|{}|rt={} act={} cs1Label=tool cs1={}",
+            cef_header(env!("CARGO_PKG_VERSION")),
+            cef_header(self.event_type.as_str()),
+            cef_header(&self.decision),
+            cef_severity(&self.risk_level),
+            self.timestamp.timestamp_millis(),
+            cef_value(&self.decision),
+            cef_value(&self.tool_name),
+        );
+        if let Some(id) = &self.decision_id {
+            line.push_str(&format!(" cs2Label=decisionId cs2={}", cef_value(id)));
+        }
+        if let Some(id) = &self.request_id {
+            line.push_str(&format!(" cs3Label=requestId cs3={}", cef_value(id)));
+        }
+        if let Some(reason) = self.details.get("reason").and_then(Value::as_str) {
+            line.push_str(&format!(" reason={}", cef_value(reason)));
+        }
+        line
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -89,6 +258,21 @@ impl AuditLog {
         &self.entries
     }
 
+    /// Iterate over entries in the order they were recorded.
+    pub fn iter(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter()
+    }
+
+    /// Iterate over the entries concerning `tool_name`.
+    pub fn filter_by_tool<'a>(
+        &'a self,
+        tool_name: &'a str,
+    ) -> impl Iterator<Item = &'a AuditEntry> + 'a {
+        self.entries
+            .iter()
+            .filter(move |e| e.tool_name == tool_name)
+    }
+
     /// Number of entries recorded so far.
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -107,6 +291,51 @@ impl AuditLog {
             .collect::<Vec<_>>()
             .join("\n")
     }
+
+    /// Write the log as JSON Lines: one JSON object per entry, each
+    /// terminated by a newline, so successive exports can be appended to
+    /// the same file.
+    pub fn export_jsonl<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.export(AuditExportFormat::Jsonl, &AuditFilter::default(), writer)
+            .map(drop)
+    }
+
+    /// Write the entries `filter` selects in `format`, one per line.
+    /// Returns the number of entries written.
+    pub fn export<W: Write>(
+        &self,
+        format: AuditExportFormat,
+        filter: &AuditFilter,
+        mut writer: W,
+    ) -> io::Result<usize> {
+        let mut written = 0;
+        for entry in self.entries.iter().filter(|e| filter.matches(e)) {
+            match format {
+                AuditExportFormat::Jsonl => serde_json::to_writer(&mut writer, entry)?,
+                AuditExportFormat::Cef => writer.write_all(entry.to_cef().as_bytes())?,
+            }
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+        writer.flush()?;
+        Ok(written)
+    }
+
+    /// Read a log written as JSON Lines, such as an audit file the MCP
+    /// server appends to.  Blank lines are skipped.
+    pub fn read_jsonl<R: BufRead>(reader: R) -> anyhow::Result<Self> {
+        let mut log = Self::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line)
+                .map_err(|e| anyhow::anyhow!("audit log line {}: {e}", n + 1))?;
+            log.record(entry);
+        }
+        Ok(log)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -202,4 +431,180 @@ mod tests {
         let log = AuditLog::new();
         assert_eq!(log.to_jsonl(), "");
     }
+
+    fn sample_log() -> AuditLog {
+        let mut log = AuditLog::new();
+        log.record(AuditEntry::now(
+            AuditEventType::ToolCallAttempt,
+            "echo",
+            "Allow",
+            "Low",
+            json!({}),
+        ));
+        log.record(AuditEntry::now(
+            AuditEventType::AccessDenied,
+            "nuke",
+            "Deny",
+            "Critical",
+            json!({"reason": "deny list"}),
+        ));
+        log.record(AuditEntry::now(
+            AuditEventType::AccessGranted,
+            "echo",
+            "Allow",
+            "Low",
+            json!({}),
+        ));
+        log
+    }
+
+    #[test]
+    fn export_jsonl_writes_one_object_per_line() {
+        let log = sample_log();
+        let mut buf = Vec::new();
+        log.export_jsonl(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.ends_with('\n'));
+
+        let entries: Vec<AuditEntry> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].tool_name, "nuke");
+        assert_eq!(entries[1].event_type, AuditEventType::AccessDenied);
+        assert_eq!(entries[1].details["reason"], "deny list");
+    }
+
+    #[test]
+    fn iter_and_filter_by_tool() {
+        let log = sample_log();
+        assert_eq!(log.iter().count(), 3);
+        let echo: Vec<&AuditEntry> = log.filter_by_tool("echo").collect();
+        assert_eq!(echo.len(), 2);
+        assert_eq!(echo[1].event_type, AuditEventType::AccessGranted);
+        assert_eq!(log.filter_by_tool("missing").count(), 0);
+    }
+
+    /// An entry at a fixed time, for exact CEF output.
+    fn entry_at(rfc3339: &str, event_type: AuditEventType, tool: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: DateTime::parse_from_rfc3339(rfc3339).unwrap().to_utc(),
+            ..AuditEntry::now(event_type, tool, "Allow", "Low", json!({}))
+        }
+    }
+
+    #[test]
+    fn filtered_jsonl_export_round_trips() {
+        let mut log = AuditLog::new();
+        log.record(entry_at(
+            "2026-03-01T09:00:00Z",
+            AuditEventType::AccessGranted,
+            "echo",
+        ));
+        log.record(
+            entry_at(
+                "2026-03-01T12:00:00Z",
+                AuditEventType::AccessDenied,
+                "shell",
+            )
+            .with_request_id("req-7"),
+        );
+        log.record(entry_at(
+            "2026-03-01T13:00:00Z",
+            AuditEventType::AccessDenied,
+            "echo",
+        ));
+        log.record(entry_at(
+            "2026-03-01T18:00:00Z",
+            AuditEventType::AccessDenied,
+            "shell",
+        ));
+
+        let filter = AuditFilter {
+            since: Some("2026-03-01T10:00:00Z".parse().unwrap()),
+            until: Some("2026-03-01T18:00:00Z".parse().unwrap()),
+            event_types: vec![AuditEventType::AccessDenied],
+            tool_name: Some("shell".into()),
+        };
+        let mut buf = Vec::new();
+        let written = log
+            .export(AuditExportFormat::Jsonl, &filter, &mut buf)
+            .unwrap();
+        assert_eq!(written, 1);
+
+        let read = AuditLog::read_jsonl(buf.as_slice()).unwrap();
+        assert_eq!(read.len(), 1);
+        let entry = &read.entries()[0];
+        assert_eq!(entry.timestamp, log.entries()[1].timestamp);
+        assert_eq!(entry.tool_name, "shell");
+        assert_eq!(entry.request_id.as_deref(), Some("req-7"));
+
+        // With no filter, everything round-trips.
+        let mut buf = Vec::new();
+        log.export(AuditExportFormat::Jsonl, &AuditFilter::default(), &mut buf)
+            .unwrap();
+        assert_eq!(AuditLog::read_jsonl(buf.as_slice()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn cef_lines_map_fields_and_escape() {
+        let granted = entry_at(
+            "2026-03-01T12:00:00Z",
+            AuditEventType::AccessGranted,
+            "echo",
+        );
+        assert_eq!(
+            granted.to_cef(),
+            concat!(
+                "CEF:0|Y-GN|ygn-core|",
+                env!("CARGO_PKG_VERSION"),
+                "|AccessGranted|Allow|3|rt=1772366400000 act=Allow cs1Label=tool cs1=echo"
+            )
+        );
+
+        let denied = AuditEntry {
+            decision: "Deny|hard".into(),
+            risk_level: "Critical".into(),
+            details: json!({ "reason": "rule a=b | c\\d\nnext" }),
+            ..entry_at(
+                "2026-03-01T12:00:00Z",
+                AuditEventType::AccessDenied,
+                "fs=rm",
+            )
+        }
+        .with_decision_id("d-1");
+        assert_eq!(
+            denied.to_cef(),
+            concat!(
+                "CEF:0|Y-GN|ygn-core|",
+                env!("CARGO_PKG_VERSION"),
+                r"|AccessDenied|Deny\|hard|10|rt=1772366400000 act=Deny|hard ",
+                r"cs1Label=tool cs1=fs\=rm cs2Label=decisionId cs2=d-1 ",
+                r"reason=rule a\=b | c\\d\nnext"
+            )
+        );
+    }
+
+    #[test]
+    fn export_formats_and_event_types_parse() {
+        assert_eq!(
+            "CEF".parse::<AuditExportFormat>().unwrap(),
+            AuditExportFormat::Cef
+        );
+        assert_eq!(
+            "jsonl".parse::<AuditExportFormat>().unwrap(),
+            AuditExportFormat::Jsonl
+        );
+        assert!("xml".parse::<AuditExportFormat>().is_err());
+        assert_eq!(
+            "access_denied".parse::<AuditEventType>().unwrap(),
+            AuditEventType::AccessDenied
+        );
+        assert_eq!(
+            "PolicyViolation".parse::<AuditEventType>().unwrap(),
+            AuditEventType::PolicyViolation
+        );
+        assert!("Exploded".parse::<AuditEventType>().is_err());
+    }
 }
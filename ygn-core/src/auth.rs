@@ -0,0 +1,233 @@
+//! API key authentication for the gateway.
+//!
+//! Keys are declared in [`NodeConfig`](crate::config::NodeConfig) under
+//! `auth.keys`.  Each key maps to a caller name, a [`TrustTier`] and an
+//! optional set of allowed routes.  Only SHA-256 digests of the keys are kept
+//! in memory once the [`ApiKeyAuth`] is built.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::registry::TrustTier;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// One API key accepted by the gateway.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ApiKeyConfig {
+    /// Caller name, reported in logs and passed to handlers.
+    pub name: String,
+    /// The bearer token clients send.
+    pub key: String,
+    /// Trust tier of the caller; untrusted callers get a stricter policy.
+    #[serde(default = "default_tier")]
+    pub trust_tier: TrustTier,
+    /// Paths this key may call.  Entries ending in `*` match by prefix.
+    /// Empty means every route.
+    #[serde(default)]
+    pub routes: Vec<String>,
+}
+
+fn default_tier() -> TrustTier {
+    TrustTier::Trusted
+}
+
+/// Gateway authentication settings (`auth` in `NodeConfig`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Accepted keys.  With none, the gateway is open.
+    pub keys: Vec<ApiKeyConfig>,
+    /// Paths reachable without a key.
+    pub exempt: Vec<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            exempt: vec!["/health".to_string()],
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ApiKeyAuth
+// ---------------------------------------------------------------------------
+
+/// The authenticated caller, inserted into request extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiIdentity {
+    pub name: String,
+    pub trust_tier: TrustTier,
+}
+
+/// Why a request was not authenticated.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    #[error("missing bearer token")]
+    Missing,
+    #[error("invalid API key")]
+    Unknown,
+    #[error("API key '{0}' may not access {1}")]
+    Forbidden(String, String),
+}
+
+#[derive(Debug)]
+struct KeyEntry {
+    identity: ApiIdentity,
+    routes: Vec<String>,
+}
+
+/// Validates bearer tokens against the configured keys.
+#[derive(Debug, Default)]
+pub struct ApiKeyAuth {
+    /// Entries keyed by the SHA-256 digest of the key.
+    keys: HashMap<[u8; 32], KeyEntry>,
+    exempt: Vec<String>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(config: AuthConfig) -> Self {
+        let keys = config
+            .keys
+            .into_iter()
+            .map(|k| {
+                let entry = KeyEntry {
+                    identity: ApiIdentity {
+                        name: k.name,
+                        trust_tier: k.trust_tier,
+                    },
+                    routes: k.routes,
+                };
+                (digest(&k.key), entry)
+            })
+            .collect();
+        Self {
+            keys,
+            exempt: config.exempt,
+        }
+    }
+
+    /// Whether any keys are configured.  When not, every request passes.
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Authenticate a request for `path` given its `Authorization` header.
+    ///
+    /// Returns `Ok(None)` when authentication is disabled or the path is
+    /// exempt, and the caller's identity otherwise.
+    pub fn authenticate(
+        &self,
+        path: &str,
+        authorization: Option<&str>,
+    ) -> Result<Option<ApiIdentity>, AuthError> {
+        if !self.is_enabled() || self.exempt.iter().any(|p| p == path) {
+            return Ok(None);
+        }
+        let token = authorization
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or(AuthError::Missing)?;
+        let entry = self.keys.get(&digest(token)).ok_or(AuthError::Unknown)?;
+        if !entry.routes.is_empty() && !entry.routes.iter().any(|r| route_matches(r, path)) {
+            return Err(AuthError::Forbidden(
+                entry.identity.name.clone(),
+                path.to_string(),
+            ));
+        }
+        Ok(Some(entry.identity.clone()))
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+fn route_matches(route: &str, path: &str) -> bool {
+    match route.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => route == path,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> ApiKeyAuth {
+        ApiKeyAuth::new(AuthConfig {
+            keys: vec![
+                ApiKeyConfig {
+                    name: "ops".into(),
+                    key: "secret-ops".into(),
+                    trust_tier: TrustTier::Trusted,
+                    routes: vec![],
+                },
+                ApiKeyConfig {
+                    name: "partner".into(),
+                    key: "secret-partner".into(),
+                    trust_tier: TrustTier::Untrusted,
+                    routes: vec!["/mcp".into(), "/registry/*".into()],
+                },
+            ],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn disabled_auth_lets_everything_through() {
+        let auth = ApiKeyAuth::new(AuthConfig::default());
+        assert!(!auth.is_enabled());
+        assert_eq!(auth.authenticate("/mcp", None), Ok(None));
+    }
+
+    #[test]
+    fn missing_and_unknown_keys_are_rejected() {
+        let auth = auth();
+        assert_eq!(auth.authenticate("/mcp", None), Err(AuthError::Missing));
+        assert_eq!(
+            auth.authenticate("/mcp", Some("Basic abc")),
+            Err(AuthError::Missing)
+        );
+        assert_eq!(
+            auth.authenticate("/mcp", Some("Bearer nope")),
+            Err(AuthError::Unknown)
+        );
+        // Exempt paths need no key.
+        assert_eq!(auth.authenticate("/health", None), Ok(None));
+    }
+
+    #[test]
+    fn valid_key_resolves_identity() {
+        let identity = auth()
+            .authenticate("/usage", Some("Bearer secret-ops"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.name, "ops");
+        assert_eq!(identity.trust_tier, TrustTier::Trusted);
+    }
+
+    #[test]
+    fn routes_restrict_key() {
+        let auth = auth();
+        let header = Some("Bearer secret-partner");
+        let identity = auth.authenticate("/mcp", header).unwrap().unwrap();
+        assert_eq!(identity.trust_tier, TrustTier::Untrusted);
+        assert!(auth.authenticate("/registry/nodes", header).is_ok());
+        assert_eq!(
+            auth.authenticate("/usage", header),
+            Err(AuthError::Forbidden("partner".into(), "/usage".into()))
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Backup and restore of the SQLite stores.
+//!
+//! [`create`] snapshots each store with SQLite's online backup API, which is
+//! safe while other connections (including WAL writers) keep using the
+//! database, and writes a [`BackupManifest`] next to the snapshots.
+//! [`restore`] checks the manifest and every snapshot before touching any
+//! live database, refuses to replace a database whose schema is newer than
+//! the snapshot unless forced, and moves each restored file into place with
+//! a rename.  Stop any node using the stores before restoring.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::schema;
+
+/// Name of the manifest inside a backup directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version of the manifest format written by this build.
+pub const MANIFEST_FORMAT: u32 = 1;
+
+/// A database that can be backed up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Store {
+    /// Short name used in the manifest and by `--only`.
+    pub name: String,
+    /// Location of the live database.
+    pub path: PathBuf,
+    /// Schema version this build creates for the store.
+    pub schema_version: u32,
+}
+
+impl Store {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>, schema_version: u32) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            schema_version,
+        }
+    }
+}
+
+/// The stores at their default locations under `~/.ygn`.
+pub fn default_stores() -> Vec<Store> {
+    vec![
+        Store::new(
+            "memory",
+            crate::sqlite_memory::default_db_path(),
+            crate::sqlite_memory::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "registry",
+            crate::sqlite_registry::default_db_path(),
+            crate::sqlite_registry::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "usage",
+            crate::usage::default_db_path(),
+            crate::usage::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "a2a_tasks",
+            crate::a2a::default_db_path(),
+            crate::a2a::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "tool_history",
+            crate::tool_history::default_db_path(),
+            crate::tool_history::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "provider_cache",
+            crate::provider_cache::default_db_path(),
+            crate::provider_cache::SCHEMA_VERSION,
+        ),
+        Store::new(
+            "schedules",
+            crate::scheduler::default_db_path(),
+            crate::scheduler::SCHEMA_VERSION,
+        ),
+    ]
+}
+
+/// Describes the snapshots in a backup directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Manifest format version, see [`MANIFEST_FORMAT`].
+    pub format: u32,
+    pub created_at: DateTime<Utc>,
+    /// Version of the ygn-core build that made the backup.
+    pub ygn_version: String,
+    pub stores: Vec<StoreSnapshot>,
+}
+
+/// One snapshot listed in a [`BackupManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    pub name: String,
+    /// File name of the snapshot, relative to the backup directory.
+    pub file: String,
+    /// Schema version recorded in the snapshot, if it had one.
+    pub schema_version: Option<u32>,
+    pub size_bytes: u64,
+}
+
+/// Snapshot every existing store into `out`, then write the manifest.
+///
+/// Stores whose database does not exist yet are skipped.
+pub fn create(stores: &[Store], out: &Path) -> anyhow::Result<BackupManifest> {
+    std::fs::create_dir_all(out)?;
+    let created_at = Utc::now();
+    let stamp = created_at.format("%Y%m%dT%H%M%SZ");
+
+    let mut snapshots = Vec::new();
+    for store in stores {
+        if !store.path.exists() {
+            tracing::debug!(store = %store.name, path = %store.path.display(), "no database; skipping");
+            continue;
+        }
+        let file = format!("{}-{stamp}.db", store.name);
+        let dest = out.join(&file);
+        if dest.exists() {
+            anyhow::bail!("{} already exists", dest.display());
+        }
+        let source = Connection::open(&store.path)?;
+        source.busy_timeout(Duration::from_secs(5))?;
+        copy_database(&source, &dest)
+            .map_err(|e| anyhow::anyhow!("backing up '{}': {e}", store.name))?;
+        let schema_version = schema::version(&Connection::open(&dest)?)?;
+        snapshots.push(StoreSnapshot {
+            name: store.name.clone(),
+            file,
+            schema_version,
+            size_bytes: std::fs::metadata(&dest)?.len(),
+        });
+    }
+    if snapshots.is_empty() {
+        anyhow::bail!("no databases found to back up");
+    }
+
+    let manifest = BackupManifest {
+        format: MANIFEST_FORMAT,
+        created_at,
+        ygn_version: env!("CARGO_PKG_VERSION").to_string(),
+        stores: snapshots,
+    };
+    std::fs::write(
+        out.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
+
+/// Read and check the manifest of the backup in `dir`.
+pub fn read_manifest(dir: &Path) -> anyhow::Result<BackupManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", path.display()))?;
+    let manifest: BackupManifest = serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("invalid manifest {}: {e}", path.display()))?;
+    if manifest.format != MANIFEST_FORMAT {
+        anyhow::bail!(
+            "unsupported manifest format {} (expected {MANIFEST_FORMAT})",
+            manifest.format
+        );
+    }
+    for (i, snapshot) in manifest.stores.iter().enumerate() {
+        if manifest.stores[..i].iter().any(|s| s.name == snapshot.name) {
+            anyhow::bail!("manifest lists store '{}' twice", snapshot.name);
+        }
+        if Path::new(&snapshot.file).file_name() != Some(snapshot.file.as_ref()) {
+            anyhow::bail!("manifest file name '{}' is not a plain name", snapshot.file);
+        }
+        if !dir.join(&snapshot.file).is_file() {
+            anyhow::bail!(
+                "snapshot '{}' listed in the manifest is missing",
+                snapshot.file
+            );
+        }
+    }
+    Ok(manifest)
+}
+
+/// Restore the stores in the backup at `from`, or only the one named
+/// `only`.  Returns the names of the restored stores.
+///
+/// Without `force`, a store is not restored over a database with a newer
+/// schema version, nor from a snapshot newer than this build knows.
+pub fn restore(
+    from: &Path,
+    stores: &[Store],
+    only: Option<&str>,
+    force: bool,
+) -> anyhow::Result<Vec<String>> {
+    let manifest = read_manifest(from)?;
+    let selected: Vec<&StoreSnapshot> = manifest
+        .stores
+        .iter()
+        .filter(|s| only.is_none_or(|name| s.name == name))
+        .collect();
+    if let (Some(name), true) = (only, selected.is_empty()) {
+        anyhow::bail!("backup has no '{name}' store");
+    }
+
+    // Check everything before replacing anything.
+    let mut plan = Vec::new();
+    for snapshot in selected {
+        let store = stores
+            .iter()
+            .find(|s| s.name == snapshot.name)
+            .ok_or_else(|| anyhow::anyhow!("backup contains unknown store '{}'", snapshot.name))?;
+        let source = from.join(&snapshot.file);
+        check_snapshot(&source, snapshot)?;
+
+        let backup_version = snapshot.schema_version.unwrap_or(0);
+        if !force {
+            if backup_version > store.schema_version {
+                anyhow::bail!(
+                    "snapshot of '{}' has schema version {backup_version}, newer than this \
+                     build's {}; use --force to restore anyway",
+                    store.name,
+                    store.schema_version
+                );
+            }
+            if store.path.exists() {
+                let live = schema::version(&Connection::open(&store.path)?)?.unwrap_or(0);
+                if live > backup_version {
+                    anyhow::bail!(
+                        "'{}' has schema version {live}, newer than the backup's \
+                         {backup_version}; use --force to restore anyway",
+                        store.name
+                    );
+                }
+            }
+        }
+        plan.push((store, source));
+    }
+
+    let mut restored = Vec::new();
+    for (store, source) in plan {
+        replace_database(&source, &store.path)
+            .map_err(|e| anyhow::anyhow!("restoring '{}': {e}", store.name))?;
+        restored.push(store.name.clone());
+    }
+    Ok(restored)
+}
+
+/// Copy the database behind `source` into a new file at `dest`.
+fn copy_database(source: &Connection, dest: &Path) -> anyhow::Result<()> {
+    let mut target = Connection::open(dest)?;
+    Backup::new(source, &mut target)?.run_to_completion(100, Duration::from_millis(10), None)?;
+    Ok(())
+}
+
+/// Make sure a snapshot is an intact database matching its manifest entry.
+fn check_snapshot(path: &Path, snapshot: &StoreSnapshot) -> anyhow::Result<()> {
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        anyhow::bail!("snapshot '{}' is corrupt: {integrity}", snapshot.file);
+    }
+    let version = schema::version(&conn)?;
+    if version != snapshot.schema_version {
+        anyhow::bail!(
+            "snapshot '{}' has schema version {version:?}, but the manifest says {:?}",
+            snapshot.file,
+            snapshot.schema_version
+        );
+    }
+    Ok(())
+}
+
+/// Copy `source` next to `target`, then rename it over `target`.
+fn replace_database(source: &Path, target: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let staged = sibling(target, ".restore");
+    remove_if_exists(&staged)?;
+    copy_database(&Connection::open(source)?, &staged)?;
+
+    // Fold any WAL into the old database so its leftover -wal and -shm
+    // files can be removed; they must not be replayed onto the new file.
+    if target.exists() {
+        let live = Connection::open(target)?;
+        live.busy_timeout(Duration::from_secs(5))?;
+        live.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        remove_if_exists(&sibling(target, suffix))?;
+        remove_if_exists(&sibling(&staged, suffix))?;
+    }
+    std::fs::rename(&staged, target)?;
+    Ok(())
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Memory, MemoryCategory};
+    use crate::sqlite_memory::SqliteMemory;
+
+    struct Fixture {
+        dir: PathBuf,
+        stores: Vec<Store>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("ygn-backup-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let stores = vec![
+                Store::new(
+                    "memory",
+                    dir.join("memory.db"),
+                    crate::sqlite_memory::SCHEMA_VERSION,
+                ),
+                Store::new(
+                    "registry",
+                    dir.join("registry.db"),
+                    crate::sqlite_registry::SCHEMA_VERSION,
+                ),
+            ];
+            Self { dir, stores }
+        }
+
+        fn memory_path(&self) -> &str {
+            self.stores[0].path.to_str().unwrap()
+        }
+
+        fn out(&self) -> PathBuf {
+            self.dir.join("backup")
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    async fn content(mem: &SqliteMemory, key: &str) -> Option<String> {
+        mem.get(MemoryCategory::Core, key)
+            .await
+            .unwrap()
+            .map(|e| e.content)
+    }
+
+    #[tokio::test]
+    async fn restore_brings_back_pre_mutation_content() {
+        let fx = Fixture::new();
+        let mem = SqliteMemory::new(fx.memory_path()).unwrap();
+        mem.store(MemoryCategory::Core, "fact", "original")
+            .await
+            .unwrap();
+
+        // Back up while the store is open and in WAL mode.
+        let manifest = create(&fx.stores, &fx.out()).unwrap();
+        assert_eq!(manifest.stores.len(), 1, "missing registry.db is skipped");
+        assert_eq!(manifest.stores[0].name, "memory");
+        assert_eq!(
+            manifest.stores[0].schema_version,
+            Some(crate::sqlite_memory::SCHEMA_VERSION)
+        );
+
+        mem.store(MemoryCategory::Core, "fact", "changed")
+            .await
+            .unwrap();
+        mem.store(MemoryCategory::Core, "extra", "added later")
+            .await
+            .unwrap();
+        drop(mem);
+
+        let restored = restore(&fx.out(), &fx.stores, None, false).unwrap();
+        assert_eq!(restored, vec!["memory"]);
+
+        let mem = SqliteMemory::new(fx.memory_path()).unwrap();
+        assert_eq!(content(&mem, "fact").await.as_deref(), Some("original"));
+        assert_eq!(content(&mem, "extra").await, None);
+        assert_eq!(mem.recall("original", None, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn newer_live_schema_needs_force() {
+        let fx = Fixture::new();
+        let mem = SqliteMemory::new(fx.memory_path()).unwrap();
+        mem.store(MemoryCategory::Core, "fact", "original")
+            .await
+            .unwrap();
+        drop(mem);
+        create(&fx.stores, &fx.out()).unwrap();
+
+        Connection::open(fx.memory_path())
+            .unwrap()
+            .execute(
+                "INSERT INTO schema_migrations (version, description, applied_at) VALUES (99, 'future', '')",
+                [],
+            )
+            .unwrap();
+        let err = restore(&fx.out(), &fx.stores, None, false).unwrap_err();
+        assert!(err.to_string().contains("newer than the backup"), "{err}");
+
+        restore(&fx.out(), &fx.stores, None, true).unwrap();
+        let conn = Connection::open(fx.memory_path()).unwrap();
+        assert_eq!(
+            schema::version(&conn).unwrap(),
+            Some(crate::sqlite_memory::SCHEMA_VERSION)
+        );
+    }
+
+    #[tokio::test]
+    async fn only_restores_the_named_store() {
+        let fx = Fixture::new();
+        SqliteMemory::new(fx.memory_path()).unwrap();
+        crate::sqlite_registry::SqliteRegistry::new(fx.stores[1].path.to_str().unwrap()).unwrap();
+        let manifest = create(&fx.stores, &fx.out()).unwrap();
+        assert_eq!(manifest.stores.len(), 2);
+
+        let restored = restore(&fx.out(), &fx.stores, Some("registry"), false).unwrap();
+        assert_eq!(restored, vec!["registry"]);
+        let err = restore(&fx.out(), &fx.stores, Some("usage"), false).unwrap_err();
+        assert!(err.to_string().contains("no 'usage' store"));
+    }
+
+    #[test]
+    fn manifest_with_missing_snapshot_is_rejected() {
+        let fx = Fixture::new();
+        Connection::open(&fx.stores[0].path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x INTEGER);")
+            .unwrap();
+        let manifest = create(&fx.stores, &fx.out()).unwrap();
+        assert_eq!(manifest.stores[0].schema_version, None);
+        std::fs::remove_file(fx.out().join(&manifest.stores[0].file)).unwrap();
+
+        let err = restore(&fx.out(), &fx.stores, None, false).unwrap_err();
+        assert!(err.to_string().contains("is missing"), "{err}");
+    }
+}
@@ -22,11 +22,31 @@ pub struct ChannelMessage {
 }
 
 /// An outbound message to be sent through a channel.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Channels that cannot thread replies or carry files ignore `reply_to`
+/// and `attachments`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SendMessage {
     pub content: String,
     pub recipient: Option<String>,
     pub metadata: serde_json::Value,
+    /// Channel-specific id of the inbound message this one answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+    /// Files sent along with the message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to a [`SendMessage`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name shown to recipients.
+    pub filename: String,
+    /// MIME type, e.g. "image/png".
+    pub mime_type: String,
+    /// File bytes, base64-encoded.
+    pub data: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -133,8 +153,22 @@ mod tests {
             content: "response".to_string(),
             recipient: Some("bob".to_string()),
             metadata: serde_json::json!({}),
+            ..Default::default()
         };
         assert_eq!(msg.content, "response");
         assert_eq!(msg.recipient.as_deref(), Some("bob"));
     }
+
+    #[test]
+    fn send_message_optional_fields_default() {
+        let msg: SendMessage =
+            serde_json::from_str(r#"{"content": "hi", "recipient": null, "metadata": {}}"#)
+                .unwrap();
+        assert!(msg.reply_to.is_none());
+        assert!(msg.attachments.is_empty());
+
+        let json = serde_json::to_value(&msg).unwrap();
+        assert!(json.get("reply_to").is_none());
+        assert!(json.get("attachments").is_none());
+    }
 }
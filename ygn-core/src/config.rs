@@ -1,10 +1,155 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::auth::AuthConfig;
+use crate::hardware::HardwareConfig;
+use crate::mcp_client::McpServerConfig;
+use crate::multi_provider::FallbackPolicy;
+use crate::observation::ObservationConfig;
+use crate::policy::PolicyConfig;
+use crate::provider_cache::CacheConfig;
+use crate::provider_health::HealthThresholds;
+use crate::rate_limiter::GatewayLimitConfig;
+use crate::registry::RegistryConfig;
+use crate::tool_limits::ToolLimitsConfig;
+use crate::tool_output::ToolOutputConfig;
+use crate::usage::UsageConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+#[schemars(title = "YGN Node Configuration")]
 pub struct NodeConfig {
+    #[schemars(extend("enum" = ["edge", "core", "brain", "brain-proxy"]))]
     pub node_role: String,
+    #[schemars(extend("enum" = ["trusted", "untrusted"]))]
     pub trust_tier: String,
     pub gateway_bind: String,
+    /// Token pricing and daily budget for usage tracking.
+    #[serde(default)]
+    pub usage: UsageConfig,
+    /// External MCP servers whose tools are imported at startup, by name.
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+    /// Friendly names for MCP-served tools, mapped to the qualified (or
+    /// unambiguous short) name they stand for.
+    #[serde(default)]
+    pub tool_aliases: BTreeMap<String, String>,
+    /// Hosts the `ask_agent` tool may delegate tasks to over A2A.  The tool
+    /// is only available to CLI commands when this is non-empty.
+    #[serde(default)]
+    pub a2a_peers: Vec<String>,
+    /// Whether MCP serves the simulated `hardware` tool (on by default).
+    #[serde(default)]
+    pub hardware: HardwareConfig,
+    /// Per-tool concurrency caps and how long calls queue for a slot.
+    #[serde(default)]
+    pub tool_limits: ToolLimitsConfig,
+    /// Largest output tools may return, with per-tool overrides and where
+    /// the full output of truncated calls is kept.
+    #[serde(default)]
+    pub tool_output: ToolOutputConfig,
+    /// File the gateway appends MCP audit entries to, as JSON Lines, after
+    /// each `POST /mcp` request and `/mcp/ws` connection.  Not written when
+    /// unset.
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+    /// Remote registry membership and heartbeat settings.
+    #[serde(default)]
+    pub registry: RegistryConfig,
+    /// Telemetry gossip with other nodes over uACP.
+    #[serde(default)]
+    pub observation: ObservationConfig,
+    /// Response cache for deterministic provider calls. Disabled when unset.
+    #[serde(default)]
+    pub provider_cache: Option<CacheConfig>,
+    /// Per-client request limits on gateway routes.
+    #[serde(default)]
+    pub rate_limit: GatewayLimitConfig,
+    /// API keys accepted by the gateway. Open when no keys are listed.
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Seconds the gateway waits for in-flight requests after a shutdown
+    /// signal before abandoning them.
+    #[serde(default = "default_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+    /// Provider routing.
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+    /// Tool-call policy for the MCP server. The built-in defaults apply
+    /// when unset.
+    #[serde(default)]
+    pub policy: Option<PolicyConfig>,
+    /// Policy file (JSON or TOML) to use instead of an inline `policy`
+    /// section.  Relative paths are resolved against the config file's
+    /// directory.
+    #[serde(default)]
+    pub policy_file: Option<PathBuf>,
+}
+
+/// Provider settings in the node config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    /// Model name to provider name, overriding the prefix rules.
+    pub models: BTreeMap<String, String>,
+    /// Seconds between gateway health probes of every provider; 0 disables
+    /// probing.
+    pub probe_interval_secs: u64,
+    /// Consecutive probe or call outcomes that flip a provider between
+    /// healthy and unhealthy.
+    pub health: HealthThresholds,
+    /// Models to fall back to when a provider call fails with a retryable
+    /// status.  No fallback when unset.
+    pub fallback: Option<FallbackPolicy>,
+}
+
+impl Default for ProvidersConfig {
+    fn default() -> Self {
+        Self {
+            models: BTreeMap::new(),
+            probe_interval_secs: 60,
+            health: HealthThresholds::default(),
+            fallback: None,
+        }
+    }
+}
+
+/// Accepted values of [`NodeConfig::node_role`].
+pub const NODE_ROLES: &[&str] = &["edge", "core", "brain", "brain-proxy"];
+
+/// Accepted values of [`NodeConfig::trust_tier`].
+pub const TRUST_TIERS: &[&str] = &["trusted", "untrusted"];
+
+/// Config file chosen with `--config`, if any.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `path` instead of [`default_config_path`] for the rest of the
+/// process.  Only the first call has an effect.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// The configuration file in effect: the one set with
+/// [`set_config_path`], or [`default_config_path`].
+pub fn config_path() -> PathBuf {
+    CONFIG_PATH
+        .get()
+        .cloned()
+        .unwrap_or_else(default_config_path)
+}
+
+/// Where this node's stable id is kept: `node_id` next to the
+/// configuration file in effect.
+pub fn node_id_path() -> PathBuf {
+    config_path().with_file_name("node_id")
+}
+
+fn default_drain_timeout_secs() -> u64 {
+    30
 }
 
 impl Default for NodeConfig {
@@ -13,39 +158,258 @@ impl Default for NodeConfig {
             node_role: "edge".to_string(),
             trust_tier: "trusted".to_string(),
             gateway_bind: "0.0.0.0:3000".to_string(),
+            usage: UsageConfig::default(),
+            mcp_servers: BTreeMap::new(),
+            tool_aliases: BTreeMap::new(),
+            a2a_peers: Vec::new(),
+            hardware: HardwareConfig::default(),
+            tool_limits: ToolLimitsConfig::default(),
+            tool_output: ToolOutputConfig::default(),
+            audit_log: None,
+            registry: RegistryConfig::default(),
+            observation: ObservationConfig::default(),
+            provider_cache: None,
+            rate_limit: GatewayLimitConfig::default(),
+            auth: AuthConfig::default(),
+            drain_timeout_secs: default_drain_timeout_secs(),
+            providers: ProvidersConfig::default(),
+            policy: None,
+            policy_file: None,
         }
     }
 }
 
 impl NodeConfig {
+    /// Load [`config_path`] if it exists, else the defaults; environment
+    /// overrides apply either way.  A file that fails to load is logged
+    /// and ignored.
     pub fn load_or_default() -> Self {
-        // TODO: load from file/env
-        Self::default()
+        let path = config_path();
+        if path.exists() {
+            match Self::load_from_file(&path) {
+                Ok(cfg) => return cfg,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "ignoring config file")
+                }
+            }
+        }
+        let mut cfg = Self::default();
+        cfg.apply_env_overrides(|key| std::env::var(key).ok());
+        if let Err(e) = cfg.check() {
+            tracing::warn!(error = %e, "ignoring environment overrides");
+            return Self::default();
+        }
+        cfg
+    }
+
+    /// Load a configuration file (TOML, JSON or YAML, by extension), then
+    /// apply the `YGN_NODE_ROLE`, `YGN_TRUST_TIER` and `YGN_GATEWAY_BIND`
+    /// environment overrides.  Fails with [`InvalidConfig`] if the result
+    /// does not pass [`check`](Self::check).
+    pub fn load_from_file(path: &Path) -> anyhow::Result<Self> {
+        let value = read_value(path)?;
+        let mut cfg: Self = serde_json::from_value(value)
+            .with_context(|| format!("invalid config file {}", path.display()))?;
+        if let (Some(policy_file), Some(dir)) = (&mut cfg.policy_file, path.parent()) {
+            if policy_file.is_relative() {
+                *policy_file = dir.join(&*policy_file);
+            }
+        }
+        cfg.apply_env_overrides(|key| std::env::var(key).ok());
+        cfg.check()
+            .with_context(|| format!("invalid config file {}", path.display()))?;
+        Ok(cfg)
+    }
+
+    /// The tool policy from `policy` or `policy_file`, else the defaults.
+    pub fn policy_config(&self) -> anyhow::Result<PolicyConfig> {
+        match (&self.policy, &self.policy_file) {
+            (Some(policy), _) => Ok(policy.clone()),
+            (None, Some(path)) => PolicyConfig::load(path),
+            (None, None) => Ok(PolicyConfig::default()),
+        }
+    }
+
+    /// Overwrite fields from environment variables, read through `lookup`.
+    pub fn apply_env_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        for (var, field) in [
+            ("YGN_NODE_ROLE", &mut self.node_role),
+            ("YGN_TRUST_TIER", &mut self.trust_tier),
+            ("YGN_GATEWAY_BIND", &mut self.gateway_bind),
+        ] {
+            if let Some(value) = lookup(var) {
+                *field = value;
+            }
+        }
+    }
+
+    /// Check what the schema cannot: see [`semantic_errors`](Self::semantic_errors).
+    pub fn check(&self) -> Result<(), InvalidConfig> {
+        let errors = self.semantic_errors();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidConfig(errors))
+        }
     }
 
+    /// Problems with an otherwise well-formed configuration: an unknown
+    /// role or trust tier, a bind address that is not `host:port`, or a
+    /// policy file that does not exist.
+    pub fn semantic_errors(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        errors.extend(check_one_of("node_role", &self.node_role, NODE_ROLES));
+        errors.extend(check_one_of("trust_tier", &self.trust_tier, TRUST_TIERS));
+        if !is_bind_address(&self.gateway_bind) {
+            errors.push(ConfigError {
+                pointer: "/gateway_bind".into(),
+                message: format!(
+                    "invalid gateway_bind '{}': expected host:port",
+                    self.gateway_bind
+                ),
+            });
+        }
+        if let Some(path) = &self.policy_file {
+            if self.policy.is_some() {
+                errors.push(ConfigError {
+                    pointer: "/policy_file".into(),
+                    message: "policy and policy_file cannot both be set".into(),
+                });
+            }
+            if !path.is_file() {
+                errors.push(ConfigError {
+                    pointer: "/policy_file".into(),
+                    message: format!("policy file {} does not exist", path.display()),
+                });
+            }
+        }
+        errors
+    }
+
+    /// JSON schema for the configuration file, generated from the structs.
+    ///
+    /// Object keys are emitted in a stable order so the output can be
+    /// committed and diffed.
     pub fn json_schema() -> String {
-        serde_json::to_string_pretty(&serde_json::json!({
-            "$schema": "http://json-schema.org/draft-07/schema#",
-            "title": "YGN Node Configuration",
-            "type": "object",
-            "properties": {
-                "node_role": {
-                    "type": "string",
-                    "enum": ["edge", "core", "brain-proxy"],
-                    "default": "edge"
-                },
-                "trust_tier": {
-                    "type": "string",
-                    "enum": ["trusted", "untrusted"],
-                    "default": "trusted"
-                },
-                "gateway_bind": {
-                    "type": "string",
-                    "default": "0.0.0.0:3000"
-                }
+        serde_json::to_string_pretty(&Self::json_schema_value()).unwrap()
+    }
+
+    fn json_schema_value() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(NodeConfig)).unwrap()
+    }
+
+    /// Validate a parsed configuration document.
+    ///
+    /// Schema violations are reported first, each pointing at the offending
+    /// field.  A document that passes the schema but still fails to
+    /// deserialize yields a single error at the root.
+    pub fn validate_value(value: &serde_json::Value) -> Vec<ConfigError> {
+        let schema = Self::json_schema_value();
+        let validator = jsonschema::validator_for(&schema).expect("generated schema compiles");
+        let mut errors: Vec<ConfigError> = validator
+            .iter_errors(value)
+            .map(|e| ConfigError {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect();
+        if errors.is_empty() {
+            if let Err(e) = serde_json::from_value::<NodeConfig>(value.clone()) {
+                errors.push(ConfigError {
+                    pointer: String::new(),
+                    message: e.to_string(),
+                });
             }
-        }))
-        .unwrap()
+        }
+        errors.sort();
+        errors.dedup();
+        errors
+    }
+
+    /// Read and validate a configuration file.  The format follows the
+    /// extension: `.toml`, `.json`, or YAML otherwise.
+    ///
+    /// A file that passes the schema is then loaded with
+    /// [`load_from_file`](Self::load_from_file), so environment overrides
+    /// apply, and checked for [semantic errors](Self::semantic_errors).
+    pub fn validate_file(path: &Path) -> anyhow::Result<Vec<ConfigError>> {
+        let errors = Self::validate_value(&read_value(path)?);
+        if !errors.is_empty() {
+            return Ok(errors);
+        }
+        match Self::load_from_file(path) {
+            Ok(_) => Ok(Vec::new()),
+            Err(e) => match e.downcast_ref::<InvalidConfig>() {
+                Some(invalid) => Ok(invalid.0.clone()),
+                None => Err(e),
+            },
+        }
+    }
+}
+
+/// Parse a configuration file into a JSON value, by extension.
+fn read_value(path: &Path) -> anyhow::Result<serde_json::Value> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => serde_json::to_value(toml::from_str::<toml::Value>(&text)?)?,
+        Some("json") => serde_json::from_str(&text)?,
+        _ => serde_yaml::from_str(&text)?,
+    };
+    Ok(value)
+}
+
+fn check_one_of(field: &str, value: &str, allowed: &[&str]) -> Option<ConfigError> {
+    (!allowed.contains(&value)).then(|| ConfigError {
+        pointer: format!("/{field}"),
+        message: format!(
+            "invalid {field} '{value}': expected one of {}",
+            allowed.join(", ")
+        ),
+    })
+}
+
+/// Whether `bind` has the `host:port` form a listener accepts.
+fn is_bind_address(bind: &str) -> bool {
+    if bind.parse::<std::net::SocketAddr>().is_ok() {
+        return true;
+    }
+    match bind.rsplit_once(':') {
+        Some((host, port)) => {
+            !host.is_empty() && !host.contains(['[', ']', ' ']) && port.parse::<u16>().is_ok()
+        }
+        None => false,
+    }
+}
+
+/// Default configuration file: `~/.ygn/config.toml`.
+pub fn default_config_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".ygn").join("config.toml")
+}
+
+/// Semantic errors in a configuration, see [`NodeConfig::semantic_errors`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{}", .0.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; "))]
+pub struct InvalidConfig(pub Vec<ConfigError>);
+
+/// A validation failure located by JSON pointer (empty for the root).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConfigError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{pointer}: {}", self.message)
     }
 }
 
@@ -65,5 +429,153 @@ mod tests {
         let schema = NodeConfig::json_schema();
         let parsed: serde_json::Value = serde_json::from_str(&schema).unwrap();
         assert_eq!(parsed["title"], "YGN Node Configuration");
+        let props = &parsed["properties"];
+        assert_eq!(props["node_role"]["default"], "edge");
+        assert_eq!(
+            props["trust_tier"]["enum"],
+            serde_json::json!(["trusted", "untrusted"])
+        );
+        assert_eq!(props["drain_timeout_secs"]["default"], 30);
+        assert!(parsed["$defs"]["RouteLimit"]["properties"]["burst"].is_object());
+    }
+
+    #[test]
+    fn default_config_passes_validation() {
+        let value = serde_json::to_value(NodeConfig::default()).unwrap();
+        assert!(NodeConfig::validate_value(&value).is_empty());
+    }
+
+    #[test]
+    fn validation_errors_point_at_fields() {
+        let value = serde_json::json!({
+            "node_role": "satellite",
+            "mcp_servers": { "files": { "args": [] } },
+            "rate_limit": { "default": { "requests_per_minute": -1, "burst": 2 } }
+        });
+        let pointers: Vec<String> = NodeConfig::validate_value(&value)
+            .into_iter()
+            .map(|e| e.pointer)
+            .collect();
+        assert!(pointers.contains(&"/node_role".to_string()));
+        assert!(pointers.contains(&"/mcp_servers/files".to_string()));
+        assert!(pointers
+            .iter()
+            .any(|p| p.starts_with("/rate_limit/default")));
+    }
+
+    fn write_temp(name: &str, text: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ygn-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn loads_toml_with_sub_sections() {
+        let path = write_temp(
+            "config.toml",
+            r#"
+node_role = "brain-proxy"
+trust_tier = "untrusted"
+gateway_bind = "127.0.0.1:4000"
+
+[providers.models]
+"llama3" = "ollama"
+
+[policy]
+denied_tools = ["shell"]
+"#,
+        );
+        let cfg = NodeConfig::load_from_file(&path).unwrap();
+        assert_eq!(cfg.node_role, "brain-proxy");
+        assert_eq!(cfg.trust_tier, "untrusted");
+        assert_eq!(cfg.gateway_bind, "127.0.0.1:4000");
+        assert_eq!(cfg.providers.models["llama3"], "ollama");
+        assert_eq!(cfg.policy.unwrap().denied_tools, vec!["shell"]);
+        assert_eq!(cfg.drain_timeout_secs, 30);
+    }
+
+    #[test]
+    fn env_overrides_file_settings() {
+        let mut cfg = NodeConfig {
+            node_role: "core".into(),
+            gateway_bind: "127.0.0.1:4000".into(),
+            ..NodeConfig::default()
+        };
+        cfg.apply_env_overrides(|key| match key {
+            "YGN_NODE_ROLE" => Some("brain".into()),
+            "YGN_GATEWAY_BIND" => Some("0.0.0.0:9000".into()),
+            _ => None,
+        });
+        assert_eq!(cfg.node_role, "brain");
+        assert_eq!(cfg.trust_tier, "trusted");
+        assert_eq!(cfg.gateway_bind, "0.0.0.0:9000");
+    }
+
+    #[test]
+    fn unknown_node_role_is_rejected() {
+        let path = write_temp("config.toml", "node_role = \"satellite\"\n");
+        let err = NodeConfig::load_from_file(&path).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "invalid node_role 'satellite': expected one of edge, core, brain, brain-proxy"
+        );
+    }
+
+    #[test]
+    fn semantic_errors_cover_bind_and_policy_file() {
+        let mut cfg = NodeConfig::default();
+        assert!(cfg.semantic_errors().is_empty());
+        for bind in ["127.0.0.1:3000", "[::1]:3000", "localhost:8080"] {
+            cfg.gateway_bind = bind.into();
+            assert!(cfg.semantic_errors().is_empty(), "{bind}");
+        }
+
+        cfg.gateway_bind = "localhost".into();
+        cfg.trust_tier = "sometimes".into();
+        cfg.policy = Some(PolicyConfig::default());
+        cfg.policy_file = Some(PathBuf::from("/nonexistent/ygn-policy.toml"));
+        let pointers: Vec<String> = cfg
+            .semantic_errors()
+            .into_iter()
+            .map(|e| e.pointer)
+            .collect();
+        assert_eq!(
+            pointers,
+            [
+                "/trust_tier",
+                "/gateway_bind",
+                "/policy_file",
+                "/policy_file"
+            ]
+        );
+    }
+
+    #[test]
+    fn policy_file_is_resolved_against_the_config_file() {
+        let path = write_temp("config.toml", "policy_file = \"policy.toml\"\n");
+        let err = NodeConfig::load_from_file(&path).unwrap_err();
+        let invalid = err.downcast_ref::<InvalidConfig>().unwrap();
+        let policy = path.parent().unwrap().join("policy.toml");
+        assert_eq!(
+            invalid.0[0].message,
+            format!("policy file {} does not exist", policy.display())
+        );
+
+        std::fs::write(&policy, "denied_tools = [\"shell\"]\n").unwrap();
+        let cfg = NodeConfig::load_from_file(&path).unwrap();
+        assert_eq!(cfg.policy_file, Some(policy));
+        assert!(NodeConfig::validate_file(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn schema_covers_providers_and_policy() {
+        let schema: serde_json::Value = serde_json::from_str(&NodeConfig::json_schema()).unwrap();
+        let props = &schema["properties"];
+        assert!(props["providers"].is_object());
+        assert!(props["policy"].is_object());
+        assert_eq!(props["node_role"]["enum"], serde_json::json!(NODE_ROLES));
+        assert!(schema["$defs"]["PolicyConfig"]["properties"]["denied_tools"].is_object());
     }
 }
@@ -31,6 +31,86 @@ pub enum ErrorCategory {
     Unknown,
 }
 
+/// Package ecosystem a missing dependency belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackageEcosystem {
+    /// Rust crate (installed with `cargo add`).
+    Cargo,
+    /// Python package (installed with `pip install`).
+    Pip,
+}
+
+/// Python import names whose pip distribution has a different name.
+const PIP_ALIASES: &[(&str, &str)] = &[
+    ("yaml", "PyYAML"),
+    ("cv2", "opencv-python"),
+    ("PIL", "Pillow"),
+    ("sklearn", "scikit-learn"),
+    ("bs4", "beautifulsoup4"),
+    ("dateutil", "python-dateutil"),
+    ("dotenv", "python-dotenv"),
+    ("jwt", "PyJWT"),
+    ("serial", "pyserial"),
+    ("Crypto", "pycryptodome"),
+];
+
+/// Python import names known to match their pip distribution name.
+const PIP_SAME_NAME: &[&str] = &[
+    "requests",
+    "numpy",
+    "pandas",
+    "scipy",
+    "pytest",
+    "httpx",
+    "flask",
+    "django",
+    "fastapi",
+    "pydantic",
+    "click",
+    "rich",
+    "jinja2",
+    "aiohttp",
+    "attrs",
+    "toml",
+    "tomli",
+    "ruff",
+    "mypy",
+    "matplotlib",
+    "sqlalchemy",
+    "uvicorn",
+    "websockets",
+    "psutil",
+    "redis",
+    "boto3",
+];
+
+impl PackageEcosystem {
+    /// The package to install for a missing `name`, when it is known.
+    ///
+    /// Crate names are installed as-is. Python import names only resolve
+    /// when they are known to equal their distribution name or have an
+    /// entry in the alias map; `pip install <module>` would otherwise risk
+    /// installing an unrelated package.
+    pub fn resolve_package(&self, name: &str) -> Option<String> {
+        match self {
+            PackageEcosystem::Cargo => Some(name.to_string()),
+            PackageEcosystem::Pip => PIP_ALIASES
+                .iter()
+                .find(|(module, _)| *module == name)
+                .map(|(_, dist)| dist.to_string())
+                .or_else(|| PIP_SAME_NAME.contains(&name).then(|| name.to_string())),
+        }
+    }
+
+    /// Shell command that installs `name` in this ecosystem.
+    pub fn install_command(&self, name: &str) -> String {
+        match self {
+            PackageEcosystem::Cargo => format!("cargo add {name}"),
+            PackageEcosystem::Pip => format!("pip install {name}"),
+        }
+    }
+}
+
 /// A single diagnostic produced by analyzing gate output.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Diagnostic {
@@ -48,6 +128,23 @@ pub struct Diagnostic {
     pub suggested_fix: Option<String>,
     /// Whether this error can be automatically fixed.
     pub auto_fixable: bool,
+    /// Parsed Python traceback, when the output contained one.
+    #[serde(default)]
+    pub traceback: Option<PythonTraceback>,
+}
+
+/// The essentials of a Python traceback: the raised exception and the
+/// innermost frame it was raised from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PythonTraceback {
+    /// Exception class, e.g. "KeyError" or "requests.HTTPError".
+    pub exception_type: String,
+    /// Exception message (may be empty).
+    pub exception_message: String,
+    /// File of the innermost frame.
+    pub file: Option<String>,
+    /// Line number of the innermost frame.
+    pub line: Option<u32>,
 }
 
 /// Result from running a single quality gate.
@@ -76,6 +173,37 @@ pub struct HealAction {
     pub description: String,
 }
 
+/// Outcome of executing a single [`HealAction`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealOutcome {
+    /// The action that was executed.
+    pub action: HealAction,
+    /// Whether the heal command exited successfully.
+    pub success: bool,
+    /// Combined stdout+stderr of the heal command.
+    pub output: String,
+}
+
+/// Structured report produced by [`GateRunner::heal_and_retry`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealReport {
+    /// Gate results from the initial run.
+    pub before: Vec<GateResult>,
+    /// Final gate results after healing (same order as `before`).
+    pub after: Vec<GateResult>,
+    /// Heal actions that were executed, in order.
+    pub actions: Vec<HealOutcome>,
+    /// Gates that failed initially and pass after healing.
+    pub fixed: Vec<String>,
+    /// Gates that still fail after healing.
+    pub still_failing: Vec<String>,
+    /// Number of heal iterations performed.
+    pub iterations: usize,
+}
+
+/// Maximum number of heal → re-run cycles in [`GateRunner::heal_and_retry`].
+pub const MAX_HEAL_ITERATIONS: usize = 3;
+
 // ---------------------------------------------------------------------------
 // DiagnosticEngine
 // ---------------------------------------------------------------------------
@@ -95,14 +223,26 @@ impl DiagnosticEngine {
     /// Analyze raw gate output, classify the error, and produce a diagnostic.
     pub fn analyze(&self, source: &str, raw_output: &str) -> Diagnostic {
         let category = Self::classify(raw_output);
+        self.diagnose(source, raw_output, category)
+    }
+
+    /// A [`ErrorCategory::ConfigurationError`] diagnostic for a problem
+    /// already known to be in the configuration, e.g. one found by
+    /// `ygn-core config validate`.
+    pub fn configuration_error(&self, source: &str, message: &str) -> Diagnostic {
+        self.diagnose(source, message, ErrorCategory::ConfigurationError)
+    }
+
+    fn diagnose(&self, source: &str, raw_output: &str, category: ErrorCategory) -> Diagnostic {
         let mut diag = Diagnostic {
             id: uuid::Uuid::new_v4().to_string(),
             timestamp: Utc::now(),
-            category: category.clone(),
+            category,
             source: source.to_string(),
             message: raw_output.to_string(),
             suggested_fix: None,
             auto_fixable: false,
+            traceback: Self::parse_python_traceback(raw_output),
         };
         diag.suggested_fix = self.suggest_fix(&diag);
         diag.auto_fixable = Self::is_auto_fixable(&diag);
@@ -124,11 +264,20 @@ impl DiagnosticEngine {
             return ErrorCategory::DependencyMissing;
         }
 
-        // Test failures: "test result: FAILED" or "FAILED" in test output
-        if raw_output.contains("test result: FAILED") || raw_output.contains("FAILED") {
+        // Test failures: cargo's "test result: FAILED", a "N failed" count
+        // (cargo/pytest summaries), or pytest's "FAILED path::test" lines.
+        // A bare "FAILED" is too common in unrelated logs to count.
+        let test_failure_re =
+            Regex::new(r"(?m)test result: FAILED|\b\d+ failed\b|^FAILED \S+::\S+").unwrap();
+        if test_failure_re.is_match(raw_output) {
             return ErrorCategory::TestFailure;
         }
 
+        // Uncaught Python exceptions are the runtime crash of Python code.
+        if raw_output.contains("Traceback (most recent call last):") {
+            return ErrorCategory::RuntimePanic;
+        }
+
         // Lint violations: "Diff in" (cargo fmt) or "warning:" (clippy/ruff)
         if raw_output.contains("Diff in") || raw_output.contains("warning:") {
             return ErrorCategory::LintViolation;
@@ -155,11 +304,99 @@ impl DiagnosticEngine {
         ErrorCategory::Unknown
     }
 
+    /// Extract the name of a missing crate or Python package from raw
+    /// output such as "could not find `tokio` in the registry" or
+    /// "ModuleNotFoundError: No module named 'requests'".
+    pub fn extract_dependency_name(raw_output: &str) -> Option<(PackageEcosystem, String)> {
+        let cargo_re = Regex::new(
+            r"(?:could not find `([A-Za-z0-9_-]+)` in (?:the )?registry|no matching package named `([A-Za-z0-9_-]+)`)",
+        )
+        .unwrap();
+        if let Some(caps) = cargo_re.captures(raw_output) {
+            let name = caps.get(1).or_else(|| caps.get(2))?.as_str();
+            return Some((PackageEcosystem::Cargo, name.to_string()));
+        }
+
+        // Only the top-level module maps to an installable package.
+        let pip_re = Regex::new(r"No module named '([A-Za-z0-9_.-]+)'").unwrap();
+        if let Some(caps) = pip_re.captures(raw_output) {
+            let module = caps[1].split('.').next().unwrap_or(&caps[1]);
+            return Some((PackageEcosystem::Pip, module.to_string()));
+        }
+
+        None
+    }
+
+    /// Parse the last Python traceback in `raw_output`, extracting the
+    /// exception line and the innermost `File "...", line N` frame.
+    pub fn parse_python_traceback(raw_output: &str) -> Option<PythonTraceback> {
+        let start = raw_output.rfind("Traceback (most recent call last):")?;
+        let tail = &raw_output[start..];
+
+        let frame_re = Regex::new(r#"^\s*File "([^"]+)", line (\d+)"#).unwrap();
+        let exc_re = Regex::new(r"^([A-Za-z_][\w.]*(?:Error|Exception|Exit|Interrupt|Warning|Iteration|Group)\w*)(?::\s?(.*))?$").unwrap();
+
+        let mut file = None;
+        let mut line = None;
+        let mut exception = None;
+        for text in tail.lines() {
+            if let Some(caps) = frame_re.captures(text) {
+                file = Some(caps[1].to_string());
+                line = caps[2].parse().ok();
+            } else if let Some(caps) = exc_re.captures(text.trim_end()) {
+                exception = Some((
+                    caps[1].to_string(),
+                    caps.get(2).map_or("", |m| m.as_str()).to_string(),
+                ));
+                break;
+            }
+        }
+
+        let (exception_type, exception_message) = exception?;
+        Some(PythonTraceback {
+            exception_type,
+            exception_message,
+            file,
+            line,
+        })
+    }
+
+    /// Fix hint for a Python exception type.
+    fn python_exception_hint(exception_type: &str) -> &'static str {
+        // Match on the bare class name so "json.JSONDecodeError" works too.
+        let name = exception_type.rsplit('.').next().unwrap_or(exception_type);
+        match name {
+            "KeyError" => "check that the key exists in the dict (use `in` or `.get()`)",
+            "IndexError" => "check the sequence length before indexing",
+            "AttributeError" => "check the object's type; it may be None or lack that attribute",
+            "TypeError" => "check argument types and the number of arguments passed",
+            "ValueError" | "JSONDecodeError" => "validate the input value before converting it",
+            "NameError" => "check for a typo or a missing import/definition",
+            "ZeroDivisionError" => "guard against a zero divisor",
+            "FileNotFoundError" => "check the path exists relative to the working directory",
+            "ImportError" | "ModuleNotFoundError" => "install the package or fix the import path",
+            "AssertionError" => "review the failing assertion and the values it compares",
+            "RecursionError" => "check the recursion's base case",
+            _ => "examine the traceback and fix the root cause",
+        }
+    }
+
     /// Produce a heuristic fix suggestion for the given diagnostic.
     pub fn suggest_fix(&self, diagnostic: &Diagnostic) -> Option<String> {
         match diagnostic.category {
             ErrorCategory::DependencyMissing => {
-                Some("Run `cargo add <crate>` or `pip install <package>`".to_string())
+                match Self::extract_dependency_name(&diagnostic.message) {
+                    Some((ecosystem, name)) => match ecosystem.resolve_package(&name) {
+                        Some(package) => {
+                            Some(format!("Run `{}`", ecosystem.install_command(&package)))
+                        }
+                        None => Some(format!(
+                            "Install the package that provides `{name}` \
+                             (its pip distribution name may differ)"
+                        )),
+                    },
+                    None => Some("Run `cargo add <crate>` or `pip install <package>`".to_string()),
+                }
             }
             ErrorCategory::LintViolation => {
                 if diagnostic.source.contains("fmt") {
@@ -181,9 +418,20 @@ impl DiagnosticEngine {
             ErrorCategory::CompilationError => {
                 Some("Check the compiler error message for type/syntax fix".to_string())
             }
-            ErrorCategory::RuntimePanic => {
-                Some("Examine the panic backtrace and fix the root cause".to_string())
-            }
+            ErrorCategory::RuntimePanic => match &diagnostic.traceback {
+                Some(tb) => {
+                    let location = match (&tb.file, tb.line) {
+                        (Some(file), Some(line)) => format!(" at {file}:{line}"),
+                        _ => String::new(),
+                    };
+                    Some(format!(
+                        "{}{location}: {}",
+                        tb.exception_type,
+                        Self::python_exception_hint(&tb.exception_type)
+                    ))
+                }
+                None => Some("Examine the panic backtrace and fix the root cause".to_string()),
+            },
             ErrorCategory::ConfigurationError => {
                 Some("Review and correct the configuration file".to_string())
             }
@@ -191,13 +439,26 @@ impl DiagnosticEngine {
         }
     }
 
+    /// The ecosystem and package to install for a missing dependency named
+    /// in `raw_output`, if it can be resolved unambiguously.
+    pub fn installable_dependency(raw_output: &str) -> Option<(PackageEcosystem, String)> {
+        let (ecosystem, name) = Self::extract_dependency_name(raw_output)?;
+        let package = ecosystem.resolve_package(&name)?;
+        Some((ecosystem, package))
+    }
+
     /// Returns `true` when the error can be auto-fixed (lint violations from
-    /// fmt/ruff are auto-fixable).
+    /// fmt/ruff, and missing dependencies whose package is known).
     pub fn is_auto_fixable(diagnostic: &Diagnostic) -> bool {
-        if diagnostic.category != ErrorCategory::LintViolation {
-            return false;
+        match diagnostic.category {
+            ErrorCategory::LintViolation => {
+                diagnostic.source.contains("fmt") || diagnostic.source.contains("ruff")
+            }
+            ErrorCategory::DependencyMissing => {
+                Self::installable_dependency(&diagnostic.message).is_some()
+            }
+            _ => false,
         }
-        diagnostic.source.contains("fmt") || diagnostic.source.contains("ruff")
     }
 }
 
@@ -211,20 +472,67 @@ impl Default for DiagnosticEngine {
 // GateRunner
 // ---------------------------------------------------------------------------
 
+/// The built-in Rust quality gates as `(name, command)` pairs.
+const DEFAULT_GATES: [(&str, &str); 3] = [
+    ("cargo fmt --check", "cargo fmt --check"),
+    ("cargo clippy", "cargo clippy -- -D warnings"),
+    ("cargo test", "cargo test"),
+];
+
 /// Runs quality-gate commands and collects diagnostics on failure.
 #[derive(Debug, Clone)]
 pub struct GateRunner {
     engine: DiagnosticEngine,
+    /// `(name, command)` pairs run by [`GateRunner::run_all_gates`].
+    gates: Vec<(String, String)>,
+    /// Heal commands keyed by gate name, overriding the built-in mapping.
+    heal_commands: std::collections::HashMap<String, String>,
 }
 
 impl GateRunner {
-    /// Create a new gate runner.
+    /// Create a gate runner with the built-in cargo gates.
     pub fn new() -> Self {
+        Self::with_gates(
+            DEFAULT_GATES
+                .iter()
+                .map(|(name, cmd)| (name.to_string(), cmd.to_string()))
+                .collect(),
+        )
+    }
+
+    /// Create a gate runner with a custom `(name, command)` pipeline, e.g.
+    /// `("ruff check", "ruff check .")` or `("pytest", "pytest -q")`.
+    pub fn with_gates(gates: Vec<(String, String)>) -> Self {
         Self {
             engine: DiagnosticEngine::new(),
+            gates,
+            heal_commands: std::collections::HashMap::new(),
         }
     }
 
+    /// Use `command` to heal auto-fixable failures of the gate named `gate`
+    /// instead of the built-in mapping (`cargo fmt`, `ruff check --fix`).
+    pub fn with_heal_command(
+        mut self,
+        gate: impl Into<String>,
+        command: impl Into<String>,
+    ) -> Self {
+        self.heal_commands.insert(gate.into(), command.into());
+        self
+    }
+
+    /// The configured `(name, command)` pairs.
+    pub fn gates(&self) -> &[(String, String)] {
+        &self.gates
+    }
+
+    fn gate_refs(&self) -> Vec<(&str, &str)> {
+        self.gates
+            .iter()
+            .map(|(name, cmd)| (name.as_str(), cmd.as_str()))
+            .collect()
+    }
+
     /// Run a single quality gate by executing a shell command.
     ///
     /// Captures stdout+stderr, measures wall-clock time, and produces a
@@ -273,32 +581,160 @@ impl GateRunner {
         }
     }
 
-    /// Run the full quality-gate sequence.
+    /// Run the configured quality-gate sequence.
     ///
-    /// Gates:
+    /// Default gates:
     /// 1. `cargo fmt --check`
     /// 2. `cargo clippy -- -D warnings`
     /// 3. `cargo test`
     pub fn run_all_gates(&self) -> Vec<GateResult> {
-        let gates = [
-            ("cargo fmt --check", "cargo fmt --check"),
-            ("cargo clippy", "cargo clippy -- -D warnings"),
-            ("cargo test", "cargo test"),
-        ];
+        self.run_gates(&self.gate_refs())
+    }
 
+    /// Run caller-supplied `(name, command)` gates in order.
+    pub fn run_gates(&self, gates: &[(&str, &str)]) -> Vec<GateResult> {
         gates
             .iter()
             .map(|(name, cmd)| self.run_gate(name, cmd))
             .collect()
     }
 
+    /// Run the configured gates concurrently, one thread per gate.
+    ///
+    /// Results are returned in the same order as [`GateRunner::run_all_gates`];
+    /// each `duration_ms` is the wall-clock time of that gate alone. Use the
+    /// sequential method when gates must not overlap.
+    pub fn run_all_gates_parallel(&self) -> Vec<GateResult> {
+        self.run_gates_parallel(&self.gate_refs())
+    }
+
+    /// Run an explicit set of `(name, command)` gates concurrently.
+    pub fn run_gates_parallel(&self, gates: &[(&str, &str)]) -> Vec<GateResult> {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = gates
+                .iter()
+                .map(|(name, cmd)| scope.spawn(move || self.run_gate(name, cmd)))
+                .collect();
+            handles
+                .into_iter()
+                .zip(gates)
+                .map(|(handle, (name, _))| {
+                    handle.join().unwrap_or_else(|_| {
+                        let msg = "Gate thread panicked".to_string();
+                        GateResult {
+                            gate_name: name.to_string(),
+                            success: false,
+                            output: msg.clone(),
+                            duration_ms: 0,
+                            diagnostic: Some(self.engine.analyze(name, &msg)),
+                        }
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Run all gates, execute heal actions for auto-fixable failures, and
+    /// re-run the failing gates, up to [`MAX_HEAL_ITERATIONS`] times.
+    pub fn heal_and_retry(&self) -> HealReport {
+        self.heal_and_retry_gates(&self.gate_refs(), MAX_HEAL_ITERATIONS)
+    }
+
+    /// Self-healing loop over an explicit set of `(name, command)` gates.
+    ///
+    /// Only auto-fixable diagnostics produce actions; non-auto-fixable
+    /// failures are reported but never acted upon. The loop stops when all
+    /// gates pass, when no action is applicable, or after `max_iterations`.
+    pub fn heal_and_retry_gates(
+        &self,
+        gates: &[(&str, &str)],
+        max_iterations: usize,
+    ) -> HealReport {
+        let before: Vec<GateResult> = gates
+            .iter()
+            .map(|(name, cmd)| self.run_gate(name, cmd))
+            .collect();
+        let mut current = before.clone();
+        let mut actions = Vec::new();
+        let mut iterations = 0;
+
+        while iterations < max_iterations {
+            let diagnostics: Vec<Diagnostic> = current
+                .iter()
+                .filter(|r| !r.success)
+                .filter_map(|r| r.diagnostic.clone())
+                .collect();
+
+            let mut pending = self.auto_heal(&diagnostics);
+            // Several gates can map to the same fix; run each command once.
+            let mut seen = std::collections::HashSet::new();
+            pending.retain(|a| seen.insert(a.command.clone()));
+            if pending.is_empty() {
+                break;
+            }
+
+            iterations += 1;
+            for action in pending {
+                let outcome = self.run_gate(&action.description, &action.command);
+                actions.push(HealOutcome {
+                    action,
+                    success: outcome.success,
+                    output: outcome.output,
+                });
+            }
+
+            for (i, (name, cmd)) in gates.iter().enumerate() {
+                if !current[i].success {
+                    current[i] = self.run_gate(name, cmd);
+                }
+            }
+
+            if current.iter().all(|r| r.success) {
+                break;
+            }
+        }
+
+        let fixed = before
+            .iter()
+            .zip(&current)
+            .filter(|(b, a)| !b.success && a.success)
+            .map(|(b, _)| b.gate_name.clone())
+            .collect();
+        let still_failing = current
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| r.gate_name.clone())
+            .collect();
+
+        HealReport {
+            before,
+            after: current,
+            actions,
+            fixed,
+            still_failing,
+            iterations,
+        }
+    }
+
     /// For auto-fixable diagnostics, return the commands needed to heal them.
     pub fn auto_heal(&self, diagnostics: &[Diagnostic]) -> Vec<HealAction> {
         diagnostics
             .iter()
             .filter(|d| DiagnosticEngine::is_auto_fixable(d))
             .map(|d| {
-                let (command, description) = if d.source.contains("fmt") {
+                let dependency = if d.category == ErrorCategory::DependencyMissing {
+                    DiagnosticEngine::installable_dependency(&d.message)
+                } else {
+                    None
+                };
+                let (command, description) = if let Some(cmd) = self.heal_commands.get(&d.source) {
+                    (cmd.clone(), format!("Heal gate `{}`", d.source))
+                } else if let Some((ecosystem, name)) = dependency {
+                    (
+                        ecosystem.install_command(&name),
+                        format!("Add missing dependency `{name}`"),
+                    )
+                } else if d.source.contains("fmt") {
                     (
                         "cargo fmt".to_string(),
                         "Auto-format Rust source code".to_string(),
@@ -376,6 +812,52 @@ mod tests {
         assert_eq!(diag.category, ErrorCategory::TestFailure);
     }
 
+    #[test]
+    fn classify_test_failure_from_count() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("pytest", "===== 2 failed, 10 passed in 0.52s =====");
+        assert_eq!(diag.category, ErrorCategory::TestFailure);
+    }
+
+    #[test]
+    fn classify_test_failure_pytest_line() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze(
+            "pytest",
+            "FAILED tests/test_guard.py::test_blocks_injection - AssertionError",
+        );
+        assert_eq!(diag.category, ErrorCategory::TestFailure);
+    }
+
+    #[test]
+    fn classify_bare_failed_is_not_test_failure() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("net-probe", "connection FAILED: host unreachable");
+        assert_eq!(diag.category, ErrorCategory::Unknown);
+    }
+
+    #[test]
+    fn classify_failed_mention_with_panic_is_runtime_panic() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze(
+            "cargo run",
+            "connecting to upstream... connection FAILED\n\
+             thread 'main' panicked at src/main.rs:12:5:\n\
+             called `Result::unwrap()` on an `Err` value",
+        );
+        assert_eq!(diag.category, ErrorCategory::RuntimePanic);
+    }
+
+    #[test]
+    fn classify_compilation_error_mentioning_failed() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze(
+            "cargo test",
+            "error[E0425]: cannot find value `x` in this scope\nerror: build FAILED",
+        );
+        assert_eq!(diag.category, ErrorCategory::CompilationError);
+    }
+
     #[test]
     fn classify_lint_violation() {
         let engine = DiagnosticEngine::new();
@@ -416,6 +898,19 @@ mod tests {
         assert_eq!(diag.category, ErrorCategory::ConfigurationError);
     }
 
+    #[test]
+    fn configuration_error_is_not_reclassified() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.configuration_error("node.toml", "/gateway_bind: expected host:port");
+        assert_eq!(diag.category, ErrorCategory::ConfigurationError);
+        assert_eq!(diag.source, "node.toml");
+        assert_eq!(
+            diag.suggested_fix.as_deref(),
+            Some("Review and correct the configuration file")
+        );
+        assert!(!diag.auto_fixable);
+    }
+
     #[test]
     fn classify_unknown_error() {
         let engine = DiagnosticEngine::new();
@@ -449,10 +944,118 @@ mod tests {
     fn suggest_fix_for_dependency() {
         let engine = DiagnosticEngine::new();
         let diag = engine.analyze("cargo build", "could not find `foo` in registry");
+        assert_eq!(diag.suggested_fix.as_deref(), Some("Run `cargo add foo`"));
+    }
+
+    #[test]
+    fn suggest_fix_for_unparseable_dependency_is_generic() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("cargo build", "error: no matching package found");
+        assert_eq!(diag.category, ErrorCategory::DependencyMissing);
         assert_eq!(
             diag.suggested_fix.as_deref(),
             Some("Run `cargo add <crate>` or `pip install <package>`")
         );
+        assert!(!diag.auto_fixable);
+    }
+
+    #[test]
+    fn extract_dependency_name_rust_forms() {
+        assert_eq!(
+            DiagnosticEngine::extract_dependency_name(
+                "error: could not find `tokio` in the registry"
+            ),
+            Some((PackageEcosystem::Cargo, "tokio".to_string()))
+        );
+        assert_eq!(
+            DiagnosticEngine::extract_dependency_name(
+                "error: no matching package named `serde-json5` found"
+            ),
+            Some((PackageEcosystem::Cargo, "serde-json5".to_string()))
+        );
+    }
+
+    #[test]
+    fn extract_dependency_name_python_forms() {
+        assert_eq!(
+            DiagnosticEngine::extract_dependency_name(
+                "ModuleNotFoundError: No module named 'requests'"
+            ),
+            Some((PackageEcosystem::Pip, "requests".to_string()))
+        );
+        // Submodule imports map to the top-level package.
+        assert_eq!(
+            DiagnosticEngine::extract_dependency_name(
+                "ModuleNotFoundError: No module named 'google.protobuf'"
+            ),
+            Some((PackageEcosystem::Pip, "google".to_string()))
+        );
+        assert!(DiagnosticEngine::extract_dependency_name("all good").is_none());
+    }
+
+    #[test]
+    fn suggest_fix_for_python_dependency() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("pytest", "ModuleNotFoundError: No module named 'requests'");
+        assert_eq!(
+            diag.suggested_fix.as_deref(),
+            Some("Run `pip install requests`")
+        );
+        assert!(diag.auto_fixable);
+    }
+
+    #[test]
+    fn python_import_aliases_resolve_to_distribution() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("pytest", "ModuleNotFoundError: No module named 'yaml'");
+        assert_eq!(
+            diag.suggested_fix.as_deref(),
+            Some("Run `pip install PyYAML`")
+        );
+        assert!(diag.auto_fixable);
+        let actions = GateRunner::new().auto_heal(&[diag]);
+        assert_eq!(actions[0].command, "pip install PyYAML");
+
+        for (module, dist) in [
+            ("cv2", "opencv-python"),
+            ("PIL", "Pillow"),
+            ("sklearn", "scikit-learn"),
+        ] {
+            assert_eq!(
+                PackageEcosystem::Pip.resolve_package(module).as_deref(),
+                Some(dist)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_python_module_is_not_auto_fixable() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze(
+            "pytest",
+            "ModuleNotFoundError: No module named 'google.protobuf'",
+        );
+        assert!(!diag.auto_fixable);
+        let fix = diag.suggested_fix.clone().unwrap();
+        assert!(fix.contains("`google`"), "{fix}");
+        assert!(!fix.contains("pip install google"), "{fix}");
+        assert!(GateRunner::new().auto_heal(&[diag]).is_empty());
+    }
+
+    #[test]
+    fn heal_action_for_missing_dependency() {
+        let runner = GateRunner::new();
+        let engine = DiagnosticEngine::new();
+        let rust = engine.analyze(
+            "cargo build",
+            "error: could not find `tokio` in the registry",
+        );
+        let python = engine.analyze("pytest", "ModuleNotFoundError: No module named 'requests'");
+        let actions = runner.auto_heal(&[rust, python]);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].command, "cargo add tokio");
+        assert_eq!(actions[1].command, "pip install requests");
+        assert!(actions[1].description.contains("requests"));
     }
 
     #[test]
@@ -578,6 +1181,80 @@ mod tests {
         assert!(actions.is_empty());
     }
 
+    // -- Heal-and-retry tests -----------------------------------------------
+
+    #[test]
+    fn heal_and_retry_all_passing_does_nothing() {
+        let runner = GateRunner::new();
+        let report = runner.heal_and_retry_gates(&[("ok", "true")], MAX_HEAL_ITERATIONS);
+        assert_eq!(report.iterations, 0);
+        assert!(report.actions.is_empty());
+        assert!(report.fixed.is_empty());
+        assert!(report.still_failing.is_empty());
+        assert!(report.after[0].success);
+    }
+
+    #[test]
+    fn heal_and_retry_never_runs_non_auto_fixable() {
+        let runner = GateRunner::new();
+        let report = runner.heal_and_retry_gates(
+            &[(
+                "cargo test",
+                "echo 'test result: FAILED. 0 passed; 1 failed'; exit 1",
+            )],
+            MAX_HEAL_ITERATIONS,
+        );
+        assert_eq!(report.iterations, 0);
+        assert!(report.actions.is_empty());
+        assert_eq!(report.still_failing, vec!["cargo test"]);
+    }
+
+    #[test]
+    fn heal_command_override_replaces_builtin_mapping() {
+        let runner = GateRunner::new().with_heal_command("ruff check", "true");
+        let engine = DiagnosticEngine::new();
+        let ruff = engine.analyze("ruff check", "warning: unused import");
+        let fmt = engine.analyze("cargo fmt --check", "Diff in src/lib.rs");
+        let actions = runner.auto_heal(&[ruff, fmt]);
+        assert_eq!(actions[0].command, "true");
+        assert_eq!(actions[1].command, "cargo fmt");
+    }
+
+    #[test]
+    fn heal_and_retry_reports_fixed_gate() {
+        let marker = std::env::temp_dir().join(format!("ygn-heal-{}", uuid::Uuid::new_v4()));
+        let marker = marker.display();
+        // The gate only passes once the heal command has created the marker.
+        let gate = format!("test -f {marker} || {{ echo 'warning: unused import'; exit 1; }}");
+        let runner = GateRunner::new().with_heal_command("ruff check", format!("touch {marker}"));
+        let report = runner.heal_and_retry_gates(&[("ruff check", &gate)], MAX_HEAL_ITERATIONS);
+        let healed = std::fs::remove_file(marker.to_string()).is_ok();
+
+        assert!(healed, "heal command did not run");
+        assert!(!report.before[0].success);
+        assert!(report.after[0].success);
+        assert_eq!(report.iterations, 1);
+        assert_eq!(report.actions.len(), 1);
+        assert_eq!(report.actions[0].action.command, format!("touch {marker}"));
+        assert!(report.actions[0].success);
+        assert_eq!(report.fixed, vec!["ruff check"]);
+        assert!(report.still_failing.is_empty());
+    }
+
+    #[test]
+    fn heal_and_retry_caps_iterations() {
+        let runner = GateRunner::new().with_heal_command("ruff check", "true");
+        let report = runner.heal_and_retry_gates(
+            &[("ruff check", "echo 'warning: unused import'; exit 1")],
+            2,
+        );
+        assert_eq!(report.iterations, 2);
+        assert_eq!(report.actions.len(), 2);
+        assert!(report.actions.iter().all(|a| a.action.command == "true"));
+        assert_eq!(report.still_failing, vec!["ruff check"]);
+        assert!(report.fixed.is_empty());
+    }
+
     // -- Serialization roundtrip tests --------------------------------------
 
     #[test]
@@ -658,4 +1335,113 @@ mod tests {
         let actions = runner.auto_heal(&[]);
         assert!(actions.is_empty());
     }
+
+    const PY_TRACEBACK: &str = r#"Traceback (most recent call last):
+  File "/srv/app/main.py", line 42, in <module>
+    run()
+  File "/srv/app/main.py", line 30, in run
+    total += prices[item]
+  File "/srv/app/pricing.py", line 17, in lookup
+    return table[key]
+KeyError: 'widget'
+"#;
+
+    #[test]
+    fn classify_python_traceback() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("python main.py", PY_TRACEBACK);
+        assert_eq!(diag.category, ErrorCategory::RuntimePanic);
+
+        let tb = diag.traceback.as_ref().unwrap();
+        assert_eq!(tb.exception_type, "KeyError");
+        assert_eq!(tb.exception_message, "'widget'");
+        assert_eq!(tb.file.as_deref(), Some("/srv/app/pricing.py"));
+        assert_eq!(tb.line, Some(17));
+
+        let fix = diag.suggested_fix.unwrap();
+        assert!(
+            fix.starts_with("KeyError at /srv/app/pricing.py:17"),
+            "{fix}"
+        );
+        assert!(fix.contains("dict"));
+    }
+
+    #[test]
+    fn python_traceback_with_dotted_exception_and_no_message() {
+        let raw = "Traceback (most recent call last):\n  File \"x.py\", line 3, in <module>\n    f()\nrequests.exceptions.ConnectionError\n";
+        let tb = DiagnosticEngine::parse_python_traceback(raw).unwrap();
+        assert_eq!(tb.exception_type, "requests.exceptions.ConnectionError");
+        assert_eq!(tb.exception_message, "");
+        assert_eq!(tb.line, Some(3));
+    }
+
+    #[test]
+    fn rust_output_has_no_traceback() {
+        let engine = DiagnosticEngine::new();
+        let diag = engine.analyze("cargo run", "thread 'main' panicked at src/main.rs:3:5");
+        assert!(diag.traceback.is_none());
+        assert_eq!(
+            diag.suggested_fix.as_deref(),
+            Some("Examine the panic backtrace and fix the root cause")
+        );
+    }
+
+    #[test]
+    fn parallel_gates_overlap_and_keep_order() {
+        let runner = GateRunner::new();
+        let gates = [
+            ("first", "sleep 0.3"),
+            ("second", "sleep 0.3 && false"),
+            ("third", "sleep 0.3"),
+        ];
+        let start = std::time::Instant::now();
+        let results = runner.run_gates_parallel(&gates);
+        let elapsed = start.elapsed().as_millis();
+
+        assert!(elapsed < 800, "gates ran sequentially ({elapsed}ms)");
+        let names: Vec<&str> = results.iter().map(|r| r.gate_name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second", "third"]);
+        assert!(results[0].success && !results[1].success && results[2].success);
+        assert!(results[1].diagnostic.is_some());
+        assert!(results.iter().all(|r| r.duration_ms >= 300));
+    }
+
+    #[test]
+    fn default_gates_are_the_cargo_trio() {
+        let runner = GateRunner::new();
+        let names: Vec<&str> = runner.gates().iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["cargo fmt --check", "cargo clippy", "cargo test"]
+        );
+    }
+
+    #[test]
+    fn custom_gates_drive_run_all_gates() {
+        let runner = GateRunner::with_gates(vec![
+            ("ruff check".to_string(), "true".to_string()),
+            (
+                "pytest".to_string(),
+                "echo 'E   AssertionError: boom'; echo '1 failed in 0.1s'; false".to_string(),
+            ),
+        ]);
+        let results = runner.run_all_gates();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].gate_name, "ruff check");
+        assert!(results[0].success);
+        assert_eq!(results[1].gate_name, "pytest");
+        assert!(!results[1].success);
+        assert_eq!(
+            results[1].diagnostic.as_ref().unwrap().category,
+            ErrorCategory::TestFailure
+        );
+    }
+
+    #[test]
+    fn run_gates_uses_explicit_pairs() {
+        let runner = GateRunner::new();
+        let results = runner.run_gates(&[("ok", "true"), ("bad", "false")]);
+        assert!(results[0].success);
+        assert!(!results[1].success);
+    }
 }
@@ -282,6 +282,7 @@ mod tests {
             content: "Hello Discord!".to_string(),
             recipient: None,
             metadata: serde_json::json!({"channel_id": "chan-42"}),
+            ..Default::default()
         };
         channel.send(msg).await.unwrap();
 
@@ -303,6 +304,7 @@ mod tests {
             content: "fallback".to_string(),
             recipient: None,
             metadata: serde_json::json!({}),
+            ..Default::default()
         };
         channel.send(msg).await.unwrap();
 
@@ -0,0 +1,206 @@
+//! Tools backed by an external executable.
+//!
+//! [`ExternalProcessTool`] runs a program for every call, writes the call's
+//! JSON arguments to its stdin and reads a [`ToolResult`] as JSON from its
+//! stdout.  The program is run directly, without a shell.  Tools of this
+//! kind are registered at runtime, e.g. through the MCP `tools/register`
+//! method; any sandbox check belongs to whoever registers them.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::tool::{Tool, ToolContent, ToolResult};
+
+/// Default time a call may take before the process is killed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A tool whose calls are handled by running an external program.
+#[derive(Debug, Clone)]
+pub struct ExternalProcessTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ExternalProcessTool {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: serde_json::Value,
+        command: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+            command: command.into(),
+            args: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Pass `args` to the program on every call.
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Kill the program if a call takes longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The program run for each call.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            content: ToolContent::default(),
+            error: Some(error),
+            truncated: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ExternalProcessTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.input_schema.clone()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let mut child = match Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => return Ok(Self::failure(format!("failed to start command: {e}"))),
+        };
+
+        // Write from a separate task so a program that prints before
+        // reading its input cannot deadlock against us.
+        let input = serde_json::to_vec(&args)?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = tokio::spawn(async move {
+            // A program that ignores its input may exit before reading it.
+            let _ = stdin.write_all(&input).await;
+        });
+
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output?,
+            Err(_) => {
+                return Ok(Self::failure(format!(
+                    "command timed out after {}s",
+                    self.timeout.as_secs_f64()
+                )))
+            }
+        };
+        let _ = writer.await;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let status = match output.status.code() {
+                Some(code) => format!("command exited with code {code}"),
+                None => "command terminated by signal".to_string(),
+            };
+            return Ok(Self::failure(match stderr.trim() {
+                "" => status,
+                stderr => format!("{status}: {stderr}"),
+            }));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(serde_json::from_str(stdout.trim()).unwrap_or_else(|e| {
+            Self::failure(format!("invalid tool result from '{}': {e}", self.command))
+        }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A tool running `sh -c script`.
+    fn sh_tool(script: &str) -> ExternalProcessTool {
+        ExternalProcessTool::new(
+            "external",
+            "runs a shell script",
+            serde_json::json!({ "type": "object" }),
+            "sh",
+        )
+        .with_args(vec!["-c".into(), script.into()])
+    }
+
+    #[tokio::test]
+    async fn reads_tool_result_from_stdout() {
+        // Succeed only if the arguments arrived on stdin.
+        let tool =
+            sh_tool(r#"grep -q '"x":1' && echo '{"success":true,"output":"saw x","error":null}'"#);
+        let result = tool.execute(serde_json::json!({ "x": 1 })).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output(), "saw x");
+    }
+
+    #[tokio::test]
+    async fn nonzero_exit_reports_stderr() {
+        let result = sh_tool("echo broken >&2; exit 2")
+            .execute(serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("command exited with code 2: broken")
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_output_is_a_failure() {
+        let result = sh_tool("echo not json")
+            .execute(serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("invalid tool result from 'sh'"));
+    }
+
+    #[tokio::test]
+    async fn slow_command_times_out() {
+        let result = sh_tool("sleep 5")
+            .with_timeout(Duration::from_millis(100))
+            .execute(serde_json::json!({}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("command timed out"));
+    }
+}
@@ -1,16 +1,285 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
 use axum::{
-    response::IntoResponse,
+    extract::{
+        ws::{Message as WsMessage, WebSocket},
+        ConnectInfo, MatchedPath, Path, Query, Request, State,
+    },
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
+use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 use crate::a2a::{self, TaskStore};
+use crate::auth::{ApiIdentity, ApiKeyAuth, AuthError};
+use crate::config::{self, NodeConfig};
 use crate::mcp::McpServer;
+use crate::mcp_proxy::McpProxy;
+use crate::memory::{ListOrder, Memory, MemoryCategory};
+use crate::metrics::Metrics;
 use crate::multi_provider::ProviderRegistry;
-use crate::provider_health::ProviderHealth;
-use crate::registry::NodeRegistry;
-use crate::sqlite_registry::SqliteRegistry;
+use crate::observation::{ObservationCollector, ObservationHandle, ObservationPublisher};
+use crate::policy::{PolicyAction, PolicyConfig, PolicyEngine};
+use crate::provider::{ChatMessage, ChatRequest, ChatRole, ContentPart, MessageContent, ToolCall};
+use crate::provider_cache::ResponseCache;
+use crate::provider_health::{HealthProber, ProviderHealth};
+use crate::rate_limiter::GatewayRateLimiter;
+use crate::registry::{self as node_registry, InMemoryRegistry, LocalNode, NodeInfo, NodeRegistry};
+use crate::remote_registry::RemoteRegistry;
+use crate::scheduler::{ScheduleStore, Scheduler, SchedulerHandle};
+use crate::skills;
+use crate::sqlite_memory::SqliteMemory;
+use crate::tool::{LogLevel, ToolRegistry};
+use crate::tool_history::{self, ExecutionOrigin, ToolExecutionLog};
+use crate::tool_limits::ToolLimiter;
+use crate::tool_output::ToolOutputLimits;
+use crate::usage::{UsageTotals, UsageTracker};
+use crate::websocket::{self, WebSocketChannel};
+
+/// Shared state handed to gateway handlers.
+#[derive(Clone)]
+pub struct AppState {
+    /// Providers backing the OpenAI-compatible endpoint.
+    pub providers: Arc<ProviderRegistry>,
+    /// Node registry served under `/registry/*`.
+    pub registry: Arc<dyn NodeRegistry>,
+    /// Local channel whose clients connect to `/ws`.
+    pub ws_channel: WebSocketChannel,
+    /// Token accounting and daily budget for `/v1/chat/completions`,
+    /// reported by `/usage`.
+    pub usage: Arc<UsageTracker>,
+    /// Per-client request limits applied to every route.
+    pub rate_limiter: Arc<GatewayRateLimiter>,
+    /// API keys checked on every route. Open when none are configured.
+    pub auth: Arc<ApiKeyAuth>,
+    /// Counters and histograms served by `/metrics`.
+    pub metrics: Arc<Metrics>,
+    /// Provider statuses served by `/health/providers`, kept current by the
+    /// [`HealthProber`] started in [`serve`].
+    pub provider_health: Arc<RwLock<ProviderHealth>>,
+    /// A2A tasks, kept so `GetTask` and `CancelTask` can find them later.
+    pub a2a_tasks: TaskStore,
+    /// Memory store browsed through `GET /memory`.
+    pub memory: Arc<dyn Memory>,
+    /// Remote node `POST /mcp` forwards to for tools not served locally,
+    /// when this node acts as a brain proxy.
+    pub mcp_proxy: Option<Arc<McpProxy>>,
+    /// Tool policy for `POST /mcp` and `/mcp/ws`, tightened per request to
+    /// the authenticated caller's trust tier.
+    pub policy: PolicyConfig,
+    /// Builds the tools served by `POST /mcp`, and advertised as this
+    /// node's capabilities by `GET /self` and to a remote registry.
+    pub mcp_tools: fn() -> ToolRegistry,
+    /// This node's stable identity, served by `GET /self` and used when
+    /// joining a remote registry.
+    pub local_node: LocalNode,
+    /// Concurrency caps shared by every `POST /mcp` request's tools, with
+    /// their load served by `/metrics`.
+    pub tool_limiter: Arc<ToolLimiter>,
+    /// Output size limits applied to every `POST /mcp` request's tools.
+    pub tool_output: Arc<ToolOutputLimits>,
+    /// File the audit entries of each `POST /mcp` request and `/mcp/ws`
+    /// connection are appended to.
+    pub audit_file: Option<PathBuf>,
+    /// Scheduled skills, run by the [`Scheduler`] started in [`serve`] and
+    /// listed by `GET /schedules`.
+    pub schedules: Option<Arc<ScheduleStore>>,
+    /// Cancelled when the gateway begins shutting down; long-lived
+    /// responses such as SSE streams end when it fires.
+    pub shutdown: CancellationToken,
+}
+
+impl std::fmt::Debug for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppState")
+            .field("providers", &self.providers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AppState {
+    /// [`new`](Self::new) over the configuration file in effect, keeping the
+    /// stores under `~/.ygn` (the usage store at
+    /// [`crate::usage::default_db_path`], the A2A task store at
+    /// [`a2a::default_db_path`], the memory store at
+    /// [`crate::sqlite_memory::default_db_path`], and so on) and the node id at
+    /// [`config::node_id_path`].
+    pub fn from_env() -> Self {
+        Self::open(
+            NodeConfig::load_or_default(),
+            &default_data_dir(),
+            &config::node_id_path(),
+        )
+    }
+
+    /// Providers from the environment and an empty in-memory registry, with
+    /// the usage, A2A task, memory, schedule and provider cache stores and
+    /// the node id kept in `data_dir`.
+    ///
+    /// Providers record their call outcomes into `provider_health`, are
+    /// wrapped in a response cache when `provider_cache` is configured, and
+    /// model routes from `providers.models` and the `providers.fallback`
+    /// policy are applied.
+    pub fn new(cfg: NodeConfig, data_dir: &std::path::Path) -> Self {
+        Self::open(cfg, data_dir, &data_dir.join("node_id"))
+    }
+
+    fn open(cfg: NodeConfig, data_dir: &std::path::Path, node_id_path: &std::path::Path) -> Self {
+        let _ = std::fs::create_dir_all(data_dir);
+        let db_path = |name: &str| data_dir.join(name).to_string_lossy().into_owned();
+        let local_node = LocalNode::load(&cfg, node_id_path).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "node id not persisted; using a new one for this run");
+            LocalNode::from_config(&cfg, uuid::Uuid::new_v4().to_string())
+        });
+        let policy = cfg.policy_config().unwrap_or_else(|e| {
+            tracing::error!(error = %e, "tool policy unusable; denying every MCP tool call");
+            PolicyConfig {
+                default_action: PolicyAction::Deny,
+                ..PolicyConfig::default()
+            }
+        });
+        let metrics = Arc::new(Metrics::new());
+        let provider_health = Arc::new(RwLock::new(
+            ProviderHealth::new().with_thresholds(cfg.providers.health),
+        ));
+        let mut providers = ProviderRegistry::from_env()
+            .with_metrics(metrics.clone())
+            .with_health(provider_health.clone());
+        for (model, provider) in cfg.providers.models {
+            providers.register_model(model, provider);
+        }
+        if let Some(policy) = cfg.providers.fallback {
+            providers = providers.with_fallback(policy);
+        }
+        if let Some(cache_cfg) = cfg.provider_cache {
+            match ResponseCache::new(&db_path("provider_cache.db"), cache_cfg) {
+                Ok(cache) => providers = providers.with_cache(Arc::new(cache)),
+                Err(e) => tracing::warn!(error = %e, "provider cache disabled"),
+            }
+        }
+        Self {
+            providers: Arc::new(providers),
+            registry: Arc::new(InMemoryRegistry::new()),
+            ws_channel: WebSocketChannel::new(),
+            usage: Arc::new(UsageTracker::open_or_in_memory(
+                &db_path("usage.db"),
+                cfg.usage,
+            )),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(cfg.rate_limit)),
+            auth: Arc::new(ApiKeyAuth::new(cfg.auth)),
+            metrics,
+            provider_health,
+            a2a_tasks: open_task_store(&db_path("a2a_tasks.db")),
+            memory: Arc::new(open_memory(&db_path("memory.db"))),
+            mcp_proxy: None,
+            policy,
+            mcp_tools: if cfg.hardware.simulated {
+                McpServer::default_registry
+            } else {
+                McpServer::registry_without_hardware
+            },
+            local_node,
+            tool_limiter: Arc::new(ToolLimiter::new(cfg.tool_limits)),
+            tool_output: Arc::new(ToolOutputLimits::new(cfg.tool_output)),
+            audit_file: cfg.audit_log,
+            schedules: open_schedule_store(&db_path("schedules.db")),
+            shutdown: CancellationToken::new(),
+        }
+    }
+}
+
+/// Where [`AppState::from_env`] keeps its stores: `~/.ygn`.
+fn default_data_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".ygn")
+}
+
+/// Open the on-disk A2A task store, falling back to memory if it is unusable.
+fn open_task_store(path: &str) -> TaskStore {
+    TaskStore::open(path).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, path = %path, "A2A task store unavailable; keeping tasks in memory");
+        TaskStore::new()
+    })
+}
+
+/// Open the on-disk schedule store; scheduling is off if it is unusable.
+fn open_schedule_store(path: &str) -> Option<Arc<ScheduleStore>> {
+    match ScheduleStore::new(path) {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            tracing::warn!(error = %e, path = %path, "schedule store unavailable; scheduling disabled");
+            None
+        }
+    }
+}
+
+/// Start the [`Scheduler`] over `state.schedules` with the skills of
+/// [`skills::builtin_skills`] and the tools served by `POST /mcp`.  Runs
+/// are recorded in the tool history at [`tool_history::default_db_path`]
+/// when it can be opened.
+fn start_scheduler(state: &AppState) -> Option<SchedulerHandle> {
+    let store = state.schedules.clone()?;
+    let skills = match skills::builtin_skills() {
+        Ok(skills) => skills,
+        Err(e) => {
+            tracing::warn!(error = %e, "skills unavailable; scheduling disabled");
+            return None;
+        }
+    };
+    let mut tools = (state.mcp_tools)();
+    let history = match ToolExecutionLog::new(&tool_history::default_db_path()) {
+        Ok(log) => Some(Arc::new(log)),
+        Err(e) => {
+            tracing::warn!(error = %e, "tool history unavailable; scheduled runs not recorded");
+            None
+        }
+    };
+    if let Some(log) = &history {
+        tools.set_history(log.clone(), ExecutionOrigin::Skill);
+    }
+    let mut scheduler = Scheduler::new(store, skills, tools).with_memory(state.memory.clone());
+    if let Some(log) = history {
+        scheduler = scheduler.with_history(log);
+    }
+    Some(scheduler.spawn())
+}
+
+/// Open the on-disk memory store, falling back to an empty in-memory one.
+fn open_memory(path: &str) -> SqliteMemory {
+    SqliteMemory::new(path).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, path = %path, "memory store unavailable; using an empty one");
+        SqliteMemory::in_memory().expect("in-memory SQLite")
+    })
+}
+
+/// `GET /metrics` — Prometheus text exposition.
+async fn metrics(State(state): State<AppState>) -> Response {
+    let mut body = state.metrics.render();
+    state.tool_limiter.render(&mut body);
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response()
+}
 
 async fn health() -> Json<Value> {
     Json(json!({
@@ -40,6 +309,7 @@ async fn list_providers() -> Json<Value> {
                 .unwrap_or(json!(null));
             json!({
                 "name": name,
+                "kind": registry.kind(name),
                 "capabilities": caps,
             })
         })
@@ -51,34 +321,61 @@ async fn list_providers() -> Json<Value> {
     }))
 }
 
-/// `GET /health/providers` — Health status summary for all providers.
-async fn providers_health() -> Json<Value> {
-    let registry = ProviderRegistry::from_env();
-    let health = ProviderHealth::new();
+/// `GET /health/providers` — Health status summary for all providers, as
+/// last seen by the periodic health probes.
+///
+/// `last_success` and `last_failure` say whether they came from a `probe`
+/// or from real `traffic`.
+///
+/// Providers behind a response cache also report `cache` hit/miss counters.
+async fn providers_health(State(state): State<AppState>) -> Json<Value> {
+    let registry = &state.providers;
+    let health = state
+        .provider_health
+        .read()
+        .unwrap_or_else(|e| e.into_inner());
 
     let statuses: Vec<Value> = registry
         .list()
         .iter()
         .map(|name| {
             let status = health.get_status(name);
-            match status {
+            let mut entry = match status {
                 Some(s) => json!({
                     "provider": s.provider,
                     "healthy": s.healthy,
                     "consecutive_failures": s.consecutive_failures,
+                    "consecutive_successes": s.consecutive_successes,
                     "total_requests": s.total_requests,
                     "total_failures": s.total_failures,
                     "avg_latency_ms": s.avg_latency_ms,
+                    "last_probe_at": s.last_probe_at,
+                    "last_success": s.last_success.map(|at| json!({
+                        "at": at,
+                        "source": s.last_success_source,
+                    })),
+                    "last_failure": s.last_failure.map(|at| json!({
+                        "at": at,
+                        "source": s.last_failure_source,
+                    })),
                 }),
                 None => json!({
                     "provider": name,
                     "healthy": true,
                     "consecutive_failures": 0,
+                    "consecutive_successes": 0,
                     "total_requests": 0,
                     "total_failures": 0,
                     "avg_latency_ms": 0.0,
+                    "last_probe_at": null,
+                    "last_success": null,
+                    "last_failure": null,
                 }),
+            };
+            if let Some(stats) = registry.get(name).and_then(|p| p.cache_stats()) {
+                entry["cache"] = json!(stats);
             }
+            entry
         })
         .collect();
 
@@ -102,18 +399,28 @@ async fn providers_health() -> Json<Value> {
 /// used by the stdio MCP server.  Supports `Accept` header awareness per the
 /// MCP Streamable HTTP specification:
 ///   - `application/json` (default) → JSON response
-///   - `text/event-stream` → reserved for future SSE streaming; currently
-///     returns JSON with `application/json` content-type.
+///   - `text/event-stream` → still returns JSON with `application/json`
+///     content-type; progress streams from `POST /mcp/stream` instead.
 ///
 /// Notifications (no `id`) return 204 No Content.
-async fn mcp_http(headers: axum::http::HeaderMap, Json(body): Json<Value>) -> impl IntoResponse {
-    let server = McpServer::with_default_tools();
+///
+/// Tool calls are checked against the configured policy, tightened to the
+/// authenticated caller's trust tier.
+async fn mcp_http(
+    State(state): State<AppState>,
+    identity: Option<axum::Extension<ApiIdentity>>,
+    request_id: Option<axum::Extension<RequestId>>,
+    headers: axum::http::HeaderMap,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    let server = mcp_server(&state, identity, request_id);
     let accept = headers
         .get("accept")
         .and_then(|v| v.to_str().ok())
         .unwrap_or("application/json");
 
     let response = server.handle_jsonrpc(body);
+    append_audit(&state, &server);
 
     if accept.contains("text/event-stream") {
         // Future: return Server-Sent Events stream.
@@ -138,63 +445,134 @@ async fn mcp_http(headers: axum::http::HeaderMap, Json(body): Json<Value>) -> im
 // A2A routes (Phase 7 — B2)
 // ---------------------------------------------------------------------------
 
-/// `GET /.well-known/agent.json` — Agent Card discovery.
-async fn agent_card() -> Json<Value> {
-    Json(a2a::agent_card())
+/// `GET /.well-known/agent.json` — Agent Card discovery, advertising the
+/// tools served by `POST /mcp` and the skills of
+/// [`skills::builtin_skills`].
+async fn agent_card(State(state): State<AppState>) -> Json<Value> {
+    let skills = skills::builtin_skills().unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "skills unavailable; agent card lists tools only");
+        skills::SkillRegistry::new()
+    });
+    Json(a2a::agent_card(&(state.mcp_tools)(), &skills))
 }
 
 /// `POST /a2a` — A2A message handler.
-async fn a2a_handler(Json(body): Json<Value>) -> Json<Value> {
-    let store = TaskStore::new();
-    Json(a2a::handle_a2a(&body, &store))
+///
+/// The task store may be SQLite, so requests are handled on the blocking
+/// pool rather than on a runtime worker.
+async fn a2a_handler(State(state): State<AppState>, Json(body): Json<Value>) -> Json<Value> {
+    let store = state.a2a_tasks.clone();
+    match tokio::task::spawn_blocking(move || a2a::handle_a2a(&body, &store)).await {
+        Ok(response) => Json(response),
+        Err(e) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32603, "message": format!("Internal error: {e}") }
+        })),
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Registry routes
 // ---------------------------------------------------------------------------
 
-/// `GET /registry/nodes` — List all registered nodes.
-async fn list_registry_nodes() -> Json<Value> {
-    let registry = SqliteRegistry::new(":memory:").unwrap();
-    // For now, returns an empty node list (no persisted state in this handler)
-    // In production, registry would be shared state via Axum State
-    let filter = crate::registry::DiscoveryFilter {
-        role: None,
-        trust_tier: None,
-        capability: None,
-        max_staleness_seconds: None,
+fn registry_error(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// `GET /self` — This node's registry entry, with the tools it serves now
+/// as capabilities.
+async fn self_info(State(state): State<AppState>) -> Json<NodeInfo> {
+    Json(state.local_node.info(&(state.mcp_tools)()))
+}
+
+/// `GET /registry/nodes` — List registered nodes.
+///
+/// Optional query parameters mirror [`node_registry::DiscoveryFilter`]:
+/// `role`, `trust_tier`, `capability`, `max_staleness_seconds`, `sort_by`,
+/// `limit`, `offset`.
+async fn list_registry_nodes(
+    State(state): State<AppState>,
+    Query(filter): Query<node_registry::DiscoveryFilter>,
+) -> Response {
+    match state.registry.discover(filter).await {
+        Ok(nodes) => Json(json!({
+            "count": nodes.len(),
+            "nodes": nodes,
+        }))
+        .into_response(),
+        Err(e) => registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// `POST /registry/register` — Register (or replace) a node.
+async fn registry_register(State(state): State<AppState>, Json(body): Json<Value>) -> Response {
+    let node: NodeInfo = match serde_json::from_value(body) {
+        Ok(n) => n,
+        Err(e) => return registry_error(StatusCode::BAD_REQUEST, format!("invalid node: {e}")),
     };
-    let nodes = registry.discover(filter).await.unwrap_or_default();
-    let node_values: Vec<Value> = nodes
-        .iter()
-        .map(|n| {
-            json!({
-                "node_id": n.node_id,
-                "role": format!("{}", n.role),
-                "trust_tier": format!("{}", n.trust_tier),
-                "capabilities": n.capabilities,
-                "last_seen": n.last_seen.to_rfc3339(),
-            })
-        })
-        .collect();
+    let node_id = node.node_id.clone();
+    match state.registry.register(node).await {
+        Ok(()) => Json(json!({ "registered": node_id })).into_response(),
+        Err(e) => registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
 
-    Json(json!({
-        "nodes": node_values,
-        "count": node_values.len(),
-    }))
+/// `GET /registry/nodes/{id}` — Look up a single node.
+async fn registry_get_node(State(state): State<AppState>, Path(node_id): Path<String>) -> Response {
+    match state.registry.get(&node_id).await {
+        Ok(Some(node)) => Json(node).into_response(),
+        Ok(None) => registry_error(StatusCode::NOT_FOUND, format!("Node not found: {node_id}")),
+        Err(e) => registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// `POST /registry/nodes/{id}/heartbeat` — Refresh a node's `last_seen`.
+///
+/// Returns 404 for unknown nodes so the caller knows to register again.
+async fn registry_heartbeat(
+    State(state): State<AppState>,
+    Path(node_id): Path<String>,
+) -> Response {
+    if matches!(state.registry.get(&node_id).await, Ok(None)) {
+        return registry_error(StatusCode::NOT_FOUND, format!("Node not found: {node_id}"));
+    }
+    match state.registry.heartbeat(&node_id).await {
+        Ok(()) => Json(json!({ "node_id": node_id })).into_response(),
+        Err(e) => registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// `DELETE /registry/nodes/{id}` — Remove a node.
+async fn registry_deregister(
+    State(state): State<AppState>,
+    Path(node_id): Path<String>,
+) -> Response {
+    match state.registry.deregister(&node_id).await {
+        Ok(removed) => Json(json!({ "removed": removed })).into_response(),
+        Err(e) => registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
 }
 
 /// `POST /registry/sync` — Cross-node registry sync.
-async fn registry_sync(Json(body): Json<Value>) -> Json<Value> {
-    let _registry = SqliteRegistry::new(":memory:").unwrap();
-    // Parse nodes from body
+///
+/// Registers every well-formed node in the `nodes` array.
+async fn registry_sync(State(state): State<AppState>, Json(body): Json<Value>) -> Json<Value> {
     let nodes_json = body.get("nodes").and_then(|n| n.as_array());
     match nodes_json {
         Some(nodes_arr) => {
-            // For now, return success with zero accepted (in-memory registry per request)
+            let mut accepted = 0;
+            for raw in nodes_arr {
+                let Ok(node) = serde_json::from_value::<NodeInfo>(raw.clone()) else {
+                    continue;
+                };
+                if state.registry.register(node).await.is_ok() {
+                    accepted += 1;
+                }
+            }
             Json(json!({
-                "accepted": 0,
-                "rejected": nodes_arr.len(),
+                "accepted": accepted,
+                "rejected": nodes_arr.len() - accepted,
             }))
         }
         None => Json(json!({
@@ -279,6 +657,35 @@ async fn sessions_list() -> Json<Value> {
     Json(json!({ "sessions": sessions, "count": count }))
 }
 
+/// `GET /schedules` — Scheduled skills with their next run (before
+/// jitter).
+async fn schedules_list(State(state): State<AppState>) -> Json<Value> {
+    let entries = state
+        .schedules
+        .as_ref()
+        .map(|store| store.list())
+        .transpose()
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "could not read schedules");
+            None
+        })
+        .unwrap_or_default();
+    let now = chrono::Utc::now();
+    let schedules: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let mut value = serde_json::to_value(entry).unwrap_or_else(|_| json!({}));
+            value["next_run"] = json!(entry
+                .enabled
+                .then(|| entry.schedule.next_after(now))
+                .flatten());
+            value
+        })
+        .collect();
+    let count = schedules.len();
+    Json(json!({ "schedules": schedules, "count": count }))
+}
+
 /// `GET /memory/stats` — Memory tier distribution.
 async fn memory_stats() -> Json<Value> {
     Json(json!({
@@ -289,38 +696,963 @@ async fn memory_stats() -> Json<Value> {
     }))
 }
 
+/// Query parameters of `GET /memory`.
+#[derive(Debug, serde::Deserialize)]
+struct MemoryListQuery {
+    category: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+    #[serde(default)]
+    order: ListOrder,
+}
+
+/// Largest page `GET /memory` returns.
+const MAX_MEMORY_PAGE: usize = 100;
+
+/// `GET /memory` — Page through memory entries.
+///
+/// Optional query parameters: `category`, `offset`, `limit` (default 20,
+/// at most 100) and `order` (`updated_at` or `created_at`, newest first).
+async fn memory_list(
+    State(state): State<AppState>,
+    Query(query): Query<MemoryListQuery>,
+) -> Response {
+    let category = query.category.as_deref().map(|c| {
+        let Ok(category) = c.parse::<MemoryCategory>();
+        category
+    });
+    let limit = query.limit.unwrap_or(20).min(MAX_MEMORY_PAGE);
+    let listed = state
+        .memory
+        .list(category.clone(), query.offset, limit, query.order)
+        .await;
+    let total = state.memory.count(category).await;
+    match (listed, total) {
+        (Ok(entries), Ok(total)) => Json(json!({
+            "count": entries.len(),
+            "total": total,
+            "offset": query.offset,
+            "limit": limit,
+            "entries": entries,
+        }))
+        .into_response(),
+        (Err(e), _) | (_, Err(e)) => {
+            registry_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+/// `GET /usage` — Token usage totals, grouped views, and per-day breakdown.
+async fn usage_summary(State(state): State<AppState>) -> Json<Value> {
+    let tracker = &state.usage;
+    let grouped = |rows: anyhow::Result<Vec<(String, UsageTotals)>>| -> Value {
+        let map: serde_json::Map<String, Value> = rows
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(k, v)| (k, json!(v)))
+            .collect();
+        Value::Object(map)
+    };
+
+    Json(json!({
+        "totals": tracker.totals().unwrap_or_default(),
+        "by_provider": grouped(tracker.totals_by_provider()),
+        "by_model": grouped(tracker.totals_by_model()),
+        "daily": tracker.daily_breakdown().unwrap_or_default(),
+        "daily_cost_limit": tracker.config().daily_cost_limit,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// OpenAI-compatible chat completions
+// ---------------------------------------------------------------------------
+
+/// Build an error response matching OpenAI's error schema.
+fn openai_error(status: StatusCode, kind: &str, code: &str, message: String) -> Response {
+    (
+        status,
+        Json(json!({
+            "error": {
+                "message": message,
+                "type": kind,
+                "param": null,
+                "code": code,
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// Convert one OpenAI content part into a [`ContentPart`].
+fn openai_part_to_content(part: &Value) -> Option<ContentPart> {
+    match part.get("type")?.as_str()? {
+        "text" => Some(ContentPart::Text {
+            text: part.get("text")?.as_str()?.to_string(),
+        }),
+        "image_url" => {
+            let url = part.get("image_url")?.get("url")?.as_str()?;
+            // data:<mime>;base64,<data>
+            if let Some((mime, data)) = url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+            {
+                Some(ContentPart::Image {
+                    data: data.to_string(),
+                    mime_type: mime.to_string(),
+                })
+            } else {
+                Some(ContentPart::ImageUrl {
+                    url: url.to_string(),
+                })
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Convert one entry of an assistant message's OpenAI `tool_calls` into a
+/// [`ToolCall`].
+fn openai_tool_call(call: &Value) -> Option<ToolCall> {
+    let function = call.get("function")?;
+    Some(ToolCall {
+        tool_name: function.get("name")?.as_str()?.to_string(),
+        arguments: function
+            .get("arguments")
+            .and_then(|a| a.as_str())
+            .and_then(|a| serde_json::from_str(a).ok())
+            .unwrap_or(Value::Null),
+        id: call.get("id").and_then(|id| id.as_str()).map(String::from),
+    })
+}
+
+/// Convert an OpenAI chat completion request body into a [`ChatRequest`].
+fn openai_to_chat_request(body: &Value) -> Result<ChatRequest, String> {
+    let model = body
+        .get("model")
+        .and_then(|m| m.as_str())
+        .ok_or("missing required field 'model'")?
+        .to_string();
+    let raw_messages = body
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .ok_or("missing required field 'messages'")?;
+
+    let mut messages = Vec::with_capacity(raw_messages.len());
+    for (i, msg) in raw_messages.iter().enumerate() {
+        let role = match msg.get("role").and_then(|r| r.as_str()) {
+            Some("system") | Some("developer") => ChatRole::System,
+            Some("user") => ChatRole::User,
+            Some("assistant") => ChatRole::Assistant,
+            Some("tool") => ChatRole::Tool,
+            other => return Err(format!("messages[{i}]: unsupported role {other:?}")),
+        };
+        let content = match msg.get("content") {
+            Some(Value::String(text)) => MessageContent::Text(text.clone()),
+            Some(Value::Array(parts)) => {
+                MessageContent::Parts(parts.iter().filter_map(openai_part_to_content).collect())
+            }
+            None | Some(Value::Null) => MessageContent::Text(String::new()),
+            Some(_) => return Err(format!("messages[{i}]: invalid content")),
+        };
+        let tool_call_id = msg
+            .get("tool_call_id")
+            .and_then(|id| id.as_str())
+            .map(String::from);
+        let tool_calls = msg
+            .get("tool_calls")
+            .and_then(|calls| calls.as_array())
+            .map(|calls| calls.iter().filter_map(openai_tool_call).collect())
+            .unwrap_or_default();
+        messages.push(ChatMessage {
+            role,
+            content,
+            tool_call_id,
+            tool_calls,
+        });
+    }
+
+    Ok(ChatRequest {
+        model,
+        messages,
+        max_tokens: body
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32),
+        temperature: body.get("temperature").and_then(|v| v.as_f64()),
+        response_format: None,
+        stop: match body.get("stop") {
+            Some(Value::String(s)) => Some(vec![s.clone()]),
+            Some(Value::Array(items)) => Some(
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+            ),
+            _ => None,
+        },
+        top_p: body.get("top_p").and_then(|v| v.as_f64()),
+        seed: body.get("seed").and_then(|v| v.as_u64()),
+        candidate_count: None,
+    })
+}
+
+/// `POST /v1/chat/completions` — OpenAI-compatible chat endpoint.
+///
+/// Routes the request via [`ProviderRegistry::resolve`], so models no
+/// provider can serve are a 404 rather than an upstream error. With `stream: true`
+/// the response is an SSE stream of `chat.completion.chunk` objects
+/// terminated by `data: [DONE]`. Requests are refused with 429 once the
+/// daily cost budget is spent, and token usage is recorded in
+/// [`AppState::usage`].
+async fn chat_completions(State(state): State<AppState>, Json(body): Json<Value>) -> Response {
+    let request = match openai_to_chat_request(&body) {
+        Ok(r) => r,
+        Err(e) => {
+            return openai_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request_error",
+                "invalid_request",
+                e,
+            )
+        }
+    };
+    let model = request.model.clone();
+    let Some(provider) = state.providers.resolve(&model).await else {
+        return openai_error(
+            StatusCode::NOT_FOUND,
+            "invalid_request_error",
+            "model_not_found",
+            format!("The model `{model}` does not exist or you do not have access to it."),
+        );
+    };
+
+    if let Err(e) = state.usage.check_budget() {
+        return openai_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            "insufficient_quota",
+            "insufficient_quota",
+            e.to_string(),
+        );
+    }
+
+    let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let created = chrono::Utc::now().timestamp();
+    let stream = body
+        .get("stream")
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false);
+
+    if !stream {
+        return match state.usage.chat(provider, request, None).await {
+            Ok(resp) => {
+                let (prompt, completion) = resp
+                    .usage
+                    .map(|u| (u.prompt_tokens, u.completion_tokens))
+                    .unwrap_or((0, 0));
+                Json(json!({
+                    "id": id,
+                    "object": "chat.completion",
+                    "created": created,
+                    "model": model,
+                    "choices": [{
+                        "index": 0,
+                        "message": { "role": "assistant", "content": resp.content },
+                        "finish_reason": "stop",
+                    }],
+                    "usage": {
+                        "prompt_tokens": prompt,
+                        "completion_tokens": completion,
+                        "total_tokens": prompt + completion,
+                    },
+                }))
+                .into_response()
+            }
+            Err(e) => openai_error(
+                StatusCode::BAD_GATEWAY,
+                "api_error",
+                "provider_error",
+                e.to_string(),
+            ),
+        };
+    }
+
+    let chunks = match provider.chat_stream(request).await {
+        Ok(s) => s,
+        Err(e) => {
+            return openai_error(
+                StatusCode::BAD_GATEWAY,
+                "api_error",
+                "provider_error",
+                e.to_string(),
+            )
+        }
+    };
+
+    let (tracker, provider_name, usage_model) = (
+        state.usage.clone(),
+        provider.name().to_string(),
+        model.clone(),
+    );
+    let chunk = move |delta: Value, finish_reason: Option<&str>| {
+        json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    };
+
+    let first = chunk(json!({ "role": "assistant", "content": "" }), None);
+    let last = chunk(json!({}), Some("stop"));
+    let body = chunks.map(move |c| match c {
+        Ok(c) => {
+            if let Some(ref u) = c.usage {
+                if let Err(e) = tracker.record(&provider_name, &usage_model, None, u) {
+                    tracing::warn!(error = %e, "failed to record streamed usage");
+                }
+            }
+            chunk(json!({ "content": c.delta }), None)
+        }
+        Err(e) => json!({
+            "error": { "message": e.to_string(), "type": "api_error", "param": null, "code": "provider_error" }
+        }),
+    });
+
+    let events = futures_util::stream::iter([first])
+        .chain(body)
+        .chain(futures_util::stream::iter([last]))
+        .map(|v| Event::default().data(v.to_string()))
+        .chain(futures_util::stream::iter(
+            [Event::default().data("[DONE]")],
+        ))
+        .map(Ok::<_, Infallible>)
+        .take_until(state.shutdown.clone().cancelled_owned());
+
+    Sse::new(events).into_response()
+}
+
+/// The MCP server for one HTTP request, policed per the caller's trust
+/// tier when authenticated and tagging its audit entries and tool history
+/// with the request's correlation id.
+fn mcp_server(
+    state: &AppState,
+    identity: Option<axum::Extension<ApiIdentity>>,
+    request_id: Option<axum::Extension<RequestId>>,
+) -> McpServer {
+    let policy = match identity {
+        Some(axum::Extension(identity)) => state.policy.clone().restricted_to(&identity.trust_tier),
+        None => state.policy.clone(),
+    };
+    let server = McpServer::with_policy((state.mcp_tools)(), PolicyEngine::from_config(policy))
+        .with_metrics(state.metrics.clone())
+        .with_tool_limiter(state.tool_limiter.clone())
+        .with_output_limits(state.tool_output.clone());
+    let server = match request_id {
+        Some(axum::Extension(RequestId(id))) => server.with_request_id(id),
+        None => server,
+    };
+    match &state.mcp_proxy {
+        Some(proxy) => server.with_proxy(proxy.clone()),
+        None => server,
+    }
+}
+
+/// Append `server`'s audit entries to the configured audit file, if any.
+fn append_audit(state: &AppState, server: &McpServer) {
+    if let Some(path) = &state.audit_file {
+        if let Err(e) = server.dump_audit_log(path) {
+            tracing::warn!(error = %e, path = %path.display(), "failed to append MCP audit log");
+        }
+    }
+}
+
+/// Query parameters of `POST /mcp/stream`.
+#[derive(Debug, serde::Deserialize)]
+struct McpStreamQuery {
+    /// Least severe `notifications/message` level to send; none when unset.
+    log_level: Option<LogLevel>,
+}
+
+/// `POST /mcp/stream` — MCP over HTTP as Server-Sent Events.
+///
+/// Same request handling as `POST /mcp`, but every `notifications/progress`
+/// the call emits is sent as a `message` event while the tool runs,
+/// followed by the JSON-RPC response; the stream then ends.  Each request
+/// gets a fresh server, so instead of `logging/setLevel` the optional
+/// `log_level` query parameter turns on `notifications/message` events.
+async fn mcp_stream(
+    State(state): State<AppState>,
+    identity: Option<axum::Extension<ApiIdentity>>,
+    request_id: Option<axum::Extension<RequestId>>,
+    Query(query): Query<McpStreamQuery>,
+    Json(body): Json<Value>,
+) -> Response {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Value>();
+    let notify = tx.clone();
+    let server = mcp_server(&state, identity, request_id).with_notifier(move |notification| {
+        let _ = notify.send(notification);
+    });
+    let server = match query.log_level {
+        Some(level) => server.with_log_level(level),
+        None => server,
+    };
+    // The server drops its sender with it, which ends the stream.
+    let audit_state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Some(response) = server.handle_jsonrpc(body) {
+            let _ = tx.send(response);
+        }
+        append_audit(&audit_state, &server);
+    });
+
+    let events = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|message| (message, rx))
+    })
+    .map(|message| Ok::<_, Infallible>(Event::default().event("message").data(message.to_string())))
+    .take_until(state.shutdown.clone().cancelled_owned());
+
+    Sse::new(events).into_response()
+}
+
+/// `GET /mcp/ws` — MCP over a persistent WebSocket.
+///
+/// Each text frame carries a JSON-RPC message, or several separated by
+/// newlines.  Responses and notifications such as `notifications/progress`
+/// come back as text frames.  One [`McpServer`] serves the whole
+/// connection, tagging every message with the upgrade request's id; its
+/// audit log is appended to the audit file on close.
+///
+/// Up to [`websocket::INBOUND_CAPACITY`] messages wait to be handled and
+/// [`websocket::OUTBOUND_CAPACITY`] frames to be sent; a client that
+/// overruns the inbound queue is disconnected, and progress notifications
+/// that do not fit are dropped.  When the socket closes, running calls are
+/// cancelled and queued messages are discarded.
+async fn mcp_ws(
+    State(state): State<AppState>,
+    identity: Option<axum::Extension<ApiIdentity>>,
+    request_id: Option<axum::Extension<RequestId>>,
+    ws: axum::extract::WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| serve_mcp_ws(state, identity, request_id, socket))
+}
+
+async fn serve_mcp_ws(
+    state: AppState,
+    identity: Option<axum::Extension<ApiIdentity>>,
+    request_id: Option<axum::Extension<RequestId>>,
+    socket: WebSocket,
+) {
+    let (outbound, mut queue) = tokio::sync::mpsc::channel::<String>(websocket::OUTBOUND_CAPACITY);
+    let (inbound, mut messages) = tokio::sync::mpsc::channel::<String>(websocket::INBOUND_CAPACITY);
+    let notify = outbound.clone();
+    let server = mcp_server(&state, identity, request_id).with_notifier(move |notification| {
+        let _ = notify.try_send(notification.to_string());
+    });
+    // Messages are handled in order on a blocking thread, as for
+    // `POST /mcp/stream`, so progress can flow while a tool runs.
+    // Cancellations are applied as frames arrive, ahead of that queue.
+    let in_flight = server.in_flight();
+    let closed = CancellationToken::new();
+    let audit_state = state.clone();
+    let worker = tokio::task::spawn_blocking({
+        let closed = closed.clone();
+        move || {
+            while let Some(message) = messages.blocking_recv() {
+                if closed.is_cancelled() {
+                    break;
+                }
+                if let Some(response) = server.handle_message(&message) {
+                    if outbound.blocking_send(response).is_err() {
+                        break;
+                    }
+                }
+            }
+            append_audit(&audit_state, &server);
+        }
+    });
+
+    let (mut sink, mut stream) = socket.split();
+    'socket: loop {
+        tokio::select! {
+            out = queue.recv() => {
+                let Some(text) = out else { break };
+                if sink.send(WsMessage::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            frame = stream.next() => match frame {
+                Some(Ok(WsMessage::Text(text))) => {
+                    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+                        in_flight.observe(line);
+                        if inbound.try_send(line.to_string()).is_err() {
+                            tracing::warn!("mcp websocket inbound queue full; closing");
+                            break 'socket;
+                        }
+                    }
+                }
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = state.shutdown.cancelled() => break,
+        }
+    }
+    closed.cancel();
+    in_flight.cancel_all();
+    drop(inbound);
+    drop(queue);
+    let _ = worker.await;
+}
+
+// ---------------------------------------------------------------------------
+// WebSocket channel
+// ---------------------------------------------------------------------------
+
+/// Upgrade to a WebSocket served by the gateway's [`WebSocketChannel`].
+///
+/// The connection id comes from `?client_id=` or the `x-client-id` header.
+async fn ws_connect(
+    State(state): State<AppState>,
+    Query(query): Query<std::collections::HashMap<String, String>>,
+    headers: axum::http::HeaderMap,
+    ws: axum::extract::WebSocketUpgrade,
+) -> Response {
+    let client_id = WebSocketChannel::client_id(&query, &headers);
+    state.ws_channel.accept(ws, client_id)
+}
+
 // ---------------------------------------------------------------------------
 // Router
 // ---------------------------------------------------------------------------
 
 /// Build the full application router.
 pub fn build_router() -> Router {
+    build_router_with_state(AppState::from_env())
+}
+
+/// Build the application router over the given shared state.
+pub fn build_router_with_state(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health))
         .route("/providers", get(list_providers))
         .route("/health/providers", get(providers_health))
         .route("/mcp", post(mcp_http))
+        .route("/mcp/stream", post(mcp_stream))
+        .route("/mcp/ws", get(mcp_ws))
+        .route("/self", get(self_info))
         .route("/.well-known/agent.json", get(agent_card))
         .route("/a2a", post(a2a_handler))
         .route("/registry/nodes", get(list_registry_nodes))
+        .route("/registry/register", post(registry_register))
+        .route(
+            "/registry/nodes/{id}",
+            get(registry_get_node).delete(registry_deregister),
+        )
+        .route("/registry/nodes/{id}/heartbeat", post(registry_heartbeat))
         .route("/registry/sync", post(registry_sync))
         .route("/guard/log", get(guard_log))
         .route("/sessions", get(sessions_list))
+        .route("/memory", get(memory_list))
         .route("/memory/stats", get(memory_stats))
+        .route("/schedules", get(schedules_list))
+        .route("/usage", get(usage_summary))
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/ws", get(ws_connect))
+        .route("/metrics", get(metrics))
+        // Runs after `authenticate`, so it can key on the caller's identity.
+        .layer(middleware::from_fn_with_state(
+            state.rate_limiter.clone(),
+            rate_limit,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.auth.clone(),
+            authenticate,
+        ))
+        // Runs before `authenticate`, so rejected keys count too.
+        .layer(middleware::from_fn_with_state(
+            state.rate_limiter.clone(),
+            rate_limit_auth_failures,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            track_metrics,
+        ))
+        .layer(middleware::from_fn(request_id))
+        .with_state(state)
+}
+
+/// Header carrying a request's correlation id, in and out.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Correlation id of the current request, available to handlers as an
+/// extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware tagging each request with a correlation id: the caller's
+/// `x-request-id` when it sent one, else a fresh UUID.  The id is stored
+/// in the request's extensions, recorded on a tracing span around the
+/// request and echoed in the response's `x-request-id` header.
+async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(id.clone()));
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path()
+    );
+    let mut response = next.run(request).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Middleware recording request counts and latency by route template.
+async fn track_metrics(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", |p| p.as_str())
+        .to_string();
+    let method = request.method().clone();
+    let started = std::time::Instant::now();
+    let response = next.run(request).await;
+    metrics.record_http(
+        &route,
+        method.as_str(),
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Middleware validating the bearer token and attaching the caller's
+/// [`ApiIdentity`] to the request.
+async fn authenticate(
+    State(auth): State<Arc<ApiKeyAuth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    match auth.authenticate(request.uri().path(), authorization) {
+        Ok(identity) => {
+            if let Some(identity) = identity {
+                request.extensions_mut().insert(identity);
+            }
+            next.run(request).await
+        }
+        Err(e @ AuthError::Forbidden(..)) => openai_error(
+            StatusCode::FORBIDDEN,
+            "invalid_request_error",
+            "route_not_allowed",
+            e.to_string(),
+        ),
+        Err(e) => {
+            let mut response = openai_error(
+                StatusCode::UNAUTHORIZED,
+                "invalid_request_error",
+                "invalid_api_key",
+                e.to_string(),
+            );
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            response
+        }
+    }
+}
+
+/// Rate-limit key for a request: the authenticated [`ApiIdentity`] when
+/// `key_by_api_key` is set and there is one, else the peer IP address.
+/// Tokens that are not configured keys never get a bucket of their own.
+fn client_key(limiter: &GatewayRateLimiter, request: &Request) -> String {
+    if limiter.config().key_by_api_key {
+        if let Some(identity) = request.extensions().get::<ApiIdentity>() {
+            return format!("key:{}", identity.name);
+        }
+    }
+    client_ip(request)
+}
+
+/// The caller's IP address, or `unknown` without connection info.
+fn client_ip(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<ConnectInfo<std::net::SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Middleware rejecting throttled clients with `429 Too Many Requests`.
+async fn rate_limit(
+    State(limiter): State<Arc<GatewayRateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = client_key(&limiter, &request);
+    match limiter.check(request.uri().path(), &key) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => too_many_requests(&key, request.uri().path(), wait),
+    }
+}
+
+/// Middleware counting failed authentication against the caller's IP, so
+/// guessing API keys is throttled like any other request.  An IP out of
+/// requests is rejected before its key is checked.
+async fn rate_limit_auth_failures(
+    State(limiter): State<Arc<GatewayRateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = client_ip(&request);
+    let path = request.uri().path().to_string();
+    if let Err(wait) = limiter.peek(&path, &ip) {
+        return too_many_requests(&ip, &path, wait);
+    }
+    let response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let _ = limiter.check(&path, &ip);
+    }
+    response
+}
+
+/// `429 Too Many Requests` for `key`, retryable after `wait`.
+fn too_many_requests(key: &str, path: &str, wait: std::time::Duration) -> Response {
+    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+    tracing::debug!(client = %key, path, retry_after, "rate limited");
+    let mut response = openai_error(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limit_error",
+        "rate_limit_exceeded",
+        format!("Rate limit exceeded; retry after {retry_after}s."),
+    );
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+/// The address to advertise to a remote registry: `registry.advertise_address`
+/// if set, else the bound address. Unspecified addresses such as `0.0.0.0`
+/// are unreachable for other nodes and rejected.
+fn advertise_address(
+    cfg: &NodeConfig,
+    bound: Option<std::net::SocketAddr>,
+) -> anyhow::Result<String> {
+    let address = match &cfg.registry.advertise_address {
+        Some(address) => address.clone(),
+        None => bound
+            .ok_or_else(|| anyhow::anyhow!("listener has no local address"))?
+            .to_string(),
+    };
+    if let Ok(addr) = address.parse::<std::net::SocketAddr>() {
+        if addr.ip().is_unspecified() {
+            anyhow::bail!(
+                "cannot advertise unspecified address {address}; set registry.advertise_address"
+            );
+        }
+    }
+    Ok(address)
+}
+
+/// Serve the gateway on `listener` until `shutdown` resolves or
+/// `state.shutdown` is cancelled.
+///
+/// Shutdown is graceful: the listener stops accepting, SSE streams end, and
+/// in-flight requests get `drain_timeout_secs` to complete before the
+/// remaining connections are abandoned.
+///
+/// When `registry.remote_url` is configured, this node registers with the
+/// remote registry, heartbeats while serving, and deregisters on shutdown.
+/// Registration is skipped with a warning if there is no reachable address
+/// to advertise (see [`advertise_address`]).
+///
+/// With `observation.peers` configured, this node's telemetry is published
+/// to them over uACP; with `observation.listen`, telemetry from other nodes
+/// is collected into `state.registry`. See [`crate::observation`].
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    mut state: AppState,
+    cfg: &NodeConfig,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    // `cfg` may differ from the config `state` was loaded from; only the
+    // node id is kept.
+    state.local_node = LocalNode::from_config(cfg, state.local_node.node_id.clone());
+    let advertised = advertise_address(cfg, listener.local_addr().ok());
+    if let Ok(address) = &advertised {
+        state.local_node.address.clone_from(address);
+    }
+    let heartbeat = cfg.registry.remote_url.as_ref().and_then(|url| {
+        if let Err(e) = &advertised {
+            tracing::warn!(error = %e, remote = %url, "not joining remote registry");
+            return None;
+        }
+        let local_node = state.local_node.clone();
+        let tools = state.mcp_tools;
+        tracing::info!(node_id = %local_node.node_id, remote = %url, "joining remote registry");
+        Some(node_registry::heartbeat_task_with(
+            Arc::new(RemoteRegistry::new(url.as_str())),
+            move || local_node.info(&tools()),
+            Duration::from_secs(cfg.registry.heartbeat_interval_secs),
+        ))
+    });
+
+    let observation = start_observation(cfg, &state.local_node.node_id, &state).await;
+
+    let scheduler = start_scheduler(&state);
+
+    let prober = (cfg.providers.probe_interval_secs > 0).then(|| {
+        HealthProber::spawn(
+            state.provider_health.clone(),
+            state.providers.clone(),
+            Duration::from_secs(cfg.providers.probe_interval_secs),
+        )
+    });
+
+    if !state.auth.is_enabled() {
+        tracing::warn!(
+            "no API keys configured (auth.keys); the gateway accepts unauthenticated requests"
+        );
+    }
+
+    let token = state.shutdown.clone();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = shutdown => trigger.cancel(),
+            _ = trigger.cancelled() => {}
+        }
+    });
+
+    let app = build_router_with_state(state)
+        .into_make_service_with_connect_info::<std::net::SocketAddr>();
+    let server = axum::serve(listener, app).with_graceful_shutdown(token.clone().cancelled_owned());
+    let drain = Duration::from_secs(cfg.drain_timeout_secs);
+    let result = tokio::select! {
+        result = server => result,
+        _ = async {
+            token.cancelled().await;
+            tokio::time::sleep(drain).await;
+        } => {
+            tracing::warn!(?drain, "drain timeout elapsed; abandoning open connections");
+            Ok(())
+        }
+    };
+
+    if let Some(handle) = heartbeat {
+        handle.shutdown().await;
+    }
+    for handle in observation {
+        handle.shutdown().await;
+    }
+    if let Some(prober) = prober {
+        prober.shutdown().await;
+    }
+    if let Some(scheduler) = scheduler {
+        scheduler.shutdown().await;
+    }
+    Ok(result?)
+}
+
+/// Start the observation publisher and collector configured in
+/// `cfg.observation`. Either one is skipped with a warning if its socket
+/// cannot be set up.
+async fn start_observation(
+    cfg: &NodeConfig,
+    node_id: &str,
+    state: &AppState,
+) -> Vec<ObservationHandle> {
+    let obs = &cfg.observation;
+    let mut handles = Vec::new();
+    if !obs.peers.is_empty() {
+        match ObservationPublisher::bind(node_id, &cfg.node_role, state.metrics.clone(), &obs.peers)
+            .await
+        {
+            Ok(publisher) => {
+                handles.push(publisher.spawn(Duration::from_secs(obs.interval_secs.max(1))))
+            }
+            Err(e) => tracing::warn!(error = %e, "not publishing observations"),
+        }
+    }
+    if let Some(listen) = &obs.listen {
+        match tokio::net::UdpSocket::bind(listen.as_str()).await {
+            Ok(socket) => {
+                tracing::info!(%listen, "collecting observations");
+                let collector =
+                    Arc::new(ObservationCollector::new().with_registry(state.registry.clone()));
+                handles.push(
+                    collector.spawn(socket, Duration::from_secs(obs.max_staleness_secs.max(1))),
+                );
+            }
+            Err(e) => tracing::warn!(error = %e, %listen, "not collecting observations"),
+        }
+    }
+    handles
 }
 
-pub async fn run(bind: &str) -> anyhow::Result<()> {
-    let app = build_router();
+/// Resolve on Ctrl-C (SIGINT) or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "cannot listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("shutdown signal received");
+}
 
+/// Run the gateway on `bind` until SIGINT/SIGTERM, or until `shutdown` is
+/// cancelled when an embedding application passes one.
+pub async fn run(bind: &str, shutdown: Option<CancellationToken>) -> anyhow::Result<()> {
+    let cfg = NodeConfig::load_or_default();
     let listener = tokio::net::TcpListener::bind(bind).await?;
     tracing::info!("ygn-core gateway listening on {bind}");
-    axum::serve(listener, app).await?;
-    Ok(())
+    let mut state = AppState::from_env();
+    if let Some(token) = shutdown {
+        state.shutdown = token;
+    }
+    serve(listener, state, &cfg, shutdown_signal()).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::{NodeRole, TrustTier};
+    use crate::usage;
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use http_body_util::BodyExt;
@@ -328,7 +1660,7 @@ mod tests {
 
     /// Helper to build the test router with all routes.
     fn test_router() -> Router {
-        build_router()
+        build_router_with_state(stub_state(Default::default()))
     }
 
     #[tokio::test]
@@ -451,9 +1783,106 @@ mod tests {
         }
     }
 
-    // -----------------------------------------------------------------------
-    // Phase 6: MCP over HTTP tests
-    // -----------------------------------------------------------------------
+    #[tokio::test]
+    async fn health_providers_reflects_probe_results() {
+        let state = stub_state(Default::default());
+        ProviderHealth::probe_all(&state.provider_health, &state.providers).await;
+        {
+            let mut health = state.provider_health.write().unwrap();
+            for _ in 0..5 {
+                health.record_failure("stub", "connection refused");
+            }
+        }
+        let response = build_router_with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/health/providers")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["status"], "degraded");
+        assert_eq!(json["providers"][0]["provider"], "stub");
+        assert_eq!(json["providers"][0]["healthy"], false);
+        assert_eq!(json["providers"][0]["total_requests"], 6);
+        assert_eq!(json["providers"][0]["last_success"]["source"], "probe");
+        assert_eq!(json["providers"][0]["last_failure"]["source"], "traffic");
+        assert!(json["providers"][0]["last_probe_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn self_returns_local_node_with_tool_capabilities() {
+        let response = build_router_with_state(stub_state(Default::default()))
+            .oneshot(Request::builder().uri("/self").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let info: NodeInfo = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info.node_id, "test-node");
+        let tools: Vec<String> = McpServer::default_registry()
+            .list()
+            .into_iter()
+            .map(|spec| spec.name)
+            .collect();
+        assert_eq!(info.capabilities, tools);
+    }
+
+    #[tokio::test]
+    async fn health_providers_reports_cache_counters() {
+        let cache = Arc::new(ResponseCache::in_memory(Default::default()).unwrap());
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(crate::provider::StubProvider::default()));
+        let providers = providers.with_cache(cache);
+
+        let request = ChatRequest {
+            model: "stub".to_string(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "Hi".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        };
+        let stub = providers.get("stub").unwrap();
+        stub.chat(request.clone()).await.unwrap();
+        assert!(stub.chat(request).await.unwrap().cached);
+
+        let state = AppState {
+            providers: Arc::new(providers),
+            ..stub_state(Default::default())
+        };
+        let response = build_router_with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/health/providers")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["providers"][0]["provider"], "stub");
+        assert_eq!(
+            json["providers"][0]["cache"],
+            json!({ "hits": 1, "misses": 1 })
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Phase 6: MCP over HTTP tests
+    // -----------------------------------------------------------------------
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn mcp_http_echo_tool() {
@@ -671,6 +2100,66 @@ mod tests {
         assert_eq!(task["status"], "completed");
     }
 
+    #[tokio::test]
+    async fn a2a_task_can_be_fetched_after_send() {
+        let app = stub_router();
+        let a2a = |body: Value| {
+            Request::builder()
+                .method("POST")
+                .uri("/a2a")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+
+        let sent = json_body(
+            app.clone()
+                .oneshot(a2a(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "SendMessage",
+                    "params": {"message": "Hello agent"}
+                })))
+                .await
+                .unwrap(),
+        )
+        .await;
+        let task_id = sent["result"]["task"]["id"].as_str().unwrap().to_string();
+
+        let fetched = json_body(
+            app.clone()
+                .oneshot(a2a(json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "GetTask",
+                    "params": {"task_id": task_id}
+                })))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(fetched["result"]["task"]["id"], task_id.as_str());
+        assert_eq!(fetched["result"]["task"]["status"], "completed");
+        assert!(fetched["result"]["task"]["result"]
+            .as_str()
+            .unwrap()
+            .contains("Hello agent"));
+
+        // Completed tasks cannot be canceled.
+        let cancel = json_body(
+            app.oneshot(a2a(json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "CancelTask",
+                "params": {"task_id": task_id}
+            })))
+            .await
+            .unwrap(),
+        )
+        .await;
+        assert_eq!(cancel["error"]["code"], -32002);
+    }
+
     // -----------------------------------------------------------------------
     // Registry API tests
     // -----------------------------------------------------------------------
@@ -721,6 +2210,201 @@ mod tests {
         assert!(json.get("accepted").is_some());
     }
 
+    #[tokio::test]
+    async fn registry_nodes_applies_query_filters() {
+        let state = stub_state(Default::default());
+        for (id, role, cap) in [
+            ("a", NodeRole::Core, "echo"),
+            ("b", NodeRole::Edge, "shell"),
+        ] {
+            state
+                .registry
+                .register(NodeInfo {
+                    node_id: id.to_string(),
+                    role,
+                    endpoints: vec![],
+                    trust_tier: TrustTier::Trusted,
+                    capabilities: vec![cap.to_string()],
+                    last_seen: chrono::Utc::now(),
+                    metadata: json!({}),
+                })
+                .await
+                .unwrap();
+        }
+        let app = build_router_with_state(state);
+
+        for (query, expected) in [
+            ("role=core", "a"),
+            ("capability=shell", "b"),
+            ("trust_tier=trusted&role=edge&max_staleness_seconds=60", "b"),
+            ("sort_by=node_id&limit=1", "a"),
+            ("limit=1&offset=1", "b"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/registry/nodes?{query}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let json: Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(json["count"], 1, "{query}");
+            assert_eq!(json["nodes"][0]["node_id"], expected, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn registry_register_heartbeat_and_deregister() {
+        let state = stub_state(Default::default());
+        let app = build_router_with_state(state.clone());
+        let node = json!({
+            "node_id": "n1",
+            "role": "edge",
+            "endpoints": [],
+            "trust_tier": "trusted",
+            "capabilities": ["echo"],
+            "last_seen": "2020-01-01T00:00:00Z",
+            "metadata": {}
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/registry/register")
+                    .header("content-type", "application/json")
+                    .body(Body::from(node.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let heartbeat = |id: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(format!("/registry/nodes/{id}/heartbeat"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(heartbeat("n1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let seen = state.registry.get("n1").await.unwrap().unwrap().last_seen;
+        assert!(chrono::Utc::now().signed_duration_since(seen).num_seconds() < 5);
+
+        let response = app.clone().oneshot(heartbeat("ghost")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/registry/nodes/n1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["removed"], true);
+    }
+
+    #[test]
+    fn advertise_address_rejects_unspecified_bind() {
+        let bound = |a: &str| Some(a.parse::<std::net::SocketAddr>().unwrap());
+        let mut cfg = NodeConfig::default();
+        assert_eq!(
+            advertise_address(&cfg, bound("127.0.0.1:3000")).unwrap(),
+            "127.0.0.1:3000"
+        );
+        let err = advertise_address(&cfg, bound("0.0.0.0:3000")).unwrap_err();
+        assert!(err.to_string().contains("advertise_address"), "{err}");
+
+        cfg.registry.advertise_address = Some("node-a.lan:3000".to_string());
+        assert_eq!(
+            advertise_address(&cfg, bound("0.0.0.0:3000")).unwrap(),
+            "node-a.lan:3000"
+        );
+        cfg.registry.advertise_address = Some("[::]:3000".to_string());
+        assert!(advertise_address(&cfg, bound("0.0.0.0:3000")).is_err());
+    }
+
+    #[tokio::test]
+    async fn node_heartbeats_into_remote_registry() {
+        // Node B: plain gateway whose registry node A joins.
+        let b_state = stub_state(Default::default());
+        let b_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let b_url = format!("http://{}", b_listener.local_addr().unwrap());
+        let b_app = build_router_with_state(b_state);
+        tokio::spawn(async move { axum::serve(b_listener, b_app).await });
+
+        // Node A: gateway configured with B as its remote registry.
+        let a_cfg = NodeConfig {
+            node_role: "core".to_string(),
+            registry: node_registry::RegistryConfig {
+                remote_url: Some(b_url.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let a_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (stop_a, stopped_a) = tokio::sync::oneshot::channel::<()>();
+        let a = tokio::spawn(async move {
+            serve(a_listener, stub_state(Default::default()), &a_cfg, async {
+                let _ = stopped_a.await;
+            })
+            .await
+        });
+
+        let client = reqwest::Client::new();
+        let mut nodes = Value::Null;
+        for _ in 0..50 {
+            let body: Value = client
+                .get(format!("{b_url}/registry/nodes"))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            if body["count"] == 1 {
+                nodes = body["nodes"].clone();
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let node = &nodes[0];
+        assert_eq!(node["role"], "core");
+        assert_eq!(node["capabilities"], json!(["echo", "hardware"]));
+        let last_seen: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(node["last_seen"].clone()).unwrap();
+        assert!(
+            chrono::Utc::now()
+                .signed_duration_since(last_seen)
+                .num_seconds()
+                < 5
+        );
+
+        // Shutting A down deregisters it from B.
+        stop_a.send(()).unwrap();
+        a.await.unwrap().unwrap();
+        let body: Value = client
+            .get(format!("{b_url}/registry/nodes"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["count"], 0);
+    }
+
     // -----------------------------------------------------------------------
     // Guard / Sessions / Memory tests
     // -----------------------------------------------------------------------
@@ -778,4 +2462,1011 @@ mod tests {
         let json: Value = serde_json::from_slice(&body).unwrap();
         assert!(json["total"].is_number());
     }
+
+    #[tokio::test]
+    async fn memory_list_paginates() {
+        let memory = Arc::new(SqliteMemory::in_memory().unwrap());
+        for i in 0..25 {
+            memory
+                .store(MemoryCategory::Daily, &format!("d{i:02}"), "entry")
+                .await
+                .unwrap();
+        }
+        memory
+            .store(MemoryCategory::Core, "core-fact", "entry")
+            .await
+            .unwrap();
+        let state = AppState {
+            memory,
+            ..stub_state(Default::default())
+        };
+        let response = build_router_with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/memory?category=daily&offset=20&limit=10&order=created_at")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["total"], 25);
+        assert_eq!(json["count"], 5);
+        assert_eq!(json["entries"][0]["key"], "d04");
+        assert_eq!(json["entries"][4]["key"], "d00");
+    }
+
+    #[tokio::test]
+    async fn schedules_lists_entries_with_their_next_run() {
+        use crate::scheduler::{Schedule, ScheduleEntry};
+        let store = Arc::new(ScheduleStore::in_memory().unwrap());
+        store
+            .add(&ScheduleEntry::new(
+                "health",
+                "health-check",
+                Schedule::Cron("*/5 * * * *".parse().unwrap()),
+            ))
+            .unwrap();
+        let mut paused = ScheduleEntry::new("paused", "health-check", Schedule::IntervalMs(100));
+        paused.enabled = false;
+        store.add(&paused).unwrap();
+        let state = AppState {
+            schedules: Some(store),
+            ..stub_state(Default::default())
+        };
+        let response = build_router_with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/schedules")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["count"], 2);
+        let health = &json["schedules"][0];
+        assert_eq!(health["name"], "health");
+        assert_eq!(health["schedule"]["cron"], "*/5 * * * *");
+        assert!(health["next_run"].is_string());
+        assert_eq!(json["schedules"][1]["schedule"]["interval_ms"], 100);
+        assert!(json["schedules"][1]["next_run"].is_null());
+
+        // Without a store the list is empty.
+        let response = stub_router()
+            .oneshot(
+                Request::builder()
+                    .uri("/schedules")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["count"], 0);
+    }
+
+    #[tokio::test]
+    async fn usage_returns_ok() {
+        let app = test_router();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/usage")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["totals"]["prompt_tokens"].is_number());
+        assert!(json["daily"].is_array());
+    }
+
+    fn stub_state(usage: usage::UsageConfig) -> AppState {
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(crate::provider::StubProvider::default()));
+        providers.register_model("stub", "stub");
+        // Unknown models fall back to an Ollama with nothing installed.
+        providers.register(Box::new(crate::multi_provider::OllamaProvider::new(
+            crate::multi_provider::OllamaConfig {
+                model: "llama3".to_string(),
+                base_url: Some("http://127.0.0.1:9".to_string()),
+            },
+        )));
+        AppState {
+            providers: Arc::new(providers),
+            registry: Arc::new(InMemoryRegistry::new()),
+            ws_channel: WebSocketChannel::new(),
+            usage: Arc::new(UsageTracker::in_memory(usage).unwrap()),
+            rate_limiter: Arc::new(GatewayRateLimiter::default()),
+            auth: Arc::new(ApiKeyAuth::default()),
+            metrics: Arc::new(Metrics::new()),
+            provider_health: Arc::new(RwLock::new(ProviderHealth::new())),
+            a2a_tasks: TaskStore::new(),
+            memory: Arc::new(SqliteMemory::in_memory().unwrap()),
+            mcp_proxy: None,
+            policy: PolicyConfig::default(),
+            mcp_tools: McpServer::default_registry,
+            local_node: LocalNode::from_config(&NodeConfig::default(), "test-node"),
+            tool_limiter: Arc::new(ToolLimiter::default()),
+            tool_output: Arc::new(ToolOutputLimits::default()),
+            audit_file: None,
+            schedules: None,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    #[test]
+    fn app_state_keeps_its_stores_in_the_data_dir() {
+        let dir = std::env::temp_dir().join(format!("ygn-app-state-{}", uuid::Uuid::new_v4()));
+        let state = AppState::new(NodeConfig::default(), &dir);
+        for name in [
+            "usage.db",
+            "a2a_tasks.db",
+            "memory.db",
+            "schedules.db",
+            "node_id",
+        ] {
+            assert!(dir.join(name).exists(), "{name}");
+        }
+        let node_id = std::fs::read_to_string(dir.join("node_id")).unwrap();
+        assert_eq!(node_id.trim(), state.local_node.node_id);
+        drop(state);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    fn stub_router() -> Router {
+        build_router_with_state(stub_state(Default::default()))
+    }
+
+    fn chat_completion_request(body: Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn chat_completions_returns_openai_shape() {
+        let response = stub_router()
+            .oneshot(chat_completion_request(json!({
+                "model": "stub",
+                "messages": [
+                    { "role": "system", "content": "Be brief." },
+                    { "role": "user", "content": "Hi" }
+                ],
+                "temperature": 0.2,
+                "max_tokens": 32
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["id"].as_str().unwrap().starts_with("chatcmpl-"));
+        assert_eq!(json["object"], "chat.completion");
+        assert!(json["created"].is_i64());
+        assert_eq!(json["model"], "stub");
+        assert_eq!(json["choices"][0]["message"]["role"], "assistant");
+        assert_eq!(
+            json["choices"][0]["message"]["content"],
+            "Hello from StubProvider"
+        );
+        assert_eq!(json["choices"][0]["finish_reason"], "stop");
+        assert_eq!(json["usage"]["total_tokens"], 0);
+    }
+
+    #[cfg(unix)]
+    fn shell_tools() -> ToolRegistry {
+        use crate::sandbox::{ProcessSandbox, SandboxProfile};
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(crate::shell::ShellTool::new(Box::new(
+            ProcessSandbox::new(SandboxProfile::Net),
+        ))));
+        registry
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn mcp_stream_sends_progress_then_response() {
+        let state = AppState {
+            mcp_tools: shell_tools,
+            // Shell calls need approval by default.
+            policy: PolicyConfig {
+                tool_overrides: std::collections::BTreeMap::from([(
+                    "shell".to_string(),
+                    crate::policy::ToolOverride {
+                        action: PolicyAction::Allow,
+                        risk_level: None,
+                    },
+                )]),
+                ..PolicyConfig::default()
+            },
+            ..stub_state(Default::default())
+        };
+        let response = build_router_with_state(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/mcp/stream")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": 9,
+                            "method": "tools/call",
+                            "params": {
+                                "name": "shell",
+                                "arguments": { "command": "printf", "args": ["a\\nb\\nc\\n"] },
+                                "_meta": { "progressToken": 42 }
+                            }
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        let messages: Vec<Value> = text
+            .split("\n\n")
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                let data = e
+                    .strip_prefix("event: message\ndata: ")
+                    .expect("message event");
+                serde_json::from_str(data).unwrap()
+            })
+            .collect();
+        assert_eq!(messages.len(), 4);
+        for (i, progress) in messages[..3].iter().enumerate() {
+            assert_eq!(progress["method"], "notifications/progress");
+            assert_eq!(progress["params"]["progressToken"], 42);
+            assert_eq!(progress["params"]["progress"], (i + 1) as f64);
+        }
+        assert_eq!(messages[2]["params"]["message"], "c");
+        assert_eq!(messages[3]["id"], 9);
+        assert!(messages[3]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("a\\nb\\nc"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn mcp_stream_sends_log_messages_at_requested_level() {
+        let response = stub_router()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/mcp/stream?log_level=warning")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": 4,
+                            "method": "tools/call",
+                            "params": { "name": "echo", "arguments": { "input": 42 } }
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        let messages: Vec<Value> = text
+            .split("\n\n")
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                serde_json::from_str(e.strip_prefix("event: message\ndata: ").unwrap()).unwrap()
+            })
+            .collect();
+
+        // The info-level "calling" message is filtered out.
+        assert_eq!(messages.len(), 2, "{messages:?}");
+        assert_eq!(messages[0]["method"], "notifications/message");
+        assert_eq!(messages[0]["params"]["level"], "warning");
+        assert_eq!(messages[0]["params"]["logger"], "tools");
+        assert_eq!(messages[1]["id"], 4);
+    }
+
+    #[tokio::test]
+    async fn chat_completions_streams_sse_chunks() {
+        let response = stub_router()
+            .oneshot(chat_completion_request(json!({
+                "model": "stub",
+                "messages": [{ "role": "user", "content": "Hi" }],
+                "stream": true
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        let events: Vec<&str> = text
+            .split("\n\n")
+            .filter(|e| !e.is_empty())
+            .map(|e| e.strip_prefix("data: ").expect("data frame"))
+            .collect();
+        assert_eq!(*events.last().unwrap(), "[DONE]");
+
+        let chunks: Vec<Value> = events[..events.len() - 1]
+            .iter()
+            .map(|e| serde_json::from_str(e).unwrap())
+            .collect();
+        assert!(chunks
+            .iter()
+            .all(|c| c["object"] == "chat.completion.chunk"));
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        let last = chunks.last().unwrap();
+        assert_eq!(last["choices"][0]["finish_reason"], "stop");
+
+        let content: String = chunks
+            .iter()
+            .filter_map(|c| c["choices"][0]["delta"]["content"].as_str())
+            .collect();
+        assert_eq!(content, "Hello from StubProvider");
+    }
+
+    #[tokio::test]
+    async fn chat_completions_records_usage_and_enforces_budget() {
+        let mut pricing = std::collections::HashMap::new();
+        pricing.insert(
+            "stub".to_string(),
+            usage::ModelPricing {
+                prompt_per_1k: 1.0,
+                completion_per_1k: 1.0,
+                ..Default::default()
+            },
+        );
+        let state = stub_state(usage::UsageConfig {
+            pricing,
+            daily_cost_limit: Some(1.0),
+        });
+        let app = build_router_with_state(state.clone());
+        let body = json!({
+            "model": "stub",
+            "messages": [{ "role": "user", "content": "Hi" }]
+        });
+
+        // Under budget: the call goes through and is recorded.
+        let response = app
+            .clone()
+            .oneshot(chat_completion_request(body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/usage")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let summary: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(summary["totals"]["requests"], 1);
+        assert_eq!(summary["by_provider"]["stub"]["requests"], 1);
+        assert_eq!(summary["daily_cost_limit"], 1.0);
+
+        // Spend past the limit; the next call is refused before the provider.
+        state
+            .usage
+            .record(
+                "stub",
+                "stub",
+                None,
+                &crate::provider::TokenUsage {
+                    prompt_tokens: 1500,
+                    completion_tokens: 0,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let response = app.oneshot(chat_completion_request(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["error"]["code"], "insufficient_quota");
+        assert!(json["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("budget exceeded"));
+        assert_eq!(state.usage.totals().unwrap().requests, 2);
+    }
+
+    #[tokio::test]
+    async fn chat_completions_unknown_model_is_404() {
+        let response = stub_router()
+            .oneshot(chat_completion_request(json!({
+                "model": "gpt-4o",
+                "messages": [{ "role": "user", "content": "Hi" }]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+        assert_eq!(json["error"]["code"], "model_not_found");
+        assert!(json["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("gpt-4o"));
+    }
+
+    #[test]
+    fn openai_request_converts_image_parts() {
+        let req = openai_to_chat_request(&json!({
+            "model": "gpt-4o",
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "What is this?" },
+                    { "type": "image_url", "image_url": { "url": "data:image/png;base64,AAAA" } },
+                    { "type": "image_url", "image_url": { "url": "https://example.com/cat.jpg" } }
+                ]
+            }]
+        }))
+        .unwrap();
+        assert_eq!(
+            req.messages[0].content.parts(),
+            vec![
+                ContentPart::Text {
+                    text: "What is this?".to_string()
+                },
+                ContentPart::Image {
+                    data: "AAAA".to_string(),
+                    mime_type: "image/png".to_string()
+                },
+                ContentPart::ImageUrl {
+                    url: "https://example.com/cat.jpg".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn openai_request_reads_sampling_params() {
+        let req = openai_to_chat_request(&json!({
+            "model": "gpt-4o",
+            "messages": [{ "role": "user", "content": "Hi" }],
+            "stop": "END",
+            "top_p": 0.5,
+            "seed": 7
+        }))
+        .unwrap();
+        assert_eq!(req.stop, Some(vec!["END".to_string()]));
+        assert_eq!(req.top_p, Some(0.5));
+        assert_eq!(req.seed, Some(7));
+
+        let req = openai_to_chat_request(&json!({
+            "model": "gpt-4o",
+            "messages": [{ "role": "user", "content": "Hi" }],
+            "stop": ["a", "b"]
+        }))
+        .unwrap();
+        assert_eq!(req.stop, Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(req.seed, None);
+    }
+
+    // -- rate limiting --------------------------------------------------------
+
+    fn limited_router(limit: crate::rate_limiter::RouteLimit) -> Router {
+        let config = crate::rate_limiter::GatewayLimitConfig {
+            default: Some(limit),
+            ..Default::default()
+        };
+        let state = AppState {
+            rate_limiter: Arc::new(GatewayRateLimiter::new(config)),
+            ..stub_state(Default::default())
+        };
+        build_router_with_state(state)
+    }
+
+    fn from_client(uri: &str, ip: [u8; 4]) -> Request<Body> {
+        let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(std::net::SocketAddr::from((ip, 4000))));
+        request
+    }
+
+    #[tokio::test]
+    async fn rate_limit_rejects_then_recovers() {
+        // Ten requests per second, no burst beyond one.
+        let app = limited_router(crate::rate_limiter::RouteLimit {
+            requests_per_minute: 600,
+            burst: 1,
+        });
+        let alice = [10, 0, 0, 1];
+
+        let response = app
+            .clone()
+            .oneshot(from_client("/usage", alice))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(from_client("/usage", alice))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["code"], "rate_limit_exceeded");
+
+        // Other clients and health checks are unaffected.
+        let response = app
+            .clone()
+            .oneshot(from_client("/usage", [10, 0, 0, 2]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(from_client("/health", alice))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let response = app.oneshot(from_client("/usage", alice)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_keys_by_api_key_when_enabled() {
+        let config = crate::rate_limiter::GatewayLimitConfig {
+            default: Some(crate::rate_limiter::RouteLimit {
+                requests_per_minute: 1,
+                burst: 1,
+            }),
+            key_by_api_key: true,
+            ..Default::default()
+        };
+        let limited = |auth: Arc<ApiKeyAuth>| {
+            build_router_with_state(AppState {
+                rate_limiter: Arc::new(GatewayRateLimiter::new(config.clone())),
+                auth,
+                ..stub_state(Default::default())
+            })
+        };
+        let with_key = |key: &str| {
+            let mut request = from_client("/usage", [10, 0, 0, 1]);
+            request.headers_mut().insert(
+                header::AUTHORIZATION,
+                format!("Bearer {key}").parse().unwrap(),
+            );
+            request
+        };
+
+        let app = limited(authed_state().auth);
+        let response = app.clone().oneshot(with_key("ops-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(with_key("ops-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // Same IP, different key: a separate bucket.
+        let response = app
+            .clone()
+            .oneshot(echo_call(Some("guest-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // Unknown keys are rejected, and count against the client's IP.
+        let response = app.clone().oneshot(with_key("made-up")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.oneshot(with_key("made-up-too")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Without auth, made-up tokens share the client's IP bucket.
+        let app = limited(Arc::new(ApiKeyAuth::default()));
+        let response = app.clone().oneshot(with_key("one")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.oneshot(with_key("two")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    // -- authentication -------------------------------------------------------
+
+    fn authed_router() -> Router {
+        build_router_with_state(authed_state())
+    }
+
+    fn authed_state() -> AppState {
+        use crate::auth::{ApiKeyConfig, AuthConfig};
+        let config = AuthConfig {
+            keys: vec![
+                ApiKeyConfig {
+                    name: "ops".into(),
+                    key: "ops-key".into(),
+                    trust_tier: TrustTier::Trusted,
+                    routes: vec![],
+                },
+                ApiKeyConfig {
+                    name: "guest".into(),
+                    key: "guest-key".into(),
+                    trust_tier: TrustTier::Untrusted,
+                    routes: vec!["/mcp".into()],
+                },
+            ],
+            ..Default::default()
+        };
+        AppState {
+            auth: Arc::new(ApiKeyAuth::new(config)),
+            ..stub_state(Default::default())
+        }
+    }
+
+    fn echo_call(key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .header("content-type", "application/json");
+        if let Some(key) = key {
+            builder = builder.header("authorization", format!("Bearer {key}"));
+        }
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {"name": "echo", "arguments": {"input": "hi"}}
+        });
+        builder.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn auth_rejects_missing_and_unknown_keys() {
+        let app = authed_router();
+        for key in [None, Some("wrong")] {
+            let response = app.clone().oneshot(echo_call(key)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+            assert_eq!(
+                json_body(response).await["error"]["code"],
+                "invalid_api_key"
+            );
+        }
+        // Health stays reachable for probes.
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn memory_list_requires_key() {
+        let request = |key: Option<&str>| {
+            let mut builder = Request::builder().uri("/memory");
+            if let Some(key) = key {
+                builder = builder.header("authorization", format!("Bearer {key}"));
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        let app = authed_router();
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.oneshot(request(Some("ops-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["total"], 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn auth_valid_key_passes() {
+        let response = authed_router()
+            .oneshot(echo_call(Some("ops-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = json_body(response).await;
+        assert_eq!(json["result"]["content"][0]["text"], "hi");
+    }
+
+    #[tokio::test]
+    async fn auth_policy_depends_on_trust_tier() {
+        let app = authed_router();
+        let response = app
+            .clone()
+            .oneshot(echo_call(Some("guest-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = json_body(response).await;
+        // Untrusted callers need approval for every tool.
+        assert_eq!(json["error"]["code"], -32002);
+
+        // And may only reach their allowed routes.
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/usage")
+                    .header("authorization", "Bearer guest-key")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    // -- request ids ----------------------------------------------------------
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn request_id_reaches_audit_entries_and_response() {
+        let path = std::env::temp_dir().join(format!("ygn-audit-{}.jsonl", uuid::Uuid::new_v4()));
+        let app = build_router_with_state(AppState {
+            audit_file: Some(path.clone()),
+            ..authed_state()
+        });
+        let mut request = echo_call(Some("ops-key"));
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, HeaderValue::from_static("test-req-123"));
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "test-req-123");
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let entries: Vec<crate::audit::AuditEntry> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert!(!entries.is_empty());
+        assert!(entries
+            .iter()
+            .all(|e| e.request_id.as_deref() == Some("test-req-123")));
+    }
+
+    #[tokio::test]
+    async fn request_id_is_generated_when_absent() {
+        let app = test_router();
+        let health = || {
+            Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let first = app.clone().oneshot(health()).await.unwrap();
+        let second = app.oneshot(health()).await.unwrap();
+        let first = first.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        let second = second.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(first).is_ok());
+        assert_ne!(first, second);
+    }
+
+    // -- metrics --------------------------------------------------------------
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn mcp_http_applies_the_configured_policy() {
+        let policy = PolicyConfig {
+            denied_tools: vec!["echo".to_string()],
+            ..PolicyConfig::default()
+        };
+        let open = build_router_with_state(AppState {
+            policy: policy.clone(),
+            ..stub_state(Default::default())
+        });
+        let authed = build_router_with_state(AppState {
+            policy,
+            ..authed_state()
+        });
+        for (app, key) in [(open, None), (authed, Some("ops-key"))] {
+            let response = app.oneshot(echo_call(key)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(json_body(response).await["error"]["code"], -32001);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn metrics_count_mcp_tool_executions() {
+        let app = build_router_with_state(stub_state(Default::default()));
+        for _ in 0..3 {
+            let response = app.clone().oneshot(echo_call(None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(
+            text.contains("ygn_tool_executions_total{tool=\"echo\",success=\"true\"} 3"),
+            "{text}"
+        );
+        assert!(text.contains("ygn_mcp_requests_total{method=\"tools/call\"} 3"));
+        assert!(text
+            .contains("ygn_http_requests_total{route=\"/mcp\",method=\"POST\",status=\"200\"} 3"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn metrics_report_limited_tool_load() {
+        let limiter = Arc::new(ToolLimiter::new(crate::tool_limits::ToolLimitsConfig {
+            limits: [("echo".to_string(), 2)].into(),
+            ..Default::default()
+        }));
+        let app = build_router_with_state(AppState {
+            tool_limiter: limiter.clone(),
+            ..stub_state(Default::default())
+        });
+        let response = app.clone().oneshot(echo_call(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let _held = limiter.acquire("echo").await.unwrap();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(
+            text.contains("ygn_tool_concurrency_limit{tool=\"echo\"} 2\n"),
+            "{text}"
+        );
+        assert!(text.contains("ygn_tool_in_flight{tool=\"echo\"} 1\n"));
+        assert!(text.contains("ygn_tool_queued{tool=\"echo\"} 0\n"));
+    }
+
+    // -- graceful shutdown ------------------------------------------------------
+
+    /// Answers after `delay`.
+    struct SlowProvider {
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::provider::Provider for SlowProvider {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn capabilities(&self) -> crate::provider::ProviderCapabilities {
+            crate::provider::StubProvider::default().capabilities()
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+            tokio::time::sleep(self.delay).await;
+            Ok(crate::provider::ChatResponse {
+                content: "finally".into(),
+                tool_calls: vec![],
+                usage: None,
+                cached: false,
+                model: None,
+                finish_reason: None,
+                alternates: Vec::new(),
+            })
+        }
+
+        async fn chat_with_tools(
+            &self,
+            request: ChatRequest,
+            _tools: &[crate::tool::ToolSpec],
+        ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+            self.chat(request).await
+        }
+    }
+
+    /// Serve a gateway whose `slow` model answers after `delay`.
+    async fn spawn_slow_gateway(
+        delay: Duration,
+        drain_timeout_secs: u64,
+    ) -> (
+        String,
+        CancellationToken,
+        tokio::task::JoinHandle<anyhow::Result<()>>,
+    ) {
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(SlowProvider { delay }));
+        providers.register_model("slow", "slow");
+        let state = AppState {
+            providers: Arc::new(providers),
+            ..stub_state(Default::default())
+        };
+        let token = state.shutdown.clone();
+        let cfg = NodeConfig {
+            drain_timeout_secs,
+            ..Default::default()
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server =
+            tokio::spawn(async move { serve(listener, state, &cfg, std::future::pending()).await });
+        (url, token, server)
+    }
+
+    fn slow_completion(url: &str) -> tokio::task::JoinHandle<reqwest::Result<reqwest::Response>> {
+        let request = reqwest::Client::new()
+            .post(format!("{url}/v1/chat/completions"))
+            .json(&json!({ "model": "slow", "messages": [{ "role": "user", "content": "hi" }] }));
+        tokio::spawn(request.send())
+    }
+
+    #[tokio::test]
+    async fn shutdown_drains_in_flight_request() {
+        let (url, token, server) = spawn_slow_gateway(Duration::from_millis(400), 30).await;
+        let in_flight = slow_completion(&url);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        token.cancel();
+        let response = in_flight.await.unwrap().unwrap();
+        assert_eq!(response.status(), 200);
+        let json: Value = response.json().await.unwrap();
+        assert_eq!(json["choices"][0]["message"]["content"], "finally");
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not stop")
+            .unwrap()
+            .unwrap();
+        // The listener is closed.
+        assert!(reqwest::get(format!("{url}/health")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown_abandons_requests_after_drain_timeout() {
+        let (url, token, server) = spawn_slow_gateway(Duration::from_secs(30), 0).await;
+        let _in_flight = slow_completion(&url);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server waited for the request")
+            .unwrap()
+            .unwrap();
+    }
 }
@@ -0,0 +1,389 @@
+//! Capability-based routing of tool calls across the grid.
+//!
+//! A [`GridExecutor`] asks a [`NodeRegistry`] for trusted, recently seen
+//! nodes that advertise a tool, picks one and calls the tool through the
+//! node's `POST /mcp` endpoint.  Nodes that cannot be reached are skipped
+//! in favour of the next candidate.  [`RemoteExecuteTool`] exposes the
+//! executor as the `remote_execute` tool, so skills can fan work out
+//! across the grid.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::mcp_client::translate_call_result;
+use crate::mcp_proxy::{McpProxy, ProxyError};
+use crate::registry::{
+    DiscoveryFilter, NodeInfo, NodeRegistry, SortBy, TrustTier, DEFAULT_MAX_STALENESS_SECS,
+};
+use crate::tool::{Tool, ToolContent, ToolResult};
+
+/// Default per-call timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Weight of the newest sample in the latency average.
+const LATENCY_WEIGHT: f64 = 0.3;
+
+/// How a [`GridExecutor`] orders candidate nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Rotate through the candidates, ordered by node ID.
+    #[default]
+    RoundRobin,
+    /// Prefer the node with the lowest average latency.  Nodes without
+    /// recorded calls go first so that they get measured.
+    LowestLatency,
+}
+
+/// Outcome of a call made by [`GridExecutor::execute`].
+#[derive(Debug, Clone)]
+pub struct RemoteExecution {
+    /// Node that executed the call.
+    pub node_id: String,
+    pub result: ToolResult,
+}
+
+/// Routes tool calls to nodes that advertise the tool.
+pub struct GridExecutor {
+    registry: Arc<dyn NodeRegistry>,
+    strategy: SelectionStrategy,
+    max_staleness_secs: u64,
+    timeout: Duration,
+    api_key: Option<String>,
+    next: AtomicUsize,
+    /// Average call latency per node ID.
+    latencies: Mutex<HashMap<String, Duration>>,
+    /// Clients by endpoint, so connections are reused across calls.
+    proxies: Mutex<HashMap<String, Arc<McpProxy>>>,
+}
+
+impl std::fmt::Debug for GridExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GridExecutor")
+            .field("strategy", &self.strategy)
+            .field("max_staleness_secs", &self.max_staleness_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GridExecutor {
+    pub fn new(registry: Arc<dyn NodeRegistry>) -> Self {
+        Self {
+            registry,
+            strategy: SelectionStrategy::default(),
+            max_staleness_secs: DEFAULT_MAX_STALENESS_SECS,
+            timeout: DEFAULT_TIMEOUT,
+            api_key: None,
+            next: AtomicUsize::new(0),
+            latencies: Mutex::new(HashMap::new()),
+            proxies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set how candidates are ordered.
+    pub fn with_strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Ignore nodes not seen within `secs` seconds.
+    pub fn with_max_staleness(mut self, secs: u64) -> Self {
+        self.max_staleness_secs = secs;
+        self
+    }
+
+    /// Set the per-call timeout.  A node that times out counts as
+    /// unreachable.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send `key` as a bearer token to every node.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Average latency of calls to `node_id`, if any were made.
+    pub fn latency(&self, node_id: &str) -> Option<Duration> {
+        self.latencies.lock().unwrap().get(node_id).copied()
+    }
+
+    /// Trusted, fresh nodes advertising `tool`, in the order they would be
+    /// tried.
+    pub async fn candidates(&self, tool: &str) -> anyhow::Result<Vec<NodeInfo>> {
+        let mut nodes = self
+            .registry
+            .discover(DiscoveryFilter {
+                trust_tier: Some(TrustTier::Trusted),
+                capability: Some(tool.to_string()),
+                max_staleness_seconds: Some(self.max_staleness_secs),
+                sort_by: Some(SortBy::NodeId),
+                ..Default::default()
+            })
+            .await?;
+        nodes.retain(|node| mcp_url(node).is_some());
+        if nodes.is_empty() {
+            return Ok(nodes);
+        }
+        match self.strategy {
+            SelectionStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed) % nodes.len();
+                nodes.rotate_left(start);
+            }
+            SelectionStrategy::LowestLatency => {
+                let latencies = self.latencies.lock().unwrap();
+                nodes.sort_by_key(|node| {
+                    latencies
+                        .get(&node.node_id)
+                        .copied()
+                        .unwrap_or(Duration::ZERO)
+                });
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Call `tool` on a node that advertises it, trying the next candidate
+    /// when a node is unreachable.  Errors if no candidate exists, if all
+    /// are unreachable, or if the chosen node rejects the call.
+    pub async fn execute(&self, tool: &str, arguments: Value) -> anyhow::Result<RemoteExecution> {
+        let candidates = self.candidates(tool).await?;
+        if candidates.is_empty() {
+            anyhow::bail!("no trusted node advertises tool '{tool}'");
+        }
+        let mut last_error = None;
+        for node in &candidates {
+            let proxy = match self.proxy(node) {
+                Ok(proxy) => proxy,
+                Err(e) => {
+                    tracing::warn!(node_id = %node.node_id, error = %e, "skipping node");
+                    last_error = Some(e.to_string());
+                    continue;
+                }
+            };
+            let started = Instant::now();
+            match proxy.call_tool(tool, arguments.clone()).await {
+                Ok(result) => {
+                    self.record_latency(&node.node_id, started.elapsed());
+                    return Ok(RemoteExecution {
+                        node_id: node.node_id.clone(),
+                        result: translate_call_result(&result),
+                    });
+                }
+                Err(e @ ProxyError::Unreachable { .. }) => {
+                    tracing::warn!(node_id = %node.node_id, tool, error = %e, "trying next node");
+                    self.record_latency(&node.node_id, self.timeout);
+                    last_error = Some(e.to_string());
+                }
+                Err(e) => anyhow::bail!("node '{}' rejected the call: {e}", node.node_id),
+            }
+        }
+        anyhow::bail!(
+            "all {} nodes advertising tool '{tool}' are unreachable; last error: {}",
+            candidates.len(),
+            last_error.expect("at least one candidate was tried")
+        )
+    }
+
+    fn proxy(&self, node: &NodeInfo) -> anyhow::Result<Arc<McpProxy>> {
+        let url = mcp_url(node).expect("candidates have an MCP endpoint");
+        let mut proxies = self.proxies.lock().unwrap();
+        if let Some(proxy) = proxies.get(&url) {
+            return Ok(proxy.clone());
+        }
+        let mut proxy = McpProxy::new(&url)?
+            .with_node_id(node.node_id.clone())
+            .with_timeout(self.timeout);
+        if let Some(key) = &self.api_key {
+            proxy = proxy.with_api_key(key.clone());
+        }
+        let proxy = Arc::new(proxy);
+        proxies.insert(url, proxy.clone());
+        Ok(proxy)
+    }
+
+    fn record_latency(&self, node_id: &str, sample: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        latencies
+            .entry(node_id.to_string())
+            .and_modify(|avg| {
+                *avg = avg.mul_f64(1.0 - LATENCY_WEIGHT) + sample.mul_f64(LATENCY_WEIGHT)
+            })
+            .or_insert(sample);
+    }
+}
+
+/// URL of the node's MCP endpoint: its `mcp` endpoint if it has one, else
+/// `/mcp` on its `http` endpoint.
+fn mcp_url(node: &NodeInfo) -> Option<String> {
+    let endpoint = ["mcp", "http"]
+        .iter()
+        .find_map(|protocol| node.endpoints.iter().find(|e| e.protocol == *protocol))?;
+    Some(if endpoint.address.contains("://") {
+        endpoint.address.clone()
+    } else {
+        format!("http://{}", endpoint.address)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// RemoteExecuteTool
+// ---------------------------------------------------------------------------
+
+/// The `remote_execute` tool: run another tool on whichever grid node
+/// advertises it.  The output is a JSON object naming the node that ran
+/// the call next to the tool's own output.
+#[derive(Debug, Clone)]
+pub struct RemoteExecuteTool {
+    executor: Arc<GridExecutor>,
+}
+
+impl RemoteExecuteTool {
+    pub fn new(executor: Arc<GridExecutor>) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl Tool for RemoteExecuteTool {
+    fn name(&self) -> &str {
+        "remote_execute"
+    }
+
+    fn description(&self) -> &str {
+        "Runs a tool on a trusted grid node that advertises it"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tool": {
+                    "type": "string",
+                    "description": "Name of the tool to run"
+                },
+                "arguments": {
+                    "type": "object",
+                    "description": "Arguments for the tool"
+                }
+            },
+            "required": ["tool"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let tool = args
+            .get("tool")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| anyhow::anyhow!("missing 'tool'"))?;
+        let arguments = args.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let execution = self.executor.execute(tool, arguments).await?;
+        Ok(ToolResult {
+            success: execution.result.success,
+            content: ToolContent::Json(json!({
+                "node_id": execution.node_id,
+                "output": execution.result.output(),
+            })),
+            error: execution.result.error,
+            truncated: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{Endpoint, InMemoryRegistry, NodeRole};
+
+    fn node(id: &str, trust_tier: TrustTier, endpoints: Vec<Endpoint>) -> NodeInfo {
+        NodeInfo {
+            node_id: id.to_string(),
+            role: NodeRole::Core,
+            endpoints,
+            trust_tier,
+            capabilities: vec!["echo@1.0.0".to_string()],
+            last_seen: chrono::Utc::now(),
+            metadata: json!({}),
+        }
+    }
+
+    fn http(address: &str) -> Vec<Endpoint> {
+        vec![Endpoint {
+            protocol: "http".into(),
+            address: address.into(),
+        }]
+    }
+
+    #[test]
+    fn mcp_url_prefers_mcp_endpoint() {
+        let mut n = node("a", TrustTier::Trusted, http("10.0.0.1:3000"));
+        assert_eq!(mcp_url(&n).unwrap(), "http://10.0.0.1:3000");
+        n.endpoints.push(Endpoint {
+            protocol: "mcp".into(),
+            address: "https://a.example.com/mcp".into(),
+        });
+        assert_eq!(mcp_url(&n).unwrap(), "https://a.example.com/mcp");
+        n.endpoints.clear();
+        assert!(mcp_url(&n).is_none());
+    }
+
+    #[tokio::test]
+    async fn candidates_are_trusted_reachable_and_rotated() {
+        let registry = Arc::new(InMemoryRegistry::new());
+        for n in [
+            node("b", TrustTier::Trusted, http("10.0.0.2:3000")),
+            node("a", TrustTier::Trusted, http("10.0.0.1:3000")),
+            node("u", TrustTier::Untrusted, http("10.0.0.3:3000")),
+            node("x", TrustTier::Trusted, Vec::new()),
+        ] {
+            registry.register(n).await.unwrap();
+        }
+        let executor = GridExecutor::new(registry);
+        let ids = |nodes: Vec<NodeInfo>| nodes.into_iter().map(|n| n.node_id).collect::<Vec<_>>();
+
+        assert_eq!(ids(executor.candidates("echo").await.unwrap()), ["a", "b"]);
+        assert_eq!(ids(executor.candidates("echo").await.unwrap()), ["b", "a"]);
+        assert!(executor.candidates("shell").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn lowest_latency_orders_by_recorded_stats() {
+        let registry = Arc::new(InMemoryRegistry::new());
+        registry
+            .register(node("a", TrustTier::Trusted, http("10.0.0.1:3000")))
+            .await
+            .unwrap();
+        registry
+            .register(node("b", TrustTier::Trusted, http("10.0.0.2:3000")))
+            .await
+            .unwrap();
+        let executor = GridExecutor::new(registry).with_strategy(SelectionStrategy::LowestLatency);
+        executor.record_latency("a", Duration::from_millis(50));
+        executor.record_latency("b", Duration::from_millis(10));
+
+        let first = executor.candidates("echo").await.unwrap();
+        assert_eq!(first[0].node_id, "b");
+
+        executor.record_latency("b", Duration::from_millis(200));
+        assert!(executor.latency("b") > executor.latency("a"));
+        let second = executor.candidates("echo").await.unwrap();
+        assert_eq!(second[0].node_id, "a");
+    }
+
+    #[tokio::test]
+    async fn no_candidate_is_an_error() {
+        let executor = GridExecutor::new(Arc::new(InMemoryRegistry::new()));
+        let err = executor.execute("echo", json!({})).await.unwrap_err();
+        assert_eq!(err.to_string(), "no trusted node advertises tool 'echo'");
+    }
+}
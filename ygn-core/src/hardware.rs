@@ -3,14 +3,17 @@
 //! Provides the [`Hardware`] trait for interacting with physical actuators and
 //! sensors, a [`SimulatedHardware`] implementation that tracks position/heading
 //! in-memory, and a [`HardwareTool`] wrapper that exposes hardware actions as
-//! an MCP-compatible [`Tool`].
+//! an MCP-compatible [`Tool`].  An optional [`WorldModel`] adds obstacles that
+//! stop driving and that the distance sensor measures.
 
 use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Mutex;
 
-use crate::tool::{Tool, ToolResult};
+use crate::tool::{Tool, ToolContent, ToolResult};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -28,7 +31,7 @@ pub enum Direction {
 }
 
 /// Type of sensor to read.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum SensorType {
     Temperature,
@@ -41,10 +44,24 @@ pub enum SensorType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum HardwareAction {
-    Drive { direction: Direction, speed: f64 },
-    Sense { sensor_type: SensorType },
-    Look { camera_id: String },
-    Speak { text: String },
+    Drive {
+        direction: Direction,
+        speed: f64,
+    },
+    Sense {
+        sensor_type: SensorType,
+    },
+    Look {
+        camera_id: String,
+    },
+    Speak {
+        text: String,
+    },
+    /// Return to the origin, facing heading 0, stopped, with sensor
+    /// readings starting over.
+    Reset,
+    /// Report the current [`SimState`] without changing it.
+    GetState,
 }
 
 /// Result of executing a hardware action.
@@ -69,6 +86,123 @@ pub struct SimState {
     pub speed: f64,
 }
 
+/// Hardware settings in the node config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct HardwareConfig {
+    /// Serve the simulated `hardware` tool over MCP.
+    pub simulated: bool,
+}
+
+impl Default for HardwareConfig {
+    fn default() -> Self {
+        Self { simulated: true }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WorldModel
+// ---------------------------------------------------------------------------
+
+/// An axis-aligned rectangular obstacle.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Obstacle {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Obstacle {
+    /// Distance along the unit ray from `(x, y)` towards `(dx, dy)` at which
+    /// it enters this obstacle, or `None` if it never does.  A ray starting
+    /// inside hits at 0 unless it is leaving through the boundary it is on.
+    fn raycast(&self, x: f64, y: f64, dx: f64, dy: f64) -> Option<f64> {
+        let mut enter = f64::NEG_INFINITY;
+        let mut exit = f64::INFINITY;
+        for (origin, dir, min, max) in [
+            (x, dx, self.min_x, self.max_x),
+            (y, dy, self.min_y, self.max_y),
+        ] {
+            if dir.abs() < 1e-12 {
+                if origin < min || origin > max {
+                    return None;
+                }
+            } else {
+                let (t1, t2) = ((min - origin) / dir, (max - origin) / dir);
+                enter = enter.max(t1.min(t2));
+                exit = exit.min(t1.max(t2));
+            }
+        }
+        (exit >= enter && exit > 1e-9).then(|| enter.max(0.0))
+    }
+}
+
+/// Default range of the simulated distance sensor, in cm.
+pub const DEFAULT_SENSOR_RANGE: f64 = 1000.0;
+
+/// Obstacles around the simulated robot.  Positions share the distance
+/// sensor's unit (cm).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldModel {
+    pub obstacles: Vec<Obstacle>,
+    /// Longest distance the distance sensor reports.
+    pub sensor_range: f64,
+}
+
+impl Default for WorldModel {
+    fn default() -> Self {
+        Self {
+            obstacles: Vec::new(),
+            sensor_range: DEFAULT_SENSOR_RANGE,
+        }
+    }
+}
+
+impl WorldModel {
+    /// Create an empty world with the default sensor range.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an obstacle spanning `(min_x, min_y)` to `(max_x, max_y)`.
+    pub fn with_obstacle(mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        self.obstacles.push(Obstacle {
+            min_x: min_x.min(max_x),
+            min_y: min_y.min(max_y),
+            max_x: min_x.max(max_x),
+            max_y: min_y.max(max_y),
+        });
+        self
+    }
+
+    /// Set the distance sensor's range.
+    pub fn with_sensor_range(mut self, range: f64) -> Self {
+        self.sensor_range = range;
+        self
+    }
+
+    /// Load a world from a JSON file.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read world file {}: {e}", path.display()))?;
+        serde_json::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("invalid world file {}: {e}", path.display()))
+    }
+
+    /// Distance from `(x, y)` along `heading` (degrees) to the nearest
+    /// obstacle, capped at `max`.
+    pub fn raycast(&self, x: f64, y: f64, heading: f64, max: f64) -> f64 {
+        let rad = heading.to_radians();
+        let (dx, dy) = (rad.cos(), rad.sin());
+        self.obstacles
+            .iter()
+            .filter_map(|o| o.raycast(x, y, dx, dy))
+            .fold(max, f64::min)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Trait
 // ---------------------------------------------------------------------------
@@ -84,6 +218,17 @@ pub trait Hardware: Send + Sync {
 
     /// Human-readable name for this hardware backend.
     fn name(&self) -> &str;
+
+    /// Return to the starting pose: origin, heading 0, stopped.  Backends
+    /// that cannot re-localize keep the default, which fails.
+    async fn reset(&self) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support reset", self.name())
+    }
+
+    /// Current pose and speed, for backends that track them.
+    fn get_state(&self) -> Option<SimState> {
+        None
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -97,14 +242,62 @@ struct SimInner {
     y: f64,
     heading: f64,
     speed: f64,
+    /// Base seed every sensor stream is derived from.
     seed: u64,
+    /// PRNG state of each sensor read so far.
+    streams: HashMap<SensorType, u64>,
+}
+
+impl SimInner {
+    fn new(seed: u64) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            heading: 0.0,
+            speed: 0.0,
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Back to the origin, facing heading 0, stopped, with every sensor
+    /// stream restarted from the base seed.
+    fn reset(&mut self) {
+        *self = Self::new(self.seed);
+    }
+
+    fn snapshot(&self) -> SimState {
+        SimState {
+            x: self.x,
+            y: self.y,
+            heading: self.heading,
+            speed: self.speed,
+        }
+    }
+}
+
+/// Initial PRNG state of `sensor`'s stream under the base `seed`.  Never
+/// zero, which xorshift would never leave.
+fn stream_seed(seed: u64, sensor: SensorType) -> u64 {
+    let salt = match sensor {
+        SensorType::Temperature => 1u64,
+        SensorType::Distance => 2,
+        SensorType::Light => 3,
+        SensorType::Pressure => 4,
+    };
+    match seed ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15) {
+        0 => salt,
+        state => state,
+    }
 }
 
 /// A simulated hardware backend that tracks position, heading, and speed
-/// in-memory.  Sensor readings are deterministic given the seed.
+/// in-memory.  Sensor readings are deterministic given the seed; with a
+/// [`WorldModel`] the distance sensor reads the true distance instead.
 #[derive(Debug)]
 pub struct SimulatedHardware {
     inner: Mutex<SimInner>,
+    world: Option<WorldModel>,
 }
 
 impl Default for SimulatedHardware {
@@ -117,34 +310,59 @@ impl SimulatedHardware {
     /// Create a new simulated hardware with the given RNG seed.
     pub fn new(seed: u64) -> Self {
         Self {
-            inner: Mutex::new(SimInner {
-                x: 0.0,
-                y: 0.0,
-                heading: 0.0,
-                speed: 0.0,
-                seed,
-            }),
+            inner: Mutex::new(SimInner::new(seed)),
+            world: None,
         }
     }
 
+    /// Place the robot in `world`: driving stops at obstacles and the
+    /// distance sensor measures them.
+    pub fn with_world(mut self, world: WorldModel) -> Self {
+        self.world = Some(world);
+        self
+    }
+
+    /// Move up to `distance` along `heading`, stopping at the first
+    /// obstacle.  Returns whether an obstacle was hit.
+    fn advance(&self, inner: &mut SimInner, heading: f64, distance: f64) -> bool {
+        let (travel, collided) = match &self.world {
+            Some(world) => {
+                let free = world.raycast(inner.x, inner.y, heading, distance);
+                (free, free < distance)
+            }
+            None => (distance, false),
+        };
+        let rad = heading.to_radians();
+        inner.x += travel * rad.cos();
+        inner.y += travel * rad.sin();
+        collided
+    }
+
     /// Get a snapshot of the current simulated state.
     pub fn state(&self) -> SimState {
-        let inner = self.inner.lock().unwrap();
-        SimState {
-            x: inner.x,
-            y: inner.y,
-            heading: inner.heading,
-            speed: inner.speed,
-        }
+        self.inner.lock().unwrap().snapshot()
     }
 
-    /// Simple deterministic pseudo-random number in [0, 1) using the seed.
-    fn next_rand(inner: &mut SimInner) -> f64 {
+    /// Return to the state [`new`](Self::new) created: at the origin,
+    /// stopped, and with every sensor's readings starting over.
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().reset();
+    }
+
+    /// Simple deterministic pseudo-random number in [0, 1) from `sensor`'s
+    /// own stream, so each sensor's readings do not depend on how reads of
+    /// other sensors are interleaved with them.
+    fn next_rand(inner: &mut SimInner, sensor: SensorType) -> f64 {
+        let seed = inner.seed;
+        let state = inner
+            .streams
+            .entry(sensor)
+            .or_insert_with(|| stream_seed(seed, sensor));
         // Simple xorshift-style PRNG for deterministic simulation.
-        inner.seed ^= inner.seed << 13;
-        inner.seed ^= inner.seed >> 7;
-        inner.seed ^= inner.seed << 17;
-        (inner.seed % 10000) as f64 / 10000.0
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state % 10000) as f64 / 10000.0
     }
 }
 
@@ -158,16 +376,15 @@ impl Hardware for SimulatedHardware {
         match action {
             HardwareAction::Drive { direction, speed } => {
                 inner.speed = speed;
+                let mut collided = false;
                 match direction {
                     Direction::Forward => {
-                        let rad = inner.heading.to_radians();
-                        inner.x += speed * rad.cos();
-                        inner.y += speed * rad.sin();
+                        let heading = inner.heading;
+                        collided = self.advance(&mut inner, heading, speed);
                     }
                     Direction::Backward => {
-                        let rad = inner.heading.to_radians();
-                        inner.x -= speed * rad.cos();
-                        inner.y -= speed * rad.sin();
+                        let heading = inner.heading + 180.0;
+                        collided = self.advance(&mut inner, heading, speed);
                     }
                     Direction::Left => {
                         inner.heading = (inner.heading - 90.0) % 360.0;
@@ -190,23 +407,27 @@ impl Hardware for SimulatedHardware {
                         "y": inner.y,
                         "heading": inner.heading,
                         "speed": inner.speed,
+                        "collided": collided,
                     }),
                     timestamp,
                 })
             }
             HardwareAction::Sense { sensor_type } => {
-                let rand_val = Self::next_rand(&mut inner);
+                let rand_val = Self::next_rand(&mut inner, sensor_type);
                 let (value, unit) = match sensor_type {
                     SensorType::Temperature => {
                         // Range: -20.0 to 50.0 Celsius
                         let temp = -20.0 + rand_val * 70.0;
                         (temp, "celsius")
                     }
-                    SensorType::Distance => {
+                    SensorType::Distance => match &self.world {
+                        Some(world) => (
+                            world.raycast(inner.x, inner.y, inner.heading, world.sensor_range),
+                            "cm",
+                        ),
                         // Range: 0.0 to 1000.0 cm
-                        let dist = rand_val * 1000.0;
-                        (dist, "cm")
-                    }
+                        None => (rand_val * 1000.0, "cm"),
+                    },
                     SensorType::Light => {
                         // Range: 0.0 to 100000.0 lux
                         let lux = rand_val * 100000.0;
@@ -248,6 +469,21 @@ impl Hardware for SimulatedHardware {
                 }),
                 timestamp,
             }),
+            HardwareAction::Reset => {
+                inner.reset();
+                Ok(HardwareResult {
+                    action: "reset".to_string(),
+                    success: true,
+                    data: serde_json::to_value(inner.snapshot())?,
+                    timestamp,
+                })
+            }
+            HardwareAction::GetState => Ok(HardwareResult {
+                action: "get_state".to_string(),
+                success: true,
+                data: serde_json::to_value(inner.snapshot())?,
+                timestamp,
+            }),
         }
     }
 
@@ -257,12 +493,23 @@ impl Hardware for SimulatedHardware {
             "sense".to_string(),
             "look".to_string(),
             "speak".to_string(),
+            "reset".to_string(),
+            "get_state".to_string(),
         ]
     }
 
     fn name(&self) -> &str {
         "simulated_hardware"
     }
+
+    async fn reset(&self) -> anyhow::Result<()> {
+        SimulatedHardware::reset(self);
+        Ok(())
+    }
+
+    fn get_state(&self) -> Option<SimState> {
+        Some(self.state())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -303,6 +550,13 @@ impl HardwareTool {
             hw: SimulatedHardware::new(seed),
         }
     }
+
+    /// Simulate the hardware inside `world`.
+    pub fn with_world(self, world: WorldModel) -> Self {
+        Self {
+            hw: self.hw.with_world(world),
+        }
+    }
 }
 
 #[async_trait]
@@ -325,7 +579,7 @@ impl Tool for HardwareTool {
                     "properties": {
                         "type": {
                             "type": "string",
-                            "enum": ["drive", "sense", "look", "speak"],
+                            "enum": ["drive", "sense", "look", "speak", "reset", "get_state"],
                             "description": "The type of hardware action"
                         },
                         "direction": {
@@ -369,13 +623,15 @@ impl Tool for HardwareTool {
         match self.hw.execute(action).await {
             Ok(result) => Ok(ToolResult {
                 success: result.success,
-                output: serde_json::to_string(&result)?,
+                content: ToolContent::json(&result)?,
                 error: None,
+                truncated: None,
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
-                output: String::new(),
+                content: ToolContent::default(),
                 error: Some(e.to_string()),
+                truncated: None,
             }),
         }
     }
@@ -523,8 +779,11 @@ mod tests {
         assert!(result.success);
         assert!(result.error.is_none());
 
-        // Parse the output to verify it contains position data.
-        let hw_result: HardwareResult = serde_json::from_str(&result.output).unwrap();
+        // The output is structured and carries position data.
+        let ToolContent::Json(output) = result.content else {
+            panic!("expected JSON content, got {:?}", result.content);
+        };
+        let hw_result: HardwareResult = serde_json::from_value(output).unwrap();
         assert!(hw_result.success);
         assert!(hw_result.data["x"].as_f64().unwrap() > 0.0);
     }
@@ -541,10 +800,106 @@ mod tests {
 
         let result = tool.execute(args).await.unwrap();
         assert!(result.success);
-        let hw_result: HardwareResult = serde_json::from_str(&result.output).unwrap();
+        let hw_result: HardwareResult = serde_json::from_str(&result.output()).unwrap();
         assert_eq!(hw_result.data["spoken_text"], "test message");
     }
 
+    /// Reads `sensor` from `hw`.
+    async fn read(hw: &SimulatedHardware, sensor: SensorType) -> f64 {
+        let result = hw
+            .execute(HardwareAction::Sense {
+                sensor_type: sensor,
+            })
+            .await
+            .unwrap();
+        result.data["value"].as_f64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn sensor_streams_do_not_depend_on_interleaving() {
+        use SensorType::{Distance, Temperature};
+
+        // Temperature, Distance, Temperature, Distance ...
+        let a = SimulatedHardware::new(9);
+        let mut a_temp = Vec::new();
+        let mut a_dist = Vec::new();
+        for _ in 0..3 {
+            a_temp.push(read(&a, Temperature).await);
+            a_dist.push(read(&a, Distance).await);
+        }
+
+        // ... versus every Distance read before any Temperature read.
+        let b = SimulatedHardware::new(9);
+        let mut b_dist = Vec::new();
+        for _ in 0..3 {
+            b_dist.push(read(&b, Distance).await);
+        }
+        let mut b_temp = Vec::new();
+        for _ in 0..3 {
+            b_temp.push(read(&b, Temperature).await);
+        }
+
+        assert_eq!(a_temp, b_temp);
+        assert_eq!(a_dist, b_dist);
+        assert_ne!(a_temp[0], a_temp[1]);
+    }
+
+    #[tokio::test]
+    async fn reset_restarts_sensor_streams() {
+        let hw = SimulatedHardware::new(3);
+        let first = read(&hw, SensorType::Light).await;
+        read(&hw, SensorType::Pressure).await;
+        hw.execute(HardwareAction::Drive {
+            direction: Direction::Forward,
+            speed: 4.0,
+        })
+        .await
+        .unwrap();
+        assert_ne!(read(&hw, SensorType::Light).await, first);
+
+        hw.reset();
+        assert_eq!(hw.state().x, 0.0);
+        assert_eq!(read(&hw, SensorType::Light).await, first);
+    }
+
+    /// A backend that only implements the required methods.
+    struct BareHardware;
+
+    #[async_trait]
+    impl Hardware for BareHardware {
+        async fn execute(&self, _action: HardwareAction) -> anyhow::Result<HardwareResult> {
+            anyhow::bail!("unsupported")
+        }
+        fn capabilities(&self) -> Vec<String> {
+            Vec::new()
+        }
+        fn name(&self) -> &str {
+            "bare"
+        }
+    }
+
+    #[tokio::test]
+    async fn reset_and_get_state_default_for_other_backends() {
+        let err = BareHardware.reset().await.unwrap_err();
+        assert!(err.to_string().contains("bare does not support reset"));
+        assert!(BareHardware.get_state().is_none());
+
+        let sim = SimulatedHardware::new(1);
+        sim.execute(HardwareAction::Drive {
+            direction: Direction::Forward,
+            speed: 3.0,
+        })
+        .await
+        .unwrap();
+        assert!((sim.get_state().unwrap().x - 3.0).abs() < 1e-9);
+        Hardware::reset(&sim).await.unwrap();
+        let state = sim.get_state().unwrap();
+        assert_eq!(
+            (state.x, state.y, state.heading, state.speed),
+            (0.0, 0.0, 0.0, 0.0)
+        );
+    }
+
     #[tokio::test]
     async fn hardware_tool_missing_action_errors() {
         let tool = HardwareTool::new();
@@ -598,4 +953,80 @@ mod tests {
         assert!((round.x - 1.0).abs() < 0.001);
         assert!((round.heading - 90.0).abs() < 0.001);
     }
+
+    // -- world model -------------------------------------------------------
+
+    async fn drive(hw: &SimulatedHardware, direction: Direction, speed: f64) -> HardwareResult {
+        hw.execute(HardwareAction::Drive { direction, speed })
+            .await
+            .unwrap()
+    }
+
+    async fn distance(hw: &SimulatedHardware) -> f64 {
+        hw.execute(HardwareAction::Sense {
+            sensor_type: SensorType::Distance,
+        })
+        .await
+        .unwrap()
+        .data["value"]
+            .as_f64()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn drive_into_wall_stops_at_boundary() {
+        let world = WorldModel::new().with_obstacle(10.0, -5.0, 12.0, 5.0);
+        let hw = SimulatedHardware::new(1).with_world(world);
+
+        let result = drive(&hw, Direction::Forward, 4.0).await;
+        assert_eq!(result.data["collided"], false);
+        assert!((hw.state().x - 4.0).abs() < 1e-9);
+
+        let result = drive(&hw, Direction::Forward, 20.0).await;
+        assert_eq!(result.data["collided"], true);
+        assert!((hw.state().x - 10.0).abs() < 1e-9);
+
+        // Pressed against the wall: no further progress, but backing off works.
+        assert_eq!(
+            drive(&hw, Direction::Forward, 1.0).await.data["collided"],
+            true
+        );
+        assert!((hw.state().x - 10.0).abs() < 1e-9);
+        assert_eq!(
+            drive(&hw, Direction::Backward, 3.0).await.data["collided"],
+            false
+        );
+        assert!((hw.state().x - 7.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn distance_sensor_raycasts_before_and_after_turning() {
+        let world = WorldModel::new()
+            .with_obstacle(50.0, -10.0, 60.0, 10.0)
+            .with_obstacle(-10.0, 30.0, 10.0, 40.0)
+            .with_sensor_range(500.0);
+        let hw = SimulatedHardware::new(1).with_world(world);
+
+        assert!((distance(&hw).await - 50.0).abs() < 1e-9);
+        drive(&hw, Direction::Right, 0.0).await;
+        assert!((distance(&hw).await - 30.0).abs() < 1e-9);
+        // Nothing to the west: the reading is capped at the sensor range.
+        drive(&hw, Direction::Right, 0.0).await;
+        assert!((distance(&hw).await - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn world_model_loads_from_json_file() {
+        let path = std::env::temp_dir().join(format!("ygn-world-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"obstacles": [{"min_x": 1, "min_y": 2, "max_x": 3, "max_y": 4}]}"#,
+        )
+        .unwrap();
+        let world = WorldModel::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(world.obstacles.len(), 1);
+        assert_eq!(world.obstacles[0].max_y, 4.0);
+        assert_eq!(world.sensor_range, DEFAULT_SENSOR_RANGE);
+    }
 }
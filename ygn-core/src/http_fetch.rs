@@ -0,0 +1,310 @@
+//! HTTP fetch tool.
+//!
+//! [`HttpFetchTool`] performs an HTTP request and reports the status,
+//! headers and body.  The URL's host is checked against a
+//! [`SandboxChecker`] with [`AccessKind::Network`] before any connection is
+//! made, so a sandbox with a domain allowlist bounds where requests can go.
+//! Redirects are not followed, since the target of a redirect has not been
+//! checked.  Bodies larger than the configured limit are truncated.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::sandbox::{AccessKind, AccessRequest, SandboxChecker};
+use crate::tool::{Tool, ToolContent, ToolResult};
+
+/// Default maximum number of body bytes returned.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 256 * 1024;
+
+/// What a finished request produced, serialized as the tool output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchOutput {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// The body, lossily decoded as UTF-8.
+    pub body: String,
+    /// Whether `body` was cut off at the size limit.
+    pub truncated: bool,
+}
+
+/// Fetches URLs after a sandbox check on their host.
+pub struct HttpFetchTool {
+    sandbox: Box<dyn SandboxChecker>,
+    client: reqwest::Client,
+    max_body_bytes: usize,
+}
+
+impl std::fmt::Debug for HttpFetchTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpFetchTool")
+            .field("sandbox", &self.sandbox.profile_name())
+            .field("max_body_bytes", &self.max_body_bytes)
+            .finish()
+    }
+}
+
+impl HttpFetchTool {
+    pub fn new(sandbox: Box<dyn SandboxChecker>) -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("failed to build HTTP client");
+        Self {
+            sandbox,
+            client,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    /// Truncate response bodies to `max_body_bytes`.
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            content: ToolContent::default(),
+            error: Some(error),
+            truncated: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for HttpFetchTool {
+    fn name(&self) -> &str {
+        "http_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a URL over HTTP and return the status, headers and body"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "http or https URL to request"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"],
+                    "description": "HTTP method (default GET)"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: url"))?;
+        let url = Url::parse(url).map_err(|e| anyhow::anyhow!("Invalid url: {e}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            anyhow::bail!("Unsupported URL scheme: {}", url.scheme());
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("URL has no host: {url}"))?
+            .to_string();
+        let method = match args.get("method").and_then(|v| v.as_str()) {
+            Some(m) => Method::from_bytes(m.to_ascii_uppercase().as_bytes())
+                .map_err(|_| anyhow::anyhow!("Invalid method: {m}"))?,
+            None => Method::GET,
+        };
+        let headers: BTreeMap<String, String> = match args.get("headers") {
+            Some(v) => serde_json::from_value(v.clone())
+                .map_err(|e| anyhow::anyhow!("Invalid headers: {e}"))?,
+            None => BTreeMap::new(),
+        };
+
+        let access = self.sandbox.check_access(&AccessRequest {
+            kind: AccessKind::Network,
+            target: host.clone(),
+        });
+        if !access.allowed {
+            return Ok(Self::failure(format!(
+                "sandbox ({}) denied access to '{host}': {}",
+                access.profile, access.reason
+            )));
+        }
+
+        let mut request = self.client.request(method, url);
+        for (name, value) in &headers {
+            request = request.header(name, value);
+        }
+        let mut response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Ok(Self::failure(format!("request failed: {e}"))),
+        };
+
+        let status = response.status();
+        let response_headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await? {
+            let room = self.max_body_bytes - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let output = FetchOutput {
+            status: status.as_u16(),
+            headers: response_headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            truncated,
+        };
+        let success = status.is_success();
+        Ok(ToolResult {
+            success,
+            content: ToolContent::json(&output)?,
+            error: (!success).then(|| format!("HTTP {status}")),
+            truncated: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::{ProcessSandbox, SandboxProfile};
+    use axum::{http::HeaderMap, routing::get, Router};
+
+    async fn mock_server() -> String {
+        let app = Router::new()
+            .route(
+                "/hello",
+                get(|headers: HeaderMap| async move {
+                    let who = headers
+                        .get("x-name")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("world")
+                        .to_string();
+                    format!("hello {who}")
+                }),
+            )
+            .route("/big", get(|| async { "x".repeat(10_000) }))
+            .route(
+                "/missing",
+                get(|| async { (axum::http::StatusCode::NOT_FOUND, "nope") }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        base_url
+    }
+
+    fn output_of(result: &ToolResult) -> FetchOutput {
+        serde_json::from_str(&result.output()).unwrap()
+    }
+
+    fn net_tool() -> HttpFetchTool {
+        HttpFetchTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)))
+    }
+
+    #[tokio::test]
+    async fn fetches_url() {
+        let base = mock_server().await;
+        let result = net_tool()
+            .execute(serde_json::json!({
+                "url": format!("{base}/hello"),
+                "headers": { "x-name": "ygn" }
+            }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let output = output_of(&result);
+        assert_eq!(output.status, 200);
+        assert_eq!(output.body, "hello ygn");
+        assert!(!output.truncated);
+        assert!(output.headers["content-type"].starts_with("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn large_body_is_truncated() {
+        let base = mock_server().await;
+        let result = net_tool()
+            .with_max_body_bytes(100)
+            .execute(serde_json::json!({ "url": format!("{base}/big") }))
+            .await
+            .unwrap();
+        let output = output_of(&result);
+        assert!(output.truncated);
+        assert_eq!(output.body.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn error_status_is_a_failure() {
+        let base = mock_server().await;
+        let result = net_tool()
+            .execute(serde_json::json!({ "url": format!("{base}/missing") }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("HTTP 404 Not Found"));
+        assert_eq!(output_of(&result).body, "nope");
+    }
+
+    #[tokio::test]
+    async fn nonet_denies_fetch() {
+        let tool = HttpFetchTool::new(Box::new(ProcessSandbox::new(SandboxProfile::NoNet)));
+        let result = tool
+            .execute(serde_json::json!({ "url": "https://example.com/" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output().is_empty());
+        let error = result.error.unwrap();
+        assert!(error.contains("denied access to 'example.com'"));
+        assert!(error.contains("NoNet"));
+    }
+
+    #[tokio::test]
+    async fn host_outside_allowlist_is_denied() {
+        let base = mock_server().await;
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        sandbox.allow_domain("example.com");
+        let result = HttpFetchTool::new(Box::new(sandbox))
+            .execute(serde_json::json!({ "url": format!("{base}/hello") }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("not in the domain allowlist"));
+    }
+}
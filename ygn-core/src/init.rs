@@ -0,0 +1,399 @@
+//! `ygn-core init`: write a starter node configuration.
+//!
+//! Each [`Profile`] fills in the settings a node in that role needs: edge
+//! nodes serve simulated hardware from the untrusted tier and must join a
+//! remote registry, brain nodes need at least one model provider and no
+//! hardware, and core nodes sit in between.  Values a profile requires are
+//! taken from [`InitOptions`], asked for when a terminal is attached, or
+//! reported as missing.  The rendered file is checked with the config
+//! validator before anything is written.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+use crate::config::NodeConfig;
+
+/// Providers a brain node can use, with the environment variable holding
+/// each one's API key (`None` when no key is needed).
+pub const PROVIDERS: &[(&str, Option<&str>)] = &[
+    ("anthropic", Some("ANTHROPIC_API_KEY")),
+    ("openai", Some("OPENAI_API_KEY")),
+    ("gemini", Some("GEMINI_API_KEY")),
+    ("ollama", None),
+];
+
+/// Kind of node a generated configuration is tailored to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Brain,
+    Core,
+    Edge,
+}
+
+impl Profile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Profile::Brain => "brain",
+            Profile::Core => "core",
+            Profile::Edge => "edge",
+        }
+    }
+
+    fn default_bind(self) -> &'static str {
+        match self {
+            Profile::Brain => "127.0.0.1:3000",
+            Profile::Core | Profile::Edge => "0.0.0.0:3000",
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "brain" => Ok(Profile::Brain),
+            "core" => Ok(Profile::Core),
+            "edge" => Ok(Profile::Edge),
+            other => Err(format!(
+                "unknown profile '{other}' (expected brain, core or edge)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Values supplied up front, typically from command-line flags.
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    pub profile: Profile,
+    /// Gateway bind address; the profile's default when unset.
+    pub gateway_bind: Option<String>,
+    /// Remote registry URL; required for edge nodes.
+    pub registry_url: Option<String>,
+    /// Providers from [`PROVIDERS`]; at least one is required for brain
+    /// nodes.
+    pub providers: Vec<String>,
+    /// Replace an existing config file.
+    pub force: bool,
+}
+
+impl InitOptions {
+    pub fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            gateway_bind: None,
+            registry_url: None,
+            providers: Vec::new(),
+            force: false,
+        }
+    }
+}
+
+/// Where answers to missing values come from.
+pub enum Prompter<'a> {
+    /// Fail on any required value that was not supplied.
+    NonInteractive,
+    /// Ask on `output` and read replies from `input`.
+    Interactive {
+        input: &'a mut dyn BufRead,
+        output: &'a mut dyn Write,
+    },
+}
+
+impl Prompter<'_> {
+    /// Ask for `question`; an empty reply takes `default`.  Returns `None`
+    /// when not interactive.
+    fn ask(&mut self, question: &str, default: Option<&str>) -> anyhow::Result<Option<String>> {
+        let Prompter::Interactive { input, output } = self else {
+            return Ok(None);
+        };
+        match default {
+            Some(default) => write!(output, "{question} [{default}]: ")?,
+            None => write!(output, "{question}: ")?,
+        }
+        output.flush()?;
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let reply = line.trim();
+        Ok(match (reply.is_empty(), default) {
+            (false, _) => Some(reply.to_string()),
+            (true, Some(default)) => Some(default.to_string()),
+            (true, None) => None,
+        })
+    }
+}
+
+/// Settings resolved for the profile.
+#[derive(Debug, Clone, PartialEq)]
+struct Answers {
+    gateway_bind: String,
+    registry_url: Option<String>,
+    providers: Vec<String>,
+}
+
+/// A configuration written by [`run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generated {
+    /// The file's contents.
+    pub text: String,
+    /// Providers the node was set up for.
+    pub providers: Vec<String>,
+}
+
+/// Write the profile's configuration to `path`.  Refuses to replace an
+/// existing file unless `options.force` is set, and writes nothing if the
+/// result does not validate.
+pub fn run(
+    path: &Path,
+    options: &InitOptions,
+    prompter: &mut Prompter,
+) -> anyhow::Result<Generated> {
+    if path.exists() && !options.force {
+        bail!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        );
+    }
+    let answers = resolve(options, prompter)?;
+    let text = render(options.profile, &answers);
+    validate(&text)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("creating {}", parent.display()))?;
+    }
+    std::fs::write(path, &text).with_context(|| format!("writing {}", path.display()))?;
+    Ok(Generated {
+        text,
+        providers: answers.providers,
+    })
+}
+
+/// API-key variables of `providers` that are not set in the environment.
+pub fn missing_keys(providers: &[String]) -> Vec<&'static str> {
+    PROVIDERS
+        .iter()
+        .filter(|(name, _)| providers.iter().any(|p| p == name))
+        .filter_map(|(_, env)| *env)
+        .filter(|env| std::env::var_os(env).is_none())
+        .collect()
+}
+
+fn resolve(options: &InitOptions, prompter: &mut Prompter) -> anyhow::Result<Answers> {
+    let profile = options.profile;
+    let gateway_bind = match &options.gateway_bind {
+        Some(bind) => bind.clone(),
+        None => prompter
+            .ask("Gateway bind address", Some(profile.default_bind()))?
+            .unwrap_or_else(|| profile.default_bind().to_string()),
+    };
+
+    let registry_url = match &options.registry_url {
+        Some(url) => Some(url.clone()),
+        None if profile == Profile::Edge => match prompter.ask("Remote registry URL", None)? {
+            Some(url) => Some(url),
+            None => bail!("the edge profile requires a remote registry (--registry-url)"),
+        },
+        None => None,
+    };
+    if let Some(url) = &registry_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!("invalid registry URL '{url}': expected http:// or https://");
+        }
+    }
+
+    let mut providers = options.providers.clone();
+    if providers.is_empty() && profile == Profile::Brain {
+        let names: Vec<&str> = PROVIDERS.iter().map(|(name, _)| *name).collect();
+        let question = format!("Providers to use ({})", names.join(", "));
+        match prompter.ask(&question, Some("anthropic"))? {
+            Some(reply) => providers = split_list(&reply),
+            None => bail!("the brain profile requires at least one provider (--providers)"),
+        }
+    }
+    for provider in &providers {
+        if !PROVIDERS.iter().any(|(name, _)| name == provider) {
+            bail!("unknown provider '{provider}'");
+        }
+    }
+    if profile == Profile::Brain && providers.is_empty() {
+        bail!("the brain profile requires at least one provider (--providers)");
+    }
+
+    Ok(Answers {
+        gateway_bind,
+        registry_url,
+        providers,
+    })
+}
+
+fn split_list(reply: &str) -> Vec<String> {
+    reply
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn render(profile: Profile, answers: &Answers) -> String {
+    let quote = |s: &str| toml::Value::String(s.to_string()).to_string();
+    let (trust_tier, simulated) = match profile {
+        Profile::Edge => ("untrusted", true),
+        Profile::Brain | Profile::Core => ("trusted", false),
+    };
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "# Y-GN node configuration ({profile} profile), generated by `ygn-core init`.\n"
+    ));
+    out.push_str("# Check it with `ygn-core config validate`.\n\n");
+    out.push_str("# Role of this node in the grid.\n");
+    out.push_str(&format!("node_role = {}\n", quote(profile.as_str())));
+    out.push_str("# Trust tier other nodes assign to this node's tools.\n");
+    out.push_str(&format!("trust_tier = {}\n", quote(trust_tier)));
+    out.push_str("# Address the HTTP gateway listens on.\n");
+    out.push_str(&format!(
+        "gateway_bind = {}\n",
+        quote(&answers.gateway_bind)
+    ));
+
+    out.push_str("\n[hardware]\n");
+    out.push_str("# Serve the simulated `hardware` tool over MCP.\n");
+    out.push_str(&format!("simulated = {simulated}\n"));
+
+    out.push_str("\n[registry]\n");
+    out.push_str("# Gateway whose node registry this node joins with heartbeats.\n");
+    match &answers.registry_url {
+        Some(url) => out.push_str(&format!("remote_url = {}\n", quote(url))),
+        None => out.push_str("# remote_url = \"http://10.0.0.5:3000\"\n"),
+    }
+    out.push_str("# Address other nodes use to reach this one; required when\n");
+    out.push_str("# gateway_bind is not a specific IP.\n");
+    out.push_str("# advertise_address = \"10.0.0.6:3000\"\n");
+    out.push_str("heartbeat_interval_secs = 30\n");
+
+    if profile == Profile::Brain {
+        out.push_str("\n[providers]\n");
+        out.push_str("# Providers are enabled by their API keys in the environment:\n");
+        for (name, env) in PROVIDERS {
+            if !answers.providers.iter().any(|p| p == name) {
+                continue;
+            }
+            match env {
+                Some(env) => out.push_str(&format!("#   {name}: export {env}\n")),
+                None => out.push_str(&format!("#   {name}: no key needed\n")),
+            }
+        }
+        out.push_str("# Seconds between provider health probes; 0 disables probing.\n");
+        out.push_str("probe_interval_secs = 60\n");
+    }
+    out
+}
+
+fn validate(text: &str) -> anyhow::Result<()> {
+    let value = serde_json::to_value(toml::from_str::<toml::Value>(text)?)?;
+    let mut errors = NodeConfig::validate_value(&value);
+    if errors.is_empty() {
+        let config: NodeConfig = serde_json::from_value(value)?;
+        errors = config.semantic_errors();
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    bail!("generated config is invalid: {}", details.join("; "))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config() -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("ygn-init-{}", uuid::Uuid::new_v4()))
+            .join("config.toml")
+    }
+
+    #[test]
+    fn prompts_fill_required_values() {
+        let path = temp_config();
+        let mut input = std::io::Cursor::new("10.0.0.6:4000\nhttp://10.0.0.5:3000\n");
+        let mut output = Vec::new();
+        let generated = run(
+            &path,
+            &InitOptions::new(Profile::Edge),
+            &mut Prompter::Interactive {
+                input: &mut input,
+                output: &mut output,
+            },
+        )
+        .unwrap();
+
+        let text = generated.text;
+        let config = NodeConfig::load_from_file(&path).unwrap();
+        assert_eq!(config.gateway_bind, "10.0.0.6:4000");
+        assert_eq!(config.trust_tier, "untrusted");
+        assert!(config.hardware.simulated);
+        assert_eq!(
+            config.registry.remote_url.as_deref(),
+            Some("http://10.0.0.5:3000")
+        );
+        assert!(text.starts_with("# Y-GN node configuration (edge profile)"));
+        let asked = String::from_utf8(output).unwrap();
+        assert!(asked.contains("Gateway bind address [0.0.0.0:3000]: "));
+        assert!(asked.contains("Remote registry URL: "));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn empty_replies_take_defaults() {
+        let path = temp_config();
+        let mut input = std::io::Cursor::new("\n\n");
+        let mut output = Vec::new();
+        let generated = run(
+            &path,
+            &InitOptions::new(Profile::Brain),
+            &mut Prompter::Interactive {
+                input: &mut input,
+                output: &mut output,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(generated.providers, ["anthropic"]);
+        let text = generated.text;
+        assert!(text.contains("gateway_bind = \"127.0.0.1:3000\""));
+        assert!(text.contains("#   anthropic: export ANTHROPIC_API_KEY"));
+        assert!(!text.contains("OPENAI_API_KEY"));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn invalid_values_write_nothing() {
+        let path = temp_config();
+        let mut options = InitOptions::new(Profile::Core);
+        options.gateway_bind = Some("not-an-address".into());
+        let err = run(&path, &options, &mut Prompter::NonInteractive).unwrap_err();
+        assert!(err.to_string().contains("gateway_bind"), "{err}");
+        assert!(!path.exists());
+
+        let mut options = InitOptions::new(Profile::Brain);
+        options.providers = vec!["nope".into()];
+        let err = run(&path, &options, &mut Prompter::NonInteractive).unwrap_err();
+        assert!(err.to_string().contains("unknown provider 'nope'"), "{err}");
+        assert!(!path.exists());
+    }
+}
@@ -1,31 +1,55 @@
 pub mod a2a;
+pub mod a2a_client;
 pub mod audit;
+pub mod auth;
+pub mod backup;
 pub mod channel;
 pub mod config;
 pub mod credential_vault;
 pub mod diagnostics;
 pub mod discord;
+pub mod external_tool;
 pub mod gateway;
+pub mod grid;
 pub mod hardware;
+pub mod http_fetch;
+pub mod init;
 pub mod landlock;
 pub mod matrix;
 pub mod mcp;
+pub mod mcp_client;
+pub mod mcp_proxy;
 pub mod memory;
+pub mod metrics;
 pub mod multi_provider;
+pub mod observation;
 pub mod observer;
 pub mod policy;
 pub mod provider;
+pub mod provider_cache;
 pub mod provider_health;
 pub mod rate_limiter;
 pub mod registry;
+pub mod remote_registry;
+pub mod repl;
 pub mod sandbox;
+pub mod scheduler;
+pub mod schema;
 pub mod security;
+pub mod shell;
+pub mod skill_planner;
 pub mod skills;
 pub mod sqlite_memory;
 pub mod sqlite_registry;
 pub mod telegram;
 pub mod telemetry;
 pub mod tool;
+pub mod tool_history;
+pub mod tool_limits;
+pub mod tool_output;
 pub mod tunnel;
 pub mod uacp;
+pub mod uacp_transport;
+pub mod usage;
 pub mod wassette;
+pub mod websocket;
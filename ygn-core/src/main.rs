@@ -1,18 +1,41 @@
 use clap::{Parser, Subcommand};
 
+use ygn_core::a2a_client;
+use ygn_core::audit;
+use ygn_core::backup;
 use ygn_core::config;
 use ygn_core::diagnostics;
 use ygn_core::gateway;
+use ygn_core::grid;
 use ygn_core::hardware;
+use ygn_core::init;
 use ygn_core::mcp;
+use ygn_core::mcp_client;
+use ygn_core::mcp_proxy::McpProxy;
+use ygn_core::memory::{ListOrder, Memory, MemoryCategory};
 use ygn_core::multi_provider::ProviderRegistry;
+use ygn_core::policy::{PolicyConfig, PolicyEngine};
 use ygn_core::registry::{self, NodeRegistry};
+use ygn_core::remote_registry::RemoteRegistry;
+use ygn_core::repl;
+use ygn_core::sandbox::{ProcessSandbox, SandboxProfile};
+use ygn_core::scheduler;
+use ygn_core::skill_planner;
 use ygn_core::skills;
+use ygn_core::sqlite_memory::{self, SqliteMemory};
 use ygn_core::tool;
+use ygn_core::tool_history::{self, ExecutionOrigin, ToolExecutionLog};
+use ygn_core::tool_limits::ToolLimiter;
+use ygn_core::tool_output::ToolOutputLimits;
+use ygn_core::uacp;
+use ygn_core::usage;
 
 #[derive(Parser)]
 #[command(name = "ygn-core", version, about = "Y-GN data-plane runtime")]
 struct Cli {
+    /// Node configuration file (default: ~/.ygn/config.toml)
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -21,10 +44,34 @@ struct Cli {
 enum Commands {
     /// Show node status
     Status,
+    /// Write a commented starter config for a node profile (to --config,
+    /// default ~/.ygn/config.toml)
+    Init {
+        /// Node profile: brain, core or edge
+        #[arg(long)]
+        profile: init::Profile,
+        /// Never prompt; fail if a value the profile requires is missing
+        #[arg(long)]
+        non_interactive: bool,
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+        /// Gateway bind address (default depends on the profile)
+        #[arg(long)]
+        bind: Option<String>,
+        /// Remote registry to join (required for edge)
+        #[arg(long)]
+        registry_url: Option<String>,
+        /// Comma-separated providers: anthropic, openai, gemini, ollama
+        /// (required for brain)
+        #[arg(long, value_delimiter = ',')]
+        providers: Vec<String>,
+    },
     /// Start the HTTP gateway
     Gateway {
-        #[arg(short, long, default_value = "0.0.0.0:3000")]
-        bind: String,
+        /// Address to listen on (default: gateway_bind from the config)
+        #[arg(short, long)]
+        bind: Option<String>,
     },
     /// Export config JSON schema
     Config {
@@ -42,7 +89,29 @@ enum Commands {
         action: ProvidersAction,
     },
     /// Start MCP server over stdio (JSON-RPC 2.0, newline-delimited)
-    Mcp,
+    Mcp {
+        /// Also serve tools imported from the configured `mcp_servers`
+        #[arg(long)]
+        import_servers: bool,
+        /// Append the session's audit log to this file (JSON Lines) on exit
+        #[arg(long)]
+        audit_log: Option<std::path::PathBuf>,
+        /// Also serve demo tools: `countdown`, a slow call that sends
+        /// progress notifications
+        #[arg(long)]
+        demo_tools: bool,
+        /// Gate tool calls with this policy file (JSON or TOML) instead of
+        /// the config's `policy` or `policy_file`; any of them enables
+        /// `tools/register` for commands its sandbox allows
+        #[arg(long)]
+        policy: Option<std::path::PathBuf>,
+        /// Act as a brain proxy: list and forward to the tools of the node
+        /// whose MCP-over-HTTP endpoint is at this URL (e.g.
+        /// http://host:3000).  YGN_PROXY_API_KEY, if set, is sent as its
+        /// bearer token
+        #[arg(long)]
+        proxy_to: Option<String>,
+    },
     /// Node registry management
     Registry {
         #[command(subcommand)]
@@ -53,6 +122,38 @@ enum Commands {
         #[command(subcommand)]
         action: SkillsAction,
     },
+    /// Run skills on a recurring schedule (while the gateway runs)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Browse the memory store
+    Memory {
+        #[command(subcommand)]
+        action: MemoryAction,
+    },
+    /// Back up or restore the SQLite stores under ~/.ygn
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Interactive session: run tools and skills, recall memories and chat
+    /// with a model (type /help inside)
+    Repl {
+        /// Model to chat with
+        #[arg(long, default_value = "llama3")]
+        model: String,
+    },
+    /// Inspect uACP frames
+    Uacp {
+        #[command(subcommand)]
+        action: UacpAction,
+    },
+    /// Work with the persistent audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
     /// Run diagnostics on stdin input (pipe gate output)
     Diagnose {
         /// Name of the gate/source that produced the output
@@ -65,12 +166,32 @@ enum Commands {
 enum ConfigAction {
     /// Print JSON schema for configuration
     Schema,
+    /// Validate a configuration file (defaults to ~/.ygn/config.toml);
+    /// exits non-zero if it has errors
+    Validate {
+        /// Config file to check (TOML, JSON or YAML by extension)
+        path: Option<std::path::PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
 enum ToolsAction {
     /// List all registered tools
     List,
+    /// Show recorded tool executions, most recent first
+    History {
+        /// Only show executions of this tool
+        #[arg(long)]
+        tool: Option<String>,
+        /// Maximum number of executions to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Re-run a recorded execution and diff its output against the original
+    Replay {
+        /// Execution id, as shown by `tools history`
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -79,10 +200,273 @@ enum ProvidersAction {
     List,
 }
 
+#[derive(Subcommand)]
+enum MemoryAction {
+    /// List entries, newest first
+    List {
+        /// Only list this category (core, daily, conversation or a custom name)
+        #[arg(long)]
+        category: Option<String>,
+        /// Number of entries to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Maximum number of entries to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Sort by last update or by creation time
+        #[arg(long, default_value = "updated_at", value_parser = ["updated_at", "created_at"])]
+        order: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum UacpAction {
+    /// Print the frames read from stdin as JSON, one line of hex or base64
+    /// per frame or batch of frames
+    Decode,
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Export audit entries as JSON Lines or CEF for a SIEM
+    Export {
+        /// Output format: jsonl or cef
+        #[arg(long, default_value = "jsonl")]
+        format: audit::AuditExportFormat,
+        /// Audit log to read (default: `audit_log` from the config)
+        #[arg(long)]
+        log: Option<std::path::PathBuf>,
+        /// Only entries at or after this time: RFC 3339, or a duration
+        /// ago such as `24h`
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time: RFC 3339, or a duration ago
+        #[arg(long)]
+        until: Option<String>,
+        /// Only these event types (repeatable), e.g. `access_denied`
+        #[arg(long = "event-type")]
+        event_types: Vec<audit::AuditEventType>,
+        /// Only entries for this tool
+        #[arg(long)]
+        tool: Option<String>,
+        /// Write here instead of stdout
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+    /// Snapshot every store into a directory, with a manifest
+    Create {
+        /// Directory to write the snapshots and manifest to
+        #[arg(long)]
+        out: std::path::PathBuf,
+    },
+    /// Restore stores from a backup directory
+    Restore {
+        /// Directory containing a backup manifest
+        #[arg(long)]
+        from: std::path::PathBuf,
+        /// Only restore this store (e.g. memory, registry, usage)
+        #[arg(long)]
+        only: Option<String>,
+        /// Restore even over a database with a newer schema version
+        #[arg(long)]
+        force: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum SkillsAction {
     /// List all registered skills
     List,
+    /// Print JSON schema for skill manifests
+    Schema,
+    /// Export a skill as a YAML or JSON manifest
+    Export {
+        /// Name of the skill to export
+        name: String,
+        /// Output file (format from extension; YAML to stdout if omitted)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Validate and import a skill manifest
+    Import {
+        /// Path to a YAML or JSON manifest
+        file: std::path::PathBuf,
+        /// Import even if the skill references tools this node lacks
+        #[arg(long)]
+        force: bool,
+    },
+    /// Run a registered skill with this node's tools, unless the
+    /// configured policy denies one of its steps
+    Run {
+        /// Name of the skill to run
+        name: String,
+        /// Print the execution plan instead of running it; exits non-zero
+        /// if a step would be denied or its tool is missing
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the plan as JSON (with --dry-run)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Ask a model to plan a skill for a goal, using this node's tools
+    Plan {
+        /// What the skill should accomplish
+        goal: String,
+        /// Run the planned skill instead of only printing it
+        #[arg(long)]
+        execute: bool,
+        /// Model to plan with
+        #[arg(long, default_value = "llama3")]
+        model: String,
+        /// Maximum number of steps in the plan
+        #[arg(long, default_value_t = skill_planner::DEFAULT_MAX_STEPS)]
+        max_steps: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// Schedule a skill on a fixed interval or a cron expression
+    Add {
+        /// Name of the schedule entry
+        name: String,
+        /// Skill to run
+        #[arg(long)]
+        skill: String,
+        /// Interval between runs, e.g. 100ms, 30s, 5m or 1h
+        #[arg(long, conflicts_with = "cron", required_unless_present = "cron")]
+        every: Option<String>,
+        /// Five-field cron expression, evaluated in UTC (e.g. "*/5 * * * *")
+        #[arg(long)]
+        cron: Option<String>,
+        /// Up to this much random delay added to each run (e.g. 10s)
+        #[arg(long)]
+        jitter: Option<String>,
+        /// Store each execution in memory under `schedule:<name>`
+        #[arg(long)]
+        remember: bool,
+        /// Add the entry disabled
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// List schedule entries
+    List,
+    /// Delete a schedule entry
+    Remove {
+        /// Name of the schedule entry
+        name: String,
+    },
+    /// Turn a schedule entry on
+    Enable {
+        /// Name of the schedule entry
+        name: String,
+    },
+    /// Turn a schedule entry off
+    Disable {
+        /// Name of the schedule entry
+        name: String,
+    },
+}
+
+/// The schedule store at [`scheduler::default_db_path`].
+fn schedule_store() -> anyhow::Result<scheduler::ScheduleStore> {
+    let path = scheduler::default_db_path();
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    scheduler::ScheduleStore::new(&path)
+}
+
+/// Built-in tools available to CLI commands, plus `remote_execute` when
+/// `registry.remote_url` is configured and `ask_agent` when `a2a_peers` is.
+/// YGN_GRID_API_KEY, if set, is sent as the bearer token to the nodes
+/// `remote_execute` calls.
+fn local_tools() -> tool::ToolRegistry {
+    let mut tool_registry = tool::ToolRegistry::new();
+    tool_registry.register(Box::new(tool::EchoTool));
+    tool_registry.register(Box::new(hardware::HardwareTool::new()));
+    let cfg = config::NodeConfig::load_or_default();
+    if !cfg.a2a_peers.is_empty() {
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        for peer in &cfg.a2a_peers {
+            sandbox.allow_domain(peer.clone());
+        }
+        tool_registry.register(Box::new(a2a_client::AskAgentTool::new(Box::new(sandbox))));
+    }
+    if let Some(url) = cfg.registry.remote_url {
+        let registry = std::sync::Arc::new(RemoteRegistry::new(url));
+        let mut executor = grid::GridExecutor::new(registry)
+            .with_max_staleness(cfg.registry.heartbeat_interval_secs * 3);
+        if let Ok(key) = std::env::var("YGN_GRID_API_KEY") {
+            executor = executor.with_api_key(key);
+        }
+        tool_registry.register(Box::new(grid::RemoteExecuteTool::new(std::sync::Arc::new(
+            executor,
+        ))));
+    }
+    tool_registry
+}
+
+/// The tool policy from the config's `policy` or `policy_file`, else the
+/// defaults.
+fn configured_policy() -> anyhow::Result<PolicyEngine> {
+    config::NodeConfig::load_or_default()
+        .policy_config()
+        .map(PolicyEngine::from_config)
+}
+
+/// Print an execution plan as a table, flagging steps that cannot run.
+fn print_plan(plan: &skills::ExecutionPlan) {
+    let risk = plan
+        .highest_risk
+        .as_ref()
+        .map_or("n/a".to_string(), |r| format!("{r:?}"));
+    println!(
+        "Plan for skill '{}' ({} steps, highest risk: {risk}):",
+        plan.skill_name,
+        plan.steps.len()
+    );
+    println!(
+        "  {:<5} {:<4} {:<20} {:<16} {:<8} ARGUMENTS",
+        "ORDER", "STEP", "TOOL", "DECISION", "RISK"
+    );
+    for (order, step) in plan.steps.iter().enumerate() {
+        let (action, risk) = match &step.decision {
+            Some(d) if step.is_denied() => ("DENIED".to_string(), format!("{:?}", d.risk_level)),
+            Some(d) => (format!("{:?}", d.action), format!("{:?}", d.risk_level)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "  {:<5} {:<4} {:<20} {:<16} {:<8} {}",
+            order + 1,
+            step.step_index,
+            step.resolved_tool.as_deref().unwrap_or(&step.tool_name),
+            action,
+            risk,
+            step.arguments
+        );
+        if let Some(e) = &step.resolution_error {
+            println!("        ! {e}");
+        }
+        if step.is_denied() {
+            if let Some(d) = &step.decision {
+                println!("        ! {}", d.reason);
+            }
+        }
+    }
+}
+
+/// Open the tool execution history at its default location.
+fn open_tool_history() -> anyhow::Result<ToolExecutionLog> {
+    let path = tool_history::default_db_path();
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    ToolExecutionLog::new(&path)
 }
 
 #[derive(Subcommand)]
@@ -93,6 +477,17 @@ enum RegistryAction {
     SelfInfo,
 }
 
+/// A time given as RFC 3339, or as a duration before now such as `24h`.
+fn parse_time_bound(value: &str) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.to_utc());
+    }
+    let ago = scheduler::parse_duration(value).map_err(|_| {
+        anyhow::anyhow!("invalid time {value:?}: expected RFC 3339 or a duration such as 24h")
+    })?;
+    Ok(chrono::Utc::now() - chrono::Duration::from_std(ago)?)
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
@@ -103,8 +498,49 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let cli = Cli::parse();
+    if let Some(path) = cli.config {
+        // Fail early on an explicit config that does not load, unless it is
+        // the file `init` is about to write.
+        if !matches!(cli.command, Commands::Init { .. }) {
+            config::NodeConfig::load_from_file(&path)?;
+        }
+        config::set_config_path(path);
+    }
 
     match cli.command {
+        Commands::Init {
+            profile,
+            non_interactive,
+            force,
+            bind,
+            registry_url,
+            providers,
+        } => {
+            use std::io::IsTerminal;
+            let path = config::config_path();
+            let options = init::InitOptions {
+                profile,
+                gateway_bind: bind,
+                registry_url,
+                providers,
+                force,
+            };
+            let stdin = std::io::stdin();
+            let (mut input, mut output) = (stdin.lock(), std::io::stderr());
+            let mut prompter = if non_interactive || !stdin.is_terminal() {
+                init::Prompter::NonInteractive
+            } else {
+                init::Prompter::Interactive {
+                    input: &mut input,
+                    output: &mut output,
+                }
+            };
+            let generated = init::run(&path, &options, &mut prompter)?;
+            println!("Wrote {profile} config to {}", path.display());
+            for env in init::missing_keys(&generated.providers) {
+                eprintln!("warning: {env} is not set");
+            }
+        }
         Commands::Status => {
             let cfg = config::NodeConfig::load_or_default();
             println!("ygn-core status: OK");
@@ -112,25 +548,94 @@ async fn main() -> anyhow::Result<()> {
             println!("  trust_tier: {}", cfg.trust_tier);
         }
         Commands::Gateway { bind } => {
-            gateway::run(&bind).await?;
+            let bind = bind.unwrap_or_else(|| config::NodeConfig::load_or_default().gateway_bind);
+            gateway::run(&bind, None).await?;
         }
         Commands::Config { action } => match action {
             ConfigAction::Schema => {
                 let schema = config::NodeConfig::json_schema();
                 println!("{schema}");
             }
+            ConfigAction::Validate { path } => {
+                let path = path.unwrap_or_else(config::config_path);
+                let errors = config::NodeConfig::validate_file(&path)?;
+                if errors.is_empty() {
+                    println!("OK");
+                } else {
+                    let engine = diagnostics::DiagnosticEngine::new();
+                    let source = path.display().to_string();
+                    let mut hint = None;
+                    for error in &errors {
+                        let diag = engine.configuration_error(&source, &error.to_string());
+                        println!("{:?}: {}", diag.category, diag.message);
+                        hint = diag.suggested_fix;
+                    }
+                    if let Some(hint) = hint {
+                        eprintln!("hint: {hint}");
+                    }
+                    std::process::exit(1);
+                }
+            }
         },
         Commands::Tools { action } => match action {
             ToolsAction::List => {
-                let mut tool_registry = tool::ToolRegistry::new();
-                tool_registry.register(Box::new(tool::EchoTool));
-                tool_registry.register(Box::new(hardware::HardwareTool::new()));
+                let mut tool_registry = local_tools();
+                let cfg = config::NodeConfig::load_or_default();
+                let clients =
+                    mcp_client::import_configured_tools(&cfg.mcp_servers, &mut tool_registry).await;
 
                 let specs = tool_registry.list();
                 println!("Registered tools ({}):", specs.len());
                 for spec in &specs {
                     println!("  - {} : {}", spec.name, spec.description);
                 }
+                for client in &clients {
+                    client.shutdown().await;
+                }
+            }
+            ToolsAction::History { tool, limit } => {
+                let log = open_tool_history()?;
+                let executions = log.list(tool.as_deref(), limit)?;
+                println!("Tool executions ({}):", executions.len());
+                for e in &executions {
+                    println!(
+                        "  {} {} [{}] {} {} ({} ms)",
+                        e.id,
+                        e.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        e.origin,
+                        e.tool_name,
+                        if e.success { "ok" } else { "failed" },
+                        e.duration_ms
+                    );
+                    println!("    args: {}", e.arguments);
+                }
+            }
+            ToolsAction::Replay { id } => {
+                let log = std::sync::Arc::new(open_tool_history()?);
+                let mut tool_registry = local_tools();
+                let cfg = config::NodeConfig::load_or_default();
+                let clients =
+                    mcp_client::import_configured_tools(&cfg.mcp_servers, &mut tool_registry).await;
+                tool_registry.set_history(log.clone(), ExecutionOrigin::Cli);
+
+                let policy = PolicyEngine::from_config(PolicyConfig::default());
+                let replay = log.replay(&id, &tool_registry, &policy).await;
+                for client in &clients {
+                    client.shutdown().await;
+                }
+                let replay = replay?;
+                if replay.is_identical() {
+                    println!(
+                        "Replay of {} ({}): output identical",
+                        id, replay.original.tool_name
+                    );
+                } else {
+                    println!(
+                        "Replay of {} ({}): output changed",
+                        id, replay.original.tool_name
+                    );
+                    print!("{}", replay.diff());
+                }
             }
         },
         Commands::Providers { action } => match action {
@@ -142,7 +647,8 @@ async fn main() -> anyhow::Result<()> {
                     let provider = registry.get(name).unwrap();
                     let caps = provider.capabilities();
                     println!(
-                        "  - {} (tool_calling={}, vision={}, streaming={})",
+                        "  - {} [{}] (tool_calling={}, vision={}, streaming={})",
+                        name,
                         provider.name(),
                         caps.native_tool_calling,
                         caps.vision,
@@ -151,31 +657,84 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
         },
-        Commands::Mcp => {
-            let server = mcp::McpServer::with_default_tools();
+        Commands::Mcp {
+            import_servers,
+            audit_log,
+            demo_tools,
+            policy,
+            proxy_to,
+        } => {
+            let mut tool_registry = mcp::McpServer::default_registry();
+            if demo_tools {
+                tool_registry.register(Box::new(tool::CountdownTool));
+            }
+            match skills::builtin_skills() {
+                Ok(skill_registry) => {
+                    let mut skill_tools = tool::ToolRegistry::new();
+                    skill_tools.register(Box::new(tool::EchoTool));
+                    tool_registry.register(Box::new(skills::SkillTool::new(
+                        skill_registry,
+                        skill_tools,
+                    )));
+                }
+                Err(e) => tracing::warn!(error = %e, "run_skill tool disabled"),
+            }
+            match open_tool_history() {
+                Ok(log) => {
+                    tool_registry.set_history(std::sync::Arc::new(log), ExecutionOrigin::Mcp)
+                }
+                Err(e) => tracing::warn!(error = %e, "tool history disabled"),
+            }
+            let cfg = config::NodeConfig::load_or_default();
+            if !cfg.hardware.simulated {
+                tool_registry.unregister("hardware");
+            }
+            // Off by default: a config listing ygn-core itself would make
+            // every child spawn another child.
+            let _clients = if import_servers {
+                mcp_client::import_configured_tools(&cfg.mcp_servers, &mut tool_registry).await
+            } else {
+                Vec::new()
+            };
+            for (from, to) in &cfg.tool_aliases {
+                if let Err(e) = tool_registry.alias(from, to) {
+                    tracing::warn!(alias = %from, error = %e, "skipping tool alias");
+                }
+            }
+            tool_registry.set_limiter(std::sync::Arc::new(ToolLimiter::new(cfg.tool_limits)));
+            tool_registry
+                .set_output_limits(std::sync::Arc::new(ToolOutputLimits::new(cfg.tool_output)));
+
+            let mut server = match policy {
+                Some(path) => {
+                    mcp::McpServer::with_policy(tool_registry, PolicyEngine::from_file(&path)?)
+                }
+                None => match (cfg.policy, cfg.policy_file) {
+                    (Some(policy), _) => mcp::McpServer::with_policy(
+                        tool_registry,
+                        PolicyEngine::from_config(policy),
+                    ),
+                    (None, Some(path)) => {
+                        mcp::McpServer::with_policy(tool_registry, PolicyEngine::from_file(&path)?)
+                    }
+                    (None, None) => mcp::McpServer::new(tool_registry),
+                },
+            };
+            if let Some(url) = proxy_to {
+                let mut proxy = McpProxy::new(&url)?;
+                if let Ok(key) = std::env::var("YGN_PROXY_API_KEY") {
+                    proxy = proxy.with_api_key(key);
+                }
+                server = server.with_proxy(std::sync::Arc::new(proxy));
+            }
+            if let Some(path) = audit_log {
+                server = server.with_audit_file(path);
+            }
             server.run_stdio()?;
         }
         Commands::Skills { action } => match action {
             SkillsAction::List => {
-                let mut skill_registry = skills::SkillRegistry::new();
-
-                // Register a sample "health-check" skill that uses the echo tool.
-                let health_check = skills::SkillDefinition {
-                    name: "health-check".to_string(),
-                    description: "Run a basic echo-based health check".to_string(),
-                    version: "1.0.0".to_string(),
-                    author: "ygn-core".to_string(),
-                    steps: vec![skills::SkillStep {
-                        tool_name: "echo".to_string(),
-                        arguments: serde_json::json!({"input": "health-ok"}),
-                        description: "Echo a health ping".to_string(),
-                        depends_on: vec![],
-                    }],
-                    tags: vec!["health".to_string(), "builtin".to_string()],
-                    created_at: chrono::Utc::now(),
-                };
-                skill_registry.register(health_check)?;
-
+                let skill_registry = skills::builtin_skills()?;
                 let all = skill_registry.list();
                 println!("Registered skills ({}):", all.len());
                 for skill in &all {
@@ -187,6 +746,362 @@ async fn main() -> anyhow::Result<()> {
                     println!("    steps: {}", skill.steps.len());
                 }
             }
+            SkillsAction::Schema => {
+                let schema = skills::manifest_schema();
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+            }
+            SkillsAction::Export { name, output } => {
+                let skill_registry = skills::builtin_skills()?;
+                let skill = skill_registry
+                    .get(&name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown skill '{name}'"))?;
+                match output {
+                    Some(path) => {
+                        let format = skills::ManifestFormat::from_path(&path);
+                        std::fs::write(&path, skill.to_manifest(format)?)?;
+                        println!("Exported skill '{name}' to {}", path.display());
+                    }
+                    None => print!("{}", skill.to_manifest(skills::ManifestFormat::Yaml)?),
+                }
+            }
+            SkillsAction::Import { file, force } => {
+                let text = std::fs::read_to_string(&file)?;
+                let skill = skills::SkillDefinition::from_manifest(&text)?;
+
+                let mut tool_registry = tool::ToolRegistry::new();
+                tool_registry.register(Box::new(tool::EchoTool));
+                tool_registry.register(Box::new(hardware::HardwareTool::new()));
+                let cfg = config::NodeConfig::load_or_default();
+                let clients =
+                    mcp_client::import_configured_tools(&cfg.mcp_servers, &mut tool_registry).await;
+
+                let executor = skills::SkillExecutor::new(&tool_registry);
+                let unknown = executor.unknown_tools(&skill);
+                let checked = if unknown.is_empty() || force {
+                    for (i, tool_name) in &unknown {
+                        eprintln!("warning: step {i} references unknown tool '{tool_name}'");
+                    }
+                    executor.validate_dependencies(&skill)
+                } else {
+                    executor
+                        .validate(&skill)
+                        .map_err(|e| anyhow::anyhow!("{e} (use --force to import anyway)"))
+                };
+                for client in &clients {
+                    client.shutdown().await;
+                }
+                checked?;
+
+                let dir = std::path::PathBuf::from(skills::default_skills_dir());
+                let stored = dir.join(format!("{}.yaml", skill.name));
+                if skills::builtin_skills()?.get(&skill.name).is_some() && !stored.exists() {
+                    anyhow::bail!("skill '{}' conflicts with a built-in skill", skill.name);
+                }
+                let path = skills::store_manifest(&dir, &skill)?;
+
+                println!(
+                    "Imported skill '{}' v{} ({} steps) to {}",
+                    skill.name,
+                    skill.version,
+                    skill.steps.len(),
+                    path.display()
+                );
+            }
+            SkillsAction::Run {
+                name,
+                dry_run,
+                json,
+            } => {
+                let skill_registry = skills::builtin_skills()?;
+                let skill = skill_registry
+                    .get(&name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown skill '{name}'"))?;
+                let tool_registry = local_tools();
+                let policy = configured_policy()?;
+                let executor = skills::SkillExecutor::new(&tool_registry).with_policy(&policy);
+                let plan = executor.plan(skill)?;
+                if dry_run {
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&plan)?);
+                    } else {
+                        print_plan(&plan);
+                    }
+                    if !plan.is_runnable() {
+                        std::process::exit(1);
+                    }
+                } else {
+                    if !plan.is_runnable() {
+                        anyhow::bail!("skill '{name}' has denied or unknown steps; see --dry-run");
+                    }
+                    let execution = executor.execute(skill).await;
+                    for step in &execution.step_results {
+                        println!(
+                            "step {} {}: {} {}",
+                            step.step_index,
+                            step.tool_name,
+                            if step.success { "ok" } else { "failed" },
+                            step.output
+                        );
+                    }
+                    if !execution.overall_success {
+                        anyhow::bail!("skill '{}' failed", skill.name);
+                    }
+                }
+            }
+            SkillsAction::Plan {
+                goal,
+                execute,
+                model,
+                max_steps,
+            } => {
+                let providers = ProviderRegistry::from_env();
+                let provider = providers
+                    .route(&model)
+                    .ok_or_else(|| anyhow::anyhow!("no provider available for model '{model}'"))?;
+                let tool_registry = local_tools();
+                let policy = PolicyEngine::from_config(PolicyConfig::default());
+                let skill = skill_planner::SkillPlanner::new(&model)
+                    .with_max_steps(max_steps)
+                    .with_policy(&policy)
+                    .plan(&goal, &tool_registry.list(), provider)
+                    .await?;
+                print!("{}", skill.to_manifest(skills::ManifestFormat::Yaml)?);
+
+                if execute {
+                    let executor = skills::SkillExecutor::new(&tool_registry);
+                    executor.validate(&skill)?;
+                    let execution = executor.execute(&skill).await;
+                    for step in &execution.step_results {
+                        println!(
+                            "step {} {}: {} {}",
+                            step.step_index,
+                            step.tool_name,
+                            if step.success { "ok" } else { "failed" },
+                            step.output
+                        );
+                    }
+                    if !execution.overall_success {
+                        anyhow::bail!("skill '{}' failed", skill.name);
+                    }
+                }
+            }
+        },
+        Commands::Memory { action } => match action {
+            MemoryAction::List {
+                category,
+                offset,
+                limit,
+                order,
+            } => {
+                let category = category.map(|c| {
+                    let Ok(category) = c.parse::<MemoryCategory>();
+                    category
+                });
+                let order = match order.as_str() {
+                    "created_at" => ListOrder::CreatedAt,
+                    _ => ListOrder::UpdatedAt,
+                };
+                let path = sqlite_memory::default_db_path();
+                if let Some(dir) = std::path::Path::new(&path).parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let memory = SqliteMemory::new(&path)?;
+                let total = memory.count(category.clone()).await?;
+                let entries = memory.list(category, offset, limit, order).await?;
+                println!("Memory entries ({} of {total}):", entries.len());
+                for entry in &entries {
+                    let timestamp = match order {
+                        ListOrder::UpdatedAt => entry.updated_at,
+                        ListOrder::CreatedAt => entry.created_at,
+                    };
+                    println!(
+                        "  [{}] {} {}: {}",
+                        entry.category,
+                        timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        entry.key,
+                        entry.content
+                    );
+                }
+            }
+        },
+        Commands::Schedule { action } => match action {
+            ScheduleAction::Add {
+                name,
+                skill,
+                every,
+                cron,
+                jitter,
+                remember,
+                disabled,
+            } => {
+                if skills::builtin_skills()?.get(&skill).is_none() {
+                    anyhow::bail!("unknown skill '{skill}'");
+                }
+                let schedule = match (every, cron) {
+                    (Some(every), _) => {
+                        let interval = scheduler::parse_duration(&every)?;
+                        if interval.is_zero() {
+                            anyhow::bail!("--every must be positive");
+                        }
+                        scheduler::Schedule::IntervalMs(interval.as_millis() as u64)
+                    }
+                    (None, Some(cron)) => scheduler::Schedule::Cron(cron.parse()?),
+                    (None, None) => anyhow::bail!("one of --every or --cron is required"),
+                };
+                let mut entry = scheduler::ScheduleEntry::new(name, skill, schedule);
+                if let Some(jitter) = jitter {
+                    entry.jitter_ms = scheduler::parse_duration(&jitter)?.as_millis() as u64;
+                }
+                entry.remember = remember;
+                entry.enabled = !disabled;
+                schedule_store()?.add(&entry)?;
+                println!(
+                    "Scheduled '{}' to run skill '{}' {}",
+                    entry.name, entry.skill, entry.schedule
+                );
+            }
+            ScheduleAction::List => {
+                let entries = schedule_store()?.list()?;
+                println!("Schedules ({}):", entries.len());
+                for entry in &entries {
+                    let last = match (entry.last_run_at, entry.last_success) {
+                        (Some(at), Some(success)) => format!(
+                            "last run {} ({})",
+                            at.to_rfc3339(),
+                            if success { "ok" } else { "failed" }
+                        ),
+                        _ => "never run".to_string(),
+                    };
+                    println!(
+                        "  - {} [{}] skill {} {}, jitter {}ms, {last}",
+                        entry.name,
+                        if entry.enabled { "enabled" } else { "disabled" },
+                        entry.skill,
+                        entry.schedule,
+                        entry.jitter_ms
+                    );
+                }
+            }
+            ScheduleAction::Remove { name } => {
+                if !schedule_store()?.remove(&name)? {
+                    anyhow::bail!("no schedule named '{name}'");
+                }
+                println!("Removed schedule '{name}'");
+            }
+            ScheduleAction::Enable { name } => {
+                if !schedule_store()?.set_enabled(&name, true)? {
+                    anyhow::bail!("no schedule named '{name}'");
+                }
+                println!("Enabled schedule '{name}'");
+            }
+            ScheduleAction::Disable { name } => {
+                if !schedule_store()?.set_enabled(&name, false)? {
+                    anyhow::bail!("no schedule named '{name}'");
+                }
+                println!("Disabled schedule '{name}'");
+            }
+        },
+        Commands::Backup { action } => match action {
+            BackupAction::Create { out } => {
+                let manifest = backup::create(&backup::default_stores(), &out)?;
+                println!(
+                    "Backed up {} stores to {}:",
+                    manifest.stores.len(),
+                    out.display()
+                );
+                for snapshot in &manifest.stores {
+                    println!(
+                        "  - {} -> {} ({} bytes)",
+                        snapshot.name, snapshot.file, snapshot.size_bytes
+                    );
+                }
+            }
+            BackupAction::Restore { from, only, force } => {
+                let restored =
+                    backup::restore(&from, &backup::default_stores(), only.as_deref(), force)?;
+                println!(
+                    "Restored {} stores: {}",
+                    restored.len(),
+                    restored.join(", ")
+                );
+            }
+        },
+        Commands::Repl { model } => {
+            use std::io::IsTerminal;
+            let path = sqlite_memory::default_db_path();
+            if let Some(dir) = std::path::Path::new(&path).parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut session = repl::Repl::new(
+                local_tools(),
+                skills::builtin_skills()?,
+                std::sync::Arc::new(SqliteMemory::new(&path)?),
+                ProviderRegistry::from_env(),
+                model,
+            )
+            .with_policy(configured_policy()?)
+            .with_usage(std::sync::Arc::new(usage::UsageTracker::open_default(
+                config::NodeConfig::load_or_default().usage,
+            )));
+            let mut out = std::io::stdout();
+            if std::io::stdin().is_terminal() {
+                let mut terminal =
+                    repl::Terminal::new(session.completions(), repl::default_history_path())?;
+                repl::run_session(&mut session, &mut terminal, &mut out).await?;
+            } else {
+                // Piped input runs as a script.
+                let mut lines: std::collections::VecDeque<String> =
+                    std::io::stdin().lines().collect::<Result<_, _>>()?;
+                repl::run_session(&mut session, &mut lines, &mut out).await?;
+            }
+        }
+        Commands::Uacp { action } => match action {
+            UacpAction::Decode => {
+                use std::io::BufRead;
+                for line in std::io::stdin().lock().lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let bytes = uacp::frame_bytes_from_text(&line)?;
+                    for msg in uacp::UacpCodec::decode_batch(&bytes)? {
+                        println!("{}", serde_json::to_string_pretty(&msg.to_json())?);
+                    }
+                }
+            }
+        },
+        Commands::Audit { action } => match action {
+            AuditAction::Export {
+                format,
+                log,
+                since,
+                until,
+                event_types,
+                tool,
+                out,
+            } => {
+                let path = log
+                    .or_else(|| config::NodeConfig::load_or_default().audit_log)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("no audit log: pass --log or set audit_log in the config")
+                    })?;
+                let file = std::fs::File::open(&path)
+                    .map_err(|e| anyhow::anyhow!("opening {}: {e}", path.display()))?;
+                let entries = audit::AuditLog::read_jsonl(std::io::BufReader::new(file))?;
+                let filter = audit::AuditFilter {
+                    since: since.as_deref().map(parse_time_bound).transpose()?,
+                    until: until.as_deref().map(parse_time_bound).transpose()?,
+                    event_types,
+                    tool_name: tool,
+                };
+                let written = match &out {
+                    Some(out) => entries.export(format, &filter, std::fs::File::create(out)?)?,
+                    None => entries.export(format, &filter, std::io::stdout().lock())?,
+                };
+                if let Some(out) = out {
+                    eprintln!("Exported {written} audit entries to {}", out.display());
+                }
+            }
         },
         Commands::Diagnose { source } => {
             use std::io::Read;
@@ -216,31 +1131,8 @@ async fn main() -> anyhow::Result<()> {
             }
             RegistryAction::SelfInfo => {
                 let cfg = config::NodeConfig::load_or_default();
-                let node_id = uuid::Uuid::new_v4().to_string();
-                let role = match cfg.node_role.as_str() {
-                    "brain" => registry::NodeRole::Brain,
-                    "core" => registry::NodeRole::Core,
-                    "brain-proxy" => registry::NodeRole::BrainProxy,
-                    _ => registry::NodeRole::Edge,
-                };
-                let trust = match cfg.trust_tier.as_str() {
-                    "untrusted" => registry::TrustTier::Untrusted,
-                    _ => registry::TrustTier::Trusted,
-                };
-                let info = registry::NodeInfo {
-                    node_id,
-                    role,
-                    endpoints: vec![registry::Endpoint {
-                        protocol: "http".to_string(),
-                        address: cfg.gateway_bind.clone(),
-                    }],
-                    trust_tier: trust,
-                    capabilities: vec!["echo".to_string(), "hardware".to_string()],
-                    last_seen: chrono::Utc::now(),
-                    metadata: serde_json::json!({
-                        "version": env!("CARGO_PKG_VERSION"),
-                    }),
-                };
+                let local = registry::LocalNode::load(&cfg, &config::node_id_path())?;
+                let info = local.info(&local_tools());
                 println!("{}", serde_json::to_string_pretty(&info)?);
             }
         },
@@ -2,14 +2,29 @@
 //!
 //! Provides a Matrix client integration as a Channel implementation.
 //! Uses a `MatrixTransport` trait to abstract the HTTP layer, enabling
-//! offline testing with `MockMatrixTransport`.
+//! offline testing with `MockMatrixTransport`; [`HttpMatrixTransport`]
+//! talks to a real homeserver.
+//!
+//! Messages are received by a `/sync` long-poll loop driven by
+//! [`Channel::listen`]: each response's `next_batch` token is passed as
+//! `since` to the next request, network failures back off exponentially
+//! and resume from the last token, and malformed events are skipped.
+//!
+//! Outgoing messages are rendered from a markdown subset into
+//! `org.matrix.custom.html`, reply to an incoming event when
+//! [`SendMessage::reply_to`] is set, and upload attachments through the media
+//! repository before sending them as `m.image` / `m.file` events.
+
+use std::collections::VecDeque;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures_util::Stream;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify, OnceCell};
 
-use crate::channel::{Channel, ChannelMessage, SendMessage};
+use crate::channel::{Attachment, Channel, ChannelMessage, SendMessage};
 
 // ---------------------------------------------------------------------------
 // Config
@@ -24,6 +39,17 @@ pub struct MatrixConfig {
     pub access_token: String,
     /// Room IDs to join and listen in. Empty means allow all.
     pub room_ids: Vec<String>,
+    /// Our own user ID, whose messages are ignored. Looked up with
+    /// `/account/whoami` when unset.
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Long-poll timeout for `/sync`, in seconds.
+    #[serde(default = "default_sync_timeout")]
+    pub sync_timeout_secs: u64,
+}
+
+fn default_sync_timeout() -> u64 {
+    30
 }
 
 // ---------------------------------------------------------------------------
@@ -45,6 +71,205 @@ pub struct MatrixMessage {
     pub timestamp: DateTime<Utc>,
 }
 
+impl MatrixMessage {
+    /// Convert a room timeline event into a message.
+    ///
+    /// Returns `Ok(None)` for events that are not `m.room.message`, and an
+    /// error for message events missing required fields.
+    pub fn from_event(room_id: &str, event: &serde_json::Value) -> anyhow::Result<Option<Self>> {
+        if event.get("type").and_then(|t| t.as_str()) != Some("m.room.message") {
+            return Ok(None);
+        }
+        let field = |name: &str| {
+            event
+                .get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("m.room.message event without '{name}'"))
+        };
+        let body = event
+            .pointer("/content/body")
+            .and_then(|b| b.as_str())
+            .ok_or_else(|| anyhow::anyhow!("m.room.message event without content.body"))?;
+        let timestamp = event
+            .get("origin_server_ts")
+            .and_then(|ts| ts.as_i64())
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(|| anyhow::anyhow!("m.room.message event without origin_server_ts"))?;
+        Ok(Some(Self {
+            event_id: field("event_id")?.to_string(),
+            room_id: room_id.to_string(),
+            sender: field("sender")?.to_string(),
+            body: body.to_string(),
+            timestamp,
+        }))
+    }
+
+    /// Render this message as a `m.room.message` timeline event.
+    pub fn to_event(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "m.room.message",
+            "event_id": self.event_id,
+            "sender": self.sender,
+            "origin_server_ts": self.timestamp.timestamp_millis(),
+            "content": { "msgtype": "m.text", "body": self.body },
+        })
+    }
+}
+
+/// One `/sync` response, reduced to what the channel consumes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncBatch {
+    /// Token to pass as `since` to the next sync.
+    pub next_batch: String,
+    /// Timeline events of joined rooms, as `(room_id, event)` in order.
+    pub events: Vec<(String, serde_json::Value)>,
+}
+
+impl SyncBatch {
+    /// Extract the token and joined-room timeline events from a raw
+    /// `/sync` response body.
+    pub fn from_response(body: &serde_json::Value) -> anyhow::Result<Self> {
+        let next_batch = body
+            .get("next_batch")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| anyhow::anyhow!("sync response without next_batch"))?
+            .to_string();
+        let mut events = Vec::new();
+        if let Some(rooms) = body.pointer("/rooms/join").and_then(|r| r.as_object()) {
+            for (room_id, room) in rooms {
+                let timeline = room
+                    .pointer("/timeline/events")
+                    .and_then(|e| e.as_array())
+                    .into_iter()
+                    .flatten();
+                events.extend(timeline.map(|event| (room_id.clone(), event.clone())));
+            }
+        }
+        Ok(Self { next_batch, events })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Formatting
+// ---------------------------------------------------------------------------
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render inline markdown: `` `code` ``, `**bold**`, `*italic*` / `_italic_`
+/// and `[text](url)`. Code spans are escaped but not otherwise formatted.
+fn render_inline(text: &str) -> String {
+    use std::sync::LazyLock;
+    static RULES: LazyLock<Vec<(regex::Regex, &str)>> = LazyLock::new(|| {
+        [
+            (r"\*\*(.+?)\*\*", "<strong>$1</strong>"),
+            (r"\*(.+?)\*", "<em>$1</em>"),
+            (r"\b_(.+?)_\b", "<em>$1</em>"),
+            (r"\[([^\]]+)\]\(([^)\s]+)\)", r#"<a href="$2">$1</a>"#),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (regex::Regex::new(pattern).unwrap(), replacement))
+        .collect()
+    });
+
+    let mut html = String::new();
+    for (i, segment) in text.split('`').enumerate() {
+        if i % 2 == 1 {
+            html.push_str(&format!("<code>{}</code>", escape_html(segment)));
+            continue;
+        }
+        let mut rendered = escape_html(segment);
+        for (pattern, replacement) in RULES.iter() {
+            rendered = pattern.replace_all(&rendered, *replacement).into_owned();
+        }
+        html.push_str(&rendered);
+    }
+    html
+}
+
+/// Render a markdown subset as Matrix HTML: inline formatting (see
+/// [`render_inline`]), `#` headings, `-` / `*` bullet lists and fenced code
+/// blocks. Returns `None` when the text has no formatting, so plain messages
+/// are sent without a `formatted_body`.
+pub fn markdown_to_html(text: &str) -> Option<String> {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut items: Vec<String> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    fn flush(blocks: &mut Vec<String>, lines: &mut Vec<String>, items: &mut Vec<String>) {
+        if !lines.is_empty() {
+            blocks.push(lines.join("<br>"));
+            lines.clear();
+        }
+        if !items.is_empty() {
+            blocks.push(format!("<ul>{}</ul>", items.join("")));
+            items.clear();
+        }
+    }
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match code.take() {
+                Some(body) => blocks.push(format!(
+                    "<pre><code>{}</code></pre>",
+                    escape_html(&body.join("\n"))
+                )),
+                None => {
+                    flush(&mut blocks, &mut lines, &mut items);
+                    code = Some(Vec::new());
+                }
+            }
+        } else if let Some(body) = code.as_mut() {
+            body.push(line);
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            if !lines.is_empty() {
+                flush(&mut blocks, &mut lines, &mut Vec::new());
+            }
+            items.push(format!("<li>{}</li>", render_inline(item)));
+        } else if let Some((level, heading)) = heading(line) {
+            flush(&mut blocks, &mut lines, &mut items);
+            blocks.push(format!("<h{level}>{}</h{level}>", render_inline(heading)));
+        } else {
+            if !items.is_empty() {
+                flush(&mut blocks, &mut Vec::new(), &mut items);
+            }
+            lines.push(render_inline(line));
+        }
+    }
+    if let Some(body) = code {
+        // Unterminated fence: keep the text as it was written.
+        lines.push(escape_html(&format!("```\n{}", body.join("\n"))).replace('\n', "<br>"));
+    }
+    flush(&mut blocks, &mut lines, &mut items);
+
+    let html = blocks.join("");
+    let plain = escape_html(text).replace('\n', "<br>");
+    (html != plain).then_some(html)
+}
+
+/// Split a `#`-style heading into its level and text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line.get(level..)?.strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text))
+}
+
+/// Content of a text `m.room.message`, with a `formatted_body` when the
+/// text uses markdown.
+pub fn text_content(body: &str) -> serde_json::Value {
+    let mut content = serde_json::json!({ "msgtype": "m.text", "body": body });
+    if let Some(html) = markdown_to_html(body) {
+        content["format"] = "org.matrix.custom.html".into();
+        content["formatted_body"] = html.into();
+    }
+    content
+}
+
 // ---------------------------------------------------------------------------
 // Transport trait
 // ---------------------------------------------------------------------------
@@ -52,11 +277,167 @@ pub struct MatrixMessage {
 /// Abstracts the HTTP layer for Matrix Client-Server API calls.
 #[async_trait]
 pub trait MatrixTransport: Send + Sync {
-    /// Send a text message to the given room.
-    async fn send(&self, room_id: &str, body: &str) -> anyhow::Result<()>;
+    /// Send an `m.room.message` event with the given content to a room.
+    async fn send_event(&self, room_id: &str, content: &serde_json::Value) -> anyhow::Result<()>;
 
-    /// Receive pending messages via long-poll / sync.
-    async fn receive(&self) -> anyhow::Result<Vec<MatrixMessage>>;
+    /// Send a plain text message to the given room.
+    async fn send(&self, room_id: &str, body: &str) -> anyhow::Result<()> {
+        self.send_event(
+            room_id,
+            &serde_json::json!({ "msgtype": "m.text", "body": body }),
+        )
+        .await
+    }
+
+    /// Upload a file to the media repository, returning its `mxc://` URI.
+    async fn upload(
+        &self,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+    ) -> anyhow::Result<String>;
+
+    /// Long-poll `/sync` for events after `since` (`None` for the initial
+    /// sync), waiting up to `timeout` for new events.
+    async fn sync(&self, since: Option<&str>, timeout: Duration) -> anyhow::Result<SyncBatch>;
+
+    /// The user ID the access token belongs to.
+    async fn whoami(&self) -> anyhow::Result<String>;
+}
+
+// ---------------------------------------------------------------------------
+// HTTP transport
+// ---------------------------------------------------------------------------
+
+/// A [`MatrixTransport`] for a real homeserver's Client-Server API (v3).
+#[derive(Debug, Clone)]
+pub struct HttpMatrixTransport {
+    homeserver_url: String,
+    access_token: String,
+    client: reqwest::Client,
+}
+
+impl HttpMatrixTransport {
+    /// Create a transport for the homeserver and token in `config`.
+    pub fn new(config: &MatrixConfig) -> Self {
+        Self {
+            homeserver_url: config.homeserver_url.trim_end_matches('/').to_string(),
+            access_token: config.access_token.clone(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Build a client API URL from percent-encoded path segments.
+    fn url(&self, segments: &[&str]) -> anyhow::Result<reqwest::Url> {
+        self.api_url("client", segments)
+    }
+
+    /// Build a `/_matrix/<api>/v3/...` URL from percent-encoded path segments.
+    fn api_url(&self, api: &str, segments: &[&str]) -> anyhow::Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(&self.homeserver_url)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("invalid homeserver URL: {}", self.homeserver_url))?
+            .extend(["_matrix", api, "v3"])
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn json(resp: reqwest::Response) -> anyhow::Result<serde_json::Value> {
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = body
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("no error message");
+            anyhow::bail!("homeserver returned {status}: {message}");
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl MatrixTransport for HttpMatrixTransport {
+    async fn send_event(&self, room_id: &str, content: &serde_json::Value) -> anyhow::Result<()> {
+        let txn_id = uuid::Uuid::new_v4().to_string();
+        let url = self.url(&["rooms", room_id, "send", "m.room.message", &txn_id])?;
+        let resp = self
+            .client
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(content)
+            .send()
+            .await?;
+        Self::json(resp).await?;
+        Ok(())
+    }
+
+    async fn upload(
+        &self,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+    ) -> anyhow::Result<String> {
+        let mut url = self.api_url("media", &["upload"])?;
+        url.query_pairs_mut().append_pair("filename", filename);
+        let resp = self
+            .client
+            .post(url)
+            .bearer_auth(&self.access_token)
+            .header(reqwest::header::CONTENT_TYPE, mime_type)
+            .body(data)
+            .send()
+            .await?;
+        Self::json(resp)
+            .await?
+            .get("content_uri")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("upload response without content_uri"))
+    }
+
+    async fn sync(&self, since: Option<&str>, timeout: Duration) -> anyhow::Result<SyncBatch> {
+        let mut url = self.url(&["sync"])?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("timeout", &timeout.as_millis().to_string());
+            match since {
+                Some(token) => {
+                    query.append_pair("since", token);
+                }
+                // The initial sync only establishes a token; replaying room
+                // history would answer old messages again.
+                None => {
+                    query.append_pair("filter", r#"{"room":{"timeline":{"limit":0}}}"#);
+                }
+            }
+        }
+        let resp = self
+            .client
+            .get(url)
+            .bearer_auth(&self.access_token)
+            // Leave the server room to answer a full long-poll.
+            .timeout(timeout + Duration::from_secs(10))
+            .send()
+            .await?;
+        SyncBatch::from_response(&Self::json(resp).await?)
+    }
+
+    async fn whoami(&self) -> anyhow::Result<String> {
+        let url = self.url(&["account", "whoami"])?;
+        let resp = self
+            .client
+            .get(url)
+            .bearer_auth(&self.access_token)
+            .send()
+            .await?;
+        Self::json(resp)
+            .await?
+            .get("user_id")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("whoami response without user_id"))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -64,9 +445,16 @@ pub trait MatrixTransport: Send + Sync {
 // ---------------------------------------------------------------------------
 
 /// A mock transport that stores messages in memory for testing.
+///
+/// `sync` returns queued batches (or errors) in order, and otherwise waits
+/// until one is queued, like a homeserver long-poll with nothing to report.
 pub struct MockMatrixTransport {
-    incoming: Mutex<Vec<MatrixMessage>>,
-    sent: Mutex<Vec<(String, String)>>,
+    user_id: String,
+    syncs: Mutex<VecDeque<anyhow::Result<SyncBatch>>>,
+    queued: Notify,
+    since: Mutex<Vec<Option<String>>>,
+    sent: Mutex<Vec<(String, serde_json::Value)>>,
+    uploads: Mutex<Vec<(String, String, Vec<u8>)>>,
 }
 
 impl Default for MockMatrixTransport {
@@ -76,39 +464,108 @@ impl Default for MockMatrixTransport {
 }
 
 impl MockMatrixTransport {
-    /// Create a new empty mock transport.
+    /// Create a new empty mock transport for user `@ygn:localhost`.
     pub fn new() -> Self {
+        Self::with_user_id("@ygn:localhost")
+    }
+
+    /// Create a new empty mock transport whose `whoami` is `user_id`.
+    pub fn with_user_id(user_id: &str) -> Self {
         Self {
-            incoming: Mutex::new(Vec::new()),
+            user_id: user_id.to_string(),
+            syncs: Mutex::new(VecDeque::new()),
+            queued: Notify::new(),
+            since: Mutex::new(Vec::new()),
             sent: Mutex::new(Vec::new()),
+            uploads: Mutex::new(Vec::new()),
         }
     }
 
-    /// Queue an incoming message that will be returned by `receive`.
+    /// Queue a sync response.
+    pub async fn queue_sync(&self, batch: SyncBatch) {
+        self.syncs.lock().await.push_back(Ok(batch));
+        self.queued.notify_one();
+    }
+
+    /// Queue a sync failure, e.g. a network error.
+    pub async fn queue_sync_error(&self, message: &str) {
+        self.syncs
+            .lock()
+            .await
+            .push_back(Err(anyhow::anyhow!(message.to_string())));
+        self.queued.notify_one();
+    }
+
+    /// Queue an incoming message, delivered in its own sync response.
     pub async fn queue_message(&self, msg: MatrixMessage) {
-        self.incoming.lock().await.push(msg);
+        let next_batch = format!("mock-{}", msg.event_id);
+        let events = vec![(msg.room_id.clone(), msg.to_event())];
+        self.queue_sync(SyncBatch { next_batch, events }).await;
+    }
+
+    /// The `since` token of every sync request so far, in order.
+    pub async fn sync_requests(&self) -> Vec<Option<String>> {
+        self.since.lock().await.clone()
     }
 
     /// Return a snapshot of all sent messages as `(room_id, body)`.
     pub async fn sent_messages(&self) -> Vec<(String, String)> {
+        self.sent
+            .lock()
+            .await
+            .iter()
+            .map(|(room, content)| {
+                let body = content["body"].as_str().unwrap_or_default();
+                (room.clone(), body.to_string())
+            })
+            .collect()
+    }
+
+    /// Return a snapshot of all sent events as `(room_id, content)`.
+    pub async fn sent_events(&self) -> Vec<(String, serde_json::Value)> {
         self.sent.lock().await.clone()
     }
+
+    /// Return a snapshot of all uploads as `(filename, mime_type, data)`.
+    pub async fn uploads(&self) -> Vec<(String, String, Vec<u8>)> {
+        self.uploads.lock().await.clone()
+    }
 }
 
 #[async_trait]
 impl MatrixTransport for MockMatrixTransport {
-    async fn send(&self, room_id: &str, body: &str) -> anyhow::Result<()> {
+    async fn send_event(&self, room_id: &str, content: &serde_json::Value) -> anyhow::Result<()> {
         self.sent
             .lock()
             .await
-            .push((room_id.to_string(), body.to_string()));
+            .push((room_id.to_string(), content.clone()));
         Ok(())
     }
 
-    async fn receive(&self) -> anyhow::Result<Vec<MatrixMessage>> {
-        let mut queue = self.incoming.lock().await;
-        let messages = queue.drain(..).collect();
-        Ok(messages)
+    async fn upload(
+        &self,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+    ) -> anyhow::Result<String> {
+        let mut uploads = self.uploads.lock().await;
+        uploads.push((filename.to_string(), mime_type.to_string(), data));
+        Ok(format!("mxc://localhost/upload{}", uploads.len()))
+    }
+
+    async fn sync(&self, since: Option<&str>, _timeout: Duration) -> anyhow::Result<SyncBatch> {
+        self.since.lock().await.push(since.map(String::from));
+        loop {
+            let queued = self.queued.notified();
+            if let Some(next) = self.syncs.lock().await.pop_front() {
+                return next;
+            }
+            queued.await;
+        }
+    }
+
+    async fn whoami(&self) -> anyhow::Result<String> {
+        Ok(self.user_id.clone())
     }
 }
 
@@ -116,16 +573,64 @@ impl MatrixTransport for MockMatrixTransport {
 // MatrixChannel
 // ---------------------------------------------------------------------------
 
+/// Delay before retrying a failed sync; doubled per consecutive failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound for the sync retry delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
 /// A `Channel` implementation backed by the Matrix Client-Server API.
 pub struct MatrixChannel {
     config: MatrixConfig,
     transport: Box<dyn MatrixTransport>,
+    own_user_id: OnceCell<String>,
+    next_batch: Mutex<Option<String>>,
+    pending: Mutex<VecDeque<MatrixMessage>>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
 }
 
 impl MatrixChannel {
     /// Create a new Matrix channel with the given config and transport.
     pub fn new(config: MatrixConfig, transport: Box<dyn MatrixTransport>) -> Self {
-        Self { config, transport }
+        Self {
+            config,
+            transport,
+            own_user_id: OnceCell::new(),
+            next_batch: Mutex::new(None),
+            pending: Mutex::new(VecDeque::new()),
+            initial_backoff: INITIAL_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+        }
+    }
+
+    /// Set the sync retry delays: `initial` after the first failure,
+    /// doubling up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Resume syncing after `token`, e.g. one saved from [`Self::sync_token`].
+    pub fn with_sync_token(mut self, token: impl Into<String>) -> Self {
+        self.next_batch = Mutex::new(Some(token.into()));
+        self
+    }
+
+    /// The `next_batch` token of the last successful sync.
+    pub async fn sync_token(&self) -> Option<String> {
+        self.next_batch.lock().await.clone()
+    }
+
+    /// Received messages as a stream; see [`Channel::listen`].
+    pub fn messages(&self) -> impl Stream<Item = anyhow::Result<ChannelMessage>> + '_ {
+        futures_util::stream::unfold(self, |channel| async move {
+            channel
+                .listen()
+                .await
+                .transpose()
+                .map(|item| (item, channel))
+        })
     }
 
     /// Check whether a room ID is allowed by the configured room list.
@@ -135,6 +640,75 @@ impl MatrixChannel {
         }
         self.config.room_ids.iter().any(|id| id == room_id)
     }
+
+    async fn own_user_id(&self) -> anyhow::Result<&str> {
+        self.own_user_id
+            .get_or_try_init(|| async {
+                match &self.config.user_id {
+                    Some(id) => Ok(id.clone()),
+                    None => self.transport.whoami().await,
+                }
+            })
+            .await
+            .map(String::as_str)
+    }
+
+    /// Upload an attachment and build the `m.image` / `m.file` content
+    /// referencing it.
+    async fn upload_attachment(
+        &self,
+        attachment: &Attachment,
+    ) -> anyhow::Result<serde_json::Value> {
+        use base64::Engine;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(&attachment.data)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "attachment '{}' is not valid base64: {e}",
+                    attachment.filename
+                )
+            })?;
+        let size = data.len();
+        let uri = self
+            .transport
+            .upload(&attachment.filename, &attachment.mime_type, data)
+            .await?;
+        let msgtype = if attachment.mime_type.starts_with("image/") {
+            "m.image"
+        } else {
+            "m.file"
+        };
+        Ok(serde_json::json!({
+            "msgtype": msgtype,
+            "body": attachment.filename,
+            "url": uri,
+            "info": { "mimetype": attachment.mime_type, "size": size },
+        }))
+    }
+
+    /// Run one sync and queue the messages it carries for delivery.
+    async fn sync_once(&self) -> anyhow::Result<()> {
+        let own = self.own_user_id().await?.to_string();
+        let since = self.next_batch.lock().await.clone();
+        let timeout = Duration::from_secs(self.config.sync_timeout_secs);
+        let batch = self.transport.sync(since.as_deref(), timeout).await?;
+
+        let mut pending = self.pending.lock().await;
+        for (room_id, event) in &batch.events {
+            if !self.is_room_allowed(room_id) {
+                continue;
+            }
+            match MatrixMessage::from_event(room_id, event) {
+                Ok(Some(msg)) if msg.sender != own => pending.push_back(msg),
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(room_id = %room_id, error = %e, "skipping malformed Matrix event")
+                }
+            }
+        }
+        *self.next_batch.lock().await = Some(batch.next_batch);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -143,41 +717,64 @@ impl Channel for MatrixChannel {
         "matrix"
     }
 
+    /// Send the text (markdown rendered as HTML, replying to `reply_to`
+    /// if set) followed by one event per attachment.
     async fn send(&self, message: SendMessage) -> anyhow::Result<()> {
         let room_id = message
             .metadata
             .get("room_id")
             .and_then(|v| v.as_str())
             .unwrap_or("!unknown:localhost");
-        self.transport.send(room_id, &message.content).await
+
+        let mut contents = Vec::new();
+        if !message.content.is_empty() || message.attachments.is_empty() {
+            contents.push(text_content(&message.content));
+        }
+        for attachment in &message.attachments {
+            contents.push(self.upload_attachment(attachment).await?);
+        }
+        if let (Some(event_id), Some(first)) = (&message.reply_to, contents.first_mut()) {
+            first["m.relates_to"] = serde_json::json!({
+                "m.in_reply_to": { "event_id": event_id }
+            });
+        }
+        for content in &contents {
+            self.transport.send_event(room_id, content).await?;
+        }
+        Ok(())
     }
 
+    /// Wait for the next message, syncing as needed. Sync failures are
+    /// retried with exponential backoff, so this only returns once a
+    /// message arrives.
     async fn listen(&self) -> anyhow::Result<Option<ChannelMessage>> {
-        let messages = self.transport.receive().await?;
-
-        for msg in messages {
-            if !self.is_room_allowed(&msg.room_id) {
-                continue;
+        let mut backoff = self.initial_backoff;
+        loop {
+            if let Some(msg) = self.pending.lock().await.pop_front() {
+                return Ok(Some(ChannelMessage {
+                    channel: "matrix".to_string(),
+                    sender: msg.sender,
+                    content: msg.body,
+                    timestamp: msg.timestamp,
+                    metadata: serde_json::json!({
+                        "event_id": msg.event_id,
+                        "room_id": msg.room_id,
+                    }),
+                }));
+            }
+            match self.sync_once().await {
+                Ok(()) => backoff = self.initial_backoff,
+                Err(e) => {
+                    tracing::warn!(error = %e, retry_in = ?backoff, "Matrix sync failed");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                }
             }
-            return Ok(Some(ChannelMessage {
-                channel: "matrix".to_string(),
-                sender: msg.sender,
-                content: msg.body,
-                timestamp: msg.timestamp,
-                metadata: serde_json::json!({
-                    "event_id": msg.event_id,
-                    "room_id": msg.room_id,
-                }),
-            }));
         }
-
-        Ok(None)
     }
 
     async fn health_check(&self) -> anyhow::Result<bool> {
-        // In mock/test scenarios this always succeeds.
-        // A real implementation would call /_matrix/client/versions.
-        Ok(true)
+        Ok(self.transport.whoami().await.is_ok())
     }
 }
 
@@ -195,6 +792,8 @@ mod tests {
             homeserver_url: "https://matrix.example.org".to_string(),
             access_token: "test-access-token".to_string(),
             room_ids,
+            user_id: None,
+            sync_timeout_secs: 30,
         }
     }
 
@@ -216,12 +815,29 @@ mod tests {
 
     #[async_trait]
     impl MatrixTransport for ArcTransport {
-        async fn send(&self, room_id: &str, body: &str) -> anyhow::Result<()> {
-            self.0.send(room_id, body).await
+        async fn send_event(
+            &self,
+            room_id: &str,
+            content: &serde_json::Value,
+        ) -> anyhow::Result<()> {
+            self.0.send_event(room_id, content).await
+        }
+
+        async fn upload(
+            &self,
+            filename: &str,
+            mime_type: &str,
+            data: Vec<u8>,
+        ) -> anyhow::Result<String> {
+            self.0.upload(filename, mime_type, data).await
         }
 
-        async fn receive(&self) -> anyhow::Result<Vec<MatrixMessage>> {
-            self.0.receive().await
+        async fn sync(&self, since: Option<&str>, timeout: Duration) -> anyhow::Result<SyncBatch> {
+            self.0.sync(since, timeout).await
+        }
+
+        async fn whoami(&self) -> anyhow::Result<String> {
+            self.0.whoami().await
         }
     }
 
@@ -264,15 +880,16 @@ mod tests {
     #[tokio::test]
     async fn send_delegates_to_transport() {
         let shared = Arc::new(MockMatrixTransport::new());
-        let channel = MatrixChannel {
-            config: make_config(vec![]),
-            transport: Box::new(ArcTransport(Arc::clone(&shared))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec![]),
+            Box::new(ArcTransport(Arc::clone(&shared))),
+        );
 
         let msg = SendMessage {
             content: "Hello Matrix!".to_string(),
             recipient: None,
             metadata: serde_json::json!({"room_id": "!room-42:matrix.org"}),
+            ..Default::default()
         };
         channel.send(msg).await.unwrap();
 
@@ -285,15 +902,16 @@ mod tests {
     #[tokio::test]
     async fn send_uses_default_room_when_missing() {
         let shared = Arc::new(MockMatrixTransport::new());
-        let channel = MatrixChannel {
-            config: make_config(vec![]),
-            transport: Box::new(ArcTransport(Arc::clone(&shared))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec![]),
+            Box::new(ArcTransport(Arc::clone(&shared))),
+        );
 
         let msg = SendMessage {
             content: "fallback".to_string(),
             recipient: None,
             metadata: serde_json::json!({}),
+            ..Default::default()
         };
         channel.send(msg).await.unwrap();
 
@@ -313,10 +931,10 @@ mod tests {
             ))
             .await;
 
-        let channel = MatrixChannel {
-            config: make_config(vec![]),
-            transport: Box::new(ArcTransport(Arc::clone(&transport))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec![]),
+            Box::new(ArcTransport(Arc::clone(&transport))),
+        );
 
         let result = channel.listen().await.unwrap();
         assert!(result.is_some());
@@ -346,10 +964,10 @@ mod tests {
             ))
             .await;
 
-        let channel = MatrixChannel {
-            config: make_config(vec!["!allowed:matrix.org".to_string()]),
-            transport: Box::new(ArcTransport(Arc::clone(&transport))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec!["!allowed:matrix.org".to_string()]),
+            Box::new(ArcTransport(Arc::clone(&transport))),
+        );
 
         let result = channel.listen().await.unwrap();
         assert!(result.is_some());
@@ -368,10 +986,10 @@ mod tests {
             ))
             .await;
 
-        let channel = MatrixChannel {
-            config: make_config(vec![]),
-            transport: Box::new(ArcTransport(Arc::clone(&transport))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec![]),
+            Box::new(ArcTransport(Arc::clone(&transport))),
+        );
 
         let result = channel.listen().await.unwrap();
         assert!(result.is_some());
@@ -379,7 +997,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn listen_returns_none_when_all_filtered() {
+    async fn listen_waits_when_all_filtered() {
         let transport = Arc::new(MockMatrixTransport::new());
         transport
             .queue_message(make_message(
@@ -390,13 +1008,14 @@ mod tests {
             ))
             .await;
 
-        let channel = MatrixChannel {
-            config: make_config(vec!["!allowed:matrix.org".to_string()]),
-            transport: Box::new(ArcTransport(Arc::clone(&transport))),
-        };
+        let channel = MatrixChannel::new(
+            make_config(vec!["!allowed:matrix.org".to_string()]),
+            Box::new(ArcTransport(Arc::clone(&transport))),
+        );
 
-        let result = channel.listen().await.unwrap();
-        assert!(result.is_none());
+        // Nothing allowed arrives, so listen keeps long-polling.
+        let result = tokio::time::timeout(Duration::from_millis(50), channel.listen()).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -434,4 +1053,349 @@ mod tests {
         assert_eq!(round.event_id, "$evt1");
         assert_eq!(round.body, "test content");
     }
+
+    fn batch(next_batch: &str, events: Vec<(&str, serde_json::Value)>) -> SyncBatch {
+        SyncBatch {
+            next_batch: next_batch.to_string(),
+            events: events
+                .into_iter()
+                .map(|(room, event)| (room.to_string(), event))
+                .collect(),
+        }
+    }
+
+    fn text_event(event_id: &str, sender: &str, body: &str) -> serde_json::Value {
+        make_message(event_id, "!r:mx.org", sender, body).to_event()
+    }
+
+    fn fast_channel(transport: &Arc<MockMatrixTransport>, config: MatrixConfig) -> MatrixChannel {
+        MatrixChannel::new(config, Box::new(ArcTransport(Arc::clone(transport))))
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(4))
+    }
+
+    #[tokio::test]
+    async fn sync_loop_resumes_from_last_token_after_errors() {
+        let transport = Arc::new(MockMatrixTransport::new());
+        transport
+            .queue_sync(batch(
+                "t1",
+                vec![("!r:mx.org", text_event("$1", "@alice:mx.org", "one"))],
+            ))
+            .await;
+        transport.queue_sync_error("connection reset").await;
+        transport.queue_sync_error("connection reset").await;
+        transport.queue_sync(batch("t2", vec![])).await;
+        transport
+            .queue_sync(batch(
+                "t3",
+                vec![("!r:mx.org", text_event("$2", "@alice:mx.org", "two"))],
+            ))
+            .await;
+        let channel = fast_channel(&transport, make_config(vec![]));
+
+        assert_eq!(channel.listen().await.unwrap().unwrap().content, "one");
+        assert_eq!(channel.sync_token().await.as_deref(), Some("t1"));
+        assert_eq!(channel.listen().await.unwrap().unwrap().content, "two");
+        assert_eq!(channel.sync_token().await.as_deref(), Some("t3"));
+
+        let since = transport.sync_requests().await;
+        let since: Vec<Option<&str>> = since.iter().map(Option::as_deref).collect();
+        assert_eq!(
+            since,
+            vec![None, Some("t1"), Some("t1"), Some("t1"), Some("t2")]
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_token_can_be_restored() {
+        let transport = Arc::new(MockMatrixTransport::new());
+        transport
+            .queue_sync(batch(
+                "t9",
+                vec![("!r:mx.org", text_event("$1", "@alice:mx.org", "hi"))],
+            ))
+            .await;
+        let channel = fast_channel(&transport, make_config(vec![])).with_sync_token("t8");
+        channel.listen().await.unwrap().unwrap();
+        assert_eq!(
+            transport.sync_requests().await,
+            vec![Some("t8".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_skips_own_malformed_and_non_message_events() {
+        let transport = Arc::new(MockMatrixTransport::with_user_id("@bot:mx.org"));
+        let member = serde_json::json!({
+            "type": "m.room.member",
+            "event_id": "$m",
+            "sender": "@alice:mx.org",
+            "content": { "membership": "join" },
+        });
+        let malformed = serde_json::json!({
+            "type": "m.room.message",
+            "event_id": "$bad",
+            "sender": "@alice:mx.org",
+            "content": { "msgtype": "m.image" },
+        });
+        transport
+            .queue_sync(batch(
+                "t1",
+                vec![
+                    ("!r:mx.org", text_event("$own", "@bot:mx.org", "echo")),
+                    ("!r:mx.org", member),
+                    ("!r:mx.org", malformed),
+                    ("!r:mx.org", text_event("$ok", "@alice:mx.org", "real")),
+                ],
+            ))
+            .await;
+        let channel = fast_channel(&transport, make_config(vec![]));
+
+        let msg = channel.listen().await.unwrap().unwrap();
+        assert_eq!(msg.content, "real");
+        assert_eq!(msg.metadata["event_id"], "$ok");
+        assert!(channel.pending.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn configured_user_id_overrides_whoami() {
+        let transport = Arc::new(MockMatrixTransport::with_user_id("@other:mx.org"));
+        transport
+            .queue_sync(batch(
+                "t1",
+                vec![
+                    ("!r:mx.org", text_event("$1", "@bot:mx.org", "mine")),
+                    ("!r:mx.org", text_event("$2", "@other:mx.org", "theirs")),
+                ],
+            ))
+            .await;
+        let mut config = make_config(vec![]);
+        config.user_id = Some("@bot:mx.org".to_string());
+        let channel = fast_channel(&transport, config);
+        assert_eq!(channel.listen().await.unwrap().unwrap().content, "theirs");
+    }
+
+    #[tokio::test]
+    async fn messages_stream_yields_in_order() {
+        use futures_util::StreamExt;
+
+        let transport = Arc::new(MockMatrixTransport::new());
+        transport
+            .queue_sync(batch(
+                "t1",
+                vec![
+                    ("!r:mx.org", text_event("$1", "@alice:mx.org", "a")),
+                    ("!r:mx.org", text_event("$2", "@alice:mx.org", "b")),
+                ],
+            ))
+            .await;
+        let channel = fast_channel(&transport, make_config(vec![]));
+        let received: Vec<String> = channel
+            .messages()
+            .take(2)
+            .map(|m| m.unwrap().content)
+            .collect()
+            .await;
+        assert_eq!(received, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn sync_batch_from_response() {
+        let body = serde_json::json!({
+            "next_batch": "s72595_4483_1934",
+            "rooms": {
+                "join": {
+                    "!a:mx.org": {
+                        "timeline": { "events": [text_event("$1", "@alice:mx.org", "hi")] }
+                    },
+                    "!b:mx.org": { "state": { "events": [] } }
+                }
+            }
+        });
+        let batch = SyncBatch::from_response(&body).unwrap();
+        assert_eq!(batch.next_batch, "s72595_4483_1934");
+        assert_eq!(batch.events.len(), 1);
+        assert_eq!(batch.events[0].0, "!a:mx.org");
+
+        let msg = MatrixMessage::from_event("!a:mx.org", &batch.events[0].1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg.body, "hi");
+
+        assert!(SyncBatch::from_response(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn http_transport_encodes_room_ids() {
+        let transport = HttpMatrixTransport::new(&make_config(vec![]));
+        let url = transport
+            .url(&["rooms", "!room/1:mx.org", "send", "m.room.message", "t1"])
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://matrix.example.org/_matrix/client/v3/rooms/!room%2F1:mx.org/send/m.room.message/t1"
+        );
+    }
+
+    #[test]
+    fn markdown_subset_renders_html() {
+        assert_eq!(markdown_to_html("just text\nover lines"), None);
+        assert_eq!(markdown_to_html("a < b"), None);
+        assert_eq!(
+            markdown_to_html("**bold**, *it* and `a<b`").as_deref(),
+            Some("<strong>bold</strong>, <em>it</em> and <code>a&lt;b</code>")
+        );
+        assert_eq!(
+            markdown_to_html("# Report\n- ok: [log](https://x.org/l)\n- `2` failed\nend")
+                .as_deref(),
+            Some(
+                "<h1>Report</h1><ul><li>ok: <a href=\"https://x.org/l\">log</a></li>\
+                 <li><code>2</code> failed</li></ul>end"
+            )
+        );
+        assert_eq!(
+            markdown_to_html("run:\n```\ncargo test <x>\n```").as_deref(),
+            Some("run:<pre><code>cargo test &lt;x&gt;</code></pre>")
+        );
+        assert_eq!(markdown_to_html("snake_case_name"), None);
+    }
+
+    #[tokio::test]
+    async fn send_formatted_text() {
+        let transport = Arc::new(MockMatrixTransport::new());
+        let channel = fast_channel(&transport, make_config(vec![]));
+        channel
+            .send(SendMessage {
+                content: "Build **failed** in `core`".to_string(),
+                metadata: serde_json::json!({ "room_id": "!r:mx.org" }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let sent = transport.sent_events().await;
+        assert_eq!(
+            sent,
+            vec![(
+                "!r:mx.org".to_string(),
+                serde_json::json!({
+                    "msgtype": "m.text",
+                    "body": "Build **failed** in `core`",
+                    "format": "org.matrix.custom.html",
+                    "formatted_body": "Build <strong>failed</strong> in <code>core</code>",
+                })
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_reply_to_incoming_event() {
+        let transport = Arc::new(MockMatrixTransport::new());
+        let channel = fast_channel(&transport, make_config(vec![]));
+        channel
+            .send(SendMessage {
+                content: "pong".to_string(),
+                metadata: serde_json::json!({ "room_id": "!r:mx.org" }),
+                reply_to: Some("$ping:mx.org".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let sent = transport.sent_events().await;
+        assert_eq!(
+            sent[0].1,
+            serde_json::json!({
+                "msgtype": "m.text",
+                "body": "pong",
+                "m.relates_to": { "m.in_reply_to": { "event_id": "$ping:mx.org" } },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn send_image_attachment_uploads_then_sends() {
+        use base64::Engine;
+
+        let transport = Arc::new(MockMatrixTransport::new());
+        let channel = fast_channel(&transport, make_config(vec![]));
+        let png = vec![0x89, b'P', b'N', b'G'];
+        channel
+            .send(SendMessage {
+                content: "see chart".to_string(),
+                metadata: serde_json::json!({ "room_id": "!r:mx.org" }),
+                attachments: vec![
+                    Attachment {
+                        filename: "chart.png".to_string(),
+                        mime_type: "image/png".to_string(),
+                        data: base64::engine::general_purpose::STANDARD.encode(&png),
+                    },
+                    Attachment {
+                        filename: "log.txt".to_string(),
+                        mime_type: "text/plain".to_string(),
+                        data: base64::engine::general_purpose::STANDARD.encode("ok"),
+                    },
+                ],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            transport.uploads().await,
+            vec![
+                ("chart.png".to_string(), "image/png".to_string(), png),
+                (
+                    "log.txt".to_string(),
+                    "text/plain".to_string(),
+                    b"ok".to_vec()
+                ),
+            ]
+        );
+        let contents: Vec<serde_json::Value> = transport
+            .sent_events()
+            .await
+            .into_iter()
+            .map(|(_, content)| content)
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                serde_json::json!({ "msgtype": "m.text", "body": "see chart" }),
+                serde_json::json!({
+                    "msgtype": "m.image",
+                    "body": "chart.png",
+                    "url": "mxc://localhost/upload1",
+                    "info": { "mimetype": "image/png", "size": 4 },
+                }),
+                serde_json::json!({
+                    "msgtype": "m.file",
+                    "body": "log.txt",
+                    "url": "mxc://localhost/upload2",
+                    "info": { "mimetype": "text/plain", "size": 2 },
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_rejects_invalid_attachment_data() {
+        let transport = Arc::new(MockMatrixTransport::new());
+        let channel = fast_channel(&transport, make_config(vec![]));
+        let err = channel
+            .send(SendMessage {
+                content: String::new(),
+                metadata: serde_json::json!({ "room_id": "!r:mx.org" }),
+                attachments: vec![Attachment {
+                    filename: "x.bin".to_string(),
+                    mime_type: "application/octet-stream".to_string(),
+                    data: "not base64!".to_string(),
+                }],
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("x.bin"), "{err}");
+        assert!(transport.sent_events().await.is_empty());
+    }
 }
@@ -2,14 +2,46 @@
 //!
 //! Implements a JSON-RPC 2.0 server over stdio (newline-delimited messages)
 //! that exposes the tool registry to external clients such as ygn-brain.
+//!
+//! A `tools/call` whose params carry `_meta.progressToken` gets
+//! `notifications/progress` messages for every [`Progress`] the tool
+//! reports, delivered through the server's notifier before the response.
+//!
+//! A `notifications/cancelled` message naming a running `tools/call` by its
+//! `requestId` stops the tool; the call then fails with
+//! [`REQUEST_CANCELLED`].  Transports that handle calls one at a time pass
+//! incoming messages to [`InFlightCalls::observe`] as they arrive, so a
+//! cancellation reaches a call that is still running.
+//!
+//! Once a client sends `logging/setLevel`, the server logs policy
+//! decisions, tool calls and skill steps at or above that level as
+//! `notifications/message`, through the same notifier.
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
 use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tokio::runtime::RuntimeFlavor;
+use tokio_util::sync::CancellationToken;
 
 use crate::audit::{AuditEntry, AuditEventType, AuditLog};
-use crate::policy::{PolicyAction, PolicyEngine};
-use crate::tool::{EchoTool, ToolRegistry};
+use crate::external_tool::ExternalProcessTool;
+use crate::hardware::HardwareTool;
+use crate::mcp_proxy::{McpProxy, ProxyError};
+use crate::metrics::Metrics;
+use crate::policy::{ApprovalHandler, AutoDenyApprovalHandler, PolicyAction, PolicyEngine};
+use crate::sandbox::{AccessKind, AccessRequest};
+use crate::tool::{
+    EchoTool, InvalidArguments, LogLevel, Progress, ProgressReporter, ToolCancelled, ToolContent,
+    ToolLookupError, ToolRegistry,
+};
+use crate::tool_limits::{ToolBusy, ToolLimiter};
+use crate::tool_output::{OutputTooLarge, ToolOutputLimits};
 
 // ---------------------------------------------------------------------------
 // JSON-RPC 2.0 types
@@ -46,18 +78,203 @@ struct JsonRpcErrorResponse {
 struct JsonRpcError {
     code: i64,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl From<(i64, String)> for JsonRpcError {
+    fn from((code, message): (i64, String)) -> Self {
+        Self {
+            code,
+            message,
+            data: None,
+        }
+    }
 }
 
 // Standard JSON-RPC error codes
 const INVALID_REQUEST: i64 = -32600;
 const METHOD_NOT_FOUND: i64 = -32601;
 const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
 
 // Custom error codes for policy enforcement
 /// The tool call was denied by the security policy engine.
 const POLICY_DENIED: i64 = -32001;
 /// The tool call requires explicit user approval before it can proceed.
 const APPROVAL_REQUIRED: i64 = -32002;
+/// The tool exceeded its per-window call limit; retry later.
+const RATE_LIMITED: i64 = -32003;
+/// The call was meant for the proxied remote node, which could not be
+/// reached.
+const REMOTE_UNREACHABLE: i64 = -32004;
+/// The tool stayed at its concurrency limit for the whole queue timeout.
+const TOOL_BUSY: i64 = -32005;
+/// The tool returned binary output over its output size limit.
+const OUTPUT_TOO_LARGE: i64 = -32006;
+/// The tool failed while running, as opposed to being called with bad
+/// input.
+const TOOL_EXECUTION_ERROR: i64 = -32010;
+/// No tool by the requested name is registered.
+const TOOL_NOT_FOUND: i64 = -32011;
+/// The call was stopped by a `notifications/cancelled` message.
+pub const REQUEST_CANCELLED: i64 = -32800;
+
+/// Receives the notifications a server emits while handling a request.
+type Notifier = Arc<dyn Fn(Value) + Send + Sync>;
+
+/// The `progressToken` of a `tools/call`, from `_meta` as the MCP spec
+/// places it, or directly in `params`.
+fn progress_token(params: &Value) -> Option<Value> {
+    params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .or_else(|| params.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
+
+/// A `notifications/progress` message for `progress` under `token`.
+fn progress_notification(token: &Value, progress: &Progress) -> Value {
+    let mut params = serde_json::to_value(progress).unwrap_or_else(|_| json!({}));
+    params["progressToken"] = token.clone();
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/progress",
+        "params": params,
+    })
+}
+
+/// A `notifications/message` log message from `logger`.
+fn log_message(level: LogLevel, logger: &str, data: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": { "level": level, "logger": logger, "data": data },
+    })
+}
+
+/// The MCP content block for what tool `name` produced: text as is, JSON
+/// serialized into a text block, and binary data in base64, as an `image`
+/// for `image/*` types and an embedded `resource` otherwise.
+fn content_block(name: &str, content: &ToolContent) -> Value {
+    use base64::Engine;
+    match content {
+        ToolContent::Text(text) => json!({ "type": "text", "text": text }),
+        ToolContent::Json(value) => json!({ "type": "text", "text": value.to_string() }),
+        ToolContent::Binary { data, mime_type } => {
+            let data = base64::engine::general_purpose::STANDARD.encode(data);
+            if mime_type.starts_with("image/") {
+                json!({ "type": "image", "data": data, "mimeType": mime_type })
+            } else {
+                json!({
+                    "type": "resource",
+                    "resource": {
+                        "uri": format!("tool://{name}/result"),
+                        "mimeType": mime_type,
+                        "blob": data,
+                    }
+                })
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cancellation
+// ---------------------------------------------------------------------------
+
+/// Cancellation tokens of the `tools/call` requests a server has received
+/// and not yet answered, by JSON-RPC id.
+///
+/// Cheap to clone and shareable across threads, so a transport can apply
+/// `notifications/cancelled` while the server is busy with a call.
+#[derive(Debug, Clone, Default)]
+pub struct InFlightCalls {
+    tokens: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+/// Removes a call from its [`InFlightCalls`] once answered.
+struct InFlightGuard<'a> {
+    calls: &'a InFlightCalls,
+    key: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut tokens) = self.calls.tokens.lock() {
+            tokens.remove(&self.key);
+        }
+    }
+}
+
+impl InFlightCalls {
+    fn key(id: &Value) -> String {
+        id.to_string()
+    }
+
+    /// The token for request `id`, tracked from now on.
+    fn token(&self, id: &Value) -> CancellationToken {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        tokens.entry(Self::key(id)).or_default().clone()
+    }
+
+    /// Start tracking request `id` until the returned guard drops.  A
+    /// cancellation that arrived while the call was queued still applies.
+    fn start(&self, id: &Value) -> (CancellationToken, InFlightGuard<'_>) {
+        let guard = InFlightGuard {
+            calls: self,
+            key: Self::key(id),
+        };
+        (self.token(id), guard)
+    }
+
+    /// Cancel request `id`.  Returns whether it was in flight.
+    pub fn cancel(&self, id: &Value) -> bool {
+        let tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        match tokens.get(&Self::key(id)) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every tracked request, e.g. when its client goes away.
+    pub fn cancel_all(&self) {
+        let tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        for token in tokens.values() {
+            token.cancel();
+        }
+    }
+
+    /// Note a raw incoming message: a `tools/call` is tracked from receipt,
+    /// and a `notifications/cancelled` cancels the call it names.
+    ///
+    /// Only well-formed calls are tracked, since those are the ones that
+    /// reach [`McpServer::handle_tools_call`], which stops tracking them
+    /// however they are answered.
+    pub fn observe(&self, message: &str) {
+        let Ok(message) = serde_json::from_str::<Value>(message) else {
+            return;
+        };
+        match message.get("method").and_then(Value::as_str) {
+            Some("tools/call") => {
+                let well_formed = message.get("jsonrpc").is_some_and(Value::is_string);
+                if let (true, Some(id)) = (well_formed, message.get("id")) {
+                    self.token(id);
+                }
+            }
+            Some("notifications/cancelled") => {
+                if let Some(id) = message.pointer("/params/requestId") {
+                    self.cancel(id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
 
 // ---------------------------------------------------------------------------
 // McpServer
@@ -67,11 +284,46 @@ const APPROVAL_REQUIRED: i64 = -32002;
 ///
 /// When a [`PolicyEngine`] is attached, every `tools/call` request is
 /// evaluated before execution.  Denied calls produce a JSON-RPC error with
-/// code [`POLICY_DENIED`]; calls that need approval use [`APPROVAL_REQUIRED`].
+/// code [`POLICY_DENIED`] and calls over a tool's rate limit use
+/// [`RATE_LIMITED`].  Calls that need approval go to the server's
+/// [`ApprovalHandler`]; they run when it approves and otherwise fail with
+/// [`APPROVAL_REQUIRED`].  The default handler refuses every request.
+///
+/// Clients may add tools backed by an external program with
+/// `tools/register` and remove them again with `tools/unregister`.  Both
+/// need a policy engine: the request is evaluated as a call to a tool of
+/// the same name as the method, and the program must pass the engine's
+/// sandbox as an [`AccessKind::Command`].
+///
+/// With an [`McpProxy`] attached, the remote node's tools are listed next
+/// to the local ones and calls for them are forwarded once the local policy
+/// allows them.  Failures reaching the remote use [`REMOTE_UNREACHABLE`].
 pub struct McpServer {
-    registry: ToolRegistry,
+    registry: RefCell<ToolRegistry>,
+    /// Names of the tools added through `tools/register`.
+    runtime_tools: RefCell<BTreeSet<String>>,
     policy: Option<PolicyEngine>,
+    /// Remote node serving the tools not registered locally.
+    proxy: Option<Arc<McpProxy>>,
     audit_log: std::cell::RefCell<AuditLog>,
+    metrics: Option<Arc<Metrics>>,
+    /// File the audit log is appended to when [`run_stdio`](Self::run_stdio) ends.
+    audit_path: Option<PathBuf>,
+    /// Where progress notifications go; [`run_stdio`](Self::run_stdio)
+    /// writes them to stdout when none is set.
+    notifier: RefCell<Option<Notifier>>,
+    /// Correlation id for every message, set per HTTP request; each message
+    /// gets a fresh one when unset.
+    request_id: Option<String>,
+    /// Correlation id of the message being handled.
+    current_request_id: RefCell<Option<String>>,
+    /// Running `tools/call` requests, cancelled by `notifications/cancelled`.
+    in_flight: InFlightCalls,
+    /// Decides calls the policy marks as needing approval.
+    approval: Arc<dyn ApprovalHandler>,
+    /// Least severe level sent as `notifications/message`; nothing is
+    /// logged to the client until it is set.
+    log_level: Cell<Option<LogLevel>>,
 }
 
 impl McpServer {
@@ -79,26 +331,165 @@ impl McpServer {
     /// policy engine.
     pub fn new(registry: ToolRegistry) -> Self {
         Self {
-            registry,
+            registry: RefCell::new(registry),
+            runtime_tools: RefCell::new(BTreeSet::new()),
             policy: None,
+            proxy: None,
             audit_log: std::cell::RefCell::new(AuditLog::new()),
+            metrics: None,
+            audit_path: None,
+            notifier: RefCell::new(None),
+            request_id: None,
+            current_request_id: RefCell::new(None),
+            in_flight: InFlightCalls::default(),
+            approval: Arc::new(AutoDenyApprovalHandler),
+            log_level: Cell::new(None),
         }
     }
 
     /// Create a new MCP server with a policy engine attached.
     pub fn with_policy(registry: ToolRegistry, policy: PolicyEngine) -> Self {
         Self {
-            registry,
+            registry: RefCell::new(registry),
+            runtime_tools: RefCell::new(BTreeSet::new()),
             policy: Some(policy),
+            proxy: None,
             audit_log: std::cell::RefCell::new(AuditLog::new()),
+            metrics: None,
+            audit_path: None,
+            notifier: RefCell::new(None),
+            request_id: None,
+            current_request_id: RefCell::new(None),
+            in_flight: InFlightCalls::default(),
+            approval: Arc::new(AutoDenyApprovalHandler),
+            log_level: Cell::new(None),
         }
     }
 
     /// Create a server with the default set of built-in tools.
     pub fn with_default_tools() -> Self {
+        Self::new(Self::default_registry())
+    }
+
+    /// Like [`with_default_tools`](Self::with_default_tools), but the
+    /// simulated hardware's sensor readings are driven by `seed`.
+    pub fn with_default_tools_seeded(seed: u64) -> Self {
+        Self::new(Self::default_registry_with_seed(seed))
+    }
+
+    /// The registry of built-in tools used by [`with_default_tools`](Self::with_default_tools):
+    /// `echo` and the simulated `hardware` tool.
+    pub fn default_registry() -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+        registry.register(Box::new(HardwareTool::new()));
+        registry
+    }
+
+    /// The default registry without the simulated `hardware` tool, for nodes
+    /// with `hardware.simulated` turned off.
+    pub fn registry_without_hardware() -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+        registry
+    }
+
+    /// The default registry with the `hardware` tool seeded by `seed`, for
+    /// deterministic sensor readings.
+    pub fn default_registry_with_seed(seed: u64) -> ToolRegistry {
         let mut registry = ToolRegistry::new();
         registry.register(Box::new(EchoTool));
-        Self::new(registry)
+        registry.register(Box::new(HardwareTool::with_seed(seed)));
+        registry
+    }
+
+    /// Forward calls for tools that are not registered locally to the node
+    /// behind `proxy`, and list its tools alongside the local ones.
+    pub fn with_proxy(mut self, proxy: Arc<McpProxy>) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Consult `handler` for calls the policy marks as needing approval.
+    pub fn with_approval_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.approval = handler;
+        self
+    }
+
+    /// Record MCP requests, policy decisions and tool executions in
+    /// `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.registry.get_mut().set_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Cap concurrent executions of local tools with `limiter`, and report
+    /// each limited tool's load in `tools/list`.
+    pub fn with_tool_limiter(mut self, limiter: Arc<ToolLimiter>) -> Self {
+        self.registry.get_mut().set_limiter(limiter);
+        self
+    }
+
+    /// Fit the output of local tools to `limits`, flagging truncated
+    /// results with `isTruncated`.
+    pub fn with_output_limits(mut self, limits: Arc<ToolOutputLimits>) -> Self {
+        self.registry.get_mut().set_output_limits(limits);
+        self
+    }
+
+    /// Send notifications, such as `notifications/progress`, to `notifier`
+    /// as they are emitted.
+    pub fn with_notifier(self, notifier: impl Fn(Value) + Send + Sync + 'static) -> Self {
+        *self.notifier.borrow_mut() = Some(Arc::new(notifier));
+        self
+    }
+
+    /// Send log messages at `level` and above from the start, as if the
+    /// client had sent `logging/setLevel`; for transports that create a
+    /// server per request.
+    pub fn with_log_level(self, level: LogLevel) -> Self {
+        self.log_level.set(Some(level));
+        self
+    }
+
+    /// Attribute every message this server handles to the request with
+    /// correlation id `request_id`, instead of generating one per message.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Handle on this server's running calls, for transports that apply
+    /// cancellations from another thread.
+    pub fn in_flight(&self) -> InFlightCalls {
+        self.in_flight.clone()
+    }
+
+    /// Append the session's audit log to `path` when the stdio loop ends.
+    pub fn with_audit_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_path = Some(path.into());
+        self
+    }
+
+    /// Send `message` from `logger` to the client as `notifications/message`
+    /// when `level` is at or above the level it asked for.
+    pub fn log_notification(&self, level: LogLevel, logger: &str, message: impl Into<String>) {
+        if let Some(log) = self.log_sink() {
+            log(level, logger, message.into());
+        }
+    }
+
+    /// Sends log messages at or above the client's level through the
+    /// notifier, or `None` when either is unset.
+    fn log_sink(&self) -> Option<impl Fn(LogLevel, &str, String) + Send + Sync + 'static> {
+        let min = self.log_level.get()?;
+        let notify = self.notifier.borrow().clone()?;
+        Some(move |level: LogLevel, logger: &str, message: String| {
+            if level >= min {
+                notify(log_message(level, logger, &message));
+            }
+        })
     }
 
     /// Access the audit log (e.g. for export after a session).
@@ -106,12 +497,27 @@ impl McpServer {
         self.audit_log.borrow()
     }
 
+    /// Append the audit log to `path` as JSON Lines, creating the file if
+    /// needed.
+    pub fn dump_audit_log(&self, path: &Path) -> io::Result<()> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        self.audit_log
+            .borrow()
+            .export_jsonl(io::BufWriter::new(file))
+    }
+
     // -- public entry point ------------------------------------------------
 
     /// Handle a parsed JSON-RPC value and return a JSON-RPC response value.
     ///
     /// This is the reusable core handler used by both stdio and HTTP transports.
     pub fn handle_jsonrpc(&self, request: Value) -> Option<Value> {
+        let request_id = self.begin_request();
+        let _span = tracing::info_span!("mcp_message", request_id = %request_id).entered();
+
         // Validate required JSON-RPC 2.0 fields before deserialization.
         // Per spec: -32600 = Invalid Request (valid JSON but not a valid Request object),
         //           -32700 = Parse error (invalid JSON — handled by the caller).
@@ -119,6 +525,7 @@ impl McpServer {
             let has_jsonrpc = obj.contains_key("jsonrpc");
             let has_method = obj.contains_key("method");
             if !has_jsonrpc || !has_method {
+                self.record_method("invalid");
                 let missing: Vec<&str> = [
                     if !has_jsonrpc { Some("jsonrpc") } else { None },
                     if !has_method { Some("method") } else { None },
@@ -137,6 +544,7 @@ impl McpServer {
                                 "Invalid Request: missing required field(s): {}",
                                 missing.join(", ")
                             ),
+                            data: None,
                         },
                     })
                     .unwrap(),
@@ -144,6 +552,7 @@ impl McpServer {
             }
         } else {
             // Not a JSON object at all — still an invalid request.
+            self.record_method("invalid");
             return Some(
                 serde_json::to_value(JsonRpcErrorResponse {
                     jsonrpc: "2.0".into(),
@@ -151,6 +560,7 @@ impl McpServer {
                     error: JsonRpcError {
                         code: INVALID_REQUEST,
                         message: "Invalid Request: expected a JSON object".into(),
+                        data: None,
                     },
                 })
                 .unwrap(),
@@ -160,6 +570,7 @@ impl McpServer {
         let req: JsonRpcRequest = match serde_json::from_value(request) {
             Ok(r) => r,
             Err(e) => {
+                self.record_method("invalid");
                 return Some(
                     serde_json::to_value(JsonRpcErrorResponse {
                         jsonrpc: "2.0".into(),
@@ -167,6 +578,7 @@ impl McpServer {
                         error: JsonRpcError {
                             code: INVALID_REQUEST,
                             message: format!("Invalid Request: {e}"),
+                            data: None,
                         },
                     })
                     .unwrap(),
@@ -174,16 +586,26 @@ impl McpServer {
             }
         };
 
+        self.record_method(&req.method);
+        if req.method == "notifications/cancelled" {
+            if let Some(id) = req.params.get("requestId") {
+                self.in_flight.cancel(id);
+            }
+        }
         let id = req.id?;
 
         let result = match req.method.as_str() {
             "initialize" => self.handle_initialize(),
             "tools/list" => self.handle_tools_list(),
-            "tools/call" => self.handle_tools_call(&req.params),
+            "tools/call" => self.handle_tools_call(&id, &req.params),
+            "tools/register" => self.handle_tools_register(&req.params),
+            "tools/unregister" => self.handle_tools_unregister(&req.params),
+            "logging/setLevel" => self.handle_set_level(&req.params),
             _ => Err((
                 METHOD_NOT_FOUND,
                 format!("Method not found: {}", req.method),
-            )),
+            )
+                .into()),
         };
 
         let response = match result {
@@ -193,10 +615,10 @@ impl McpServer {
                 result: value,
             })
             .unwrap(),
-            Err((code, message)) => serde_json::to_value(JsonRpcErrorResponse {
+            Err(error) => serde_json::to_value(JsonRpcErrorResponse {
                 jsonrpc: "2.0".into(),
                 id,
-                error: JsonRpcError { code, message },
+                error,
             })
             .unwrap(),
         };
@@ -213,6 +635,9 @@ impl McpServer {
             return None;
         }
 
+        let request_id = self.begin_request();
+        let _span = tracing::info_span!("mcp_message", request_id = %request_id).entered();
+
         // Step 1: Parse raw JSON. If this fails, it is a -32700 Parse error.
         let raw: Value = match serde_json::from_str(trimmed) {
             Ok(v) => v,
@@ -223,6 +648,7 @@ impl McpServer {
                     error: JsonRpcError {
                         code: -32700,
                         message: format!("Parse error: {e}"),
+                        data: None,
                     },
                 };
                 return Some(serde_json::to_string(&err).unwrap());
@@ -234,6 +660,7 @@ impl McpServer {
             let has_jsonrpc = obj.contains_key("jsonrpc");
             let has_method = obj.contains_key("method");
             if !has_jsonrpc || !has_method {
+                self.record_method("invalid");
                 let missing: Vec<&str> = [
                     if !has_jsonrpc { Some("jsonrpc") } else { None },
                     if !has_method { Some("method") } else { None },
@@ -251,6 +678,7 @@ impl McpServer {
                             "Invalid Request: missing required field(s): {}",
                             missing.join(", ")
                         ),
+                        data: None,
                     },
                 };
                 return Some(serde_json::to_string(&err).unwrap());
@@ -262,6 +690,7 @@ impl McpServer {
                 error: JsonRpcError {
                     code: INVALID_REQUEST,
                     message: "Invalid Request: expected a JSON object".into(),
+                    data: None,
                 },
             };
             return Some(serde_json::to_string(&err).unwrap());
@@ -277,6 +706,7 @@ impl McpServer {
                     error: JsonRpcError {
                         code: INVALID_REQUEST,
                         message: format!("Invalid Request: {e}"),
+                        data: None,
                     },
                 };
                 return Some(serde_json::to_string(&err).unwrap());
@@ -284,16 +714,26 @@ impl McpServer {
         };
 
         // Notifications have no id — acknowledge silently.
+        self.record_method(&req.method);
+        if req.method == "notifications/cancelled" {
+            if let Some(id) = req.params.get("requestId") {
+                self.in_flight.cancel(id);
+            }
+        }
         let id = req.id?;
 
         let result = match req.method.as_str() {
             "initialize" => self.handle_initialize(),
             "tools/list" => self.handle_tools_list(),
-            "tools/call" => self.handle_tools_call(&req.params),
+            "tools/call" => self.handle_tools_call(&id, &req.params),
+            "tools/register" => self.handle_tools_register(&req.params),
+            "tools/unregister" => self.handle_tools_unregister(&req.params),
+            "logging/setLevel" => self.handle_set_level(&req.params),
             _ => Err((
                 METHOD_NOT_FOUND,
                 format!("Method not found: {}", req.method),
-            )),
+            )
+                .into()),
         };
 
         let response_json = match result {
@@ -303,10 +743,10 @@ impl McpServer {
                 result: value,
             })
             .unwrap(),
-            Err((code, message)) => serde_json::to_string(&JsonRpcErrorResponse {
+            Err(error) => serde_json::to_string(&JsonRpcErrorResponse {
                 jsonrpc: "2.0".into(),
                 id,
-                error: JsonRpcError { code, message },
+                error,
             })
             .unwrap(),
         };
@@ -315,15 +755,38 @@ impl McpServer {
     }
 
     /// Run the MCP server over stdio, reading newline-delimited JSON-RPC
-    /// messages from stdin and writing responses to stdout.
+    /// messages from stdin and writing responses to stdout.  Progress
+    /// notifications are written to stdout as they happen, ahead of the
+    /// response they belong to.
     pub fn run_stdio(&self) -> io::Result<()> {
         let stdin = io::stdin();
         let mut stdout = io::stdout();
 
+        if self.notifier.borrow().is_none() {
+            *self.notifier.borrow_mut() = Some(Arc::new(|notification: Value| {
+                let mut stdout = io::stdout().lock();
+                let _ = writeln!(stdout, "{notification}");
+                let _ = stdout.flush();
+            }));
+        }
+
         // Log to stderr so we never pollute the JSON-RPC channel.
         eprintln!("ygn-core MCP server started (stdio mode)");
 
-        for line in stdin.lock().lines() {
+        // Lines are read on their own thread so a cancellation can reach
+        // the call being handled here.
+        let (lines_tx, lines) = std::sync::mpsc::channel();
+        let in_flight = self.in_flight();
+        std::thread::spawn(move || {
+            for line in stdin.lock().lines() {
+                let line = line.inspect(|line| in_flight.observe(line));
+                let failed = line.is_err();
+                if lines_tx.send(line).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        for line in lines {
             let line = line?;
             if let Some(response) = self.handle_message(&line) {
                 writeln!(stdout, "{response}")?;
@@ -332,16 +795,20 @@ impl McpServer {
         }
 
         eprintln!("ygn-core MCP server shutting down");
+        if let Some(path) = &self.audit_path {
+            self.dump_audit_log(path)?;
+        }
         Ok(())
     }
 
     // -- method handlers ---------------------------------------------------
 
-    fn handle_initialize(&self) -> Result<Value, (i64, String)> {
+    fn handle_initialize(&self) -> Result<Value, JsonRpcError> {
         Ok(json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {}
+                "tools": {},
+                "logging": {}
             },
             "serverInfo": {
                 "name": "ygn-core",
@@ -350,106 +817,317 @@ impl McpServer {
         }))
     }
 
-    fn handle_tools_list(&self) -> Result<Value, (i64, String)> {
-        let tools: Vec<Value> = self
-            .registry
-            .list()
+    fn handle_set_level(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let level = params.get("level").cloned().ok_or_else(|| {
+            (
+                INVALID_PARAMS,
+                "Missing required parameter: level".to_string(),
+            )
+        })?;
+        let level: LogLevel = serde_json::from_value(level)
+            .map_err(|e| (INVALID_PARAMS, format!("Invalid log level: {e}")))?;
+        self.log_level.set(Some(level));
+        Ok(json!({}))
+    }
+
+    fn handle_tools_list(&self) -> Result<Value, JsonRpcError> {
+        let registry = self.registry.borrow();
+        let mut specs = registry.list();
+        let local = specs.len();
+        if let Some(proxy) = &self.proxy {
+            // An unreachable remote leaves the local tools usable.
+            match Self::block_on(proxy.list_tools())? {
+                Ok(remote) => {
+                    for mut spec in remote {
+                        if specs.iter().any(|s| s.name == spec.name) {
+                            spec.name = format!("{}/{}", proxy.node_id(), spec.name);
+                        }
+                        specs.push(spec);
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "listing remote tools failed"),
+            }
+        }
+        let tools: Vec<Value> = specs
             .iter()
-            .map(|spec| {
-                json!({
+            .enumerate()
+            .map(|(i, spec)| {
+                let mut tool = json!({
                     "name": spec.name,
                     "description": spec.description,
                     "inputSchema": spec.parameters_schema
-                })
+                });
+                if let Some(load) = registry.concurrency(&spec.name).filter(|_| i < local) {
+                    tool["concurrency"] = json!(load);
+                }
+                tool
             })
             .collect();
 
         Ok(json!({ "tools": tools }))
     }
 
-    fn handle_tools_call(&self, params: &Value) -> Result<Value, (i64, String)> {
-        let name = params.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
-            (
-                INVALID_PARAMS,
-                "Missing required parameter: name".to_string(),
-            )
-        })?;
+    /// Set the correlation id of the message about to be handled: the one
+    /// given to [`with_request_id`](Self::with_request_id), else a fresh
+    /// UUID.  Audit entries and tool executions are tagged with it.
+    fn begin_request(&self) -> String {
+        let id = self
+            .request_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        self.registry.borrow_mut().set_request_id(Some(id.clone()));
+        *self.current_request_id.borrow_mut() = Some(id.clone());
+        id
+    }
 
-        let arguments = params
-            .get("arguments")
-            .cloned()
-            .unwrap_or_else(|| json!({}));
+    /// Record `entry` in the audit log, tagged with the current message's
+    /// correlation id.
+    fn audit(&self, entry: AuditEntry) {
+        let entry = match self.current_request_id.borrow().as_deref() {
+            Some(id) => entry.with_request_id(id),
+            None => entry,
+        };
+        self.audit_log.borrow_mut().record(entry);
+    }
+
+    fn record_method(&self, method: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_mcp_method(method);
+        }
+    }
 
-        // --- Policy check (if a policy engine is attached) ----------------
+    /// Evaluate a call to `name` against the attached policy engine, if
+    /// any, recording the decision in the audit log.
+    fn check_policy(&self, name: &str, arguments: &Value) -> Result<(), JsonRpcError> {
         if let Some(ref policy) = self.policy {
-            let decision = policy.evaluate(name, &arguments);
+            let decision = policy.evaluate(name, arguments);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_policy_decision(&decision.action);
+            }
+            let level = match decision.action {
+                PolicyAction::Allow => LogLevel::Info,
+                PolicyAction::RequireApproval => LogLevel::Notice,
+                PolicyAction::Deny | PolicyAction::RateLimited => LogLevel::Warning,
+            };
+            self.log_notification(
+                level,
+                "policy",
+                format!(
+                    "{:?} call to '{name}' ({:?} risk): {}",
+                    decision.action, decision.risk_level, decision.reason
+                ),
+            );
 
             // Record the attempt in the audit log.
-            self.audit_log.borrow_mut().record(AuditEntry::now(
-                AuditEventType::ToolCallAttempt,
-                name,
-                format!("{:?}", decision.action),
-                format!("{:?}", decision.risk_level),
-                json!({ "arguments": arguments }),
-            ));
+            self.audit(
+                AuditEntry::now(
+                    AuditEventType::ToolCallAttempt,
+                    name,
+                    format!("{:?}", decision.action),
+                    format!("{:?}", decision.risk_level),
+                    json!({ "arguments": arguments }),
+                )
+                .with_decision_id(&decision.decision_id),
+            );
 
             match decision.action {
                 PolicyAction::Deny => {
-                    self.audit_log.borrow_mut().record(AuditEntry::now(
-                        AuditEventType::AccessDenied,
-                        name,
-                        "Deny",
-                        format!("{:?}", decision.risk_level),
-                        json!({ "reason": decision.reason }),
-                    ));
-                    return Err((POLICY_DENIED, decision.reason));
+                    self.audit(
+                        AuditEntry::now(
+                            AuditEventType::AccessDenied,
+                            name,
+                            "Deny",
+                            format!("{:?}", decision.risk_level),
+                            json!({ "reason": decision.reason }),
+                        )
+                        .with_decision_id(&decision.decision_id),
+                    );
+                    return Err((POLICY_DENIED, decision.reason).into());
                 }
                 PolicyAction::RequireApproval => {
-                    self.audit_log.borrow_mut().record(AuditEntry::now(
-                        AuditEventType::ApprovalRequired,
-                        name,
-                        "RequireApproval",
-                        format!("{:?}", decision.risk_level),
-                        json!({ "reason": decision.reason }),
-                    ));
-                    return Err((APPROVAL_REQUIRED, decision.reason));
+                    self.audit(
+                        AuditEntry::now(
+                            AuditEventType::ApprovalRequired,
+                            name,
+                            "RequireApproval",
+                            format!("{:?}", decision.risk_level),
+                            json!({ "reason": decision.reason }),
+                        )
+                        .with_decision_id(&decision.decision_id),
+                    );
+                    let approved = Self::block_on(self.approval.request_approval(name, arguments))?;
+                    if !approved {
+                        return Err((APPROVAL_REQUIRED, decision.reason).into());
+                    }
+                    self.audit(
+                        AuditEntry::now(
+                            AuditEventType::ApprovalGranted,
+                            name,
+                            "Approved",
+                            format!("{:?}", decision.risk_level),
+                            json!({ "reason": decision.reason }),
+                        )
+                        .with_decision_id(&decision.decision_id),
+                    );
+                    // Approval does not exempt a call from the tool's rate
+                    // limit.
+                    if let Some(limited) = policy.count_approved_call(name) {
+                        if let Some(metrics) = &self.metrics {
+                            metrics.record_policy_decision(&limited.action);
+                        }
+                        self.audit(
+                            AuditEntry::now(
+                                AuditEventType::AccessDenied,
+                                name,
+                                "RateLimited",
+                                format!("{:?}", limited.risk_level),
+                                json!({ "reason": limited.reason }),
+                            )
+                            .with_decision_id(&decision.decision_id),
+                        );
+                        return Err((RATE_LIMITED, limited.reason).into());
+                    }
+                }
+                PolicyAction::RateLimited => {
+                    self.audit(
+                        AuditEntry::now(
+                            AuditEventType::AccessDenied,
+                            name,
+                            "RateLimited",
+                            format!("{:?}", decision.risk_level),
+                            json!({ "reason": decision.reason }),
+                        )
+                        .with_decision_id(&decision.decision_id),
+                    );
+                    return Err((RATE_LIMITED, decision.reason).into());
                 }
                 PolicyAction::Allow => {
-                    self.audit_log.borrow_mut().record(AuditEntry::now(
-                        AuditEventType::AccessGranted,
-                        name,
-                        "Allow",
-                        format!("{:?}", decision.risk_level),
-                        json!({ "reason": decision.reason }),
-                    ));
+                    self.audit(
+                        AuditEntry::now(
+                            AuditEventType::AccessGranted,
+                            name,
+                            "Allow",
+                            format!("{:?}", decision.risk_level),
+                            json!({ "reason": decision.reason }),
+                        )
+                        .with_decision_id(&decision.decision_id),
+                    );
                 }
             }
         }
+        Ok(())
+    }
 
-        let tool = self
-            .registry
-            .get(name)
-            .ok_or_else(|| (INVALID_PARAMS, format!("Tool not found: {name}")))?;
+    fn handle_tools_call(&self, id: &Value, params: &Value) -> Result<Value, JsonRpcError> {
+        // Taken first, so that calls rejected before they run stop being
+        // tracked too.
+        let (cancel, _in_flight) = self.in_flight.start(id);
+        let name = params.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
+            (
+                INVALID_PARAMS,
+                "Missing required parameter: name".to_string(),
+            )
+        })?;
 
-        // Run the async tool execution synchronously.
-        // If we are already inside a tokio runtime (e.g. main is #[tokio::main]),
-        // use block_in_place + the existing handle; otherwise create a new runtime.
-        let result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
-            tokio::task::block_in_place(|| handle.block_on(tool.execute(arguments)))
-        } else {
-            let rt = tokio::runtime::Runtime::new()
-                .map_err(|e| (INVALID_PARAMS, format!("Runtime error: {e}")))?;
-            rt.block_on(tool.execute(arguments))
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        let registry = self.registry.borrow();
+        // Policy sees the qualified name, so aliases and short names cannot
+        // sidestep rules written against it.
+        let name = match registry.resolve(name) {
+            Ok(qualified) => qualified,
+            Err(ToolLookupError::NotFound(_)) => {
+                self.check_policy(name, &arguments)?;
+                if let Some(proxy) = &self.proxy {
+                    return self.forward_call(proxy, name, arguments);
+                }
+                return Err((TOOL_NOT_FOUND, format!("Tool not found: {name}")).into());
+            }
+            Err(ToolLookupError::Ambiguous { name, candidates }) => {
+                return Err(JsonRpcError {
+                    code: INVALID_PARAMS,
+                    message: format!(
+                        "ambiguous tool name '{name}': matches {}",
+                        candidates.join(", ")
+                    ),
+                    data: Some(json!({ "candidates": candidates })),
+                });
+            }
+        };
+        self.check_policy(name, &arguments)?;
+        let notifier = self.notifier.borrow().clone();
+        let progress = match (progress_token(params), notifier) {
+            (Some(token), Some(notify)) => {
+                ProgressReporter::new(move |p| notify(progress_notification(&token, &p)))
+            }
+            _ => ProgressReporter::noop(),
+        };
+        let progress = match self.log_sink() {
+            Some(log) => progress.with_logger(log),
+            None => progress,
+        };
+        self.log_notification(LogLevel::Info, "tools", format!("calling '{name}'"));
+        let started = std::time::Instant::now();
+        let result =
+            Self::block_on(registry.execute_cancellable(name, arguments, &progress, &cancel))?;
+        let elapsed_ms = started.elapsed().as_millis();
+        match &result {
+            Ok(r) if r.success => self.log_notification(
+                LogLevel::Info,
+                "tools",
+                format!("'{name}' finished in {elapsed_ms} ms"),
+            ),
+            Ok(r) => self.log_notification(
+                LogLevel::Warning,
+                "tools",
+                format!(
+                    "'{name}' failed after {elapsed_ms} ms: {}",
+                    r.error.as_deref().unwrap_or("unknown error")
+                ),
+            ),
+            Err(e) => self.log_notification(
+                LogLevel::Warning,
+                "tools",
+                format!("'{name}' failed after {elapsed_ms} ms: {e}"),
+            ),
         }
-        .map_err(|e| (INVALID_PARAMS, format!("Tool execution error: {e}")))?;
+        let result = result.map_err(|e| {
+            if let Some(cancelled) = e.downcast_ref::<ToolCancelled>() {
+                return (REQUEST_CANCELLED, cancelled.to_string()).into();
+            }
+            if let Some(invalid) = e.downcast_ref::<InvalidArguments>() {
+                return JsonRpcError {
+                    code: INVALID_PARAMS,
+                    message: invalid.to_string(),
+                    data: Some(json!({ "violations": invalid.violations })),
+                };
+            }
+            if let Some(busy) = e.downcast_ref::<ToolBusy>() {
+                return JsonRpcError {
+                    code: TOOL_BUSY,
+                    message: busy.to_string(),
+                    data: Some(json!({ "tool": busy.tool, "limit": busy.limit })),
+                };
+            }
+            if let Some(large) = e.downcast_ref::<OutputTooLarge>() {
+                return JsonRpcError {
+                    code: OUTPUT_TOO_LARGE,
+                    message: large.to_string(),
+                    data: Some(json!({ "bytes": large.bytes, "limit": large.limit })),
+                };
+            }
+            (TOOL_EXECUTION_ERROR, format!("Tool execution error: {e}")).into()
+        })?;
 
         if result.success {
-            Ok(json!({
-                "content": [{
-                    "type": "text",
-                    "text": result.output
-                }]
-            }))
+            let mut response = json!({ "content": [content_block(name, &result.content)] });
+            if result.truncated.is_some() {
+                response["isTruncated"] = json!(true);
+            }
+            Ok(response)
         } else {
             Ok(json!({
                 "content": [{
@@ -460,6 +1138,186 @@ impl McpServer {
             }))
         }
     }
+
+    /// Call `name` on the proxied node.  A name listed with the node id
+    /// prefix is called by its remote name.
+    fn forward_call(
+        &self,
+        proxy: &McpProxy,
+        name: &str,
+        arguments: Value,
+    ) -> Result<Value, JsonRpcError> {
+        let remote_name = name
+            .strip_prefix(proxy.node_id())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(name);
+        Self::block_on(proxy.call_tool(remote_name, arguments))?.map_err(|e| match e {
+            ProxyError::Unreachable { .. } => (REMOTE_UNREACHABLE, e.to_string()).into(),
+            ProxyError::Remote {
+                code,
+                message,
+                data,
+            } => JsonRpcError {
+                code,
+                message,
+                data,
+            },
+        })
+    }
+
+    /// Run a future to completion from the synchronous handlers.
+    ///
+    /// If we are already inside a multi-threaded tokio runtime (e.g. main
+    /// is #[tokio::main]), use block_in_place + the existing handle.  A
+    /// current-thread runtime cannot be blocked, so the future runs on a
+    /// new runtime on its own thread; outside any runtime, on a new runtime
+    /// here.
+    fn block_on<F>(future: F) -> Result<F::Output, JsonRpcError>
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        let run = |future: F| {
+            let rt = tokio::runtime::Runtime::new()
+                .map_err(|e| (INTERNAL_ERROR, format!("Runtime error: {e}")))?;
+            Ok(rt.block_on(future))
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+                std::thread::scope(|scope| scope.spawn(|| run(future)).join())
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            }
+            Ok(handle) => Ok(tokio::task::block_in_place(|| handle.block_on(future))),
+            Err(_) => run(future),
+        }
+    }
+
+    fn handle_tools_register(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let params: RegisterToolParams = serde_json::from_value(params.clone())
+            .map_err(|e| (INVALID_PARAMS, format!("Invalid params: {e}")))?;
+        if params.name.is_empty() {
+            return Err((INVALID_PARAMS, "Tool name must not be empty".to_string()).into());
+        }
+        if let Err(e) = jsonschema::validator_for(&params.input_schema) {
+            return Err((INVALID_PARAMS, format!("Invalid inputSchema: {e}")).into());
+        }
+
+        let Some(policy) = &self.policy else {
+            return Err((
+                POLICY_DENIED,
+                "Tool registration requires a policy engine".to_string(),
+            )
+                .into());
+        };
+        self.check_policy(
+            "tools/register",
+            &json!({ "name": params.name, "command": params.command, "args": params.args }),
+        )?;
+        // Without an allowlist the sandbox would run any program a caller
+        // names, so registration fails closed.
+        if !policy.sandbox().restricts_commands() {
+            self.audit(AuditEntry::now(
+                AuditEventType::AccessDenied,
+                "tools/register",
+                "Deny",
+                "High",
+                json!({ "command": params.command, "reason": "no command allowlist" }),
+            ));
+            return Err((
+                POLICY_DENIED,
+                "Tool registration requires a command allowlist (allowed_commands)".to_string(),
+            )
+                .into());
+        }
+        let access = policy.sandbox().check_access(&AccessRequest {
+            kind: AccessKind::Command,
+            target: params.command.clone(),
+        });
+        if !access.allowed {
+            self.audit(AuditEntry::now(
+                AuditEventType::AccessDenied,
+                "tools/register",
+                "Deny",
+                "High",
+                json!({ "command": params.command, "reason": access.reason }),
+            ));
+            return Err((
+                POLICY_DENIED,
+                format!(
+                    "sandbox ({}) denied command '{}': {}",
+                    access.profile, params.command, access.reason
+                ),
+            )
+                .into());
+        }
+
+        let mut registry = self.registry.borrow_mut();
+        if registry.is_registered(&params.name) {
+            return Err((
+                INVALID_PARAMS,
+                format!("Tool already registered: {}", params.name),
+            )
+                .into());
+        }
+        let tool = ExternalProcessTool::new(
+            params.name.clone(),
+            params.description,
+            params.input_schema,
+            params.command,
+        )
+        .with_args(params.args)
+        .with_timeout(policy.max_execution_time());
+        registry.register(Box::new(tool));
+        self.runtime_tools.borrow_mut().insert(params.name.clone());
+        Ok(json!({ "registered": params.name }))
+    }
+
+    fn handle_tools_unregister(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let name = params.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
+            (
+                INVALID_PARAMS,
+                "Missing required parameter: name".to_string(),
+            )
+        })?;
+        if self.policy.is_none() {
+            return Err((
+                POLICY_DENIED,
+                "Tool registration requires a policy engine".to_string(),
+            )
+                .into());
+        }
+        self.check_policy("tools/unregister", &json!({ "name": name }))?;
+        // Built-in tools stay put; only runtime registrations can go.
+        if !self.runtime_tools.borrow_mut().remove(name) {
+            return Err((
+                INVALID_PARAMS,
+                format!("Tool was not registered at runtime: {name}"),
+            )
+                .into());
+        }
+        self.registry.borrow_mut().unregister(name);
+        Ok(json!({ "unregistered": name }))
+    }
+}
+
+/// Parameters of `tools/register`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterToolParams {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_input_schema")]
+    input_schema: Value,
+    /// Program run for each call, without a shell.
+    command: String,
+    /// Arguments passed to `command` on each call.
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+fn default_input_schema() -> Value {
+    json!({ "type": "object" })
 }
 
 // ---------------------------------------------------------------------------
@@ -493,6 +1351,7 @@ mod tests {
         assert_eq!(v["id"], 1);
         assert_eq!(v["result"]["protocolVersion"], "2024-11-05");
         assert!(v["result"]["capabilities"]["tools"].is_object());
+        assert!(v["result"]["capabilities"]["logging"].is_object());
         assert_eq!(v["result"]["serverInfo"]["name"], "ygn-core");
     }
 
@@ -508,7 +1367,7 @@ mod tests {
     // -- tools/list --------------------------------------------------------
 
     #[test]
-    fn tools_list_returns_echo_tool() {
+    fn tools_list_returns_echo_and_hardware_tools() {
         let srv = server();
         let req = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list","params":{}}"#;
         let resp = srv.handle_message(req).expect("should produce a response");
@@ -516,9 +1375,29 @@ mod tests {
 
         assert_eq!(v["id"], 2);
         let tools = v["result"]["tools"].as_array().expect("tools array");
-        assert_eq!(tools.len(), 1);
+        assert_eq!(tools.len(), 2);
         assert_eq!(tools[0]["name"], "echo");
         assert!(tools[0]["inputSchema"]["properties"]["input"].is_object());
+        assert_eq!(tools[1]["name"], "hardware");
+        assert!(tools[1]["inputSchema"]["properties"]["action"].is_object());
+    }
+
+    // -- tools/call hardware -----------------------------------------------
+
+    #[test]
+    fn seeded_hardware_tool_reads_deterministically() {
+        let sense = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"hardware","arguments":{"action":{"type":"sense","sensor_type":"temperature"}}}}"#;
+        let read = |seed| {
+            let resp = McpServer::with_default_tools_seeded(seed)
+                .handle_message(sense)
+                .expect("should produce a response");
+            let v = parse_response(&resp);
+            assert!(v["result"].get("isError").is_none());
+            let text = v["result"]["content"][0]["text"].as_str().unwrap();
+            serde_json::from_str::<Value>(text).unwrap()["data"]["value"].clone()
+        };
+        assert_eq!(read(7), read(7));
+        assert_ne!(read(7), read(8));
     }
 
     // -- tools/call echo ---------------------------------------------------
@@ -537,6 +1416,73 @@ mod tests {
         assert_eq!(content[0]["text"], "hello world");
     }
 
+    #[test]
+    fn hardware_reset_and_get_state_via_tools_call() {
+        let srv = server();
+        let call = |action: Value| {
+            let v = rpc(
+                &srv,
+                "tools/call",
+                json!({ "name": "hardware", "arguments": { "action": action } }),
+            );
+            let text = v["result"]["content"][0]["text"].as_str().unwrap();
+            serde_json::from_str::<Value>(text).unwrap()
+        };
+        let origin = json!({ "x": 0.0, "y": 0.0, "heading": 0.0, "speed": 0.0 });
+
+        call(json!({ "type": "drive", "direction": "right", "speed": 0.0 }));
+        call(json!({ "type": "drive", "direction": "forward", "speed": 5.0 }));
+        let state = call(json!({ "type": "get_state" }));
+        assert_eq!(state["action"], "get_state");
+        assert_eq!(state["data"]["heading"], 90.0);
+        assert_eq!(state["data"]["speed"], 5.0);
+        assert!((state["data"]["y"].as_f64().unwrap() - 5.0).abs() < 1e-9);
+        // Reading the state leaves it unchanged.
+        assert_eq!(call(json!({ "type": "get_state" }))["data"], state["data"]);
+
+        let reset = call(json!({ "type": "reset" }));
+        assert_eq!(reset["action"], "reset");
+        assert_eq!(reset["data"], origin);
+        assert_eq!(call(json!({ "type": "get_state" }))["data"], origin);
+    }
+
+    #[test]
+    fn hardware_can_be_left_out() {
+        let srv = McpServer::new(McpServer::registry_without_hardware());
+        assert_eq!(tool_names(&srv), vec!["echo"]);
+    }
+
+    // -- tool concurrency limits ------------------------------------------
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn busy_tool_is_reported_and_rejected() {
+        let limiter = Arc::new(ToolLimiter::new(crate::tool_limits::ToolLimitsConfig {
+            limits: [("hardware".to_string(), 1)].into(),
+            queue_timeout_ms: 20,
+            max_queued: None,
+        }));
+        let srv = McpServer::with_default_tools().with_tool_limiter(limiter.clone());
+        let _held = limiter.acquire("hardware").await.unwrap();
+
+        let tools = rpc(&srv, "tools/list", json!({}))["result"]["tools"].clone();
+        assert!(tools[0].get("concurrency").is_none());
+        assert_eq!(
+            tools[1]["concurrency"],
+            json!({ "limit": 1, "inFlight": 1, "queued": 0 })
+        );
+
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "hardware", "arguments": { "action": { "type": "speak", "text": "hi" } } }),
+        );
+        assert_eq!(v["error"]["code"], TOOL_BUSY);
+        assert_eq!(
+            v["error"]["data"],
+            json!({ "tool": "hardware", "limit": 1 })
+        );
+    }
+
     // -- unknown method → error -------------------------------------------
 
     #[test]
@@ -564,7 +1510,7 @@ mod tests {
         let v = parse_response(&resp);
 
         assert_eq!(v["id"], 5);
-        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        assert_eq!(v["error"]["code"], TOOL_NOT_FOUND);
         assert!(v["error"]["message"]
             .as_str()
             .unwrap()
@@ -573,14 +1519,53 @@ mod tests {
 
     // -- missing tool name in params → error ------------------------------
 
-    #[test]
-    fn tools_call_missing_name_returns_error() {
-        let srv = server();
-        let req = r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{}}"#;
-        let resp = srv.handle_message(req).expect("should produce a response");
-        let v = parse_response(&resp);
+    /// Fails every call with an error rather than a failed result.
+    struct BrokenTool;
 
-        assert_eq!(v["id"], 6);
+    #[async_trait::async_trait]
+    impl crate::tool::Tool for BrokenTool {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        fn description(&self) -> &str {
+            "Always errors"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, _args: Value) -> anyhow::Result<crate::tool::ToolResult> {
+            anyhow::bail!("sensor bus offline")
+        }
+    }
+
+    #[test]
+    fn tool_runtime_error_has_its_own_code() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(BrokenTool));
+        let srv = McpServer::new(registry);
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "broken", "arguments": {} }),
+        );
+        assert_eq!(v["error"]["code"], TOOL_EXECUTION_ERROR);
+        assert_eq!(
+            v["error"]["message"],
+            "Tool execution error: sensor bus offline"
+        );
+    }
+
+    #[test]
+    fn tools_call_missing_name_returns_error() {
+        let srv = server();
+        let req = r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{}}"#;
+        let resp = srv.handle_message(req).expect("should produce a response");
+        let v = parse_response(&resp);
+
+        assert_eq!(v["id"], 6);
         assert_eq!(v["error"]["code"], INVALID_PARAMS);
         assert!(v["error"]["message"]
             .as_str()
@@ -623,7 +1608,7 @@ mod tests {
 
         assert_eq!(v["id"], 7);
         let tools = v["result"]["tools"].as_array().expect("tools array");
-        assert_eq!(tools.len(), 1);
+        assert_eq!(tools.len(), 2);
     }
 
     // -- string id is preserved -------------------------------------------
@@ -748,6 +1733,162 @@ mod tests {
         assert!(v["error"]["message"].as_str().unwrap().contains("approval"));
     }
 
+    /// Approves calls and remembers what it was asked.
+    #[derive(Default)]
+    struct RecordingApprover(std::sync::Mutex<Vec<(String, Value)>>);
+
+    #[async_trait::async_trait]
+    impl ApprovalHandler for RecordingApprover {
+        async fn request_approval(&self, tool: &str, arguments: &Value) -> bool {
+            self.0
+                .lock()
+                .unwrap()
+                .push((tool.to_string(), arguments.clone()));
+            true
+        }
+    }
+
+    fn server_requiring_approval_for_echo() -> McpServer {
+        let policy = PolicyEngine::new(
+            Box::new(ProcessSandbox::new(SandboxProfile::Net)),
+            vec!["echo".into()],
+            vec![],
+            Duration::from_secs(30),
+        );
+        McpServer::with_policy(McpServer::registry_without_hardware(), policy)
+    }
+
+    #[test]
+    fn approved_call_executes_and_is_audited() {
+        let approver = Arc::new(RecordingApprover::default());
+        let srv = server_requiring_approval_for_echo().with_approval_handler(approver.clone());
+        let resp = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "input": "go" } }),
+        );
+        assert_eq!(resp["result"]["content"][0]["text"], "go");
+        assert_eq!(
+            *approver.0.lock().unwrap(),
+            vec![("echo".to_string(), json!({ "input": "go" }))]
+        );
+
+        let log = srv.audit_log();
+        let events: Vec<_> = log.entries().iter().map(|e| &e.event_type).collect();
+        assert_eq!(
+            events,
+            [
+                &AuditEventType::ToolCallAttempt,
+                &AuditEventType::ApprovalRequired,
+                &AuditEventType::ApprovalGranted,
+            ]
+        );
+        let granted = &log.entries()[2];
+        assert_eq!(granted.decision, "Approved");
+        assert_eq!(granted.decision_id, log.entries()[0].decision_id);
+    }
+
+    #[test]
+    fn approval_handlers_decide_calls_needing_approval() {
+        let call = json!({ "name": "echo", "arguments": { "input": "go" } });
+        let denied = server_requiring_approval_for_echo();
+        let resp = rpc(&denied, "tools/call", call.clone());
+        assert_eq!(resp["error"]["code"], APPROVAL_REQUIRED);
+        assert!(!denied
+            .audit_log()
+            .entries()
+            .iter()
+            .any(|e| e.event_type == AuditEventType::ApprovalGranted));
+
+        let approved = server_requiring_approval_for_echo()
+            .with_approval_handler(Arc::new(crate::policy::AlwaysApproveApprovalHandler));
+        let resp = rpc(&approved, "tools/call", call);
+        assert_eq!(resp["result"]["content"][0]["text"], "go");
+    }
+
+    #[test]
+    fn approved_calls_count_against_rate_limit() {
+        let policy = PolicyEngine::new(
+            Box::new(ProcessSandbox::new(SandboxProfile::Net)),
+            vec!["echo".into()],
+            vec![],
+            Duration::from_secs(30),
+        )
+        .with_rate_limit(
+            "echo",
+            crate::policy::ToolRateLimit {
+                max_calls: 1,
+                window_secs: 60,
+            },
+        );
+        let srv = McpServer::with_policy(McpServer::registry_without_hardware(), policy)
+            .with_approval_handler(Arc::new(crate::policy::AlwaysApproveApprovalHandler));
+        let call = json!({ "name": "echo", "arguments": { "input": "go" } });
+
+        let resp = rpc(&srv, "tools/call", call.clone());
+        assert_eq!(resp["result"]["content"][0]["text"], "go");
+        let resp = rpc(&srv, "tools/call", call);
+        assert_eq!(resp["error"]["code"], RATE_LIMITED);
+        let log = srv.audit_log();
+        let last = log.entries().last().unwrap();
+        assert_eq!(last.event_type, AuditEventType::AccessDenied);
+        assert_eq!(last.decision, "RateLimited");
+    }
+
+    #[test]
+    fn rate_limited_tool_returns_error() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+        let policy = PolicyEngine::new(
+            Box::new(ProcessSandbox::new(SandboxProfile::Net)),
+            vec![],
+            vec![],
+            Duration::from_secs(30),
+        )
+        .with_rate_limit(
+            "echo",
+            crate::policy::ToolRateLimit {
+                max_calls: 2,
+                window_secs: 60,
+            },
+        );
+        let srv = McpServer::with_policy(registry, policy);
+        let req = r#"{"jsonrpc":"2.0","id":12,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+
+        for _ in 0..2 {
+            let v = parse_response(&srv.handle_message(req).unwrap());
+            assert!(v["error"].is_null(), "unexpected error: {v}");
+        }
+        let v = parse_response(&srv.handle_message(req).unwrap());
+        assert_eq!(v["error"]["code"], RATE_LIMITED);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("rate limit"));
+        assert_eq!(
+            srv.audit_log()
+                .filter_by_tool("echo")
+                .last()
+                .unwrap()
+                .decision,
+            "RateLimited"
+        );
+    }
+
+    #[test]
+    fn schema_violations_are_reported_as_invalid_params() {
+        let srv = server();
+        let req = r#"{"jsonrpc":"2.0","id":13,"method":"tools/call","params":{"name":"echo","arguments":{"input":42}}}"#;
+        let v = parse_response(&srv.handle_message(req).unwrap());
+
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        let violations = v["error"]["data"]["violations"].as_array().unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0]["instance_path"], "/input");
+        assert_eq!(violations[0]["expected"], "string");
+        assert!(violations[0]["message"].as_str().unwrap().contains("42"));
+    }
+
     #[test]
     fn policy_allowed_tool_executes() {
         let srv = server_with_policy();
@@ -761,6 +1902,47 @@ mod tests {
         assert_eq!(content[0]["text"], "safe");
     }
 
+    #[test]
+    fn each_stdio_message_gets_its_own_request_id() {
+        let srv = server_with_policy();
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"dangerous_tool","arguments":{}}}"#;
+        srv.handle_message(req);
+        let first_count = srv.audit_log().len();
+        srv.handle_message(req);
+
+        let log = srv.audit_log();
+        let ids: Vec<_> = log
+            .entries()
+            .iter()
+            .map(|e| e.request_id.clone().expect("tagged"))
+            .collect();
+        let (first, second) = ids.split_at(first_count);
+        assert!(!first.is_empty() && !second.is_empty());
+        assert!(first.iter().all(|id| *id == first[0]));
+        assert!(second.iter().all(|id| *id == second[0]));
+        assert_ne!(first[0], second[0]);
+    }
+
+    #[test]
+    fn dump_audit_log_appends_jsonl() {
+        let srv = server_with_policy();
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"dangerous_tool","arguments":{}}}"#;
+        srv.handle_message(req);
+        let path = std::env::temp_dir().join(format!("ygn-audit-{}.jsonl", uuid::Uuid::new_v4()));
+
+        srv.dump_audit_log(&path).unwrap();
+        srv.dump_audit_log(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let entries: Vec<AuditEntry> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2 * srv.audit_log().len());
+        assert!(entries.iter().all(|e| e.tool_name == "dangerous_tool"));
+    }
+
     #[test]
     fn policy_audit_log_records_events() {
         let srv = server_with_policy();
@@ -782,4 +1964,721 @@ mod tests {
             log.len()
         );
     }
+
+    #[test]
+    fn audit_entries_share_the_decision_id_of_their_attempt() {
+        let srv = server_with_policy();
+        let denied = r#"{"jsonrpc":"2.0","id":15,"method":"tools/call","params":{"name":"dangerous_tool","arguments":{}}}"#;
+        srv.handle_message(denied);
+        let allowed = r#"{"jsonrpc":"2.0","id":16,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+        srv.handle_message(allowed);
+
+        let log = srv.audit_log();
+        let entries = log.entries();
+        let id_of = |event: AuditEventType| {
+            entries
+                .iter()
+                .find(|e| e.event_type == event)
+                .and_then(|e| e.decision_id.clone())
+                .unwrap()
+        };
+        let attempts: Vec<String> = entries
+            .iter()
+            .filter(|e| e.event_type == AuditEventType::ToolCallAttempt)
+            .map(|e| e.decision_id.clone().unwrap())
+            .collect();
+        assert_eq!(attempts.len(), 2);
+        assert_ne!(attempts[0], attempts[1]);
+        assert_eq!(id_of(AuditEventType::AccessDenied), attempts[0]);
+        assert_eq!(id_of(AuditEventType::AccessGranted), attempts[1]);
+    }
+
+    // -- progress -------------------------------------------------------------
+
+    /// Reports three progress updates, then succeeds.
+    struct ThreeStepTool;
+
+    #[async_trait::async_trait]
+    impl crate::tool::Tool for ThreeStepTool {
+        fn name(&self) -> &str {
+            "three_steps"
+        }
+
+        fn description(&self) -> &str {
+            "Reports progress three times"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<crate::tool::ToolResult> {
+            self.execute_with_progress(args, &ProgressReporter::noop())
+                .await
+        }
+
+        async fn execute_with_progress(
+            &self,
+            _args: Value,
+            progress: &ProgressReporter,
+        ) -> anyhow::Result<crate::tool::ToolResult> {
+            for step in 1..=3 {
+                progress.report(f64::from(step), Some(3.0), Some(format!("step {step}")));
+            }
+            Ok(crate::tool::ToolResult {
+                success: true,
+                content: "done".into(),
+                error: None,
+                truncated: None,
+            })
+        }
+    }
+
+    /// Returns the content variant named by its `kind` argument.
+    struct ContentTool;
+
+    #[async_trait::async_trait]
+    impl crate::tool::Tool for ContentTool {
+        fn name(&self) -> &str {
+            "content"
+        }
+
+        fn description(&self) -> &str {
+            "Returns text, JSON or binary content"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<crate::tool::ToolResult> {
+            let content = match args["kind"].as_str().unwrap_or_default() {
+                "json" => ToolContent::Json(json!({ "celsius": 21.5 })),
+                "image" => ToolContent::Binary {
+                    data: vec![0x89, b'P', b'N', b'G'],
+                    mime_type: "image/png".into(),
+                },
+                "pdf" => ToolContent::Binary {
+                    data: b"%PDF".to_vec(),
+                    mime_type: "application/pdf".into(),
+                },
+                "huge" => ToolContent::Text("0123456789abcdef".repeat(64 * 1024)),
+                _ => ToolContent::Text("plain".into()),
+            };
+            Ok(crate::tool::ToolResult {
+                success: true,
+                content,
+                error: None,
+                truncated: None,
+            })
+        }
+    }
+
+    #[test]
+    fn tool_content_maps_to_mcp_content_blocks() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(ContentTool));
+        let srv = McpServer::new(registry);
+        let block = |kind: &str| {
+            let resp = rpc(
+                &srv,
+                "tools/call",
+                json!({ "name": "content", "arguments": { "kind": kind } }),
+            );
+            resp["result"]["content"][0].clone()
+        };
+
+        assert_eq!(block("text"), json!({ "type": "text", "text": "plain" }));
+        let text = block("json");
+        assert_eq!(text["type"], "text");
+        assert_eq!(
+            serde_json::from_str::<Value>(text["text"].as_str().unwrap()).unwrap(),
+            json!({ "celsius": 21.5 })
+        );
+        assert_eq!(
+            block("image"),
+            json!({ "type": "image", "data": "iVBORw==", "mimeType": "image/png" })
+        );
+        assert_eq!(
+            block("pdf"),
+            json!({
+                "type": "resource",
+                "resource": {
+                    "uri": "tool://content/result",
+                    "mimeType": "application/pdf",
+                    "blob": "JVBERg==",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn oversized_output_is_truncated_and_spilled() {
+        let spill_dir = std::env::temp_dir().join(format!("ygn-spill-{}", uuid::Uuid::new_v4()));
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(ContentTool));
+        let srv = McpServer::new(registry).with_output_limits(Arc::new(ToolOutputLimits::new(
+            crate::tool_output::ToolOutputConfig {
+                max_bytes: 4096,
+                spill_dir: Some(spill_dir.clone()),
+                ..Default::default()
+            },
+        )));
+
+        let resp = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "content", "arguments": { "kind": "huge" } }),
+        );
+        assert_eq!(resp["result"]["isTruncated"], true);
+        let text = resp["result"]["content"][0]["text"].as_str().unwrap();
+        let (kept, marker) = text.split_once('\n').unwrap();
+        assert_eq!(kept.len(), 4096);
+        assert!(
+            marker.starts_with("[output truncated at byte 4096 of 1048576; full output in "),
+            "{marker}"
+        );
+
+        let path = marker
+            .trim_end_matches(']')
+            .rsplit_once("full output in ")
+            .unwrap()
+            .1;
+        assert!(std::path::Path::new(path).starts_with(&spill_dir));
+        let full = std::fs::read_to_string(path).unwrap();
+        assert_eq!(full, "0123456789abcdef".repeat(64 * 1024));
+        assert!(full.starts_with(kept));
+
+        let resp = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "content", "arguments": { "kind": "text" } }),
+        );
+        assert!(resp["result"].get("isTruncated").is_none());
+        std::fs::remove_dir_all(&spill_dir).ok();
+    }
+
+    #[test]
+    fn oversized_binary_output_is_rejected() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(ContentTool));
+        let srv = McpServer::new(registry).with_output_limits(Arc::new(ToolOutputLimits::new(
+            crate::tool_output::ToolOutputConfig {
+                max_bytes: 2,
+                ..Default::default()
+            },
+        )));
+
+        let resp = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "content", "arguments": { "kind": "image" } }),
+        );
+        assert_eq!(resp["error"]["code"], OUTPUT_TOO_LARGE);
+        assert_eq!(resp["error"]["data"], json!({ "bytes": 4, "limit": 2 }));
+    }
+
+    fn progress_server() -> (McpServer, Arc<std::sync::Mutex<Vec<Value>>>) {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(ThreeStepTool));
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let srv = McpServer::new(registry).with_notifier(move |n| sink.lock().unwrap().push(n));
+        (srv, sent)
+    }
+
+    #[test]
+    fn tools_call_with_progress_token_emits_notifications() {
+        let (srv, sent) = progress_server();
+        let raw = srv
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"three_steps","arguments":{},"_meta":{"progressToken":"tok-1"}}}"#,
+            )
+            .unwrap();
+        assert_eq!(parse_response(&raw)["result"]["content"][0]["text"], "done");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 3);
+        for (i, notification) in sent.iter().enumerate() {
+            assert_eq!(notification["jsonrpc"], "2.0");
+            assert_eq!(notification["method"], "notifications/progress");
+            assert!(notification.get("id").is_none());
+            let params = &notification["params"];
+            assert_eq!(params["progressToken"], "tok-1");
+            assert_eq!(params["progress"], (i + 1) as f64);
+            assert_eq!(params["total"], 3.0);
+            assert_eq!(params["message"], format!("step {}", i + 1));
+        }
+    }
+
+    #[test]
+    fn tools_call_without_progress_token_emits_nothing() {
+        let (srv, sent) = progress_server();
+        let raw = srv
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"three_steps","arguments":{}}}"#,
+            )
+            .unwrap();
+        assert_eq!(parse_response(&raw)["result"]["content"][0]["text"], "done");
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_level_filters_log_notifications() {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let srv = server_with_policy().with_notifier(move |n| sink.lock().unwrap().push(n));
+        let echo = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+
+        // Nothing is logged before the client picks a level.
+        srv.handle_message(echo).unwrap();
+        assert!(sent.lock().unwrap().is_empty());
+
+        let raw = srv
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"warning"}}"#,
+            )
+            .unwrap();
+        assert_eq!(parse_response(&raw)["result"], json!({}));
+        srv.handle_message(echo).unwrap();
+        srv.handle_message(
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"dangerous_tool","arguments":{}}}"#,
+        )
+        .unwrap();
+        srv.log_notification(LogLevel::Info, "test", "quiet");
+        srv.log_notification(LogLevel::Error, "test", "loud");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2, "{sent:?}");
+        assert_eq!(sent[0]["method"], "notifications/message");
+        assert_eq!(sent[0]["params"]["level"], "warning");
+        assert_eq!(sent[0]["params"]["logger"], "policy");
+        assert!(sent[0]["params"]["data"]
+            .as_str()
+            .unwrap()
+            .contains("dangerous_tool"));
+        assert_eq!(
+            sent[1]["params"],
+            json!({ "level": "error", "logger": "test", "data": "loud" })
+        );
+    }
+
+    #[test]
+    fn set_level_logs_tool_calls_and_rejects_unknown_levels() {
+        let (srv, sent) = progress_server();
+        let raw = srv
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"loud"}}"#,
+            )
+            .unwrap();
+        assert_eq!(parse_response(&raw)["error"]["code"], INVALID_PARAMS);
+
+        srv.handle_message(
+            r#"{"jsonrpc":"2.0","id":2,"method":"logging/setLevel","params":{"level":"info"}}"#,
+        )
+        .unwrap();
+        srv.handle_message(
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"three_steps","arguments":{}}}"#,
+        )
+        .unwrap();
+        let sent = sent.lock().unwrap();
+        let logged: Vec<&str> = sent
+            .iter()
+            .map(|n| n["params"]["data"].as_str().unwrap())
+            .collect();
+        assert_eq!(logged.len(), 2, "{logged:?}");
+        assert_eq!(logged[0], "calling 'three_steps'");
+        assert!(logged[1].starts_with("'three_steps' finished in"));
+    }
+
+    #[test]
+    fn cancelled_notification_stops_a_running_call() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(crate::tool::CountdownTool));
+        let srv = McpServer::new(registry);
+        let in_flight = srv.in_flight();
+        let canceller = std::thread::spawn(move || {
+            let id = json!(7);
+            while !in_flight.cancel(&id) {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+        });
+        let started = std::time::Instant::now();
+        let raw = srv
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"countdown","arguments":{"steps":100,"interval_ms":50}}}"#,
+            )
+            .unwrap();
+        canceller.join().unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+        let resp = parse_response(&raw);
+        assert_eq!(resp["id"], 7);
+        assert_eq!(resp["error"]["code"], REQUEST_CANCELLED);
+        assert!(srv.in_flight.tokens.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn cancellation_observed_before_the_call_runs_applies() {
+        let srv = McpServer::new(McpServer::registry_without_hardware());
+        let call = r#"{"jsonrpc":"2.0","id":"a","method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+        let cancelled =
+            r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":"a"}}"#;
+        srv.in_flight().observe(call);
+        srv.in_flight().observe(cancelled);
+        let resp = parse_response(&srv.handle_message(call).unwrap());
+        assert_eq!(resp["error"]["code"], REQUEST_CANCELLED);
+        // The notification itself gets no response.
+        assert!(srv.handle_message(cancelled).is_none());
+    }
+
+    #[test]
+    fn rejected_calls_stop_being_tracked() {
+        let srv = McpServer::new(McpServer::registry_without_hardware());
+        for call in [
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"missing"}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{}}"#,
+            r#"{"id":3,"method":"tools/call","params":{"name":"echo"}}"#,
+        ] {
+            srv.in_flight().observe(call);
+            let resp = parse_response(&srv.handle_message(call).unwrap());
+            assert!(resp["error"].is_object(), "{resp}");
+        }
+        assert!(srv.in_flight.tokens.lock().unwrap().is_empty());
+
+        // A late cancellation of a rejected id does not reach a new call
+        // that reuses it.
+        assert!(!srv.in_flight().cancel(&json!(1)));
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+        let resp = parse_response(&srv.handle_message(call).unwrap());
+        assert!(resp["result"].is_object(), "{resp}");
+    }
+
+    // -- namespaces -----------------------------------------------------------
+
+    #[test]
+    fn tools_resolve_by_namespace_alias_and_short_name() {
+        let mut registry = ToolRegistry::new();
+        registry.register_in("node-a", Box::new(EchoTool));
+        registry.register_in("node-b", Box::new(EchoTool));
+        registry.alias("say", "node-b/echo").unwrap();
+        let policy = PolicyEngine::new(
+            Box::new(ProcessSandbox::new(SandboxProfile::Net)),
+            vec![],
+            vec!["node-b/echo".to_string()],
+            Duration::from_secs(5),
+        );
+        let srv = McpServer::with_policy(registry, policy);
+
+        assert_eq!(tool_names(&srv), ["node-a/echo", "node-b/echo"]);
+
+        let args = json!({ "input": "hi" });
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "echo", "arguments": args }),
+        );
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        assert_eq!(
+            v["error"]["data"]["candidates"],
+            json!(["node-a/echo", "node-b/echo"])
+        );
+
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "node-a/echo", "arguments": args }),
+        );
+        assert_eq!(v["result"]["content"][0]["text"], "hi");
+
+        // The alias resolves before the policy check, so the deny rule on
+        // the qualified name still applies.
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "say", "arguments": args }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+    }
+
+    // -- runtime tool registration -----------------------------------------
+
+    /// Helper: a server whose sandbox only lets tools run `sh`.
+    fn server_allowing_sh(denied: Vec<String>) -> McpServer {
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        sandbox.allow_command("sh");
+        let policy = PolicyEngine::new(Box::new(sandbox), vec![], denied, Duration::from_secs(5));
+        McpServer::with_policy(McpServer::default_registry(), policy)
+    }
+
+    fn rpc(srv: &McpServer, method: &str, params: Value) -> Value {
+        srv.handle_jsonrpc(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .expect("should produce a response")
+    }
+
+    fn tool_names(srv: &McpServer) -> Vec<String> {
+        rpc(srv, "tools/list", json!({}))["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn registered_tool_can_be_listed_called_and_unregistered() {
+        let srv = server_allowing_sh(vec![]);
+        let schema = json!({
+            "type": "object",
+            "properties": { "who": { "type": "string" } },
+            "required": ["who"]
+        });
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({
+                "name": "greet",
+                "description": "Greets from a script",
+                "inputSchema": schema,
+                "command": "sh",
+                "args": ["-c", r#"grep -q '"who":"ygn"' && echo '{"success":true,"output":"hello ygn","error":null}'"#]
+            }),
+        );
+        assert_eq!(v["result"]["registered"], "greet");
+
+        let v = rpc(&srv, "tools/list", json!({}));
+        let greet = v["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|t| t["name"] == "greet")
+            .expect("greet is listed");
+        assert_eq!(greet["description"], "Greets from a script");
+        assert_eq!(greet["inputSchema"], schema);
+
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "greet", "arguments": { "who": "ygn" } }),
+        );
+        assert_eq!(v["result"]["content"][0]["text"], "hello ygn");
+        assert!(v["result"].get("isError").is_none());
+
+        // The registered schema is enforced before the program runs.
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "greet", "arguments": {} }),
+        );
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+
+        let v = rpc(&srv, "tools/unregister", json!({ "name": "greet" }));
+        assert_eq!(v["result"]["unregistered"], "greet");
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "greet", "arguments": { "who": "ygn" } }),
+        );
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Tool not found"));
+    }
+
+    #[test]
+    fn register_requires_a_policy_engine() {
+        let srv = server();
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "greet", "command": "sh" }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+    }
+
+    #[test]
+    fn register_is_denied_under_the_default_policy() {
+        let srv = McpServer::with_policy(
+            McpServer::default_registry(),
+            PolicyEngine::from_config(crate::policy::PolicyConfig::default()),
+        );
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "greet", "command": "sh" }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("command allowlist"));
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+    }
+
+    #[test]
+    fn register_checks_command_allowlist() {
+        let srv = server_allowing_sh(vec![]);
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "wipe", "command": "rm", "args": ["-rf", "/"] }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not in the command allowlist"));
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+        assert!(srv.audit_log().entries().iter().any(
+            |e| e.tool_name == "tools/register" && e.event_type == AuditEventType::AccessDenied
+        ));
+    }
+
+    #[test]
+    fn register_can_be_denied_by_policy() {
+        let srv = server_allowing_sh(vec!["tools/register".into()]);
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "greet", "command": "sh" }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("deny list"));
+    }
+
+    #[test]
+    fn register_rejects_duplicates_and_bad_schemas() {
+        let srv = server_allowing_sh(vec![]);
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "echo", "command": "sh" }),
+        );
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("already registered"));
+
+        let v = rpc(
+            &srv,
+            "tools/register",
+            json!({ "name": "odd", "command": "sh", "inputSchema": { "type": 5 } }),
+        );
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid inputSchema"));
+    }
+
+    #[test]
+    fn builtin_tools_cannot_be_unregistered() {
+        let srv = server_allowing_sh(vec![]);
+        let v = rpc(&srv, "tools/unregister", json!({ "name": "echo" }));
+        assert_eq!(v["error"]["code"], INVALID_PARAMS);
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+    }
+
+    // -- brain-proxy forwarding --------------------------------------------
+
+    /// Helper: serve the gateway router on a local port and return a proxy
+    /// to it named `core-1`.
+    async fn remote_core() -> Arc<McpProxy> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let data_dir = std::env::temp_dir().join(format!("ygn-mcp-core-{}", uuid::Uuid::new_v4()));
+        let app = crate::gateway::build_router_with_state(crate::gateway::AppState::new(
+            crate::config::NodeConfig::default(),
+            &data_dir,
+        ));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Arc::new(McpProxy::new(&url).unwrap().with_node_id("core-1"))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn proxy_lists_and_forwards_remote_tools() {
+        let srv = server_with_policy().with_proxy(remote_core().await);
+
+        // The remote echo clashes with the local one.
+        assert_eq!(tool_names(&srv), vec!["echo", "core-1/echo", "hardware"]);
+
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "core-1/echo", "arguments": { "input": "via proxy" } }),
+        );
+        assert_eq!(v["result"]["content"][0]["text"], "via proxy");
+        assert!(
+            srv.audit_log()
+                .entries()
+                .iter()
+                .any(|e| e.tool_name == "core-1/echo"
+                    && e.event_type == AuditEventType::AccessGranted)
+        );
+
+        // Remote JSON-RPC errors come back unchanged.
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "missing", "arguments": {} }),
+        );
+        assert_eq!(v["error"]["code"], TOOL_NOT_FOUND);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Tool not found: missing"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn proxy_applies_local_policy_before_forwarding() {
+        let policy = PolicyEngine::new(
+            Box::new(ProcessSandbox::new(SandboxProfile::Net)),
+            vec![],
+            vec!["core-1/*".into()],
+            Duration::from_secs(30),
+        );
+        let srv = McpServer::with_policy(McpServer::default_registry(), policy)
+            .with_proxy(remote_core().await);
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "core-1/echo", "arguments": { "input": "hi" } }),
+        );
+        assert_eq!(v["error"]["code"], POLICY_DENIED);
+    }
+
+    #[test]
+    fn unreachable_remote_keeps_local_tools_working() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let proxy = McpProxy::new(&format!("http://127.0.0.1:{port}")).unwrap();
+        let srv = server().with_proxy(Arc::new(proxy));
+
+        assert_eq!(tool_names(&srv), vec!["echo", "hardware"]);
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "input": "local" } }),
+        );
+        assert_eq!(v["result"]["content"][0]["text"], "local");
+        let v = rpc(
+            &srv,
+            "tools/call",
+            json!({ "name": "remote_only", "arguments": {} }),
+        );
+        assert_eq!(v["error"]["code"], REMOTE_UNREACHABLE);
+        assert!(v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("unreachable"));
+    }
 }
@@ -0,0 +1,536 @@
+//! MCP client mode: consume tools from external MCP servers.
+//!
+//! An [`McpClient`] spawns an MCP server as a child process (command + args
+//! from config), performs the `initialize` handshake over stdio, and lists
+//! the server's tools.  Each remote tool is wrapped as a local [`Tool`]
+//! ([`McpRemoteTool`]) named `<server>/<tool>` whose `execute()` forwards a
+//! `tools/call` request, so external tools can be federated into the Y-GN
+//! [`ToolRegistry`] without shadowing local tools.
+//!
+//! If the child process dies, the next request transparently restarts it;
+//! a request that was in flight when the child died fails with an error.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolContent, ToolRegistry, ToolResult, ToolSpec};
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// How to launch an external MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema)]
+pub struct McpServerConfig {
+    /// Executable to spawn.
+    pub command: String,
+    /// Arguments passed to the executable.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Per-request timeout in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+// ---------------------------------------------------------------------------
+// McpClient
+// ---------------------------------------------------------------------------
+
+/// A live stdio connection to a child MCP server.
+struct Connection {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// Client for a single external MCP server reached over stdio.
+pub struct McpClient {
+    name: String,
+    config: McpServerConfig,
+    conn: Mutex<Option<Connection>>,
+    next_id: AtomicU64,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("name", &self.name)
+            .field("command", &self.config.command)
+            .finish_non_exhaustive()
+    }
+}
+
+impl McpClient {
+    /// Create a client. The child process is started lazily on first use.
+    pub fn new(name: impl Into<String>, config: McpServerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            conn: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Name of the server as configured.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Start the child process (if not running) and complete the handshake.
+    pub async fn connect(&self) -> anyhow::Result<()> {
+        let mut guard = self.conn.lock().await;
+        self.ensure_connected(&mut guard).await
+    }
+
+    /// PID of the running child process, if any.
+    pub async fn pid(&self) -> Option<u32> {
+        self.conn.lock().await.as_ref().and_then(|c| c.child.id())
+    }
+
+    /// Send a JSON-RPC request and return its `result`.
+    ///
+    /// JSON-RPC errors are mapped to `Err` with the server's code and message.
+    pub async fn request(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let mut guard = self.conn.lock().await;
+        self.ensure_connected(&mut guard).await?;
+        let conn = guard.as_mut().expect("connected above");
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let outcome = tokio::time::timeout(timeout, self.roundtrip(conn, method, params)).await;
+        match outcome {
+            Ok(Ok(response)) => self.response_result(response),
+            Ok(Err(e)) => {
+                // The pipe broke or the child exited: drop the connection so
+                // the next request restarts the server.
+                *guard = None;
+                Err(e)
+            }
+            Err(_) => {
+                *guard = None;
+                anyhow::bail!(
+                    "MCP server '{}' timed out after {}s on {method}",
+                    self.name,
+                    self.config.timeout_secs
+                )
+            }
+        }
+    }
+
+    /// List the server's tools.
+    pub async fn list_tools(&self) -> anyhow::Result<Vec<ToolSpec>> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(parse_tool_list(&result))
+    }
+
+    /// Call a remote tool and translate the MCP result into a [`ToolResult`].
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> anyhow::Result<ToolResult> {
+        let result = self
+            .request(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        Ok(translate_call_result(&result))
+    }
+
+    /// Connect, list tools, and wrap each one as a local [`Tool`].
+    pub async fn import_tools(self: &Arc<Self>) -> anyhow::Result<Vec<Box<dyn Tool>>> {
+        let specs = self.list_tools().await?;
+        Ok(specs
+            .into_iter()
+            .map(|spec| {
+                Box::new(McpRemoteTool {
+                    client: Arc::clone(self),
+                    name: format!("{}/{}", self.name, spec.name),
+                    spec,
+                }) as Box<dyn Tool>
+            })
+            .collect())
+    }
+
+    /// Terminate the child process, if running.
+    pub async fn shutdown(&self) {
+        if let Some(mut conn) = self.conn.lock().await.take() {
+            let _ = conn.child.kill().await;
+        }
+    }
+
+    // -- internals ---------------------------------------------------------
+
+    async fn ensure_connected(&self, guard: &mut Option<Connection>) -> anyhow::Result<()> {
+        if let Some(conn) = guard.as_mut() {
+            match conn.child.try_wait() {
+                Ok(None) => return Ok(()),
+                Ok(Some(status)) => {
+                    tracing::warn!(server = %self.name, %status, "MCP server exited; restarting");
+                }
+                Err(e) => {
+                    tracing::warn!(server = %self.name, error = %e, "MCP server state unknown; restarting");
+                }
+            }
+            *guard = None;
+        }
+
+        let mut conn = self
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("MCP server '{}' failed to start: {e}", self.name))?;
+        let init = self
+            .roundtrip(
+                &mut conn,
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "ygn-core",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+            .await
+            .and_then(|resp| self.response_result(resp))
+            .map_err(|e| anyhow::anyhow!("MCP server '{}' failed to initialize: {e}", self.name))?;
+        tracing::debug!(server = %self.name, info = %init, "MCP server initialized");
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        conn.stdin
+            .write_all(format!("{notification}\n").as_bytes())
+            .await?;
+        conn.stdin.flush().await?;
+
+        *guard = Some(conn);
+        Ok(())
+    }
+
+    fn spawn(&self) -> std::io::Result<Connection> {
+        let mut child = Command::new(&self.config.command)
+            .args(&self.config.args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Connection {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Write one request and read lines until the matching response arrives.
+    async fn roundtrip(
+        &self,
+        conn: &mut Connection,
+        method: &str,
+        params: Value,
+    ) -> anyhow::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        conn.stdin
+            .write_all(format!("{request}\n").as_bytes())
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("MCP server '{}' is not accepting input: {e}", self.name)
+            })?;
+        conn.stdin.flush().await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let n = conn.stdout.read_line(&mut line).await?;
+            if n == 0 {
+                anyhow::bail!("MCP server '{}' exited during {method}", self.name);
+            }
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            // Skip notifications and responses to other requests.
+            if message.get("id").and_then(|v| v.as_u64()) == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+
+    fn response_result(&self, response: Value) -> anyhow::Result<Value> {
+        if let Some(err) = response.get("error") {
+            let code = err.get("code").and_then(|c| c.as_i64()).unwrap_or(0);
+            let message = err
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            anyhow::bail!("MCP server '{}' error {code}: {message}", self.name);
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+/// The tools described by an MCP `tools/list` result.  Entries without a
+/// name are skipped.
+pub fn parse_tool_list(result: &Value) -> Vec<ToolSpec> {
+    let tools = result
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .cloned()
+        .unwrap_or_default();
+    tools
+        .iter()
+        .filter_map(|t| {
+            Some(ToolSpec {
+                name: t.get("name")?.as_str()?.to_string(),
+                description: t
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                parameters_schema: t
+                    .get("inputSchema")
+                    .cloned()
+                    .unwrap_or_else(|| json!({ "type": "object" })),
+            })
+        })
+        .collect()
+}
+
+/// Translate an MCP `tools/call` result into a [`ToolResult`].
+///
+/// Text content items are joined with newlines; `isError: true` maps to a
+/// failed result carrying the text as its error.  A result with no text
+/// but an `image` or embedded `resource` blob becomes binary content.
+pub fn translate_call_result(result: &Value) -> ToolResult {
+    let items = result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let text = items
+        .iter()
+        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+        ToolResult {
+            success: false,
+            content: ToolContent::default(),
+            error: Some(text),
+            truncated: None,
+        }
+    } else {
+        let binary = items.iter().find_map(binary_content);
+        ToolResult {
+            success: true,
+            content: match binary {
+                Some(binary) if text.is_empty() => binary,
+                _ => ToolContent::Text(text),
+            },
+            error: None,
+            truncated: None,
+        }
+    }
+}
+
+/// Binary content from an `image` block or a `resource` block with a blob.
+fn binary_content(item: &Value) -> Option<ToolContent> {
+    use base64::Engine;
+    let source = match item.get("type")?.as_str()? {
+        "image" => item,
+        "resource" => item.get("resource")?,
+        _ => return None,
+    };
+    let data = source
+        .get("data")
+        .or_else(|| source.get("blob"))?
+        .as_str()?;
+    Some(ToolContent::Binary {
+        data: base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .ok()?,
+        mime_type: source.get("mimeType")?.as_str()?.to_string(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// McpRemoteTool
+// ---------------------------------------------------------------------------
+
+/// A tool exposed by an external MCP server, registered locally as
+/// `<server>/<tool>`.
+pub struct McpRemoteTool {
+    client: Arc<McpClient>,
+    /// Namespaced local name.
+    name: String,
+    /// The tool as the remote server describes it.
+    spec: ToolSpec,
+}
+
+impl std::fmt::Debug for McpRemoteTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpRemoteTool")
+            .field("server", &self.client.name)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Tool for McpRemoteTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.spec.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.spec.parameters_schema.clone()
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        self.client.call_tool(&self.spec.name, args).await
+    }
+
+    /// The remote server validates its own arguments, against a schema we
+    /// may not be able to compile.
+    fn validates_arguments(&self) -> bool {
+        false
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Startup import
+// ---------------------------------------------------------------------------
+
+/// Import the tools of every configured MCP server into `registry`.
+///
+/// Servers that fail to start are logged and skipped so one broken entry
+/// does not prevent startup, as are tools whose namespaced name is already
+/// registered. Returns the clients that connected.
+pub async fn import_configured_tools(
+    servers: &BTreeMap<String, McpServerConfig>,
+    registry: &mut ToolRegistry,
+) -> Vec<Arc<McpClient>> {
+    let mut clients = Vec::new();
+    for (name, config) in servers {
+        let client = Arc::new(McpClient::new(name.clone(), config.clone()));
+        match client.import_tools().await {
+            Ok(tools) => {
+                tracing::info!(server = %name, count = tools.len(), "imported MCP tools");
+                for tool in tools {
+                    if registry.is_registered(tool.name()) {
+                        tracing::warn!(server = %name, tool = %tool.name(), "skipping duplicate MCP tool");
+                        continue;
+                    }
+                    registry.register(tool);
+                }
+                clients.push(client);
+            }
+            Err(e) => {
+                tracing::warn!(server = %name, error = %e, "skipping MCP server");
+            }
+        }
+    }
+    clients
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_text_result() {
+        let result = json!({
+            "content": [
+                { "type": "text", "text": "line 1" },
+                { "type": "text", "text": "line 2" }
+            ]
+        });
+        let tr = translate_call_result(&result);
+        assert!(tr.success);
+        assert_eq!(tr.output(), "line 1\nline 2");
+        assert!(tr.error.is_none());
+    }
+
+    #[test]
+    fn translate_error_result() {
+        let result = json!({
+            "content": [{ "type": "text", "text": "boom" }],
+            "isError": true
+        });
+        let tr = translate_call_result(&result);
+        assert!(!tr.success);
+        assert_eq!(tr.error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn translate_image_result() {
+        let result = json!({
+            "content": [{ "type": "image", "data": "iVBORw==", "mimeType": "image/png" }]
+        });
+        let tr = translate_call_result(&result);
+        assert_eq!(
+            tr.content,
+            ToolContent::Binary {
+                data: vec![0x89, b'P', b'N', b'G'],
+                mime_type: "image/png".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn server_config_defaults() {
+        let cfg: McpServerConfig = serde_json::from_value(json!({ "command": "fs-mcp" })).unwrap();
+        assert!(cfg.args.is_empty());
+        assert_eq!(cfg.timeout_secs, 30);
+    }
+
+    #[tokio::test]
+    async fn missing_command_surfaces_start_error() {
+        let client = McpClient::new(
+            "ghost",
+            McpServerConfig {
+                command: "/nonexistent/ygn-mcp-server".to_string(),
+                args: vec![],
+                timeout_secs: 5,
+            },
+        );
+        let err = client.connect().await.unwrap_err().to_string();
+        assert!(err.contains("'ghost' failed to start"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn import_configured_skips_broken_servers() {
+        let mut servers = BTreeMap::new();
+        servers.insert(
+            "broken".to_string(),
+            McpServerConfig {
+                command: "/nonexistent/ygn-mcp-server".to_string(),
+                args: vec![],
+                timeout_secs: 5,
+            },
+        );
+        let mut registry = ToolRegistry::new();
+        let clients = import_configured_tools(&servers, &mut registry).await;
+        assert!(clients.is_empty());
+        assert!(registry.is_empty());
+    }
+}
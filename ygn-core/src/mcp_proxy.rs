@@ -0,0 +1,206 @@
+//! Brain-proxy forwarding: relay MCP requests to a remote node.
+//!
+//! An [`McpProxy`] speaks MCP over HTTP to another node's `POST /mcp`
+//! endpoint.  Attached to an [`McpServer`](crate::mcp::McpServer) with
+//! [`with_proxy`](crate::mcp::McpServer::with_proxy), it lets a
+//! `BrainProxy` node serve the remote node's tools next to its own: the
+//! server merges both tool lists and forwards calls it cannot serve
+//! locally, after its own policy check.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use reqwest::Url;
+use serde_json::{json, Value};
+
+use crate::mcp_client::parse_tool_list;
+use crate::tool::ToolSpec;
+
+/// Default per-request timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why a forwarded request failed.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProxyError {
+    /// The remote could not be reached or did not answer with JSON-RPC.
+    #[error("remote node '{node_id}' is unreachable: {reason}")]
+    Unreachable { node_id: String, reason: String },
+    /// The remote answered with a JSON-RPC error.
+    #[error("remote node error {code}: {message}")]
+    Remote {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+}
+
+/// Client for a remote node's MCP-over-HTTP endpoint.
+#[derive(Debug)]
+pub struct McpProxy {
+    node_id: String,
+    endpoint: Url,
+    client: reqwest::Client,
+    api_key: Option<String>,
+    next_id: AtomicU64,
+}
+
+impl McpProxy {
+    /// Proxy to the node at `url`.  A URL without a path (e.g.
+    /// "http://10.0.0.5:3000") means the node's `/mcp` endpoint.  The node
+    /// id defaults to the URL's `host[:port]`.
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        let mut endpoint =
+            Url::parse(url).map_err(|e| anyhow::anyhow!("invalid proxy URL '{url}': {e}"))?;
+        if !matches!(endpoint.scheme(), "http" | "https") {
+            anyhow::bail!("unsupported proxy URL scheme: {}", endpoint.scheme());
+        }
+        let host = endpoint
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("proxy URL has no host: {url}"))?;
+        let node_id = match endpoint.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        if endpoint.path() == "/" {
+            endpoint.set_path("/mcp");
+        }
+        Ok(Self {
+            node_id,
+            endpoint,
+            client: Self::build_client(DEFAULT_TIMEOUT),
+            api_key: None,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Name the remote node; remote tools whose names clash with local
+    /// ones are listed as `<node_id>/<tool>`.
+    pub fn with_node_id(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = node_id.into();
+        self
+    }
+
+    /// Send `key` as a bearer token with every request.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Set the per-request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(timeout);
+        self
+    }
+
+    fn build_client(timeout: Duration) -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default()
+    }
+
+    /// Id of the remote node.
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// The endpoint requests are posted to.
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_str()
+    }
+
+    /// Send a JSON-RPC request and return its `result`.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, ProxyError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let mut request = self.client.post(self.endpoint.clone()).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let unreachable = |reason: String| ProxyError::Unreachable {
+            node_id: self.node_id.clone(),
+            reason,
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| unreachable(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(unreachable(format!("HTTP {status}")));
+        }
+        let response: Value = response
+            .json()
+            .await
+            .map_err(|e| unreachable(format!("invalid response: {e}")))?;
+        if let Some(error) = response.get("error") {
+            return Err(ProxyError::Remote {
+                code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(0),
+                message: error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error")
+                    .to_string(),
+                data: error.get("data").cloned(),
+            });
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| unreachable("response has neither result nor error".into()))
+    }
+
+    /// List the remote node's tools.
+    pub async fn list_tools(&self) -> Result<Vec<ToolSpec>, ProxyError> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(parse_tool_list(&result))
+    }
+
+    /// Call a remote tool and return the MCP `tools/call` result as is.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, ProxyError> {
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_url_targets_mcp_endpoint() {
+        let proxy = McpProxy::new("http://10.0.0.5:3000").unwrap();
+        assert_eq!(proxy.endpoint(), "http://10.0.0.5:3000/mcp");
+        assert_eq!(proxy.node_id(), "10.0.0.5:3000");
+
+        let proxy = McpProxy::new("https://core.example.com/custom/mcp")
+            .unwrap()
+            .with_node_id("core-1");
+        assert_eq!(proxy.endpoint(), "https://core.example.com/custom/mcp");
+        assert_eq!(proxy.node_id(), "core-1");
+
+        assert!(McpProxy::new("ftp://example.com").is_err());
+        assert!(McpProxy::new("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn connection_failure_is_unreachable() {
+        // Bind and drop a listener to find a port nothing listens on.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let proxy = McpProxy::new(&format!("http://127.0.0.1:{port}")).unwrap();
+        let err = proxy.list_tools().await.unwrap_err();
+        assert!(matches!(err, ProxyError::Unreachable { .. }), "{err}");
+    }
+}
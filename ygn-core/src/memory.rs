@@ -24,6 +24,56 @@ pub enum MemoryCategory {
     Custom(String),
 }
 
+impl std::fmt::Display for MemoryCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryCategory::Core => f.write_str("core"),
+            MemoryCategory::Daily => f.write_str("daily"),
+            MemoryCategory::Conversation => f.write_str("conversation"),
+            MemoryCategory::Custom(name) => write!(f, "custom:{name}"),
+        }
+    }
+}
+
+impl std::str::FromStr for MemoryCategory {
+    type Err = std::convert::Infallible;
+
+    /// `core`, `daily` and `conversation` name the built-in categories;
+    /// anything else (optionally prefixed with `custom:`) is a custom one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "core" => MemoryCategory::Core,
+            "daily" => MemoryCategory::Daily,
+            "conversation" => MemoryCategory::Conversation,
+            other => {
+                MemoryCategory::Custom(other.strip_prefix("custom:").unwrap_or(other).to_string())
+            }
+        })
+    }
+}
+
+/// Which timestamp [`Memory::list`] sorts by, newest first.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListOrder {
+    #[default]
+    UpdatedAt,
+    CreatedAt,
+}
+
+/// How a recall query's words must appear in a matching entry.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Any one of the words.
+    #[default]
+    AnyWord,
+    /// Every word, in any order.
+    AllWords,
+    /// The words together, in order.
+    Phrase,
+}
+
 /// A single memory entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryEntry {
@@ -34,6 +84,9 @@ pub struct MemoryEntry {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub metadata: serde_json::Value,
+    /// Session the entry is scoped to; `None` for a global entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -68,6 +121,23 @@ pub trait Memory: Send + Sync {
 
     /// Check whether the memory backend is healthy.
     async fn health_check(&self) -> anyhow::Result<bool>;
+
+    /// List entries, optionally in one category, newest first by `order`.
+    /// Skips the first `offset` entries and returns at most `limit`.
+    async fn list(
+        &self,
+        _category: Option<MemoryCategory>,
+        _offset: usize,
+        _limit: usize,
+        _order: ListOrder,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        anyhow::bail!("this memory backend does not support listing")
+    }
+
+    /// Number of entries, optionally in one category.
+    async fn count(&self, _category: Option<MemoryCategory>) -> anyhow::Result<usize> {
+        anyhow::bail!("this memory backend does not support counting")
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -95,6 +165,7 @@ impl Memory for NoopMemory {
             created_at: now,
             updated_at: now,
             metadata: serde_json::json!({}),
+            session_id: None,
         })
     }
 
@@ -122,6 +193,20 @@ impl Memory for NoopMemory {
     async fn health_check(&self) -> anyhow::Result<bool> {
         Ok(true)
     }
+
+    async fn list(
+        &self,
+        _category: Option<MemoryCategory>,
+        _offset: usize,
+        _limit: usize,
+        _order: ListOrder,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(vec![])
+    }
+
+    async fn count(&self, _category: Option<MemoryCategory>) -> anyhow::Result<usize> {
+        Ok(0)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -175,6 +260,20 @@ mod tests {
         assert!(mem.health_check().await.unwrap());
     }
 
+    #[test]
+    fn memory_category_from_str() {
+        assert_eq!("daily".parse(), Ok(MemoryCategory::Daily));
+        assert_eq!(
+            "custom:notes".parse(),
+            Ok(MemoryCategory::Custom("notes".into()))
+        );
+        assert_eq!("notes".parse(), Ok(MemoryCategory::Custom("notes".into())));
+        assert_eq!(
+            MemoryCategory::Custom("notes".into()).to_string(),
+            "custom:notes"
+        );
+    }
+
     #[test]
     fn memory_category_custom() {
         let cat = MemoryCategory::Custom("project-notes".to_string());
@@ -193,6 +292,7 @@ mod tests {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             metadata: serde_json::json!({}),
+            session_id: None,
         };
         let json = serde_json::to_string(&entry).unwrap();
         let round: MemoryEntry = serde_json::from_str(&json).unwrap();
@@ -0,0 +1,549 @@
+//! Prometheus metrics for the gateway, providers, tools and MCP.
+//!
+//! A small in-crate registry of labelled counters and histograms rendered in
+//! the Prometheus text exposition format by `GET /metrics`.  Every label is
+//! drawn from a bounded set (route templates, provider and tool names,
+//! policy actions, known MCP methods) so cardinality stays fixed.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::policy::PolicyAction;
+use crate::provider::{
+    ChatRequest, ChatResponse, ChatStream, ProbeResult, Provider, ProviderCapabilities,
+    ProviderError,
+};
+use crate::provider_cache::CacheStats;
+use crate::tool::ToolSpec;
+
+/// Histogram buckets for HTTP handlers and tools, in seconds.
+const HTTP_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Histogram buckets for LLM calls, in seconds.
+const PROVIDER_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+// ---------------------------------------------------------------------------
+// Primitives
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn inc(&self, values: &[&str]) {
+        let key = values.iter().map(|v| v.to_string()).collect();
+        *self.values.lock().unwrap().entry(key).or_default() += 1;
+    }
+
+    fn get(&self, values: &[&str]) -> u64 {
+        let key: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        self.values.lock().unwrap().get(&key).copied().unwrap_or(0)
+    }
+
+    /// Sum over the label sets accepted by `filter`.
+    fn sum(&self, filter: impl Fn(&[String]) -> bool) -> u64 {
+        self.values
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(values, _)| filter(values))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} counter", self.name);
+        for (values, count) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "{}{} {count}",
+                self.name,
+                label_set(self.labels, values, None)
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct HistogramData {
+    /// Per-bucket (non-cumulative) counts.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug)]
+struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    buckets: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, HistogramData>>,
+}
+
+impl HistogramVec {
+    fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            buckets,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn observe(&self, values: &[&str], elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let key = values.iter().map(|v| v.to_string()).collect();
+        let mut map = self.values.lock().unwrap();
+        let data = map.entry(key).or_insert_with(|| HistogramData {
+            counts: vec![0; self.buckets.len()],
+            ..Default::default()
+        });
+        if let Some(i) = self.buckets.iter().position(|b| secs <= *b) {
+            data.counts[i] += 1;
+        }
+        data.sum += secs;
+        data.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} histogram", self.name);
+        for (values, data) in self.values.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in self.buckets.iter().zip(&data.counts) {
+                cumulative += count;
+                let le = bound.to_string();
+                let labels = label_set(self.labels, values, Some(&le));
+                let _ = writeln!(out, "{}_bucket{labels} {cumulative}", self.name);
+            }
+            let labels = label_set(self.labels, values, Some("+Inf"));
+            let _ = writeln!(out, "{}_bucket{labels} {}", self.name, data.count);
+            let labels = label_set(self.labels, values, None);
+            let _ = writeln!(out, "{}_sum{labels} {}", self.name, data.sum);
+            let _ = writeln!(out, "{}_count{labels} {}", self.name, data.count);
+        }
+    }
+}
+
+/// Format `{a="x",b="y"}`, optionally with a trailing `le` label.
+fn label_set(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// ---------------------------------------------------------------------------
+// Metrics
+// ---------------------------------------------------------------------------
+
+/// All metrics exported by a node.  Shared as `Arc<Metrics>`.
+#[derive(Debug)]
+pub struct Metrics {
+    http_requests: CounterVec,
+    http_duration: HistogramVec,
+    provider_duration: HistogramVec,
+    provider_failures: CounterVec,
+    tool_executions: CounterVec,
+    tool_duration: HistogramVec,
+    policy_decisions: CounterVec,
+    mcp_requests: CounterVec,
+    tools_in_flight: AtomicU64,
+}
+
+/// Node-wide totals from [`Metrics::summary`], small enough to gossip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSummary {
+    pub http_requests: u64,
+    pub tool_executions: u64,
+    pub tool_failures: u64,
+    pub provider_failures: u64,
+    /// Tool executions started but not yet finished.
+    pub tools_in_flight: u64,
+}
+
+/// Counts a tool execution in `ygn_tools_in_flight` until dropped.
+#[derive(Debug)]
+pub struct ToolInFlight<'a>(&'a AtomicU64);
+
+impl Drop for ToolInFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            http_requests: CounterVec::new(
+                "ygn_http_requests_total",
+                "HTTP requests handled by the gateway.",
+                &["route", "method", "status"],
+            ),
+            http_duration: HistogramVec::new(
+                "ygn_http_request_duration_seconds",
+                "Gateway request latency.",
+                &["route"],
+                HTTP_BUCKETS,
+            ),
+            provider_duration: HistogramVec::new(
+                "ygn_provider_chat_duration_seconds",
+                "LLM provider chat latency.",
+                &["provider"],
+                PROVIDER_BUCKETS,
+            ),
+            provider_failures: CounterVec::new(
+                "ygn_provider_chat_failures_total",
+                "LLM provider chat calls that returned an error.",
+                &["provider"],
+            ),
+            tool_executions: CounterVec::new(
+                "ygn_tool_executions_total",
+                "Tool executions by tool and outcome.",
+                &["tool", "success"],
+            ),
+            tool_duration: HistogramVec::new(
+                "ygn_tool_execution_duration_seconds",
+                "Tool execution latency.",
+                &["tool"],
+                HTTP_BUCKETS,
+            ),
+            policy_decisions: CounterVec::new(
+                "ygn_policy_decisions_total",
+                "Policy engine decisions by action.",
+                &["action"],
+            ),
+            mcp_requests: CounterVec::new(
+                "ygn_mcp_requests_total",
+                "MCP JSON-RPC requests by method.",
+                &["method"],
+            ),
+            tools_in_flight: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a gateway request.  `route` must be the matched route
+    /// template, not the raw path.
+    pub fn record_http(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        self.http_requests
+            .inc(&[route, method, &status.to_string()]);
+        self.http_duration.observe(&[route], elapsed);
+    }
+
+    pub fn record_provider_chat(&self, provider: &str, ok: bool, elapsed: Duration) {
+        self.provider_duration.observe(&[provider], elapsed);
+        if !ok {
+            self.provider_failures.inc(&[provider]);
+        }
+    }
+
+    pub fn record_tool_execution(&self, tool: &str, success: bool, elapsed: Duration) {
+        self.tool_executions
+            .inc(&[tool, if success { "true" } else { "false" }]);
+        self.tool_duration.observe(&[tool], elapsed);
+    }
+
+    /// Mark a tool execution as started; it stays in flight until the
+    /// returned guard is dropped.
+    pub fn tool_started(&self) -> ToolInFlight<'_> {
+        self.tools_in_flight.fetch_add(1, Ordering::Relaxed);
+        ToolInFlight(&self.tools_in_flight)
+    }
+
+    pub fn record_policy_decision(&self, action: &PolicyAction) {
+        self.policy_decisions.inc(&[&format!("{action:?}")]);
+    }
+
+    /// Record an MCP request.  Unknown method names are folded into
+    /// `other` to keep the label set bounded.
+    pub fn record_mcp_method(&self, method: &str) {
+        let method = match method {
+            "initialize" | "tools/list" | "tools/call" | "invalid" => method,
+            _ => "other",
+        };
+        self.mcp_requests.inc(&[method]);
+    }
+
+    /// Number of executions recorded for `tool` with the given outcome.
+    pub fn tool_executions(&self, tool: &str, success: bool) -> u64 {
+        self.tool_executions
+            .get(&[tool, if success { "true" } else { "false" }])
+    }
+
+    /// Totals across all label sets.
+    pub fn summary(&self) -> MetricsSummary {
+        MetricsSummary {
+            http_requests: self.http_requests.sum(|_| true),
+            tool_executions: self.tool_executions.sum(|_| true),
+            tool_failures: self.tool_executions.sum(|v| v[1] == "false"),
+            provider_failures: self.provider_failures.sum(|_| true),
+            tools_in_flight: self.tools_in_flight.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.http_requests.render(&mut out);
+        self.http_duration.render(&mut out);
+        self.provider_duration.render(&mut out);
+        self.provider_failures.render(&mut out);
+        self.tool_executions.render(&mut out);
+        self.tool_duration.render(&mut out);
+        self.policy_decisions.render(&mut out);
+        self.mcp_requests.render(&mut out);
+        let _ = writeln!(
+            out,
+            "# HELP ygn_tools_in_flight Tool executions currently running.\n\
+             # TYPE ygn_tools_in_flight gauge\n\
+             ygn_tools_in_flight {}",
+            self.tools_in_flight.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// InstrumentedProvider
+// ---------------------------------------------------------------------------
+
+/// Wraps a [`Provider`] and records chat latency and failures.
+pub struct InstrumentedProvider {
+    inner: Box<dyn Provider>,
+    metrics: Arc<Metrics>,
+}
+
+impl std::fmt::Debug for InstrumentedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstrumentedProvider")
+            .field("inner", &self.inner.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl InstrumentedProvider {
+    pub fn new(inner: Box<dyn Provider>, metrics: Arc<Metrics>) -> Self {
+        Self { inner, metrics }
+    }
+
+    fn record<T, E>(&self, started: Instant, result: &Result<T, E>) {
+        self.metrics
+            .record_provider_chat(self.inner.name(), result.is_ok(), started.elapsed());
+    }
+}
+
+#[async_trait]
+impl Provider for InstrumentedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let started = Instant::now();
+        let result = self.inner.chat(request).await;
+        self.record(started, &result);
+        result
+    }
+
+    async fn chat_with_tools(
+        &self,
+        request: ChatRequest,
+        tools: &[ToolSpec],
+    ) -> Result<ChatResponse, ProviderError> {
+        let started = Instant::now();
+        let result = self.inner.chat_with_tools(request, tools).await;
+        self.record(started, &result);
+        result
+    }
+
+    async fn chat_stream(&self, request: ChatRequest) -> anyhow::Result<ChatStream> {
+        // Only the time to open the stream is measured.
+        let started = Instant::now();
+        let result = self.inner.chat_stream(request).await;
+        self.record(started, &result);
+        result
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        self.inner.cache_stats()
+    }
+
+    async fn supports_model(&self, model: &str) -> bool {
+        self.inner.supports_model(model).await
+    }
+
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        self.inner.embed(texts).await
+    }
+
+    async fn health_probe(&self) -> ProbeResult {
+        self.inner.health_probe().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{ChatMessage, ChatRole, StubProvider};
+
+    #[test]
+    fn counters_render_with_labels() {
+        let metrics = Metrics::new();
+        metrics.record_tool_execution("echo", true, Duration::from_millis(2));
+        metrics.record_tool_execution("echo", true, Duration::from_millis(2));
+        metrics.record_tool_execution("echo", false, Duration::from_millis(2));
+        metrics.record_policy_decision(&PolicyAction::Deny);
+
+        assert_eq!(metrics.tool_executions("echo", true), 2);
+        let text = metrics.render();
+        assert!(text.contains("# TYPE ygn_tool_executions_total counter"));
+        assert!(text.contains("ygn_tool_executions_total{tool=\"echo\",success=\"true\"} 2"));
+        assert!(text.contains("ygn_tool_executions_total{tool=\"echo\",success=\"false\"} 1"));
+        assert!(text.contains("ygn_policy_decisions_total{action=\"Deny\"} 1"));
+    }
+
+    #[test]
+    fn summary_totals_counters_and_in_flight_tools() {
+        let metrics = Metrics::new();
+        metrics.record_tool_execution("echo", true, Duration::from_millis(2));
+        metrics.record_tool_execution("shell", false, Duration::from_millis(2));
+        metrics.record_http("/health", "GET", 200, Duration::from_millis(1));
+        metrics.record_provider_chat("stub", false, Duration::from_millis(1));
+        let running = metrics.tool_started();
+
+        let summary = metrics.summary();
+        assert_eq!(summary.tool_executions, 2);
+        assert_eq!(summary.tool_failures, 1);
+        assert_eq!(summary.http_requests, 1);
+        assert_eq!(summary.provider_failures, 1);
+        assert_eq!(summary.tools_in_flight, 1);
+        assert!(metrics.render().contains("ygn_tools_in_flight 1\n"));
+
+        drop(running);
+        assert_eq!(metrics.summary().tools_in_flight, 0);
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = Metrics::new();
+        metrics.record_http("/mcp", "POST", 200, Duration::from_millis(3));
+        metrics.record_http("/mcp", "POST", 200, Duration::from_millis(300));
+        metrics.record_http("/mcp", "POST", 200, Duration::from_secs(20));
+
+        let text = metrics.render();
+        let bucket = |le: &str| {
+            format!("ygn_http_request_duration_seconds_bucket{{route=\"/mcp\",le=\"{le}\"}}")
+        };
+        assert!(text.contains(&format!("{} 1\n", bucket("0.005"))));
+        assert!(text.contains(&format!("{} 2\n", bucket("0.5"))));
+        assert!(text.contains(&format!("{} 2\n", bucket("10"))));
+        assert!(text.contains(&format!("{} 3\n", bucket("+Inf"))));
+        assert!(text.contains("ygn_http_request_duration_seconds_count{route=\"/mcp\"} 3"));
+        assert!(text
+            .contains("ygn_http_requests_total{route=\"/mcp\",method=\"POST\",status=\"200\"} 3"));
+    }
+
+    #[test]
+    fn unknown_mcp_methods_are_folded() {
+        let metrics = Metrics::new();
+        metrics.record_mcp_method("tools/call");
+        metrics.record_mcp_method("made/up/1");
+        metrics.record_mcp_method("made/up/2");
+        let text = metrics.render();
+        assert!(text.contains("ygn_mcp_requests_total{method=\"tools/call\"} 1"));
+        assert!(text.contains("ygn_mcp_requests_total{method=\"other\"} 2"));
+        assert!(!text.contains("made/up"));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[tokio::test]
+    async fn instrumented_provider_records_latency() {
+        let metrics = Arc::new(Metrics::new());
+        let provider =
+            InstrumentedProvider::new(Box::new(StubProvider::default()), metrics.clone());
+        let request = ChatRequest {
+            model: "stub".into(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "Hi".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        };
+        provider.chat(request).await.unwrap();
+        let text = metrics.render();
+        assert!(text.contains("ygn_provider_chat_duration_seconds_count{provider=\"stub\"} 1"));
+        assert!(!text.contains("ygn_provider_chat_failures_total{"));
+    }
+}
@@ -3,12 +3,21 @@
 //! Each provider implements the `Provider` trait from `provider.rs` and
 //! communicates with its respective API via `reqwest`.
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::metrics::{InstrumentedProvider, Metrics};
 use crate::provider::{
-    ChatRequest, ChatResponse, ChatRole, Provider, ProviderCapabilities, TokenUsage, ToolCall,
+    ChatCandidate, ChatMessage, ChatRequest, ChatResponse, ChatRole, ContentPart, MessageContent,
+    ProbeResult, Provider, ProviderCapabilities, ProviderError, ResponseFormat, SafetyRating,
+    TokenUsage, ToolCall,
 };
+use crate::provider_cache::{CachingProvider, ResponseCache};
+use crate::provider_health::{MonitoredProvider, ProviderHealth};
 use crate::tool::ToolSpec;
 
 // ---------------------------------------------------------------------------
@@ -19,16 +28,54 @@ use crate::tool::ToolSpec;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeConfig {
     pub api_key: String,
+    /// Further keys, used round-robin with `api_key`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
     pub model: String,
     pub base_url: Option<String>,
+    /// Mark the system prompt and tool definitions as cacheable, so
+    /// repeated turns read them from Anthropic's prompt cache.
+    #[serde(default)]
+    pub prompt_caching: bool,
 }
 
 /// Configuration for the OpenAI provider.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIConfig {
     pub api_key: String,
+    /// Further keys, used round-robin with `api_key`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
     pub model: String,
     pub base_url: Option<String>,
+    /// Which OpenAI API chat requests go to.
+    #[serde(default)]
+    pub api_flavor: OpenAIApiFlavor,
+    /// Regex matching reasoning models, which take `max_completion_tokens`
+    /// and no temperature.
+    #[serde(default = "default_openai_reasoning_models")]
+    pub reasoning_models: String,
+    /// `reasoning_effort` sent to reasoning models, e.g. `"low"` or `"high"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+}
+
+/// The OpenAI API an [`OpenAIProvider`] speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAIApiFlavor {
+    /// `POST /v1/chat/completions`.
+    #[default]
+    ChatCompletions,
+    /// `POST /v1/responses`.
+    Responses,
+}
+
+/// Default [`OpenAIConfig::reasoning_models`]: the o-series and GPT-5.
+pub const OPENAI_REASONING_MODELS: &str = r"^(o\d|gpt-5)";
+
+fn default_openai_reasoning_models() -> String {
+    OPENAI_REASONING_MODELS.to_string()
 }
 
 /// Configuration for the Google Gemini provider.
@@ -45,13 +92,222 @@ pub struct OllamaConfig {
     pub base_url: Option<String>,
 }
 
+// ---------------------------------------------------------------------------
+// Structured output
+// ---------------------------------------------------------------------------
+
+/// Name of the synthetic tool Claude is forced to call for structured output.
+const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
+/// Validate a response against the request's `response_format`, if any.
+///
+/// Providers enforce the format natively where they can; this check catches
+/// models that ignore it and surfaces a [`crate::provider::StructuredOutputError`]
+/// carrying the raw text. A response that calls tools is an intermediate turn,
+/// not the final answer, so it is passed through unchecked.
+fn enforce_response_format(
+    request: &ChatRequest,
+    response: ChatResponse,
+) -> Result<ChatResponse, ProviderError> {
+    if !response.tool_calls.is_empty() {
+        return Ok(response);
+    }
+    if let Some(format) = &request.response_format {
+        format.validate(&response.content)?;
+    }
+    Ok(response)
+}
+
+/// Schema to send to providers that need one even for plain JSON mode.
+fn response_schema_or_object(format: &ResponseFormat) -> serde_json::Value {
+    format
+        .schema()
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({ "type": "object" }))
+}
+
+// ---------------------------------------------------------------------------
+// Embeddings
+// ---------------------------------------------------------------------------
+
+/// Default embedding model for [`OpenAIProvider`].
+pub const OPENAI_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// Default embedding model for [`GeminiProvider`].
+pub const GEMINI_EMBEDDING_MODEL: &str = "text-embedding-004";
+
+/// Default embedding model for [`OllamaProvider`].
+pub const OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// Parse a JSON array of numbers into an embedding vector.
+fn parse_vector(value: Option<&serde_json::Value>) -> anyhow::Result<Vec<f32>> {
+    let values = value
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("embedding response has no vector"))?;
+    values
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .map(|f| f as f32)
+                .ok_or_else(|| anyhow::anyhow!("embedding vector contains a non-number: {v}"))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Health probes
+// ---------------------------------------------------------------------------
+
+/// Timeout for a [`Provider::health_probe`] request.
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Probe a provider by sending `request`, typically a model listing, and
+/// requiring a success status.
+async fn probe_endpoint(request: reqwest::RequestBuilder) -> ProbeResult {
+    ProbeResult::measure(async {
+        request
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// API key pools
+// ---------------------------------------------------------------------------
+
+/// API keys taken in turn, one per request.
+struct KeyPool {
+    keys: Vec<String>,
+    next: AtomicUsize,
+}
+
+impl KeyPool {
+    /// Pool of `primary` followed by the distinct keys in `extra`.
+    fn new(primary: &str, extra: &[String]) -> Self {
+        let mut keys = vec![primary.to_string()];
+        for key in extra {
+            if !key.is_empty() && !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        Self {
+            keys,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Keys to try for one request: the next key in turn, then the others
+    /// as fallbacks.
+    fn rotation(&self) -> impl Iterator<Item = &str> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.keys.len();
+        (0..self.keys.len()).map(move |i| self.keys[(start + i) % self.keys.len()].as_str())
+    }
+
+    /// Send the request `build` makes for a key, moving on to the next key
+    /// only while the response is 429.  If every key is rate limited the
+    /// last 429 is returned.
+    async fn send(
+        &self,
+        build: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        let mut keys = self.rotation().peekable();
+        loop {
+            let key = keys.next().unwrap_or_default();
+            let resp = build(key).send().await?;
+            if resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS || keys.peek().is_none() {
+                return Ok(resp);
+            }
+            tracing::debug!("API key rate limited, trying the next one");
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Responses
+// ---------------------------------------------------------------------------
+
+/// Await a request to `provider` and decode its JSON body.
+///
+/// An error status becomes the matching [`ProviderError`], carrying the
+/// message from the body's `error` (OpenAI, Claude and Gemini nest it as
+/// `error.message`, Ollama gives a string) or else the raw body.
+async fn read_json_response(
+    provider: &str,
+    response: impl std::future::Future<Output = reqwest::Result<reqwest::Response>>,
+) -> Result<serde_json::Value, ProviderError> {
+    let resp = response
+        .await
+        .map_err(|e| ProviderError::from_reqwest(provider, e))?;
+    let status = resp.status();
+    let retry_after = resp
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .map(std::time::Duration::from_secs);
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| ProviderError::from_reqwest(provider, e))?;
+    let body = serde_json::from_str::<serde_json::Value>(&text);
+
+    if !status.is_success() {
+        let message = body
+            .ok()
+            .and_then(|body| {
+                let error = body.get("error")?;
+                error
+                    .get("message")
+                    .unwrap_or(error)
+                    .as_str()
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| match text.trim() {
+                "" => "unknown error".to_string(),
+                text => text.to_string(),
+            });
+        return Err(ProviderError::from_status(
+            provider,
+            status.as_u16(),
+            retry_after,
+            message,
+        ));
+    }
+    body.map_err(|e| ProviderError::Decode {
+        provider: provider.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Extra keys from a comma-separated environment variable.
+fn keys_from_env(var: &str) -> Vec<String> {
+    std::env::var(var)
+        .map(|v| {
+            v.split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // ---------------------------------------------------------------------------
 // Claude Provider
 // ---------------------------------------------------------------------------
 
+/// `cache_control` marker for a prompt cache breakpoint.
+fn ephemeral_cache_control() -> serde_json::Value {
+    serde_json::json!({ "type": "ephemeral" })
+}
+
 /// Anthropic Claude provider using the Messages API.
 pub struct ClaudeProvider {
     pub config: ClaudeConfig,
+    keys: KeyPool,
     client: reqwest::Client,
 }
 
@@ -67,18 +323,22 @@ impl ClaudeProvider {
     /// Create a new Claude provider with the given config.
     pub fn new(config: ClaudeConfig) -> Self {
         Self {
+            keys: KeyPool::new(&config.api_key, &config.api_keys),
             config,
             client: reqwest::Client::new(),
         }
     }
 
-    /// Create a Claude provider from the `ANTHROPIC_API_KEY` env var.
+    /// Create a Claude provider from the `ANTHROPIC_API_KEY` env var, plus
+    /// any comma-separated keys in `ANTHROPIC_API_KEYS`.
     pub fn from_env() -> Option<Self> {
         let api_key = std::env::var("ANTHROPIC_API_KEY").ok()?;
         Some(Self::new(ClaudeConfig {
             api_key,
+            api_keys: keys_from_env("ANTHROPIC_API_KEYS"),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         }))
     }
 
@@ -100,7 +360,7 @@ impl ClaudeProvider {
             .messages
             .iter()
             .find(|m| m.role == ChatRole::System)
-            .map(|m| m.content.clone());
+            .map(|m| m.content.text());
 
         // Map non-system messages.
         let messages: Vec<serde_json::Value> = request
@@ -110,7 +370,7 @@ impl ClaudeProvider {
             .map(|m| {
                 serde_json::json!({
                     "role": claude_role(&m.role),
-                    "content": m.content,
+                    "content": claude_message_content(m),
                 })
             })
             .collect();
@@ -122,14 +382,28 @@ impl ClaudeProvider {
         });
 
         if let Some(sys) = system_msg {
-            body["system"] = serde_json::Value::String(sys);
+            body["system"] = if self.config.prompt_caching {
+                serde_json::json!([{
+                    "type": "text",
+                    "text": sys,
+                    "cache_control": ephemeral_cache_control(),
+                }])
+            } else {
+                serde_json::Value::String(sys)
+            };
         }
         if let Some(temp) = request.temperature {
             body["temperature"] = serde_json::json!(temp);
         }
+        if let Some(stop) = &request.stop {
+            body["stop_sequences"] = serde_json::json!(stop);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = serde_json::json!(top_p);
+        }
         if let Some(tool_specs) = tools {
             if !tool_specs.is_empty() {
-                let tool_defs: Vec<serde_json::Value> = tool_specs
+                let mut tool_defs: Vec<serde_json::Value> = tool_specs
                     .iter()
                     .map(|t| {
                         serde_json::json!({
@@ -139,13 +413,55 @@ impl ClaudeProvider {
                         })
                     })
                     .collect();
+                // A breakpoint on the last tool caches the whole array.
+                if let Some(last) = tool_defs.last_mut().filter(|_| self.config.prompt_caching) {
+                    last["cache_control"] = ephemeral_cache_control();
+                }
                 body["tools"] = serde_json::Value::Array(tool_defs);
             }
         }
+        // Claude has no JSON mode; offer a tool whose input schema is the
+        // requested one and read the answer from its arguments. With other
+        // tools present the model must still be free to call them first, so
+        // it is only required to call *some* tool.
+        if let Some(format) = &request.response_format {
+            let tool = serde_json::json!({
+                "name": STRUCTURED_OUTPUT_TOOL,
+                "description": "Respond with the final answer as structured JSON.",
+                "input_schema": response_schema_or_object(format),
+            });
+            match body.get_mut("tools").and_then(|t| t.as_array_mut()) {
+                Some(tool_defs) => {
+                    tool_defs.push(tool);
+                    body["tool_choice"] = serde_json::json!({ "type": "any" });
+                }
+                None => {
+                    body["tools"] = serde_json::json!([tool]);
+                    body["tool_choice"] = serde_json::json!({
+                        "type": "tool",
+                        "name": STRUCTURED_OUTPUT_TOOL,
+                    });
+                }
+            }
+        }
 
         body
     }
 
+    /// Replace the response content with the arguments of the forced
+    /// `structured_output` tool call, if the model made one.
+    fn extract_structured_output(mut response: ChatResponse) -> ChatResponse {
+        if let Some(idx) = response
+            .tool_calls
+            .iter()
+            .position(|c| c.tool_name == STRUCTURED_OUTPUT_TOOL)
+        {
+            let call = response.tool_calls.remove(idx);
+            response.content = call.arguments.to_string();
+        }
+        response
+    }
+
     /// Parse an Anthropic Messages API response into a ChatResponse.
     fn parse_response(body: &serde_json::Value) -> anyhow::Result<ChatResponse> {
         let mut content = String::new();
@@ -172,6 +488,7 @@ impl ClaudeProvider {
                         tool_calls.push(ToolCall {
                             tool_name: name,
                             arguments,
+                            id: block.get("id").and_then(|i| i.as_str()).map(String::from),
                         });
                     }
                     _ => {}
@@ -179,19 +496,93 @@ impl ClaudeProvider {
             }
         }
 
-        let usage = body.get("usage").map(|u| TokenUsage {
-            prompt_tokens: u.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
-            completion_tokens: u.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        let usage = body.get("usage").map(|u| {
+            let tokens = |field: &str| u.get(field).and_then(|v| v.as_u64()).map(|v| v as u32);
+            TokenUsage {
+                prompt_tokens: tokens("input_tokens").unwrap_or(0),
+                completion_tokens: tokens("output_tokens").unwrap_or(0),
+                cache_creation_tokens: tokens("cache_creation_input_tokens"),
+                cache_read_tokens: tokens("cache_read_input_tokens"),
+            }
         });
 
         Ok(ChatResponse {
             content,
             tool_calls,
             usage,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
         })
     }
 }
 
+/// Map a message's content to Anthropic's format.  A tool result becomes a
+/// `tool_result` block and an assistant's tool calls become `tool_use`
+/// blocks, both keyed by the call id, so Claude can pair them.
+fn claude_message_content(message: &ChatMessage) -> serde_json::Value {
+    if let (ChatRole::Tool, Some(id)) = (&message.role, &message.tool_call_id) {
+        return serde_json::json!([{
+            "type": "tool_result",
+            "tool_use_id": id,
+            "content": claude_content(&message.content),
+        }]);
+    }
+    if message.role == ChatRole::Assistant && !message.tool_calls.is_empty() {
+        let mut blocks = match claude_content(&message.content) {
+            serde_json::Value::String(text) if text.is_empty() => Vec::new(),
+            serde_json::Value::String(text) => {
+                vec![serde_json::json!({ "type": "text", "text": text })]
+            }
+            serde_json::Value::Array(blocks) => blocks,
+            other => vec![other],
+        };
+        blocks.extend(message.tool_calls.iter().map(|call| {
+            serde_json::json!({
+                "type": "tool_use",
+                "id": call.id,
+                "name": call.tool_name,
+                "input": call.arguments,
+            })
+        }));
+        return serde_json::Value::Array(blocks);
+    }
+    claude_content(&message.content)
+}
+
+/// Map message content to Anthropic's format: a plain string, or an array
+/// of `text` / `image` blocks.
+fn claude_content(content: &MessageContent) -> serde_json::Value {
+    match content {
+        MessageContent::Text(text) => serde_json::json!(text),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => serde_json::json!({
+                    "type": "text",
+                    "text": text,
+                }),
+                ContentPart::Image { data, mime_type } => serde_json::json!({
+                    "type": "image",
+                    "source": {
+                        "type": "base64",
+                        "media_type": mime_type,
+                        "data": data,
+                    }
+                }),
+                ContentPart::ImageUrl { url } => serde_json::json!({
+                    "type": "image",
+                    "source": {
+                        "type": "url",
+                        "url": url,
+                    }
+                }),
+            })
+            .collect(),
+    }
+}
+
 fn claude_role(role: &ChatRole) -> &'static str {
     match role {
         ChatRole::User => "user",
@@ -215,66 +606,70 @@ impl Provider for ClaudeProvider {
         }
     }
 
-    async fn chat(&self, request: ChatRequest) -> anyhow::Result<ChatResponse> {
+    /// Lists models, which needs a valid key but no tokens.
+    async fn health_probe(&self) -> ProbeResult {
+        let url = format!("{}/v1/models", self.base_url());
+        probe_endpoint(
+            self.client
+                .get(&url)
+                .header("x-api-key", self.keys.rotation().next().unwrap_or_default())
+                .header("anthropic-version", "2023-06-01"),
+        )
+        .await
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
         let url = format!("{}/v1/messages", self.base_url());
         let body = self.build_request_body(&request, None);
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("x-api-key", &self.config.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
+        let resp_body = read_json_response(
+            "Claude",
+            self.keys.send(|key| {
+                self.client
+                    .post(&url)
+                    .header("x-api-key", key)
+                    .header("anthropic-version", "2023-06-01")
+                    .header("content-type", "application/json")
+                    .json(&body)
+            }),
+        )
+        .await?;
 
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("Claude API error ({}): {}", status, msg);
+        let mut response =
+            Self::parse_response(&resp_body).map_err(|e| ProviderError::decode("Claude", e))?;
+        if request.response_format.is_some() {
+            response = Self::extract_structured_output(response);
         }
-
-        Self::parse_response(&resp_body)
+        enforce_response_format(&request, response)
     }
 
     async fn chat_with_tools(
         &self,
         request: ChatRequest,
         tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> Result<ChatResponse, ProviderError> {
         let url = format!("{}/v1/messages", self.base_url());
         let body = self.build_request_body(&request, Some(tools));
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("x-api-key", &self.config.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
+        let resp_body = read_json_response(
+            "Claude",
+            self.keys.send(|key| {
+                self.client
+                    .post(&url)
+                    .header("x-api-key", key)
+                    .header("anthropic-version", "2023-06-01")
+                    .header("content-type", "application/json")
+                    .json(&body)
+            }),
+        )
+        .await?;
 
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("Claude API error ({}): {}", status, msg);
+        let mut response =
+            Self::parse_response(&resp_body).map_err(|e| ProviderError::decode("Claude", e))?;
+        if request.response_format.is_some() {
+            response = Self::extract_structured_output(response);
         }
-
-        Self::parse_response(&resp_body)
+        enforce_response_format(&request, response)
     }
 }
 
@@ -282,10 +677,16 @@ impl Provider for ClaudeProvider {
 // OpenAI Provider
 // ---------------------------------------------------------------------------
 
-/// OpenAI Chat Completions provider. Also supports Codex, Azure OpenAI,
-/// and other compatible endpoints via `base_url` override.
+/// OpenAI provider, speaking Chat Completions or the Responses API per
+/// [`OpenAIConfig::api_flavor`]. Also supports Codex, Azure OpenAI, and
+/// other compatible endpoints via `base_url` override.
 pub struct OpenAIProvider {
     pub config: OpenAIConfig,
+    /// Model used by [`Provider::embed`].
+    pub embedding_model: String,
+    keys: KeyPool,
+    /// Compiled [`OpenAIConfig::reasoning_models`]; `None` if invalid.
+    reasoning_models: Option<regex::Regex>,
     client: reqwest::Client,
 }
 
@@ -300,19 +701,36 @@ impl std::fmt::Debug for OpenAIProvider {
 impl OpenAIProvider {
     /// Create a new OpenAI provider with the given config.
     pub fn new(config: OpenAIConfig) -> Self {
+        let reasoning_models = regex::Regex::new(&config.reasoning_models)
+            .inspect_err(|e| tracing::warn!(error = %e, "invalid OpenAI reasoning model pattern"))
+            .ok();
         Self {
+            keys: KeyPool::new(&config.api_key, &config.api_keys),
+            reasoning_models,
             config,
+            embedding_model: OPENAI_EMBEDDING_MODEL.to_string(),
             client: reqwest::Client::new(),
         }
     }
 
-    /// Create an OpenAI provider from the `OPENAI_API_KEY` env var.
+    /// Use `model` for embeddings instead of the default.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Create an OpenAI provider from the `OPENAI_API_KEY` env var, plus
+    /// any comma-separated keys in `OPENAI_API_KEYS`.
     pub fn from_env() -> Option<Self> {
         let api_key = std::env::var("OPENAI_API_KEY").ok()?;
         Some(Self::new(OpenAIConfig {
             api_key,
+            api_keys: keys_from_env("OPENAI_API_KEYS"),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         }))
     }
 
@@ -323,6 +741,71 @@ impl OpenAIProvider {
             .unwrap_or("https://api.openai.com")
     }
 
+    /// Whether `model` is a reasoning model, per
+    /// [`OpenAIConfig::reasoning_models`].
+    fn is_reasoning_model(&self, model: &str) -> bool {
+        self.reasoning_models
+            .as_ref()
+            .is_some_and(|pattern| pattern.is_match(model))
+    }
+
+    /// URL chat requests are posted to for the configured API flavor.
+    fn chat_url(&self) -> String {
+        match self.config.api_flavor {
+            OpenAIApiFlavor::ChatCompletions => format!("{}/v1/chat/completions", self.base_url()),
+            OpenAIApiFlavor::Responses => format!("{}/v1/responses", self.base_url()),
+        }
+    }
+
+    /// Build the chat request body for the configured API flavor.
+    fn build_chat_body(
+        &self,
+        request: &ChatRequest,
+        tools: Option<&[ToolSpec]>,
+    ) -> serde_json::Value {
+        match self.config.api_flavor {
+            OpenAIApiFlavor::ChatCompletions => self.build_request_body(request, tools),
+            OpenAIApiFlavor::Responses => self.build_responses_body(request, tools),
+        }
+    }
+
+    /// Parse a chat response in the configured API flavor.
+    fn parse_chat_response(&self, body: &serde_json::Value) -> anyhow::Result<ChatResponse> {
+        match self.config.api_flavor {
+            OpenAIApiFlavor::ChatCompletions => Self::parse_response(body),
+            OpenAIApiFlavor::Responses => Self::parse_responses_response(body),
+        }
+    }
+
+    /// Build the OpenAI `/v1/embeddings` request body.
+    fn build_embedding_body(&self, texts: &[String]) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.embedding_model,
+            "input": texts,
+        })
+    }
+
+    /// Parse an OpenAI embeddings response, ordering vectors by `index`.
+    fn parse_embedding_response(body: &serde_json::Value) -> anyhow::Result<Vec<Vec<f32>>> {
+        let data = body
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or_else(|| anyhow::anyhow!("OpenAI embeddings response has no data"))?;
+        let mut indexed = data
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let index = item
+                    .get("index")
+                    .and_then(|i| i.as_u64())
+                    .map_or(i, |i| i as usize);
+                Ok((index, parse_vector(item.get("embedding"))?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        indexed.sort_by_key(|(index, _)| *index);
+        Ok(indexed.into_iter().map(|(_, vector)| vector).collect())
+    }
+
     /// Build the OpenAI Chat Completions API request body.
     fn build_request_body(
         &self,
@@ -333,10 +816,30 @@ impl OpenAIProvider {
             .messages
             .iter()
             .map(|m| {
-                serde_json::json!({
+                let mut message = serde_json::json!({
                     "role": openai_role(&m.role),
-                    "content": m.content,
-                })
+                    "content": openai_content(&m.content),
+                });
+                if let Some(id) = &m.tool_call_id {
+                    message["tool_call_id"] = serde_json::json!(id);
+                }
+                if !m.tool_calls.is_empty() {
+                    message["tool_calls"] = m
+                        .tool_calls
+                        .iter()
+                        .map(|call| {
+                            serde_json::json!({
+                                "id": call.id,
+                                "type": "function",
+                                "function": {
+                                    "name": call.tool_name,
+                                    "arguments": call.arguments.to_string(),
+                                },
+                            })
+                        })
+                        .collect();
+                }
+                message
             })
             .collect();
 
@@ -345,12 +848,31 @@ impl OpenAIProvider {
             "messages": messages,
         });
 
+        let reasoning = self.is_reasoning_model(&request.model);
         if let Some(max_tokens) = request.max_tokens {
-            body["max_tokens"] = serde_json::json!(max_tokens);
+            let field = if reasoning {
+                "max_completion_tokens"
+            } else {
+                "max_tokens"
+            };
+            body[field] = serde_json::json!(max_tokens);
         }
-        if let Some(temp) = request.temperature {
+        if reasoning {
+            if let Some(effort) = &self.config.reasoning_effort {
+                body["reasoning_effort"] = serde_json::json!(effort);
+            }
+        } else if let Some(temp) = request.temperature {
             body["temperature"] = serde_json::json!(temp);
         }
+        if let Some(stop) = &request.stop {
+            body["stop"] = serde_json::json!(stop);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = serde_json::json!(top_p);
+        }
+        if let Some(seed) = request.seed {
+            body["seed"] = serde_json::json!(seed);
+        }
         if let Some(tool_specs) = tools {
             if !tool_specs.is_empty() {
                 let tool_defs: Vec<serde_json::Value> = tool_specs
@@ -369,6 +891,18 @@ impl OpenAIProvider {
                 body["tools"] = serde_json::Value::Array(tool_defs);
             }
         }
+        match &request.response_format {
+            Some(ResponseFormat::Json) => {
+                body["response_format"] = serde_json::json!({ "type": "json_object" });
+            }
+            Some(ResponseFormat::JsonSchema { schema }) => {
+                body["response_format"] = serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "response", "schema": schema },
+                });
+            }
+            None => {}
+        }
 
         body
     }
@@ -385,11 +919,7 @@ impl OpenAIProvider {
             .get("message")
             .ok_or_else(|| anyhow::anyhow!("no message in choice"))?;
 
-        let content = message
-            .get("content")
-            .and_then(|c| c.as_str())
-            .unwrap_or("")
-            .to_string();
+        let content = openai_message_text(message)?;
 
         let mut tool_calls = Vec::new();
         if let Some(tc_array) = message.get("tool_calls").and_then(|tc| tc.as_array()) {
@@ -409,6 +939,7 @@ impl OpenAIProvider {
                     tool_calls.push(ToolCall {
                         tool_name: name,
                         arguments,
+                        id: tc.get("id").and_then(|i| i.as_str()).map(String::from),
                     });
                 }
             }
@@ -420,16 +951,269 @@ impl OpenAIProvider {
                 .get("completion_tokens")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(0) as u32,
+            ..Default::default()
+        });
+
+        Ok(ChatResponse {
+            content,
+            tool_calls,
+            usage,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
+        })
+    }
+
+    /// Build the OpenAI Responses API (`/v1/responses`) request body.
+    ///
+    /// Messages become `input` items; assistant tool calls and tool results
+    /// become `function_call` and `function_call_output` items. The API has
+    /// no `stop` or `seed`, so those are dropped.
+    fn build_responses_body(
+        &self,
+        request: &ChatRequest,
+        tools: Option<&[ToolSpec]>,
+    ) -> serde_json::Value {
+        let mut input = Vec::new();
+        for m in &request.messages {
+            if m.role == ChatRole::Tool {
+                input.push(serde_json::json!({
+                    "type": "function_call_output",
+                    "call_id": m.tool_call_id,
+                    "output": m.content.text(),
+                }));
+                continue;
+            }
+            if m.tool_calls.is_empty() || !m.content.text().is_empty() {
+                input.push(serde_json::json!({
+                    "role": openai_role(&m.role),
+                    "content": responses_content(&m.role, &m.content),
+                }));
+            }
+            for call in &m.tool_calls {
+                input.push(serde_json::json!({
+                    "type": "function_call",
+                    "call_id": call.id,
+                    "name": call.tool_name,
+                    "arguments": call.arguments.to_string(),
+                }));
+            }
+        }
+
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "input": input,
+        });
+
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_output_tokens"] = serde_json::json!(max_tokens);
+        }
+        if self.is_reasoning_model(&request.model) {
+            if let Some(effort) = &self.config.reasoning_effort {
+                body["reasoning"] = serde_json::json!({ "effort": effort });
+            }
+        } else if let Some(temp) = request.temperature {
+            body["temperature"] = serde_json::json!(temp);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = serde_json::json!(top_p);
+        }
+        if let Some(tool_specs) = tools.filter(|t| !t.is_empty()) {
+            body["tools"] = tool_specs
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "type": "function",
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": t.parameters_schema,
+                    })
+                })
+                .collect();
+        }
+        match &request.response_format {
+            Some(ResponseFormat::Json) => {
+                body["text"] = serde_json::json!({ "format": { "type": "json_object" } });
+            }
+            Some(ResponseFormat::JsonSchema { schema }) => {
+                body["text"] = serde_json::json!({
+                    "format": { "type": "json_schema", "name": "response", "schema": schema },
+                });
+            }
+            None => {}
+        }
+
+        body
+    }
+
+    /// Parse an OpenAI Responses API response into a ChatResponse,
+    /// concatenating the `output_text` parts of every output message.
+    fn parse_responses_response(body: &serde_json::Value) -> anyhow::Result<ChatResponse> {
+        let output = body
+            .get("output")
+            .and_then(|o| o.as_array())
+            .ok_or_else(|| anyhow::anyhow!("no output in OpenAI response"))?;
+
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for item in output {
+            match item.get("type").and_then(|t| t.as_str()) {
+                Some("message") => {
+                    let parts = item.get("content").and_then(|c| c.as_array());
+                    for part in parts.into_iter().flatten() {
+                        match part.get("type").and_then(|t| t.as_str()) {
+                            Some("output_text") => content
+                                .push_str(part.get("text").and_then(|t| t.as_str()).unwrap_or("")),
+                            Some("refusal") => {
+                                return Err(ProviderError::Refused {
+                                    provider: "OpenAI".to_string(),
+                                    reason: part
+                                        .get("refusal")
+                                        .and_then(|r| r.as_str())
+                                        .unwrap_or("")
+                                        .to_string(),
+                                }
+                                .into())
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Some("function_call") => {
+                    let name = item
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let args_str = item
+                        .get("arguments")
+                        .and_then(|a| a.as_str())
+                        .unwrap_or("{}");
+                    let arguments: serde_json::Value =
+                        serde_json::from_str(args_str).unwrap_or(serde_json::Value::Null);
+                    tool_calls.push(ToolCall {
+                        tool_name: name,
+                        arguments,
+                        id: item
+                            .get("call_id")
+                            .and_then(|i| i.as_str())
+                            .map(String::from),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let usage = body.get("usage").map(|u| TokenUsage {
+            prompt_tokens: u.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+            completion_tokens: u.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+            ..Default::default()
         });
 
         Ok(ChatResponse {
             content,
             tool_calls,
             usage,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
         })
     }
 }
 
+/// Map message content to a Responses API input message's content: a plain
+/// string, or `input_text` / `input_image` parts (`output_text` for the
+/// assistant's own turns).
+fn responses_content(role: &ChatRole, content: &MessageContent) -> serde_json::Value {
+    let text_type = match role {
+        ChatRole::Assistant => "output_text",
+        _ => "input_text",
+    };
+    match content {
+        MessageContent::Text(text) => serde_json::json!(text),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => serde_json::json!({
+                    "type": text_type,
+                    "text": text,
+                }),
+                ContentPart::Image { data, mime_type } => serde_json::json!({
+                    "type": "input_image",
+                    "image_url": format!("data:{mime_type};base64,{data}"),
+                }),
+                ContentPart::ImageUrl { url } => serde_json::json!({
+                    "type": "input_image",
+                    "image_url": url,
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// The text of an OpenAI response message, whose `content` is either a
+/// string or an array of `text` parts (concatenated in order).
+///
+/// A refusal, given as the message's `refusal` field or as a `refusal`
+/// part, is returned as an error rather than as empty content.
+fn openai_message_text(message: &serde_json::Value) -> anyhow::Result<String> {
+    let mut refusal = message.get("refusal").and_then(|r| r.as_str());
+    let text = match message.get("content") {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(parts)) => {
+            let mut text = String::new();
+            for part in parts {
+                match part.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => {
+                        text.push_str(part.get("text").and_then(|t| t.as_str()).unwrap_or(""))
+                    }
+                    Some("refusal") => {
+                        refusal = refusal.or(part.get("refusal").and_then(|r| r.as_str()))
+                    }
+                    _ => {}
+                }
+            }
+            text
+        }
+        _ => String::new(),
+    };
+    match refusal {
+        Some(reason) => Err(ProviderError::Refused {
+            provider: "OpenAI".to_string(),
+            reason: reason.to_string(),
+        }
+        .into()),
+        None => Ok(text),
+    }
+}
+
+/// Map message content to OpenAI's format: a plain string, or an array of
+/// `text` / `image_url` entries (inline images become data URLs).
+fn openai_content(content: &MessageContent) -> serde_json::Value {
+    match content {
+        MessageContent::Text(text) => serde_json::json!(text),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => serde_json::json!({
+                    "type": "text",
+                    "text": text,
+                }),
+                ContentPart::Image { data, mime_type } => serde_json::json!({
+                    "type": "image_url",
+                    "image_url": { "url": format!("data:{mime_type};base64,{data}") },
+                }),
+                ContentPart::ImageUrl { url } => serde_json::json!({
+                    "type": "image_url",
+                    "image_url": { "url": url },
+                }),
+            })
+            .collect(),
+    }
+}
+
 fn openai_role(role: &ChatRole) -> &'static str {
     match role {
         ChatRole::System => "system",
@@ -453,64 +1237,95 @@ impl Provider for OpenAIProvider {
         }
     }
 
-    async fn chat(&self, request: ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = format!("{}/v1/chat/completions", self.base_url());
-        let body = self.build_request_body(&request, None);
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
-
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("OpenAI API error ({}): {}", status, msg);
-        }
+    /// Lists models, which needs a valid key but no tokens.
+    async fn health_probe(&self) -> ProbeResult {
+        let url = format!("{}/v1/models", self.base_url());
+        probe_endpoint(
+            self.client
+                .get(&url)
+                .bearer_auth(self.keys.rotation().next().unwrap_or_default()),
+        )
+        .await
+    }
 
-        Self::parse_response(&resp_body)
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let url = self.chat_url();
+        let body = self.build_chat_body(&request, None);
+
+        let resp_body = read_json_response(
+            "OpenAI",
+            self.keys.send(|key| {
+                self.client
+                    .post(&url)
+                    .bearer_auth(key)
+                    .header("content-type", "application/json")
+                    .json(&body)
+            }),
+        )
+        .await?;
+
+        enforce_response_format(
+            &request,
+            self.parse_chat_response(&resp_body)
+                .map_err(|e| ProviderError::decode("OpenAI", e))?,
+        )
     }
 
     async fn chat_with_tools(
         &self,
         request: ChatRequest,
         tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse> {
-        let url = format!("{}/v1/chat/completions", self.base_url());
-        let body = self.build_request_body(&request, Some(tools));
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
+    ) -> Result<ChatResponse, ProviderError> {
+        let url = self.chat_url();
+        let body = self.build_chat_body(&request, Some(tools));
+
+        let resp_body = read_json_response(
+            "OpenAI",
+            self.keys.send(|key| {
+                self.client
+                    .post(&url)
+                    .bearer_auth(key)
+                    .header("content-type", "application/json")
+                    .json(&body)
+            }),
+        )
+        .await?;
+
+        enforce_response_format(
+            &request,
+            self.parse_chat_response(&resp_body)
+                .map_err(|e| ProviderError::decode("OpenAI", e))?,
+        )
+    }
 
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("OpenAI API error ({}): {}", status, msg);
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
         }
-
-        Self::parse_response(&resp_body)
+        let url = format!("{}/v1/embeddings", self.base_url());
+        let body = self.build_embedding_body(texts);
+
+        let resp_body = read_json_response(
+            "OpenAI",
+            self.keys.send(|key| {
+                self.client
+                    .post(&url)
+                    .bearer_auth(key)
+                    .header("content-type", "application/json")
+                    .json(&body)
+            }),
+        )
+        .await?;
+
+        let vectors = Self::parse_embedding_response(&resp_body)?;
+        if vectors.len() != texts.len() {
+            anyhow::bail!(
+                "OpenAI returned {} embeddings for {} inputs",
+                vectors.len(),
+                texts.len()
+            );
+        }
+        Ok(vectors)
     }
 }
 
@@ -521,6 +1336,8 @@ impl Provider for OpenAIProvider {
 /// Google Gemini provider using the Generative AI API.
 pub struct GeminiProvider {
     pub config: GeminiConfig,
+    /// Model used by [`Provider::embed`].
+    pub embedding_model: String,
     client: reqwest::Client,
 }
 
@@ -537,10 +1354,17 @@ impl GeminiProvider {
     pub fn new(config: GeminiConfig) -> Self {
         Self {
             config,
+            embedding_model: GEMINI_EMBEDDING_MODEL.to_string(),
             client: reqwest::Client::new(),
         }
     }
 
+    /// Use `model` for embeddings instead of the default.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
     /// Create a Gemini provider from the `GEMINI_API_KEY` env var.
     pub fn from_env() -> Option<Self> {
         let api_key = std::env::var("GEMINI_API_KEY").ok()?;
@@ -550,6 +1374,36 @@ impl GeminiProvider {
         }))
     }
 
+    /// The `generateContent` URL for `request`'s model, or the configured
+    /// model when the request names none.
+    fn generate_content_url(&self, request: &ChatRequest) -> anyhow::Result<String> {
+        let model = if request.model.is_empty() {
+            &self.config.model
+        } else {
+            &request.model
+        };
+        if model.is_empty() {
+            anyhow::bail!("Gemini request has no model and no default model is configured");
+        }
+        Ok(format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+            model, self.config.api_key
+        ))
+    }
+
+    /// Build the Gemini `embedContent` request body for one text.
+    fn build_embedding_body(&self, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "model": format!("models/{}", self.embedding_model),
+            "content": { "parts": [{ "text": text }] },
+        })
+    }
+
+    /// Parse a Gemini `embedContent` response.
+    fn parse_embedding_response(body: &serde_json::Value) -> anyhow::Result<Vec<f32>> {
+        parse_vector(body.get("embedding").and_then(|e| e.get("values")))
+    }
+
     /// Build the Gemini generateContent request body.
     fn build_request_body(
         &self,
@@ -562,12 +1416,12 @@ impl GeminiProvider {
         for msg in &request.messages {
             match msg.role {
                 ChatRole::System => {
-                    system_instruction = Some(msg.content.clone());
+                    system_instruction = Some(msg.content.text());
                 }
                 _ => {
                     contents.push(serde_json::json!({
                         "role": gemini_role(&msg.role),
-                        "parts": [{ "text": msg.content }],
+                        "parts": gemini_parts(&msg.content),
                     }));
                 }
             }
@@ -591,6 +1445,24 @@ impl GeminiProvider {
         if let Some(temp) = request.temperature {
             gen_config.insert("temperature".to_string(), serde_json::json!(temp));
         }
+        if let Some(stop) = &request.stop {
+            gen_config.insert("stopSequences".to_string(), serde_json::json!(stop));
+        }
+        if let Some(top_p) = request.top_p {
+            gen_config.insert("topP".to_string(), serde_json::json!(top_p));
+        }
+        if let Some(count) = request.candidate_count {
+            gen_config.insert("candidateCount".to_string(), serde_json::json!(count));
+        }
+        if let Some(format) = &request.response_format {
+            gen_config.insert(
+                "responseMimeType".to_string(),
+                serde_json::json!("application/json"),
+            );
+            if let Some(schema) = format.schema() {
+                gen_config.insert("responseSchema".to_string(), schema.clone());
+            }
+        }
         if !gen_config.is_empty() {
             body["generationConfig"] = serde_json::Value::Object(gen_config);
         }
@@ -617,39 +1489,57 @@ impl GeminiProvider {
     }
 
     /// Parse a Gemini generateContent response into a ChatResponse.
+    ///
+    /// The first candidate not withheld by a content filter becomes the
+    /// response and the others its alternates.  Fails with
+    /// [`ProviderError::Blocked`] when the prompt or every candidate was
+    /// blocked.
     fn parse_response(body: &serde_json::Value) -> anyhow::Result<ChatResponse> {
-        let mut content = String::new();
-        let mut tool_calls = Vec::new();
-
-        if let Some(candidates) = body.get("candidates").and_then(|c| c.as_array()) {
-            if let Some(candidate) = candidates.first() {
-                if let Some(parts) = candidate
-                    .get("content")
-                    .and_then(|c| c.get("parts"))
-                    .and_then(|p| p.as_array())
-                {
-                    for part in parts {
-                        if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
-                            content.push_str(text);
-                        }
-                        if let Some(fc) = part.get("functionCall") {
-                            let name = fc
-                                .get("name")
-                                .and_then(|n| n.as_str())
-                                .unwrap_or("")
-                                .to_string();
-                            let arguments =
-                                fc.get("args").cloned().unwrap_or(serde_json::Value::Null);
-                            tool_calls.push(ToolCall {
-                                tool_name: name,
-                                arguments,
-                            });
-                        }
+        let candidates = body
+            .get("candidates")
+            .and_then(|c| c.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        if candidates.is_empty() {
+            if let Some(feedback) = body.get("promptFeedback") {
+                if let Some(reason) = feedback.get("blockReason").and_then(|r| r.as_str()) {
+                    return Err(ProviderError::Blocked {
+                        provider: "Gemini".to_string(),
+                        reason: reason.to_string(),
+                        safety_ratings: gemini_safety_ratings(feedback),
                     }
+                    .into());
                 }
             }
         }
 
+        let mut parsed = Vec::with_capacity(candidates.len());
+        let mut first_block = None;
+        for candidate in candidates {
+            let finish_reason = candidate
+                .get("finishReason")
+                .and_then(|r| r.as_str())
+                .map(str::to_string);
+            if let Some(reason) = finish_reason.as_deref().filter(|r| is_gemini_block(r)) {
+                first_block.get_or_insert_with(|| ProviderError::Blocked {
+                    provider: "Gemini".to_string(),
+                    reason: reason.to_string(),
+                    safety_ratings: gemini_safety_ratings(candidate),
+                });
+                continue;
+            }
+            parsed.push(gemini_candidate(candidate, finish_reason));
+        }
+        if parsed.is_empty() {
+            if let Some(blocked) = first_block {
+                return Err(blocked.into());
+            }
+        }
+
+        let mut candidates = parsed.into_iter();
+        let top = candidates.next().unwrap_or_default();
+
         let usage = body.get("usageMetadata").map(|u| TokenUsage {
             prompt_tokens: u
                 .get("promptTokenCount")
@@ -659,13 +1549,125 @@ impl GeminiProvider {
                 .get("candidatesTokenCount")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(0) as u32,
+            ..Default::default()
         });
 
         Ok(ChatResponse {
-            content,
-            tool_calls,
+            content: top.content,
+            tool_calls: top.tool_calls,
             usage,
+            cached: false,
+            model: None,
+            finish_reason: top.finish_reason,
+            alternates: candidates.collect(),
+        })
+    }
+}
+
+/// Gemini finish reasons meaning the candidate was withheld by a filter.
+const GEMINI_BLOCK_REASONS: &[&str] = &[
+    "SAFETY",
+    "RECITATION",
+    "OTHER",
+    "BLOCKLIST",
+    "PROHIBITED_CONTENT",
+    "SPII",
+    "IMAGE_SAFETY",
+];
+
+fn is_gemini_block(finish_reason: &str) -> bool {
+    GEMINI_BLOCK_REASONS.contains(&finish_reason)
+}
+
+/// The text and function calls of a Gemini candidate, across all of its
+/// parts.
+fn gemini_candidate(candidate: &serde_json::Value, finish_reason: Option<String>) -> ChatCandidate {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    let parts = candidate
+        .get("content")
+        .and_then(|c| c.get("parts"))
+        .and_then(|p| p.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for part in parts {
+        if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+            content.push_str(text);
+        }
+        if let Some(fc) = part.get("functionCall") {
+            let name = fc
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_string();
+            let arguments = fc.get("args").cloned().unwrap_or(serde_json::Value::Null);
+            tool_calls.push(ToolCall {
+                tool_name: name,
+                arguments,
+                id: None,
+            });
+        }
+    }
+    ChatCandidate {
+        content,
+        tool_calls,
+        finish_reason,
+    }
+}
+
+/// The `safetyRatings` of a Gemini candidate or prompt feedback.
+fn gemini_safety_ratings(value: &serde_json::Value) -> Vec<SafetyRating> {
+    value
+        .get("safetyRatings")
+        .and_then(|r| r.as_array())
+        .map(|ratings| {
+            ratings
+                .iter()
+                .map(|r| SafetyRating {
+                    category: r["category"].as_str().unwrap_or_default().to_string(),
+                    probability: r["probability"].as_str().unwrap_or_default().to_string(),
+                    blocked: r["blocked"].as_bool().unwrap_or(false),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Map message content to Gemini `parts`: `text`, `inlineData` for base64
+/// images, and `fileData` for image URLs.
+fn gemini_parts(content: &MessageContent) -> Vec<serde_json::Value> {
+    content
+        .parts()
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => serde_json::json!({ "text": text }),
+            ContentPart::Image { data, mime_type } => serde_json::json!({
+                "inlineData": {
+                    "mimeType": mime_type,
+                    "data": data,
+                }
+            }),
+            ContentPart::ImageUrl { url } => serde_json::json!({
+                "fileData": {
+                    "mimeType": guess_image_mime(url),
+                    "fileUri": url,
+                }
+            }),
         })
+        .collect()
+}
+
+/// Best-effort image MIME type from a URL's file extension.
+fn guess_image_mime(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "image/jpeg"
     }
 }
 
@@ -691,68 +1693,81 @@ impl Provider for GeminiProvider {
         }
     }
 
-    async fn chat(&self, request: ChatRequest) -> anyhow::Result<ChatResponse> {
+    /// Lists models, which needs a valid key but no tokens.
+    async fn health_probe(&self) -> ProbeResult {
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            request.model, self.config.api_key
+            "https://generativelanguage.googleapis.com/v1beta/models?key={}",
+            self.config.api_key
         );
-        let body = self.build_request_body(&request, None);
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
+        probe_endpoint(self.client.get(&url)).await
+    }
 
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("Gemini API error ({}): {}", status, msg);
-        }
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let url = self.generate_content_url(&request)?;
+        let body = self.build_request_body(&request, None);
 
-        Self::parse_response(&resp_body)
+        let resp_body = read_json_response(
+            "Gemini",
+            self.client
+                .post(&url)
+                .header("content-type", "application/json")
+                .json(&body)
+                .send(),
+        )
+        .await?;
+
+        enforce_response_format(
+            &request,
+            Self::parse_response(&resp_body).map_err(|e| ProviderError::decode("Gemini", e))?,
+        )
     }
 
     async fn chat_with_tools(
         &self,
         request: ChatRequest,
         tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> Result<ChatResponse, ProviderError> {
+        let url = self.generate_content_url(&request)?;
+        let body = self.build_request_body(&request, Some(tools));
+
+        let resp_body = read_json_response(
+            "Gemini",
+            self.client
+                .post(&url)
+                .header("content-type", "application/json")
+                .json(&body)
+                .send(),
+        )
+        .await?;
+
+        enforce_response_format(
+            &request,
+            Self::parse_response(&resp_body).map_err(|e| ProviderError::decode("Gemini", e))?,
+        )
+    }
+
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            request.model, self.config.api_key
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent?key={}",
+            self.embedding_model, self.config.api_key
         );
-        let body = self.build_request_body(&request, Some(tools));
 
-        let resp = self
-            .client
-            .post(&url)
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
+        // embedContent takes a single content per call.
+        let mut vectors = Vec::with_capacity(texts.len());
+        for text in texts {
+            let resp_body = read_json_response(
+                "Gemini",
+                self.client
+                    .post(&url)
+                    .header("content-type", "application/json")
+                    .json(&self.build_embedding_body(text))
+                    .send(),
+            )
             .await?;
 
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
-
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("Gemini API error ({}): {}", status, msg);
+            vectors.push(Self::parse_embedding_response(&resp_body)?);
         }
-
-        Self::parse_response(&resp_body)
+        Ok(vectors)
     }
 }
 
@@ -763,6 +1778,8 @@ impl Provider for GeminiProvider {
 /// Ollama provider for local LLM inference.
 pub struct OllamaProvider {
     pub config: OllamaConfig,
+    /// Model used by [`Provider::embed`].
+    pub embedding_model: String,
     client: reqwest::Client,
 }
 
@@ -779,10 +1796,17 @@ impl OllamaProvider {
     pub fn new(config: OllamaConfig) -> Self {
         Self {
             config,
+            embedding_model: OLLAMA_EMBEDDING_MODEL.to_string(),
             client: reqwest::Client::new(),
         }
     }
 
+    /// Use `model` for embeddings instead of the default.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
     /// Create an Ollama provider with default localhost settings.
     pub fn with_defaults() -> Self {
         Self::new(OllamaConfig {
@@ -798,15 +1822,64 @@ impl OllamaProvider {
             .unwrap_or("http://localhost:11434")
     }
 
+    /// Names of the locally installed models, from `/api/tags`.
+    pub async fn list_models(&self) -> anyhow::Result<Vec<String>> {
+        let url = format!("{}/api/tags", self.base_url());
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(body
+            .get("models")
+            .and_then(|m| m.as_array())
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Build the Ollama `/api/embeddings` request body for one text.
+    fn build_embedding_body(&self, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.embedding_model,
+            "prompt": text,
+        })
+    }
+
+    /// Parse an Ollama `/api/embeddings` response.
+    fn parse_embedding_response(body: &serde_json::Value) -> anyhow::Result<Vec<f32>> {
+        parse_vector(body.get("embedding"))
+    }
+
     /// Build the Ollama /api/chat request body.
-    fn build_request_body(&self, request: &ChatRequest) -> serde_json::Value {
+    ///
+    /// Fails if any message carries image parts, since the default Ollama
+    /// provider does not advertise vision support.
+    fn build_request_body(&self, request: &ChatRequest) -> anyhow::Result<serde_json::Value> {
+        if !self.capabilities().vision && request.messages.iter().any(|m| m.content.has_images()) {
+            anyhow::bail!(
+                "Ollama provider does not support image content (model '{}'); \
+                 use a vision-capable provider",
+                request.model
+            );
+        }
+
         let messages: Vec<serde_json::Value> = request
             .messages
             .iter()
             .map(|m| {
                 serde_json::json!({
                     "role": ollama_role(&m.role),
-                    "content": m.content,
+                    "content": m.content.text(),
                 })
             })
             .collect();
@@ -824,11 +1897,25 @@ impl OllamaProvider {
         if let Some(max_tokens) = request.max_tokens {
             options.insert("num_predict".to_string(), serde_json::json!(max_tokens));
         }
+        if let Some(stop) = &request.stop {
+            options.insert("stop".to_string(), serde_json::json!(stop));
+        }
+        if let Some(top_p) = request.top_p {
+            options.insert("top_p".to_string(), serde_json::json!(top_p));
+        }
+        if let Some(seed) = request.seed {
+            options.insert("seed".to_string(), serde_json::json!(seed));
+        }
         if !options.is_empty() {
             body["options"] = serde_json::Value::Object(options);
         }
+        match &request.response_format {
+            Some(ResponseFormat::Json) => body["format"] = serde_json::json!("json"),
+            Some(ResponseFormat::JsonSchema { schema }) => body["format"] = schema.clone(),
+            None => {}
+        }
 
-        body
+        Ok(body)
     }
 
     /// Parse an Ollama /api/chat response into a ChatResponse.
@@ -849,6 +1936,7 @@ impl OllamaProvider {
                     .unwrap_or(0) as u32,
                 completion_tokens: body.get("eval_count").and_then(|v| v.as_u64()).unwrap_or(0)
                     as u32,
+                ..Default::default()
             })
         } else {
             None
@@ -858,6 +1946,10 @@ impl OllamaProvider {
             content,
             tool_calls: vec![],
             usage,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
         })
     }
 }
@@ -877,6 +1969,20 @@ impl Provider for OllamaProvider {
         "ollama"
     }
 
+    /// Only models that are installed locally; an untagged name matches its
+    /// `:latest` tag. An unreachable server supports nothing.
+    async fn supports_model(&self, model: &str) -> bool {
+        match self.list_models().await {
+            Ok(models) => models
+                .iter()
+                .any(|m| m == model || m.strip_suffix(":latest") == Some(model)),
+            Err(e) => {
+                tracing::debug!(error = %e, "ollama model list unavailable");
+                false
+            }
+        }
+    }
+
     fn capabilities(&self) -> ProviderCapabilities {
         ProviderCapabilities {
             native_tool_calling: false,
@@ -885,57 +1991,149 @@ impl Provider for OllamaProvider {
         }
     }
 
-    async fn chat(&self, request: ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = format!("{}/api/chat", self.base_url());
-        let body = self.build_request_body(&request);
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
-
-        let status = resp.status();
-        let resp_body: serde_json::Value = resp.json().await?;
-
-        if !status.is_success() {
-            let msg = resp_body
-                .get("error")
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            anyhow::bail!("Ollama API error ({}): {}", status, msg);
-        }
+    /// Lists installed models via `/api/tags`.
+    async fn health_probe(&self) -> ProbeResult {
+        ProbeResult::measure(async { self.list_models().await.map(drop) }).await
+    }
 
-        Self::parse_response(&resp_body)
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let url = format!("{}/api/chat", self.base_url());
+        let body = self.build_request_body(&request)?;
+
+        let resp_body = read_json_response(
+            "Ollama",
+            self.client
+                .post(&url)
+                .header("content-type", "application/json")
+                .json(&body)
+                .send(),
+        )
+        .await?;
+
+        enforce_response_format(
+            &request,
+            Self::parse_response(&resp_body).map_err(|e| ProviderError::decode("Ollama", e))?,
+        )
     }
 
     async fn chat_with_tools(
         &self,
         request: ChatRequest,
         _tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> Result<ChatResponse, ProviderError> {
         // Ollama does not natively support tool calling; delegate to plain chat.
         self.chat(request).await
     }
+
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embeddings", self.base_url());
+
+        // /api/embeddings takes a single prompt per call.
+        let mut vectors = Vec::with_capacity(texts.len());
+        for text in texts {
+            let resp_body = read_json_response(
+                "Ollama",
+                self.client
+                    .post(&url)
+                    .header("content-type", "application/json")
+                    .json(&self.build_embedding_body(text))
+                    .send(),
+            )
+            .await?;
+
+            vectors.push(Self::parse_embedding_response(&resp_body)?);
+        }
+        Ok(vectors)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Model fallback
+// ---------------------------------------------------------------------------
+
+/// Provider error statuses that fall back to the next model by default:
+/// rate limits, server errors and Claude's 529 "overloaded".
+pub const DEFAULT_RETRY_STATUSES: &[u16] = &[429, 500, 502, 503, 504, 529];
+
+/// Models to try in turn when the requested one fails, for
+/// [`ProviderRegistry::chat_with_fallback`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct FallbackPolicy {
+    /// Models tried, in order, after the request's own model.
+    pub models: Vec<String>,
+    /// Provider error statuses that move on to the next model.  Other
+    /// errors, such as 400 for an invalid request, are returned as is.
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for FallbackPolicy {
+    fn default() -> Self {
+        Self {
+            models: Vec::new(),
+            retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+        }
+    }
+}
+
+impl FallbackPolicy {
+    /// Fall back to `models` on the default retry statuses.
+    pub fn new(models: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            models: models.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `error` moves on to the next model: an error status among
+    /// the retry statuses, a timeout, or a failure to reach the provider.
+    pub fn is_retryable(&self, error: &ProviderError) -> bool {
+        match error {
+            ProviderError::Timeout { .. } | ProviderError::Transport { .. } => true,
+            _ => error
+                .status()
+                .is_some_and(|status| self.retry_statuses.contains(&status)),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Provider Registry
 // ---------------------------------------------------------------------------
 
+/// A provider held by a [`ProviderRegistry`] under its instance name.
+struct Instance {
+    name: String,
+    provider: Box<dyn Provider>,
+}
+
 /// Registry that holds multiple provider implementations and provides
 /// lookup by name or model-name routing.
+///
+/// Providers are held under instance names, which default to the
+/// provider's own name but can be chosen with
+/// [`register_named`](Self::register_named), so several providers of one
+/// kind (say, two OpenAI-compatible endpoints) can sit side by side.
 pub struct ProviderRegistry {
-    providers: Vec<Box<dyn Provider>>,
+    providers: Vec<Instance>,
+    /// Explicit model name -> instance name routes, checked before the
+    /// prefix rules in [`ProviderRegistry::route`].
+    models: HashMap<String, String>,
+    /// Model name prefix -> instance name routes, checked after `models`
+    /// and before the built-in prefix rules.
+    prefixes: Vec<(String, String)>,
+    /// Used by [`ProviderRegistry::chat_with_fallback`] when the call
+    /// gives no policy.
+    fallback: Option<FallbackPolicy>,
 }
 
 impl std::fmt::Debug for ProviderRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let names: Vec<&str> = self.providers.iter().map(|p| p.name()).collect();
         f.debug_struct("ProviderRegistry")
-            .field("providers", &names)
+            .field("providers", &self.list())
+            .field("models", &self.models)
+            .field("prefixes", &self.prefixes)
+            .field("fallback", &self.fallback)
             .finish()
     }
 }
@@ -945,39 +2143,100 @@ impl ProviderRegistry {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            models: HashMap::new(),
+            prefixes: Vec::new(),
+            fallback: None,
         }
     }
 
-    /// Register a provider.
+    /// Route `model` to the provider instance named `provider`, overriding
+    /// the prefix rules.
+    pub fn register_model(&mut self, model: impl Into<String>, provider: impl Into<String>) {
+        self.models.insert(model.into(), provider.into());
+    }
+
+    /// Route models whose names start with `prefix` to the provider
+    /// instance named `provider`, ahead of the built-in prefix rules.
+    /// The longest matching prefix wins.
+    pub fn register_prefix(&mut self, prefix: impl Into<String>, provider: impl Into<String>) {
+        let prefix = prefix.into().to_lowercase();
+        self.prefixes.retain(|(existing, _)| *existing != prefix);
+        self.prefixes.push((prefix, provider.into()));
+    }
+
+    /// Register a provider under its own name.
     pub fn register(&mut self, provider: Box<dyn Provider>) {
-        self.providers.push(provider);
+        let name = provider.name().to_string();
+        self.register_named(name, provider);
+    }
+
+    /// Register a provider under the instance name `name`, replacing any
+    /// provider already registered under it.
+    pub fn register_named(&mut self, name: impl Into<String>, provider: Box<dyn Provider>) {
+        let name = name.into();
+        match self.providers.iter_mut().find(|i| i.name == name) {
+            Some(existing) => existing.provider = provider,
+            None => self.providers.push(Instance { name, provider }),
+        }
     }
 
-    /// Get a provider by its name.
+    /// Get a provider by its instance name.
     pub fn get(&self, name: &str) -> Option<&dyn Provider> {
         self.providers
             .iter()
-            .find(|p| p.name() == name)
-            .map(|p| &**p)
+            .find(|i| i.name == name)
+            .map(|i| &*i.provider)
+    }
+
+    /// The kind of the provider instance `name`: the underlying provider's
+    /// own name, e.g. `"openai"` for an instance registered as
+    /// `"azure-gpt4"`.
+    pub fn kind(&self, name: &str) -> Option<&str> {
+        self.get(name).map(|p| p.name())
     }
 
-    /// List all registered provider names.
+    /// List all registered instance names.
     pub fn list(&self) -> Vec<&str> {
-        self.providers.iter().map(|p| p.name()).collect()
+        self.providers.iter().map(|i| i.name.as_str()).collect()
     }
 
     /// Get the first registered provider (the "default").
     pub fn get_default(&self) -> Option<&dyn Provider> {
-        self.providers.first().map(|p| &**p)
+        self.providers.first().map(|i| &*i.provider)
+    }
+
+    /// The instance named `kind`, else the first instance of that kind.
+    fn get_kind(&self, kind: &str) -> Option<&dyn Provider> {
+        self.get(kind).or_else(|| {
+            self.providers
+                .iter()
+                .find(|i| i.provider.name() == kind)
+                .map(|i| &*i.provider)
+        })
     }
 
     /// Route a model name to the appropriate provider.
     ///
-    /// Uses prefix matching: model names starting with "claude" go to the
-    /// claude provider, "gpt" or "o1" or "o3" to openai, "gemini" to gemini,
-    /// and everything else to ollama (if registered).
+    /// Models registered with [`ProviderRegistry::register_model`] go to
+    /// their provider, then those matching a
+    /// [`register_prefix`](Self::register_prefix) prefix. Otherwise uses
+    /// prefix matching: model names starting with "claude" go to the claude
+    /// provider, "gpt" or "o1" or "o3" to openai, "gemini" to gemini, and
+    /// everything else to ollama (if registered). Each of these goes to the
+    /// instance of that name, else to the first instance of that kind.
     pub fn route(&self, model_name: &str) -> Option<&dyn Provider> {
+        if let Some(provider) = self.models.get(model_name) {
+            return self.get(provider);
+        }
         let lower = model_name.to_lowercase();
+        if let Some((_, provider)) = self
+            .prefixes
+            .iter()
+            .filter(|(prefix, _)| lower.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+        {
+            return self.get(provider);
+        }
         let target = if lower.starts_with("claude") {
             "claude"
         } else if lower.starts_with("gpt")
@@ -994,7 +2253,75 @@ impl ProviderRegistry {
             "ollama"
         };
 
-        self.get(target)
+        self.get_kind(target)
+    }
+
+    /// Like [`ProviderRegistry::route`], but only returns a provider that
+    /// reports it can serve the model, so unknown names are not silently
+    /// sent to the ollama fallback.
+    pub async fn resolve(&self, model_name: &str) -> Option<&dyn Provider> {
+        let provider = self.route(model_name)?;
+        provider
+            .supports_model(model_name)
+            .await
+            .then_some(provider)
+    }
+
+    /// Fall back along `policy` in [`ProviderRegistry::chat_with_fallback`]
+    /// calls that give no policy of their own.
+    pub fn with_fallback(mut self, policy: FallbackPolicy) -> Self {
+        self.fallback = Some(policy);
+        self
+    }
+
+    /// Send `request` to its model, then to each fallback model in turn
+    /// while the failure is retryable, stopping at the first success.
+    ///
+    /// Fallbacks come from `policy`, else from the registry's
+    /// [`with_fallback`](Self::with_fallback) policy.  Every model is
+    /// routed with [`route`](Self::route), and the response's `model` names
+    /// the one that answered.  A non-retryable error is returned at once;
+    /// when every model fails, the last error is returned.
+    pub async fn chat_with_fallback(
+        &self,
+        request: ChatRequest,
+        policy: Option<&FallbackPolicy>,
+    ) -> Result<ChatResponse, ProviderError> {
+        let default = FallbackPolicy::default();
+        let policy = policy.or(self.fallback.as_ref()).unwrap_or(&default);
+        let mut models = vec![request.model.clone()];
+        for model in &policy.models {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+
+        let mut last_error = None;
+        for model in models {
+            let Some(provider) = self.route(&model) else {
+                tracing::debug!(model = %model, "no provider for model, trying the next fallback");
+                last_error = Some(ProviderError::Other(anyhow::anyhow!(
+                    "no provider registered for model `{model}`"
+                )));
+                continue;
+            };
+            let attempt = ChatRequest {
+                model: model.clone(),
+                ..request.clone()
+            };
+            match provider.chat(attempt).await {
+                Ok(mut response) => {
+                    response.model = Some(model);
+                    return Ok(response);
+                }
+                Err(e) if policy.is_retryable(&e) => {
+                    tracing::warn!(model = %model, error = %e, "model failed, trying the next fallback");
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.expect("the request's own model is always tried"))
     }
 
     /// Create a registry populated with all providers whose API keys are
@@ -1015,8 +2342,47 @@ impl ProviderRegistry {
         // Ollama is always available (local, no key needed).
         registry.register(Box::new(OllamaProvider::with_defaults()));
 
+        for instance in ProviderInstanceEnv::all() {
+            instance.register_into(&mut registry);
+        }
+
         registry
     }
+
+    /// Wrap every registered provider in an [`InstrumentedProvider`]
+    /// recording into `metrics`.
+    pub fn with_metrics(self, metrics: Arc<Metrics>) -> Self {
+        self.map_providers(|p| Box::new(InstrumentedProvider::new(p, metrics.clone())))
+    }
+
+    /// Wrap every registered provider in a [`MonitoredProvider`]
+    /// recording call outcomes into `health`.
+    pub fn with_health(self, health: Arc<RwLock<ProviderHealth>>) -> Self {
+        self.map_providers(|p| Box::new(MonitoredProvider::new(p, health.clone())))
+    }
+
+    /// Wrap every registered provider in a [`CachingProvider`] sharing
+    /// `cache`.
+    pub fn with_cache(self, cache: Arc<ResponseCache>) -> Self {
+        self.map_providers(|p| Box::new(CachingProvider::new(p, cache.clone())))
+    }
+
+    /// Replace every provider with `wrap(provider)`, keeping its instance
+    /// name.
+    fn map_providers(
+        mut self,
+        mut wrap: impl FnMut(Box<dyn Provider>) -> Box<dyn Provider>,
+    ) -> Self {
+        self.providers = self
+            .providers
+            .into_iter()
+            .map(|i| Instance {
+                name: i.name,
+                provider: wrap(i.provider),
+            })
+            .collect();
+        self
+    }
 }
 
 impl Default for ProviderRegistry {
@@ -1025,6 +2391,154 @@ impl Default for ProviderRegistry {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Indexed provider instances
+// ---------------------------------------------------------------------------
+
+/// One provider instance described by `YGN_PROVIDER_<n>_*` env vars:
+///
+/// - `KIND`: `claude` (or `anthropic`), `openai`, `gemini` or `ollama`;
+///   required.
+/// - `NAME`: instance name, `<kind>-<n>` by default.
+/// - `BASE_URL`, `API_KEY`, `MODEL`: the provider's settings.  `API_KEY`
+///   falls back to the kind's usual variable, e.g. `OPENAI_API_KEY`.
+/// - `MODEL_PREFIXES`: comma-separated model prefixes routed to the
+///   instance.  `MODEL` itself is always routed to it.
+#[derive(Debug, Clone, PartialEq)]
+struct ProviderInstanceEnv {
+    index: u32,
+    kind: String,
+    name: String,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    model_prefixes: Vec<String>,
+}
+
+impl ProviderInstanceEnv {
+    /// Every instance in the environment, in index order.
+    fn all() -> Vec<Self> {
+        let mut indexes: Vec<u32> = std::env::vars()
+            .filter_map(|(key, _)| {
+                key.strip_prefix("YGN_PROVIDER_")?
+                    .strip_suffix("_KIND")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        indexes.sort_unstable();
+        indexes.into_iter().filter_map(Self::from_env).collect()
+    }
+
+    fn from_env(index: u32) -> Option<Self> {
+        let var = |field: &str| {
+            std::env::var(format!("YGN_PROVIDER_{index}_{field}"))
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let kind = var("KIND")?.to_lowercase();
+        let kind = match kind.as_str() {
+            "anthropic" => "claude".to_string(),
+            _ => kind,
+        };
+        Some(Self {
+            index,
+            name: var("NAME").unwrap_or_else(|| format!("{kind}-{index}")),
+            base_url: var("BASE_URL"),
+            api_key: var("API_KEY"),
+            model: var("MODEL"),
+            model_prefixes: var("MODEL_PREFIXES")
+                .map(|v| {
+                    v.split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            kind,
+        })
+    }
+
+    /// Build the provider, or `None` (with a warning) when the kind is
+    /// unknown or a required API key is missing.
+    fn build(&self) -> Option<Box<dyn Provider>> {
+        let api_key = |fallback: &str| {
+            let key = self
+                .api_key
+                .clone()
+                .or_else(|| std::env::var(fallback).ok());
+            if key.is_none() {
+                tracing::warn!(
+                    index = self.index,
+                    name = %self.name,
+                    "provider instance has no API key, skipping"
+                );
+            }
+            key
+        };
+        let model = |default: &str| self.model.clone().unwrap_or_else(|| default.to_string());
+        let provider: Box<dyn Provider> = match self.kind.as_str() {
+            "claude" => Box::new(ClaudeProvider::new(ClaudeConfig {
+                api_key: api_key("ANTHROPIC_API_KEY")?,
+                api_keys: Vec::new(),
+                model: model("claude-sonnet-4-20250514"),
+                base_url: self.base_url.clone(),
+                prompt_caching: false,
+            })),
+            "openai" => Box::new(OpenAIProvider::new(OpenAIConfig {
+                // Local OpenAI-compatible servers often need no key.
+                api_key: match &self.base_url {
+                    Some(_) => self.api_key.clone().unwrap_or_default(),
+                    None => api_key("OPENAI_API_KEY")?,
+                },
+                api_keys: Vec::new(),
+                model: model("gpt-4o"),
+                base_url: self.base_url.clone(),
+                api_flavor: OpenAIApiFlavor::ChatCompletions,
+                reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+                reasoning_effort: None,
+            })),
+            "gemini" => {
+                if self.base_url.is_some() {
+                    tracing::warn!(name = %self.name, "gemini instances ignore BASE_URL");
+                }
+                Box::new(GeminiProvider::new(GeminiConfig {
+                    api_key: api_key("GEMINI_API_KEY")?,
+                    model: model("gemini-pro"),
+                }))
+            }
+            "ollama" => Box::new(OllamaProvider::new(OllamaConfig {
+                model: model("llama3"),
+                base_url: self.base_url.clone(),
+            })),
+            other => {
+                tracing::warn!(
+                    index = self.index,
+                    kind = other,
+                    "unknown provider kind, skipping"
+                );
+                return None;
+            }
+        };
+        Some(provider)
+    }
+
+    /// Register the instance and its model routes into `registry`.
+    fn register_into(&self, registry: &mut ProviderRegistry) {
+        let Some(provider) = self.build() else {
+            return;
+        };
+        registry.register_named(self.name.clone(), provider);
+        if let Some(model) = &self.model {
+            registry.register_model(model.clone(), self.name.clone());
+        }
+        for prefix in &self.model_prefixes {
+            registry.register_prefix(prefix.clone(), self.name.clone());
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -1032,7 +2546,9 @@ impl Default for ProviderRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::provider::{ChatMessage, ChatRequest, ChatRole, StubProvider};
+    use crate::provider::{
+        ChatMessage, ChatRequest, ChatRole, ContentPart, StubProvider, ToolCall,
+    };
     use std::sync::Mutex;
 
     /// Mutex to serialize tests that mutate environment variables, since
@@ -1045,10 +2561,17 @@ mod tests {
             model: "test-model".to_string(),
             messages: vec![ChatMessage {
                 role: ChatRole::User,
-                content: "Hello".to_string(),
+                content: "Hello".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
             }],
             max_tokens: Some(100),
             temperature: Some(0.7),
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
         }
     }
 
@@ -1058,15 +2581,24 @@ mod tests {
             messages: vec![
                 ChatMessage {
                     role: ChatRole::System,
-                    content: "You are helpful.".to_string(),
+                    content: "You are helpful.".into(),
+                    tool_call_id: None,
+                    tool_calls: Vec::new(),
                 },
                 ChatMessage {
                     role: ChatRole::User,
-                    content: "Hello".to_string(),
+                    content: "Hello".into(),
+                    tool_call_id: None,
+                    tool_calls: Vec::new(),
                 },
             ],
             max_tokens: Some(100),
             temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
         }
     }
 
@@ -1126,13 +2658,82 @@ mod tests {
         assert!(names.contains(&"ollama"));
     }
 
+    #[test]
+    fn registry_route_explicit_model() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(StubProvider::default()));
+        registry.register(Box::new(OllamaProvider::with_defaults()));
+        // Provider names are not model names.
+        assert_eq!(registry.route("stub").unwrap().name(), "ollama");
+        registry.register_model("stub-model", "stub");
+        assert_eq!(registry.route("stub-model").unwrap().name(), "stub");
+        registry.register_model("gpt-local", "ollama");
+        assert_eq!(registry.route("gpt-local").unwrap().name(), "ollama");
+    }
+
+    #[test]
+    fn registry_holds_several_instances_of_one_kind() {
+        let mut registry = ProviderRegistry::new();
+        for (name, url) in [
+            ("azure-gpt4", "https://azure.example.com"),
+            ("vllm", "http://localhost:8000"),
+        ] {
+            registry.register_named(
+                name,
+                Box::new(OpenAIProvider::new(OpenAIConfig {
+                    api_key: String::new(),
+                    api_keys: Vec::new(),
+                    model: "gpt-4o".into(),
+                    base_url: Some(url.into()),
+                    api_flavor: OpenAIApiFlavor::ChatCompletions,
+                    reasoning_models: OPENAI_REASONING_MODELS.into(),
+                    reasoning_effort: None,
+                })),
+            );
+        }
+        registry.register(Box::new(StubProvider::default()));
+
+        assert_eq!(registry.list(), ["azure-gpt4", "vllm", "stub"]);
+        assert!(registry.get("openai").is_none());
+        assert_eq!(registry.kind("azure-gpt4"), Some("openai"));
+        assert_eq!(registry.kind("vllm"), Some("openai"));
+        assert_eq!(registry.kind("stub"), Some("stub"));
+        assert_eq!(registry.kind("missing"), None);
+
+        // Re-registering a name replaces the instance.
+        registry.register_named("vllm", Box::new(OllamaProvider::with_defaults()));
+        assert_eq!(registry.list().len(), 3);
+        assert_eq!(registry.kind("vllm"), Some("ollama"));
+    }
+
+    #[test]
+    fn registry_routes_by_instance_prefix() {
+        let mut registry = ProviderRegistry::new();
+        registry.register_named("azure", Box::new(StubProvider::default()));
+        registry.register_named("vllm", Box::new(OllamaProvider::with_defaults()));
+        registry.register_prefix("gpt-4o", "azure");
+        registry.register_prefix("qwen", "vllm");
+        registry.register_prefix("Qwen2.5-coder", "azure");
+
+        let kind = |model: &str| registry.route(model).map(|p| p.name());
+        assert_eq!(kind("gpt-4o-mini"), Some("stub"));
+        assert_eq!(kind("qwen3-32b"), Some("ollama"));
+        // The longest prefix wins, case-insensitively.
+        assert_eq!(kind("qwen2.5-coder-7b"), Some("stub"));
+        // Built-in rules fall back to an instance of the right kind.
+        assert_eq!(kind("llama3"), Some("ollama"));
+        assert_eq!(kind("claude-3-opus"), None);
+    }
+
     #[test]
     fn registry_route_claude_models() {
         let mut registry = ProviderRegistry::new();
         registry.register(Box::new(ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         })));
         assert!(registry.route("claude-3-opus").is_some());
         assert_eq!(registry.route("claude-3-opus").unwrap().name(), "claude");
@@ -1147,8 +2748,12 @@ mod tests {
         let mut registry = ProviderRegistry::new();
         registry.register(Box::new(OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         })));
         assert_eq!(registry.route("gpt-4").unwrap().name(), "openai");
         assert_eq!(registry.route("gpt-4o").unwrap().name(), "openai");
@@ -1180,6 +2785,47 @@ mod tests {
         assert_eq!(registry.route("codellama").unwrap().name(), "ollama");
     }
 
+    /// Serve a fake Ollama `/api/tags` listing `llama3:latest`.
+    async fn fake_ollama() -> OllamaProvider {
+        use axum::{routing::get, Json, Router};
+        let app = Router::new().route(
+            "/api/tags",
+            get(|| async {
+                Json(serde_json::json!({
+                    "models": [{ "name": "llama3:latest" }, { "name": "mistral:7b" }]
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        OllamaProvider::new(OllamaConfig {
+            model: "llama3".to_string(),
+            base_url: Some(base_url),
+        })
+    }
+
+    #[tokio::test]
+    async fn registry_resolve_checks_ollama_models() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(fake_ollama().await));
+        assert_eq!(registry.resolve("llama3").await.unwrap().name(), "ollama");
+        assert!(registry.resolve("mistral:7b").await.is_some());
+        assert!(registry.resolve("mistral").await.is_none());
+        assert!(registry.resolve("no-such-model").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn registry_resolve_unreachable_ollama_is_none() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(OllamaProvider::new(OllamaConfig {
+            model: "llama3".to_string(),
+            base_url: Some("http://127.0.0.1:9".to_string()),
+        })));
+        assert_eq!(registry.route("llama3").unwrap().name(), "ollama");
+        assert!(registry.resolve("llama3").await.is_none());
+    }
+
     #[test]
     fn registry_route_missing_provider_returns_none() {
         let registry = ProviderRegistry::new();
@@ -1209,8 +2855,10 @@ mod tests {
     fn claude_config_serialization() {
         let config = ClaudeConfig {
             api_key: "sk-test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         };
         let json = serde_json::to_string(&config).unwrap();
         let round: ClaudeConfig = serde_json::from_str(&json).unwrap();
@@ -1221,8 +2869,10 @@ mod tests {
     fn claude_build_request_body_basic() {
         let provider = ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         });
         let body = provider.build_request_body(&sample_request(), None);
         assert_eq!(body["model"], "test-model");
@@ -1235,8 +2885,10 @@ mod tests {
     fn claude_build_request_extracts_system() {
         let provider = ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         });
         let body = provider.build_request_body(&sample_request_with_system(), None);
         assert_eq!(body["system"], "You are helpful.");
@@ -1250,8 +2902,10 @@ mod tests {
     fn claude_build_request_with_tools() {
         let provider = ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         });
         let tools = vec![sample_tool_spec()];
         let body = provider.build_request_body(&sample_request(), Some(&tools));
@@ -1304,14 +2958,222 @@ mod tests {
     }
 
     #[test]
-    fn claude_capabilities() {
-        let provider = ClaudeProvider::new(ClaudeConfig {
-            api_key: "test".to_string(),
-            model: "claude-sonnet-4-20250514".to_string(),
-            base_url: None,
-        });
-        let caps = provider.capabilities();
-        assert!(caps.native_tool_calling);
+    fn gemini_safety_block_is_a_typed_error() {
+        let resp_json: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/gemini/safety_blocked.json"))
+                .unwrap();
+        let err = GeminiProvider::parse_response(&resp_json).unwrap_err();
+        let err = ProviderError::decode("Gemini", err);
+        let ProviderError::Blocked {
+            reason,
+            safety_ratings,
+            ..
+        } = &err
+        else {
+            panic!("expected a block, got {err:?}");
+        };
+        assert_eq!(reason, "SAFETY");
+        assert_eq!(safety_ratings.len(), 4);
+        assert!(safety_ratings[3].blocked);
+        assert_eq!(
+            err.to_string(),
+            "Gemini blocked the response (SAFETY): HARM_CATEGORY_DANGEROUS_CONTENT HIGH"
+        );
+    }
+
+    #[test]
+    fn gemini_blocked_prompt_is_a_typed_error() {
+        let resp_json = serde_json::json!({
+            "promptFeedback": {
+                "blockReason": "PROHIBITED_CONTENT",
+                "safetyRatings": []
+            }
+        });
+        let err = GeminiProvider::parse_response(&resp_json).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<ProviderError>(),
+                Some(ProviderError::Blocked { reason, .. }) if reason == "PROHIBITED_CONTENT"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn gemini_multi_candidate_response_keeps_alternates() {
+        let resp_json: serde_json::Value = serde_json::from_str(include_str!(
+            "../tests/fixtures/gemini/multi_candidate.json"
+        ))
+        .unwrap();
+        let resp = GeminiProvider::parse_response(&resp_json).unwrap();
+
+        assert_eq!(resp.content, "Checking the weather and the time.");
+        let names: Vec<&str> = resp
+            .tool_calls
+            .iter()
+            .map(|c| c.tool_name.as_str())
+            .collect();
+        assert_eq!(names, ["get_weather", "get_time"]);
+        assert_eq!(resp.tool_calls[1].arguments["timezone"], "Europe/Paris");
+        assert_eq!(resp.finish_reason.as_deref(), Some("STOP"));
+
+        // The recitation-blocked candidate is dropped.
+        assert_eq!(resp.alternates.len(), 1);
+        assert_eq!(resp.alternates[0].content, "It is sunny in Paris.");
+        assert_eq!(
+            resp.alternates[0].finish_reason.as_deref(),
+            Some("MAX_TOKENS")
+        );
+        assert_eq!(resp.usage.unwrap().completion_tokens, 31);
+    }
+
+    #[test]
+    fn gemini_request_asks_for_candidate_count() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "test".to_string(),
+            model: "gemini-pro".to_string(),
+        });
+        let request = ChatRequest {
+            candidate_count: Some(3),
+            ..sample_request()
+        };
+        let body = provider.build_request_body(&request, None);
+        assert_eq!(body["generationConfig"]["candidateCount"], 3);
+    }
+
+    #[test]
+    fn claude_prompt_caching_marks_system_and_tools() {
+        let config = ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: true,
+        };
+        let tools = vec![
+            sample_tool_spec(),
+            ToolSpec {
+                name: "get_time".to_string(),
+                ..sample_tool_spec()
+            },
+        ];
+        let request = sample_request_with_system();
+
+        let body = ClaudeProvider::new(config.clone()).build_request_body(&request, Some(&tools));
+        assert_eq!(
+            body["system"],
+            serde_json::json!([{
+                "type": "text",
+                "text": "You are helpful.",
+                "cache_control": { "type": "ephemeral" },
+            }])
+        );
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(body["tools"][1]["cache_control"]["type"], "ephemeral");
+
+        let body = ClaudeProvider::new(ClaudeConfig {
+            prompt_caching: false,
+            ..config
+        })
+        .build_request_body(&request, Some(&tools));
+        assert_eq!(body["system"], "You are helpful.");
+        assert!(!body.to_string().contains("cache_control"));
+    }
+
+    #[test]
+    fn claude_parse_response_reads_cache_usage() {
+        let resp_json = serde_json::json!({
+            "content": [{ "type": "text", "text": "Hi" }],
+            "usage": {
+                "input_tokens": 12,
+                "output_tokens": 5,
+                "cache_creation_input_tokens": 2048,
+                "cache_read_input_tokens": 0
+            }
+        });
+        let usage = ClaudeProvider::parse_response(&resp_json)
+            .unwrap()
+            .usage
+            .unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.cache_creation_tokens, Some(2048));
+        assert_eq!(usage.cache_read_tokens, Some(0));
+
+        // Older responses without the cache fields leave them unset.
+        let resp_json = serde_json::json!({
+            "content": [],
+            "usage": { "input_tokens": 1, "output_tokens": 1 }
+        });
+        let usage = ClaudeProvider::parse_response(&resp_json)
+            .unwrap()
+            .usage
+            .unwrap();
+        assert_eq!(usage.cache_creation_tokens, None);
+        assert_eq!(usage.cache_read_tokens, None);
+    }
+
+    /// An assistant turn that called two tools, then both results.
+    fn parallel_tool_turn(calls: Vec<ToolCall>) -> Vec<ChatMessage> {
+        let mut messages = vec![ChatMessage {
+            role: ChatRole::Assistant,
+            content: "".into(),
+            tool_call_id: None,
+            tool_calls: calls.clone(),
+        }];
+        messages.extend(calls.into_iter().map(|call| ChatMessage {
+            role: ChatRole::Tool,
+            content: format!("{} result", call.tool_name).into(),
+            tool_call_id: call.id,
+            tool_calls: Vec::new(),
+        }));
+        messages
+    }
+
+    #[test]
+    fn claude_parallel_tool_calls_keep_distinct_ids() {
+        let resp_json = serde_json::json!({
+            "content": [
+                { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": { "location": "NYC" } },
+                { "type": "tool_use", "id": "toolu_2", "name": "get_time", "input": { "zone": "EST" } }
+            ]
+        });
+        let resp = ClaudeProvider::parse_response(&resp_json).unwrap();
+        let ids: Vec<_> = resp.tool_calls.iter().map(|c| c.id.as_deref()).collect();
+        assert_eq!(ids, [Some("toolu_1"), Some("toolu_2")]);
+
+        let mut request = sample_request();
+        request.messages.extend(parallel_tool_turn(resp.tool_calls));
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let body = provider.build_request_body(&request, None);
+        let messages = body["messages"].as_array().unwrap();
+        let uses = messages[1]["content"].as_array().unwrap();
+        assert_eq!(uses[0]["type"], "tool_use");
+        assert_eq!(uses[0]["id"], "toolu_1");
+        assert_eq!(uses[1]["id"], "toolu_2");
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[2]["content"][0]["content"], "get_weather result");
+        assert_eq!(messages[3]["content"][0]["tool_use_id"], "toolu_2");
+    }
+
+    #[test]
+    fn claude_capabilities() {
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let caps = provider.capabilities();
+        assert!(caps.native_tool_calling);
         assert!(caps.vision);
         assert!(caps.streaming);
     }
@@ -1320,8 +3182,10 @@ mod tests {
     fn claude_name() {
         let provider = ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: None,
+            prompt_caching: false,
         });
         assert_eq!(provider.name(), "claude");
     }
@@ -1330,8 +3194,10 @@ mod tests {
     fn claude_custom_base_url() {
         let provider = ClaudeProvider::new(ClaudeConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "claude-sonnet-4-20250514".to_string(),
             base_url: Some("https://custom.api.com".to_string()),
+            prompt_caching: false,
         });
         assert_eq!(provider.base_url(), "https://custom.api.com");
     }
@@ -1362,8 +3228,12 @@ mod tests {
     fn openai_config_serialization() {
         let config = OpenAIConfig {
             api_key: "sk-test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         };
         let json = serde_json::to_string(&config).unwrap();
         let round: OpenAIConfig = serde_json::from_str(&json).unwrap();
@@ -1374,8 +3244,12 @@ mod tests {
     fn openai_build_request_body_basic() {
         let provider = OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         });
         let body = provider.build_request_body(&sample_request(), None);
         assert_eq!(body["model"], "test-model");
@@ -1388,8 +3262,12 @@ mod tests {
     fn openai_build_request_preserves_system_role() {
         let provider = OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         });
         let body = provider.build_request_body(&sample_request_with_system(), None);
         let messages = body["messages"].as_array().unwrap();
@@ -1402,8 +3280,12 @@ mod tests {
     fn openai_build_request_with_tools() {
         let provider = OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         });
         let tools = vec![sample_tool_spec()];
         let body = provider.build_request_body(&sample_request(), Some(&tools));
@@ -1413,6 +3295,195 @@ mod tests {
         assert_eq!(tool_defs[0]["function"]["name"], "get_weather");
     }
 
+    fn openai_with(api_flavor: OpenAIApiFlavor, reasoning_effort: Option<&str>) -> OpenAIProvider {
+        OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: reasoning_effort.map(String::from),
+        })
+    }
+
+    #[test]
+    fn openai_config_defaults_to_chat_completions() {
+        let config: OpenAIConfig =
+            serde_json::from_str(r#"{"api_key":"k","model":"gpt-4o","base_url":null}"#).unwrap();
+        assert_eq!(config.api_flavor, OpenAIApiFlavor::ChatCompletions);
+        assert_eq!(config.reasoning_models, OPENAI_REASONING_MODELS);
+        assert!(config.reasoning_effort.is_none());
+
+        let config: OpenAIConfig = serde_json::from_str(
+            r#"{"api_key":"k","model":"o3","base_url":null,"api_flavor":"responses"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.api_flavor, OpenAIApiFlavor::Responses);
+    }
+
+    #[test]
+    fn openai_reasoning_models_take_completion_tokens_and_effort() {
+        let provider = openai_with(OpenAIApiFlavor::ChatCompletions, Some("high"));
+        let mut request = sample_request();
+        request.model = "o3-mini".to_string();
+        let body = provider.build_request_body(&request, None);
+        assert_eq!(body["max_completion_tokens"], 100);
+        assert!(body.get("max_tokens").is_none());
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["reasoning_effort"], "high");
+
+        // Other models keep the classic parameters and get no effort.
+        request.model = "gpt-4o".to_string();
+        let body = provider.build_request_body(&request, None);
+        assert_eq!(body["max_tokens"], 100);
+        assert_eq!(body["temperature"], 0.7);
+        assert!(body.get("max_completion_tokens").is_none());
+        assert!(body.get("reasoning_effort").is_none());
+    }
+
+    #[test]
+    fn openai_reasoning_model_pattern_is_configurable() {
+        let mut config = openai_with(OpenAIApiFlavor::ChatCompletions, None).config;
+        config.reasoning_models = "^my-thinker".to_string();
+        let provider = OpenAIProvider::new(config);
+        let mut request = sample_request();
+        request.model = "my-thinker-2".to_string();
+        assert_eq!(
+            provider.build_request_body(&request, None)["max_completion_tokens"],
+            100
+        );
+        request.model = "o3".to_string();
+        assert_eq!(
+            provider.build_request_body(&request, None)["max_tokens"],
+            100
+        );
+
+        // An invalid pattern matches nothing.
+        let mut config = provider.config.clone();
+        config.reasoning_models = "(".to_string();
+        assert!(!OpenAIProvider::new(config).is_reasoning_model("o3"));
+    }
+
+    #[test]
+    fn openai_responses_body_uses_input_items() {
+        let provider = openai_with(OpenAIApiFlavor::Responses, None);
+        let mut request = sample_request_with_system();
+        request.stop = Some(vec!["END".to_string()]);
+        request.messages.push(ChatMessage {
+            role: ChatRole::Assistant,
+            content: "".into(),
+            tool_call_id: None,
+            tool_calls: vec![ToolCall {
+                tool_name: "get_weather".to_string(),
+                arguments: serde_json::json!({ "location": "Paris" }),
+                id: Some("call_1".to_string()),
+            }],
+        });
+        request.messages.push(ChatMessage {
+            role: ChatRole::Tool,
+            content: "sunny".into(),
+            tool_call_id: Some("call_1".to_string()),
+            tool_calls: Vec::new(),
+        });
+        request.messages.push(ChatMessage {
+            role: ChatRole::User,
+            content: vec![
+                ContentPart::Text {
+                    text: "And this?".to_string(),
+                },
+                ContentPart::ImageUrl {
+                    url: "https://example.com/a.png".to_string(),
+                },
+            ]
+            .into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        });
+        request.response_format = Some(ResponseFormat::Json);
+        let body = provider.build_chat_body(&request, Some(&[sample_tool_spec()]));
+
+        assert!(body.get("messages").is_none());
+        assert!(body.get("stop").is_none());
+        assert_eq!(body["max_output_tokens"], 100);
+        let input = body["input"].as_array().unwrap();
+        assert_eq!(input.len(), 5);
+        assert_eq!(input[0]["role"], "system");
+        assert_eq!(input[1]["role"], "user");
+        assert_eq!(input[2]["type"], "function_call");
+        assert_eq!(input[2]["call_id"], "call_1");
+        assert_eq!(input[2]["arguments"], r#"{"location":"Paris"}"#);
+        assert_eq!(input[3]["type"], "function_call_output");
+        assert_eq!(input[3]["output"], "sunny");
+        assert_eq!(input[4]["content"][0]["type"], "input_text");
+        assert_eq!(input[4]["content"][1]["type"], "input_image");
+        assert_eq!(
+            input[4]["content"][1]["image_url"],
+            "https://example.com/a.png"
+        );
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["name"], "get_weather");
+        assert_eq!(body["text"]["format"]["type"], "json_object");
+    }
+
+    #[test]
+    fn openai_responses_body_maps_reasoning_parameters() {
+        let provider = openai_with(OpenAIApiFlavor::Responses, Some("low"));
+        let mut request = sample_request();
+        request.model = "o4-mini".to_string();
+        let body = provider.build_chat_body(&request, None);
+        assert_eq!(body["max_output_tokens"], 100);
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["reasoning"]["effort"], "low");
+
+        request.model = "gpt-4.1".to_string();
+        let body = provider.build_chat_body(&request, None);
+        assert_eq!(body["temperature"], 0.7);
+        assert!(body.get("reasoning").is_none());
+    }
+
+    #[test]
+    fn openai_parse_responses_output() {
+        let resp_json = serde_json::json!({
+            "output": [
+                { "type": "reasoning", "summary": [] },
+                {
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [
+                        { "type": "output_text", "text": "Hello " },
+                        { "type": "output_text", "text": "there" }
+                    ]
+                },
+                {
+                    "type": "function_call",
+                    "call_id": "call_9",
+                    "name": "get_weather",
+                    "arguments": "{\"location\":\"Oslo\"}"
+                }
+            ],
+            "usage": { "input_tokens": 12, "output_tokens": 30, "total_tokens": 42 }
+        });
+        let resp = OpenAIProvider::parse_responses_response(&resp_json).unwrap();
+        assert_eq!(resp.content, "Hello there");
+        assert_eq!(resp.tool_calls.len(), 1);
+        assert_eq!(resp.tool_calls[0].id.as_deref(), Some("call_9"));
+        assert_eq!(resp.tool_calls[0].arguments["location"], "Oslo");
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.completion_tokens, 30);
+
+        let refused = serde_json::json!({
+            "output": [{
+                "type": "message",
+                "content": [{ "type": "refusal", "refusal": "no" }]
+            }]
+        });
+        let err = OpenAIProvider::parse_responses_response(&refused).unwrap_err();
+        assert!(err.to_string().contains("refused"));
+        assert!(OpenAIProvider::parse_responses_response(&serde_json::json!({})).is_err());
+    }
+
     #[test]
     fn openai_parse_response_text() {
         let resp_json = serde_json::json!({
@@ -1464,6 +3535,94 @@ mod tests {
         assert_eq!(resp.tool_calls[0].arguments["location"], "NYC");
     }
 
+    #[test]
+    fn openai_parallel_tool_calls_keep_distinct_ids() {
+        let resp_json = serde_json::json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [
+                        { "id": "call_a", "type": "function",
+                          "function": { "name": "get_weather", "arguments": "{\"location\":\"NYC\"}" } },
+                        { "id": "call_b", "type": "function",
+                          "function": { "name": "get_time", "arguments": "{}" } }
+                    ]
+                }
+            }]
+        });
+        let resp = OpenAIProvider::parse_response(&resp_json).unwrap();
+        let ids: Vec<_> = resp.tool_calls.iter().map(|c| c.id.as_deref()).collect();
+        assert_eq!(ids, [Some("call_a"), Some("call_b")]);
+
+        let mut request = sample_request();
+        request.messages.extend(parallel_tool_turn(resp.tool_calls));
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        });
+        let body = provider.build_request_body(&request, None);
+        let messages = body["messages"].as_array().unwrap();
+        let calls = messages[1]["tool_calls"].as_array().unwrap();
+        assert_eq!(calls[0]["id"], "call_a");
+        assert_eq!(calls[0]["function"]["arguments"], "{\"location\":\"NYC\"}");
+        assert_eq!(calls[1]["id"], "call_b");
+        assert_eq!(messages[2]["role"], "tool");
+        assert_eq!(messages[2]["tool_call_id"], "call_a");
+        assert_eq!(messages[3]["tool_call_id"], "call_b");
+        assert_eq!(messages[3]["content"], "get_time result");
+    }
+
+    #[test]
+    fn openai_parse_response_string_content() {
+        let resp_json = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "plain", "refusal": null } }]
+        });
+        let resp = OpenAIProvider::parse_response(&resp_json).unwrap();
+        assert_eq!(resp.content, "plain");
+    }
+
+    #[test]
+    fn openai_parse_response_concatenates_content_parts() {
+        let resp_json = serde_json::json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": [
+                        { "type": "text", "text": "Hello, " },
+                        { "type": "text", "text": "world" }
+                    ]
+                }
+            }]
+        });
+        let resp = OpenAIProvider::parse_response(&resp_json).unwrap();
+        assert_eq!(resp.content, "Hello, world");
+    }
+
+    #[test]
+    fn openai_parse_response_refusal_errors() {
+        let field = serde_json::json!({
+            "choices": [{ "message": { "content": null, "refusal": "I can't help with that." } }]
+        });
+        let err = OpenAIProvider::parse_response(&field).unwrap_err();
+        assert!(err.to_string().contains("I can't help with that."));
+
+        let part = serde_json::json!({
+            "choices": [{
+                "message": {
+                    "content": [{ "type": "refusal", "refusal": "Not allowed." }]
+                }
+            }]
+        });
+        let err = OpenAIProvider::parse_response(&part).unwrap_err();
+        assert!(err.to_string().contains("Not allowed."));
+    }
+
     #[test]
     fn openai_parse_response_no_choices_errors() {
         let resp_json = serde_json::json!({ "choices": [] });
@@ -1475,8 +3634,12 @@ mod tests {
     fn openai_capabilities() {
         let provider = OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         });
         let caps = provider.capabilities();
         assert!(caps.native_tool_calling);
@@ -1488,8 +3651,12 @@ mod tests {
     fn openai_custom_base_url() {
         let provider = OpenAIProvider::new(OpenAIConfig {
             api_key: "test".to_string(),
+            api_keys: Vec::new(),
             model: "gpt-4o".to_string(),
             base_url: Some("https://my-azure.openai.azure.com".to_string()),
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
         });
         assert_eq!(provider.base_url(), "https://my-azure.openai.azure.com");
     }
@@ -1543,6 +3710,37 @@ mod tests {
         assert_eq!(body["generationConfig"]["temperature"], 0.7);
     }
 
+    #[test]
+    fn gemini_url_falls_back_to_config_model() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "k".to_string(),
+            model: "gemini-1.5-flash".to_string(),
+        });
+        let mut request = sample_request();
+        assert!(provider
+            .generate_content_url(&request)
+            .unwrap()
+            .contains("/models/test-model:generateContent?key=k"));
+
+        request.model = String::new();
+        assert!(provider
+            .generate_content_url(&request)
+            .unwrap()
+            .contains("/models/gemini-1.5-flash:generateContent?key=k"));
+    }
+
+    #[test]
+    fn gemini_url_without_any_model_errors() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "k".to_string(),
+            model: String::new(),
+        });
+        let mut request = sample_request();
+        request.model = String::new();
+        let err = provider.generate_content_url(&request).unwrap_err();
+        assert!(err.to_string().contains("no model"));
+    }
+
     #[test]
     fn gemini_build_request_extracts_system() {
         let provider = GeminiProvider::new(GeminiConfig {
@@ -1662,7 +3860,7 @@ mod tests {
     #[test]
     fn ollama_build_request_body() {
         let provider = OllamaProvider::with_defaults();
-        let body = provider.build_request_body(&sample_request());
+        let body = provider.build_request_body(&sample_request()).unwrap();
         assert_eq!(body["model"], "test-model");
         assert_eq!(body["stream"], false);
         assert!(body["messages"].is_array());
@@ -1733,49 +3931,994 @@ mod tests {
     }
 
     // -----------------------------------------------------------------------
-    // Role mapping tests
+    // Vision content tests
     // -----------------------------------------------------------------------
 
+    /// A 1x1 transparent PNG, base64-encoded.
+    const TINY_PNG_B64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    fn vision_request() -> ChatRequest {
+        ChatRequest {
+            model: "test-model".to_string(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: vec![
+                    ContentPart::Text {
+                        text: "Describe these images.".to_string(),
+                    },
+                    ContentPart::Image {
+                        data: TINY_PNG_B64.to_string(),
+                        mime_type: "image/png".to_string(),
+                    },
+                    ContentPart::ImageUrl {
+                        url: "https://example.com/cat.png".to_string(),
+                    },
+                ]
+                .into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: Some(100),
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        }
+    }
+
     #[test]
-    fn claude_role_mapping() {
-        assert_eq!(claude_role(&ChatRole::User), "user");
-        assert_eq!(claude_role(&ChatRole::Assistant), "assistant");
-        assert_eq!(claude_role(&ChatRole::Tool), "user");
-        assert_eq!(claude_role(&ChatRole::System), "user");
+    fn claude_build_request_maps_image_parts() {
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let body = provider.build_request_body(&vision_request(), None);
+        let blocks = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0]["type"], "text");
+        assert_eq!(blocks[1]["type"], "image");
+        assert_eq!(blocks[1]["source"]["type"], "base64");
+        assert_eq!(blocks[1]["source"]["media_type"], "image/png");
+        assert_eq!(blocks[2]["source"]["type"], "url");
+        assert_eq!(blocks[2]["source"]["url"], "https://example.com/cat.png");
     }
 
     #[test]
-    fn openai_role_mapping() {
-        assert_eq!(openai_role(&ChatRole::System), "system");
-        assert_eq!(openai_role(&ChatRole::User), "user");
-        assert_eq!(openai_role(&ChatRole::Assistant), "assistant");
-        assert_eq!(openai_role(&ChatRole::Tool), "tool");
+    fn openai_build_request_maps_image_parts() {
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        });
+        let body = provider.build_request_body(&vision_request(), None);
+        let entries = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["type"], "text");
+        assert_eq!(entries[1]["type"], "image_url");
+        assert!(entries[1]["image_url"]["url"]
+            .as_str()
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+        assert_eq!(
+            entries[2]["image_url"]["url"],
+            "https://example.com/cat.png"
+        );
     }
 
     #[test]
-    fn gemini_role_mapping() {
-        assert_eq!(gemini_role(&ChatRole::User), "user");
-        assert_eq!(gemini_role(&ChatRole::Assistant), "model");
-        assert_eq!(gemini_role(&ChatRole::Tool), "user");
-        assert_eq!(gemini_role(&ChatRole::System), "user");
+    fn gemini_build_request_maps_image_parts() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "test".to_string(),
+            model: "gemini-pro".to_string(),
+        });
+        let body = provider.build_request_body(&vision_request(), None);
+        let parts = body["contents"][0]["parts"].as_array().unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0]["text"], "Describe these images.");
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(parts[1]["inlineData"]["data"], TINY_PNG_B64);
+        assert_eq!(
+            parts[2]["fileData"]["fileUri"],
+            "https://example.com/cat.png"
+        );
+        assert_eq!(parts[2]["fileData"]["mimeType"], "image/png");
     }
 
     #[test]
-    fn ollama_role_mapping() {
-        assert_eq!(ollama_role(&ChatRole::System), "system");
-        assert_eq!(ollama_role(&ChatRole::User), "user");
-        assert_eq!(ollama_role(&ChatRole::Assistant), "assistant");
-        assert_eq!(ollama_role(&ChatRole::Tool), "user");
+    fn ollama_rejects_image_parts() {
+        let provider = OllamaProvider::with_defaults();
+        let err = provider
+            .build_request_body(&vision_request())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not support image content"), "{err}");
     }
 
-    // -----------------------------------------------------------------------
-    // from_env registry test
-    // -----------------------------------------------------------------------
+    #[test]
+    fn text_only_content_stays_plain_string() {
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        });
+        let body = provider.build_request_body(&sample_request(), None);
+        assert_eq!(body["messages"][0]["content"], "Hello");
+    }
 
     #[test]
-    fn registry_from_env_always_includes_ollama() {
-        let _lock = ENV_MUTEX.lock().unwrap();
-        // Clear all provider keys to ensure only ollama is registered.
+    fn embedded_png_request_body_end_to_end() {
+        use base64::Engine;
+
+        // The embedded fixture must be a real PNG.
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(TINY_PNG_B64)
+            .unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+        let request = ChatRequest {
+            model: "claude-sonnet-4-20250514".to_string(),
+            messages: vec![
+                ChatMessage {
+                    role: ChatRole::System,
+                    content: "You describe images.".into(),
+                    tool_call_id: None,
+                    tool_calls: Vec::new(),
+                },
+                ChatMessage {
+                    role: ChatRole::User,
+                    content: vec![
+                        ContentPart::Text {
+                            text: "What color is this pixel?".to_string(),
+                        },
+                        ContentPart::Image {
+                            data: base64::engine::general_purpose::STANDARD.encode(&bytes),
+                            mime_type: "image/png".to_string(),
+                        },
+                    ]
+                    .into(),
+                    tool_call_id: None,
+                    tool_calls: Vec::new(),
+                },
+            ],
+            max_tokens: Some(50),
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        };
+
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let body = provider.build_request_body(&request, None);
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "model": "claude-sonnet-4-20250514",
+                "system": "You describe images.",
+                "max_tokens": 50,
+                "messages": [{
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "What color is this pixel?" },
+                        {
+                            "type": "image",
+                            "source": {
+                                "type": "base64",
+                                "media_type": "image/png",
+                                "data": TINY_PNG_B64,
+                            }
+                        }
+                    ]
+                }]
+            })
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Structured output tests
+    // -----------------------------------------------------------------------
+
+    fn weather_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["city", "temp"],
+            "properties": {
+                "city": { "type": "string" },
+                "temp": { "type": "number" }
+            }
+        })
+    }
+
+    fn structured_request(format: ResponseFormat) -> ChatRequest {
+        ChatRequest {
+            response_format: Some(format),
+            ..sample_request()
+        }
+    }
+
+    #[test]
+    fn claude_build_request_forces_structured_output_tool() {
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let tools = vec![sample_tool_spec()];
+        let body = provider.build_request_body(&request, Some(&tools));
+        let tool_defs = body["tools"].as_array().unwrap();
+        assert_eq!(tool_defs.len(), 2);
+        assert_eq!(tool_defs[1]["name"], STRUCTURED_OUTPUT_TOOL);
+        assert_eq!(tool_defs[1]["input_schema"], weather_schema());
+        // Other tools stay callable: any tool call satisfies the choice.
+        assert_eq!(body["tool_choice"], serde_json::json!({ "type": "any" }));
+
+        let body = provider.build_request_body(&structured_request(ResponseFormat::Json), None);
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"]["type"], "tool");
+        assert_eq!(body["tool_choice"]["name"], STRUCTURED_OUTPUT_TOOL);
+    }
+
+    #[test]
+    fn claude_extracts_structured_output_from_tool_call() {
+        let resp_json = serde_json::json!({
+            "content": [{
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": "structured_output",
+                "input": { "city": "Oslo", "temp": 4.5 }
+            }],
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        });
+        let response = ClaudeProvider::parse_response(&resp_json).unwrap();
+        let response = ClaudeProvider::extract_structured_output(response);
+        assert!(response.tool_calls.is_empty());
+
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let response = enforce_response_format(&request, response).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&response.content).unwrap();
+        assert_eq!(value["city"], "Oslo");
+    }
+
+    #[test]
+    fn openai_build_request_sets_response_format() {
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        });
+        let body = provider.build_request_body(&structured_request(ResponseFormat::Json), None);
+        assert_eq!(body["response_format"]["type"], "json_object");
+
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let body = provider.build_request_body(&request, None);
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(
+            body["response_format"]["json_schema"]["schema"],
+            weather_schema()
+        );
+
+        let body = provider.build_request_body(&sample_request(), None);
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn gemini_build_request_sets_response_mime_type() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "test".to_string(),
+            model: "gemini-pro".to_string(),
+        });
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let body = provider.build_request_body(&request, None);
+        let gen_config = &body["generationConfig"];
+        assert_eq!(gen_config["responseMimeType"], "application/json");
+        assert_eq!(gen_config["responseSchema"], weather_schema());
+
+        let body = provider.build_request_body(&structured_request(ResponseFormat::Json), None);
+        assert_eq!(
+            body["generationConfig"]["responseMimeType"],
+            "application/json"
+        );
+        assert!(body["generationConfig"].get("responseSchema").is_none());
+
+        let body = provider.build_request_body(&sample_request(), None);
+        assert!(body["generationConfig"].get("responseMimeType").is_none());
+    }
+
+    #[test]
+    fn ollama_build_request_sets_format() {
+        let provider = OllamaProvider::with_defaults();
+        let body = provider
+            .build_request_body(&structured_request(ResponseFormat::Json))
+            .unwrap();
+        assert_eq!(body["format"], "json");
+
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let body = provider.build_request_body(&request).unwrap();
+        assert_eq!(body["format"], weather_schema());
+    }
+
+    fn sampling_request() -> ChatRequest {
+        ChatRequest {
+            stop: Some(vec!["END".to_string()]),
+            top_p: Some(0.9),
+            seed: Some(42),
+            ..sample_request()
+        }
+    }
+
+    #[test]
+    fn claude_build_request_maps_sampling_params() {
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let body = provider.build_request_body(&sampling_request(), None);
+        assert_eq!(body["stop_sequences"], serde_json::json!(["END"]));
+        assert_eq!(body["top_p"], 0.9);
+        assert!(body.get("seed").is_none());
+
+        let body = provider.build_request_body(&sample_request(), None);
+        assert!(body.get("stop_sequences").is_none());
+        assert!(body.get("top_p").is_none());
+    }
+
+    #[test]
+    fn openai_build_request_maps_sampling_params() {
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        });
+        let body = provider.build_request_body(&sampling_request(), None);
+        assert_eq!(body["stop"], serde_json::json!(["END"]));
+        assert_eq!(body["top_p"], 0.9);
+        assert_eq!(body["seed"], 42);
+
+        let body = provider.build_request_body(&sample_request(), None);
+        for key in ["stop", "top_p", "seed"] {
+            assert!(body.get(key).is_none(), "{key}");
+        }
+    }
+
+    #[test]
+    fn gemini_build_request_maps_sampling_params() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "test".to_string(),
+            model: "gemini-pro".to_string(),
+        });
+        let body = provider.build_request_body(&sampling_request(), None);
+        let gen_config = &body["generationConfig"];
+        assert_eq!(gen_config["stopSequences"], serde_json::json!(["END"]));
+        assert_eq!(gen_config["topP"], 0.9);
+
+        let body = provider.build_request_body(&sample_request(), None);
+        assert!(body["generationConfig"].get("stopSequences").is_none());
+        assert!(body["generationConfig"].get("topP").is_none());
+    }
+
+    #[test]
+    fn ollama_build_request_maps_sampling_params() {
+        let provider = OllamaProvider::with_defaults();
+        let body = provider.build_request_body(&sampling_request()).unwrap();
+        assert_eq!(body["options"]["stop"], serde_json::json!(["END"]));
+        assert_eq!(body["options"]["top_p"], 0.9);
+        assert_eq!(body["options"]["seed"], 42);
+
+        let body = provider.build_request_body(&sample_request()).unwrap();
+        for key in ["stop", "top_p", "seed"] {
+            assert!(body["options"].get(key).is_none(), "{key}");
+        }
+    }
+
+    #[test]
+    fn enforce_response_format_rejects_invalid_output() {
+        let request = structured_request(ResponseFormat::JsonSchema {
+            schema: weather_schema(),
+        });
+        let response = ChatResponse {
+            content: r#"{"city": "Oslo"}"#.to_string(),
+            tool_calls: vec![],
+            usage: None,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
+        };
+        let err = enforce_response_format(&request, response).unwrap_err();
+        let ProviderError::StructuredOutput(err) = err else {
+            panic!("expected a structured output error, got {err}");
+        };
+        assert!(err.message.contains("temp"), "{}", err.message);
+        assert_eq!(err.raw, r#"{"city": "Oslo"}"#);
+
+        // Without a response format, any text passes through.
+        let response = ChatResponse {
+            content: "not json".to_string(),
+            tool_calls: vec![],
+            usage: None,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
+        };
+        assert!(enforce_response_format(&sample_request(), response).is_ok());
+
+        // A tool-calling turn is not the final answer and is not validated.
+        let response = ChatResponse {
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                tool_name: "echo".to_string(),
+                arguments: serde_json::json!({ "input": "hi" }),
+                id: None,
+            }],
+            usage: None,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
+        };
+        let response = enforce_response_format(&request, response).unwrap();
+        assert_eq!(response.tool_calls.len(), 1);
+    }
+
+    // -----------------------------------------------------------------------
+    // Embedding tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn openai_embedding_request_and_response() {
+        let provider = OpenAIProvider::new(OpenAIConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "gpt-4o".to_string(),
+            base_url: None,
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        })
+        .with_embedding_model("text-embedding-3-large");
+        let texts = vec!["alpha".to_string(), "beta".to_string()];
+        let body = provider.build_embedding_body(&texts);
+        assert_eq!(body["model"], "text-embedding-3-large");
+        assert_eq!(body["input"], serde_json::json!(["alpha", "beta"]));
+
+        // Vectors come back ordered by `index`, whatever the array order.
+        let response = serde_json::json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 1, "embedding": [0.5, -0.5] },
+                { "object": "embedding", "index": 0, "embedding": [0.25, 1.0] }
+            ],
+            "model": "text-embedding-3-large"
+        });
+        let vectors = OpenAIProvider::parse_embedding_response(&response).unwrap();
+        assert_eq!(vectors, vec![vec![0.25, 1.0], vec![0.5, -0.5]]);
+    }
+
+    #[test]
+    fn openai_embedding_response_without_data_is_error() {
+        let response = serde_json::json!({ "object": "list" });
+        assert!(OpenAIProvider::parse_embedding_response(&response).is_err());
+    }
+
+    #[test]
+    fn gemini_embedding_request_and_response() {
+        let provider = GeminiProvider::new(GeminiConfig {
+            api_key: "test".to_string(),
+            model: "gemini-pro".to_string(),
+        });
+        assert_eq!(provider.embedding_model, GEMINI_EMBEDDING_MODEL);
+        let body = provider.build_embedding_body("alpha");
+        assert_eq!(body["model"], "models/text-embedding-004");
+        assert_eq!(body["content"]["parts"][0]["text"], "alpha");
+
+        let response = serde_json::json!({ "embedding": { "values": [0.1, 0.2, 0.3] } });
+        let vector = GeminiProvider::parse_embedding_response(&response).unwrap();
+        assert_eq!(vector, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn ollama_embedding_request_and_response() {
+        let provider = OllamaProvider::with_defaults();
+        let body = provider.build_embedding_body("alpha");
+        assert_eq!(body["model"], OLLAMA_EMBEDDING_MODEL);
+        assert_eq!(body["prompt"], "alpha");
+
+        let response = serde_json::json!({ "embedding": [1.5, -2.0] });
+        let vector = OllamaProvider::parse_embedding_response(&response).unwrap();
+        assert_eq!(vector, vec![1.5, -2.0]);
+
+        let bad = serde_json::json!({ "embedding": [1.5, "x"] });
+        let err = OllamaProvider::parse_embedding_response(&bad).unwrap_err();
+        assert!(err.to_string().contains("non-number"));
+    }
+
+    #[tokio::test]
+    async fn ollama_embed_calls_endpoint_per_text() {
+        use axum::{routing::post, Json, Router};
+        let app = Router::new().route(
+            "/api/embeddings",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let len = body["prompt"].as_str().unwrap_or_default().len();
+                Json(serde_json::json!({ "embedding": [len as f64, 0.0] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let provider = OllamaProvider::new(OllamaConfig {
+            model: "llama3".to_string(),
+            base_url: Some(base_url),
+        });
+
+        let texts = vec!["a".to_string(), "abc".to_string()];
+        let vectors = provider.embed(&texts).await.unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![3.0, 0.0]]);
+    }
+
+    #[tokio::test]
+    async fn embed_is_unsupported_by_default() {
+        let provider = ClaudeProvider::new(ClaudeConfig {
+            api_key: "test".to_string(),
+            api_keys: Vec::new(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: None,
+            prompt_caching: false,
+        });
+        let err = provider.embed(&["alpha".to_string()]).await.unwrap_err();
+        assert!(err.to_string().contains("does not support embeddings"));
+    }
+
+    // -----------------------------------------------------------------------
+    // Health probe tests
+    // -----------------------------------------------------------------------
+
+    /// Serve a fake OpenAI `/v1/models` that only accepts `sk-good`.
+    async fn fake_openai_models() -> String {
+        use axum::{http::HeaderMap, http::StatusCode, routing::get, Json, Router};
+        let app = Router::new().route(
+            "/v1/models",
+            get(|headers: HeaderMap| async move {
+                match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+                    Some("Bearer sk-good") => {
+                        Ok(Json(serde_json::json!({ "object": "list", "data": [] })))
+                    }
+                    _ => Err(StatusCode::UNAUTHORIZED),
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        base_url
+    }
+
+    #[tokio::test]
+    async fn openai_probe_lists_models() {
+        let base_url = fake_openai_models().await;
+        let probe = |api_key: &str| {
+            OpenAIProvider::new(OpenAIConfig {
+                api_key: api_key.to_string(),
+                api_keys: Vec::new(),
+                model: "gpt-4o".to_string(),
+                base_url: Some(base_url.clone()),
+                api_flavor: OpenAIApiFlavor::ChatCompletions,
+                reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+                reasoning_effort: None,
+            })
+        };
+
+        let up = probe("sk-good").health_probe().await;
+        assert!(up.up, "{:?}", up.error);
+        assert!(up.latency_ms >= 0.0);
+
+        let down = probe("sk-bad").health_probe().await;
+        assert!(!down.up);
+        assert!(down.error.unwrap().contains("401"));
+    }
+
+    // -----------------------------------------------------------------------
+    // API key pool tests
+    // -----------------------------------------------------------------------
+
+    /// OpenAI-compatible chat endpoint that records the key of every call
+    /// and answers 429 for `sk-limited`.
+    async fn fake_openai_chat(seen: Arc<std::sync::Mutex<Vec<String>>>) -> String {
+        use axum::{http::HeaderMap, http::StatusCode, routing::post, Json, Router};
+        let app = Router::new().route(
+            "/v1/chat/completions",
+            post(move |headers: HeaderMap| async move {
+                let key = headers
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .trim_start_matches("Bearer ")
+                    .to_string();
+                seen.lock().unwrap().push(key.clone());
+                if key == "sk-limited" {
+                    return (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        Json(serde_json::json!({ "error": { "message": "slow down" } })),
+                    );
+                }
+                (
+                    StatusCode::OK,
+                    Json(serde_json::json!({
+                        "choices": [{ "message": { "role": "assistant", "content": key } }]
+                    })),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        base_url
+    }
+
+    fn pooled_openai(base_url: String, keys: &[&str]) -> OpenAIProvider {
+        OpenAIProvider::new(OpenAIConfig {
+            api_key: keys[0].to_string(),
+            api_keys: keys[1..].iter().map(|k| k.to_string()).collect(),
+            model: "gpt-4o".to_string(),
+            base_url: Some(base_url),
+            api_flavor: OpenAIApiFlavor::ChatCompletions,
+            reasoning_models: OPENAI_REASONING_MODELS.to_string(),
+            reasoning_effort: None,
+        })
+    }
+
+    /// OpenAI-compatible chat endpoint that fails in a different way for
+    /// each key.
+    async fn fake_failing_openai() -> String {
+        use axum::{http::HeaderMap, http::StatusCode, response::IntoResponse, routing::post};
+        let app = axum::Router::new().route(
+            "/v1/chat/completions",
+            post(|headers: HeaderMap| async move {
+                let key = headers
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .trim_start_matches("Bearer ");
+                let error = |message: &str| {
+                    axum::Json(serde_json::json!({ "error": { "message": message } }))
+                };
+                match key {
+                    "sk-bad" => (StatusCode::UNAUTHORIZED, error("invalid key")).into_response(),
+                    "sk-limited" => (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        [("retry-after", "7")],
+                        error("slow down"),
+                    )
+                        .into_response(),
+                    "sk-broken" => (StatusCode::BAD_GATEWAY, "upstream exploded").into_response(),
+                    _ => (StatusCode::OK, "not json").into_response(),
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        base_url
+    }
+
+    #[tokio::test]
+    async fn http_failures_map_to_provider_errors() {
+        let base_url = fake_failing_openai().await;
+        let chat = |key: &str, base_url: String| {
+            let provider = pooled_openai(base_url, &[key]);
+            async move { provider.chat(sample_request()).await.unwrap_err() }
+        };
+
+        let err = chat("sk-bad", base_url.clone()).await;
+        assert!(
+            matches!(&err, ProviderError::Auth { status: 401, message, .. } if message == "invalid key"),
+            "{err}"
+        );
+
+        let err = chat("sk-limited", base_url.clone()).await;
+        assert!(
+            matches!(err, ProviderError::RateLimited { retry_after: Some(d), .. } if d.as_secs() == 7),
+            "{err}"
+        );
+
+        // A non-JSON error body is kept as the message.
+        let err = chat("sk-broken", base_url.clone()).await;
+        assert!(
+            matches!(&err, ProviderError::Api { status: 502, message, .. } if message == "upstream exploded"),
+            "{err}"
+        );
+
+        let err = chat("sk-good", base_url).await;
+        assert!(matches!(err, ProviderError::Decode { .. }), "{err}");
+
+        // Nothing listens on port 9 of localhost.
+        let err = chat("sk-good", "http://127.0.0.1:9".to_string()).await;
+        assert!(matches!(err, ProviderError::Transport { .. }), "{err}");
+        assert!(FallbackPolicy::default().is_retryable(&err));
+    }
+
+    #[tokio::test]
+    async fn successive_calls_cycle_api_keys() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = pooled_openai(
+            fake_openai_chat(seen.clone()).await,
+            &["sk-a", "sk-b", "sk-c"],
+        );
+        for _ in 0..4 {
+            provider.chat(sample_request()).await.unwrap();
+        }
+        assert_eq!(*seen.lock().unwrap(), ["sk-a", "sk-b", "sk-c", "sk-a"]);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_key_falls_through_to_next() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = pooled_openai(
+            fake_openai_chat(seen.clone()).await,
+            &["sk-limited", "sk-b"],
+        );
+        let response = provider.chat(sample_request()).await.unwrap();
+        assert_eq!(response.content, "sk-b");
+        assert_eq!(*seen.lock().unwrap(), ["sk-limited", "sk-b"]);
+
+        // With every key limited, the 429 surfaces as an error.
+        let provider = pooled_openai(fake_openai_chat(seen.clone()).await, &["sk-limited"]);
+        let err = provider.chat(sample_request()).await.unwrap_err();
+        assert!(err.to_string().contains("429"), "{err}");
+    }
+
+    #[test]
+    fn key_pool_treats_single_key_as_pool_of_one() {
+        let pool = KeyPool::new("sk-only", &["sk-only".to_string(), String::new()]);
+        assert_eq!(pool.keys, ["sk-only"]);
+        assert_eq!(pool.rotation().collect::<Vec<_>>(), ["sk-only"]);
+        assert_eq!(pool.rotation().collect::<Vec<_>>(), ["sk-only"]);
+    }
+
+    #[tokio::test]
+    async fn ollama_probe_lists_tags() {
+        let up = fake_ollama().await.health_probe().await;
+        assert!(up.up, "{:?}", up.error);
+
+        let down = OllamaProvider::new(OllamaConfig {
+            model: "llama3".to_string(),
+            base_url: Some("http://127.0.0.1:9".to_string()),
+        })
+        .health_probe()
+        .await;
+        assert!(!down.up);
+        assert!(down.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn default_probe_sends_a_chat() {
+        let up = StubProvider::default().health_probe().await;
+        assert!(up.up);
+        assert!(up.error.is_none());
+    }
+
+    // -----------------------------------------------------------------------
+    // Role mapping tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn claude_role_mapping() {
+        assert_eq!(claude_role(&ChatRole::User), "user");
+        assert_eq!(claude_role(&ChatRole::Assistant), "assistant");
+        assert_eq!(claude_role(&ChatRole::Tool), "user");
+        assert_eq!(claude_role(&ChatRole::System), "user");
+    }
+
+    #[test]
+    fn openai_role_mapping() {
+        assert_eq!(openai_role(&ChatRole::System), "system");
+        assert_eq!(openai_role(&ChatRole::User), "user");
+        assert_eq!(openai_role(&ChatRole::Assistant), "assistant");
+        assert_eq!(openai_role(&ChatRole::Tool), "tool");
+    }
+
+    #[test]
+    fn gemini_role_mapping() {
+        assert_eq!(gemini_role(&ChatRole::User), "user");
+        assert_eq!(gemini_role(&ChatRole::Assistant), "model");
+        assert_eq!(gemini_role(&ChatRole::Tool), "user");
+        assert_eq!(gemini_role(&ChatRole::System), "user");
+    }
+
+    #[test]
+    fn ollama_role_mapping() {
+        assert_eq!(ollama_role(&ChatRole::System), "system");
+        assert_eq!(ollama_role(&ChatRole::User), "user");
+        assert_eq!(ollama_role(&ChatRole::Assistant), "assistant");
+        assert_eq!(ollama_role(&ChatRole::Tool), "user");
+    }
+
+    // -----------------------------------------------------------------------
+    // Model fallback tests
+    // -----------------------------------------------------------------------
+
+    /// A [`StubProvider`] under its own name that fails every call with
+    /// `fail_status` when set, logging the models it was asked for.
+    struct FlakyStub {
+        name: &'static str,
+        fail_status: Option<u16>,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Provider for FlakyStub {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            StubProvider::default().capabilities()
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+            self.calls.lock().unwrap().push(request.model.clone());
+            if let Some(status) = self.fail_status {
+                return Err(ProviderError::from_status(
+                    self.name, status, None, "failed",
+                ));
+            }
+            StubProvider {
+                response_text: format!("from {}", self.name),
+            }
+            .chat(request)
+            .await
+        }
+
+        async fn chat_with_tools(
+            &self,
+            request: ChatRequest,
+            _tools: &[ToolSpec],
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(request).await
+        }
+    }
+
+    /// Registry with `primary` serving `model-a` and failing with
+    /// `primary_status`, and a working `secondary` serving `model-b`.
+    fn fallback_registry(primary_status: u16) -> (ProviderRegistry, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = ProviderRegistry::new();
+        for (name, fail_status) in [("primary", Some(primary_status)), ("secondary", None)] {
+            registry.register(Box::new(FlakyStub {
+                name,
+                fail_status,
+                calls: calls.clone(),
+            }));
+        }
+        registry.register_model("model-a", "primary");
+        registry.register_model("model-b", "secondary");
+        (registry, calls)
+    }
+
+    fn request_for(model: &str) -> ChatRequest {
+        ChatRequest {
+            model: model.to_string(),
+            ..sample_request()
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback_moves_past_an_overloaded_model() {
+        let (registry, calls) = fallback_registry(529);
+        let policy = FallbackPolicy::new(["model-b"]);
+        let response = registry
+            .chat_with_fallback(request_for("model-a"), Some(&policy))
+            .await
+            .unwrap();
+        assert_eq!(response.content, "from secondary");
+        assert_eq!(response.model.as_deref(), Some("model-b"));
+        assert_eq!(*calls.lock().unwrap(), ["model-a", "model-b"]);
+    }
+
+    #[tokio::test]
+    async fn fallback_stops_at_the_first_success() {
+        let (registry, calls) = fallback_registry(529);
+        let policy = FallbackPolicy::new(["model-a"]);
+        let response = registry
+            .chat_with_fallback(request_for("model-b"), Some(&policy))
+            .await
+            .unwrap();
+        assert_eq!(response.model.as_deref(), Some("model-b"));
+        assert_eq!(*calls.lock().unwrap(), ["model-b"]);
+    }
+
+    #[tokio::test]
+    async fn invalid_request_does_not_fall_back() {
+        let (registry, calls) = fallback_registry(400);
+        let policy = FallbackPolicy::new(["model-b"]);
+        let err = registry
+            .chat_with_fallback(request_for("model-a"), Some(&policy))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ProviderError::Api { status: 400, .. }),
+            "{err}"
+        );
+        assert_eq!(*calls.lock().unwrap(), ["model-a"]);
+    }
+
+    #[tokio::test]
+    async fn registry_policy_applies_when_the_call_gives_none() {
+        let (registry, calls) = fallback_registry(503);
+        let registry = registry.with_fallback(FallbackPolicy {
+            models: vec!["model-a".into(), "model-b".into()],
+            retry_statuses: vec![503],
+        });
+        let response = registry
+            .chat_with_fallback(request_for("model-a"), None)
+            .await
+            .unwrap();
+        assert_eq!(response.model.as_deref(), Some("model-b"));
+        // The request's own model is not tried twice.
+        assert_eq!(*calls.lock().unwrap(), ["model-a", "model-b"]);
+
+        // Without any policy, a retryable error is returned as is.
+        let (registry, calls) = fallback_registry(503);
+        let err = registry
+            .chat_with_fallback(request_for("model-a"), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("primary API error (503)"), "{err}");
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    // -----------------------------------------------------------------------
+    // from_env registry test
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn registry_from_env_always_includes_ollama() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        // Clear all provider keys to ensure only ollama is registered.
         std::env::remove_var("ANTHROPIC_API_KEY");
         std::env::remove_var("OPENAI_API_KEY");
         std::env::remove_var("GEMINI_API_KEY");
@@ -1786,6 +4929,54 @@ mod tests {
         assert_eq!(names.len(), 1);
     }
 
+    #[test]
+    fn registry_from_env_builds_indexed_instances() {
+        let _lock = ENV_MUTEX.lock().unwrap();
+        std::env::remove_var("ANTHROPIC_API_KEY");
+        std::env::remove_var("OPENAI_API_KEY");
+        std::env::remove_var("GEMINI_API_KEY");
+        let vars = [
+            ("YGN_PROVIDER_1_KIND", "openai"),
+            ("YGN_PROVIDER_1_NAME", "azure-gpt4"),
+            ("YGN_PROVIDER_1_BASE_URL", "https://azure.example.com"),
+            ("YGN_PROVIDER_1_API_KEY", "azure-key"),
+            ("YGN_PROVIDER_1_MODEL_PREFIXES", "gpt-4o, gpt-4.1"),
+            ("YGN_PROVIDER_2_KIND", "openai"),
+            ("YGN_PROVIDER_2_BASE_URL", "http://localhost:8000"),
+            ("YGN_PROVIDER_2_MODEL", "qwen3-32b"),
+            ("YGN_PROVIDER_2_MODEL_PREFIXES", "qwen"),
+            // No key anywhere: skipped.
+            ("YGN_PROVIDER_3_KIND", "gemini"),
+            ("YGN_PROVIDER_4_KIND", "teapot"),
+        ];
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+
+        let registry = ProviderRegistry::from_env();
+        for (key, _) in vars {
+            std::env::remove_var(key);
+        }
+
+        assert_eq!(registry.list(), ["ollama", "azure-gpt4", "openai-2"]);
+        assert_eq!(registry.kind("azure-gpt4"), Some("openai"));
+        assert_eq!(registry.kind("openai-2"), Some("openai"));
+        let route = |model: &str| {
+            let provider = registry.route(model).unwrap();
+            registry
+                .list()
+                .into_iter()
+                .find(|name| std::ptr::addr_eq(registry.get(name).unwrap(), provider))
+                .unwrap()
+        };
+        assert_eq!(route("gpt-4o"), "azure-gpt4");
+        assert_eq!(route("gpt-4.1-mini"), "azure-gpt4");
+        assert_eq!(route("qwen2.5-72b"), "openai-2");
+        assert_eq!(route("qwen3-32b"), "openai-2");
+        // Other OpenAI models go to the first instance of the kind.
+        assert_eq!(route("o3-mini"), "azure-gpt4");
+    }
+
     #[test]
     fn registry_from_env_with_all_keys() {
         let _lock = ENV_MUTEX.lock().unwrap();
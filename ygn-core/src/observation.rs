@@ -0,0 +1,470 @@
+//! uACP observation bus: nodes gossip lightweight telemetry.
+//!
+//! An [`ObservationPublisher`] periodically sends this node's
+//! [`NodeTelemetry`] as a uACP OBSERVE message, one UDP datagram per peer.
+//! An [`ObservationCollector`] on the receiving side keeps the last
+//! telemetry heard from each node, evicts entries that go stale, and copies
+//! fresh values into the node registry under `metadata.telemetry`.
+//! Frames that do not decode are counted and dropped.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+
+use crate::metrics::{Metrics, MetricsSummary};
+use crate::registry::{NodePatch, NodeRegistry};
+use crate::uacp::{UacpCodec, UacpMessage, UacpVerb};
+
+/// Largest datagram the collector reads; telemetry frames are far smaller.
+const MAX_DATAGRAM: usize = 8 * 1024;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Observation bus settings in the node config.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[serde(default)]
+pub struct ObservationConfig {
+    /// UDP `host:port` to collect telemetry from other nodes on. Nothing is
+    /// collected when unset.
+    pub listen: Option<String>,
+    /// UDP `host:port` addresses this node publishes its telemetry to.
+    pub peers: Vec<String>,
+    /// Seconds between published observations.
+    pub interval_secs: u64,
+    /// Seconds after which a node's last telemetry is forgotten.
+    pub max_staleness_secs: u64,
+}
+
+impl Default for ObservationConfig {
+    fn default() -> Self {
+        Self {
+            listen: None,
+            peers: Vec::new(),
+            interval_secs: 10,
+            max_staleness_secs: 60,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Telemetry
+// ---------------------------------------------------------------------------
+
+/// Telemetry carried in an OBSERVE payload, encoded as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeTelemetry {
+    pub node_id: String,
+    pub role: String,
+    /// Load counters from the node's [`Metrics`].
+    pub load: MetricsSummary,
+    /// Tool executions waiting or running on the node.
+    pub tool_queue_depth: u64,
+    /// Unix milliseconds when the telemetry was taken.
+    pub timestamp: u64,
+}
+
+impl NodeTelemetry {
+    /// Snapshot `metrics` for the node `node_id`.
+    pub fn from_metrics(node_id: &str, role: &str, metrics: &Metrics) -> Self {
+        let load = metrics.summary();
+        Self {
+            node_id: node_id.to_string(),
+            role: role.to_string(),
+            tool_queue_depth: load.tools_in_flight,
+            load,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        }
+    }
+
+    /// Wrap the telemetry in an OBSERVE message sent by its node.
+    pub fn to_message(&self) -> UacpMessage {
+        let payload = serde_json::to_vec(self).expect("telemetry serializes");
+        UacpMessage::observe(&self.node_id, &payload)
+    }
+
+    /// Read telemetry from an OBSERVE message. The payload must name the
+    /// message's sender as its node.
+    pub fn from_message(msg: &UacpMessage) -> anyhow::Result<Self> {
+        if msg.verb != UacpVerb::Observe {
+            anyhow::bail!("expected an OBSERVE message, got {:?}", msg.verb);
+        }
+        let telemetry: Self = serde_json::from_slice(&msg.payload)?;
+        if telemetry.node_id != msg.sender_id {
+            anyhow::bail!(
+                "telemetry for node {} sent by {}",
+                telemetry.node_id,
+                msg.sender_id
+            );
+        }
+        Ok(telemetry)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Background tasks
+// ---------------------------------------------------------------------------
+
+/// Handle to a running publisher or collector task.
+#[derive(Debug)]
+pub struct ObservationHandle {
+    stop: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl ObservationHandle {
+    /// Stop the task and wait for it to finish.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.join.await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Publisher
+// ---------------------------------------------------------------------------
+
+/// Sends this node's telemetry to a fixed set of peers.
+#[derive(Debug)]
+pub struct ObservationPublisher {
+    socket: UdpSocket,
+    peers: Vec<SocketAddr>,
+    node_id: String,
+    role: String,
+    metrics: Arc<Metrics>,
+}
+
+impl ObservationPublisher {
+    /// Bind an ephemeral UDP socket for publishing to `peers`.
+    pub async fn bind(
+        node_id: &str,
+        role: &str,
+        metrics: Arc<Metrics>,
+        peers: &[String],
+    ) -> anyhow::Result<Self> {
+        let mut addrs = Vec::with_capacity(peers.len());
+        for peer in peers {
+            let resolved = tokio::net::lookup_host(peer.as_str())
+                .await
+                .map_err(|e| anyhow::anyhow!("cannot resolve observation peer {peer}: {e}"))?;
+            addrs.extend(resolved.take(1));
+        }
+        let local = match addrs.first() {
+            Some(SocketAddr::V6(_)) => "[::]:0",
+            _ => "0.0.0.0:0",
+        };
+        let socket = UdpSocket::bind(local).await?;
+        Ok(Self {
+            socket,
+            peers: addrs,
+            node_id: node_id.to_string(),
+            role: role.to_string(),
+            metrics,
+        })
+    }
+
+    /// Send one observation to every peer. A peer that cannot be reached is
+    /// logged and skipped.
+    pub async fn publish_once(&self) -> NodeTelemetry {
+        let telemetry = NodeTelemetry::from_metrics(&self.node_id, &self.role, &self.metrics);
+        let frame = UacpCodec::encode(&telemetry.to_message());
+        for peer in &self.peers {
+            if let Err(e) = self.socket.send_to(&frame, peer).await {
+                tracing::debug!(%peer, error = %e, "observation not sent");
+            }
+        }
+        telemetry
+    }
+
+    /// Publish every `interval` until [`ObservationHandle::shutdown`].
+    pub fn spawn(self, interval: Duration) -> ObservationHandle {
+        let (stop, mut stopped) = tokio::sync::oneshot::channel();
+        let join = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = &mut stopped => break,
+                }
+                self.publish_once().await;
+            }
+        });
+        ObservationHandle { stop, join }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Collector
+// ---------------------------------------------------------------------------
+
+/// Last-known telemetry per node, fed by OBSERVE frames.
+#[derive(Default)]
+pub struct ObservationCollector {
+    entries: Mutex<HashMap<String, (NodeTelemetry, Instant)>>,
+    malformed: AtomicU64,
+    registry: Option<Arc<dyn NodeRegistry>>,
+}
+
+impl std::fmt::Debug for ObservationCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObservationCollector")
+            .field("nodes", &self.entries.lock().unwrap().len())
+            .field("malformed", &self.malformed_count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ObservationCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also merge each node's telemetry into its registry metadata as
+    /// `metadata.telemetry`. Telemetry from nodes the registry does not
+    /// know is kept here only.
+    pub fn with_registry(mut self, registry: Arc<dyn NodeRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Decode one frame and record its telemetry. Returns `false`, and
+    /// counts the frame as malformed, if it is not a valid OBSERVE message.
+    pub async fn ingest(&self, frame: &[u8]) -> bool {
+        let telemetry =
+            match UacpCodec::decode(frame).and_then(|msg| NodeTelemetry::from_message(&msg)) {
+                Ok(telemetry) => telemetry,
+                Err(e) => {
+                    self.malformed.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(error = %e, "dropping malformed observation");
+                    return false;
+                }
+            };
+        if let Some(registry) = &self.registry {
+            let patch = NodePatch {
+                capabilities: Vec::new(),
+                metadata: serde_json::json!({ "telemetry": &telemetry }),
+            };
+            if let Err(e) = registry.update(&telemetry.node_id, patch).await {
+                tracing::debug!(node_id = %telemetry.node_id, error = %e, "telemetry not applied to registry");
+            }
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(telemetry.node_id.clone(), (telemetry, Instant::now()));
+        true
+    }
+
+    /// Last telemetry received from `node_id`.
+    pub fn get(&self, node_id: &str) -> Option<NodeTelemetry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(node_id)
+            .map(|(telemetry, _)| telemetry.clone())
+    }
+
+    /// Last telemetry from every node, ordered by node ID.
+    pub fn snapshot(&self) -> Vec<NodeTelemetry> {
+        let mut all: Vec<NodeTelemetry> = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(telemetry, _)| telemetry.clone())
+            .collect();
+        all.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        all
+    }
+
+    /// Forget nodes not heard from within `max_staleness`. Returns how many
+    /// were removed.
+    pub fn evict_stale(&self, max_staleness: Duration) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, (_, received)| received.elapsed() <= max_staleness);
+        before - entries.len()
+    }
+
+    /// Frames dropped because they did not decode.
+    pub fn malformed_count(&self) -> u64 {
+        self.malformed.load(Ordering::Relaxed)
+    }
+
+    /// Receive frames on `socket` until [`ObservationHandle::shutdown`],
+    /// evicting stale entries as they age past `max_staleness`.
+    pub fn spawn(self: Arc<Self>, socket: UdpSocket, max_staleness: Duration) -> ObservationHandle {
+        let (stop, mut stopped) = tokio::sync::oneshot::channel();
+        let join = tokio::spawn(async move {
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            let mut ticker = tokio::time::interval(max_staleness);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    received = socket.recv_from(&mut buf) => match received {
+                        Ok((len, _)) => {
+                            self.ingest(&buf[..len]).await;
+                        }
+                        Err(e) => tracing::debug!(error = %e, "observation receive failed"),
+                    },
+                    _ = ticker.tick() => {
+                        self.evict_stale(max_staleness);
+                    }
+                    _ = &mut stopped => break,
+                }
+            }
+        });
+        ObservationHandle { stop, join }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{InMemoryRegistry, NodeInfo, NodeRole, TrustTier};
+
+    fn telemetry(node_id: &str) -> NodeTelemetry {
+        NodeTelemetry {
+            node_id: node_id.to_string(),
+            role: "edge".to_string(),
+            load: MetricsSummary {
+                http_requests: 7,
+                tool_executions: 3,
+                tool_failures: 1,
+                provider_failures: 0,
+                tools_in_flight: 2,
+            },
+            tool_queue_depth: 2,
+            timestamp: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn telemetry_round_trips_through_observe_frame() {
+        let original = telemetry("edge-1");
+        let frame = UacpCodec::encode(&original.to_message());
+        let msg = UacpCodec::decode(&frame).unwrap();
+        assert_eq!(msg.verb, UacpVerb::Observe);
+        assert_eq!(msg.sender_id, "edge-1");
+        assert_eq!(NodeTelemetry::from_message(&msg).unwrap(), original);
+    }
+
+    #[test]
+    fn telemetry_from_metrics_reports_in_flight_tools() {
+        let metrics = Metrics::new();
+        metrics.record_tool_execution("echo", false, Duration::from_millis(1));
+        let _running = metrics.tool_started();
+        let t = NodeTelemetry::from_metrics("core-1", "core", &metrics);
+        assert_eq!(t.tool_queue_depth, 1);
+        assert_eq!(t.load.tool_failures, 1);
+        assert!(t.timestamp > 0);
+    }
+
+    #[tokio::test]
+    async fn malformed_frames_are_counted_and_dropped() {
+        let collector = ObservationCollector::new();
+        assert!(!collector.ingest(b"garbage").await);
+        let not_json = UacpCodec::encode(&UacpMessage::observe("edge-1", b"{oops"));
+        assert!(!collector.ingest(&not_json).await);
+        let wrong_verb = UacpCodec::encode(&UacpMessage::tell("edge-1", b"{}"));
+        assert!(!collector.ingest(&wrong_verb).await);
+        let mut spoofed = telemetry("edge-1").to_message();
+        spoofed.sender_id = "edge-2".to_string();
+        assert!(!collector.ingest(&UacpCodec::encode(&spoofed)).await);
+
+        assert_eq!(collector.malformed_count(), 4);
+        assert!(collector.snapshot().is_empty());
+
+        assert!(
+            collector
+                .ingest(&UacpCodec::encode(&telemetry("edge-1").to_message()))
+                .await
+        );
+        assert_eq!(collector.snapshot().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stale_entries_are_evicted() {
+        let collector = ObservationCollector::new();
+        let frame = UacpCodec::encode(&telemetry("edge-1").to_message());
+        collector.ingest(&frame).await;
+        assert_eq!(collector.evict_stale(Duration::from_secs(60)), 0);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(collector.evict_stale(Duration::from_millis(10)), 1);
+        assert!(collector.get("edge-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn telemetry_is_merged_into_registry_metadata() {
+        let registry = Arc::new(InMemoryRegistry::new());
+        registry
+            .register(NodeInfo {
+                node_id: "edge-1".to_string(),
+                role: NodeRole::Edge,
+                endpoints: vec![],
+                trust_tier: TrustTier::Trusted,
+                capabilities: vec![],
+                last_seen: chrono::Utc::now(),
+                metadata: serde_json::json!({ "zone": "lab" }),
+            })
+            .await
+            .unwrap();
+        let collector = ObservationCollector::new().with_registry(registry.clone());
+
+        for node_id in ["edge-1", "unknown"] {
+            let frame = UacpCodec::encode(&telemetry(node_id).to_message());
+            assert!(collector.ingest(&frame).await);
+        }
+
+        let node = registry.get("edge-1").await.unwrap().unwrap();
+        assert_eq!(node.metadata["zone"], "lab");
+        assert_eq!(node.metadata["telemetry"]["tool_queue_depth"], 2);
+        assert_eq!(node.metadata["telemetry"]["load"]["http_requests"], 7);
+        assert!(collector.get("unknown").is_some());
+    }
+
+    #[tokio::test]
+    async fn publisher_reaches_collector_over_udp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap().to_string();
+        let collector = Arc::new(ObservationCollector::new());
+        let collecting = collector.clone().spawn(socket, Duration::from_secs(60));
+
+        let metrics = Arc::new(Metrics::new());
+        metrics.record_tool_execution("echo", true, Duration::from_millis(1));
+        metrics.record_tool_execution("echo", true, Duration::from_millis(1));
+        let _running = metrics.tool_started();
+        let publisher = ObservationPublisher::bind("core-1", "core", metrics.clone(), &[addr])
+            .await
+            .unwrap();
+        let publishing = publisher.spawn(Duration::from_millis(20));
+
+        let mut seen = None;
+        for _ in 0..100 {
+            seen = collector.get("core-1");
+            if seen.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        publishing.shutdown().await;
+        collecting.shutdown().await;
+
+        let seen = seen.expect("collector received telemetry");
+        assert_eq!(seen.role, "core");
+        assert_eq!(seen.load.tool_executions, 2);
+        assert_eq!(seen.tool_queue_depth, 1);
+        assert_eq!(collector.malformed_count(), 0);
+    }
+}
@@ -2,20 +2,29 @@
 //!
 //! Evaluates tool-call requests against security rules, sandbox restrictions,
 //! and explicit allow/deny lists.  Produces a [`PolicyDecision`] that the MCP
-//! layer uses to gate execution.
+//! layer uses to gate execution.  Every decision carries a unique id, which
+//! audit entries record, and a trace of the rules consulted.  Rule outcomes
+//! can be cached per tool, arguments and policy version; an engine the
+//! caller owns can be [reloaded](PolicyEngine::reload) into a new version.
 
+use anyhow::Context;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::sandbox::SandboxChecker;
+use crate::registry::TrustTier;
+use crate::sandbox::{ProcessSandbox, SandboxChecker, SandboxProfile};
 
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
 
 /// The action dictated by the policy engine.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
 pub enum PolicyAction {
     /// Execution may proceed.
     Allow,
@@ -23,10 +32,14 @@ pub enum PolicyAction {
     Deny,
     /// Execution requires explicit user approval before proceeding.
     RequireApproval,
+    /// Execution is refused because the tool exceeded its call rate limit.
+    RateLimited,
 }
 
-/// Risk classification for a tool call.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Risk classification for a tool call, ordered from `Low` to `Critical`.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema,
+)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -43,6 +56,466 @@ pub struct PolicyDecision {
     pub reason: String,
     /// Risk classification for this call.
     pub risk_level: RiskLevel,
+    /// Unique id of this evaluation, for correlating audit entries.
+    #[serde(default)]
+    pub decision_id: String,
+    /// The rules consulted, in order, and what each found.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<String>,
+    /// True when the rule outcome came from the decision cache.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+impl PolicyDecision {
+    fn new(action: PolicyAction, reason: String, risk_level: RiskLevel) -> Self {
+        Self {
+            action,
+            reason,
+            risk_level,
+            decision_id: String::new(),
+            trace: Vec::new(),
+            cached: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NamePattern
+// ---------------------------------------------------------------------------
+
+/// A tool-name entry in a deny or approval list.
+///
+/// Entries written as `/.../` are regular expressions, entries containing
+/// `*` are globs (`*` matches any run of characters), and everything else
+/// must match the tool name exactly.
+#[derive(Debug, Clone)]
+pub enum NamePattern {
+    Exact(String),
+    Pattern { source: String, regex: Regex },
+}
+
+impl NamePattern {
+    /// Compile a list entry.
+    pub fn parse(entry: &str) -> anyhow::Result<Self> {
+        let regex = if let Some(body) = entry
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+            .filter(|body| !body.is_empty())
+        {
+            Regex::new(body)
+        } else if entry.contains('*') {
+            let glob = entry
+                .split('*')
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(".*");
+            Regex::new(&format!("^{glob}$"))
+        } else {
+            return Ok(Self::Exact(entry.to_string()));
+        };
+        let regex = regex.with_context(|| format!("invalid tool pattern '{entry}'"))?;
+        Ok(Self::Pattern {
+            source: entry.to_string(),
+            regex,
+        })
+    }
+
+    /// Compile `entry`, falling back to an exact match (with a warning) if
+    /// it is not a valid pattern.
+    fn parse_or_exact(entry: String) -> Self {
+        Self::parse(&entry).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "treating tool pattern as an exact name");
+            Self::Exact(entry)
+        })
+    }
+
+    pub fn matches(&self, tool_name: &str) -> bool {
+        match self {
+            Self::Exact(name) => name == tool_name,
+            Self::Pattern { regex, .. } => regex.is_match(tool_name),
+        }
+    }
+
+    /// The entry as written in the list.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Exact(name) => name,
+            Self::Pattern { source, .. } => source,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ArgumentRule
+// ---------------------------------------------------------------------------
+
+/// Comparison applied by an [`ArgumentCondition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgumentOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// String contains a substring, or array contains an element.
+    Contains,
+    StartsWith,
+    NotStartsWith,
+}
+
+/// A single test against a value inside the call arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ArgumentCondition {
+    /// Dotted path into the arguments, e.g. `action.type` or `paths.0`.
+    pub json_path: String,
+    pub op: ArgumentOp,
+    pub value: Value,
+}
+
+impl ArgumentCondition {
+    /// Whether `args` satisfies the condition.  A path that does not exist
+    /// never matches, whatever the operator.
+    pub fn matches(&self, args: &Value) -> bool {
+        let pointer: String = self
+            .json_path
+            .trim_start_matches('$')
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("/{}", s.replace('~', "~0").replace('/', "~1")))
+            .collect();
+        let Some(actual) = args.pointer(&pointer) else {
+            return false;
+        };
+        let expected = &self.value;
+        let ordering = match (actual.as_f64(), expected.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        };
+        match self.op {
+            ArgumentOp::Eq => ordering.map_or(actual == expected, |o| o.is_eq()),
+            ArgumentOp::Ne => ordering.map_or(actual != expected, |o| o.is_ne()),
+            ArgumentOp::Gt => ordering.is_some_and(|o| o.is_gt()),
+            ArgumentOp::Gte => ordering.is_some_and(|o| o.is_ge()),
+            ArgumentOp::Lt => ordering.is_some_and(|o| o.is_lt()),
+            ArgumentOp::Lte => ordering.is_some_and(|o| o.is_le()),
+            ArgumentOp::Contains => match (actual, expected.as_str()) {
+                (Value::String(s), Some(needle)) => s.contains(needle),
+                (Value::Array(items), _) => items.contains(expected),
+                _ => false,
+            },
+            ArgumentOp::StartsWith | ArgumentOp::NotStartsWith => {
+                match (actual.as_str(), expected.as_str()) {
+                    (Some(s), Some(prefix)) => {
+                        s.starts_with(prefix) == (self.op == ArgumentOp::StartsWith)
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// A rule that inspects the arguments of calls to matching tools.
+///
+/// The rule fires when its condition and every condition in `and` hold.
+/// Argument rules can only make a decision stricter, never looser.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ArgumentRule {
+    /// Tool name, glob or `/regex/` (see [`NamePattern`]).
+    pub tool: String,
+    pub json_path: String,
+    pub op: ArgumentOp,
+    pub value: Value,
+    /// Action taken when the rule fires.
+    pub action: PolicyAction,
+    /// Further conditions that must also hold.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub and: Vec<ArgumentCondition>,
+}
+
+impl ArgumentRule {
+    fn conditions(&self) -> impl Iterator<Item = ArgumentCondition> + '_ {
+        std::iter::once(ArgumentCondition {
+            json_path: self.json_path.clone(),
+            op: self.op,
+            value: self.value.clone(),
+        })
+        .chain(self.and.iter().cloned())
+    }
+
+    fn describe(&self) -> String {
+        self.conditions()
+            .map(|c| format!("{} {:?} {}", c.json_path, c.op, c.value))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PolicyConfig
+// ---------------------------------------------------------------------------
+
+/// Per-tool override that replaces the built-in heuristics for one tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolOverride {
+    /// Action to take whenever this tool is called.
+    pub action: PolicyAction,
+    /// Risk level reported with the decision.  When omitted, `Deny` maps to
+    /// `Critical`, `RequireApproval` to `High` and `Allow` to the default risk.
+    #[serde(default)]
+    pub risk_level: Option<RiskLevel>,
+}
+
+/// Cap on how often one tool may be called.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolRateLimit {
+    /// Calls allowed within one window.
+    pub max_calls: u32,
+    /// Length of the sliding window, in seconds.
+    #[schemars(range(min = 1))]
+    pub window_secs: u64,
+}
+
+/// Declarative policy definition, loaded from a JSON or TOML file by
+/// [`PolicyEngine::from_file`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+#[schemars(title = "YGN Policy Configuration")]
+pub struct PolicyConfig {
+    /// Tool names that are always blocked.  Entries may be globs (`fs_*`)
+    /// or `/regex/` patterns, see [`NamePattern`].
+    pub denied_tools: Vec<String>,
+    /// Tool names that require explicit user approval, in the same syntax.
+    pub approval_required: Vec<String>,
+    /// Risk level for tools no other rule classifies.
+    pub default_risk: RiskLevel,
+    /// Least strict action for tools that are not listed or overridden.
+    /// Heuristics may still escalate it (e.g. shell tools need approval).
+    pub default_action: PolicyAction,
+    /// Per-tool overrides, keyed by tool name.
+    pub tool_overrides: BTreeMap<String, ToolOverride>,
+    /// Rules inspecting call arguments; see [`ArgumentRule`].
+    pub argument_rules: Vec<ArgumentRule>,
+    /// Per-tool call rate limits, keyed by tool name.
+    pub rate_limits: BTreeMap<String, ToolRateLimit>,
+    /// Maximum wall-clock time a tool is allowed to run, in seconds.
+    pub max_execution_time_secs: u64,
+    /// Profile of the process sandbox the engine checks access against.
+    pub sandbox_profile: SandboxProfile,
+    /// Programs the sandbox lets tools run.  Empty allows any program, but
+    /// then `tools/register` is refused.
+    pub allowed_commands: Vec<String>,
+    /// Rule outcomes to keep in the engine's decision cache.  0 disables
+    /// caching.
+    pub decision_cache_size: usize,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            denied_tools: Vec::new(),
+            approval_required: Vec::new(),
+            default_risk: RiskLevel::Low,
+            default_action: PolicyAction::Allow,
+            tool_overrides: BTreeMap::new(),
+            argument_rules: Vec::new(),
+            rate_limits: BTreeMap::new(),
+            max_execution_time_secs: 30,
+            sandbox_profile: SandboxProfile::Net,
+            allowed_commands: Vec::new(),
+            decision_cache_size: 0,
+        }
+    }
+}
+
+impl PolicyConfig {
+    /// Default policy for callers at the given trust tier.
+    ///
+    /// Trusted callers get the permissive defaults.  Untrusted callers need
+    /// approval for every tool call and run without network access.
+    pub fn for_trust_tier(tier: &TrustTier) -> Self {
+        Self::default().restricted_to(tier)
+    }
+
+    /// This policy, tightened for callers at the given trust tier.
+    ///
+    /// Trusted callers get the policy unchanged.  For untrusted callers,
+    /// every tool call the policy would allow needs approval instead, the
+    /// default risk is at least `Medium`, and a networked sandbox loses its
+    /// network access.  Nothing the policy denies or limits is relaxed.
+    pub fn restricted_to(mut self, tier: &TrustTier) -> Self {
+        if *tier == TrustTier::Trusted {
+            return self;
+        }
+        self.default_risk = self.default_risk.max(RiskLevel::Medium);
+        if strictness(&self.default_action) < strictness(&PolicyAction::RequireApproval) {
+            self.default_action = PolicyAction::RequireApproval;
+        }
+        for tool_override in self.tool_overrides.values_mut() {
+            if tool_override.action == PolicyAction::Allow {
+                tool_override.action = PolicyAction::RequireApproval;
+            }
+        }
+        if self.sandbox_profile == SandboxProfile::Net {
+            self.sandbox_profile = SandboxProfile::NoNet;
+        }
+        self
+    }
+
+    /// Parse a policy file.  Files ending in `.toml` are read as TOML,
+    /// everything else as JSON.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("reading policy file {}", path.display()))?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let config = if is_toml {
+            toml::from_str(&raw).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&raw).map_err(anyhow::Error::from)
+        };
+        let config: Self =
+            config.with_context(|| format!("parsing policy file {}", path.display()))?;
+        let rule_tools = config.argument_rules.iter().map(|r| &r.tool);
+        for entry in config
+            .denied_tools
+            .iter()
+            .chain(&config.approval_required)
+            .chain(rule_tools)
+        {
+            NamePattern::parse(entry).with_context(|| format!("in {}", path.display()))?;
+        }
+        Ok(config)
+    }
+
+    /// Return the JSON Schema for the policy file format, generated from
+    /// the structs so it cannot fall behind them.
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&schemars::schema_for!(PolicyConfig)).unwrap()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DecisionCache
+// ---------------------------------------------------------------------------
+
+/// Tool name, serialized call arguments and policy version.  The arguments
+/// are kept whole, so a hit is only ever for exactly the same call.
+type CacheKey = (String, String, u64);
+
+/// A cached decision, linked into the cache's recency list.
+#[derive(Debug)]
+struct CacheNode {
+    key: CacheKey,
+    decision: PolicyDecision,
+    /// Less recently used neighbour.
+    prev: Option<usize>,
+    /// More recently used neighbour.
+    next: Option<usize>,
+}
+
+/// Least-recently-used cache of rule outcomes, before rate limits.
+///
+/// Nodes live in a slab and form a doubly linked list from least to most
+/// recently used, so lookups, inserts and evictions are all O(1).
+#[derive(Debug)]
+struct DecisionCache {
+    capacity: usize,
+    index: HashMap<CacheKey, usize>,
+    nodes: Vec<CacheNode>,
+    /// Least recently used node.
+    head: Option<usize>,
+    /// Most recently used node.
+    tail: Option<usize>,
+}
+
+impl DecisionCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            index: HashMap::new(),
+            nodes: Vec::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<PolicyDecision> {
+        let slot = *self.index.get(key)?;
+        self.touch(slot);
+        Some(self.nodes[slot].decision.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, decision: PolicyDecision) {
+        if let Some(&slot) = self.index.get(&key) {
+            self.nodes[slot].decision = decision;
+            self.touch(slot);
+            return;
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        let slot = if self.nodes.len() < self.capacity {
+            self.nodes.push(CacheNode {
+                key: key.clone(),
+                decision,
+                prev: None,
+                next: None,
+            });
+            self.nodes.len() - 1
+        } else {
+            // Full: reuse the least recently used node.
+            let slot = self.head.expect("a full cache has a head");
+            self.unlink(slot);
+            let node = &mut self.nodes[slot];
+            self.index.remove(&node.key);
+            node.key = key.clone();
+            node.decision = decision;
+            slot
+        };
+        self.index.insert(key, slot);
+        self.push_back(slot);
+    }
+
+    /// Mark `slot` as the most recently used.
+    fn touch(&mut self, slot: usize) {
+        if self.tail != Some(slot) {
+            self.unlink(slot);
+            self.push_back(slot);
+        }
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.nodes[slot].prev, self.nodes[slot].next);
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+        self.nodes[slot].prev = None;
+        self.nodes[slot].next = None;
+    }
+
+    fn push_back(&mut self, slot: usize) {
+        self.nodes[slot].prev = self.tail;
+        self.nodes[slot].next = None;
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(slot),
+            None => self.head = Some(slot),
+        }
+        self.tail = Some(slot);
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -53,16 +526,35 @@ pub struct PolicyDecision {
 pub struct PolicyEngine {
     sandbox: Box<dyn SandboxChecker>,
     /// Tool names that require explicit user approval before execution.
-    approval_required: Vec<String>,
+    approval_required: Vec<NamePattern>,
     /// Tool names that are always blocked.
-    denied_tools: Vec<String>,
+    denied_tools: Vec<NamePattern>,
+    /// Per-tool overrides that take precedence over the heuristics.
+    tool_overrides: BTreeMap<String, ToolOverride>,
+    /// Risk level for tools no other rule classifies.
+    default_risk: RiskLevel,
+    /// Least strict action for tools no list or override covers.
+    default_action: PolicyAction,
+    /// Argument rules with their compiled tool patterns.
+    argument_rules: Vec<(NamePattern, ArgumentRule)>,
+    /// Per-tool call rate limits.
+    rate_limits: BTreeMap<String, ToolRateLimit>,
+    /// Timestamps of recent allowed calls for rate-limited tools.
+    call_history: Mutex<HashMap<String, VecDeque<Instant>>>,
     /// Maximum wall-clock time a tool is allowed to run.
     #[allow(dead_code)]
     max_execution_time: Duration,
+    /// Bumped by every [`reload`](Self::reload).
+    version: u64,
+    /// Cached rule outcomes, when enabled.
+    decision_cache: Option<Mutex<DecisionCache>>,
 }
 
 impl PolicyEngine {
     /// Create a new policy engine.
+    ///
+    /// List entries are compiled as [`NamePattern`]s; invalid patterns fall
+    /// back to exact names.
     pub fn new(
         sandbox: Box<dyn SandboxChecker>,
         approval_required: Vec<String>,
@@ -71,10 +563,89 @@ impl PolicyEngine {
     ) -> Self {
         Self {
             sandbox,
-            approval_required,
-            denied_tools,
+            approval_required: compile(approval_required),
+            denied_tools: compile(denied_tools),
+            tool_overrides: BTreeMap::new(),
+            default_risk: RiskLevel::Low,
+            default_action: PolicyAction::Allow,
+            argument_rules: Vec::new(),
+            rate_limits: BTreeMap::new(),
+            call_history: Mutex::new(HashMap::new()),
             max_execution_time,
+            version: 0,
+            decision_cache: None,
+        }
+    }
+
+    /// Build an engine from a [`PolicyConfig`], checking access against a
+    /// [`ProcessSandbox`] with the configured profile.
+    pub fn from_config(config: PolicyConfig) -> Self {
+        let mut sandbox = ProcessSandbox::new(config.sandbox_profile);
+        for command in config.allowed_commands {
+            sandbox.allow_command(command);
         }
+        Self {
+            sandbox: Box::new(sandbox),
+            approval_required: compile(config.approval_required),
+            denied_tools: compile(config.denied_tools),
+            tool_overrides: config.tool_overrides,
+            default_risk: config.default_risk,
+            default_action: config.default_action,
+            argument_rules: compile_rules(config.argument_rules),
+            rate_limits: config.rate_limits,
+            call_history: Mutex::new(HashMap::new()),
+            max_execution_time: Duration::from_secs(config.max_execution_time_secs),
+            version: 0,
+            decision_cache: (config.decision_cache_size > 0)
+                .then(|| Mutex::new(DecisionCache::new(config.decision_cache_size))),
+        }
+    }
+
+    /// Cache up to `capacity` rule outcomes, keyed by tool name, arguments
+    /// and policy version.  Rate limits are still checked on every call.
+    pub fn with_decision_cache(mut self, capacity: usize) -> Self {
+        self.decision_cache = (capacity > 0).then(|| Mutex::new(DecisionCache::new(capacity)));
+        self
+    }
+
+    /// Replace the rules with `config`, starting a new policy version with
+    /// an empty decision cache sized by `config.decision_cache_size`.
+    /// Rate-limit history carries over.
+    pub fn reload(&mut self, config: PolicyConfig) {
+        let mut fresh = Self::from_config(config);
+        fresh.version = self.version + 1;
+        fresh.call_history = std::mem::take(&mut self.call_history);
+        *self = fresh;
+    }
+
+    /// [`reload`](Self::reload) from a JSON or TOML policy file.  The
+    /// current rules stay in place if the file cannot be loaded.
+    pub fn reload_from_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let config = PolicyConfig::load(path.as_ref())?;
+        self.reload(config);
+        Ok(())
+    }
+
+    /// Policy version, starting at 0 and bumped by every reload.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Add argument rules to the engine.
+    pub fn with_argument_rules(mut self, rules: Vec<ArgumentRule>) -> Self {
+        self.argument_rules.extend(compile_rules(rules));
+        self
+    }
+
+    /// Limit how often `tool` may be called.
+    pub fn with_rate_limit(mut self, tool: impl Into<String>, limit: ToolRateLimit) -> Self {
+        self.rate_limits.insert(tool.into(), limit);
+        self
+    }
+
+    /// Load a [`PolicyConfig`] from a JSON or TOML file and build the engine.
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        PolicyConfig::load(path.as_ref()).map(Self::from_config)
     }
 
     /// Evaluate a tool-call request and produce a [`PolicyDecision`].
@@ -82,63 +653,218 @@ impl PolicyEngine {
     /// Rules (evaluated in order):
     ///
     /// 1. If the tool is on the **denied** list -> `Deny` / `Critical`.
-    /// 2. If the tool is on the **approval-required** list -> `RequireApproval` / `High`.
-    /// 3. If the tool name matches known shell/command patterns -> `RequireApproval` / `High`.
-    /// 4. If the tool involves file writes -> `Allow` / `Medium`
+    /// 2. If the tool has a **per-tool override** -> the override's action.
+    /// 3. If the tool is on the **approval-required** list -> `RequireApproval` / `High`.
+    /// 4. If the tool name matches known shell/command patterns -> `RequireApproval` / `High`.
+    /// 5. If the tool involves file writes -> `Allow` / `Medium`
     ///    (sandbox may still deny if outside allowed paths).
-    /// 5. Everything else -> `Allow` / `Low`.
+    /// 6. Everything else -> `Allow` at the default risk (`Low` unless configured).
+    ///
+    /// Rules 4-6 never produce an action less strict than the configured
+    /// default action.
+    ///
+    /// Finally, every [`ArgumentRule`] for the tool is checked against
+    /// `args`; if the strictest one that fires is stricter than the decision
+    /// above, it replaces it.
+    ///
+    /// A call that ends up allowed counts against the tool's rate limit, if
+    /// it has one.  Once the limit is reached within the window the call is
+    /// `RateLimited` instead.  Calls that need approval are counted once
+    /// approved, through [`count_approved_call`](Self::count_approved_call).
+    ///
+    /// Each call gets a fresh `decision_id`, even when the rule outcome
+    /// comes from the decision cache.
     pub fn evaluate(&self, tool_name: &str, args: &Value) -> PolicyDecision {
+        let mut decision = self.cached_rules(tool_name, args);
+        if decision.action == PolicyAction::Allow {
+            if let Some(mut limited) = self.check_rate_limit(tool_name, Instant::now()) {
+                limited.trace = std::mem::take(&mut decision.trace);
+                limited.trace.push("rate limit: exceeded".to_string());
+                limited.cached = decision.cached;
+                decision = limited;
+            } else if self.rate_limits.contains_key(tool_name) {
+                decision.trace.push("rate limit: within limit".to_string());
+            }
+        }
+        decision.decision_id = uuid::Uuid::new_v4().to_string();
+        decision
+    }
+
+    /// Count a call that needed approval, and got it, against the tool's
+    /// rate limit, as [`evaluate`](Self::evaluate) does for allowed calls.
+    /// Returns the `RateLimited` decision when the limit is reached.
+    pub fn count_approved_call(&self, tool_name: &str) -> Option<PolicyDecision> {
+        let mut limited = self.check_rate_limit(tool_name, Instant::now())?;
+        limited.trace.push("rate limit: exceeded".to_string());
+        limited.decision_id = uuid::Uuid::new_v4().to_string();
+        Some(limited)
+    }
+
+    /// What [`evaluate`](Self::evaluate) would decide from the name and
+    /// argument rules, without counting against any rate limit.
+    pub fn preview(&self, tool_name: &str, args: &Value) -> PolicyDecision {
+        let mut decision = self.cached_rules(tool_name, args);
+        decision.decision_id = uuid::Uuid::new_v4().to_string();
+        decision
+    }
+
+    /// [`evaluate_rules`](Self::evaluate_rules), through the decision
+    /// cache when one is enabled.
+    fn cached_rules(&self, tool_name: &str, args: &Value) -> PolicyDecision {
+        let Some(cache) = &self.decision_cache else {
+            return self.evaluate_rules(tool_name, args);
+        };
+        let key = (tool_name.to_string(), args.to_string(), self.version);
+        if let Some(mut hit) = cache.lock().unwrap().get(&key) {
+            hit.cached = true;
+            return hit;
+        }
+        let decision = self.evaluate_rules(tool_name, args);
+        cache.lock().unwrap().insert(key, decision.clone());
+        decision
+    }
+
+    /// The decision from the name and argument rules, without rate limits.
+    fn evaluate_rules(&self, tool_name: &str, args: &Value) -> PolicyDecision {
+        let mut trace = Vec::new();
+        let decision = self.evaluate_name(tool_name, args, &mut trace);
+        let fired = self
+            .argument_rules
+            .iter()
+            .enumerate()
+            .filter(|(_, (pattern, rule))| {
+                pattern.matches(tool_name) && rule.conditions().all(|c| c.matches(args))
+            })
+            .max_by_key(|(i, (_, rule))| (strictness(&rule.action), std::cmp::Reverse(*i)));
+        let mut decision = match fired {
+            Some((i, (_, rule))) if strictness(&rule.action) > strictness(&decision.action) => {
+                trace.push(format!(
+                    "argument rule #{} ({}): {:?}",
+                    i + 1,
+                    rule.describe(),
+                    rule.action
+                ));
+                PolicyDecision::new(
+                    rule.action.clone(),
+                    format!(
+                        "Tool '{}': argument rule #{} ({}) requires {:?}",
+                        tool_name,
+                        i + 1,
+                        rule.describe(),
+                        rule.action
+                    ),
+                    match rule.action {
+                        PolicyAction::Deny => RiskLevel::Critical,
+                        _ => RiskLevel::High,
+                    },
+                )
+            }
+            Some((i, _)) => {
+                trace.push(format!("argument rule #{}: not stricter", i + 1));
+                decision
+            }
+            None => {
+                if !self.argument_rules.is_empty() {
+                    trace.push("argument rules: none fired".to_string());
+                }
+                decision
+            }
+        };
+        decision.trace = trace;
+        decision
+    }
+
+    /// The decision from the name-based rules alone, noting each rule
+    /// consulted in `trace`.
+    fn evaluate_name(
+        &self,
+        tool_name: &str,
+        args: &Value,
+        trace: &mut Vec<String>,
+    ) -> PolicyDecision {
         // --- 1. Denied tools --------------------------------------------------
         if self.is_denied(tool_name) {
-            return PolicyDecision {
-                action: PolicyAction::Deny,
-                reason: format!("Tool '{}' is on the deny list", tool_name),
-                risk_level: RiskLevel::Critical,
-            };
+            trace.push("denied_tools: matched".to_string());
+            return PolicyDecision::new(
+                PolicyAction::Deny,
+                format!("Tool '{}' is on the deny list", tool_name),
+                RiskLevel::Critical,
+            );
         }
+        trace.push("denied_tools: no match".to_string());
 
-        // --- 2. Explicit approval list ----------------------------------------
+        // --- 2. Per-tool overrides --------------------------------------------
+        if let Some(rule) = self.tool_overrides.get(tool_name) {
+            trace.push(format!("tool_overrides: {:?}", rule.action));
+            let risk_level = rule.risk_level.clone().unwrap_or(match rule.action {
+                PolicyAction::Deny => RiskLevel::Critical,
+                PolicyAction::RequireApproval | PolicyAction::RateLimited => RiskLevel::High,
+                PolicyAction::Allow => self.default_risk.clone(),
+            });
+            return PolicyDecision::new(
+                rule.action.clone(),
+                format!("Tool '{}' has a policy override", tool_name),
+                risk_level,
+            );
+        }
+
+        // --- 3. Explicit approval list ----------------------------------------
         if self.requires_approval(tool_name) {
-            return PolicyDecision {
-                action: PolicyAction::RequireApproval,
-                reason: format!(
+            trace.push("approval_required: matched".to_string());
+            return PolicyDecision::new(
+                PolicyAction::RequireApproval,
+                format!(
                     "Tool '{}' requires explicit approval before execution",
                     tool_name
                 ),
-                risk_level: RiskLevel::High,
-            };
+                RiskLevel::High,
+            );
         }
 
-        // --- 3. Shell / command heuristics ------------------------------------
+        // --- 4. Shell / command heuristics ------------------------------------
         if Self::is_shell_tool(tool_name) {
-            return PolicyDecision {
-                action: PolicyAction::RequireApproval,
-                reason: format!(
+            trace.push("shell heuristic: matched".to_string());
+            return PolicyDecision::new(
+                self.at_least(PolicyAction::RequireApproval),
+                format!(
                     "Tool '{}' is a shell/command tool — user approval required",
                     tool_name
                 ),
-                risk_level: RiskLevel::High,
-            };
+                RiskLevel::High,
+            );
         }
 
-        // --- 4. File-write heuristics -----------------------------------------
+        // --- 5. File-write heuristics -----------------------------------------
         if Self::is_file_write_tool(tool_name, args) {
-            return PolicyDecision {
-                action: PolicyAction::Allow,
-                reason: format!(
-                    "Tool '{}' involves file writes — allowed at Medium risk",
-                    tool_name
+            trace.push("file-write heuristic: matched".to_string());
+            let action = self.at_least(PolicyAction::Allow);
+            return PolicyDecision::new(
+                action.clone(),
+                format!(
+                    "Tool '{}' involves file writes — {:?} at Medium risk",
+                    tool_name, action
                 ),
-                risk_level: RiskLevel::Medium,
-            };
+                RiskLevel::Medium,
+            );
         }
 
-        // --- 5. Default: low-risk allow ---------------------------------------
-        PolicyDecision {
-            action: PolicyAction::Allow,
-            reason: format!("Tool '{}' is allowed at Low risk", tool_name),
-            risk_level: RiskLevel::Low,
-        }
+        // --- 6. Default action at the configured risk --------------------------
+        let action = self.default_action.clone();
+        trace.push(format!(
+            "default: {:?} at {:?} risk",
+            action, self.default_risk
+        ));
+        let reason = match action {
+            PolicyAction::Allow => format!(
+                "Tool '{}' is allowed at {:?} risk",
+                tool_name, self.default_risk
+            ),
+            _ => format!(
+                "Tool '{}' defaults to {:?} under this policy",
+                tool_name, action
+            ),
+        };
+        PolicyDecision::new(action, reason, self.default_risk.clone())
     }
 
     /// Access the underlying sandbox checker (e.g. for the MCP layer to run
@@ -154,12 +880,48 @@ impl PolicyEngine {
 
     // -- private helpers ---------------------------------------------------
 
+    /// Record a call to `tool_name` at `now`, or return a `RateLimited`
+    /// decision if the tool already used up its window.
+    fn check_rate_limit(&self, tool_name: &str, now: Instant) -> Option<PolicyDecision> {
+        let limit = self.rate_limits.get(tool_name)?;
+        let window = Duration::from_secs(limit.window_secs);
+        let mut history = self.call_history.lock().unwrap();
+        let calls = history.entry(tool_name.to_string()).or_default();
+        while calls
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) >= window)
+        {
+            calls.pop_front();
+        }
+        if calls.len() >= limit.max_calls as usize {
+            return Some(PolicyDecision::new(
+                PolicyAction::RateLimited,
+                format!(
+                    "Tool '{}' exceeded its rate limit of {} calls per {}s",
+                    tool_name, limit.max_calls, limit.window_secs
+                ),
+                RiskLevel::Medium,
+            ));
+        }
+        calls.push_back(now);
+        None
+    }
+
+    /// The stricter of `action` and the configured default action.
+    fn at_least(&self, action: PolicyAction) -> PolicyAction {
+        if strictness(&self.default_action) > strictness(&action) {
+            self.default_action.clone()
+        } else {
+            action
+        }
+    }
+
     fn is_denied(&self, tool_name: &str) -> bool {
-        self.denied_tools.iter().any(|d| d == tool_name)
+        self.denied_tools.iter().any(|d| d.matches(tool_name))
     }
 
     fn requires_approval(&self, tool_name: &str) -> bool {
-        self.approval_required.iter().any(|a| a == tool_name)
+        self.approval_required.iter().any(|a| a.matches(tool_name))
     }
 
     /// Heuristic: tool names that look like shell/command execution.
@@ -195,16 +957,90 @@ impl PolicyEngine {
     }
 }
 
+fn compile(entries: Vec<String>) -> Vec<NamePattern> {
+    entries
+        .into_iter()
+        .map(NamePattern::parse_or_exact)
+        .collect()
+}
+
+fn compile_rules(rules: Vec<ArgumentRule>) -> Vec<(NamePattern, ArgumentRule)> {
+    rules
+        .into_iter()
+        .map(|rule| (NamePattern::parse_or_exact(rule.tool.clone()), rule))
+        .collect()
+}
+
+/// Orders actions from least (`Allow`) to most (`Deny`) strict.
+fn strictness(action: &PolicyAction) -> u8 {
+    match action {
+        PolicyAction::Allow => 0,
+        PolicyAction::RequireApproval => 1,
+        PolicyAction::RateLimited => 2,
+        PolicyAction::Deny => 3,
+    }
+}
+
 impl std::fmt::Debug for PolicyEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn names(list: &[NamePattern]) -> Vec<&str> {
+            list.iter().map(NamePattern::as_str).collect()
+        }
         f.debug_struct("PolicyEngine")
-            .field("approval_required", &self.approval_required)
-            .field("denied_tools", &self.denied_tools)
+            .field("approval_required", &names(&self.approval_required))
+            .field("denied_tools", &names(&self.denied_tools))
+            .field("tool_overrides", &self.tool_overrides)
+            .field("default_risk", &self.default_risk)
+            .field("default_action", &self.default_action)
+            .field("argument_rules", &self.argument_rules.len())
+            .field("rate_limits", &self.rate_limits)
             .field("max_execution_time", &self.max_execution_time)
+            .field("version", &self.version)
+            .field(
+                "decision_cache",
+                &self
+                    .decision_cache
+                    .as_ref()
+                    .map(|c| c.lock().unwrap().capacity),
+            )
             .finish()
     }
 }
 
+// ---------------------------------------------------------------------------
+// Approval
+// ---------------------------------------------------------------------------
+
+/// Decides calls that the policy marks [`PolicyAction::RequireApproval`],
+/// e.g. by asking a human.
+#[async_trait::async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    /// Whether the call to `tool` with `arguments` may go ahead.
+    async fn request_approval(&self, tool: &str, arguments: &Value) -> bool;
+}
+
+/// Refuses every approval request, so such calls fail as before.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoDenyApprovalHandler;
+
+#[async_trait::async_trait]
+impl ApprovalHandler for AutoDenyApprovalHandler {
+    async fn request_approval(&self, _tool: &str, _arguments: &Value) -> bool {
+        false
+    }
+}
+
+/// Grants every approval request; meant for tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysApproveApprovalHandler;
+
+#[async_trait::async_trait]
+impl ApprovalHandler for AlwaysApproveApprovalHandler {
+    async fn request_approval(&self, _tool: &str, _arguments: &Value) -> bool {
+        true
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -305,9 +1141,585 @@ mod tests {
         assert_eq!(decision.risk_level, RiskLevel::Critical);
     }
 
+    #[test]
+    fn glob_denies_tool_family() {
+        let pe = engine(vec![], vec!["fs_*"]);
+        let args = serde_json::json!({});
+        let decision = pe.evaluate("fs_write", &args);
+        assert_eq!(decision.action, PolicyAction::Deny);
+        assert_eq!(pe.evaluate("fs_read", &args).action, PolicyAction::Deny);
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+        // Globs are anchored: a mid-name match does not count.
+        assert_eq!(pe.evaluate("my_fs_tool", &args).action, PolicyAction::Allow);
+    }
+
+    #[test]
+    fn regex_requires_approval() {
+        let pe = engine(vec!["/^(deploy|release)_/"], vec![]);
+        let args = serde_json::json!({});
+        assert_eq!(
+            pe.evaluate("deploy_prod", &args).action,
+            PolicyAction::RequireApproval
+        );
+        assert_eq!(
+            pe.evaluate("release_v2", &args).action,
+            PolicyAction::RequireApproval
+        );
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+    }
+
+    #[test]
+    fn pattern_syntax() {
+        assert!(matches!(
+            NamePattern::parse("echo").unwrap(),
+            NamePattern::Exact(_)
+        ));
+        // Regex metacharacters in globs are literal.
+        let glob = NamePattern::parse("a.b*").unwrap();
+        assert!(glob.matches("a.bc"));
+        assert!(!glob.matches("axbc"));
+        assert!(NamePattern::parse("/[/").is_err());
+        // An invalid regex degrades to an exact name in the infallible path.
+        let pe = engine(vec![], vec!["/[/"]);
+        let args = serde_json::json!({});
+        assert_eq!(pe.evaluate("/[/", &args).action, PolicyAction::Deny);
+    }
+
+    #[test]
+    fn from_file_rejects_invalid_pattern() {
+        let path = write_policy("json", r#"{ "denied_tools": ["/(unclosed/"] }"#);
+        let err = PolicyEngine::from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(format!("{err:#}").contains("invalid tool pattern"));
+    }
+
+    // -- argument rules -----------------------------------------------------
+
+    fn argument_rules() -> Vec<ArgumentRule> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "tool": "hardware",
+                "json_path": "action.type",
+                "op": "eq",
+                "value": "drive",
+                "and": [{ "json_path": "action.speed", "op": "gt", "value": 50 }],
+                "action": "Deny"
+            },
+            {
+                "tool": "fs_*",
+                "json_path": "path",
+                "op": "not_starts_with",
+                "value": "/tmp/",
+                "action": "RequireApproval"
+            }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn argument_rule_fires_on_match() {
+        let pe = engine(vec![], vec![]).with_argument_rules(argument_rules());
+        let args = serde_json::json!({ "action": { "type": "drive", "speed": 80 } });
+        let decision = pe.evaluate("hardware", &args);
+        assert_eq!(decision.action, PolicyAction::Deny);
+        assert_eq!(decision.risk_level, RiskLevel::Critical);
+        assert!(decision.reason.contains("argument rule #1"));
+        assert!(decision.reason.contains("action.speed Gt 50"));
+
+        let decision = pe.evaluate("fs_write", &serde_json::json!({ "path": "/etc/passwd" }));
+        assert_eq!(decision.action, PolicyAction::RequireApproval);
+        assert!(decision.reason.contains("argument rule #2"));
+    }
+
+    #[test]
+    fn argument_rule_ignored_without_match() {
+        let pe = engine(vec![], vec![]).with_argument_rules(argument_rules());
+        for args in [
+            serde_json::json!({ "action": { "type": "drive", "speed": 20 } }),
+            serde_json::json!({ "action": { "type": "stop", "speed": 80 } }),
+            serde_json::json!({}),
+        ] {
+            let decision = pe.evaluate("hardware", &args);
+            assert_eq!(decision.action, PolicyAction::Allow, "{args}");
+            assert_eq!(decision.risk_level, RiskLevel::Low);
+        }
+        // In-scope path: the file-write heuristic decides.
+        let decision = pe.evaluate("fs_write", &serde_json::json!({ "path": "/tmp/out" }));
+        assert_eq!(decision.action, PolicyAction::Allow);
+        assert_eq!(decision.risk_level, RiskLevel::Medium);
+    }
+
+    #[test]
+    fn argument_rules_never_loosen() {
+        let rule = serde_json::from_value(serde_json::json!({
+            "tool": "nuke",
+            "json_path": "dry_run",
+            "op": "eq",
+            "value": true,
+            "action": "Allow"
+        }))
+        .unwrap();
+        let pe = engine(vec![], vec!["nuke"]).with_argument_rules(vec![rule]);
+        let decision = pe.evaluate("nuke", &serde_json::json!({ "dry_run": true }));
+        assert_eq!(decision.action, PolicyAction::Deny);
+    }
+
+    #[test]
+    fn argument_ops() {
+        let cond = |path: &str, op: ArgumentOp, value: Value| ArgumentCondition {
+            json_path: path.into(),
+            op,
+            value,
+        };
+        let args = serde_json::json!({ "n": 5, "s": "hello", "tags": ["a", "b"] });
+        assert!(cond("n", ArgumentOp::Eq, 5.0.into()).matches(&args));
+        assert!(cond("n", ArgumentOp::Lte, 5.into()).matches(&args));
+        assert!(!cond("n", ArgumentOp::Lt, 5.into()).matches(&args));
+        assert!(cond("s", ArgumentOp::Ne, "bye".into()).matches(&args));
+        assert!(cond("s", ArgumentOp::Contains, "ell".into()).matches(&args));
+        assert!(cond("tags", ArgumentOp::Contains, "b".into()).matches(&args));
+        assert!(cond("tags.0", ArgumentOp::StartsWith, "a".into()).matches(&args));
+        assert!(cond("$.s", ArgumentOp::Eq, "hello".into()).matches(&args));
+        assert!(!cond("s", ArgumentOp::Gt, 1.into()).matches(&args));
+        assert!(!cond("missing", ArgumentOp::Ne, 1.into()).matches(&args));
+    }
+
     #[test]
     fn sandbox_accessor_works() {
         let pe = engine(vec![], vec![]);
         assert_eq!(pe.sandbox().profile_name(), "AllowAll");
     }
+
+    // -- policy files -------------------------------------------------------
+
+    fn write_policy(ext: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("ygn-policy-{}.{ext}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn from_file_json_denies_listed_tool() {
+        let path = write_policy(
+            "json",
+            r#"{
+                "denied_tools": ["rm_rf"],
+                "approval_required": ["deploy"],
+                "default_risk": "Medium",
+                "tool_overrides": {
+                    "bash_exec": { "action": "Allow", "risk_level": "Low" },
+                    "fetch": { "action": "RequireApproval" }
+                },
+                "argument_rules": [
+                    { "tool": "fetch", "json_path": "url", "op": "starts_with",
+                      "value": "http://", "action": "Deny" }
+                ],
+                "max_execution_time_secs": 5
+            }"#,
+        );
+        let pe = PolicyEngine::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let args = serde_json::json!({});
+        let decision = pe.evaluate("rm_rf", &args);
+        assert_eq!(decision.action, PolicyAction::Deny);
+        assert_eq!(decision.risk_level, RiskLevel::Critical);
+
+        assert_eq!(
+            pe.evaluate("deploy", &args).action,
+            PolicyAction::RequireApproval
+        );
+        // The override beats the shell heuristic.
+        let decision = pe.evaluate("bash_exec", &args);
+        assert_eq!(decision.action, PolicyAction::Allow);
+        assert_eq!(decision.risk_level, RiskLevel::Low);
+        let decision = pe.evaluate("fetch", &args);
+        assert_eq!(decision.action, PolicyAction::RequireApproval);
+        assert_eq!(decision.risk_level, RiskLevel::High);
+
+        let insecure = serde_json::json!({ "url": "http://example.com" });
+        assert_eq!(pe.evaluate("fetch", &insecure).action, PolicyAction::Deny);
+
+        assert_eq!(pe.evaluate("echo", &args).risk_level, RiskLevel::Medium);
+        assert_eq!(pe.max_execution_time(), Duration::from_secs(5));
+        assert_eq!(pe.sandbox().profile_name(), "Net");
+    }
+
+    #[test]
+    fn from_file_toml_denies_listed_tool() {
+        let path = write_policy(
+            "toml",
+            r#"
+denied_tools = ["dangerous_tool"]
+sandbox_profile = "NoNet"
+allowed_commands = ["lint"]
+
+[tool_overrides.write_file]
+action = "Deny"
+"#,
+        );
+        let pe = PolicyEngine::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let args = serde_json::json!({});
+        assert_eq!(
+            pe.evaluate("dangerous_tool", &args).action,
+            PolicyAction::Deny
+        );
+        let decision = pe.evaluate("write_file", &args);
+        assert_eq!(decision.action, PolicyAction::Deny);
+        assert_eq!(decision.risk_level, RiskLevel::Critical);
+        assert_eq!(pe.max_execution_time(), Duration::from_secs(30));
+        assert_eq!(pe.sandbox().profile_name(), "NoNet");
+        let command = |target: &str| {
+            pe.sandbox()
+                .check_access(&AccessRequest {
+                    kind: crate::sandbox::AccessKind::Command,
+                    target: target.into(),
+                })
+                .allowed
+        };
+        assert!(command("lint"));
+        assert!(!command("rm"));
+    }
+
+    #[test]
+    fn from_file_rejects_unknown_fields() {
+        let path = write_policy("json", r#"{ "deny": ["rm_rf"] }"#);
+        let err = PolicyEngine::from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(format!("{err:#}").contains("unknown field"));
+    }
+
+    #[test]
+    fn untrusted_tier_is_stricter() {
+        let args = serde_json::json!({});
+        let trusted = PolicyEngine::from_config(PolicyConfig::for_trust_tier(&TrustTier::Trusted));
+        let untrusted =
+            PolicyEngine::from_config(PolicyConfig::for_trust_tier(&TrustTier::Untrusted));
+
+        assert_eq!(trusted.evaluate("echo", &args).action, PolicyAction::Allow);
+        let decision = untrusted.evaluate("echo", &args);
+        assert_eq!(decision.action, PolicyAction::RequireApproval);
+        assert_eq!(decision.risk_level, RiskLevel::Medium);
+        assert_eq!(
+            untrusted.evaluate("write_file", &args).action,
+            PolicyAction::RequireApproval
+        );
+        assert_eq!(untrusted.sandbox().profile_name(), "NoNet");
+    }
+
+    #[test]
+    fn restricting_to_a_tier_never_relaxes_the_policy() {
+        let args = serde_json::json!({});
+        let config = PolicyConfig {
+            denied_tools: vec!["shell_exec".into()],
+            tool_overrides: BTreeMap::from([(
+                "echo".to_string(),
+                ToolOverride {
+                    action: PolicyAction::Allow,
+                    risk_level: None,
+                },
+            )]),
+            ..PolicyConfig::default()
+        };
+        let trusted = PolicyEngine::from_config(config.clone().restricted_to(&TrustTier::Trusted));
+        let untrusted = PolicyEngine::from_config(config.restricted_to(&TrustTier::Untrusted));
+
+        assert_eq!(trusted.evaluate("echo", &args).action, PolicyAction::Allow);
+        assert_eq!(
+            trusted.evaluate("shell_exec", &args).action,
+            PolicyAction::Deny
+        );
+        assert_eq!(
+            untrusted.evaluate("echo", &args).action,
+            PolicyAction::RequireApproval
+        );
+        assert_eq!(
+            untrusted.evaluate("shell_exec", &args).action,
+            PolicyAction::Deny
+        );
+        assert_eq!(untrusted.sandbox().profile_name(), "NoNet");
+    }
+
+    #[test]
+    fn default_deny_escalates_heuristics() {
+        let pe = PolicyEngine::from_config(PolicyConfig {
+            default_action: PolicyAction::Deny,
+            tool_overrides: BTreeMap::from([(
+                "echo".to_string(),
+                ToolOverride {
+                    action: PolicyAction::Allow,
+                    risk_level: None,
+                },
+            )]),
+            ..Default::default()
+        });
+        let args = serde_json::json!({});
+        assert_eq!(pe.evaluate("bash_exec", &args).action, PolicyAction::Deny);
+        assert_eq!(pe.evaluate("write_file", &args).action, PolicyAction::Deny);
+        assert_eq!(pe.evaluate("anything", &args).action, PolicyAction::Deny);
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+    }
+
+    #[test]
+    fn policy_json_schema_accepts_default_config() {
+        let schema: Value = serde_json::from_str(&PolicyConfig::json_schema()).unwrap();
+        assert_eq!(schema["title"], "YGN Policy Configuration");
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let config = serde_json::to_value(PolicyConfig::default()).unwrap();
+        assert!(validator.is_valid(&config));
+        assert!(!validator.is_valid(&serde_json::json!({ "default_risk": "Extreme" })));
+        assert!(validator.is_valid(&serde_json::json!({
+            "tool_overrides": { "echo": { "action": "RateLimited" } }
+        })));
+        assert!(!validator.is_valid(&serde_json::json!({ "unknown": true })));
+        assert!(!validator.is_valid(&serde_json::json!({
+            "rate_limits": { "echo": { "max_calls": 1, "window_secs": 0 } }
+        })));
+    }
+
+    #[test]
+    fn call_over_rate_limit_is_rejected() {
+        let pe = engine(vec![], vec![]).with_rate_limit(
+            "echo",
+            ToolRateLimit {
+                max_calls: 3,
+                window_secs: 60,
+            },
+        );
+        let args = serde_json::json!({});
+        for _ in 0..3 {
+            assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+        }
+        let decision = pe.evaluate("echo", &args);
+        assert_eq!(decision.action, PolicyAction::RateLimited);
+        assert!(decision.reason.contains("rate limit of 3 calls per 60s"));
+        // Other tools are unaffected.
+        assert_eq!(pe.evaluate("read_file", &args).action, PolicyAction::Allow);
+    }
+
+    #[test]
+    fn rate_limit_window_slides() {
+        let pe = engine(vec![], vec![]).with_rate_limit(
+            "echo",
+            ToolRateLimit {
+                max_calls: 1,
+                window_secs: 10,
+            },
+        );
+        let start = Instant::now();
+        assert!(pe.check_rate_limit("echo", start).is_none());
+        assert!(pe
+            .check_rate_limit("echo", start + Duration::from_secs(5))
+            .is_some());
+        assert!(pe
+            .check_rate_limit("echo", start + Duration::from_secs(10))
+            .is_none());
+    }
+
+    #[test]
+    fn denied_calls_do_not_use_rate_limit() {
+        let pe = PolicyEngine::from_config(PolicyConfig {
+            rate_limits: BTreeMap::from([(
+                "deploy".to_string(),
+                ToolRateLimit {
+                    max_calls: 1,
+                    window_secs: 60,
+                },
+            )]),
+            approval_required: vec!["deploy".into()],
+            ..Default::default()
+        });
+        let args = serde_json::json!({});
+        for _ in 0..3 {
+            assert_eq!(
+                pe.evaluate("deploy", &args).action,
+                PolicyAction::RequireApproval
+            );
+        }
+    }
+
+    #[test]
+    fn approved_calls_use_rate_limit() {
+        let pe = engine(vec![], vec![]).with_rate_limit(
+            "deploy",
+            ToolRateLimit {
+                max_calls: 1,
+                window_secs: 60,
+            },
+        );
+        assert!(pe.count_approved_call("deploy").is_none());
+        let limited = pe.count_approved_call("deploy").unwrap();
+        assert_eq!(limited.action, PolicyAction::RateLimited);
+        assert!(!limited.decision_id.is_empty());
+        assert!(pe.count_approved_call("echo").is_none());
+    }
+
+    #[test]
+    fn preview_does_not_use_rate_limit() {
+        let pe = engine(vec![], vec![]).with_rate_limit(
+            "echo",
+            ToolRateLimit {
+                max_calls: 1,
+                window_secs: 60,
+            },
+        );
+        let args = serde_json::json!({});
+        for _ in 0..3 {
+            assert_eq!(pe.preview("echo", &args).action, PolicyAction::Allow);
+        }
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::RateLimited);
+    }
+
+    #[test]
+    fn each_decision_gets_its_own_id_and_a_trace() {
+        let pe = engine(vec![], vec!["rm_rf"]);
+        let args = serde_json::json!({});
+        let a = pe.evaluate("echo", &args);
+        let b = pe.evaluate("echo", &args);
+        assert!(!a.decision_id.is_empty());
+        assert_ne!(a.decision_id, b.decision_id);
+        assert_eq!(a.trace.first().unwrap(), "denied_tools: no match");
+        assert!(a.trace.last().unwrap().starts_with("default:"));
+        let denied = pe.evaluate("rm_rf", &args);
+        assert_eq!(denied.trace, vec!["denied_tools: matched".to_string()]);
+    }
+
+    #[test]
+    fn decision_cache_marks_hits() {
+        let pe = engine(vec![], vec![]).with_decision_cache(8);
+        let args = serde_json::json!({"path": "/tmp/a"});
+        let first = pe.evaluate("echo", &args);
+        assert!(!first.cached);
+        let second = pe.evaluate("echo", &args);
+        assert!(second.cached);
+        assert_eq!(second.action, first.action);
+        assert_eq!(second.trace, first.trace);
+        assert_ne!(second.decision_id, first.decision_id);
+        assert!(
+            !pe.evaluate("echo", &serde_json::json!({"path": "/tmp/b"}))
+                .cached
+        );
+    }
+
+    #[test]
+    fn decision_cache_evicts_least_recently_used() {
+        let pe = PolicyEngine::from_config(PolicyConfig {
+            decision_cache_size: 2,
+            ..Default::default()
+        });
+        let args = serde_json::json!({});
+        pe.evaluate("a", &args);
+        pe.evaluate("b", &args);
+        pe.evaluate("a", &args);
+        pe.evaluate("c", &args);
+        assert!(pe.evaluate("a", &args).cached);
+        assert!(!pe.evaluate("b", &args).cached);
+    }
+
+    #[test]
+    fn decision_cache_tracks_recency_through_updates() {
+        let key = |tool: &str| (tool.to_string(), "{}".to_string(), 0);
+        let decision = |reason: &str| {
+            PolicyDecision::new(PolicyAction::Allow, reason.to_string(), RiskLevel::Low)
+        };
+        let mut cache = DecisionCache::new(3);
+        for tool in ["a", "b", "c"] {
+            cache.insert(key(tool), decision(tool));
+        }
+        // Recency is now b, c, a; re-inserting c refreshes it too.
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(key("c"), decision("c2"));
+        cache.insert(key("d"), decision("d"));
+        assert!(cache.get(&key("b")).is_none());
+        assert_eq!(cache.get(&key("c")).unwrap().reason, "c2");
+        cache.insert(key("e"), decision("e"));
+        assert!(cache.get(&key("a")).is_none());
+        assert!(cache.get(&key("d")).is_some());
+        assert_eq!(cache.index.len(), 3);
+        assert_eq!(cache.nodes.len(), 3);
+    }
+
+    #[test]
+    fn decision_cache_keys_on_exact_arguments() {
+        let pe = PolicyEngine::from_config(PolicyConfig {
+            decision_cache_size: 8,
+            argument_rules: vec![ArgumentRule {
+                tool: "fetch".into(),
+                json_path: "url".into(),
+                op: ArgumentOp::StartsWith,
+                value: serde_json::json!("https://internal"),
+                action: PolicyAction::Deny,
+                and: vec![],
+            }],
+            ..Default::default()
+        });
+        let allowed = serde_json::json!({"url": "https://example.com"});
+        let denied = serde_json::json!({"url": "https://internal.example"});
+        assert_eq!(pe.evaluate("fetch", &allowed).action, PolicyAction::Allow);
+        let decision = pe.evaluate("fetch", &denied);
+        assert_eq!(decision.action, PolicyAction::Deny);
+        assert!(!decision.cached);
+    }
+
+    #[test]
+    fn cached_decisions_still_use_rate_limit() {
+        let pe = engine(vec![], vec![])
+            .with_decision_cache(8)
+            .with_rate_limit(
+                "echo",
+                ToolRateLimit {
+                    max_calls: 1,
+                    window_secs: 60,
+                },
+            );
+        let args = serde_json::json!({});
+        assert_eq!(pe.evaluate("echo", &args).action, PolicyAction::Allow);
+        let limited = pe.evaluate("echo", &args);
+        assert_eq!(limited.action, PolicyAction::RateLimited);
+        assert!(limited.cached);
+    }
+
+    #[test]
+    fn reload_invalidates_decision_cache() {
+        let mut pe = engine(vec![], vec![]).with_decision_cache(8);
+        let args = serde_json::json!({});
+        pe.evaluate("deploy", &args);
+        assert!(pe.evaluate("deploy", &args).cached);
+        assert_eq!(pe.version(), 0);
+
+        pe.reload(PolicyConfig {
+            denied_tools: vec!["deploy".into()],
+            decision_cache_size: 8,
+            ..Default::default()
+        });
+        assert_eq!(pe.version(), 1);
+        let after = pe.evaluate("deploy", &args);
+        assert!(!after.cached);
+        assert_eq!(after.action, PolicyAction::Deny);
+        assert!(pe.evaluate("deploy", &args).cached);
+
+        // The new config's cache size applies; 0 turns caching off.
+        pe.reload(PolicyConfig::default());
+        pe.evaluate("deploy", &args);
+        assert!(!pe.evaluate("deploy", &args).cached);
+    }
+
+    #[test]
+    fn reload_from_file_keeps_rules_on_error() {
+        let path = write_policy("json", r#"{"denied_tools": ["deploy"]}"#);
+        let mut pe = engine(vec![], vec![]);
+        pe.reload_from_file(&path).unwrap();
+        assert_eq!(pe.version(), 1);
+        let args = serde_json::json!({});
+        assert_eq!(pe.evaluate("deploy", &args).action, PolicyAction::Deny);
+
+        std::fs::write(&path, "not a policy").unwrap();
+        assert!(pe.reload_from_file(&path).is_err());
+        assert_eq!(pe.version(), 1);
+        assert_eq!(pe.evaluate("deploy", &args).action, PolicyAction::Deny);
+        std::fs::remove_file(&path).ok();
+    }
 }
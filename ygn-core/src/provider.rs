@@ -3,9 +3,14 @@
 //! Defines the interface for LLM backends (Anthropic, OpenAI, Ollama, etc.)
 //! based on ZeroClaw's Provider trait architecture.
 
+use std::pin::Pin;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use futures_util::Stream;
 use serde::{Deserialize, Serialize};
 
+use crate::provider_cache::CacheStats;
 use crate::tool::ToolSpec;
 
 // ---------------------------------------------------------------------------
@@ -29,11 +34,100 @@ pub enum ChatRole {
     Tool,
 }
 
+/// A single part of a multimodal message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    /// Plain text.
+    Text { text: String },
+    /// Inline image bytes, base64-encoded.
+    Image { data: String, mime_type: String },
+    /// Image referenced by URL.
+    ImageUrl { url: String },
+}
+
+/// Content of a chat message: either plain text or a list of parts.
+///
+/// Serializes as a bare string for [`MessageContent::Text`] so that
+/// text-only messages keep their original wire shape.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Concatenate all text parts, ignoring images.
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(t) => t.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|p| match p {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Returns `true` if any part is an image.
+    pub fn has_images(&self) -> bool {
+        match self {
+            MessageContent::Text(_) => false,
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .any(|p| matches!(p, ContentPart::Image { .. } | ContentPart::ImageUrl { .. })),
+        }
+    }
+
+    /// View the content as a list of parts (plain text becomes one part).
+    pub fn parts(&self) -> Vec<ContentPart> {
+        match self {
+            MessageContent::Text(t) => vec![ContentPart::Text { text: t.clone() }],
+            MessageContent::Parts(parts) => parts.clone(),
+        }
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Parts(parts)
+    }
+}
+
+impl PartialEq<&str> for MessageContent {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, MessageContent::Text(t) if t == other)
+    }
+}
+
 /// A single message within a chat request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: ChatRole,
-    pub content: String,
+    pub content: MessageContent,
+    /// On a `Tool` message, the [`ToolCall::id`] this is the result of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// On an `Assistant` message, the tool calls the model made, so the
+    /// results that follow can be matched to them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
 }
 
 /// Request sent to a provider.
@@ -43,6 +137,240 @@ pub struct ChatRequest {
     pub messages: Vec<ChatMessage>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f64>,
+    /// Sequences that end generation when the model produces one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    /// Nucleus sampling: only tokens within this cumulative probability
+    /// mass are considered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Sampling seed for repeatable output. Ignored by providers without
+    /// one (Claude, Gemini).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Require the response content to be JSON, enforced natively by the
+    /// provider and checked with [`ResponseFormat::validate`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Number of candidate responses to generate.  The first is returned
+    /// as the response and the rest in [`ChatResponse::alternates`].
+    /// Ignored by providers without one (all but Gemini).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_count: Option<u32>,
+}
+
+/// Structured output mode for [`ChatRequest::response_format`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any valid JSON.
+    Json,
+    /// JSON that validates against `schema` (a JSON Schema document).
+    JsonSchema { schema: serde_json::Value },
+}
+
+/// A response that does not satisfy the requested [`ResponseFormat`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("structured output invalid: {message}")]
+pub struct StructuredOutputError {
+    /// What was wrong with the response.
+    pub message: String,
+    /// The response content as returned by the provider.
+    pub raw: String,
+}
+
+/// Why a provider chat call failed, so callers can tell an auth failure
+/// from a rate limit or a timeout without matching on message text.
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    /// The provider rejected the credentials (401 or 403).
+    #[error("{provider} rejected the API key ({status}): {message}")]
+    Auth {
+        provider: String,
+        status: u16,
+        message: String,
+    },
+    /// The provider answered 429.
+    #[error("{provider} rate limited the request (429): {message}")]
+    RateLimited {
+        provider: String,
+        /// Wait the provider asked for in its `Retry-After` header.
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// The request timed out before the provider answered.
+    #[error("{provider} request timed out")]
+    Timeout { provider: String },
+    /// Any other error status.
+    #[error("{provider} API error ({status}): {message}")]
+    Api {
+        provider: String,
+        status: u16,
+        message: String,
+    },
+    /// The response could not be decoded.
+    #[error("invalid {provider} response: {message}")]
+    Decode { provider: String, message: String },
+    /// The provider could not be reached or the connection failed.
+    #[error("{provider} request failed: {message}")]
+    Transport { provider: String, message: String },
+    /// The model declined to answer.
+    #[error("{provider} model refused the request: {reason}")]
+    Refused { provider: String, reason: String },
+    /// The provider's content filters withheld the response, e.g. Gemini's
+    /// `SAFETY` or `RECITATION` finish reasons.
+    #[error("{provider} blocked the response ({reason}){}", format_ratings(.safety_ratings))]
+    Blocked {
+        provider: String,
+        reason: String,
+        /// Ratings that led to the block, when the provider reports them.
+        safety_ratings: Vec<SafetyRating>,
+    },
+    /// The response does not satisfy the requested [`ResponseFormat`].
+    #[error(transparent)]
+    StructuredOutput(#[from] StructuredOutputError),
+    /// Anything else, such as a request the provider cannot serve.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// The flagged ratings of a [`ProviderError::Blocked`], for its message.
+fn format_ratings(ratings: &[SafetyRating]) -> String {
+    let flagged: Vec<String> = ratings
+        .iter()
+        .filter(|r| r.blocked || !matches!(r.probability.as_str(), "NEGLIGIBLE" | "LOW"))
+        .map(|r| format!("{} {}", r.category, r.probability))
+        .collect();
+    if flagged.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", flagged.join(", "))
+    }
+}
+
+/// A provider's assessment of one harm category in a response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyRating {
+    /// e.g. `HARM_CATEGORY_DANGEROUS_CONTENT`.
+    pub category: String,
+    /// e.g. `NEGLIGIBLE`, `LOW`, `MEDIUM` or `HIGH`.
+    pub probability: String,
+    /// Whether this rating caused the block.
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+impl ProviderError {
+    /// Classify an error status from `provider`.
+    pub fn from_status(
+        provider: impl Into<String>,
+        status: u16,
+        retry_after: Option<Duration>,
+        message: impl Into<String>,
+    ) -> Self {
+        let (provider, message) = (provider.into(), message.into());
+        match status {
+            401 | 403 => Self::Auth {
+                provider,
+                status,
+                message,
+            },
+            429 => Self::RateLimited {
+                provider,
+                retry_after,
+                message,
+            },
+            408 => Self::Timeout { provider },
+            _ => Self::Api {
+                provider,
+                status,
+                message,
+            },
+        }
+    }
+
+    /// Classify a failed HTTP exchange with `provider`.
+    pub fn from_reqwest(provider: impl Into<String>, error: reqwest::Error) -> Self {
+        let provider = provider.into();
+        if error.is_timeout() {
+            Self::Timeout { provider }
+        } else if error.is_decode() {
+            Self::Decode {
+                provider,
+                message: error.to_string(),
+            }
+        } else {
+            Self::Transport {
+                provider,
+                message: error.to_string(),
+            }
+        }
+    }
+
+    /// A response from `provider` that could not be parsed.  Errors that
+    /// are already a [`ProviderError`], such as a refusal, are kept as is.
+    pub fn decode(provider: impl Into<String>, error: anyhow::Error) -> Self {
+        match error.downcast::<Self>() {
+            Ok(error) => error,
+            Err(error) => Self::Decode {
+                provider: provider.into(),
+                message: error.to_string(),
+            },
+        }
+    }
+
+    /// HTTP status the provider answered with, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Auth { status, .. } | Self::Api { status, .. } => Some(*status),
+            Self::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+}
+
+impl ResponseFormat {
+    /// The JSON Schema to enforce, if any.
+    pub fn schema(&self) -> Option<&serde_json::Value> {
+        match self {
+            ResponseFormat::Json => None,
+            ResponseFormat::JsonSchema { schema } => Some(schema),
+        }
+    }
+
+    /// Parse `content` as JSON and, for [`ResponseFormat::JsonSchema`],
+    /// validate it against the schema.
+    pub fn validate(&self, content: &str) -> Result<serde_json::Value, StructuredOutputError> {
+        let fail = |message: String| StructuredOutputError {
+            message,
+            raw: content.to_string(),
+        };
+        let value: serde_json::Value = serde_json::from_str(content.trim())
+            .map_err(|e| fail(format!("response is not valid JSON: {e}")))?;
+        if let Some(schema) = self.schema() {
+            let validator = jsonschema::validator_for(schema)
+                .map_err(|e| fail(format!("invalid response schema: {e}")))?;
+            let errors: Vec<String> = validator
+                .iter_errors(&value)
+                .map(|e| {
+                    let path = e.instance_path.to_string();
+                    let path = if path.is_empty() {
+                        "/".to_string()
+                    } else {
+                        path
+                    };
+                    format!("{path}: {e}")
+                })
+                .collect();
+            if !errors.is_empty() {
+                return Err(fail(format!(
+                    "response does not match schema: {}",
+                    errors.join("; ")
+                )));
+            }
+        }
+        Ok(value)
+    }
 }
 
 /// A tool call returned by the provider.
@@ -50,6 +378,10 @@ pub struct ChatRequest {
 pub struct ToolCall {
     pub tool_name: String,
     pub arguments: serde_json::Value,
+    /// Provider-assigned id, echoed back in the result's
+    /// [`ChatMessage::tool_call_id`].  Providers without ids leave it unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
 }
 
 /// Response returned by a provider.
@@ -58,13 +390,95 @@ pub struct ChatResponse {
     pub content: String,
     pub tool_calls: Vec<ToolCall>,
     pub usage: Option<TokenUsage>,
+    /// True when served from a [`crate::provider_cache::CachingProvider`]
+    /// instead of the backend.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// Model that served the response, set by
+    /// [`ProviderRegistry::chat_with_fallback`](crate::multi_provider::ProviderRegistry::chat_with_fallback)
+    /// so callers can tell when a fallback model answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Why generation stopped, as reported by the provider (e.g. `STOP` or
+    /// `MAX_TOKENS`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    /// The other candidates generated when
+    /// [`ChatRequest::candidate_count`] is above one, in provider order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<ChatCandidate>,
+}
+
+/// One of several candidate responses to a request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatCandidate {
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
 }
 
 /// Token usage information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TokenUsage {
+    /// Input tokens, excluding any counted in the cache fields below.
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
+    /// Input tokens written to the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_tokens: Option<u32>,
+    /// Input tokens served from the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u32>,
+}
+
+/// An incremental piece of a streamed chat response.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatChunk {
+    /// Text appended to the response by this chunk.
+    pub delta: String,
+    /// Usage totals, typically only present on the final chunk.
+    pub usage: Option<TokenUsage>,
+}
+
+/// Stream of chunks returned by [`Provider::chat_stream`].
+pub type ChatStream = Pin<Box<dyn Stream<Item = anyhow::Result<ChatChunk>> + Send>>;
+
+/// Outcome of a [`Provider::health_probe`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// Whether the provider answered successfully.
+    pub up: bool,
+    /// Time taken by the probe, in milliseconds.
+    pub latency_ms: f64,
+    /// Why the probe failed, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProbeResult {
+    /// Run `probe` and time it; an error marks the provider down.
+    pub async fn measure<F>(probe: F) -> Self
+    where
+        F: std::future::Future<Output = anyhow::Result<()>>,
+    {
+        let started = std::time::Instant::now();
+        let result = probe.await;
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        match result {
+            Ok(()) => Self {
+                up: true,
+                latency_ms,
+                error: None,
+            },
+            Err(e) => Self {
+                up: false,
+                latency_ms,
+                error: Some(e.to_string()),
+            },
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -81,14 +495,74 @@ pub trait Provider: Send + Sync {
     fn capabilities(&self) -> ProviderCapabilities;
 
     /// Send a chat request and receive a response.
-    async fn chat(&self, request: ChatRequest) -> anyhow::Result<ChatResponse>;
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError>;
 
     /// Send a chat request with tool definitions.
     async fn chat_with_tools(
         &self,
         request: ChatRequest,
         tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse>;
+    ) -> Result<ChatResponse, ProviderError>;
+
+    /// Send a chat request and receive the response incrementally.
+    ///
+    /// The default implementation performs a regular [`Provider::chat`] and
+    /// yields the whole response as a single chunk; providers with native
+    /// streaming override it.
+    async fn chat_stream(&self, request: ChatRequest) -> anyhow::Result<ChatStream> {
+        let response = self.chat(request).await?;
+        let chunk = ChatChunk {
+            delta: response.content,
+            usage: response.usage,
+        };
+        Ok(Box::pin(futures_util::stream::iter([Ok(chunk)])))
+    }
+
+    /// Response cache hit/miss counters, for providers wrapped in a
+    /// [`crate::provider_cache::CachingProvider`].
+    fn cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
+
+    /// Whether this provider can serve `model`. Hosted providers accept
+    /// any name and let the API reject it; local providers check what is
+    /// installed.
+    async fn supports_model(&self, _model: &str) -> bool {
+        true
+    }
+
+    /// Embed each of `texts` into a vector, in input order.
+    ///
+    /// The default implementation fails; providers with an embeddings
+    /// endpoint override it.
+    async fn embed(&self, _texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        anyhow::bail!("provider '{}' does not support embeddings", self.name())
+    }
+
+    /// Check that the provider is reachable, without a real workload.
+    ///
+    /// The default sends a one-token chat request with no model set;
+    /// providers with a cheaper endpoint, such as a model listing,
+    /// override it.
+    async fn health_probe(&self) -> ProbeResult {
+        let request = ChatRequest {
+            model: String::new(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "ping".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: Some(1),
+            temperature: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            response_format: None,
+            candidate_count: None,
+        };
+        ProbeResult::measure(async { Ok(self.chat(request).await.map(drop)?) }).await
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -124,14 +598,19 @@ impl Provider for StubProvider {
         }
     }
 
-    async fn chat(&self, _request: ChatRequest) -> anyhow::Result<ChatResponse> {
+    async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ProviderError> {
         Ok(ChatResponse {
             content: self.response_text.clone(),
             tool_calls: vec![],
             usage: Some(TokenUsage {
                 prompt_tokens: 0,
                 completion_tokens: 0,
+                ..Default::default()
             }),
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
         })
     }
 
@@ -139,10 +618,33 @@ impl Provider for StubProvider {
         &self,
         request: ChatRequest,
         _tools: &[ToolSpec],
-    ) -> anyhow::Result<ChatResponse> {
+    ) -> Result<ChatResponse, ProviderError> {
         // Stub ignores tools and delegates to plain chat.
         self.chat(request).await
     }
+
+    async fn chat_stream(&self, _request: ChatRequest) -> anyhow::Result<ChatStream> {
+        // Emit one chunk per word so consumers see real incremental output.
+        let mut chunks: Vec<anyhow::Result<ChatChunk>> = self
+            .response_text
+            .split_inclusive(' ')
+            .map(|word| {
+                Ok(ChatChunk {
+                    delta: word.to_string(),
+                    usage: None,
+                })
+            })
+            .collect();
+        chunks.push(Ok(ChatChunk {
+            delta: String::new(),
+            usage: Some(TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                ..Default::default()
+            }),
+        }));
+        Ok(Box::pin(futures_util::stream::iter(chunks)))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -158,10 +660,17 @@ mod tests {
             model: "stub".to_string(),
             messages: vec![ChatMessage {
                 role: ChatRole::User,
-                content: "Hi".to_string(),
+                content: "Hi".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
             }],
             max_tokens: Some(100),
             temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
         }
     }
 
@@ -194,15 +703,171 @@ mod tests {
         assert_eq!(resp.content, "tool-aware");
     }
 
+    #[tokio::test]
+    async fn stub_provider_streams_words() {
+        use futures_util::StreamExt;
+
+        let provider = StubProvider::default();
+        let stream = provider.chat_stream(sample_request()).await.unwrap();
+        let chunks: Vec<ChatChunk> = stream.map(|c| c.unwrap()).collect().await;
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Hello from StubProvider");
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.last().unwrap().usage.is_some());
+    }
+
+    #[test]
+    fn response_format_accepts_matching_json() {
+        let format = ResponseFormat::JsonSchema {
+            schema: serde_json::json!({
+                "type": "object",
+                "required": ["city"],
+                "properties": { "city": { "type": "string" } }
+            }),
+        };
+        let value = format.validate(r#" {"city": "Oslo"} "#).unwrap();
+        assert_eq!(value["city"], "Oslo");
+        assert!(ResponseFormat::Json.validate("[1, 2]").is_ok());
+    }
+
+    #[test]
+    fn response_format_rejects_non_json_with_raw_text() {
+        let err = ResponseFormat::Json
+            .validate("Sure! Here is the JSON:")
+            .unwrap_err();
+        assert!(err.message.contains("not valid JSON"), "{}", err.message);
+        assert_eq!(err.raw, "Sure! Here is the JSON:");
+    }
+
+    #[test]
+    fn response_format_reports_schema_violations() {
+        let format = ResponseFormat::JsonSchema {
+            schema: serde_json::json!({
+                "type": "object",
+                "properties": { "temp": { "type": "number" } },
+                "required": ["temp"]
+            }),
+        };
+        let err = format.validate(r#"{"temp": "warm"}"#).unwrap_err();
+        assert!(err.message.contains("/temp"), "{}", err.message);
+        assert_eq!(err.raw, r#"{"temp": "warm"}"#);
+
+        // The typed error survives conversion to anyhow.
+        let any: anyhow::Error = err.into();
+        assert!(any.downcast_ref::<StructuredOutputError>().is_some());
+    }
+
+    #[test]
+    fn provider_errors_classify_statuses() {
+        let auth = ProviderError::from_status("OpenAI", 401, None, "bad key");
+        assert!(matches!(auth, ProviderError::Auth { status: 401, .. }));
+        assert_eq!(
+            auth.to_string(),
+            "OpenAI rejected the API key (401): bad key"
+        );
+
+        let limited = ProviderError::from_status("Claude", 429, Some(Duration::from_secs(3)), "x");
+        assert!(matches!(
+            limited,
+            ProviderError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_secs(3)
+        ));
+        assert_eq!(limited.status(), Some(429));
+
+        assert!(matches!(
+            ProviderError::from_status("Gemini", 408, None, "slow"),
+            ProviderError::Timeout { .. }
+        ));
+        let api = ProviderError::from_status("Ollama", 503, None, "down");
+        assert_eq!(api.status(), Some(503));
+        assert_eq!(api.to_string(), "Ollama API error (503): down");
+
+        // Existing callers keep working through anyhow.
+        let any: anyhow::Error = api.into();
+        assert_eq!(
+            any.downcast_ref::<ProviderError>().unwrap().status(),
+            Some(503)
+        );
+    }
+
+    #[test]
+    fn decode_keeps_provider_errors() {
+        let refused: anyhow::Error = ProviderError::Refused {
+            provider: "OpenAI".into(),
+            reason: "no".into(),
+        }
+        .into();
+        assert!(matches!(
+            ProviderError::decode("OpenAI", refused),
+            ProviderError::Refused { .. }
+        ));
+        assert!(matches!(
+            ProviderError::decode("OpenAI", anyhow::anyhow!("no choices")),
+            ProviderError::Decode { .. }
+        ));
+    }
+
+    #[test]
+    fn response_format_serialization() {
+        let format = ResponseFormat::JsonSchema {
+            schema: serde_json::json!({ "type": "object" }),
+        };
+        let json = serde_json::to_value(&format).unwrap();
+        assert_eq!(json["type"], "json_schema");
+        assert_eq!(json["schema"]["type"], "object");
+        let round: ResponseFormat = serde_json::from_value(json).unwrap();
+        assert_eq!(round, format);
+    }
+
     #[test]
     fn chat_message_serialization() {
         let msg = ChatMessage {
             role: ChatRole::User,
-            content: "hello".to_string(),
+            content: "hello".into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
         };
         let json = serde_json::to_string(&msg).unwrap();
         let round: ChatMessage = serde_json::from_str(&json).unwrap();
         assert_eq!(round.role, ChatRole::User);
         assert_eq!(round.content, "hello");
     }
+
+    #[test]
+    fn text_content_serializes_as_plain_string() {
+        let msg = ChatMessage {
+            role: ChatRole::User,
+            content: "hello".into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["content"], "hello");
+    }
+
+    #[test]
+    fn multimodal_content_roundtrip() {
+        let msg = ChatMessage {
+            role: ChatRole::User,
+            content: vec![
+                ContentPart::Text {
+                    text: "What is this?".to_string(),
+                },
+                ContentPart::Image {
+                    data: "aGVsbG8=".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+                ContentPart::ImageUrl {
+                    url: "https://example.com/cat.jpg".to_string(),
+                },
+            ]
+            .into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        let round: ChatMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(round.content, msg.content);
+        assert!(round.content.has_images());
+        assert_eq!(round.content.text(), "What is this?");
+    }
 }
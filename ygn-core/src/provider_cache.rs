@@ -0,0 +1,527 @@
+//! Response caching for deterministic provider calls.
+//!
+//! [`CachingProvider`] wraps any [`Provider`] and stores its responses in a
+//! SQLite-backed [`ResponseCache`], keyed by a SHA-256 hash of the request
+//! (provider, model, messages, tools and sampling parameters). Only
+//! deterministic calls are cached: the temperature must be unset or zero and
+//! the response must not contain tool calls. Entries expire after a TTL and
+//! the least recently used ones are evicted beyond `max_entries`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::provider::{
+    ChatRequest, ChatResponse, ChatStream, ProbeResult, Provider, ProviderCapabilities,
+    ProviderError,
+};
+use crate::schema::{self, Migration};
+use crate::tool::ToolSpec;
+
+/// Schema history of the `response_cache` table.
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create response_cache",
+    "CREATE TABLE IF NOT EXISTS response_cache (
+        key         TEXT PRIMARY KEY,
+        response    TEXT NOT NULL,
+        created_at  INTEGER NOT NULL,
+        last_used   INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_response_cache_last_used
+        ON response_cache(last_used);",
+)];
+
+/// Version of the `response_cache` schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Response cache settings in the node config.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Seconds a cached response stays valid.
+    pub ttl_secs: u64,
+    /// Maximum number of cached responses; least recently used are evicted.
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 3600,
+            max_entries: 1000,
+        }
+    }
+}
+
+/// Hit/miss counters for a [`CachingProvider`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Default on-disk location of the response cache (`~/.ygn/provider_cache.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/provider_cache.db")
+}
+
+// ---------------------------------------------------------------------------
+// ResponseCache
+// ---------------------------------------------------------------------------
+
+/// SQLite store of cached chat responses, shareable between providers.
+pub struct ResponseCache {
+    conn: Mutex<Connection>,
+    config: CacheConfig,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    /// Open (or create) a file-based response cache.
+    pub fn new(path: &str, config: CacheConfig) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open(path)?, config)
+    }
+
+    /// Create an in-memory response cache (useful for testing).
+    pub fn in_memory(config: CacheConfig) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, config)
+    }
+
+    fn with_connection(mut conn: Connection, config: CacheConfig) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config,
+        })
+    }
+
+    /// Look up a live entry, refreshing its LRU position. Expired entries
+    /// are removed and reported as misses.
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<ChatResponse>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let row: Option<(String, i64)> = conn
+            .query_row(
+                "SELECT response, created_at FROM response_cache WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((response, created_at)) = row else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        if now.timestamp() - created_at >= self.config.ttl_secs as i64 {
+            conn.execute("DELETE FROM response_cache WHERE key = ?1", params![key])?;
+            return Ok(None);
+        }
+        conn.execute(
+            "UPDATE response_cache SET last_used = ?1 WHERE key = ?2",
+            params![lru_tick(), key],
+        )?;
+        Ok(Some(serde_json::from_str(&response)?))
+    }
+
+    /// Store `response` under `key`, then evict the least recently used
+    /// entries beyond `max_entries`.
+    pub fn put(&self, key: &str, response: &ChatResponse) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO response_cache (key, response, created_at, last_used)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                key,
+                serde_json::to_string(response)?,
+                Utc::now().timestamp(),
+                lru_tick()
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM response_cache WHERE key IN (
+                SELECT key FROM response_cache ORDER BY last_used DESC LIMIT -1 OFFSET ?1
+            )",
+            params![self.config.max_entries as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Number of stored entries, including expired ones not yet looked up.
+    pub fn len(&self) -> anyhow::Result<usize> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM response_cache", [], |r| r.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> anyhow::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+}
+
+/// Monotonic-enough ordering value for LRU bookkeeping.
+fn lru_tick() -> i64 {
+    Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX)
+}
+
+// ---------------------------------------------------------------------------
+// CachingProvider
+// ---------------------------------------------------------------------------
+
+/// A [`Provider`] that serves repeated deterministic requests from a
+/// [`ResponseCache`] instead of calling the wrapped provider again.
+pub struct CachingProvider {
+    inner: Box<dyn Provider>,
+    cache: Arc<ResponseCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl std::fmt::Debug for CachingProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachingProvider")
+            .field("inner", &self.inner.name())
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CachingProvider {
+    /// Wrap `inner`, storing its responses in `cache`.
+    pub fn new(inner: Box<dyn Provider>, cache: Arc<ResponseCache>) -> Self {
+        Self {
+            inner,
+            cache,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Current hit/miss counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// SHA-256 over everything that determines the response.
+    fn cache_key(&self, request: &ChatRequest, tools: &[ToolSpec]) -> anyhow::Result<String> {
+        let mut material = serde_json::json!({
+            "provider": self.inner.name(),
+            "model": request.model,
+            "messages": request.messages,
+            "tools": tools,
+            "max_tokens": request.max_tokens,
+            "temperature": request.temperature,
+            "response_format": request.response_format,
+        });
+        // Added only when set, so keys of existing entries stay valid.
+        if let Some(stop) = &request.stop {
+            material["stop"] = serde_json::json!(stop);
+        }
+        if let Some(top_p) = request.top_p {
+            material["top_p"] = serde_json::json!(top_p);
+        }
+        if let Some(seed) = request.seed {
+            material["seed"] = serde_json::json!(seed);
+        }
+        let material = serde_json::to_vec(&material)?;
+        Ok(Sha256::digest(material)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    }
+
+    async fn cached_chat(
+        &self,
+        request: ChatRequest,
+        tools: Option<&[ToolSpec]>,
+    ) -> Result<ChatResponse, ProviderError> {
+        let call = |request| async {
+            match tools {
+                Some(tools) => self.inner.chat_with_tools(request, tools).await,
+                None => self.inner.chat(request).await,
+            }
+        };
+
+        if request.temperature.unwrap_or(0.0) != 0.0 {
+            return call(request).await;
+        }
+
+        let key = self.cache_key(&request, tools.unwrap_or_default())?;
+        match self.cache.get(&key) {
+            Ok(Some(mut response)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                response.cached = true;
+                return Ok(response);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "response cache lookup failed"),
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let response = call(request).await?;
+        if response.tool_calls.is_empty() {
+            if let Err(e) = self.cache.put(&key, &response) {
+                tracing::warn!(error = %e, "response cache write failed");
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Provider for CachingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        self.cached_chat(request, None).await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        request: ChatRequest,
+        tools: &[ToolSpec],
+    ) -> Result<ChatResponse, ProviderError> {
+        self.cached_chat(request, Some(tools)).await
+    }
+
+    async fn chat_stream(&self, request: ChatRequest) -> anyhow::Result<ChatStream> {
+        // Streams are passed through uncached.
+        self.inner.chat_stream(request).await
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        Some(self.stats())
+    }
+    async fn supports_model(&self, model: &str) -> bool {
+        self.inner.supports_model(model).await
+    }
+
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        // Embeddings are passed through uncached.
+        self.inner.embed(texts).await
+    }
+
+    async fn health_probe(&self) -> ProbeResult {
+        self.inner.health_probe().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{ChatMessage, ChatRole, StubProvider, ToolCall};
+
+    /// Counts calls and returns a fixed response.
+    struct CountingProvider {
+        calls: Arc<AtomicU64>,
+        tool_calls: Vec<ToolCall>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            StubProvider::default().capabilities()
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut response = StubProvider::default().chat(request).await?;
+            response.content = format!("call {n}");
+            response.tool_calls = self.tool_calls.clone();
+            Ok(response)
+        }
+
+        async fn chat_with_tools(
+            &self,
+            request: ChatRequest,
+            _tools: &[ToolSpec],
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(request).await
+        }
+    }
+
+    fn counting(tool_calls: Vec<ToolCall>) -> (Arc<AtomicU64>, Box<dyn Provider>) {
+        let calls = Arc::new(AtomicU64::new(0));
+        let provider = CountingProvider {
+            calls: calls.clone(),
+            tool_calls,
+        };
+        (calls, Box::new(provider))
+    }
+
+    fn cache(config: CacheConfig) -> Arc<ResponseCache> {
+        Arc::new(ResponseCache::in_memory(config).unwrap())
+    }
+
+    fn request(text: &str, temperature: Option<f64>) -> ChatRequest {
+        ChatRequest {
+            model: "stub".to_string(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: text.into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: Some(100),
+            temperature,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn second_identical_call_is_served_from_cache() {
+        let provider = CachingProvider::new(
+            Box::new(StubProvider::default()),
+            cache(CacheConfig::default()),
+        );
+        let first = provider.chat(request("Hi", None)).await.unwrap();
+        let second = provider.chat(request("Hi", None)).await.unwrap();
+
+        assert!(!first.cached);
+        assert!(second.cached);
+        assert_eq!(second.content, "Hello from StubProvider");
+        assert_eq!(provider.stats(), CacheStats { hits: 1, misses: 1 });
+        assert_eq!(provider.cache_stats(), Some(provider.stats()));
+    }
+
+    #[tokio::test]
+    async fn cache_hit_skips_inner_provider() {
+        let (calls, inner) = counting(vec![]);
+        let provider = CachingProvider::new(inner, cache(CacheConfig::default()));
+
+        provider.chat(request("Hi", Some(0.0))).await.unwrap();
+        let again = provider.chat(request("Hi", Some(0.0))).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(again.content, "call 1");
+
+        // A different prompt is a different key.
+        provider.chat(request("Bye", Some(0.0))).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn nonzero_temperature_bypasses_cache() {
+        let (calls, inner) = counting(vec![]);
+        let provider = CachingProvider::new(inner, cache(CacheConfig::default()));
+
+        provider.chat(request("Hi", Some(0.7))).await.unwrap();
+        provider.chat(request("Hi", Some(0.7))).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(provider.stats(), CacheStats::default());
+    }
+
+    #[tokio::test]
+    async fn responses_with_tool_calls_are_not_cached() {
+        let (calls, inner) = counting(vec![ToolCall {
+            tool_name: "echo".to_string(),
+            arguments: serde_json::json!({}),
+            id: None,
+        }]);
+        let provider = CachingProvider::new(inner, cache(CacheConfig::default()));
+
+        provider
+            .chat_with_tools(request("Hi", None), &[])
+            .await
+            .unwrap();
+        provider
+            .chat_with_tools(request("Hi", None), &[])
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_refetched() {
+        let (calls, inner) = counting(vec![]);
+        let provider = CachingProvider::new(
+            inner,
+            cache(CacheConfig {
+                ttl_secs: 0,
+                ..Default::default()
+            }),
+        );
+
+        provider.chat(request("Hi", None)).await.unwrap();
+        provider.chat(request("Hi", None)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_entry_is_evicted() {
+        let (calls, inner) = counting(vec![]);
+        let store = cache(CacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        let provider = CachingProvider::new(inner, store.clone());
+
+        provider.chat(request("a", None)).await.unwrap();
+        provider.chat(request("b", None)).await.unwrap();
+        // Touch "a" so "b" becomes the least recently used.
+        provider.chat(request("a", None)).await.unwrap();
+        provider.chat(request("c", None)).await.unwrap();
+        assert_eq!(store.len().unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        provider.chat(request("a", None)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        provider.chat(request("b", None)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn cached_flag_is_omitted_from_fresh_responses() {
+        let response = ChatResponse {
+            content: "hi".to_string(),
+            tool_calls: vec![],
+            usage: None,
+            cached: false,
+            model: None,
+            finish_reason: None,
+            alternates: Vec::new(),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json.get("cached").is_none());
+    }
+}
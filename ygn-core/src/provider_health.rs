@@ -2,22 +2,77 @@
 //!
 //! Records success/failure of LLM provider calls and exposes a simple
 //! circuit-breaker that disables providers with too many consecutive
-//! failures.
+//! failures. A [`HealthProber`] keeps the statuses current by probing every
+//! provider periodically, instead of waiting for real calls to fail, and a
+//! [`MonitoredProvider`] records the outcome of every real call.  Each
+//! outcome remembers whether it came from a probe or from real traffic.
 
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::multi_provider::ProviderRegistry;
+use crate::provider::{
+    ChatRequest, ChatResponse, ChatStream, ProbeResult, Provider, ProviderCapabilities,
+    ProviderError,
+};
+use crate::provider_cache::CacheStats;
+use crate::tool::ToolSpec;
+
+/// Longest a single provider probe may take in [`ProviderHealth::probe_all`].
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
 
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
 
+/// Where a recorded outcome came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSource {
+    /// A [`Provider::health_probe`].
+    Probe,
+    /// A real chat call.
+    Traffic,
+}
+
+/// When a provider changes state, in consecutive outcomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct HealthThresholds {
+    /// Consecutive failures that mark a provider unhealthy.
+    pub unhealthy_after: u32,
+    /// Consecutive successes that mark an unhealthy provider healthy again.
+    pub healthy_after: u32,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            unhealthy_after: 5,
+            healthy_after: 1,
+        }
+    }
+}
+
 /// Health status for a single LLM provider.
 #[derive(Debug, Clone)]
 pub struct HealthStatus {
     pub provider: String,
     pub healthy: bool,
     pub last_success: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether `last_success` was a probe or a real call.
+    pub last_success_source: Option<HealthSource>,
     pub last_failure: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether `last_failure` was a probe or a real call.
+    pub last_failure_source: Option<HealthSource>,
+    /// When the provider was last probed, whatever the outcome.
+    pub last_probe_at: Option<chrono::DateTime<chrono::Utc>>,
     pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
     pub total_requests: u64,
     pub total_failures: u64,
     /// Running average latency in milliseconds.
@@ -30,8 +85,12 @@ impl HealthStatus {
             provider: provider.to_string(),
             healthy: true,
             last_success: None,
+            last_success_source: None,
             last_failure: None,
+            last_failure_source: None,
+            last_probe_at: None,
             consecutive_failures: 0,
+            consecutive_successes: 0,
             total_requests: 0,
             total_failures: 0,
             avg_latency_ms: 0.0,
@@ -46,6 +105,7 @@ impl HealthStatus {
 /// Tracks health status of LLM providers.
 pub struct ProviderHealth {
     statuses: HashMap<String, HealthStatus>,
+    thresholds: HealthThresholds,
 }
 
 impl ProviderHealth {
@@ -53,11 +113,29 @@ impl ProviderHealth {
     pub fn new() -> Self {
         Self {
             statuses: HashMap::new(),
+            thresholds: HealthThresholds::default(),
         }
     }
 
+    /// Change state after the given runs of failures and successes instead
+    /// of the defaults.
+    pub fn with_thresholds(mut self, thresholds: HealthThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
     /// Record a successful call to the given provider.
     pub fn record_success(&mut self, provider: &str, latency_ms: f64) {
+        self.success(provider, latency_ms, HealthSource::Traffic);
+    }
+
+    /// Record a failed call to the given provider.
+    pub fn record_failure(&mut self, provider: &str, error_msg: &str) {
+        self.failure(provider, error_msg, HealthSource::Traffic);
+    }
+
+    fn success(&mut self, provider: &str, latency_ms: f64, source: HealthSource) {
+        let healthy_after = self.thresholds.healthy_after;
         let status = self
             .statuses
             .entry(provider.to_string())
@@ -65,8 +143,12 @@ impl ProviderHealth {
 
         status.total_requests += 1;
         status.consecutive_failures = 0;
-        status.healthy = true;
+        status.consecutive_successes += 1;
+        if status.consecutive_successes >= healthy_after {
+            status.healthy = true;
+        }
         status.last_success = Some(chrono::Utc::now());
+        status.last_success_source = Some(source);
 
         // Incremental average: avg = avg + (new - avg) / n
         let n = (status.total_requests - status.total_failures) as f64;
@@ -75,8 +157,8 @@ impl ProviderHealth {
         }
     }
 
-    /// Record a failed call to the given provider.
-    pub fn record_failure(&mut self, provider: &str, _error_msg: &str) {
+    fn failure(&mut self, provider: &str, _error_msg: &str, source: HealthSource) {
+        let unhealthy_after = self.thresholds.unhealthy_after;
         let status = self
             .statuses
             .entry(provider.to_string())
@@ -85,20 +167,65 @@ impl ProviderHealth {
         status.total_requests += 1;
         status.total_failures += 1;
         status.consecutive_failures += 1;
+        status.consecutive_successes = 0;
         status.last_failure = Some(chrono::Utc::now());
+        status.last_failure_source = Some(source);
 
-        if status.consecutive_failures >= 5 {
+        if status.consecutive_failures >= unhealthy_after {
             status.healthy = false;
         }
     }
 
-    /// Returns `true` if the provider is healthy (fewer than 5 consecutive
-    /// failures). An unknown provider is considered healthy.
+    /// Record the outcome of a [`crate::provider::Provider::health_probe`]
+    /// as a success or failure.
+    pub fn record_probe(&mut self, provider: &str, result: &ProbeResult) {
+        if result.up {
+            self.success(provider, result.latency_ms, HealthSource::Probe);
+        } else {
+            let error = result.error.as_deref().unwrap_or("probe failed");
+            self.failure(provider, error, HealthSource::Probe);
+        }
+        if let Some(status) = self.statuses.get_mut(provider) {
+            status.last_probe_at = Some(chrono::Utc::now());
+        }
+    }
+
+    /// Probe every provider in `providers` concurrently and record the
+    /// results in `health`. A probe that takes longer than
+    /// [`PROBE_TIMEOUT`] counts as a failure. The lock is only held while
+    /// recording.
+    pub async fn probe_all(health: &RwLock<ProviderHealth>, providers: &ProviderRegistry) {
+        let names = providers.list();
+        let results = futures_util::future::join_all(names.iter().filter_map(|name| {
+            let provider = providers.get(name)?;
+            Some(async move {
+                let result = tokio::time::timeout(PROBE_TIMEOUT, provider.health_probe())
+                    .await
+                    .unwrap_or_else(|_| ProbeResult {
+                        up: false,
+                        latency_ms: PROBE_TIMEOUT.as_secs_f64() * 1000.0,
+                        error: Some("probe timed out".to_string()),
+                    });
+                (*name, result)
+            })
+        }))
+        .await;
+
+        let mut health = health.write().unwrap_or_else(|e| e.into_inner());
+        for (name, result) in results {
+            if !result.up {
+                tracing::debug!(provider = name, error = ?result.error, "provider probe failed");
+            }
+            health.record_probe(name, &result);
+        }
+    }
+
+    /// Returns `true` if the provider is healthy: it has not failed
+    /// [`HealthThresholds::unhealthy_after`] times in a row, or has since
+    /// succeeded [`HealthThresholds::healthy_after`] times in a row. An
+    /// unknown provider is considered healthy.
     pub fn is_healthy(&self, provider: &str) -> bool {
-        self.statuses
-            .get(provider)
-            .map(|s| s.consecutive_failures < 5)
-            .unwrap_or(true)
+        self.statuses.get(provider).is_none_or(|s| s.healthy)
     }
 
     /// Get the full health status for a provider, if tracked.
@@ -149,6 +276,138 @@ impl std::fmt::Debug for ProviderHealth {
     }
 }
 
+// ---------------------------------------------------------------------------
+// HealthProber
+// ---------------------------------------------------------------------------
+
+/// Handle to a background task started by [`HealthProber::spawn`].
+#[derive(Debug)]
+pub struct HealthProber {
+    stop: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl HealthProber {
+    /// Probe every provider in `providers` now and then every `interval`,
+    /// recording the results in `health`.
+    pub fn spawn(
+        health: Arc<RwLock<ProviderHealth>>,
+        providers: Arc<ProviderRegistry>,
+        interval: Duration,
+    ) -> Self {
+        let (stop, mut stopped) = tokio::sync::oneshot::channel();
+        let join = tokio::spawn(async move {
+            loop {
+                // Stopping abandons a round still waiting on slow providers.
+                tokio::select! {
+                    _ = ProviderHealth::probe_all(&health, &providers) => {}
+                    _ = &mut stopped => break,
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = &mut stopped => break,
+                }
+            }
+        });
+        Self { stop, join }
+    }
+
+    /// Stop probing.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.join.await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MonitoredProvider
+// ---------------------------------------------------------------------------
+
+/// Wraps a [`Provider`] and records the latency and outcome of every chat
+/// call in a shared [`ProviderHealth`].  Probes are not recorded here;
+/// [`ProviderHealth::probe_all`] records those itself.
+pub struct MonitoredProvider {
+    inner: Box<dyn Provider>,
+    health: Arc<RwLock<ProviderHealth>>,
+}
+
+impl std::fmt::Debug for MonitoredProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MonitoredProvider")
+            .field("inner", &self.inner.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MonitoredProvider {
+    pub fn new(inner: Box<dyn Provider>, health: Arc<RwLock<ProviderHealth>>) -> Self {
+        Self { inner, health }
+    }
+
+    fn record<T, E: std::fmt::Display>(&self, started: Instant, result: &Result<T, E>) {
+        let mut health = self.health.write().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Ok(_) => {
+                health.record_success(self.inner.name(), started.elapsed().as_secs_f64() * 1000.0)
+            }
+            Err(e) => health.record_failure(self.inner.name(), &e.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for MonitoredProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let started = Instant::now();
+        let result = self.inner.chat(request).await;
+        self.record(started, &result);
+        result
+    }
+
+    async fn chat_with_tools(
+        &self,
+        request: ChatRequest,
+        tools: &[ToolSpec],
+    ) -> Result<ChatResponse, ProviderError> {
+        let started = Instant::now();
+        let result = self.inner.chat_with_tools(request, tools).await;
+        self.record(started, &result);
+        result
+    }
+
+    async fn chat_stream(&self, request: ChatRequest) -> anyhow::Result<ChatStream> {
+        // Only opening the stream counts; errors mid-stream are not seen here.
+        let started = Instant::now();
+        let result = self.inner.chat_stream(request).await;
+        self.record(started, &result);
+        result
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        self.inner.cache_stats()
+    }
+
+    async fn supports_model(&self, model: &str) -> bool {
+        self.inner.supports_model(model).await
+    }
+
+    async fn embed(&self, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+        self.inner.embed(texts).await
+    }
+
+    async fn health_probe(&self) -> ProbeResult {
+        self.inner.health_probe().await
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -257,6 +516,107 @@ mod tests {
         assert_eq!(names, vec!["claude", "gemini", "openai"]);
     }
 
+    /// Answers probes with a fixed outcome.
+    struct ProbedProvider {
+        name: &'static str,
+        up: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::provider::Provider for ProbedProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn capabilities(&self) -> crate::provider::ProviderCapabilities {
+            crate::provider::ProviderCapabilities {
+                native_tool_calling: false,
+                vision: false,
+                streaming: false,
+            }
+        }
+
+        async fn chat(
+            &self,
+            _request: crate::provider::ChatRequest,
+        ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+            Err(anyhow::anyhow!("not used").into())
+        }
+
+        async fn chat_with_tools(
+            &self,
+            _request: crate::provider::ChatRequest,
+            _tools: &[crate::tool::ToolSpec],
+        ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+            Err(anyhow::anyhow!("not used").into())
+        }
+
+        async fn health_probe(&self) -> ProbeResult {
+            let up = self.up;
+            ProbeResult::measure(async move {
+                if up {
+                    Ok(())
+                } else {
+                    anyhow::bail!("connection refused")
+                }
+            })
+            .await
+        }
+    }
+
+    fn probed_registry() -> ProviderRegistry {
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(ProbedProvider {
+            name: "up",
+            up: true,
+        }));
+        providers.register(Box::new(ProbedProvider {
+            name: "down",
+            up: false,
+        }));
+        providers
+    }
+
+    #[tokio::test]
+    async fn probe_all_records_up_and_down() {
+        let health = RwLock::new(ProviderHealth::new());
+        let providers = probed_registry();
+        for _ in 0..5 {
+            ProviderHealth::probe_all(&health, &providers).await;
+        }
+
+        let health = health.read().unwrap();
+        let up = health.get_status("up").unwrap();
+        assert_eq!(up.total_requests, 5);
+        assert_eq!(up.consecutive_failures, 0);
+        assert!(up.last_success.is_some());
+        let down = health.get_status("down").unwrap();
+        assert_eq!(down.consecutive_failures, 5);
+        assert!(!health.is_healthy("down"));
+        assert!(health.is_healthy("up"));
+    }
+
+    #[tokio::test]
+    async fn prober_probes_immediately_and_stops() {
+        let health = Arc::new(RwLock::new(ProviderHealth::new()));
+        let prober = HealthProber::spawn(
+            health.clone(),
+            Arc::new(probed_registry()),
+            Duration::from_secs(3600),
+        );
+        for _ in 0..100 {
+            if health.read().unwrap().all_statuses().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        prober.shutdown().await;
+
+        let health = health.read().unwrap();
+        assert_eq!(health.get_status("up").unwrap().total_requests, 1);
+        assert_eq!(health.get_status("down").unwrap().consecutive_failures, 1);
+    }
+
     #[test]
     fn healthy_after_recovery() {
         let mut health = ProviderHealth::new();
@@ -271,4 +631,135 @@ mod tests {
         assert!(health.is_healthy("claude"));
         assert!(!health.circuit_breaker("claude"));
     }
+
+    #[test]
+    fn thresholds_add_hysteresis() {
+        let mut health = ProviderHealth::new().with_thresholds(HealthThresholds {
+            unhealthy_after: 2,
+            healthy_after: 3,
+        });
+        health.record_failure("claude", "error");
+        assert!(health.is_healthy("claude"));
+        health.record_failure("claude", "error");
+        assert!(!health.is_healthy("claude"));
+
+        health.record_success("claude", 10.0);
+        health.record_success("claude", 10.0);
+        assert!(!health.is_healthy("claude"));
+        health.record_success("claude", 10.0);
+        assert!(health.is_healthy("claude"));
+        let status = health.get_status("claude").unwrap();
+        assert_eq!(status.consecutive_successes, 3);
+        assert_eq!(status.last_success_source, Some(HealthSource::Traffic));
+        assert!(status.last_probe_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn probes_flip_provider_once_endpoint_recovers() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        use axum::http::StatusCode;
+
+        // Fails the first two probes, then succeeds.
+        let calls = Arc::new(AtomicU32::new(0));
+        let app = axum::Router::new().route(
+            "/api/tags",
+            axum::routing::get(move || {
+                let calls = calls.clone();
+                async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err(StatusCode::SERVICE_UNAVAILABLE)
+                    } else {
+                        Ok(axum::Json(serde_json::json!({ "models": [] })))
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(crate::multi_provider::OllamaProvider::new(
+            crate::multi_provider::OllamaConfig {
+                model: "llama3".into(),
+                base_url: Some(format!("http://{addr}")),
+            },
+        )));
+        let health = RwLock::new(ProviderHealth::new().with_thresholds(HealthThresholds {
+            unhealthy_after: 2,
+            healthy_after: 2,
+        }));
+
+        let mut healthy = Vec::new();
+        for _ in 0..4 {
+            ProviderHealth::probe_all(&health, &providers).await;
+            healthy.push(health.read().unwrap().is_healthy("ollama"));
+        }
+        assert_eq!(healthy, [true, false, false, true]);
+
+        let health = health.read().unwrap();
+        let status = health.get_status("ollama").unwrap();
+        assert_eq!(status.last_success_source, Some(HealthSource::Probe));
+        assert_eq!(status.last_failure_source, Some(HealthSource::Probe));
+        assert!(status.last_probe_at.is_some());
+    }
+
+    fn empty_request() -> crate::provider::ChatRequest {
+        crate::provider::ChatRequest {
+            model: "m".into(),
+            messages: vec![],
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn monitored_provider_records_failed_call() {
+        let health = Arc::new(RwLock::new(ProviderHealth::new()));
+        let provider = MonitoredProvider::new(
+            Box::new(ProbedProvider {
+                name: "down",
+                up: false,
+            }),
+            health.clone(),
+        );
+        assert!(provider.chat(empty_request()).await.is_err());
+
+        let health = health.read().unwrap();
+        let status = health.get_status("down").unwrap();
+        assert_eq!(status.total_requests, 1);
+        assert_eq!(status.total_failures, 1);
+        assert_eq!(status.consecutive_failures, 1);
+        assert!(status.last_failure.is_some());
+    }
+
+    #[tokio::test]
+    async fn monitored_provider_records_success_and_resets_failures() {
+        let health = Arc::new(RwLock::new(ProviderHealth::new()));
+        health
+            .write()
+            .unwrap()
+            .record_failure("stub", "earlier outage");
+        let provider = MonitoredProvider::new(
+            Box::new(crate::provider::StubProvider::default()),
+            health.clone(),
+        );
+        provider
+            .chat_with_tools(empty_request(), &[])
+            .await
+            .unwrap();
+
+        let health = health.read().unwrap();
+        let status = health.get_status("stub").unwrap();
+        assert_eq!(status.total_requests, 2);
+        assert_eq!(status.total_failures, 1);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(status.avg_latency_ms >= 0.0);
+    }
 }
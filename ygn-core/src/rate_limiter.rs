@@ -1,12 +1,17 @@
-//! Token-bucket rate limiter for LLM API calls.
+//! Token-bucket rate limiters.
 //!
-//! Each provider has its own bucket with configurable rate and burst
-//! capacity. No external crates — pure hand-rolled token bucket.
+//! [`RateLimiter`] throttles outbound LLM API calls: each provider has its own
+//! bucket with configurable rate and burst capacity.  [`GatewayRateLimiter`]
+//! throttles inbound gateway requests per route and per client, and is safe
+//! to share across worker tasks.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::time::{Duration, Instant};
 
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
 // ---------------------------------------------------------------------------
 // Error type
 // ---------------------------------------------------------------------------
@@ -71,9 +76,15 @@ impl TokenBucket {
 
     /// Try to consume one token. Returns Ok(()) or the duration to wait.
     fn try_acquire(&mut self) -> Result<(), Duration> {
+        self.peek()?;
+        self.available -= 1.0;
+        Ok(())
+    }
+
+    /// Like [`try_acquire`](Self::try_acquire), without consuming the token.
+    fn peek(&mut self) -> Result<(), Duration> {
         self.refill();
         if self.available >= 1.0 {
-            self.available -= 1.0;
             Ok(())
         } else {
             // How long until one token is available?
@@ -208,6 +219,130 @@ impl fmt::Debug for RateLimiter {
     }
 }
 
+// ---------------------------------------------------------------------------
+// GatewayRateLimiter
+// ---------------------------------------------------------------------------
+
+/// Requests per minute and burst capacity for one gateway route.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RouteLimit {
+    /// Sustained rate, refilled continuously.
+    pub requests_per_minute: u32,
+    /// Requests a client may make back to back before being throttled.
+    pub burst: u32,
+}
+
+/// Gateway rate-limit settings (`rate_limit` in [`NodeConfig`]).
+///
+/// Routes are matched by exact request path.  With no `default` and no
+/// `routes`, nothing is limited.
+///
+/// [`NodeConfig`]: crate::config::NodeConfig
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct GatewayLimitConfig {
+    /// Limit for routes without their own entry; those routes share one
+    /// bucket per client.
+    pub default: Option<RouteLimit>,
+    /// Per-route limits keyed by path, e.g. `/mcp`.
+    pub routes: BTreeMap<String, RouteLimit>,
+    /// Paths that are never limited.
+    pub exempt: Vec<String>,
+    /// Key clients by the API key they authenticated with rather than their
+    /// IP address.  Requests without a configured key, and requests that
+    /// fail authentication, are keyed by IP.
+    pub key_by_api_key: bool,
+}
+
+impl Default for GatewayLimitConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            routes: BTreeMap::new(),
+            exempt: vec!["/health".to_string(), "/health/providers".to_string()],
+            key_by_api_key: false,
+        }
+    }
+}
+
+/// Number of tracked buckets above which idle (full) buckets are evicted.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Per-route, per-client token buckets for the gateway.
+#[derive(Debug, Default)]
+pub struct GatewayRateLimiter {
+    config: GatewayLimitConfig,
+    /// Buckets keyed by `(route scope, client key)`.
+    buckets: DashMap<(String, String), TokenBucket>,
+}
+
+impl GatewayRateLimiter {
+    pub fn new(config: GatewayLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &GatewayLimitConfig {
+        &self.config
+    }
+
+    /// Consume one request for `client` on `path`.  Returns the time until
+    /// the next request would be admitted when the client is throttled.
+    pub fn check(&self, path: &str, client: &str) -> Result<(), Duration> {
+        self.with_bucket(path, client, TokenBucket::try_acquire)
+    }
+
+    /// Whether [`check`](Self::check) would admit `client` on `path`,
+    /// without consuming a request.
+    pub fn peek(&self, path: &str, client: &str) -> Result<(), Duration> {
+        self.with_bucket(path, client, TokenBucket::peek)
+    }
+
+    /// Run `f` on `client`'s bucket for `path`; unlimited paths pass.
+    fn with_bucket(
+        &self,
+        path: &str,
+        client: &str,
+        f: fn(&mut TokenBucket) -> Result<(), Duration>,
+    ) -> Result<(), Duration> {
+        if self.config.exempt.iter().any(|p| p == path) {
+            return Ok(());
+        }
+        let (scope, limit) = match self.config.routes.get_key_value(path) {
+            Some((route, limit)) => (route.as_str(), limit),
+            None => match &self.config.default {
+                Some(limit) => ("*", limit),
+                None => return Ok(()),
+            },
+        };
+        if self.buckets.len() > MAX_TRACKED_BUCKETS {
+            self.evict_idle();
+        }
+        let rate = f64::from(limit.requests_per_minute) / 60.0;
+        let mut bucket = self
+            .buckets
+            .entry((scope.to_string(), client.to_string()))
+            .or_insert_with(|| TokenBucket::new(rate, limit.burst.max(1)));
+        f(&mut bucket)
+    }
+
+    /// Number of client buckets currently tracked.
+    pub fn tracked(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Drop buckets that have refilled completely; recreating them later is
+    /// indistinguishable from keeping them.
+    fn evict_idle(&self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.refill();
+            bucket.available < bucket.capacity as f64
+        });
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -352,4 +487,80 @@ mod tests {
         let wait = limiter.wait_and_acquire("test");
         assert!(wait > Duration::ZERO);
     }
+
+    // -- gateway limiter ----------------------------------------------------
+
+    fn gateway_config(rpm: u32, burst: u32) -> GatewayLimitConfig {
+        GatewayLimitConfig {
+            default: Some(RouteLimit {
+                requests_per_minute: rpm,
+                burst,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gateway_limits_per_client() {
+        let limiter = GatewayRateLimiter::new(gateway_config(60, 2));
+        assert!(limiter.check("/mcp", "1.2.3.4").is_ok());
+        assert!(limiter.check("/mcp", "1.2.3.4").is_ok());
+        let wait = limiter.check("/mcp", "1.2.3.4").unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(1));
+        // Another client has its own bucket.
+        assert!(limiter.check("/mcp", "5.6.7.8").is_ok());
+    }
+
+    #[test]
+    fn gateway_peek_does_not_consume() {
+        let limiter = GatewayRateLimiter::new(gateway_config(60, 1));
+        assert!(limiter.peek("/mcp", "1.2.3.4").is_ok());
+        assert!(limiter.peek("/mcp", "1.2.3.4").is_ok());
+        assert!(limiter.check("/mcp", "1.2.3.4").is_ok());
+        assert!(limiter.peek("/mcp", "1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn gateway_route_limit_overrides_default() {
+        let mut config = gateway_config(60, 1);
+        config.routes.insert(
+            "/v1/chat/completions".into(),
+            RouteLimit {
+                requests_per_minute: 60,
+                burst: 3,
+            },
+        );
+        let limiter = GatewayRateLimiter::new(config);
+        for _ in 0..3 {
+            assert!(limiter.check("/v1/chat/completions", "c").is_ok());
+        }
+        assert!(limiter.check("/v1/chat/completions", "c").is_err());
+        // Unlisted routes share the default bucket.
+        assert!(limiter.check("/mcp", "c").is_ok());
+        assert!(limiter.check("/a2a", "c").is_err());
+    }
+
+    #[test]
+    fn gateway_exempt_and_unconfigured_paths_pass() {
+        let limiter = GatewayRateLimiter::new(gateway_config(60, 1));
+        for _ in 0..5 {
+            assert!(limiter.check("/health", "c").is_ok());
+        }
+        let open = GatewayRateLimiter::new(GatewayLimitConfig::default());
+        for _ in 0..5 {
+            assert!(open.check("/mcp", "c").is_ok());
+        }
+        assert_eq!(open.tracked(), 0);
+    }
+
+    #[test]
+    fn gateway_evicts_idle_buckets() {
+        let limiter = GatewayRateLimiter::new(gateway_config(6000, 1));
+        limiter.check("/mcp", "busy").unwrap();
+        limiter.check("/mcp", "idle").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        limiter.check("/mcp", "busy").unwrap();
+        limiter.evict_idle();
+        assert_eq!(limiter.tracked(), 1);
+    }
 }
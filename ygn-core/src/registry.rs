@@ -3,12 +3,23 @@
 //! Provides the [`NodeRegistry`] trait for registering, discovering, and
 //! managing nodes in the Yggdrasil-Grid Nexus distributed runtime, along
 //! with an [`InMemoryRegistry`] implementation backed by a `Mutex<HashMap>`.
+//! Changes can be observed through [`NodeRegistry::subscribe`], and
+//! [`NodeRegistry::select_for`] picks one node to send a call to.
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::config::NodeConfig;
+use crate::tool::ToolRegistry;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -36,7 +47,7 @@ impl std::fmt::Display for NodeRole {
 }
 
 /// Trust level assigned to a node.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TrustTier {
     Trusted,
@@ -62,7 +73,7 @@ pub struct Endpoint {
 }
 
 /// Metadata describing a node in the grid.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NodeInfo {
     /// Unique identifier for this node (UUID).
     pub node_id: String,
@@ -72,7 +83,8 @@ pub struct NodeInfo {
     pub endpoints: Vec<Endpoint>,
     /// Trust level of this node.
     pub trust_tier: TrustTier,
-    /// Tool names this node can execute.
+    /// Tool names this node can execute, optionally versioned as
+    /// `name@semver` (e.g. `echo@1.2.0`).
     pub capabilities: Vec<String>,
     /// Last time this node was seen (heartbeat timestamp).
     pub last_seen: DateTime<Utc>,
@@ -80,6 +92,48 @@ pub struct NodeInfo {
     pub metadata: serde_json::Value,
 }
 
+/// Nodes seen longer ago than this are skipped by
+/// [`NodeRegistry::select_for`]: three default heartbeat intervals.
+pub const DEFAULT_MAX_STALENESS_SECS: u64 = 90;
+
+/// Endpoint protocols in the order [`NodeRegistry::select_for`] prefers
+/// them; other protocols come last.
+const ENDPOINT_PREFERENCE: &[&str] = &["mcp", "uacp", "http"];
+
+/// How [`NodeRegistry::select_for`] picks among the nodes that qualify.
+#[derive(Debug, Clone)]
+pub enum DispatchStrategy {
+    /// The node with the lowest ID.
+    FirstTrusted,
+    /// The node whose last heartbeat is oldest; ties go to the lowest ID.
+    LeastRecentlySeen,
+    /// Each call takes the next node by ID, advancing the shared counter.
+    RoundRobin(Arc<AtomicUsize>),
+}
+
+impl DispatchStrategy {
+    /// Round robin starting from the node with the lowest ID.
+    pub fn round_robin() -> Self {
+        Self::RoundRobin(Arc::default())
+    }
+}
+
+/// The node [`NodeRegistry::select_for`] picked, and how to reach it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSelection {
+    pub node: NodeInfo,
+    pub endpoint: Endpoint,
+}
+
+/// The endpoint of `node` to call: the first by [`ENDPOINT_PREFERENCE`],
+/// else its first endpoint.
+pub fn best_endpoint(node: &NodeInfo) -> Option<&Endpoint> {
+    ENDPOINT_PREFERENCE
+        .iter()
+        .find_map(|protocol| node.endpoints.iter().find(|e| e.protocol == *protocol))
+        .or_else(|| node.endpoints.first())
+}
+
 /// Filter criteria for node discovery.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiscoveryFilter {
@@ -87,11 +141,294 @@ pub struct DiscoveryFilter {
     pub role: Option<NodeRole>,
     /// Filter by trust tier.
     pub trust_tier: Option<TrustTier>,
-    /// Filter by capability (tool name the node must support).
+    /// Filter by capability (tool name the node must support), optionally
+    /// with a version constraint: `echo`, `echo@1.2.0` or `echo>=1.1`.
+    /// See [`CapabilityRequirement`].
     pub capability: Option<String>,
     /// Maximum staleness in seconds — nodes whose `last_seen` is older than
     /// `now - max_staleness_seconds` are excluded.
     pub max_staleness_seconds: Option<u64>,
+    /// Order of the returned nodes. Unordered when unset, unless `limit` or
+    /// `offset` is given, in which case nodes are ordered by ID.
+    pub sort_by: Option<SortBy>,
+    /// Maximum number of nodes to return.
+    pub limit: Option<usize>,
+    /// Number of matching nodes to skip before returning results.
+    pub offset: Option<usize>,
+}
+
+/// Sort order for [`DiscoveryFilter::sort_by`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// Most recently seen first; ties broken by node ID.
+    LastSeenDesc,
+    /// Ascending node ID.
+    NodeId,
+}
+
+impl std::fmt::Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortBy::LastSeenDesc => write!(f, "last_seen_desc"),
+            SortBy::NodeId => write!(f, "node_id"),
+        }
+    }
+}
+
+/// Additive changes applied by [`NodeRegistry::update`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodePatch {
+    /// Capabilities to add; existing ones are kept, except that one with
+    /// the same name (e.g. `echo@1.0.0` for a patched `echo@2.0.0`) is
+    /// replaced in place.
+    pub capabilities: Vec<String>,
+    /// Metadata deep-merged into the node's existing metadata.
+    pub metadata: serde_json::Value,
+}
+
+impl NodePatch {
+    /// Apply this patch to `node` and mark it as seen now.
+    pub fn apply(self, node: &mut NodeInfo) {
+        for cap in self.capabilities {
+            let name = parse_capability(&cap).0;
+            let mut same_name = node
+                .capabilities
+                .iter()
+                .enumerate()
+                .filter(|(_, existing)| parse_capability(existing).0 == name)
+                .map(|(i, _)| i);
+            match same_name.next() {
+                Some(first) => {
+                    let duplicates: Vec<usize> = same_name.collect();
+                    node.capabilities[first] = cap;
+                    for i in duplicates.into_iter().rev() {
+                        node.capabilities.remove(i);
+                    }
+                }
+                None => node.capabilities.push(cap),
+            }
+        }
+        merge_json(&mut node.metadata, self.metadata);
+        node.last_seen = Utc::now();
+    }
+}
+
+/// Changes a node announces along with a heartbeat, applied by
+/// [`NodeRegistry::heartbeat_with`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeartbeatUpdate {
+    /// Capabilities the node gained, merged as by [`NodePatch`].
+    pub add_capabilities: Vec<String>,
+    /// Metadata deep-merged into the node's existing metadata.
+    pub metadata_patch: serde_json::Value,
+}
+
+impl From<HeartbeatUpdate> for NodePatch {
+    fn from(update: HeartbeatUpdate) -> Self {
+        Self {
+            capabilities: update.add_capabilities,
+            metadata: update.metadata_patch,
+        }
+    }
+}
+
+/// Deep-merge `patch` into `target`: objects are merged key by key, any
+/// other patch value (except `null`, which is ignored) replaces the target.
+fn merge_json(target: &mut serde_json::Value, patch: serde_json::Value) {
+    use serde_json::Value;
+    match (target, patch) {
+        (_, Value::Null) => {}
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch.into_iter().filter(|(_, v)| !v.is_null()) {
+                merge_json(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// A change to the set of registered nodes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RegistryEvent {
+    /// A node was registered, or its registration was updated.
+    Registered { node: NodeInfo },
+    /// A node was explicitly removed from the registry.
+    Deregistered { node_id: String },
+    /// A node's registration lapsed: it was evicted as stale, or it sent a
+    /// heartbeat after the registry had already forgotten it.
+    HeartbeatExpired { node_id: String },
+}
+
+/// Stream of events returned by [`NodeRegistry::subscribe`].
+pub type RegistryEventStream = Pin<Box<dyn Stream<Item = RegistryEvent> + Send>>;
+
+/// Registry settings in the node config.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[serde(default)]
+pub struct RegistryConfig {
+    /// Base URL of a remote gateway whose registry this node joins
+    /// (e.g. "http://10.0.0.5:3000"). Heartbeats are disabled when unset.
+    pub remote_url: Option<String>,
+    /// `host:port` other nodes should use to reach this one. Defaults to
+    /// the gateway's bound address, which must then be a specific IP.
+    pub advertise_address: Option<String>,
+    /// Seconds between heartbeats.
+    pub heartbeat_interval_secs: u64,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            remote_url: None,
+            advertise_address: None,
+            heartbeat_interval_secs: 30,
+        }
+    }
+}
+
+/// Split a node capability into its name and optional version.
+///
+/// `echo@1.2.0` yields `("echo", Some(1.2.0))`; partial versions such as
+/// `echo@1.2` are padded with zeros. A bare or unparseable version yields
+/// `None`.
+pub fn parse_capability(capability: &str) -> (&str, Option<semver::Version>) {
+    match capability.split_once('@') {
+        Some((name, version)) => (name, parse_version_lenient(version)),
+        None => (capability, None),
+    }
+}
+
+fn parse_version_lenient(version: &str) -> Option<semver::Version> {
+    let version = version.trim();
+    let parts = version.split('.').count();
+    let padded = match parts {
+        1 => format!("{version}.0.0"),
+        2 => format!("{version}.0"),
+        _ => version.to_string(),
+    };
+    semver::Version::parse(&padded).ok()
+}
+
+/// A capability a discovered node must have, with an optional version
+/// constraint.
+///
+/// Parsed from `name`, `name@version` (exact match) or `name<op>version`
+/// where the constraint uses semver requirement syntax, e.g. `echo>=1.1` or
+/// `echo^1.2`. A bare name matches any version, including unversioned
+/// capabilities; a constrained requirement only matches capabilities that
+/// carry a satisfying version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityRequirement {
+    pub name: String,
+    pub version: Option<semver::VersionReq>,
+}
+
+impl CapabilityRequirement {
+    /// Parse a requirement. Strings whose constraint is not valid semver are
+    /// treated as bare names, so they keep matching by exact string.
+    pub fn parse(requirement: &str) -> Self {
+        let bare = || Self {
+            name: requirement.to_string(),
+            version: None,
+        };
+        let Some(idx) = requirement.find(['@', '=', '<', '>', '^', '~']) else {
+            return bare();
+        };
+        let (name, constraint) = requirement.split_at(idx);
+        let constraint = match constraint.strip_prefix('@') {
+            Some(version) => format!("={version}"),
+            None => constraint.to_string(),
+        };
+        match semver::VersionReq::parse(&constraint) {
+            Ok(version) if !name.is_empty() => Self {
+                name: name.to_string(),
+                version: Some(version),
+            },
+            _ => bare(),
+        }
+    }
+
+    /// Whether a single node capability satisfies this requirement.
+    pub fn is_satisfied_by(&self, capability: &str) -> bool {
+        let (name, version) = parse_capability(capability);
+        if name != self.name {
+            return false;
+        }
+        match (&self.version, version) {
+            (None, _) => true,
+            (Some(req), Some(version)) => req.matches(&version),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Whether any of `capabilities` satisfies this requirement.
+    pub fn is_satisfied_by_any(&self, capabilities: &[String]) -> bool {
+        capabilities.iter().any(|c| self.is_satisfied_by(c))
+    }
+}
+
+impl DiscoveryFilter {
+    /// Whether `node` satisfies every criterion of this filter at time `now`.
+    pub fn matches(&self, node: &NodeInfo, now: DateTime<Utc>) -> bool {
+        // Role filter
+        if let Some(ref role) = self.role {
+            if &node.role != role {
+                return false;
+            }
+        }
+        // Trust tier filter
+        if let Some(ref tier) = self.trust_tier {
+            if &node.trust_tier != tier {
+                return false;
+            }
+        }
+        // Capability filter
+        if let Some(ref cap) = self.capability {
+            if !CapabilityRequirement::parse(cap).is_satisfied_by_any(&node.capabilities) {
+                return false;
+            }
+        }
+        // Staleness filter
+        if let Some(max_secs) = self.max_staleness_seconds {
+            let age = now
+                .signed_duration_since(node.last_seen)
+                .num_seconds()
+                .max(0) as u64;
+            if age > max_secs {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The sort order to apply: `sort_by`, or [`SortBy::NodeId`] when the
+    /// results are paginated, so pages are stable.
+    pub fn effective_sort(&self) -> Option<SortBy> {
+        self.sort_by
+            .or_else(|| (self.limit.is_some() || self.offset.is_some()).then_some(SortBy::NodeId))
+    }
+
+    /// Sort and paginate already-filtered `nodes`.
+    pub fn paginate(&self, mut nodes: Vec<NodeInfo>) -> Vec<NodeInfo> {
+        match self.effective_sort() {
+            Some(SortBy::LastSeenDesc) => nodes.sort_by(|a, b| {
+                b.last_seen
+                    .cmp(&a.last_seen)
+                    .then_with(|| a.node_id.cmp(&b.node_id))
+            }),
+            Some(SortBy::NodeId) => nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id)),
+            None => {}
+        }
+        nodes
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -116,16 +453,123 @@ pub trait NodeRegistry: Send + Sync {
 
     /// Look up a single node by ID.
     async fn get(&self, node_id: &str) -> anyhow::Result<Option<NodeInfo>>;
+
+    /// Merge `patch` into an existing node and refresh its `last_seen`.
+    ///
+    /// Unlike [`register`](Self::register), which replaces the node, this
+    /// only adds capabilities and deep-merges metadata. Errors with
+    /// "Node not found" if the node is not registered.
+    async fn update(&self, node_id: &str, patch: NodePatch) -> anyhow::Result<NodeInfo> {
+        let mut node = self
+            .get(node_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Node not found: {node_id}"))?;
+        patch.apply(&mut node);
+        self.register(node.clone()).await?;
+        Ok(node)
+    }
+
+    /// Refresh a node's `last_seen` and merge in the capabilities and
+    /// metadata it announces, in one step, so readers never see the new
+    /// timestamp with stale capabilities or the reverse.  Returns the
+    /// updated node; errors with "Node not found" like
+    /// [`heartbeat`](Self::heartbeat).
+    ///
+    /// Goes through [`update`](Self::update), so it is atomic wherever
+    /// that is.
+    async fn heartbeat_with(
+        &self,
+        node_id: &str,
+        update: HeartbeatUpdate,
+    ) -> anyhow::Result<NodeInfo> {
+        self.update(node_id, update.into()).await
+    }
+
+    /// Remove nodes whose `last_seen` is older than `max_staleness_seconds`.
+    /// Returns the number of evicted nodes.
+    ///
+    /// Backends that do not own their node data evict nothing.
+    async fn evict_stale(&self, _max_staleness_seconds: u64) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
+    /// Subscribe to changes made after this call.
+    ///
+    /// Backends that cannot observe changes return an empty stream.
+    fn subscribe(&self) -> RegistryEventStream {
+        Box::pin(futures_util::stream::empty())
+    }
+
+    /// Pick one node to send a call needing `capability` to, with the
+    /// endpoint to reach it on.
+    ///
+    /// Only nodes seen within [`DEFAULT_MAX_STALENESS_SECS`] that have an
+    /// endpoint qualify.  Trusted nodes are chosen over untrusted ones
+    /// whenever any qualify; `strategy` picks among the rest.  `None` when
+    /// no node qualifies.
+    async fn select_for(
+        &self,
+        capability: &str,
+        strategy: &DispatchStrategy,
+    ) -> anyhow::Result<Option<NodeSelection>> {
+        let mut nodes = self
+            .discover(DiscoveryFilter {
+                capability: Some(capability.to_string()),
+                max_staleness_seconds: Some(DEFAULT_MAX_STALENESS_SECS),
+                sort_by: Some(SortBy::NodeId),
+                ..Default::default()
+            })
+            .await?;
+        nodes.retain(|node| !node.endpoints.is_empty());
+        if nodes
+            .iter()
+            .any(|node| node.trust_tier == TrustTier::Trusted)
+        {
+            nodes.retain(|node| node.trust_tier == TrustTier::Trusted);
+        }
+        if nodes.is_empty() {
+            return Ok(None);
+        }
+        let index = match strategy {
+            DispatchStrategy::FirstTrusted => 0,
+            DispatchStrategy::LeastRecentlySeen => nodes
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, node)| node.last_seen)
+                .map_or(0, |(i, _)| i),
+            DispatchStrategy::RoundRobin(next) => {
+                next.fetch_add(1, Ordering::Relaxed) % nodes.len()
+            }
+        };
+        let node = nodes.swap_remove(index);
+        let endpoint = best_endpoint(&node)
+            .cloned()
+            .expect("nodes without endpoints were skipped");
+        Ok(Some(NodeSelection { node, endpoint }))
+    }
 }
 
 // ---------------------------------------------------------------------------
 // InMemoryRegistry
 // ---------------------------------------------------------------------------
 
+/// Events buffered per subscriber before the slowest one starts lagging.
+const EVENT_CAPACITY: usize = 64;
+
 /// A simple in-process node registry backed by a `Mutex<HashMap>`.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct InMemoryRegistry {
     nodes: Mutex<HashMap<String, NodeInfo>>,
+    events: broadcast::Sender<RegistryEvent>,
+}
+
+impl Default for InMemoryRegistry {
+    fn default() -> Self {
+        Self {
+            nodes: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
 }
 
 impl InMemoryRegistry {
@@ -133,19 +577,52 @@ impl InMemoryRegistry {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn publish(&self, event: RegistryEvent) {
+        // No subscribers is not an error.
+        let _ = self.events.send(event);
+    }
+}
+
+/// Adapt a broadcast receiver into a [`RegistryEventStream`].
+///
+/// A subscriber that falls behind skips the events it missed rather than
+/// ending the stream.
+fn broadcast_stream(rx: broadcast::Receiver<RegistryEvent>) -> RegistryEventStream {
+    futures_util::stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some((event, rx)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "registry subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .boxed()
 }
 
 #[async_trait]
 impl NodeRegistry for InMemoryRegistry {
     async fn register(&self, node: NodeInfo) -> anyhow::Result<()> {
         let mut map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        map.insert(node.node_id.clone(), node);
+        map.insert(node.node_id.clone(), node.clone());
+        drop(map);
+        self.publish(RegistryEvent::Registered { node });
         Ok(())
     }
 
     async fn deregister(&self, node_id: &str) -> anyhow::Result<bool> {
         let mut map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        Ok(map.remove(node_id).is_some())
+        let removed = map.remove(node_id).is_some();
+        drop(map);
+        if removed {
+            self.publish(RegistryEvent::Deregistered {
+                node_id: node_id.to_string(),
+            });
+        }
+        Ok(removed)
     }
 
     async fn discover(&self, filter: DiscoveryFilter) -> anyhow::Result<Vec<NodeInfo>> {
@@ -154,41 +631,11 @@ impl NodeRegistry for InMemoryRegistry {
 
         let results = map
             .values()
-            .filter(|node| {
-                // Role filter
-                if let Some(ref role) = filter.role {
-                    if &node.role != role {
-                        return false;
-                    }
-                }
-                // Trust tier filter
-                if let Some(ref tier) = filter.trust_tier {
-                    if &node.trust_tier != tier {
-                        return false;
-                    }
-                }
-                // Capability filter
-                if let Some(ref cap) = filter.capability {
-                    if !node.capabilities.iter().any(|c| c == cap) {
-                        return false;
-                    }
-                }
-                // Staleness filter
-                if let Some(max_secs) = filter.max_staleness_seconds {
-                    let age = now
-                        .signed_duration_since(node.last_seen)
-                        .num_seconds()
-                        .max(0) as u64;
-                    if age > max_secs {
-                        return false;
-                    }
-                }
-                true
-            })
+            .filter(|node| filter.matches(node, now))
             .cloned()
             .collect();
 
-        Ok(results)
+        Ok(filter.paginate(results))
     }
 
     async fn heartbeat(&self, node_id: &str) -> anyhow::Result<()> {
@@ -198,7 +645,13 @@ impl NodeRegistry for InMemoryRegistry {
                 node.last_seen = Utc::now();
                 Ok(())
             }
-            None => Err(anyhow::anyhow!("Node not found: {node_id}")),
+            None => {
+                drop(map);
+                self.publish(RegistryEvent::HeartbeatExpired {
+                    node_id: node_id.to_string(),
+                });
+                Err(anyhow::anyhow!("Node not found: {node_id}"))
+            }
         }
     }
 
@@ -206,6 +659,297 @@ impl NodeRegistry for InMemoryRegistry {
         let map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
         Ok(map.get(node_id).cloned())
     }
+
+    async fn update(&self, node_id: &str, patch: NodePatch) -> anyhow::Result<NodeInfo> {
+        let mut map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let node = map
+            .get_mut(node_id)
+            .ok_or_else(|| anyhow::anyhow!("Node not found: {node_id}"))?;
+        patch.apply(node);
+        let node = node.clone();
+        drop(map);
+        self.publish(RegistryEvent::Registered { node: node.clone() });
+        Ok(node)
+    }
+
+    async fn heartbeat_with(
+        &self,
+        node_id: &str,
+        update: HeartbeatUpdate,
+    ) -> anyhow::Result<NodeInfo> {
+        let mut map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let Some(node) = map.get_mut(node_id) else {
+            drop(map);
+            self.publish(RegistryEvent::HeartbeatExpired {
+                node_id: node_id.to_string(),
+            });
+            anyhow::bail!("Node not found: {node_id}");
+        };
+        NodePatch::from(update).apply(node);
+        let node = node.clone();
+        drop(map);
+        self.publish(RegistryEvent::Registered { node: node.clone() });
+        Ok(node)
+    }
+
+    async fn evict_stale(&self, max_staleness_seconds: u64) -> anyhow::Result<usize> {
+        let filter = DiscoveryFilter {
+            max_staleness_seconds: Some(max_staleness_seconds),
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let mut map = self.nodes.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let stale: Vec<String> = map
+            .values()
+            .filter(|node| !filter.matches(node, now))
+            .map(|node| node.node_id.clone())
+            .collect();
+        for node_id in &stale {
+            map.remove(node_id);
+        }
+        drop(map);
+        for node_id in &stale {
+            self.publish(RegistryEvent::HeartbeatExpired {
+                node_id: node_id.clone(),
+            });
+        }
+        Ok(stale.len())
+    }
+
+    fn subscribe(&self) -> RegistryEventStream {
+        broadcast_stream(self.events.subscribe())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local node
+// ---------------------------------------------------------------------------
+
+/// How this node describes itself to registries: a stable id, and the
+/// role, trust tier and address from its config.  Its capabilities are
+/// read from the tools it serves each time it is described, so they
+/// follow the live tool set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalNode {
+    pub node_id: String,
+    pub role: NodeRole,
+    pub trust_tier: TrustTier,
+    /// `host:port` other nodes reach this one at.
+    pub address: String,
+}
+
+impl LocalNode {
+    /// Describe the node configured by `cfg` under `node_id`, at
+    /// `registry.advertise_address`, else `gateway_bind`.
+    pub fn from_config(cfg: &NodeConfig, node_id: impl Into<String>) -> Self {
+        let role = match cfg.node_role.as_str() {
+            "core" => NodeRole::Core,
+            "brain" => NodeRole::Brain,
+            "brain-proxy" | "brain_proxy" => NodeRole::BrainProxy,
+            _ => NodeRole::Edge,
+        };
+        let trust_tier = match cfg.trust_tier.as_str() {
+            "untrusted" => TrustTier::Untrusted,
+            _ => TrustTier::Trusted,
+        };
+        Self {
+            node_id: node_id.into(),
+            role,
+            trust_tier,
+            address: cfg
+                .registry
+                .advertise_address
+                .clone()
+                .unwrap_or_else(|| cfg.gateway_bind.clone()),
+        }
+    }
+
+    /// Like [`from_config`](Self::from_config), with the id kept at
+    /// `id_path` (see [`load_or_create_node_id`]).
+    pub fn load(cfg: &NodeConfig, id_path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::from_config(cfg, load_or_create_node_id(id_path)?))
+    }
+
+    /// This node's registry entry, advertising the tools in `tools`.
+    pub fn info(&self, tools: &ToolRegistry) -> NodeInfo {
+        NodeInfo {
+            node_id: self.node_id.clone(),
+            role: self.role.clone(),
+            endpoints: vec![Endpoint {
+                protocol: "http".to_string(),
+                address: self.address.clone(),
+            }],
+            trust_tier: self.trust_tier.clone(),
+            capabilities: tools.list().into_iter().map(|spec| spec.name).collect(),
+            last_seen: Utc::now(),
+            metadata: serde_json::json!({ "version": env!("CARGO_PKG_VERSION") }),
+        }
+    }
+}
+
+/// The node id stored at `path`, or a new one written there if the file
+/// is missing or empty, so a node keeps its identity across restarts.
+pub fn load_or_create_node_id(path: &Path) -> anyhow::Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(id) if !id.trim().is_empty() => return Ok(id.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => anyhow::bail!("reading node id from {}: {e}", path.display()),
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, format!("{id}\n"))
+        .map_err(|e| anyhow::anyhow!("writing node id to {}: {e}", path.display()))?;
+    Ok(id)
+}
+
+// ---------------------------------------------------------------------------
+// Heartbeat daemon
+// ---------------------------------------------------------------------------
+
+/// Handle to a running [`heartbeat_task`].
+#[derive(Debug)]
+pub struct HeartbeatHandle {
+    stop: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl HeartbeatHandle {
+    /// Stop heartbeating and deregister the node from the registry.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.join.await;
+    }
+}
+
+/// Up to 10% of `interval`, so that nodes started together spread out
+/// their heartbeats.
+fn jitter(interval: Duration) -> Duration {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    interval.mul_f64(f64::from(nanos % 1000) / 10_000.0)
+}
+
+/// Keep `node` registered in `registry`.
+///
+/// Registers the node immediately, then sends a heartbeat every `interval`
+/// (plus jitter). If the registry no longer knows the node — e.g. it was
+/// evicted or the remote restarted — the node is registered again.
+/// [`HeartbeatHandle::shutdown`] deregisters the node.
+pub fn heartbeat_task(
+    registry: Arc<dyn NodeRegistry>,
+    node: NodeInfo,
+    interval: Duration,
+) -> HeartbeatHandle {
+    heartbeat_task_with(registry, move || node.clone(), interval)
+}
+
+/// Like [`heartbeat_task`], but describes the node afresh with `describe`
+/// before every heartbeat, registering it again whenever its capabilities,
+/// endpoints or metadata have changed since they were last announced.
+pub fn heartbeat_task_with(
+    registry: Arc<dyn NodeRegistry>,
+    describe: impl Fn() -> NodeInfo + Send + 'static,
+    interval: Duration,
+) -> HeartbeatHandle {
+    let (stop, mut stopped) = tokio::sync::oneshot::channel();
+    let join = tokio::spawn(async move {
+        // What the registry was last told, compared without `last_seen`.
+        let mut announced: Option<NodeInfo> = None;
+        let mut node_id = String::new();
+        loop {
+            let mut node = describe();
+            node_id.clone_from(&node.node_id);
+            let current = announced.as_ref().is_some_and(|old| {
+                old.node_id == node.node_id
+                    && old.capabilities == node.capabilities
+                    && old.endpoints == node.endpoints
+                    && old.metadata == node.metadata
+            });
+            let result = if current {
+                registry.heartbeat(&node.node_id).await
+            } else {
+                Err(anyhow::anyhow!("Node not found: {}", node.node_id))
+            };
+            match result {
+                Ok(()) => {}
+                Err(e) if e.to_string().contains("Node not found") => {
+                    node.last_seen = Utc::now();
+                    match registry.register(node.clone()).await {
+                        Ok(()) => announced = Some(node),
+                        Err(e) => {
+                            tracing::warn!(node_id = %node.node_id, error = %e, "registration failed")
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(node_id = %node.node_id, error = %e, "heartbeat failed");
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval + jitter(interval)) => {}
+                _ = &mut stopped => break,
+            }
+        }
+
+        if let Err(e) = registry.deregister(&node_id).await {
+            tracing::warn!(node_id = %node_id, error = %e, "deregistration failed");
+        }
+    });
+    HeartbeatHandle { stop, join }
+}
+
+// ---------------------------------------------------------------------------
+// Eviction task
+// ---------------------------------------------------------------------------
+
+/// Handle to a running [`spawn_eviction_task`].
+#[derive(Debug)]
+pub struct EvictionHandle {
+    stop: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl EvictionHandle {
+    /// Stop the eviction task and wait for it to finish.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.join.await;
+    }
+}
+
+/// Periodically evict nodes that have not sent a heartbeat within
+/// `max_staleness`.
+///
+/// Runs [`NodeRegistry::evict_stale`] every `interval` on a background task
+/// until [`EvictionHandle::shutdown`] is called.
+pub fn spawn_eviction_task(
+    registry: Arc<dyn NodeRegistry>,
+    interval: Duration,
+    max_staleness: Duration,
+) -> EvictionHandle {
+    let (stop, mut stopped) = tokio::sync::oneshot::channel();
+    let join = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = &mut stopped => break,
+            }
+            match registry.evict_stale(max_staleness.as_secs()).await {
+                Ok(0) => {}
+                Ok(evicted) => tracing::info!(evicted, "evicted stale nodes"),
+                Err(e) => tracing::warn!(error = %e, "stale node eviction failed"),
+            }
+        }
+    });
+    EvictionHandle { stop, join }
 }
 
 // ---------------------------------------------------------------------------
@@ -287,6 +1031,142 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn heartbeat_with_merges_capabilities_and_refreshes_last_seen() {
+        let reg = InMemoryRegistry::new();
+        let mut node = make_node("n1", NodeRole::Edge, TrustTier::Trusted, vec!["echo@1.0"]);
+        node.last_seen = Utc::now() - Duration::seconds(120);
+        node.metadata = serde_json::json!({ "zone": "lab", "load": { "cpu": 0.5 } });
+        let old_ts = node.last_seen;
+        reg.register(node).await.unwrap();
+        let mut events = reg.subscribe();
+
+        let updated = reg
+            .heartbeat_with(
+                "n1",
+                HeartbeatUpdate {
+                    add_capabilities: vec!["echo@1.1".into(), "gpio".into()],
+                    metadata_patch: serde_json::json!({ "load": { "mem": 0.2 } }),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.capabilities, vec!["echo@1.1", "gpio"]);
+        assert_eq!(
+            updated.metadata,
+            serde_json::json!({ "zone": "lab", "load": { "cpu": 0.5, "mem": 0.2 } })
+        );
+        assert!(updated.last_seen > old_ts);
+        let stored = reg.get("n1").await.unwrap().unwrap();
+        assert_eq!(stored.capabilities, updated.capabilities);
+        assert_eq!(stored.last_seen, updated.last_seen);
+        assert_eq!(
+            events.next().await,
+            Some(RegistryEvent::Registered { node: updated })
+        );
+
+        let err = reg
+            .heartbeat_with("ghost", HeartbeatUpdate::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Node not found: ghost"), "{err}");
+        assert_eq!(
+            events.next().await,
+            Some(RegistryEvent::HeartbeatExpired {
+                node_id: "ghost".into()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn discover_paginates_in_stable_order() {
+        let reg = InMemoryRegistry::new();
+        for (i, id) in ["n3", "n1", "n4", "n2"].iter().enumerate() {
+            let mut node = make_node(id, NodeRole::Edge, TrustTier::Trusted, vec![]);
+            node.last_seen = Utc::now() - Duration::seconds(i as i64 * 10);
+            reg.register(node).await.unwrap();
+        }
+        let ids = |nodes: Vec<NodeInfo>| -> Vec<String> {
+            nodes.into_iter().map(|n| n.node_id).collect()
+        };
+
+        let page = reg
+            .discover(DiscoveryFilter {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(page), vec!["n1", "n2"]);
+
+        let page = reg
+            .discover(DiscoveryFilter {
+                limit: Some(2),
+                offset: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(page), vec!["n3", "n4"]);
+
+        let newest = reg
+            .discover(DiscoveryFilter {
+                sort_by: Some(SortBy::LastSeenDesc),
+                limit: Some(3),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(newest), vec!["n3", "n1", "n4"]);
+
+        // No pagination: everything, as before.
+        let all = reg.discover(DiscoveryFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn subscribe_receives_registry_changes() {
+        let reg = InMemoryRegistry::new();
+        let mut events = reg.subscribe();
+
+        let node = make_node("n1", NodeRole::Brain, TrustTier::Trusted, vec![]);
+        reg.register(node.clone()).await.unwrap();
+        reg.heartbeat("n1").await.unwrap();
+        reg.deregister("n1").await.unwrap();
+        // Removing an absent node publishes nothing.
+        reg.deregister("n1").await.unwrap();
+        let _ = reg.heartbeat("n1").await;
+
+        assert_eq!(
+            events.next().await.unwrap(),
+            RegistryEvent::Registered { node }
+        );
+        assert_eq!(
+            events.next().await.unwrap(),
+            RegistryEvent::Deregistered {
+                node_id: "n1".into()
+            }
+        );
+        assert_eq!(
+            events.next().await.unwrap(),
+            RegistryEvent::HeartbeatExpired {
+                node_id: "n1".into()
+            }
+        );
+    }
+
+    #[test]
+    fn registry_event_serializes_with_tag() {
+        let event = RegistryEvent::Deregistered {
+            node_id: "n1".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "event": "deregistered", "node_id": "n1" })
+        );
+    }
+
     #[tokio::test]
     async fn discover_by_role() {
         let reg = InMemoryRegistry::new();
@@ -362,6 +1242,71 @@ mod tests {
         assert_eq!(results[0].node_id, "n1");
     }
 
+    #[test]
+    fn capability_requirement_parsing() {
+        let bare = CapabilityRequirement::parse("echo");
+        assert_eq!(bare.name, "echo");
+        assert!(bare.version.is_none());
+
+        let exact = CapabilityRequirement::parse("echo@1.2.0");
+        assert_eq!(exact.name, "echo");
+        assert!(exact.is_satisfied_by("echo@1.2.0"));
+        assert!(!exact.is_satisfied_by("echo@1.2.1"));
+
+        let min = CapabilityRequirement::parse("echo>=1.1");
+        assert_eq!(min.name, "echo");
+        assert!(min.is_satisfied_by("echo@1.2.0"));
+        assert!(min.is_satisfied_by("echo@1.1"));
+        assert!(!min.is_satisfied_by("echo@1.0.9"));
+        assert!(!min.is_satisfied_by("echo"));
+        assert!(!min.is_satisfied_by("echoes@2.0.0"));
+
+        // Invalid constraints fall back to an exact-name match.
+        let odd = CapabilityRequirement::parse("echo>=banana");
+        assert_eq!(odd.name, "echo>=banana");
+        assert!(odd.version.is_none());
+    }
+
+    #[test]
+    fn bare_capability_matches_any_version() {
+        let req = CapabilityRequirement::parse("echo");
+        assert!(req.is_satisfied_by("echo"));
+        assert!(req.is_satisfied_by("echo@0.1.0"));
+        assert!(req.is_satisfied_by("echo@3.0.0"));
+        assert!(!req.is_satisfied_by("shell@1.0.0"));
+    }
+
+    #[tokio::test]
+    async fn discover_by_capability_version() {
+        let reg = InMemoryRegistry::new();
+        for (id, cap) in [
+            ("old", "echo@1.0.0"),
+            ("new", "echo@1.2.0"),
+            ("bare", "echo"),
+        ] {
+            reg.register(make_node(id, NodeRole::Edge, TrustTier::Trusted, vec![cap]))
+                .await
+                .unwrap();
+        }
+
+        let discover = |capability: &str| {
+            reg.discover(DiscoveryFilter {
+                capability: Some(capability.to_string()),
+                sort_by: Some(SortBy::NodeId),
+                ..Default::default()
+            })
+        };
+        let ids = |nodes: Vec<NodeInfo>| nodes.into_iter().map(|n| n.node_id).collect::<Vec<_>>();
+
+        assert_eq!(ids(discover("echo>=1.1").await.unwrap()), vec!["new"]);
+        assert_eq!(ids(discover("echo@1.0.0").await.unwrap()), vec!["old"]);
+        assert!(discover("echo>=2").await.unwrap().is_empty());
+        assert_eq!(
+            ids(discover("echo").await.unwrap()),
+            vec!["bare", "new", "old"]
+        );
+    }
+
     #[tokio::test]
     async fn staleness_filter() {
         let reg = InMemoryRegistry::new();
@@ -461,4 +1406,315 @@ mod tests {
         let results = reg.discover(filter).await.unwrap();
         assert_eq!(results.len(), 2);
     }
+
+    #[tokio::test]
+    async fn heartbeat_task_registers_and_deregisters() {
+        let reg = Arc::new(InMemoryRegistry::new());
+        let node = make_node("hb", NodeRole::Edge, TrustTier::Trusted, vec!["echo"]);
+        let handle = heartbeat_task(reg.clone(), node, std::time::Duration::from_millis(20));
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(reg.get("hb").await.unwrap().is_some());
+
+        handle.shutdown().await;
+        assert!(reg.get("hb").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn heartbeat_task_reregisters_when_node_is_forgotten() {
+        let reg = Arc::new(InMemoryRegistry::new());
+        let node = make_node("hb", NodeRole::Edge, TrustTier::Trusted, vec![]);
+        let handle = heartbeat_task(reg.clone(), node, std::time::Duration::from_millis(20));
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        reg.deregister("hb").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        assert!(reg.get("hb").await.unwrap().is_some());
+
+        handle.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn heartbeat_task_announces_changed_capabilities() {
+        let reg = Arc::new(InMemoryRegistry::new());
+        let caps = Arc::new(std::sync::Mutex::new(vec!["echo"]));
+        let describe = {
+            let caps = caps.clone();
+            move || {
+                let caps = caps.lock().unwrap().clone();
+                make_node("hb", NodeRole::Edge, TrustTier::Trusted, caps)
+            }
+        };
+        let handle =
+            heartbeat_task_with(reg.clone(), describe, std::time::Duration::from_millis(20));
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(
+            reg.get("hb").await.unwrap().unwrap().capabilities,
+            vec!["echo"]
+        );
+
+        caps.lock().unwrap().push("shell");
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        assert_eq!(
+            reg.get("hb").await.unwrap().unwrap().capabilities,
+            vec!["echo", "shell"]
+        );
+
+        handle.shutdown().await;
+        assert!(reg.get("hb").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn local_node_keeps_its_id_across_loads() {
+        let dir = std::env::temp_dir().join(format!("ygn-node-id-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("node_id");
+        let cfg = NodeConfig::default();
+
+        let first = LocalNode::load(&cfg, &path).unwrap();
+        let second = LocalNode::load(&cfg, &path).unwrap();
+        assert_eq!(first.node_id, second.node_id);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().trim(),
+            first.node_id
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn local_node_info_follows_registered_tools() {
+        let node = LocalNode::from_config(&NodeConfig::default(), "self");
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(crate::tool::EchoTool));
+        assert_eq!(node.info(&tools).capabilities, vec!["echo"]);
+
+        tools.register(Box::new(crate::hardware::HardwareTool::new()));
+        let info = node.info(&tools);
+        assert_eq!(info.node_id, "self");
+        assert!(info.capabilities.contains(&"echo".to_string()));
+        assert_eq!(info.capabilities.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn update_merges_capabilities_and_metadata() {
+        let reg = InMemoryRegistry::new();
+        let mut node = make_node("n1", NodeRole::Edge, TrustTier::Trusted, vec!["echo"]);
+        node.metadata = serde_json::json!({ "zone": "lab", "hw": { "gpio": true } });
+        reg.register(node).await.unwrap();
+
+        let patch = NodePatch {
+            capabilities: vec!["shell".into(), "echo".into()],
+            metadata: serde_json::json!({ "hw": { "i2c": true }, "load": 0.5 }),
+        };
+        let updated = reg.update("n1", patch).await.unwrap();
+
+        assert_eq!(updated.capabilities, vec!["echo", "shell"]);
+        assert_eq!(
+            updated.metadata,
+            serde_json::json!({
+                "zone": "lab",
+                "hw": { "gpio": true, "i2c": true },
+                "load": 0.5
+            })
+        );
+        let stored = reg.get("n1").await.unwrap().unwrap();
+        assert_eq!(stored, updated);
+
+        // A later partial update keeps what was announced earlier.
+        reg.update(
+            "n1",
+            NodePatch {
+                metadata: serde_json::json!({ "load": 0.9 }),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let stored = reg.get("n1").await.unwrap().unwrap();
+        assert_eq!(stored.capabilities, vec!["echo", "shell"]);
+        assert_eq!(stored.metadata["hw"]["gpio"], true);
+        assert_eq!(stored.metadata["load"], 0.9);
+    }
+
+    #[test]
+    fn patch_replaces_capability_version() {
+        let mut node = make_node(
+            "n1",
+            NodeRole::Edge,
+            TrustTier::Trusted,
+            vec!["echo@1.0.0", "shell", "gpio@2"],
+        );
+        NodePatch {
+            capabilities: vec!["echo@2.1.0".into(), "gpio@2".into(), "i2c@1".into()],
+            ..Default::default()
+        }
+        .apply(&mut node);
+        assert_eq!(
+            node.capabilities,
+            vec!["echo@2.1.0", "shell", "gpio@2", "i2c@1"]
+        );
+
+        // Discovery now sees only the new version.
+        let wants = |capability: &str| DiscoveryFilter {
+            capability: Some(capability.into()),
+            ..Default::default()
+        };
+        assert!(!wants("echo^1").matches(&node, Utc::now()));
+        assert!(wants("echo>=2").matches(&node, Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn update_missing_node_errors() {
+        let reg = InMemoryRegistry::new();
+        let err = reg.update("ghost", NodePatch::default()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Node not found: ghost");
+    }
+
+    #[tokio::test]
+    async fn evict_stale_removes_old_nodes_and_publishes() {
+        let reg = InMemoryRegistry::new();
+        let mut events = reg.subscribe();
+        let mut stale = make_node("stale", NodeRole::Edge, TrustTier::Trusted, vec![]);
+        stale.last_seen = Utc::now() - Duration::seconds(600);
+        reg.register(stale).await.unwrap();
+        reg.register(make_node(
+            "fresh",
+            NodeRole::Edge,
+            TrustTier::Trusted,
+            vec![],
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(reg.evict_stale(300).await.unwrap(), 1);
+        assert!(reg.get("stale").await.unwrap().is_none());
+        assert!(reg.get("fresh").await.unwrap().is_some());
+
+        // Skip the two registrations.
+        events.next().await.unwrap();
+        events.next().await.unwrap();
+        assert_eq!(
+            events.next().await.unwrap(),
+            RegistryEvent::HeartbeatExpired {
+                node_id: "stale".into()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn eviction_task_removes_stale_node_within_one_interval() {
+        let reg = Arc::new(InMemoryRegistry::new());
+        let mut stale = make_node("stale", NodeRole::Brain, TrustTier::Trusted, vec![]);
+        stale.last_seen = Utc::now() - Duration::seconds(600);
+        reg.register(stale).await.unwrap();
+        reg.register(make_node(
+            "fresh",
+            NodeRole::Edge,
+            TrustTier::Trusted,
+            vec![],
+        ))
+        .await
+        .unwrap();
+
+        let interval = std::time::Duration::from_millis(50);
+        let handle =
+            spawn_eviction_task(reg.clone(), interval, std::time::Duration::from_secs(300));
+        tokio::time::sleep(interval).await;
+        assert!(reg.get("stale").await.unwrap().is_none());
+        assert!(reg.get("fresh").await.unwrap().is_some());
+
+        handle.shutdown().await;
+    }
+
+    /// Trusted `a` (seen 10s ago) and `b` (seen 60s ago), untrusted `u`
+    /// (seen 120s ago), a stale trusted node, and a trusted node without
+    /// endpoints, all offering `echo`.
+    async fn dispatch_fixture() -> InMemoryRegistry {
+        let reg = InMemoryRegistry::new();
+        for (id, trust, age) in [
+            ("b", TrustTier::Trusted, 60),
+            ("a", TrustTier::Trusted, 10),
+            ("u", TrustTier::Untrusted, 120),
+            ("stale", TrustTier::Trusted, 600),
+        ] {
+            let mut node = make_node(id, NodeRole::Core, trust, vec!["echo"]);
+            node.last_seen = Utc::now() - Duration::seconds(age);
+            reg.register(node).await.unwrap();
+        }
+        let mut unreachable = make_node("0", NodeRole::Core, TrustTier::Trusted, vec!["echo"]);
+        unreachable.endpoints.clear();
+        reg.register(unreachable).await.unwrap();
+        reg
+    }
+
+    async fn selected(reg: &InMemoryRegistry, strategy: &DispatchStrategy) -> String {
+        reg.select_for("echo", strategy)
+            .await
+            .unwrap()
+            .unwrap()
+            .node
+            .node_id
+    }
+
+    #[tokio::test]
+    async fn select_first_trusted_takes_lowest_id() {
+        let reg = dispatch_fixture().await;
+        assert_eq!(selected(&reg, &DispatchStrategy::FirstTrusted).await, "a");
+        assert!(reg
+            .select_for("unknown", &DispatchStrategy::FirstTrusted)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn select_least_recently_seen_skips_stale_nodes() {
+        let reg = dispatch_fixture().await;
+        assert_eq!(
+            selected(&reg, &DispatchStrategy::LeastRecentlySeen).await,
+            "b"
+        );
+    }
+
+    #[tokio::test]
+    async fn select_round_robin_cycles_trusted_nodes() {
+        let reg = dispatch_fixture().await;
+        let strategy = DispatchStrategy::round_robin();
+        let mut picks = Vec::new();
+        for _ in 0..3 {
+            picks.push(selected(&reg, &strategy).await);
+        }
+        assert_eq!(picks, ["a", "b", "a"]);
+    }
+
+    #[tokio::test]
+    async fn select_falls_back_to_untrusted_and_prefers_mcp_endpoint() {
+        let reg = InMemoryRegistry::new();
+        let mut node = make_node(
+            "u",
+            NodeRole::Edge,
+            TrustTier::Untrusted,
+            vec!["echo@1.2.0"],
+        );
+        node.endpoints.push(Endpoint {
+            protocol: "mcp".to_string(),
+            address: "http://127.0.0.1:3001/mcp".to_string(),
+        });
+        reg.register(node).await.unwrap();
+
+        let selection = reg
+            .select_for("echo>=1.0", &DispatchStrategy::FirstTrusted)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(selection.node.node_id, "u");
+        assert_eq!(selection.endpoint.protocol, "mcp");
+    }
+
+    #[test]
+    fn registry_config_defaults() {
+        let cfg: RegistryConfig = serde_json::from_str("{}").unwrap();
+        assert!(cfg.remote_url.is_none());
+        assert_eq!(cfg.heartbeat_interval_secs, 30);
+    }
 }
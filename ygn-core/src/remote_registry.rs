@@ -0,0 +1,225 @@
+//! HTTP client for another node's registry.
+//!
+//! [`RemoteRegistry`] implements [`NodeRegistry`] by calling the registry
+//! endpoints of a remote ygn-core gateway, so a node can join a grid whose
+//! registry lives elsewhere:
+//!
+//! | Method       | Endpoint                                |
+//! |--------------|-----------------------------------------|
+//! | `register`   | `POST /registry/register`               |
+//! | `discover`   | `GET /registry/nodes?role=..&...`       |
+//! | `heartbeat`  | `POST /registry/nodes/{id}/heartbeat`   |
+//! | `deregister` | `DELETE /registry/nodes/{id}`           |
+//! | `get`        | `GET /registry/nodes/{id}`              |
+//!
+//! Every request has a timeout, and requests that fail to connect are
+//! retried with exponential backoff.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::registry::{DiscoveryFilter, NodeInfo, NodeRegistry};
+
+/// Default per-request timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default number of retries after a connection error.
+const DEFAULT_RETRIES: u32 = 2;
+/// Delay before the first retry; doubled for each subsequent one.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A [`NodeRegistry`] backed by a remote gateway's `/registry/*` endpoints.
+#[derive(Debug, Clone)]
+pub struct RemoteRegistry {
+    base_url: String,
+    client: reqwest::Client,
+    max_retries: u32,
+}
+
+impl RemoteRegistry {
+    /// Create a client for the gateway at `base_url` (e.g. "http://10.0.0.5:3000").
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client: Self::build_client(DEFAULT_TIMEOUT),
+            max_retries: DEFAULT_RETRIES,
+        }
+    }
+
+    /// Set the per-request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Self::build_client(timeout);
+        self
+    }
+
+    /// Set how many times a request is retried after a connection error.
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    fn build_client(timeout: Duration) -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Send a request, retrying on connection errors only.
+    async fn send(&self, build: impl Fn() -> RequestBuilder) -> anyhow::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            match build().send().await {
+                Ok(resp) => return Ok(resp),
+                Err(e) if e.is_connect() && attempt < self.max_retries => {
+                    let delay = RETRY_BACKOFF * 2u32.pow(attempt);
+                    tracing::debug!(error = %e, attempt, "registry request failed; retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) if e.is_timeout() => {
+                    anyhow::bail!("registry request to {} timed out", self.base_url)
+                }
+                Err(e) => anyhow::bail!("registry request to {} failed: {e}", self.base_url),
+            }
+        }
+    }
+
+    /// Turn a non-success response into an error carrying the server message.
+    async fn check(resp: Response) -> anyhow::Result<Response> {
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        let message = body
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("no error message");
+        anyhow::bail!("remote registry returned {status}: {message}")
+    }
+}
+
+/// Encode the set fields of `filter` as query parameters.
+fn filter_query(filter: &DiscoveryFilter) -> Vec<(&'static str, String)> {
+    let mut query = Vec::new();
+    if let Some(ref role) = filter.role {
+        query.push(("role", role.to_string()));
+    }
+    if let Some(ref tier) = filter.trust_tier {
+        query.push(("trust_tier", tier.to_string()));
+    }
+    if let Some(ref cap) = filter.capability {
+        query.push(("capability", cap.clone()));
+    }
+    if let Some(secs) = filter.max_staleness_seconds {
+        query.push(("max_staleness_seconds", secs.to_string()));
+    }
+    if let Some(sort) = filter.sort_by {
+        query.push(("sort_by", sort.to_string()));
+    }
+    if let Some(limit) = filter.limit {
+        query.push(("limit", limit.to_string()));
+    }
+    if let Some(offset) = filter.offset {
+        query.push(("offset", offset.to_string()));
+    }
+    query
+}
+
+#[async_trait]
+impl NodeRegistry for RemoteRegistry {
+    async fn register(&self, node: NodeInfo) -> anyhow::Result<()> {
+        let url = self.url("/registry/register");
+        let resp = self.send(|| self.client.post(&url).json(&node)).await?;
+        Self::check(resp).await?;
+        Ok(())
+    }
+
+    async fn deregister(&self, node_id: &str) -> anyhow::Result<bool> {
+        let url = self.url(&format!("/registry/nodes/{node_id}"));
+        let resp = Self::check(self.send(|| self.client.delete(&url)).await?).await?;
+        let body: serde_json::Value = resp.json().await?;
+        Ok(body
+            .get("removed")
+            .and_then(|r| r.as_bool())
+            .unwrap_or(false))
+    }
+
+    async fn discover(&self, filter: DiscoveryFilter) -> anyhow::Result<Vec<NodeInfo>> {
+        let url = self.url("/registry/nodes");
+        let query = filter_query(&filter);
+        let resp = Self::check(self.send(|| self.client.get(&url).query(&query)).await?).await?;
+        let body: serde_json::Value = resp.json().await?;
+        Ok(serde_json::from_value(
+            body.get("nodes").cloned().unwrap_or_default(),
+        )?)
+    }
+
+    async fn heartbeat(&self, node_id: &str) -> anyhow::Result<()> {
+        let url = self.url(&format!("/registry/nodes/{node_id}/heartbeat"));
+        let resp = self.send(|| self.client.post(&url)).await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            anyhow::bail!("Node not found: {node_id}");
+        }
+        Self::check(resp).await?;
+        Ok(())
+    }
+
+    async fn get(&self, node_id: &str) -> anyhow::Result<Option<NodeInfo>> {
+        let url = self.url(&format!("/registry/nodes/{node_id}"));
+        let resp = self.send(|| self.client.get(&url)).await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(Self::check(resp).await?.json().await?))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{NodeRole, SortBy, TrustTier};
+
+    #[test]
+    fn base_url_trailing_slash_is_trimmed() {
+        let reg = RemoteRegistry::new("http://127.0.0.1:3000/");
+        assert_eq!(
+            reg.url("/registry/nodes"),
+            "http://127.0.0.1:3000/registry/nodes"
+        );
+    }
+
+    #[test]
+    fn filter_query_encodes_set_fields_only() {
+        let filter = DiscoveryFilter {
+            role: Some(NodeRole::BrainProxy),
+            trust_tier: Some(TrustTier::Untrusted),
+            capability: None,
+            max_staleness_seconds: Some(60),
+            sort_by: Some(SortBy::LastSeenDesc),
+            limit: Some(10),
+            offset: None,
+        };
+        assert_eq!(
+            filter_query(&filter),
+            vec![
+                ("role", "brain_proxy".to_string()),
+                ("trust_tier", "untrusted".to_string()),
+                ("max_staleness_seconds", "60".to_string()),
+                ("sort_by", "last_seen_desc".to_string()),
+                ("limit", "10".to_string()),
+            ]
+        );
+        assert!(filter_query(&DiscoveryFilter::default()).is_empty());
+    }
+}
@@ -0,0 +1,797 @@
+//! Interactive REPL behind `ygn-core repl`.
+//!
+//! A [`Repl`] holds one session: the node's tools, skills, memory and
+//! providers, plus the chat history.  Lines starting with `/` are commands
+//! (`/tools`, `/call`, `/skills run`, `/memory recall`, ...); anything else
+//! is sent to the session's model as chat.  [`run_session`] drives a `Repl`
+//! from any [`LineInput`] and writes to any [`Write`], so the whole loop is
+//! testable without a TTY; [`Terminal`] is the rustyline-backed input used
+//! by the CLI, with tab completion and persistent history.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use crate::memory::{Memory, MemoryCategory};
+use crate::multi_provider::ProviderRegistry;
+use crate::policy::{PolicyAction, PolicyConfig, PolicyEngine};
+use crate::provider::{ChatMessage, ChatRequest, ChatRole};
+use crate::skills::{SkillExecutor, SkillRegistry};
+use crate::tool::ToolRegistry;
+use crate::usage::{UsageConfig, UsageTracker};
+
+/// Prompt for a new input.
+pub const PROMPT: &str = "ygn> ";
+
+/// Prompt for the next line of an unfinished multi-line input.
+pub const CONTINUATION_PROMPT: &str = "...> ";
+
+/// Commands understood by [`Repl::handle_line`].
+pub const COMMANDS: &[&str] = &[
+    "/help", "/tools", "/call", "/skills", "/memory", "/model", "/clear", "/exit",
+];
+
+/// Entries returned by `/memory recall`.
+const RECALL_LIMIT: usize = 10;
+
+const HELP: &str = "\
+Commands:
+  /tools                    list the registered tools
+  /call <tool> [json]       run a tool with JSON arguments (default {})
+  /skills                   list the registered skills
+  /skills run <name>        run a skill
+  /memory recall <query>    search the memory store
+  /model [name]             show or switch the chat model
+  /clear                    forget the chat history
+  /exit                     leave the REPL (also Ctrl-D)
+Anything else is sent to the model as chat; Ctrl-C cancels a pending reply.
+Unbalanced { or [ continue the input on the next line.";
+
+// ---------------------------------------------------------------------------
+// Input
+// ---------------------------------------------------------------------------
+
+/// One read from a [`LineInput`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Line(String),
+    /// The user pressed Ctrl-C at the prompt.
+    Interrupted,
+    /// End of input, e.g. Ctrl-D.
+    Eof,
+}
+
+/// Source of REPL input lines.
+pub trait LineInput {
+    /// Show `prompt` and read the next line.
+    fn read_line(&mut self, prompt: &str) -> anyhow::Result<Input>;
+}
+
+/// Scripted input, one line per entry; used for piped input and tests.
+impl LineInput for VecDeque<String> {
+    fn read_line(&mut self, _prompt: &str) -> anyhow::Result<Input> {
+        Ok(self.pop_front().map_or(Input::Eof, Input::Line))
+    }
+}
+
+/// Whether `input` has more `{`/`[` open than closed outside JSON strings,
+/// so it continues on the next line.
+pub fn needs_more(input: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in input.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+// ---------------------------------------------------------------------------
+// Completion
+// ---------------------------------------------------------------------------
+
+/// Tab completion over command, tool and skill names.
+#[derive(Debug, Clone, Default)]
+pub struct Completions {
+    tools: Vec<String>,
+    skills: Vec<String>,
+}
+
+impl Completions {
+    /// Candidates for the word being typed at the end of `line`, with the
+    /// byte offset where that word starts.
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(' ').map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let words: Vec<&str> = line[..start].split_whitespace().collect();
+        let choices: Vec<&str> = match words.as_slice() {
+            [] => COMMANDS.to_vec(),
+            ["/call"] => self.tools.iter().map(String::as_str).collect(),
+            ["/skills"] => vec!["run"],
+            ["/skills", "run"] => self.skills.iter().map(String::as_str).collect(),
+            ["/memory"] => vec!["recall"],
+            _ => Vec::new(),
+        };
+        let candidates = choices
+            .into_iter()
+            .filter(|c| c.starts_with(word))
+            .map(str::to_string)
+            .collect();
+        (start, candidates)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Repl
+// ---------------------------------------------------------------------------
+
+/// What [`run_session`] does after a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Resolves when the user asks to cancel a pending chat reply.
+type Interrupt = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// One interactive session over a node's tools, skills, memory and
+/// providers.
+pub struct Repl {
+    tools: ToolRegistry,
+    skills: SkillRegistry,
+    policy: PolicyEngine,
+    memory: Arc<dyn Memory>,
+    providers: ProviderRegistry,
+    /// Records chat usage and enforces the daily budget.
+    usage: Arc<UsageTracker>,
+    model: String,
+    /// Chat so far, sent with every new message.
+    history: Vec<ChatMessage>,
+    /// Key prefix of the exchanges stored in the memory store.
+    session_id: String,
+    interrupt: Interrupt,
+}
+
+impl std::fmt::Debug for Repl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Repl")
+            .field("model", &self.model)
+            .field("session_id", &self.session_id)
+            .field("history", &self.history.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Repl {
+    /// A session chatting with `model`, under the default policy, that
+    /// cancels replies on Ctrl-C.
+    pub fn new(
+        tools: ToolRegistry,
+        skills: SkillRegistry,
+        memory: Arc<dyn Memory>,
+        providers: ProviderRegistry,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            tools,
+            skills,
+            policy: PolicyEngine::from_config(PolicyConfig::default()),
+            memory,
+            providers,
+            usage: Arc::new(
+                UsageTracker::in_memory(UsageConfig::default()).expect("in-memory SQLite"),
+            ),
+            model: model.into(),
+            history: Vec::new(),
+            session_id: format!("repl-{}", uuid::Uuid::new_v4()),
+            interrupt: Arc::new(|| {
+                Box::pin(async {
+                    let _ = tokio::signal::ctrl_c().await;
+                })
+            }),
+        }
+    }
+
+    /// Gate `/call` and `/skills run` with `policy`; denied calls are
+    /// refused.
+    pub fn with_policy(mut self, policy: PolicyEngine) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Send chat through `usage`, which records it under this session and
+    /// refuses it once the daily budget is spent.
+    pub fn with_usage(mut self, usage: Arc<UsageTracker>) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Cancel a pending chat reply when the future `interrupt` returns
+    /// resolves, instead of on Ctrl-C.
+    pub fn with_interrupt(
+        mut self,
+        interrupt: impl Fn() -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    ) -> Self {
+        self.interrupt = Arc::new(interrupt);
+        self
+    }
+
+    /// The chat so far.
+    pub fn history(&self) -> &[ChatMessage] {
+        &self.history
+    }
+
+    /// Completion candidates for this session's tools and skills.
+    pub fn completions(&self) -> Completions {
+        Completions {
+            tools: self.tools.list().into_iter().map(|t| t.name).collect(),
+            skills: self.skills.list().iter().map(|s| s.name.clone()).collect(),
+        }
+    }
+
+    /// Run one input: a command, or a chat message.
+    pub async fn handle_line(&mut self, line: &str, out: &mut dyn Write) -> anyhow::Result<Flow> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Flow::Continue);
+        }
+        let Some(command) = line.strip_prefix('/') else {
+            self.chat(line, out).await?;
+            return Ok(Flow::Continue);
+        };
+        let (name, rest) = command
+            .split_once(char::is_whitespace)
+            .map_or((command, ""), |(name, rest)| (name, rest.trim()));
+        match name {
+            "help" => writeln!(out, "{HELP}")?,
+            "tools" => {
+                for spec in self.tools.list() {
+                    writeln!(out, "  {:<20} {}", spec.name, spec.description)?;
+                }
+            }
+            "call" => self.call(rest, out).await?,
+            "skills" => self.skills(rest, out).await?,
+            "memory" => self.memory(rest, out).await?,
+            "model" if rest.is_empty() => writeln!(out, "model: {}", self.model)?,
+            "model" => {
+                self.model = rest.to_string();
+                writeln!(out, "model set to {}", self.model)?;
+            }
+            "clear" => {
+                self.history.clear();
+                writeln!(out, "chat history cleared")?;
+            }
+            "exit" | "quit" => return Ok(Flow::Exit),
+            other => anyhow::bail!("unknown command '/{other}'; try /help"),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// `/call <tool> [json]`.
+    async fn call(&self, rest: &str, out: &mut dyn Write) -> anyhow::Result<()> {
+        let (tool, args) = rest
+            .split_once(char::is_whitespace)
+            .map_or((rest, ""), |(tool, args)| (tool, args.trim()));
+        if tool.is_empty() {
+            anyhow::bail!("usage: /call <tool> [json]");
+        }
+        let args: serde_json::Value = if args.is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(args)
+                .map_err(|e| anyhow::anyhow!("arguments are not valid JSON: {e}"))?
+        };
+        let decision = self.policy.evaluate(tool, &args);
+        if decision.action == PolicyAction::Deny {
+            anyhow::bail!("'{tool}' denied by policy: {}", decision.reason);
+        }
+        let result = self.tools.execute(tool, args).await?;
+        match result.error {
+            Some(error) if !result.success => writeln!(out, "failed: {error}")?,
+            _ => writeln!(out, "{}", result.output())?,
+        }
+        Ok(())
+    }
+
+    /// `/skills` and `/skills run <name>`.
+    async fn skills(&self, rest: &str, out: &mut dyn Write) -> anyhow::Result<()> {
+        let name = match rest.split_once(char::is_whitespace) {
+            None if rest.is_empty() || rest == "list" => {
+                for skill in self.skills.list() {
+                    writeln!(out, "  {:<20} {}", skill.name, skill.description)?;
+                }
+                return Ok(());
+            }
+            Some(("run", name)) => name.trim(),
+            _ => anyhow::bail!("usage: /skills [run <name>]"),
+        };
+        let skill = self
+            .skills
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown skill '{name}'"))?;
+        let executor = SkillExecutor::new(&self.tools).with_policy(&self.policy);
+        if !executor.plan(skill)?.is_runnable() {
+            anyhow::bail!("skill '{name}' has denied or unknown steps");
+        }
+        let execution = executor.execute(skill).await;
+        for step in &execution.step_results {
+            writeln!(
+                out,
+                "step {} {}: {} {}",
+                step.step_index,
+                step.tool_name,
+                if step.success { "ok" } else { "failed" },
+                step.output
+            )?;
+        }
+        if !execution.overall_success {
+            anyhow::bail!("skill '{name}' failed");
+        }
+        Ok(())
+    }
+
+    /// `/memory recall <query>`.
+    async fn memory(&self, rest: &str, out: &mut dyn Write) -> anyhow::Result<()> {
+        let Some(query) = rest.strip_prefix("recall").map(str::trim) else {
+            anyhow::bail!("usage: /memory recall <query>");
+        };
+        if query.is_empty() {
+            anyhow::bail!("usage: /memory recall <query>");
+        }
+        let entries = self.memory.recall(query, None, RECALL_LIMIT).await?;
+        if entries.is_empty() {
+            writeln!(out, "no memories match '{query}'")?;
+        }
+        for entry in entries {
+            writeln!(
+                out,
+                "  [{}] {}: {}",
+                entry.category, entry.key, entry.content
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Send `text` with the chat history to the session's model.  The
+    /// exchange joins the history, and is stored in the memory store's
+    /// conversation category, only once a reply arrives.
+    async fn chat(&mut self, text: &str, out: &mut dyn Write) -> anyhow::Result<()> {
+        let provider = self
+            .providers
+            .route(&self.model)
+            .ok_or_else(|| anyhow::anyhow!("no provider available for model '{}'", self.model))?;
+        let user = ChatMessage {
+            role: ChatRole::User,
+            content: text.into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        };
+        let mut messages = self.history.clone();
+        messages.push(user.clone());
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages,
+            max_tokens: None,
+            temperature: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            response_format: None,
+            candidate_count: None,
+        };
+
+        let response = tokio::select! {
+            response = self.usage.chat(provider, request, Some(&self.session_id)) => response?,
+            _ = (self.interrupt)() => {
+                writeln!(out, "(cancelled)")?;
+                return Ok(());
+            }
+        };
+        writeln!(out, "{}", response.content)?;
+
+        let turn = self.history.len() / 2;
+        self.history.push(user);
+        self.history.push(ChatMessage {
+            role: ChatRole::Assistant,
+            content: response.content.as_str().into(),
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        });
+        let exchange = format!("user: {text}\nassistant: {}", response.content);
+        if let Err(e) = self
+            .memory
+            .store(
+                MemoryCategory::Conversation,
+                &format!("{}/{turn}", self.session_id),
+                &exchange,
+            )
+            .await
+        {
+            tracing::warn!(error = %e, "failed to store REPL exchange");
+        }
+        Ok(())
+    }
+}
+
+/// Read lines from `input` and run them on `repl` until `/exit` or end of
+/// input.  Lines with unbalanced JSON brackets continue on the next line
+/// under [`CONTINUATION_PROMPT`]; Ctrl-C at a prompt discards the pending
+/// input.  Command errors are reported to `out` and the session goes on.
+pub async fn run_session(
+    repl: &mut Repl,
+    input: &mut dyn LineInput,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    loop {
+        let mut buffer = match input.read_line(PROMPT)? {
+            Input::Line(line) => line,
+            Input::Interrupted => continue,
+            Input::Eof => return Ok(()),
+        };
+        while needs_more(&buffer) {
+            match input.read_line(CONTINUATION_PROMPT)? {
+                Input::Line(line) => {
+                    buffer.push('\n');
+                    buffer.push_str(&line);
+                }
+                Input::Interrupted => {
+                    buffer.clear();
+                    break;
+                }
+                Input::Eof => break,
+            }
+        }
+        match repl.handle_line(&buffer, out).await {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Exit) => return Ok(()),
+            Err(e) => writeln!(out, "error: {e:#}")?,
+        }
+        out.flush()?;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Terminal
+// ---------------------------------------------------------------------------
+
+impl Completer for Completions {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(Completions::complete(self, &line[..pos]))
+    }
+}
+
+impl Hinter for Completions {
+    type Hint = String;
+}
+
+impl Highlighter for Completions {}
+
+impl Validator for Completions {}
+
+impl Helper for Completions {}
+
+/// Default location of the REPL's line history.
+pub fn default_history_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(format!("{home}/.ygn/repl_history"))
+}
+
+/// Line editor on the terminal, completing with [`Completions`] and
+/// keeping its history in a file.
+pub struct Terminal {
+    editor: Editor<Completions, DefaultHistory>,
+    history_path: PathBuf,
+}
+
+impl Terminal {
+    pub fn new(completions: Completions, history_path: PathBuf) -> anyhow::Result<Self> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(completions));
+        // A missing history file just means a first session.
+        let _ = editor.load_history(&history_path);
+        Ok(Self {
+            editor,
+            history_path,
+        })
+    }
+}
+
+impl LineInput for Terminal {
+    fn read_line(&mut self, prompt: &str) -> anyhow::Result<Input> {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = self.editor.add_history_entry(line.as_str());
+                }
+                Ok(Input::Line(line))
+            }
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(ReadlineError::Eof) => Ok(Input::Eof),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if let Some(dir) = self.history_path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = self.editor.save_history(&self.history_path) {
+            tracing::debug!(error = %e, "failed to save REPL history");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::NoopMemory;
+    use crate::provider::StubProvider;
+    use crate::skills::{SkillDefinition, SkillStep};
+    use crate::sqlite_memory::SqliteMemory;
+    use crate::tool::EchoTool;
+
+    fn repl_with(memory: Arc<dyn Memory>) -> Repl {
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(EchoTool));
+        let mut skills = SkillRegistry::new();
+        skills
+            .register(SkillDefinition {
+                name: "ping".into(),
+                description: "Echo a ping".into(),
+                version: "1.0.0".into(),
+                author: "test".into(),
+                steps: vec![SkillStep {
+                    tool_name: "echo".into(),
+                    arguments: serde_json::json!({ "input": "pong" }),
+                    description: "ping".into(),
+                    depends_on: vec![],
+                }],
+                tags: vec![],
+                created_at: chrono::Utc::now(),
+            })
+            .unwrap();
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(StubProvider {
+            response_text: "hello back".into(),
+        }));
+        providers.register_model("stub-model", "stub");
+        Repl::new(tools, skills, memory, providers, "stub-model")
+    }
+
+    fn repl() -> Repl {
+        repl_with(Arc::new(NoopMemory))
+    }
+
+    /// Run `lines` as a session and return its output.
+    async fn session(repl: &mut Repl, lines: &[&str]) -> String {
+        let mut input: VecDeque<String> = lines.iter().map(|l| l.to_string()).collect();
+        let mut out = Vec::new();
+        run_session(repl, &mut input, &mut out).await.unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn tools_and_call_commands() {
+        let out = session(
+            &mut repl(),
+            &["/tools", r#"/call echo {"input": "hi there"}"#],
+        )
+        .await;
+        assert!(out.contains("echo"), "{out}");
+        assert!(out.contains("hi there"), "{out}");
+    }
+
+    #[tokio::test]
+    async fn multi_line_json_continues_until_balanced() {
+        let mut repl = repl();
+        let lines = ["/call echo {", r#"  "input": "a } in a string""#, "}"];
+        let mut input: VecDeque<String> = lines.iter().map(|l| l.to_string()).collect();
+
+        struct Recording<'a>(&'a mut VecDeque<String>, Vec<String>);
+        impl LineInput for Recording<'_> {
+            fn read_line(&mut self, prompt: &str) -> anyhow::Result<Input> {
+                self.1.push(prompt.to_string());
+                self.0.read_line(prompt)
+            }
+        }
+        let mut recording = Recording(&mut input, Vec::new());
+        let mut out = Vec::new();
+        run_session(&mut repl, &mut recording, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(
+            recording.1,
+            [PROMPT, CONTINUATION_PROMPT, CONTINUATION_PROMPT, PROMPT]
+        );
+        assert!(String::from_utf8(out).unwrap().contains("a } in a string"));
+    }
+
+    #[tokio::test]
+    async fn errors_are_reported_and_the_session_continues() {
+        let out = session(
+            &mut repl(),
+            &[
+                "/call echo {not json}",
+                "/nope",
+                "/call missing",
+                "/exit",
+                "/tools",
+            ],
+        )
+        .await;
+        assert!(out.contains("error: arguments are not valid JSON"), "{out}");
+        assert!(out.contains("error: unknown command '/nope'"), "{out}");
+        assert_eq!(out.matches("error: ").count(), 3, "{out}");
+        // Nothing runs after /exit.
+        assert!(!out.contains("  echo"), "{out}");
+    }
+
+    #[tokio::test]
+    async fn denied_calls_are_refused() {
+        let mut repl = repl().with_policy(PolicyEngine::from_config(PolicyConfig {
+            denied_tools: vec!["echo".into()],
+            ..Default::default()
+        }));
+        let out = session(
+            &mut repl,
+            &[r#"/call echo {"input": "x"}"#, "/skills run ping"],
+        )
+        .await;
+        assert!(out.contains("error: 'echo' denied by policy"), "{out}");
+        assert!(out.contains("error: skill 'ping' has denied"), "{out}");
+    }
+
+    #[tokio::test]
+    async fn skills_list_and_run() {
+        let out = session(&mut repl(), &["/skills", "/skills run ping"]).await;
+        assert!(out.contains("Echo a ping"), "{out}");
+        assert!(out.contains("step 0 echo: ok pong"), "{out}");
+    }
+
+    #[tokio::test]
+    async fn chat_keeps_history_and_stores_exchanges() {
+        let memory = Arc::new(SqliteMemory::in_memory().unwrap());
+        let mut repl = repl_with(memory.clone());
+        let out = session(&mut repl, &["hello", "again"]).await;
+        assert_eq!(out.matches("hello back").count(), 2);
+        assert_eq!(repl.history().len(), 4);
+        assert_eq!(repl.history()[2].content.text(), "again");
+
+        let out = session(&mut repl, &["/memory recall again"]).await;
+        assert!(out.contains("[conversation]"), "{out}");
+        assert!(out.contains("assistant: hello back"), "{out}");
+
+        session(&mut repl, &["/clear"]).await;
+        assert!(repl.history().is_empty());
+    }
+
+    #[tokio::test]
+    async fn chat_is_tracked_and_refused_over_budget() {
+        let usage = Arc::new(UsageTracker::in_memory(UsageConfig::default()).unwrap());
+        let mut repl = repl().with_usage(usage.clone());
+        let out = session(&mut repl, &["hello"]).await;
+        assert!(out.contains("hello back"), "{out}");
+        assert_eq!(usage.session_totals(&repl.session_id).unwrap().requests, 1);
+
+        let spent = UsageConfig {
+            daily_cost_limit: Some(0.0),
+            ..Default::default()
+        };
+        let mut over_budget =
+            self::repl().with_usage(Arc::new(UsageTracker::in_memory(spent).unwrap()));
+        let out = session(&mut over_budget, &["hello"]).await;
+        assert!(out.contains("daily cost budget exceeded"), "{out}");
+        assert!(!out.contains("hello back"), "{out}");
+        assert!(over_budget.history().is_empty());
+    }
+
+    #[tokio::test]
+    async fn interrupt_cancels_a_pending_reply() {
+        struct Hanging;
+        #[async_trait::async_trait]
+        impl crate::provider::Provider for Hanging {
+            fn name(&self) -> &str {
+                "hanging"
+            }
+            fn capabilities(&self) -> crate::provider::ProviderCapabilities {
+                StubProvider::default().capabilities()
+            }
+            async fn chat(
+                &self,
+                _request: ChatRequest,
+            ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+                std::future::pending().await
+            }
+            async fn chat_with_tools(
+                &self,
+                request: ChatRequest,
+                _tools: &[crate::tool::ToolSpec],
+            ) -> Result<crate::provider::ChatResponse, crate::provider::ProviderError> {
+                self.chat(request).await
+            }
+        }
+        let mut providers = ProviderRegistry::new();
+        providers.register(Box::new(Hanging));
+        providers.register_model("slow", "hanging");
+        let mut repl = Repl::new(
+            ToolRegistry::new(),
+            SkillRegistry::new(),
+            Arc::new(NoopMemory),
+            providers,
+            "slow",
+        )
+        .with_interrupt(|| Box::pin(async {}));
+
+        let out = session(&mut repl, &["are you there?", "/model"]).await;
+        assert!(out.contains("(cancelled)"), "{out}");
+        assert!(out.contains("model: slow"), "{out}");
+        assert!(repl.history().is_empty());
+    }
+
+    #[test]
+    fn completes_commands_tools_and_skills() {
+        let completions = repl().completions();
+        assert_eq!(
+            completions.complete("/sk"),
+            (0, vec!["/skills".to_string()])
+        );
+        assert_eq!(
+            completions.complete("/call e"),
+            (6, vec!["echo".to_string()])
+        );
+        assert_eq!(
+            completions.complete("/skills run "),
+            (12, vec!["ping".to_string()])
+        );
+        assert_eq!(
+            completions.complete("/memory r"),
+            (8, vec!["recall".to_string()])
+        );
+        assert!(completions.complete("/call echo ").1.is_empty());
+    }
+
+    #[test]
+    fn needs_more_tracks_open_brackets_outside_strings() {
+        assert!(needs_more("/call echo {"));
+        assert!(needs_more(r#"{"a": ["#));
+        assert!(!needs_more(r#"{"a": "{"}"#));
+        assert!(!needs_more(r#"{"a": "\"{"}"#));
+        assert!(!needs_more("hello"));
+    }
+}
@@ -45,7 +45,7 @@ pub struct AccessResult {
 }
 
 /// Sandbox profile controlling which operations are permitted.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
 pub enum SandboxProfile {
     /// No network access allowed.
     NoNet,
@@ -66,6 +66,12 @@ pub enum SandboxProfile {
 pub trait SandboxChecker: Send + Sync {
     fn check_access(&self, request: &AccessRequest) -> AccessResult;
     fn profile_name(&self) -> &str;
+
+    /// Whether commands are limited to an allowlist, rather than any
+    /// program the profile permits.
+    fn restricts_commands(&self) -> bool {
+        false
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -78,6 +84,8 @@ pub trait SandboxChecker: Send + Sync {
 pub struct ProcessSandbox {
     profile: SandboxProfile,
     allowed_paths: Vec<PathBuf>,
+    allowed_domains: Vec<String>,
+    allowed_commands: Vec<String>,
     scratch_dir: PathBuf,
 }
 
@@ -89,6 +97,8 @@ impl ProcessSandbox {
         Self {
             profile,
             allowed_paths: vec![],
+            allowed_domains: vec![],
+            allowed_commands: vec![],
             scratch_dir: std::env::temp_dir().join("ygn-sandbox"),
         }
     }
@@ -98,6 +108,22 @@ impl ProcessSandbox {
         self.allowed_paths.push(path);
     }
 
+    /// Add a domain network access may reach.  Subdomains are included.
+    ///
+    /// Without any allowed domains, every host is reachable (unless the
+    /// profile blocks the network altogether).
+    pub fn allow_domain(&mut self, domain: impl Into<String>) {
+        self.allowed_domains
+            .push(domain.into().trim_end_matches('.').to_ascii_lowercase());
+    }
+
+    /// Add a program commands may run, matched exactly as given.
+    ///
+    /// Without any allowed commands, every program may run.
+    pub fn allow_command(&mut self, command: impl Into<String>) {
+        self.allowed_commands.push(command.into());
+    }
+
     /// Set the scratch directory for `ScratchFs` profile.
     pub fn set_scratch_dir(&mut self, dir: PathBuf) {
         self.scratch_dir = dir;
@@ -116,13 +142,18 @@ impl ProcessSandbox {
 
     // -- internal checks ---------------------------------------------------
 
-    fn check_network(&self, _request: &AccessRequest) -> AccessResult {
+    fn check_network(&self, request: &AccessRequest) -> AccessResult {
         match self.profile {
             SandboxProfile::NoNet => AccessResult {
                 allowed: false,
                 reason: "Network access is blocked by NoNet profile".into(),
                 profile: self.profile_label().into(),
             },
+            _ if !self.is_allowed_domain(&request.target) => AccessResult {
+                allowed: false,
+                reason: format!("Host '{}' is not in the domain allowlist", request.target),
+                profile: self.profile_label().into(),
+            },
             _ => AccessResult {
                 allowed: true,
                 reason: "Network access permitted".into(),
@@ -213,10 +244,20 @@ impl ProcessSandbox {
         }
     }
 
-    fn check_command(&self, _request: &AccessRequest) -> AccessResult {
+    fn check_command(&self, request: &AccessRequest) -> AccessResult {
         // Commands are allowed by all profiles — the policy engine handles
         // higher-level tool approval. The sandbox only enforces I/O
-        // restrictions.
+        // restrictions and, when configured, the command allowlist.
+        if !self.allowed_commands.is_empty() && !self.allowed_commands.contains(&request.target) {
+            return AccessResult {
+                allowed: false,
+                reason: format!(
+                    "Command '{}' is not in the command allowlist",
+                    request.target
+                ),
+                profile: self.profile_label().into(),
+            };
+        }
         AccessResult {
             allowed: true,
             reason: "Command execution permitted by sandbox (policy engine may add further checks)"
@@ -243,6 +284,23 @@ impl ProcessSandbox {
         self.allowed_paths.iter().any(|p| target.starts_with(p))
     }
 
+    /// Whether `target` (a host or a URL) is an allowed domain or a
+    /// subdomain of one.  Always true when no domains are configured.
+    fn is_allowed_domain(&self, target: &str) -> bool {
+        if self.allowed_domains.is_empty() {
+            return true;
+        }
+        let url = reqwest::Url::parse(target).ok();
+        let host = url.as_ref().and_then(|u| u.host_str()).unwrap_or(target);
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.allowed_domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+
     fn profile_label(&self) -> &str {
         match self.profile {
             SandboxProfile::NoNet => "NoNet",
@@ -261,6 +319,10 @@ impl SandboxChecker for ProcessSandbox {
     fn profile_name(&self) -> &str {
         self.profile_label()
     }
+
+    fn restricts_commands(&self) -> bool {
+        !self.allowed_commands.is_empty()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -298,6 +360,26 @@ mod tests {
 
     // -- Net profile -------------------------------------------------------
 
+    #[test]
+    fn domain_allowlist_restricts_network_access() {
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        sandbox.allow_domain("Example.com");
+        let check = |target: &str| {
+            sandbox
+                .check_access(&AccessRequest {
+                    kind: AccessKind::Network,
+                    target: target.into(),
+                })
+                .allowed
+        };
+        assert!(check("example.com"));
+        assert!(check("api.example.com"));
+        assert!(check("https://api.example.com/v1"));
+        assert!(!check("evil.com"));
+        assert!(!check("notexample.com"));
+        assert!(!check("https://example.com.evil.com/"));
+    }
+
     #[test]
     fn net_allows_network_access() {
         let sandbox = ProcessSandbox::new(SandboxProfile::Net);
@@ -435,6 +517,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn command_allowlist_restricts_commands() {
+        let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+        sandbox.allow_command("/usr/local/bin/lint");
+        let check = |target: &str| {
+            sandbox.check_access(&AccessRequest {
+                kind: AccessKind::Command,
+                target: target.into(),
+            })
+        };
+        assert!(check("/usr/local/bin/lint").allowed);
+        let denied = check("lint");
+        assert!(!denied.allowed);
+        assert!(denied.reason.contains("not in the command allowlist"));
+    }
+
     // -- No allowed paths configured => all reads pass ---------------------
 
     #[test]
@@ -0,0 +1,849 @@
+//! Scheduled skills.
+//!
+//! A [`ScheduleStore`] keeps schedule entries in SQLite: which skill to
+//! run, on a fixed interval or a five-field cron expression (evaluated in
+//! UTC), whether the entry is enabled, and up to how much random jitter to
+//! add to each run.  A [`Scheduler`] runs due skills in the background
+//! through [`SkillExecutor`], records every [`SkillExecution`] in the tool
+//! execution history and, for entries that ask for it, stores the latest
+//! one in memory under `schedule:<name>`.
+//!
+//! Runs missed while the process was down are not made up: each entry's
+//! first run is computed from the time the scheduler starts.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::memory::{Memory, MemoryCategory};
+use crate::schema::{self, Migration};
+use crate::skills::{SkillExecution, SkillExecutor, SkillRegistry};
+use crate::tool::{ToolContent, ToolRegistry, ToolResult};
+use crate::tool_history::{ExecutionOrigin, ToolExecution, ToolExecutionLog};
+
+/// Schema history of the `schedules` table.
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create schedules",
+    "CREATE TABLE IF NOT EXISTS schedules (
+        name         TEXT PRIMARY KEY,
+        skill        TEXT NOT NULL,
+        interval_ms  INTEGER,
+        cron         TEXT,
+        enabled      INTEGER NOT NULL,
+        jitter_ms    INTEGER NOT NULL,
+        remember     INTEGER NOT NULL,
+        created_at   TEXT NOT NULL,
+        last_run_at  TEXT,
+        last_success INTEGER
+    );",
+)];
+
+/// Version of the `schedules` schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+/// How often a running scheduler rereads its entries, so that entries
+/// added or changed from the CLI are picked up.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default on-disk location of the schedules (`~/.ygn/schedules.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/schedules.db")
+}
+
+// ---------------------------------------------------------------------------
+// CronExpr
+// ---------------------------------------------------------------------------
+
+/// A five-field cron expression: minute, hour, day of month, month and day
+/// of week (0 or 7 is Sunday).  Fields take `*`, values, ranges (`1-5`),
+/// lists (`1,15`) and steps (`*/5`, `0-30/10`).  As in cron, when both day
+/// fields are restricted a day matching either one qualifies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronExpr {
+    /// The first minute strictly after `after` that matches.  `None` if
+    /// none does within five years (e.g. `0 0 30 2 *`).
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let limit = after + ChronoDuration::days(5 * 366);
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        while t <= limit {
+            if !has(self.months, t.month()) {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    month => (t.year(), month + 1),
+                };
+                t = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(t) {
+                t = midnight(t) + ChronoDuration::days(1);
+            } else if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + ChronoDuration::hours(1);
+            } else if !has(self.minutes, t.minute()) {
+                t += ChronoDuration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let day = has(self.days, t.day());
+        let weekday = has(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn midnight(t: DateTime<Utc>) -> DateTime<Utc> {
+    t.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight exists")
+        .and_utc()
+}
+
+/// Parse one cron field into a bit set of the values in `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid step in '{part}'"))?;
+                if step == 0 {
+                    anyhow::bail!("step must be positive in '{part}'");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let value = |s: &str| -> anyhow::Result<u32> {
+            let v: u32 = s
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid value '{s}' in '{field}'"))?;
+            if v < min || v > max {
+                anyhow::bail!("value {v} out of range {min}-{max} in '{field}'");
+            }
+            Ok(v)
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` means every 15 starting at 5.
+            None if step > 1 => (value(range)?, max),
+            None => {
+                let v = value(range)?;
+                (v, v)
+            }
+        };
+        if start > end {
+            anyhow::bail!("empty range '{range}' in '{field}'");
+        }
+        for v in (start..=end).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+impl FromStr for CronExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            anyhow::bail!(
+                "cron expression '{s}' must have 5 fields, found {}",
+                fields.len()
+            );
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7 is another name for Sunday.
+        if has(weekdays, 7) {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            source: fields.join(" "),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: day != "*",
+            weekdays_restricted: weekday != "*",
+        })
+    }
+}
+
+impl std::fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for CronExpr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronExpr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// When an entry runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// Every `interval_ms` milliseconds.
+    IntervalMs(u64),
+    /// At the minutes matching a cron expression, in UTC.
+    Cron(CronExpr),
+}
+
+impl Schedule {
+    /// The next run strictly after `after`, before jitter.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::IntervalMs(ms) => Some(after + ChronoDuration::milliseconds(*ms as i64)),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::IntervalMs(ms) => write!(f, "every {ms}ms"),
+            Schedule::Cron(cron) => write!(f, "cron '{cron}'"),
+        }
+    }
+}
+
+/// Parse a duration such as `100ms`, `30s`, `5m` or `1h`; a bare number is
+/// seconds.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let n: u64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid duration '{s}'"))?;
+    Ok(match unit {
+        "ms" => Duration::from_millis(n),
+        "" | "s" => Duration::from_secs(n),
+        "m" => Duration::from_secs(n * 60),
+        "h" => Duration::from_secs(n * 3600),
+        _ => anyhow::bail!("invalid duration unit in '{s}' (use ms, s, m or h)"),
+    })
+}
+
+/// A skill run on a schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    /// Unique name of the entry.
+    pub name: String,
+    /// Skill to run.
+    pub skill: String,
+    pub schedule: Schedule,
+    pub enabled: bool,
+    /// Up to this many milliseconds of random delay added to each run.
+    pub jitter_ms: u64,
+    /// Store each execution in memory under `schedule:<name>`.
+    pub remember: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_success: Option<bool>,
+}
+
+impl ScheduleEntry {
+    /// An enabled entry without jitter that is not stored in memory.
+    pub fn new(name: impl Into<String>, skill: impl Into<String>, schedule: Schedule) -> Self {
+        Self {
+            name: name.into(),
+            skill: skill.into(),
+            schedule,
+            enabled: true,
+            jitter_ms: 0,
+            remember: false,
+            created_at: Utc::now(),
+            last_run_at: None,
+            last_success: None,
+        }
+    }
+
+    /// The next run after `after`, jitter included.
+    pub fn next_run(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let next = self.schedule.next_after(after)?;
+        Some(next + ChronoDuration::milliseconds(jitter(self.jitter_ms) as i64))
+    }
+
+    /// Key of the entry's latest execution in memory.
+    pub fn memory_key(&self) -> String {
+        format!("schedule:{}", self.name)
+    }
+}
+
+/// A pseudo-random delay of up to `max_ms` milliseconds.
+fn jitter(max_ms: u64) -> u64 {
+    if max_ms == 0 {
+        return 0;
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    u64::from(nanos) % (max_ms + 1)
+}
+
+// ---------------------------------------------------------------------------
+// ScheduleStore
+// ---------------------------------------------------------------------------
+
+/// Schedule entries persisted in SQLite.
+pub struct ScheduleStore {
+    conn: Mutex<Connection>,
+}
+
+impl std::fmt::Debug for ScheduleStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScheduleStore").finish_non_exhaustive()
+    }
+}
+
+impl ScheduleStore {
+    /// Open (or create) a file-based store.
+    pub fn new(path: &str) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Create an in-memory store (useful for testing).
+    pub fn in_memory() -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Add `entry`; fails if an entry of the same name exists.
+    pub fn add(&self, entry: &ScheduleEntry) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let (interval_ms, cron) = match &entry.schedule {
+            Schedule::IntervalMs(ms) => (Some(*ms as i64), None),
+            Schedule::Cron(cron) => (None, Some(cron.to_string())),
+        };
+        let added = conn.execute(
+            "INSERT OR IGNORE INTO schedules
+                (name, skill, interval_ms, cron, enabled, jitter_ms, remember, created_at,
+                 last_run_at, last_success)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                entry.name,
+                entry.skill,
+                interval_ms,
+                cron,
+                entry.enabled,
+                entry.jitter_ms as i64,
+                entry.remember,
+                entry.created_at.to_rfc3339(),
+                entry.last_run_at.map(|t| t.to_rfc3339()),
+                entry.last_success,
+            ],
+        )?;
+        if added == 0 {
+            anyhow::bail!("schedule '{}' already exists", entry.name);
+        }
+        Ok(())
+    }
+
+    /// Look up an entry by name.
+    pub fn get(&self, name: &str) -> anyhow::Result<Option<ScheduleEntry>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        Ok(conn
+            .query_row(
+                "SELECT name, skill, interval_ms, cron, enabled, jitter_ms, remember, created_at,
+                        last_run_at, last_success
+                 FROM schedules WHERE name = ?1",
+                params![name],
+                row_to_entry,
+            )
+            .optional()?)
+    }
+
+    /// Every entry, by name.
+    pub fn list(&self) -> anyhow::Result<Vec<ScheduleEntry>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let mut stmt = conn.prepare(
+            "SELECT name, skill, interval_ms, cron, enabled, jitter_ms, remember, created_at,
+                    last_run_at, last_success
+             FROM schedules ORDER BY name",
+        )?;
+        let rows = stmt
+            .query_map([], row_to_entry)?
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Delete an entry.  Returns whether it existed.
+    pub fn remove(&self, name: &str) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        Ok(conn.execute("DELETE FROM schedules WHERE name = ?1", params![name])? > 0)
+    }
+
+    /// Turn an entry on or off.  Returns whether it exists.
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> anyhow::Result<bool> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        Ok(conn.execute(
+            "UPDATE schedules SET enabled = ?2 WHERE name = ?1",
+            params![name, enabled],
+        )? > 0)
+    }
+
+    /// Note that the entry ran at `at` with the given outcome.
+    pub fn record_run(&self, name: &str, at: DateTime<Utc>, success: bool) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        conn.execute(
+            "UPDATE schedules SET last_run_at = ?2, last_success = ?3 WHERE name = ?1",
+            params![name, at.to_rfc3339(), success],
+        )?;
+        Ok(())
+    }
+}
+
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<ScheduleEntry> {
+    let interval_ms: Option<i64> = row.get(2)?;
+    let cron: Option<String> = row.get(3)?;
+    let schedule = match (interval_ms, cron) {
+        (Some(ms), _) => Schedule::IntervalMs(ms as u64),
+        (None, Some(cron)) => Schedule::Cron(cron.parse().map_err(|e: anyhow::Error| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into())
+        })?),
+        (None, None) => {
+            return Err(rusqlite::Error::InvalidColumnType(
+                2,
+                "interval_ms".to_string(),
+                rusqlite::types::Type::Null,
+            ))
+        }
+    };
+    let timestamp = |s: String| {
+        DateTime::parse_from_rfc3339(&s)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_default()
+    };
+    Ok(ScheduleEntry {
+        name: row.get(0)?,
+        skill: row.get(1)?,
+        schedule,
+        enabled: row.get(4)?,
+        jitter_ms: row.get::<_, i64>(5)? as u64,
+        remember: row.get(6)?,
+        created_at: timestamp(row.get(7)?),
+        last_run_at: row.get::<_, Option<String>>(8)?.map(timestamp),
+        last_success: row.get(9)?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Scheduler
+// ---------------------------------------------------------------------------
+
+/// Runs the enabled entries of a [`ScheduleStore`] when they are due.
+pub struct Scheduler {
+    store: Arc<ScheduleStore>,
+    skills: SkillRegistry,
+    tools: ToolRegistry,
+    history: Option<Arc<ToolExecutionLog>>,
+    memory: Option<Arc<dyn Memory>>,
+    poll_interval: Duration,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("skills", &self.skills.len())
+            .field("poll_interval", &self.poll_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Scheduler {
+    pub fn new(store: Arc<ScheduleStore>, skills: SkillRegistry, tools: ToolRegistry) -> Self {
+        Self {
+            store,
+            skills,
+            tools,
+            history: None,
+            memory: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Record each skill execution in `history` as a `run_skill` call.
+    pub fn with_history(mut self, history: Arc<ToolExecutionLog>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Store executions of entries with `remember` set in `memory`.
+    pub fn with_memory(mut self, memory: Arc<dyn Memory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Reread the store every `poll_interval` instead of
+    /// [`DEFAULT_POLL_INTERVAL`].
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Run `entry`'s skill now and record the execution.  `None` if the
+    /// skill is unknown.
+    pub async fn run_entry(&self, entry: &ScheduleEntry) -> Option<SkillExecution> {
+        let Some(skill) = self.skills.get(&entry.skill) else {
+            tracing::warn!(schedule = %entry.name, skill = %entry.skill, "scheduled skill not found");
+            return None;
+        };
+        let started = std::time::Instant::now();
+        let execution = SkillExecutor::new(&self.tools).execute(skill).await;
+        let elapsed = started.elapsed();
+        let success = execution.overall_success;
+        tracing::info!(schedule = %entry.name, skill = %entry.skill, success, "scheduled skill ran");
+
+        if let Err(e) = self.store.record_run(&entry.name, Utc::now(), success) {
+            tracing::warn!(schedule = %entry.name, error = %e, "could not record schedule run");
+        }
+        if let Some(history) = &self.history {
+            let result = ToolContent::json(&execution).map(|content| ToolResult {
+                success,
+                content,
+                error: (!success).then(|| format!("skill '{}' failed", entry.skill)),
+                truncated: None,
+            });
+            let record = ToolExecution::new(
+                "run_skill",
+                json!({ "name": entry.skill, "schedule": entry.name }),
+                &result.map_err(Into::into),
+                elapsed,
+                ExecutionOrigin::Schedule,
+            );
+            if let Err(e) = history.record(&record) {
+                tracing::warn!(schedule = %entry.name, error = %e, "could not record execution");
+            }
+        }
+        if let (true, Some(memory)) = (entry.remember, &self.memory) {
+            let stored = match serde_json::to_string(&execution) {
+                Ok(content) => memory
+                    .store(MemoryCategory::Daily, &entry.memory_key(), &content)
+                    .await
+                    .map(drop),
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = stored {
+                tracing::warn!(schedule = %entry.name, error = %e, "could not store execution");
+            }
+        }
+        Some(execution)
+    }
+
+    /// Run entries as they come due until the returned handle is shut
+    /// down.
+    pub fn spawn(self) -> SchedulerHandle {
+        let (stop, mut stopped) = tokio::sync::oneshot::channel();
+        let join = tokio::spawn(async move {
+            // Next run of each enabled entry, first computed when the entry
+            // is seen, so nothing missed before then is backfilled.
+            let mut due: HashMap<String, DateTime<Utc>> = HashMap::new();
+            loop {
+                let entries = self.store.list().unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "could not read schedules");
+                    Vec::new()
+                });
+                due.retain(|name, _| entries.iter().any(|e| e.enabled && e.name == *name));
+                for entry in entries.iter().filter(|e| e.enabled) {
+                    let now = Utc::now();
+                    let Some(next) = due
+                        .get(&entry.name)
+                        .copied()
+                        .or_else(|| entry.next_run(now))
+                    else {
+                        continue;
+                    };
+                    if next > now {
+                        due.insert(entry.name.clone(), next);
+                        continue;
+                    }
+                    tokio::select! {
+                        _ = self.run_entry(entry) => {}
+                        _ = &mut stopped => return,
+                    }
+                    match entry.next_run(Utc::now()) {
+                        Some(next) => due.insert(entry.name.clone(), next),
+                        None => due.remove(&entry.name),
+                    };
+                }
+
+                let now = Utc::now();
+                let wait = due
+                    .values()
+                    .map(|next| (*next - now).to_std().unwrap_or_default())
+                    .min()
+                    .map_or(self.poll_interval, |wait| wait.min(self.poll_interval));
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = &mut stopped => return,
+                }
+            }
+        });
+        SchedulerHandle { stop, join }
+    }
+}
+
+/// Handle to a background task started by [`Scheduler::spawn`].
+#[derive(Debug)]
+pub struct SchedulerHandle {
+    stop: tokio::sync::oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl SchedulerHandle {
+    /// Stop scheduling; a skill still running is abandoned.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(());
+        let _ = self.join.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqlite_memory::SqliteMemory;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(cron: &str, after: &str) -> String {
+        cron.parse::<CronExpr>()
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn cron_finds_the_next_matching_minute() {
+        assert_eq!(
+            next("*/5 * * * *", "2026-03-01T10:02:30Z"),
+            "2026-03-01T10:05:00+00:00"
+        );
+        assert_eq!(
+            next("*/5 * * * *", "2026-03-01T10:05:00Z"),
+            "2026-03-01T10:10:00+00:00"
+        );
+        assert_eq!(
+            next("30 9 * * 1-5", "2026-03-06T10:00:00Z"),
+            "2026-03-09T09:30:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 1 1 *", "2026-03-01T00:00:00Z"),
+            "2027-01-01T00:00:00+00:00"
+        );
+        // Either restricted day field qualifies; 7 is Sunday.
+        assert_eq!(
+            next("0 12 15 * 7", "2026-03-02T00:00:00Z"),
+            "2026-03-08T12:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn cron_rejects_malformed_expressions() {
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+        ] {
+            assert!(bad.parse::<CronExpr>().is_err(), "{bad}");
+        }
+        assert!("0 0 30 2 *"
+            .parse::<CronExpr>()
+            .unwrap()
+            .next_after(Utc::now())
+            .is_none());
+    }
+
+    #[test]
+    fn durations_parse_with_units() {
+        assert_eq!(parse_duration("100ms").unwrap(), Duration::from_millis(100));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+        assert!(parse_duration("5d").is_err());
+    }
+
+    #[test]
+    fn store_adds_lists_toggles_and_removes() {
+        let store = ScheduleStore::in_memory().unwrap();
+        let mut entry = ScheduleEntry::new(
+            "hourly",
+            "health-check",
+            Schedule::Cron("0 * * * *".parse().unwrap()),
+        );
+        entry.jitter_ms = 500;
+        store.add(&entry).unwrap();
+        store
+            .add(&ScheduleEntry::new(
+                "fast",
+                "health-check",
+                Schedule::IntervalMs(100),
+            ))
+            .unwrap();
+        assert!(store.add(&entry).is_err());
+
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["fast", "hourly"]);
+        let stored = store.get("hourly").unwrap().unwrap();
+        assert_eq!(stored.schedule, entry.schedule);
+        assert_eq!(stored.jitter_ms, 500);
+
+        assert!(store.set_enabled("hourly", false).unwrap());
+        assert!(!store.get("hourly").unwrap().unwrap().enabled);
+        assert!(!store.set_enabled("missing", true).unwrap());
+
+        let ran = Utc::now();
+        store.record_run("fast", ran, true).unwrap();
+        let fast = store.get("fast").unwrap().unwrap();
+        assert_eq!(fast.last_success, Some(true));
+        assert!(fast.last_run_at.is_some());
+
+        assert!(store.remove("fast").unwrap());
+        assert!(!store.remove("fast").unwrap());
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let mut entry = ScheduleEntry::new("j", "s", Schedule::IntervalMs(1_000));
+        entry.jitter_ms = 200;
+        let now = Utc::now();
+        for _ in 0..20 {
+            let delay = entry.next_run(now).unwrap() - now;
+            assert!(delay >= ChronoDuration::milliseconds(1_000));
+            assert!(delay <= ChronoDuration::milliseconds(1_200));
+        }
+    }
+
+    #[tokio::test]
+    async fn interval_entries_run_repeatedly_and_are_recorded() {
+        let store = Arc::new(ScheduleStore::in_memory().unwrap());
+        let mut entry = ScheduleEntry::new("pulse", "health-check", Schedule::IntervalMs(100));
+        entry.remember = true;
+        store.add(&entry).unwrap();
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(crate::tool::EchoTool));
+        let history = Arc::new(ToolExecutionLog::in_memory().unwrap());
+        let memory = Arc::new(SqliteMemory::in_memory().unwrap());
+
+        let handle = Scheduler::new(
+            store.clone(),
+            crate::skills::builtin_skills().unwrap(),
+            tools,
+        )
+        .with_history(history.clone())
+        .with_memory(memory.clone())
+        .with_poll_interval(Duration::from_millis(20))
+        .spawn();
+        tokio::time::sleep(Duration::from_millis(450)).await;
+        handle.shutdown().await;
+
+        let runs = history.list(Some("run_skill"), 100).unwrap();
+        assert!(runs.len() >= 2, "only {} runs recorded", runs.len());
+        assert!(runs.iter().all(|r| r.success));
+        assert_eq!(runs[0].origin, ExecutionOrigin::Schedule);
+        assert_eq!(runs[0].arguments["schedule"], "pulse");
+        assert_eq!(
+            runs[0].result["output"]["value"]["skill_name"],
+            "health-check"
+        );
+        assert_eq!(
+            store.get("pulse").unwrap().unwrap().last_success,
+            Some(true)
+        );
+
+        let remembered = memory
+            .get(MemoryCategory::Daily, "schedule:pulse")
+            .await
+            .unwrap()
+            .expect("execution stored in memory");
+        let execution: SkillExecution = serde_json::from_str(&remembered.content).unwrap();
+        assert!(execution.overall_success);
+    }
+
+    #[tokio::test]
+    async fn disabled_entries_and_missed_runs_do_not_run() {
+        let store = Arc::new(ScheduleStore::in_memory().unwrap());
+        let mut disabled = ScheduleEntry::new("off", "health-check", Schedule::IntervalMs(10));
+        disabled.enabled = false;
+        store.add(&disabled).unwrap();
+        // Last ran long ago: the missed runs are not made up at startup.
+        let mut stale = ScheduleEntry::new("stale", "health-check", Schedule::IntervalMs(60_000));
+        stale.last_run_at = Some(Utc::now() - ChronoDuration::hours(2));
+        store.add(&stale).unwrap();
+        let history = Arc::new(ToolExecutionLog::in_memory().unwrap());
+
+        let handle = Scheduler::new(
+            store,
+            crate::skills::builtin_skills().unwrap(),
+            ToolRegistry::new(),
+        )
+        .with_history(history.clone())
+        .with_poll_interval(Duration::from_millis(10))
+        .spawn();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.shutdown().await;
+
+        assert!(history.list(None, 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+//! Schema migrations for the SQLite stores.
+//!
+//! Each store lists its schema history as ordered [`Migration`]s, starting
+//! at version 1.  [`migrate`] runs on open: it applies every migration newer
+//! than the database, each in its own transaction, and records it in the
+//! `schema_migrations` table.  A database whose version is ahead of the
+//! migrations this build knows is refused rather than opened.
+
+use rusqlite::{Connection, OptionalExtension, Transaction, TransactionBehavior};
+
+/// One step in a store's schema history.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Version the database is at after this migration.
+    pub version: u32,
+    /// Short summary, recorded in `schema_migrations`.
+    pub description: &'static str,
+    /// Statements to run; may be empty when `hook` does the work.
+    pub sql: &'static str,
+    /// Rust step run after `sql`, for changes SQL alone cannot express.
+    pub hook: Option<fn(&Transaction<'_>) -> rusqlite::Result<()>>,
+}
+
+impl Migration {
+    /// A migration made of SQL statements only.
+    pub const fn sql(version: u32, description: &'static str, sql: &'static str) -> Self {
+        Self {
+            version,
+            description,
+            sql,
+            hook: None,
+        }
+    }
+
+    /// A migration done by a Rust function.
+    pub const fn hook(
+        version: u32,
+        description: &'static str,
+        hook: fn(&Transaction<'_>) -> rusqlite::Result<()>,
+    ) -> Self {
+        Self {
+            version,
+            description,
+            sql: "",
+            hook: Some(hook),
+        }
+    }
+}
+
+/// Version reached after the last of `migrations`.
+pub const fn latest(migrations: &[Migration]) -> u32 {
+    migrations[migrations.len() - 1].version
+}
+
+/// Bring `conn` up to the latest of `migrations` and return that version.
+///
+/// Fails without changing anything if the database is at a version newer
+/// than the last migration.
+pub fn migrate(conn: &mut Connection, migrations: &[Migration]) -> anyhow::Result<u32> {
+    debug_assert!(
+        migrations
+            .iter()
+            .enumerate()
+            .all(|(i, m)| m.version as usize == i + 1),
+        "migrations must be numbered 1, 2, 3, ..."
+    );
+    let latest = latest(migrations);
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version     INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at  TEXT NOT NULL
+        );",
+    )?;
+
+    loop {
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let current = current_version(&tx)?;
+        if current > latest {
+            anyhow::bail!(
+                "database schema version {current} is newer than this build supports ({latest})"
+            );
+        }
+        let Some(migration) = migrations.iter().find(|m| m.version > current) else {
+            return Ok(current);
+        };
+        tx.execute_batch(migration.sql)?;
+        if let Some(hook) = migration.hook {
+            hook(&tx)?;
+        }
+        tx.execute(
+            "INSERT INTO schema_migrations (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                migration.version,
+                migration.description,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        tx.commit()?;
+        tracing::debug!(
+            version = migration.version,
+            description = migration.description,
+            "applied schema migration"
+        );
+    }
+}
+
+/// The schema version of `conn`, or `None` for a database that has never
+/// been migrated.
+pub fn version(conn: &Connection) -> rusqlite::Result<Option<u32>> {
+    let has_table: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_table {
+        return Ok(None);
+    }
+    conn.query_row("SELECT MAX(version) FROM schema_migrations", [], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map(Option::flatten)
+}
+
+fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    Ok(version(conn)?.unwrap_or(0))
+}
+
+/// Add `column` to `table` unless it is already there.  Returns whether it
+/// was added.  Useful in hooks for columns some older databases have.
+pub fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+        ))?;
+    }
+    Ok(!exists)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_flag(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+        add_column_if_missing(tx, "items", "flag", "INTEGER NOT NULL DEFAULT 0")?;
+        tx.execute("UPDATE items SET flag = 1 WHERE name = 'old'", [])?;
+        Ok(())
+    }
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration::sql(
+            1,
+            "create items",
+            "CREATE TABLE IF NOT EXISTS items (name TEXT NOT NULL);",
+        ),
+        Migration::hook(2, "add and backfill flag", add_flag),
+    ];
+
+    fn names_and_flags(conn: &Connection) -> Vec<(String, i64)> {
+        let mut stmt = conn
+            .prepare("SELECT name, flag FROM items ORDER BY name")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_unversioned_database_and_runs_hooks() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (name TEXT NOT NULL); INSERT INTO items VALUES ('old');",
+        )
+        .unwrap();
+        assert_eq!(version(&conn).unwrap(), None);
+
+        assert_eq!(migrate(&mut conn, MIGRATIONS).unwrap(), 2);
+        assert_eq!(version(&conn).unwrap(), Some(2));
+        assert_eq!(names_and_flags(&conn), vec![("old".to_string(), 1)]);
+
+        // Running again applies nothing.
+        conn.execute("INSERT INTO items (name) VALUES ('new')", [])
+            .unwrap();
+        assert_eq!(migrate(&mut conn, MIGRATIONS).unwrap(), 2);
+        let applied: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(applied, 2);
+        assert_eq!(
+            names_and_flags(&conn),
+            vec![("new".to_string(), 0), ("old".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn refuses_database_ahead_of_build() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, MIGRATIONS).unwrap();
+        let err = migrate(&mut conn, &MIGRATIONS[..1]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("newer than this build supports (1)"));
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        const BROKEN: &[Migration] = &[
+            Migration::sql(1, "create items", "CREATE TABLE items (name TEXT);"),
+            Migration::sql(
+                2,
+                "half broken",
+                "CREATE TABLE other (x INTEGER); SELECT * FROM missing;",
+            ),
+        ];
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert!(migrate(&mut conn, BROKEN).is_err());
+        assert_eq!(version(&conn).unwrap(), Some(1));
+        let other: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'other')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!other);
+    }
+}
@@ -0,0 +1,289 @@
+//! Shell command tool.
+//!
+//! [`ShellTool`] runs a program with arguments and reports its stdout,
+//! stderr and exit code.  Every call is checked against a
+//! [`SandboxChecker`] with [`AccessKind::Command`] before anything is
+//! spawned.  The tool is opt-in: register it explicitly where commands
+//! should be available.  Each line the command prints to stdout is
+//! reported as progress.
+
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+
+use crate::sandbox::{AccessKind, AccessRequest, SandboxChecker};
+use crate::tool::{ProgressReporter, Tool, ToolContent, ToolResult};
+
+/// What a finished command produced, serialized as the tool output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// `None` when the process was killed by a signal.
+    pub exit_code: Option<i32>,
+}
+
+/// Runs a program directly (no shell interpolation) after a sandbox check.
+pub struct ShellTool {
+    sandbox: Box<dyn SandboxChecker>,
+}
+
+impl std::fmt::Debug for ShellTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShellTool")
+            .field("sandbox", &self.sandbox.profile_name())
+            .finish()
+    }
+}
+
+impl ShellTool {
+    pub fn new(sandbox: Box<dyn SandboxChecker>) -> Self {
+        Self { sandbox }
+    }
+
+    fn failure(error: String) -> ToolResult {
+        ToolResult {
+            success: false,
+            content: ToolContent::default(),
+            error: Some(error),
+            truncated: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ShellTool {
+    fn name(&self) -> &str {
+        "shell"
+    }
+
+    fn description(&self) -> &str {
+        "Run a command with arguments and return its stdout, stderr and exit code"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Program to run, resolved through PATH"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Arguments passed to the program as-is"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        self.execute_with_progress(args, &ProgressReporter::noop())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<ToolResult> {
+        let command = args
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: command"))?
+            .to_string();
+        let argv: Vec<String> = match args.get("args") {
+            Some(v) => serde_json::from_value(v.clone())
+                .map_err(|e| anyhow::anyhow!("Invalid args: {e}"))?,
+            None => Vec::new(),
+        };
+
+        let access = self.sandbox.check_access(&AccessRequest {
+            kind: AccessKind::Command,
+            target: command.clone(),
+        });
+        if !access.allowed {
+            return Ok(Self::failure(format!(
+                "sandbox ({}) denied command '{command}': {}",
+                access.profile, access.reason
+            )));
+        }
+
+        let child = Command::new(&command)
+            .args(&argv)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => return Ok(Self::failure(format!("failed to start command: {e}"))),
+        };
+
+        let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let read_stdout = async {
+            let mut buf = Vec::new();
+            let mut lines = 0u32;
+            loop {
+                let start = buf.len();
+                if stdout.read_until(b'\n', &mut buf).await? == 0 {
+                    break;
+                }
+                lines += 1;
+                if progress.is_active() {
+                    let line = String::from_utf8_lossy(&buf[start..]);
+                    progress.report(f64::from(lines), None, Some(line.trim_end().to_string()));
+                }
+            }
+            Ok::<_, std::io::Error>(buf)
+        };
+        let read_stderr = async {
+            let mut buf = Vec::new();
+            stderr.read_to_end(&mut buf).await?;
+            Ok::<_, std::io::Error>(buf)
+        };
+        let (out, err) = tokio::try_join!(read_stdout, read_stderr)?;
+        let status = child.wait().await?;
+
+        let result = CommandOutput {
+            stdout: String::from_utf8_lossy(&out).into_owned(),
+            stderr: String::from_utf8_lossy(&err).into_owned(),
+            exit_code: status.code(),
+        };
+        let success = status.success();
+        Ok(ToolResult {
+            success,
+            content: ToolContent::json(&result)?,
+            error: (!success).then(|| match result.exit_code {
+                Some(code) => format!("command exited with code {code}"),
+                None => "command terminated by signal".to_string(),
+            }),
+            truncated: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::{AccessResult, ProcessSandbox, SandboxProfile};
+    use std::sync::{Arc, Mutex};
+
+    /// Denies every command.
+    struct DenyCommands;
+
+    impl SandboxChecker for DenyCommands {
+        fn check_access(&self, request: &AccessRequest) -> AccessResult {
+            AccessResult {
+                allowed: request.kind != AccessKind::Command,
+                reason: "commands are disabled".into(),
+                profile: "deny-commands".into(),
+            }
+        }
+
+        fn profile_name(&self) -> &str {
+            "deny-commands"
+        }
+    }
+
+    fn output_of(result: &ToolResult) -> CommandOutput {
+        serde_json::from_str(&result.output()).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_command_and_captures_output() {
+        let tool = ShellTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)));
+        let result = tool
+            .execute(serde_json::json!({ "command": "echo", "args": ["hello", "world"] }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.error.is_none());
+        let output = output_of(&result);
+        assert_eq!(output.stdout, "hello world\n");
+        assert_eq!(output.exit_code, Some(0));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn nonzero_exit_is_a_failure() {
+        let tool = ShellTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)));
+        let result = tool
+            .execute(
+                serde_json::json!({ "command": "sh", "args": ["-c", "echo oops >&2; exit 3"] }),
+            )
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("command exited with code 3"));
+        let output = output_of(&result);
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(output.exit_code, Some(3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_each_stdout_line_as_progress() {
+        let tool = ShellTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)));
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let progress = ProgressReporter::new(move |p| sink.lock().unwrap().push(p));
+        let result = tool
+            .execute_with_progress(
+                serde_json::json!({ "command": "printf", "args": ["one\\ntwo\\nthree"] }),
+                &progress,
+            )
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(output_of(&result).stdout, "one\ntwo\nthree");
+
+        let updates = updates.lock().unwrap();
+        let messages: Vec<_> = updates
+            .iter()
+            .filter_map(|p| p.message.as_deref())
+            .collect();
+        assert_eq!(messages, vec!["one", "two", "three"]);
+        assert_eq!(updates[2].progress, 3.0);
+    }
+
+    #[tokio::test]
+    async fn sandbox_denial_prevents_execution() {
+        let tool = ShellTool::new(Box::new(DenyCommands));
+        let marker = std::env::temp_dir().join(format!("ygn-shell-{}", uuid::Uuid::new_v4()));
+        let result = tool
+            .execute(serde_json::json!({ "command": "touch", "args": [marker] }))
+            .await
+            .unwrap();
+        assert!(!marker.exists());
+        assert!(!result.success);
+        assert!(result.output().is_empty());
+        let error = result.error.unwrap();
+        assert!(error.contains("denied command 'touch'"));
+        assert!(error.contains("commands are disabled"));
+    }
+
+    #[tokio::test]
+    async fn missing_program_is_reported() {
+        let tool = ShellTool::new(Box::new(ProcessSandbox::new(SandboxProfile::Net)));
+        let result = tool
+            .execute(serde_json::json!({ "command": "ygn-no-such-program" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("failed to start command"));
+    }
+}
@@ -0,0 +1,446 @@
+//! Skill planner.
+//!
+//! [`SkillPlanner`] asks a provider to turn a natural-language goal into a
+//! [`SkillDefinition`].  The model is constrained to a JSON Schema listing
+//! only the offered tools; the returned plan is then checked for unknown or
+//! policy-denied tools, the step cap and a valid dependency graph.  An
+//! invalid plan is sent back once with the errors so the model can fix it.
+
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::policy::{PolicyAction, PolicyEngine};
+use crate::provider::{ChatMessage, ChatRequest, ChatRole, Provider, ResponseFormat};
+use crate::skills::{SkillDefinition, SkillExecutor, SkillStep};
+use crate::tool::{ToolRegistry, ToolSpec};
+
+/// Default cap on the number of steps in a plan.
+pub const DEFAULT_MAX_STEPS: usize = 8;
+
+/// What the model is asked to return.
+#[derive(Debug, Deserialize)]
+struct PlanDraft {
+    name: String,
+    #[serde(default)]
+    description: String,
+    steps: Vec<SkillStep>,
+}
+
+/// Turns goals into validated skills using a provider.
+pub struct SkillPlanner<'a> {
+    model: String,
+    max_steps: usize,
+    policy: Option<&'a PolicyEngine>,
+}
+
+impl<'a> SkillPlanner<'a> {
+    /// Create a planner that sends requests for `model`.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            max_steps: DEFAULT_MAX_STEPS,
+            policy: None,
+        }
+    }
+
+    /// Cap the number of steps a plan may have.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Leave out tools this policy denies, and reject plans that use them.
+    pub fn with_policy(mut self, policy: &'a PolicyEngine) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Ask `provider` for a skill that achieves `goal` using only `tools`.
+    ///
+    /// If the first plan is invalid, the validation errors are appended to
+    /// the conversation and the model gets one more attempt.
+    pub async fn plan(
+        &self,
+        goal: &str,
+        tools: &[ToolSpec],
+        provider: &dyn Provider,
+    ) -> anyhow::Result<SkillDefinition> {
+        if self.max_steps == 0 {
+            anyhow::bail!("max steps must be at least 1");
+        }
+        let offered: Vec<&ToolSpec> = tools
+            .iter()
+            .filter(|spec| !self.denied(&spec.name, &json!({})))
+            .collect();
+        if offered.is_empty() {
+            anyhow::bail!("no tools are available for planning");
+        }
+
+        let format = ResponseFormat::JsonSchema {
+            schema: self.plan_schema(&offered),
+        };
+        let mut messages = vec![
+            ChatMessage {
+                role: ChatRole::System,
+                content: self.system_prompt(&offered).into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: format!("Goal: {goal}").into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            },
+        ];
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let response = provider
+                .chat(ChatRequest {
+                    model: self.model.clone(),
+                    messages: messages.clone(),
+                    max_tokens: Some(2048),
+                    temperature: Some(0.0),
+                    response_format: Some(format.clone()),
+                    stop: None,
+                    top_p: None,
+                    seed: None,
+                    candidate_count: None,
+                })
+                .await?;
+            match self.parse(goal, &format, &response.content, &offered) {
+                Ok(skill) => return Ok(skill),
+                Err(e) if attempt == 1 => {
+                    tracing::debug!(error = %e, "plan rejected, retrying");
+                    messages.push(ChatMessage {
+                        role: ChatRole::Assistant,
+                        content: response.content.into(),
+                        tool_call_id: None,
+                        tool_calls: Vec::new(),
+                    });
+                    messages.push(ChatMessage {
+                        role: ChatRole::User,
+                        content: format!("That plan is invalid: {e}\nReply with a corrected plan.")
+                            .into(),
+                        tool_call_id: None,
+                        tool_calls: Vec::new(),
+                    });
+                }
+                Err(e) => anyhow::bail!("planner produced an invalid plan: {e}"),
+            }
+        }
+    }
+
+    fn denied(&self, tool_name: &str, args: &Value) -> bool {
+        self.policy
+            .is_some_and(|p| p.preview(tool_name, args).action == PolicyAction::Deny)
+    }
+
+    fn system_prompt(&self, tools: &[&ToolSpec]) -> String {
+        let mut prompt = format!(
+            "You plan workflows as a list of tool calls. Use at most {} steps and \
+             only the tools below. Each step names a tool, gives its JSON \
+             arguments, and lists the indices of earlier steps it depends on.\n\n\
+             Tools:\n",
+            self.max_steps
+        );
+        for spec in tools {
+            prompt.push_str(&format!(
+                "- {}: {} (arguments schema: {})\n",
+                spec.name, spec.description, spec.parameters_schema
+            ));
+        }
+        prompt
+    }
+
+    fn plan_schema(&self, tools: &[&ToolSpec]) -> Value {
+        let names: Vec<&str> = tools.iter().map(|spec| spec.name.as_str()).collect();
+        json!({
+            "type": "object",
+            "required": ["name", "steps"],
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "description": { "type": "string" },
+                "steps": {
+                    "type": "array",
+                    "minItems": 1,
+                    "maxItems": self.max_steps,
+                    "items": {
+                        "type": "object",
+                        "required": ["tool_name"],
+                        "properties": {
+                            "tool_name": { "type": "string", "enum": names },
+                            "arguments": { "type": "object" },
+                            "description": { "type": "string" },
+                            "depends_on": {
+                                "type": "array",
+                                "items": { "type": "integer", "minimum": 0 }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn parse(
+        &self,
+        goal: &str,
+        format: &ResponseFormat,
+        content: &str,
+        tools: &[&ToolSpec],
+    ) -> anyhow::Result<SkillDefinition> {
+        let value = format.validate(content)?;
+        let draft: PlanDraft = serde_json::from_value(value)?;
+        let skill = SkillDefinition {
+            name: draft.name,
+            description: if draft.description.is_empty() {
+                goal.to_string()
+            } else {
+                draft.description
+            },
+            version: "0.1.0".to_string(),
+            author: "planner".to_string(),
+            steps: draft.steps,
+            tags: vec!["planned".to_string()],
+            created_at: Utc::now(),
+        };
+        self.validate(&skill, tools)?;
+        Ok(skill)
+    }
+
+    /// Check a plan against the step cap, the offered tools and the policy.
+    fn validate(&self, skill: &SkillDefinition, tools: &[&ToolSpec]) -> anyhow::Result<()> {
+        if skill.steps.is_empty() {
+            anyhow::bail!("plan has no steps");
+        }
+        if skill.steps.len() > self.max_steps {
+            anyhow::bail!(
+                "plan has {} steps, more than the maximum of {}",
+                skill.steps.len(),
+                self.max_steps
+            );
+        }
+        for (i, step) in skill.steps.iter().enumerate() {
+            if !tools.iter().any(|spec| spec.name == step.tool_name) {
+                anyhow::bail!("step {} references unknown tool '{}'", i, step.tool_name);
+            }
+            if self.denied(&step.tool_name, &step.arguments) {
+                anyhow::bail!(
+                    "step {} uses tool '{}', which policy denies",
+                    i,
+                    step.tool_name
+                );
+            }
+        }
+        // Tool names were checked against the specs above; the executor only
+        // needs to check the dependency graph.
+        SkillExecutor::new(&ToolRegistry::new()).validate_dependencies(skill)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::PolicyConfig;
+    use crate::provider::{ChatResponse, ProviderError, StubProvider};
+    use async_trait::async_trait;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Returns queued responses in order and records every request.
+    struct ScriptedProvider {
+        responses: Mutex<VecDeque<String>>,
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(responses: &[&str]) -> Self {
+            Self {
+                responses: Mutex::new(responses.iter().map(|r| r.to_string()).collect()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn capabilities(&self) -> crate::provider::ProviderCapabilities {
+            StubProvider::default().capabilities()
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ProviderError> {
+            self.requests.lock().unwrap().push(request);
+            let content = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| anyhow::anyhow!("no more scripted responses"))?;
+            Ok(ChatResponse {
+                content,
+                tool_calls: vec![],
+                usage: None,
+                cached: false,
+                model: None,
+                finish_reason: None,
+                alternates: Vec::new(),
+            })
+        }
+
+        async fn chat_with_tools(
+            &self,
+            request: ChatRequest,
+            _tools: &[ToolSpec],
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(request).await
+        }
+    }
+
+    fn spec(name: &str) -> ToolSpec {
+        ToolSpec {
+            name: name.to_string(),
+            description: format!("The {name} tool"),
+            parameters_schema: json!({ "type": "object" }),
+        }
+    }
+
+    fn tools() -> Vec<ToolSpec> {
+        vec![spec("echo"), spec("hardware"), spec("shell")]
+    }
+
+    const VALID_PLAN: &str = r#"{
+        "name": "greet-twice",
+        "description": "Echo a greeting, then echo it again",
+        "steps": [
+            { "tool_name": "echo", "arguments": { "input": "hi" } },
+            { "tool_name": "echo", "arguments": { "input": "hi again" }, "depends_on": [0] }
+        ]
+    }"#;
+
+    const CYCLIC_PLAN: &str = r#"{
+        "name": "loop",
+        "steps": [
+            { "tool_name": "echo", "depends_on": [1] },
+            { "tool_name": "echo", "depends_on": [0] }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn plans_skill_from_stub_provider() {
+        let provider = StubProvider {
+            response_text: VALID_PLAN.to_string(),
+        };
+        let skill = SkillPlanner::new("stub")
+            .plan("say hi twice", &tools(), &provider)
+            .await
+            .unwrap();
+        assert_eq!(skill.name, "greet-twice");
+        assert_eq!(skill.steps.len(), 2);
+        assert_eq!(skill.steps[1].depends_on, vec![0]);
+        assert_eq!(skill.tags, vec!["planned"]);
+    }
+
+    #[tokio::test]
+    async fn request_uses_structured_output_limited_to_tools() {
+        let provider = ScriptedProvider::new(&[VALID_PLAN]);
+        let policy = PolicyEngine::from_config(PolicyConfig {
+            denied_tools: vec!["shell".into()],
+            ..Default::default()
+        });
+        SkillPlanner::new("stub")
+            .with_max_steps(3)
+            .with_policy(&policy)
+            .plan("say hi twice", &tools(), &provider)
+            .await
+            .unwrap();
+
+        let requests = provider.requests.lock().unwrap();
+        let schema = requests[0]
+            .response_format
+            .as_ref()
+            .unwrap()
+            .schema()
+            .unwrap();
+        assert_eq!(schema["properties"]["steps"]["maxItems"], 3);
+        assert_eq!(
+            schema["properties"]["steps"]["items"]["properties"]["tool_name"]["enum"],
+            json!(["echo", "hardware"])
+        );
+        assert!(!requests[0].messages[0].content.text().contains("shell"));
+    }
+
+    #[tokio::test]
+    async fn invalid_plan_is_retried_with_errors() {
+        let provider = ScriptedProvider::new(&[CYCLIC_PLAN, VALID_PLAN]);
+        let skill = SkillPlanner::new("stub")
+            .plan("say hi twice", &tools(), &provider)
+            .await
+            .unwrap();
+        assert_eq!(skill.name, "greet-twice");
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let retry = &requests[1].messages;
+        assert_eq!(retry.len(), 4);
+        assert_eq!(retry[2].role, ChatRole::Assistant);
+        let feedback = retry[3].content.text();
+        assert!(feedback.contains("That plan is invalid"));
+        assert!(feedback.contains("cycle"), "{feedback}");
+    }
+
+    #[tokio::test]
+    async fn second_invalid_plan_fails() {
+        let provider = ScriptedProvider::new(&[CYCLIC_PLAN, "not json"]);
+        let err = SkillPlanner::new("stub")
+            .plan("loop", &tools(), &provider)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid plan"));
+        assert_eq!(provider.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn plans_over_the_step_cap_are_rejected() {
+        let provider = StubProvider {
+            response_text: VALID_PLAN.to_string(),
+        };
+        let err = SkillPlanner::new("stub")
+            .with_max_steps(1)
+            .plan("say hi twice", &tools(), &provider)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid plan"));
+    }
+
+    #[tokio::test]
+    async fn denied_tools_are_refused() {
+        let plan = r#"{ "name": "danger", "steps": [ { "tool_name": "shell" } ] }"#;
+        let provider = StubProvider {
+            response_text: plan.to_string(),
+        };
+        let policy = PolicyEngine::from_config(PolicyConfig {
+            denied_tools: vec!["shell".into()],
+            ..Default::default()
+        });
+        let planner = SkillPlanner::new("stub").with_policy(&policy);
+        assert!(planner.plan("run ls", &tools(), &provider).await.is_err());
+
+        let err = planner
+            .plan("run ls", &[spec("shell")], &provider)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no tools are available"));
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! A skill is a higher-level abstraction over tools — it composes multiple
 //! tool calls into a reusable workflow with dependency ordering.
+//!
+//! Skills can be shared as portable YAML or JSON manifests; see
+//! [`SkillDefinition::to_manifest`] and [`SkillDefinition::from_manifest`].
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::tool::ToolRegistry;
+use crate::policy::{PolicyAction, PolicyDecision, PolicyEngine, RiskLevel};
+use crate::tool::{
+    LogLevel, ProgressReporter, Tool, ToolContent, ToolLookupError, ToolRegistry, ToolResult,
+};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -19,13 +29,20 @@ pub struct SkillStep {
     /// Name of the tool to invoke.
     pub tool_name: String,
     /// JSON arguments to pass to the tool.
+    #[serde(default = "empty_arguments")]
     pub arguments: serde_json::Value,
     /// Human-readable description of what this step does.
+    #[serde(default)]
     pub description: String,
     /// Indices of steps this step depends on (must complete first).
+    #[serde(default)]
     pub depends_on: Vec<usize>,
 }
 
+fn empty_arguments() -> serde_json::Value {
+    json!({})
+}
+
 /// Definition of a reusable skill.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillDefinition {
@@ -46,6 +63,60 @@ pub struct StepResult {
     pub success: bool,
     pub output: String,
     pub duration_ms: u64,
+    /// The step was not run because a dependency failed.
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+/// One step of an [`ExecutionPlan`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedStep {
+    pub step_index: usize,
+    /// Tool name as written in the skill.
+    pub tool_name: String,
+    /// Registered tool the name resolves to.
+    pub resolved_tool: Option<String>,
+    /// Why the name does not resolve to a single tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution_error: Option<String>,
+    /// Arguments the tool would receive. Skills pass arguments verbatim;
+    /// nothing is substituted from earlier outputs.
+    pub arguments: Value,
+    pub depends_on: Vec<usize>,
+    /// What the policy would decide, when the executor has one.
+    pub decision: Option<PolicyDecision>,
+}
+
+impl PlannedStep {
+    /// The policy would deny this step.
+    pub fn is_denied(&self) -> bool {
+        self.decision
+            .as_ref()
+            .is_some_and(|d| d.action == PolicyAction::Deny)
+    }
+}
+
+/// What [`SkillExecutor::execute`] would do, without running anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    pub skill_name: String,
+    /// Steps in the order they would run.
+    pub steps: Vec<PlannedStep>,
+    /// Highest risk among the policy decisions.
+    pub highest_risk: Option<RiskLevel>,
+    /// Indices of steps the policy would deny.
+    pub denied_steps: Vec<usize>,
+    /// Indices of steps that need approval first.
+    pub approval_steps: Vec<usize>,
+    /// Indices of steps whose tool does not resolve.
+    pub unresolved_steps: Vec<usize>,
+}
+
+impl ExecutionPlan {
+    /// No step would be denied or fail to find its tool.
+    pub fn is_runnable(&self) -> bool {
+        self.denied_steps.is_empty() && self.unresolved_steps.is_empty()
+    }
 }
 
 /// Result of executing an entire skill.
@@ -58,6 +129,220 @@ pub struct SkillExecution {
     pub overall_success: bool,
 }
 
+// ---------------------------------------------------------------------------
+// Manifests
+// ---------------------------------------------------------------------------
+
+/// Manifest format version written by [`SkillDefinition::to_manifest`] and
+/// accepted by [`SkillDefinition::from_manifest`].
+pub const MANIFEST_VERSION: u64 = 1;
+
+/// Serialization format of a skill manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Yaml,
+    Json,
+}
+
+impl ManifestFormat {
+    /// Pick a format from a file extension; anything but `.json` is YAML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ManifestFormat::Json,
+            _ => ManifestFormat::Yaml,
+        }
+    }
+}
+
+/// On-disk shape of a skill manifest.
+#[derive(Debug, Serialize, Deserialize)]
+struct SkillManifest {
+    manifest_version: u64,
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    steps: Vec<SkillStep>,
+}
+
+/// JSON Schema describing a skill manifest.
+///
+/// [`SkillDefinition::from_manifest`] validates incoming manifests against
+/// this schema before deserializing them.
+pub fn manifest_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "YGN Skill Manifest",
+        "type": "object",
+        "required": ["manifest_version", "name", "version", "steps"],
+        "additionalProperties": false,
+        "properties": {
+            "manifest_version": { "type": "integer", "enum": [MANIFEST_VERSION] },
+            "name": { "type": "string", "minLength": 1 },
+            "version": { "type": "string" },
+            "description": { "type": "string" },
+            "author": { "type": "string" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "created_at": { "type": "string", "format": "date-time" },
+            "steps": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["tool_name"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "tool_name": { "type": "string", "minLength": 1 },
+                        "arguments": { "type": "object" },
+                        "description": { "type": "string" },
+                        "depends_on": {
+                            "type": "array",
+                            "items": { "type": "integer", "minimum": 0 }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Render a JSON pointer (`/steps/2/depends_on/0`) as a manifest path
+/// (`steps[2].depends_on[0]`), or `(root)` for the empty pointer.
+fn manifest_path(pointer: &str) -> String {
+    let mut path = String::new();
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        if token.parse::<usize>().is_ok() {
+            path.push_str(&format!("[{token}]"));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&token);
+        }
+    }
+    if path.is_empty() {
+        "(root)".to_string()
+    } else {
+        path
+    }
+}
+
+/// Validate `value` against [`manifest_schema`], returning one
+/// `"<path>: <problem>"` message per violation.
+fn manifest_errors(value: &Value) -> Vec<String> {
+    use jsonschema::error::{TypeKind, ValidationErrorKind};
+
+    let validator = jsonschema::options()
+        .should_validate_formats(true)
+        .build(&manifest_schema())
+        .expect("manifest schema is valid");
+
+    let mut errors = Vec::new();
+    for error in validator.iter_errors(value) {
+        let pointer = error.instance_path.to_string();
+        let at = manifest_path(&pointer);
+        match &error.kind {
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                for key in unexpected {
+                    errors.push(format!(
+                        "{}: unknown field",
+                        manifest_path(&format!("{pointer}/{key}"))
+                    ));
+                }
+            }
+            ValidationErrorKind::Required { property } => {
+                let field = property
+                    .as_str()
+                    .map_or_else(|| property.to_string(), String::from);
+                errors.push(format!("{at}: missing required field '{field}'"));
+            }
+            ValidationErrorKind::Type {
+                kind: TypeKind::Single(expected),
+            } => {
+                let expected = expected.to_string();
+                let article = if matches!(expected.as_str(), "integer" | "object" | "array") {
+                    "an"
+                } else {
+                    "a"
+                };
+                errors.push(format!("{at}: must be {article} {expected}"));
+            }
+            ValidationErrorKind::Enum { options } => {
+                let list: Vec<String> = options
+                    .as_array()
+                    .map(|a| a.iter().map(Value::to_string).collect())
+                    .unwrap_or_default();
+                errors.push(format!("{at}: must be one of {}", list.join(", ")));
+            }
+            ValidationErrorKind::Minimum { limit } => {
+                errors.push(format!("{at}: must be at least {limit}"));
+            }
+            ValidationErrorKind::MinLength { .. } => {
+                errors.push(format!("{at}: must not be empty"));
+            }
+            ValidationErrorKind::Format { format } if format == "date-time" => {
+                errors.push(format!("{at}: must be an RFC 3339 date-time"));
+            }
+            _ => errors.push(format!("{at}: {error}")),
+        }
+    }
+    errors
+}
+
+impl SkillDefinition {
+    /// Serialize this skill as a portable manifest.
+    pub fn to_manifest(&self, format: ManifestFormat) -> anyhow::Result<String> {
+        let manifest = SkillManifest {
+            manifest_version: MANIFEST_VERSION,
+            name: self.name.clone(),
+            version: self.version.clone(),
+            description: self.description.clone(),
+            author: self.author.clone(),
+            tags: self.tags.clone(),
+            created_at: Some(self.created_at),
+            steps: self.steps.clone(),
+        };
+        Ok(match format {
+            ManifestFormat::Yaml => serde_yaml::to_string(&manifest)?,
+            ManifestFormat::Json => serde_json::to_string_pretty(&manifest)?,
+        })
+    }
+
+    /// Parse a YAML or JSON manifest, validating it against
+    /// [`manifest_schema`].
+    ///
+    /// Every schema violation is reported, one per line, with its path
+    /// (e.g. `steps[2].depends_on[0]: must be an integer`). A manifest
+    /// without `created_at` is stamped with the current time.
+    pub fn from_manifest(text: &str) -> anyhow::Result<Self> {
+        // YAML is a superset of JSON, so one parser handles both formats.
+        let value: Value = serde_yaml::from_str(text)
+            .map_err(|e| anyhow::anyhow!("invalid skill manifest: {e}"))?;
+
+        let errors = manifest_errors(&value);
+        if !errors.is_empty() {
+            anyhow::bail!("invalid skill manifest:\n  {}", errors.join("\n  "));
+        }
+
+        let manifest: SkillManifest = serde_json::from_value(value)?;
+        Ok(SkillDefinition {
+            name: manifest.name,
+            description: manifest.description,
+            version: manifest.version,
+            author: manifest.author,
+            steps: manifest.steps,
+            tags: manifest.tags,
+            created_at: manifest.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // SkillRegistry
 // ---------------------------------------------------------------------------
@@ -116,35 +401,264 @@ impl SkillRegistry {
     pub fn is_empty(&self) -> bool {
         self.skills.is_empty()
     }
+
+    /// Register every `.yaml`, `.yml` or `.json` manifest in `dir`,
+    /// returning how many were loaded.
+    ///
+    /// A missing directory loads nothing. Manifests that fail validation or
+    /// collide with an already registered skill are skipped with a warning.
+    pub fn load_dir(&mut self, dir: &Path) -> anyhow::Result<usize> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                matches!(
+                    p.extension().and_then(|e| e.to_str()),
+                    Some("yaml" | "yml" | "json")
+                )
+            })
+            .collect();
+        paths.sort();
+
+        let mut loaded = 0;
+        for path in paths {
+            let skill = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| SkillDefinition::from_manifest(&text))
+                .and_then(|skill| self.register(skill));
+            match skill {
+                Ok(()) => loaded += 1,
+                Err(e) => tracing::warn!(path = %path.display(), "skipping skill manifest: {e}"),
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+/// Default directory for imported skill manifests (`~/.ygn/skills`).
+pub fn default_skills_dir() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/skills")
+}
+
+/// Skills bundled with ygn-core, plus those imported into
+/// [`default_skills_dir`].
+pub fn builtin_skills() -> anyhow::Result<SkillRegistry> {
+    let mut skill_registry = SkillRegistry::new();
+
+    // Register a sample "health-check" skill that uses the echo tool.
+    let health_check = SkillDefinition {
+        name: "health-check".to_string(),
+        description: "Run a basic echo-based health check".to_string(),
+        version: "1.0.0".to_string(),
+        author: "ygn-core".to_string(),
+        steps: vec![SkillStep {
+            tool_name: "echo".to_string(),
+            arguments: json!({"input": "health-ok"}),
+            description: "Echo a health ping".to_string(),
+            depends_on: vec![],
+        }],
+        tags: vec!["health".to_string(), "builtin".to_string()],
+        created_at: Utc::now(),
+    };
+    skill_registry.register(health_check)?;
+
+    skill_registry.load_dir(Path::new(&default_skills_dir()))?;
+    Ok(skill_registry)
+}
+
+/// Write `skill` as `<dir>/<name>.yaml`, creating `dir` if needed, so that
+/// [`SkillRegistry::load_dir`] picks it up. Returns the written path.
+pub fn store_manifest(dir: &Path, skill: &SkillDefinition) -> anyhow::Result<PathBuf> {
+    if skill.name.starts_with('.') || skill.name.contains(['/', '\\']) {
+        anyhow::bail!("skill name '{}' cannot be used as a file name", skill.name);
+    }
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.yaml", skill.name));
+    std::fs::write(&path, skill.to_manifest(ManifestFormat::Yaml)?)?;
+    Ok(path)
 }
 
 // ---------------------------------------------------------------------------
 // SkillExecutor
 // ---------------------------------------------------------------------------
 
+/// Logger name of the messages [`SkillExecutor`] logs.
+const SKILLS_LOGGER: &str = "skills";
+
 /// Validates and executes skills using a reference to the tool registry.
 pub struct SkillExecutor<'a> {
     tool_registry: &'a ToolRegistry,
+    /// Consulted by [`SkillExecutor::plan`].
+    policy: Option<&'a PolicyEngine>,
+    /// Told about every finished step.
+    progress: ProgressReporter,
 }
 
 impl<'a> SkillExecutor<'a> {
     /// Create a new executor bound to the given tool registry.
     pub fn new(tool_registry: &'a ToolRegistry) -> Self {
-        Self { tool_registry }
+        Self {
+            tool_registry,
+            policy: None,
+            progress: ProgressReporter::noop(),
+        }
+    }
+
+    /// Report each finished (or skipped) step through `progress`, counting
+    /// steps out of the skill's total.  The skill's start, steps and outcome
+    /// also go to the reporter's logger, if any, as `skills`.
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn report_step(&self, done: usize, total: usize, result: &StepResult) {
+        let (outcome, level) = if result.skipped {
+            ("skipped", LogLevel::Notice)
+        } else if result.success {
+            ("succeeded", LogLevel::Info)
+        } else {
+            ("failed", LogLevel::Warning)
+        };
+        let message = format!(
+            "step {} ({}) {outcome}",
+            result.step_index, result.tool_name
+        );
+        self.progress.log(level, SKILLS_LOGGER, message.clone());
+        if self.progress.is_active() {
+            self.progress
+                .report(done as f64, Some(total as f64), Some(message));
+        }
+    }
+
+    /// Log the start of `skill` through the progress reporter's logger.
+    fn log_started(&self, skill: &SkillDefinition) {
+        self.progress.log(
+            LogLevel::Info,
+            SKILLS_LOGGER,
+            format!(
+                "running skill '{}' ({} steps)",
+                skill.name,
+                skill.steps.len()
+            ),
+        );
+    }
+
+    /// Log how `execution` ended, then hand it back.
+    fn finished(&self, execution: SkillExecution) -> SkillExecution {
+        let (level, outcome) = match execution.overall_success {
+            true => (LogLevel::Info, "succeeded"),
+            false => (LogLevel::Warning, "failed"),
+        };
+        self.progress.log(
+            level,
+            SKILLS_LOGGER,
+            format!("skill '{}' {outcome}", execution.skill_name),
+        );
+        execution
+    }
+
+    /// Include this policy's decision for each step in [`plan`](Self::plan).
+    pub fn with_policy(mut self, policy: &'a PolicyEngine) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Report how a skill would run, without executing any tool: steps in
+    /// dependency order, the tool each resolves to, its arguments and the
+    /// policy decision.  Policy rate limits are not consumed.  Errors if
+    /// the dependency graph is invalid.
+    pub fn plan(&self, skill: &SkillDefinition) -> anyhow::Result<ExecutionPlan> {
+        self.validate_dependencies(skill)?;
+        let order = self.topological_sort(&skill.steps)?;
+        let mut plan = ExecutionPlan {
+            skill_name: skill.name.clone(),
+            steps: Vec::with_capacity(order.len()),
+            highest_risk: None,
+            denied_steps: Vec::new(),
+            approval_steps: Vec::new(),
+            unresolved_steps: Vec::new(),
+        };
+        for idx in order {
+            let step = &skill.steps[idx];
+            let (resolved_tool, resolution_error) =
+                match self.tool_registry.resolve(&step.tool_name) {
+                    Ok(name) => (Some(name.to_string()), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+            let decision = self.policy.map(|policy| {
+                let name = resolved_tool.as_deref().unwrap_or(&step.tool_name);
+                policy.preview(name, &step.arguments)
+            });
+            if resolved_tool.is_none() {
+                plan.unresolved_steps.push(idx);
+            }
+            if let Some(decision) = &decision {
+                match decision.action {
+                    PolicyAction::Deny => plan.denied_steps.push(idx),
+                    PolicyAction::RequireApproval => plan.approval_steps.push(idx),
+                    PolicyAction::Allow | PolicyAction::RateLimited => {}
+                }
+                if plan.highest_risk.as_ref() < Some(&decision.risk_level) {
+                    plan.highest_risk = Some(decision.risk_level.clone());
+                }
+            }
+            plan.steps.push(PlannedStep {
+                step_index: idx,
+                tool_name: step.tool_name.clone(),
+                resolved_tool,
+                resolution_error,
+                arguments: step.arguments.clone(),
+                depends_on: step.depends_on.clone(),
+                decision,
+            });
+        }
+        Ok(plan)
     }
 
     /// Validate a skill definition:
-    /// - Every tool_name must exist in the tool registry.
+    /// - Every tool_name must resolve to exactly one tool in the registry
+    ///   (see [`ToolRegistry::resolve`]).
     /// - Dependency indices must be in range.
     /// - The dependency graph must be acyclic.
     pub fn validate(&self, skill: &SkillDefinition) -> anyhow::Result<()> {
+        for (i, step) in skill.steps.iter().enumerate() {
+            match self.tool_registry.resolve(&step.tool_name) {
+                Ok(_) => {}
+                Err(ToolLookupError::NotFound(name)) => {
+                    anyhow::bail!("step {} references unknown tool '{}'", i, name)
+                }
+                Err(e) => anyhow::bail!("step {}: {}", i, e),
+            }
+        }
+        self.validate_dependencies(skill)
+    }
+
+    /// Steps (index, tool name) whose tool name does not resolve to a
+    /// single tool in the registry.
+    pub fn unknown_tools<'s>(&self, skill: &'s SkillDefinition) -> Vec<(usize, &'s str)> {
+        skill
+            .steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| self.tool_registry.get(&step.tool_name).is_none())
+            .map(|(i, step)| (i, step.tool_name.as_str()))
+            .collect()
+    }
+
+    /// Validate only the dependency graph: indices must be in range and
+    /// the graph must be acyclic.
+    pub fn validate_dependencies(&self, skill: &SkillDefinition) -> anyhow::Result<()> {
         let step_count = skill.steps.len();
 
-        // Check tool existence and dependency indices.
         for (i, step) in skill.steps.iter().enumerate() {
-            if self.tool_registry.get(&step.tool_name).is_none() {
-                anyhow::bail!("step {} references unknown tool '{}'", i, step.tool_name);
-            }
             for &dep in &step.depends_on {
                 if dep >= step_count {
                     anyhow::bail!(
@@ -168,68 +682,158 @@ impl<'a> SkillExecutor<'a> {
         let started_at = Utc::now();
         let mut step_results = Vec::new();
         let mut overall_success = true;
+        self.log_started(skill);
 
         let order = match self.topological_sort(&skill.steps) {
             Ok(o) => o,
             Err(_) => {
-                return SkillExecution {
+                return self.finished(SkillExecution {
                     skill_name: skill.name.clone(),
                     started_at,
                     completed_at: Some(Utc::now()),
                     step_results,
                     overall_success: false,
-                };
+                });
             }
         };
 
         for &idx in &order {
-            let step = &skill.steps[idx];
-            let step_start = std::time::Instant::now();
-
-            let result = if let Some(tool) = self.tool_registry.get(&step.tool_name) {
-                match tool.execute(step.arguments.clone()).await {
-                    Ok(tr) => StepResult {
-                        step_index: idx,
-                        tool_name: step.tool_name.clone(),
-                        success: tr.success,
-                        output: tr.output,
-                        duration_ms: step_start.elapsed().as_millis() as u64,
-                    },
-                    Err(e) => {
-                        overall_success = false;
-                        StepResult {
-                            step_index: idx,
-                            tool_name: step.tool_name.clone(),
-                            success: false,
-                            output: e.to_string(),
-                            duration_ms: step_start.elapsed().as_millis() as u64,
-                        }
+            let result = self.run_step(idx, &skill.steps[idx]).await;
+            if !result.success {
+                overall_success = false;
+            }
+            self.report_step(step_results.len() + 1, order.len(), &result);
+            step_results.push(result);
+        }
+
+        self.finished(SkillExecution {
+            skill_name: skill.name.clone(),
+            started_at,
+            completed_at: Some(Utc::now()),
+            step_results,
+            overall_success,
+        })
+    }
+
+    /// Execute a skill, running every step whose dependencies have succeeded
+    /// concurrently, with at most `max_concurrency` steps in flight.
+    ///
+    /// A step only starts once all of its `depends_on` steps completed
+    /// successfully; if a dependency fails, every downstream step is
+    /// reported as skipped instead of being run. Results are collected in
+    /// completion order.
+    pub async fn execute_parallel(
+        &self,
+        skill: &SkillDefinition,
+        max_concurrency: usize,
+    ) -> SkillExecution {
+        let started_at = Utc::now();
+        let mut step_results = Vec::new();
+        self.log_started(skill);
+
+        if self.validate_dependencies(skill).is_err() {
+            return self.finished(SkillExecution {
+                skill_name: skill.name.clone(),
+                started_at,
+                completed_at: Some(Utc::now()),
+                step_results,
+                overall_success: false,
+            });
+        }
+
+        let n = skill.steps.len();
+        let mut pending_deps: Vec<usize> = skill.steps.iter().map(|s| s.depends_on.len()).collect();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (i, step) in skill.steps.iter().enumerate() {
+            for &dep in &step.depends_on {
+                dependents[dep].push(i);
+            }
+        }
+
+        let mut ready: std::collections::VecDeque<usize> =
+            (0..n).filter(|&i| pending_deps[i] == 0).collect();
+        let mut skipped = vec![false; n];
+        let mut in_flight = futures_util::stream::FuturesUnordered::new();
+        let max_concurrency = max_concurrency.max(1);
+
+        loop {
+            while in_flight.len() < max_concurrency {
+                let Some(idx) = ready.pop_front() else { break };
+                in_flight.push(self.run_step(idx, &skill.steps[idx]));
+            }
+
+            let Some(result) = in_flight.next().await else {
+                break;
+            };
+
+            if result.success {
+                for &next in &dependents[result.step_index] {
+                    pending_deps[next] -= 1;
+                    if pending_deps[next] == 0 && !skipped[next] {
+                        ready.push_back(next);
                     }
                 }
             } else {
-                overall_success = false;
-                StepResult {
-                    step_index: idx,
-                    tool_name: step.tool_name.clone(),
-                    success: false,
-                    output: format!("tool '{}' not found", step.tool_name),
-                    duration_ms: step_start.elapsed().as_millis() as u64,
+                // Skip everything downstream of the failed step.
+                let mut stack = vec![result.step_index];
+                while let Some(failed) = stack.pop() {
+                    for &next in &dependents[failed] {
+                        if skipped[next] {
+                            continue;
+                        }
+                        skipped[next] = true;
+                        stack.push(next);
+                        let skipped = StepResult {
+                            step_index: next,
+                            tool_name: skill.steps[next].tool_name.clone(),
+                            success: false,
+                            output: format!("skipped: dependency step {failed} did not succeed"),
+                            duration_ms: 0,
+                            skipped: true,
+                        };
+                        self.report_step(step_results.len() + 1, n, &skipped);
+                        step_results.push(skipped);
+                    }
                 }
-            };
-
-            if !result.success {
-                overall_success = false;
             }
-
+            self.report_step(step_results.len() + 1, n, &result);
             step_results.push(result);
         }
 
-        SkillExecution {
+        let overall_success = step_results.iter().all(|r| r.success);
+        self.finished(SkillExecution {
             skill_name: skill.name.clone(),
             started_at,
             completed_at: Some(Utc::now()),
             step_results,
             overall_success,
+        })
+    }
+
+    /// Run a single step against the tool registry.
+    async fn run_step(&self, idx: usize, step: &SkillStep) -> StepResult {
+        let step_start = std::time::Instant::now();
+        let (success, output) = match self.tool_registry.resolve(&step.tool_name) {
+            Ok(name) => match self
+                .tool_registry
+                .execute(name, step.arguments.clone())
+                .await
+            {
+                Ok(tr) => (tr.success, tr.output().into_owned()),
+                Err(e) => (false, e.to_string()),
+            },
+            Err(ToolLookupError::NotFound(_)) => {
+                (false, format!("tool '{}' not found", step.tool_name))
+            }
+            Err(e) => (false, e.to_string()),
+        };
+        StepResult {
+            step_index: idx,
+            tool_name: step.tool_name.clone(),
+            success,
+            output,
+            duration_ms: step_start.elapsed().as_millis() as u64,
+            skipped: false,
         }
     }
 
@@ -273,6 +877,93 @@ impl<'a> SkillExecutor<'a> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// SkillTool
+// ---------------------------------------------------------------------------
+
+/// Exposes a [`SkillRegistry`] as the `run_skill` tool, so that clients
+/// such as MCP can run a skill by name and follow it step by step.
+///
+/// Steps run against the tool registry the `SkillTool` owns, not the one
+/// it is registered in.
+pub struct SkillTool {
+    skills: SkillRegistry,
+    tools: ToolRegistry,
+}
+
+impl std::fmt::Debug for SkillTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkillTool")
+            .field("skills", &self.skills.list().len())
+            .field("tools", &self.tools.len())
+            .finish()
+    }
+}
+
+impl SkillTool {
+    pub fn new(skills: SkillRegistry, tools: ToolRegistry) -> Self {
+        Self { skills, tools }
+    }
+}
+
+#[async_trait]
+impl Tool for SkillTool {
+    fn name(&self) -> &str {
+        "run_skill"
+    }
+
+    fn description(&self) -> &str {
+        "Run a registered skill by name and return the result of every step"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name of the skill to run"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        self.execute_with_progress(args, &ProgressReporter::noop())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        args: Value,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<ToolResult> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: name"))?;
+        let Some(skill) = self.skills.get(name) else {
+            return Ok(ToolResult {
+                success: false,
+                content: ToolContent::default(),
+                error: Some(format!("unknown skill '{name}'")),
+                truncated: None,
+            });
+        };
+        let execution = SkillExecutor::new(&self.tools)
+            .with_progress(progress.clone())
+            .execute(skill)
+            .await;
+        Ok(ToolResult {
+            success: execution.overall_success,
+            content: ToolContent::json(&execution)?,
+            error: (!execution.overall_success).then(|| format!("skill '{name}' failed")),
+            truncated: None,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -280,7 +971,76 @@ impl<'a> SkillExecutor<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tool::{EchoTool, ToolRegistry};
+    use crate::tool::{EchoTool, Tool, ToolRegistry, ToolResult};
+    use async_trait::async_trait;
+
+    /// Echoes `input` after sleeping `delay_ms`; fails when `fail` is true.
+    struct SlowEchoTool;
+
+    #[async_trait]
+    impl Tool for SlowEchoTool {
+        fn name(&self) -> &str {
+            "slow_echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo after a delay"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+            let delay = args["delay_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            Ok(ToolResult {
+                success: !args["fail"].as_bool().unwrap_or(false),
+                content: args["input"].as_str().unwrap_or_default().into(),
+                error: None,
+                truncated: None,
+            })
+        }
+    }
+
+    /// An `echo` that counts its calls.
+    struct CountingEcho(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl Tool for CountingEcho {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Counting echo"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            EchoTool.execute(args).await
+        }
+    }
+
+    fn slow_step(args: Value, depends_on: Vec<usize>) -> SkillStep {
+        SkillStep {
+            tool_name: "slow_echo".to_string(),
+            arguments: args,
+            description: String::new(),
+            depends_on,
+        }
+    }
+
+    fn skill_with_steps(steps: Vec<SkillStep>) -> SkillDefinition {
+        SkillDefinition {
+            steps,
+            ..sample_skill()
+        }
+    }
 
     fn sample_skill() -> SkillDefinition {
         SkillDefinition {
@@ -378,6 +1138,139 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("unknown tool"));
     }
 
+    #[tokio::test]
+    async fn validate_resolves_namespaced_tools() {
+        let mut tool_reg = ToolRegistry::new();
+        tool_reg.register_in("node-a", Box::new(EchoTool));
+        let executor = SkillExecutor::new(&tool_reg);
+        executor.validate(&sample_skill()).unwrap();
+        assert!(executor.execute(&sample_skill()).await.overall_success);
+
+        tool_reg.register_in("node-b", Box::new(EchoTool));
+        let executor = SkillExecutor::new(&tool_reg);
+        let err = executor.validate(&sample_skill()).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "step 0: ambiguous tool name 'echo': matches node-a/echo, node-b/echo"
+        );
+        assert_eq!(executor.unknown_tools(&sample_skill()).len(), 2);
+    }
+
+    #[test]
+    fn plan_orders_steps_and_flags_denied_ones() {
+        let tool_reg = tool_registry_with_echo();
+        let policy = PolicyEngine::from_config(crate::policy::PolicyConfig {
+            denied_tools: vec!["shell".to_string()],
+            ..Default::default()
+        });
+        let mut skill = sample_skill();
+        // Listed first, but runs last.
+        skill.steps.insert(
+            0,
+            SkillStep {
+                tool_name: "shell".to_string(),
+                arguments: json!({ "command": "rm -rf /" }),
+                description: "Clean up".to_string(),
+                depends_on: vec![2],
+            },
+        );
+        skill.steps[2].depends_on = vec![1];
+
+        let plan = SkillExecutor::new(&tool_reg)
+            .with_policy(&policy)
+            .plan(&skill)
+            .unwrap();
+        let order: Vec<usize> = plan.steps.iter().map(|s| s.step_index).collect();
+        assert_eq!(order, [1, 2, 0]);
+        assert_eq!(plan.steps[0].resolved_tool.as_deref(), Some("echo"));
+        assert_eq!(plan.steps[0].arguments, json!({ "input": "ping" }));
+        assert!(!plan.steps[0].is_denied());
+
+        assert!(plan.steps[2].is_denied());
+        assert_eq!(plan.denied_steps, [0]);
+        assert_eq!(plan.unresolved_steps, [0]);
+        assert_eq!(plan.highest_risk, Some(RiskLevel::Critical));
+        assert!(!plan.is_runnable());
+    }
+
+    #[tokio::test]
+    async fn plan_runs_no_tools() {
+        let mut tool_reg = ToolRegistry::new();
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        tool_reg.register(Box::new(CountingEcho(calls.clone())));
+        let plan = SkillExecutor::new(&tool_reg).plan(&sample_skill()).unwrap();
+        assert_eq!(plan.steps.len(), 2);
+        assert!(plan.steps.iter().all(|s| s.decision.is_none()));
+        assert!(plan.is_runnable());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn skill_tool_reports_each_step() {
+        let mut skills = SkillRegistry::new();
+        skills.register(sample_skill()).unwrap();
+        let tool = SkillTool::new(skills, tool_registry_with_echo());
+        let updates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let progress = ProgressReporter::new(move |p| sink.lock().unwrap().push(p));
+
+        let result = tool
+            .execute_with_progress(json!({ "name": "health-check" }), &progress)
+            .await
+            .unwrap();
+        assert!(result.success);
+        let execution: SkillExecution = serde_json::from_str(&result.output()).unwrap();
+        assert_eq!(execution.step_results.len(), 2);
+
+        let missing = tool.execute(json!({ "name": "nope" })).await.unwrap();
+        assert!(!missing.success);
+        assert_eq!(missing.error.as_deref(), Some("unknown skill 'nope'"));
+
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].progress, 2.0);
+        assert_eq!(updates[1].total, Some(2.0));
+        assert_eq!(
+            updates[0].message.as_deref(),
+            Some("step 0 (echo) succeeded")
+        );
+    }
+
+    #[tokio::test]
+    async fn executor_logs_steps_and_outcome() {
+        let tool_reg = tool_registry_with_echo();
+        let logged = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = logged.clone();
+        let progress = ProgressReporter::noop().with_logger(move |level, logger, message| {
+            sink.lock()
+                .unwrap()
+                .push((level, logger.to_string(), message));
+        });
+        let mut skill = sample_skill();
+        skill.steps[1].tool_name = "missing".into();
+
+        let execution = SkillExecutor::new(&tool_reg)
+            .with_progress(progress)
+            .execute(&skill)
+            .await;
+        assert!(!execution.overall_success);
+
+        let logged = logged.lock().unwrap();
+        let levels: Vec<LogLevel> = logged.iter().map(|(level, ..)| *level).collect();
+        assert_eq!(
+            levels,
+            [
+                LogLevel::Info,
+                LogLevel::Info,
+                LogLevel::Warning,
+                LogLevel::Warning
+            ]
+        );
+        assert!(logged.iter().all(|(_, logger, _)| logger == "skills"));
+        assert_eq!(logged[2].2, "step 1 (missing) failed");
+        assert_eq!(logged[3].2, "skill 'health-check' failed");
+    }
+
     #[test]
     fn validate_cycle_detection() {
         let tool_reg = tool_registry_with_echo();
@@ -455,6 +1348,82 @@ mod tests {
         assert_eq!(execution.step_results[1].output, "pong");
     }
 
+    #[tokio::test]
+    async fn execute_parallel_overlaps_independent_steps() {
+        let mut tool_reg = ToolRegistry::new();
+        tool_reg.register(Box::new(SlowEchoTool));
+        let executor = SkillExecutor::new(&tool_reg);
+        let skill = skill_with_steps(vec![
+            slow_step(json!({"input": "a", "delay_ms": 100}), vec![]),
+            slow_step(json!({"input": "b", "delay_ms": 100}), vec![]),
+        ]);
+
+        let start = std::time::Instant::now();
+        let execution = executor.execute_parallel(&skill, 4).await;
+        let elapsed = start.elapsed();
+
+        assert!(execution.overall_success);
+        assert_eq!(execution.step_results.len(), 2);
+        assert!(
+            elapsed < std::time::Duration::from_millis(180),
+            "took {elapsed:?}"
+        );
+
+        // With a concurrency of one the steps run back to back.
+        let start = std::time::Instant::now();
+        executor.execute_parallel(&skill, 1).await;
+        assert!(start.elapsed() >= std::time::Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn execute_parallel_waits_for_dependencies() {
+        let mut tool_reg = ToolRegistry::new();
+        tool_reg.register(Box::new(SlowEchoTool));
+        let executor = SkillExecutor::new(&tool_reg);
+        let skill = skill_with_steps(vec![
+            slow_step(json!({"input": "slow", "delay_ms": 50}), vec![]),
+            slow_step(json!({"input": "fast"}), vec![]),
+            slow_step(json!({"input": "after-slow"}), vec![0]),
+        ]);
+
+        let execution = executor.execute_parallel(&skill, 4).await;
+        let order: Vec<usize> = execution
+            .step_results
+            .iter()
+            .map(|r| r.step_index)
+            .collect();
+        assert_eq!(order, vec![1, 0, 2]);
+    }
+
+    #[tokio::test]
+    async fn execute_parallel_skips_downstream_of_failure() {
+        let mut tool_reg = ToolRegistry::new();
+        tool_reg.register(Box::new(SlowEchoTool));
+        let executor = SkillExecutor::new(&tool_reg);
+        let skill = skill_with_steps(vec![
+            slow_step(json!({"input": "boom", "fail": true}), vec![]),
+            slow_step(json!({"input": "child"}), vec![0]),
+            slow_step(json!({"input": "grandchild"}), vec![1]),
+            slow_step(json!({"input": "independent"}), vec![]),
+        ]);
+
+        let execution = executor.execute_parallel(&skill, 4).await;
+        assert!(!execution.overall_success);
+        assert_eq!(execution.step_results.len(), 4);
+
+        let by_index = |i: usize| {
+            execution
+                .step_results
+                .iter()
+                .find(|r| r.step_index == i)
+                .unwrap()
+        };
+        assert!(!by_index(0).success && !by_index(0).skipped);
+        assert!(by_index(1).skipped);
+        assert!(by_index(2).skipped);
+        assert!(by_index(3).success && !by_index(3).skipped);
+    }
+
     #[test]
     fn skill_definition_serialization() {
         let skill = sample_skill();
@@ -465,6 +1434,142 @@ mod tests {
         assert_eq!(round.tags, vec!["health", "diagnostic"]);
     }
 
+    #[test]
+    fn manifest_yaml_round_trip() {
+        let skill = sample_skill();
+        let yaml = skill.to_manifest(ManifestFormat::Yaml).unwrap();
+        assert!(yaml.starts_with("manifest_version: 1\n"));
+
+        let round = SkillDefinition::from_manifest(&yaml).unwrap();
+        assert_eq!(round.name, skill.name);
+        assert_eq!(round.version, skill.version);
+        assert_eq!(round.tags, skill.tags);
+        assert_eq!(round.created_at, skill.created_at);
+        assert_eq!(round.steps.len(), 2);
+        assert_eq!(round.steps[1].arguments, json!({"input": "pong"}));
+        assert_eq!(round.steps[1].depends_on, vec![0]);
+    }
+
+    #[test]
+    fn manifest_json_round_trip() {
+        let skill = sample_skill();
+        let text = skill.to_manifest(ManifestFormat::Json).unwrap();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed["manifest_version"], MANIFEST_VERSION);
+
+        let round = SkillDefinition::from_manifest(&text).unwrap();
+        assert_eq!(round.name, skill.name);
+        assert_eq!(round.steps[0].tool_name, "echo");
+    }
+
+    #[test]
+    fn manifest_optional_fields_default() {
+        let yaml =
+            "manifest_version: 1\nname: minimal\nversion: '0.1'\nsteps:\n  - tool_name: echo\n";
+        let skill = SkillDefinition::from_manifest(yaml).unwrap();
+        assert_eq!(skill.author, "");
+        assert!(skill.tags.is_empty());
+        assert_eq!(skill.steps[0].arguments, json!({}));
+        assert!(skill.steps[0].depends_on.is_empty());
+    }
+
+    #[test]
+    fn malformed_manifest_reports_paths() {
+        let yaml = r#"
+manifest_version: 1
+name: broken
+created_at: yesterday
+steps:
+  - tool_name: echo
+  - tool_name: echo
+  - tool_name: echo
+    depends_on: ["zero", 1]
+    retries: 3
+  - arguments: []
+"#;
+        let err = SkillDefinition::from_manifest(yaml)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("(root): missing required field 'version'"),
+            "{err}"
+        );
+        assert!(
+            err.contains("steps[2].depends_on[0]: must be an integer"),
+            "{err}"
+        );
+        assert!(err.contains("steps[2].retries: unknown field"), "{err}");
+        assert!(
+            err.contains("steps[3]: missing required field 'tool_name'"),
+            "{err}"
+        );
+        assert!(
+            err.contains("steps[3].arguments: must be an object"),
+            "{err}"
+        );
+        assert!(
+            err.contains("created_at: must be an RFC 3339 date-time"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn stored_manifests_load_from_dir() {
+        let dir = std::env::temp_dir().join(format!("ygn-skills-{}", uuid::Uuid::new_v4()));
+        let skill = sample_skill();
+        let path = store_manifest(&dir, &skill).unwrap();
+        assert_eq!(path, dir.join(format!("{}.yaml", skill.name)));
+        std::fs::write(dir.join("broken.yaml"), "name: broken\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let mut reg = SkillRegistry::new();
+        assert_eq!(reg.load_dir(&dir).unwrap(), 1);
+        assert_eq!(reg.get(&skill.name).unwrap().steps.len(), 2);
+        // Already registered skills are skipped, not fatal.
+        assert_eq!(reg.load_dir(&dir).unwrap(), 0);
+
+        let mut bad = sample_skill();
+        bad.name = "../escape".to_string();
+        assert!(store_manifest(&dir, &bad).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reg.load_dir(&dir).unwrap(), 0);
+    }
+
+    #[test]
+    fn manifest_rejects_unsupported_version() {
+        let yaml = "manifest_version: 2\nname: x\nversion: '1'\nsteps: []\n";
+        let err = SkillDefinition::from_manifest(yaml)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("manifest_version: must be one of 1"), "{err}");
+    }
+
+    #[test]
+    fn manifest_format_from_extension() {
+        assert_eq!(
+            ManifestFormat::from_path(Path::new("skill.JSON")),
+            ManifestFormat::Json
+        );
+        assert_eq!(
+            ManifestFormat::from_path(Path::new("skill.yml")),
+            ManifestFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn unknown_tools_lists_every_missing_tool() {
+        let tool_reg = ToolRegistry::new();
+        let executor = SkillExecutor::new(&tool_reg);
+        let skill = sample_skill();
+        assert_eq!(
+            executor.unknown_tools(&skill),
+            vec![(0, "echo"), (1, "echo")]
+        );
+        // Dependency checks do not need the tools to exist.
+        executor.validate_dependencies(&skill).unwrap();
+    }
+
     #[test]
     fn empty_registry() {
         let registry = SkillRegistry::new();
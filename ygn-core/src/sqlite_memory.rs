@@ -3,19 +3,86 @@
 //! Provides persistent memory storage using SQLite with FTS5 full-text search.
 //! Inspired by the ZeroClaw memory architecture.
 
+use anyhow::Context;
 use chrono::Utc;
+use futures_util::Stream;
 use rusqlite::{params, Connection};
-use std::sync::Mutex;
+use std::io::{BufRead, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::memory::{ListOrder, MatchMode, Memory, MemoryCategory, MemoryEntry};
+use crate::schema::{self, Migration};
+
+/// Schema history of the memory store.
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create memories and full-text index",
+        "CREATE TABLE IF NOT EXISTS memories (
+            id         TEXT PRIMARY KEY,
+            key        TEXT NOT NULL,
+            content    TEXT NOT NULL,
+            category   TEXT NOT NULL,
+            session_id TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            embedding  BLOB
+        );
 
-use crate::memory::{Memory, MemoryCategory, MemoryEntry};
+        CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts
+            USING fts5(key, content, content=memories, content_rowid=rowid);
+
+        -- Triggers to keep FTS index in sync with the main table
+        CREATE TRIGGER IF NOT EXISTS memories_ai AFTER INSERT ON memories BEGIN
+            INSERT INTO memories_fts(rowid, key, content)
+            VALUES (new.rowid, new.key, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS memories_ad AFTER DELETE ON memories BEGIN
+            INSERT INTO memories_fts(memories_fts, rowid, key, content)
+            VALUES ('delete', old.rowid, old.key, old.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE ON memories BEGIN
+            INSERT INTO memories_fts(memories_fts, rowid, key, content)
+            VALUES ('delete', old.rowid, old.key, old.content);
+            INSERT INTO memories_fts(rowid, key, content)
+            VALUES (new.rowid, new.key, new.content);
+        END;",
+    ),
+    Migration::sql(
+        2,
+        "add memories.expires_at",
+        "ALTER TABLE memories ADD COLUMN expires_at TEXT;",
+    ),
+    Migration::sql(
+        3,
+        "index memories by session",
+        "UPDATE memories SET session_id = NULL WHERE session_id = '';
+        CREATE INDEX IF NOT EXISTS memories_session
+            ON memories(session_id, category, key);",
+    ),
+];
+
+/// Version of the memory schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+/// Batches of recall results, best match first; see
+/// [`SqliteMemory::recall_stream`].
+pub type RecallStream = Pin<Box<dyn Stream<Item = anyhow::Result<Vec<MemoryEntry>>> + Send>>;
 
 // ---------------------------------------------------------------------------
 // SqliteMemory
 // ---------------------------------------------------------------------------
 
 /// A memory backend backed by SQLite with FTS5 full-text search.
+///
+/// Entries are global unless stored with [`Self::store_in_session`].  The
+/// [`Memory`] methods that address one key, `get` and `forget`, see global
+/// entries only; `recall`, `list` and `count` span every session.
 pub struct SqliteMemory {
-    conn: Mutex<Connection>,
+    conn: Arc<Mutex<Connection>>,
 }
 
 impl std::fmt::Debug for SqliteMemory {
@@ -29,7 +96,7 @@ impl SqliteMemory {
     pub fn new(path: &str) -> anyhow::Result<Self> {
         let conn = Connection::open(path)?;
         let mem = Self {
-            conn: Mutex::new(conn),
+            conn: Arc::new(Mutex::new(conn)),
         };
         mem.init_pragmas()?;
         mem.init_schema()?;
@@ -40,7 +107,7 @@ impl SqliteMemory {
     pub fn in_memory() -> anyhow::Result<Self> {
         let conn = Connection::open_in_memory()?;
         let mem = Self {
-            conn: Mutex::new(conn),
+            conn: Arc::new(Mutex::new(conn)),
         };
         mem.init_pragmas()?;
         mem.init_schema()?;
@@ -57,44 +124,245 @@ impl SqliteMemory {
         Ok(())
     }
 
-    /// Create the memories table and FTS5 virtual table if they don't exist.
+    /// Create or upgrade the schema; see [`MIGRATIONS`].
     fn init_schema(&self) -> anyhow::Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS memories (
-                id         TEXT PRIMARY KEY,
-                key        TEXT NOT NULL,
-                content    TEXT NOT NULL,
-                category   TEXT NOT NULL,
-                session_id TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                embedding  BLOB
-            );
-
-            CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts
-                USING fts5(key, content, content=memories, content_rowid=rowid);
-
-            -- Triggers to keep FTS index in sync with the main table
-            CREATE TRIGGER IF NOT EXISTS memories_ai AFTER INSERT ON memories BEGIN
-                INSERT INTO memories_fts(rowid, key, content)
-                VALUES (new.rowid, new.key, new.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS memories_ad AFTER DELETE ON memories BEGIN
-                INSERT INTO memories_fts(memories_fts, rowid, key, content)
-                VALUES ('delete', old.rowid, old.key, old.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE ON memories BEGIN
-                INSERT INTO memories_fts(memories_fts, rowid, key, content)
-                VALUES ('delete', old.rowid, old.key, old.content);
-                INSERT INTO memories_fts(rowid, key, content)
-                VALUES (new.rowid, new.key, new.content);
-            END;",
-        )?;
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
         Ok(())
     }
+
+    /// Run blocking database work on Tokio's blocking pool, so a slow query
+    /// never stalls an async worker thread.
+    async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> anyhow::Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+            f(&mut conn)
+        })
+        .await?
+    }
+
+    /// Like [`Memory::recall`] without a limit, yielding the matches in
+    /// batches of up to `batch_size` instead of collecting them all.
+    ///
+    /// Each batch is one `LIMIT`/`OFFSET` page of the same BM25-ranked
+    /// query, so the ranking holds across batches.  Pages are read lazily;
+    /// entries stored or removed between batches can shift later pages.
+    pub fn recall_stream(
+        &self,
+        query: &str,
+        category: Option<MemoryCategory>,
+        batch_size: usize,
+    ) -> RecallStream {
+        let batch_size = batch_size.max(1);
+        let memory = Self {
+            conn: Arc::clone(&self.conn),
+        };
+        let state =
+            fts_query(query, MatchMode::AnyWord).map(|fts_query| (memory, fts_query, category, 0));
+        Box::pin(futures_util::stream::try_unfold(
+            state,
+            move |state| async move {
+                let Some((memory, fts_query, category, offset)) = state else {
+                    return Ok(None);
+                };
+                let (query, cat) = (fts_query.clone(), category.clone());
+                let batch = memory
+                    .with_conn(move |conn| {
+                        recall_page(conn, &query, cat.as_ref(), None, batch_size, offset)
+                    })
+                    .await?;
+                if batch.is_empty() {
+                    return Ok(None);
+                }
+                let next = (batch.len() == batch_size)
+                    .then(|| (memory, fts_query, category, offset + batch_size));
+                Ok(Some((batch, next)))
+            },
+        ))
+    }
+
+    /// Like [`Memory::recall`], matching the query's words as `mode` says
+    /// instead of matching any one of them.
+    pub async fn recall_with_mode(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        category: Option<MemoryCategory>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.recall_matching(query, mode, category, None, limit)
+            .await
+    }
+
+    /// Like [`Memory::recall`], but only entries stored in `session_id`.
+    pub async fn recall_in_session(
+        &self,
+        session_id: &str,
+        query: &str,
+        category: Option<MemoryCategory>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.recall_matching(query, MatchMode::AnyWord, category, Some(session_id), limit)
+            .await
+    }
+
+    async fn recall_matching(
+        &self,
+        query: &str,
+        mode: MatchMode,
+        category: Option<MemoryCategory>,
+        session: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let Some(fts_query) = fts_query(query, mode) else {
+            return Ok(Vec::new());
+        };
+        let session = session.map(String::from);
+        self.with_conn(move |conn| {
+            recall_page(
+                conn,
+                &fts_query,
+                category.as_ref(),
+                session.as_deref(),
+                limit,
+                0,
+            )
+        })
+        .await
+    }
+
+    /// Like [`Memory::store`], but scoped to `session_id`: the entry lives
+    /// alongside, not over, an entry with the same key stored globally or
+    /// in another session.
+    pub async fn store_in_session(
+        &self,
+        session_id: &str,
+        category: MemoryCategory,
+        key: &str,
+        content: &str,
+    ) -> anyhow::Result<MemoryEntry> {
+        let (session_id, key, content) =
+            (session_id.to_string(), key.to_string(), content.to_string());
+        self.with_conn(move |conn| upsert(conn, Some(&session_id), category, &key, &content))
+            .await
+    }
+
+    /// Like [`Memory::list`], but only entries stored in `session_id`.
+    pub async fn list_in_session(
+        &self,
+        session_id: &str,
+        category: Option<MemoryCategory>,
+        offset: usize,
+        limit: usize,
+        order: ListOrder,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let session_id = session_id.to_string();
+        self.with_conn(move |conn| {
+            list_page(
+                conn,
+                category.as_ref(),
+                Some(&session_id),
+                offset,
+                limit,
+                order,
+            )
+        })
+        .await
+    }
+
+    /// Delete every entry stored in `session_id`, returning how many.
+    pub async fn forget_session(&self, session_id: &str) -> anyhow::Result<usize> {
+        let session_id = session_id.to_string();
+        self.with_conn(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM memories WHERE session_id = ?1",
+                params![session_id],
+            )?)
+        })
+        .await
+    }
+
+    /// [`Memory::store`] every `(category, key, content)` in one
+    /// transaction, returning the stored entries in order.  A key repeated
+    /// within the batch is updated in place like any other duplicate.
+    pub async fn store_batch(
+        &self,
+        entries: &[(MemoryCategory, String, String)],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let entries = entries.to_vec();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let stored = entries
+                .into_iter()
+                .map(|(category, key, content)| upsert(&tx, None, category, &key, &content))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            tx.commit()?;
+            Ok(stored)
+        })
+        .await
+    }
+
+    /// Load entries written by [`Self::export_jsonl`], one JSON
+    /// [`MemoryEntry`] per line, in one transaction.  Entries keep their id
+    /// and timestamps; one whose key is already stored in its category
+    /// updates that entry instead.  Returns the number of entries read.
+    pub async fn import_jsonl<R: BufRead>(&self, reader: R) -> anyhow::Result<usize> {
+        let mut entries = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: MemoryEntry = serde_json::from_str(&line)
+                .with_context(|| format!("invalid memory entry on line {}", i + 1))?;
+            entries.push(entry);
+        }
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            for entry in &entries {
+                import_entry(&tx, entry)?;
+            }
+            tx.commit()?;
+            Ok(entries.len())
+        })
+        .await
+    }
+
+    /// Write every stored entry as JSON Lines, oldest first, for
+    /// [`Self::import_jsonl`].  Embeddings are not exported.
+    pub async fn export_jsonl<W: Write>(&self, mut writer: W) -> anyhow::Result<usize> {
+        const PAGE: usize = 500;
+        let mut exported = 0;
+        loop {
+            let page = self
+                .with_conn(move |conn| {
+                    let mut stmt = conn.prepare(
+                        "SELECT id, key, content, category, session_id, created_at, updated_at \
+                         FROM memories ORDER BY rowid LIMIT ?1 OFFSET ?2",
+                    )?;
+                    let rows =
+                        stmt.query_map(params![PAGE as i64, exported as i64], row_to_entry)?;
+                    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+                })
+                .await?;
+            for entry in &page {
+                serde_json::to_writer(&mut writer, entry)?;
+                writer.write_all(b"\n")?;
+            }
+            exported += page.len();
+            if page.len() < PAGE {
+                break;
+            }
+        }
+        writer.flush()?;
+        Ok(exported)
+    }
+
     /// Store a memory entry with an optional embedding vector.
     pub async fn store_with_embedding(
         &self,
@@ -104,38 +372,44 @@ impl SqliteMemory {
         session_id: Option<&str>,
         embedding: Option<&[f32]>,
     ) -> anyhow::Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let now = Utc::now();
-        let now_str = now.to_rfc3339();
-        let id = uuid::Uuid::new_v4().to_string();
-        let cat_str = category_to_string(&category);
-
-        let emb_bytes: Option<Vec<u8>> =
-            embedding.map(|e| e.iter().flat_map(|f| f.to_le_bytes()).collect::<Vec<u8>>());
-
-        // Check if key+category already exists — if so, UPDATE instead of INSERT
-        let existing_id: Option<String> = conn
-            .query_row(
-                "SELECT id FROM memories WHERE key = ?1 AND category = ?2",
-                params![key, cat_str],
-                |row| row.get(0),
-            )
-            .ok();
-
-        if let Some(eid) = existing_id {
-            conn.execute(
-                "UPDATE memories SET content = ?1, updated_at = ?2, embedding = ?3 WHERE id = ?4",
-                params![content, &now_str, emb_bytes, &eid],
-            )?;
-        } else {
-            conn.execute(
-                "INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at, embedding) \
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                params![&id, key, content, &cat_str, session_id, &now_str, &now_str, emb_bytes],
-            )?;
-        }
+        let (key, content) = (key.to_string(), content.to_string());
+        let session_id = session_id.map(String::from);
+        let embedding = embedding.map(<[f32]>::to_vec);
+        self.with_conn(move |conn| {
+            let now = Utc::now();
+            let now_str = now.to_rfc3339();
+            let id = uuid::Uuid::new_v4().to_string();
+            let cat_str = category_to_string(&category);
+
+            let emb_bytes: Option<Vec<u8>> =
+                embedding.map(|e| e.iter().flat_map(|f| f.to_le_bytes()).collect::<Vec<u8>>());
+
+            // Check if key+category+session already exists — if so, UPDATE instead of INSERT
+            let existing_id: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM memories \
+                     WHERE key = ?1 AND category = ?2 AND session_id IS ?3",
+                    params![key, cat_str, session_id],
+                    |row| row.get(0),
+                )
+                .ok();
+
+            if let Some(eid) = existing_id {
+                conn.execute(
+                    "UPDATE memories SET content = ?1, updated_at = ?2, embedding = ?3 WHERE id = ?4",
+                    params![content, &now_str, emb_bytes, &eid],
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at, embedding) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![&id, key, content, &cat_str, session_id, &now_str, &now_str, emb_bytes],
+                )?;
+            }
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// Recall memories with optional embedding-based reranking.
@@ -148,50 +422,74 @@ impl SqliteMemory {
         limit: usize,
         query_embedding: Option<&[f32]>,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let query = query.to_string();
+        let query_embedding = query_embedding.map(<[f32]>::to_vec);
+        self.with_conn(move |conn| {
+            if query.trim().is_empty() {
+                return Ok(Vec::new());
+            }
 
-        if query.trim().is_empty() {
-            return Ok(Vec::new());
-        }
+            // Tokenize and create an OR query for FTS5
+            let fts_query: String = query
+                .split_whitespace()
+                .map(|w| {
+                    let escaped = w.replace('"', "");
+                    format!("\"{escaped}\"")
+                })
+                .collect::<Vec<_>>()
+                .join(" OR ");
 
-        // Tokenize and create an OR query for FTS5
-        let fts_query: String = query
-            .split_whitespace()
-            .map(|w| {
-                let escaped = w.replace('"', "");
-                format!("\"{escaped}\"")
-            })
-            .collect::<Vec<_>>()
-            .join(" OR ");
-
-        // Fetch candidates with BM25 scores (fetch more than limit for reranking)
-        let fetch_limit = if query_embedding.is_some() {
-            (limit * 5).max(50) // Over-fetch for reranking
-        } else {
-            limit
-        };
+            // Fetch candidates with BM25 scores (fetch more than limit for reranking)
+            let fetch_limit = if query_embedding.is_some() {
+                (limit * 5).max(50) // Over-fetch for reranking
+            } else {
+                limit
+            };
 
-        struct Candidate {
-            entry: MemoryEntry,
-            bm25_score: f64,
-            embedding_blob: Option<Vec<u8>>,
-        }
+            struct Candidate {
+                entry: MemoryEntry,
+                bm25_score: f64,
+                embedding_blob: Option<Vec<u8>>,
+            }
 
-        let mut candidates: Vec<Candidate> = Vec::new();
-
-        if let Some(ref cat) = category {
-            let cat_str = category_to_string(cat);
-            let mut stmt = conn.prepare(
-                "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at,
-                        bm25(memories_fts) AS bm25_score, m.embedding
-                 FROM memories_fts f
-                 JOIN memories m ON m.rowid = f.rowid
-                 WHERE memories_fts MATCH ?1 AND m.category = ?2
-                 ORDER BY bm25(memories_fts)
-                 LIMIT ?3",
-            )?;
-            let rows =
-                stmt.query_map(params![&fts_query, &cat_str, fetch_limit as i64], |row| {
+            let mut candidates: Vec<Candidate> = Vec::new();
+
+            if let Some(ref cat) = category {
+                let cat_str = category_to_string(cat);
+                let mut stmt = conn.prepare(
+                    "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at,
+                            bm25(memories_fts) AS bm25_score, m.embedding
+                     FROM memories_fts f
+                     JOIN memories m ON m.rowid = f.rowid
+                     WHERE memories_fts MATCH ?1 AND m.category = ?2
+                     ORDER BY bm25(memories_fts)
+                     LIMIT ?3",
+                )?;
+                let rows =
+                    stmt.query_map(params![&fts_query, &cat_str, fetch_limit as i64], |row| {
+                        let entry = row_to_entry(row)?;
+                        let bm25_score: f64 = row.get(7)?;
+                        let embedding_blob: Option<Vec<u8>> = row.get(8)?;
+                        Ok(Candidate {
+                            entry,
+                            bm25_score,
+                            embedding_blob,
+                        })
+                    })?;
+                for row in rows {
+                    candidates.push(row?);
+                }
+            } else {
+                let mut stmt = conn.prepare(
+                    "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at,
+                            bm25(memories_fts) AS bm25_score, m.embedding
+                     FROM memories_fts f
+                     JOIN memories m ON m.rowid = f.rowid
+                     WHERE memories_fts MATCH ?1
+                     ORDER BY bm25(memories_fts)
+                     LIMIT ?2",
+                )?;
+                let rows = stmt.query_map(params![&fts_query, fetch_limit as i64], |row| {
                     let entry = row_to_entry(row)?;
                     let bm25_score: f64 = row.get(7)?;
                     let embedding_blob: Option<Vec<u8>> = row.get(8)?;
@@ -201,119 +499,182 @@ impl SqliteMemory {
                         embedding_blob,
                     })
                 })?;
-            for row in rows {
-                candidates.push(row?);
+                for row in rows {
+                    candidates.push(row?);
+                }
             }
-        } else {
-            let mut stmt = conn.prepare(
-                "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at,
-                        bm25(memories_fts) AS bm25_score, m.embedding
-                 FROM memories_fts f
-                 JOIN memories m ON m.rowid = f.rowid
-                 WHERE memories_fts MATCH ?1
-                 ORDER BY bm25(memories_fts)
-                 LIMIT ?2",
-            )?;
-            let rows = stmt.query_map(params![&fts_query, fetch_limit as i64], |row| {
-                let entry = row_to_entry(row)?;
-                let bm25_score: f64 = row.get(7)?;
-                let embedding_blob: Option<Vec<u8>> = row.get(8)?;
-                Ok(Candidate {
-                    entry,
-                    bm25_score,
-                    embedding_blob,
-                })
-            })?;
-            for row in rows {
-                candidates.push(row?);
+
+            // If no query embedding, just return the BM25-ordered results
+            if query_embedding.is_none() || candidates.is_empty() {
+                return Ok(candidates
+                    .into_iter()
+                    .take(limit)
+                    .map(|c| c.entry)
+                    .collect());
             }
-        }
 
-        // If no query embedding, just return the BM25-ordered results
-        if query_embedding.is_none() || candidates.is_empty() {
-            return Ok(candidates
+            let q_emb = query_embedding.as_deref().unwrap();
+
+            // Normalize BM25 scores (BM25 in SQLite FTS5 returns negative values;
+            // more negative = better match). We normalize to [0, 1].
+            let min_bm25 = candidates
+                .iter()
+                .map(|c| c.bm25_score)
+                .fold(f64::INFINITY, f64::min);
+            let max_bm25 = candidates
+                .iter()
+                .map(|c| c.bm25_score)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let bm25_range = (max_bm25 - min_bm25).abs();
+
+            // Compute hybrid scores and sort
+            let mut scored: Vec<(MemoryEntry, f64)> = candidates
                 .into_iter()
-                .take(limit)
-                .map(|c| c.entry)
-                .collect());
-        }
-
-        let q_emb = query_embedding.unwrap();
-
-        // Normalize BM25 scores (BM25 in SQLite FTS5 returns negative values;
-        // more negative = better match). We normalize to [0, 1].
-        let min_bm25 = candidates
-            .iter()
-            .map(|c| c.bm25_score)
-            .fold(f64::INFINITY, f64::min);
-        let max_bm25 = candidates
-            .iter()
-            .map(|c| c.bm25_score)
-            .fold(f64::NEG_INFINITY, f64::max);
-        let bm25_range = (max_bm25 - min_bm25).abs();
-
-        // Compute hybrid scores and sort
-        let mut scored: Vec<(MemoryEntry, f64)> = candidates
-            .into_iter()
-            .map(|c| {
-                // Normalize BM25 to [0, 1] (more negative = better, so invert)
-                let norm_bm25 = if bm25_range > f64::EPSILON {
-                    (max_bm25 - c.bm25_score) / bm25_range
-                } else {
-                    1.0
-                };
-
-                // Compute cosine similarity if embedding is available
-                let cos_sim = if let Some(ref blob) = c.embedding_blob {
-                    let stored_emb: Vec<f32> = blob
-                        .chunks_exact(4)
-                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
-                        .collect();
-                    cosine_similarity(q_emb, &stored_emb) as f64
-                } else {
-                    0.0
-                };
-
-                // Hybrid score: 0.7 * cosine + 0.3 * normalized_bm25
-                let hybrid = 0.7 * cos_sim + 0.3 * norm_bm25;
-                (c.entry, hybrid)
-            })
-            .collect();
+                .map(|c| {
+                    // Normalize BM25 to [0, 1] (more negative = better, so invert)
+                    let norm_bm25 = if bm25_range > f64::EPSILON {
+                        (max_bm25 - c.bm25_score) / bm25_range
+                    } else {
+                        1.0
+                    };
+
+                    // Compute cosine similarity if embedding is available
+                    let cos_sim = if let Some(ref blob) = c.embedding_blob {
+                        let stored_emb: Vec<f32> = blob
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                            .collect();
+                        cosine_similarity(q_emb, &stored_emb) as f64
+                    } else {
+                        0.0
+                    };
+
+                    // Hybrid score: 0.7 * cosine + 0.3 * normalized_bm25
+                    let hybrid = 0.7 * cos_sim + 0.3 * norm_bm25;
+                    (c.entry, hybrid)
+                })
+                .collect();
 
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
-        Ok(scored.into_iter().take(limit).map(|(e, _)| e).collect())
+            Ok(scored.into_iter().take(limit).map(|(e, _)| e).collect())
+        })
+        .await
     }
 }
 
+/// Default on-disk location of the memory store (`~/.ygn/memory.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/memory.db")
+}
+
 // ---------------------------------------------------------------------------
 // Helper — category <-> string
 // ---------------------------------------------------------------------------
 
 fn category_to_string(cat: &MemoryCategory) -> String {
-    match cat {
-        MemoryCategory::Core => "core".to_string(),
-        MemoryCategory::Daily => "daily".to_string(),
-        MemoryCategory::Conversation => "conversation".to_string(),
-        MemoryCategory::Custom(s) => format!("custom:{s}"),
-    }
+    cat.to_string()
 }
 
 fn string_to_category(s: &str) -> MemoryCategory {
-    match s {
-        "core" => MemoryCategory::Core,
-        "daily" => MemoryCategory::Daily,
-        "conversation" => MemoryCategory::Conversation,
-        other => {
-            if let Some(rest) = other.strip_prefix("custom:") {
-                MemoryCategory::Custom(rest.to_string())
-            } else {
-                MemoryCategory::Custom(other.to_string())
-            }
-        }
+    let Ok(category) = s.parse();
+    category
+}
+
+// ---------------------------------------------------------------------------
+// Helpers — writes
+// ---------------------------------------------------------------------------
+
+/// Insert `content` under `key`, or update the entry already stored under
+/// `key` in `category` and the same session (or globally, for `None`).
+fn upsert(
+    conn: &Connection,
+    session_id: Option<&str>,
+    category: MemoryCategory,
+    key: &str,
+    content: &str,
+) -> anyhow::Result<MemoryEntry> {
+    let now = Utc::now();
+    let now_str = now.to_rfc3339();
+    let id = uuid::Uuid::new_v4().to_string();
+    let cat_str = category_to_string(&category);
+
+    // Check if key+category+session already exists — if so, UPDATE instead of INSERT
+    let existing_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM memories WHERE key = ?1 AND category = ?2 AND session_id IS ?3",
+            params![key, cat_str, session_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    if let Some(eid) = existing_id {
+        conn.execute(
+            "UPDATE memories SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, &now_str, &eid],
+        )?;
+        let entry = conn.query_row(
+            "SELECT id, key, content, category, session_id, created_at, updated_at \
+             FROM memories WHERE id = ?1",
+            params![&eid],
+            row_to_entry,
+        )?;
+        Ok(entry)
+    } else {
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![&id, key, content, &cat_str, session_id, &now_str, &now_str],
+        )?;
+        Ok(MemoryEntry {
+            id,
+            category,
+            key: key.to_string(),
+            content: content.to_string(),
+            created_at: now,
+            updated_at: now,
+            metadata: serde_json::json!({}),
+            session_id: session_id.map(String::from),
+        })
     }
 }
 
+/// Store an exported entry, keeping its id, timestamps and session, or
+/// update the entry already stored under its key like [`upsert`].
+fn import_entry(conn: &Connection, entry: &MemoryEntry) -> anyhow::Result<()> {
+    let cat_str = category_to_string(&entry.category);
+    let updated = conn.execute(
+        "UPDATE memories SET content = ?1, updated_at = ?2 \
+         WHERE key = ?3 AND category = ?4 AND session_id IS ?5",
+        params![
+            entry.content,
+            entry.updated_at.to_rfc3339(),
+            entry.key,
+            &cat_str,
+            entry.session_id
+        ],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.id,
+                entry.key,
+                entry.content,
+                &cat_str,
+                entry.session_id,
+                entry.created_at.to_rfc3339(),
+                entry.updated_at.to_rfc3339()
+            ],
+        )?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Helper — row → MemoryEntry
 // ---------------------------------------------------------------------------
@@ -323,7 +684,7 @@ fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<MemoryEntry> {
     let key: String = row.get(1)?;
     let content: String = row.get(2)?;
     let category_str: String = row.get(3)?;
-    let _session_id: Option<String> = row.get(4)?;
+    let session_id: Option<String> = row.get(4)?;
     let created_at_str: String = row.get(5)?;
     let updated_at_str: String = row.get(6)?;
 
@@ -342,9 +703,92 @@ fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<MemoryEntry> {
         created_at,
         updated_at,
         metadata: serde_json::json!({}),
+        session_id,
     })
 }
 
+// ---------------------------------------------------------------------------
+// Full-text recall
+// ---------------------------------------------------------------------------
+
+/// FTS5 query matching the words of `query` as `mode` says, or `None` for
+/// a blank query.
+///
+/// Every word is an FTS5 string, so operators and punctuation in user input
+/// are searched for rather than parsed.
+fn fts_query(query: &str, mode: MatchMode) -> Option<String> {
+    if query.trim().is_empty() {
+        return None;
+    }
+    let words = query.split_whitespace().map(fts_string);
+    Some(match mode {
+        MatchMode::AnyWord => words.collect::<Vec<_>>().join(" OR "),
+        MatchMode::AllWords => words.collect::<Vec<_>>().join(" AND "),
+        MatchMode::Phrase => fts_string(&query.split_whitespace().collect::<Vec<_>>().join(" ")),
+    })
+}
+
+/// `s` as a quoted FTS5 string, doubling any embedded quotes.
+fn fts_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// One page of entries matching `fts_query`, ranked by BM25.  rowid breaks
+/// ties so consecutive pages never overlap.
+/// A `session` of `None` matches entries of every session.
+fn recall_page(
+    conn: &Connection,
+    fts_query: &str,
+    category: Option<&MemoryCategory>,
+    session: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> anyhow::Result<Vec<MemoryEntry>> {
+    let cat_str = category.map(category_to_string);
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at
+         FROM memories_fts f
+         JOIN memories m ON m.rowid = f.rowid
+         WHERE memories_fts MATCH ?1 AND (?2 IS NULL OR m.category = ?2)
+           AND (?3 IS NULL OR m.session_id = ?3)
+         ORDER BY bm25(memories_fts), m.rowid
+         LIMIT ?4 OFFSET ?5",
+    )?;
+    let rows = stmt.query_map(
+        params![fts_query, cat_str, session, limit as i64, offset as i64],
+        row_to_entry,
+    )?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// One page of entries newest first by `order`, optionally in one category
+/// and one session.  rowid breaks timestamp ties so pages never overlap.
+fn list_page(
+    conn: &Connection,
+    category: Option<&MemoryCategory>,
+    session: Option<&str>,
+    offset: usize,
+    limit: usize,
+    order: ListOrder,
+) -> anyhow::Result<Vec<MemoryEntry>> {
+    let column = match order {
+        ListOrder::UpdatedAt => "updated_at",
+        ListOrder::CreatedAt => "created_at",
+    };
+    let sql = format!(
+        "SELECT id, key, content, category, session_id, created_at, updated_at \
+         FROM memories WHERE (?1 IS NULL OR category = ?1) AND (?2 IS NULL OR session_id = ?2) \
+         ORDER BY {column} DESC, rowid DESC LIMIT ?3 OFFSET ?4"
+    );
+    let cat_str = category.map(category_to_string);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        params![cat_str, session, limit as i64, offset as i64],
+        row_to_entry,
+    )?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
 // ---------------------------------------------------------------------------
 // Memory trait implementation
 // ---------------------------------------------------------------------------
@@ -357,49 +801,9 @@ impl Memory for SqliteMemory {
         key: &str,
         content: &str,
     ) -> anyhow::Result<MemoryEntry> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let now = Utc::now();
-        let now_str = now.to_rfc3339();
-        let id = uuid::Uuid::new_v4().to_string();
-        let cat_str = category_to_string(&category);
-
-        // Check if key+category already exists — if so, UPDATE instead of INSERT
-        let existing_id: Option<String> = conn
-            .query_row(
-                "SELECT id FROM memories WHERE key = ?1 AND category = ?2",
-                params![key, cat_str],
-                |row| row.get(0),
-            )
-            .ok();
-
-        if let Some(eid) = existing_id {
-            conn.execute(
-                "UPDATE memories SET content = ?1, updated_at = ?2 WHERE id = ?3",
-                params![content, &now_str, &eid],
-            )?;
-            let entry = conn.query_row(
-                "SELECT id, key, content, category, session_id, created_at, updated_at \
-                 FROM memories WHERE id = ?1",
-                params![&eid],
-                row_to_entry,
-            )?;
-            Ok(entry)
-        } else {
-            conn.execute(
-                "INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at) \
-                 VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6)",
-                params![&id, key, content, &cat_str, &now_str, &now_str],
-            )?;
-            Ok(MemoryEntry {
-                id,
-                category,
-                key: key.to_string(),
-                content: content.to_string(),
-                created_at: now,
-                updated_at: now,
-                metadata: serde_json::json!({}),
-            })
-        }
+        let (key, content) = (key.to_string(), content.to_string());
+        self.with_conn(move |conn| upsert(conn, None, category, &key, &content))
+            .await
     }
 
     async fn recall(
@@ -408,56 +812,8 @@ impl Memory for SqliteMemory {
         category: Option<MemoryCategory>,
         limit: usize,
     ) -> anyhow::Result<Vec<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-
-        // FTS5 search using BM25 ranking
-        let mut entries: Vec<MemoryEntry> = Vec::new();
-
-        if query.trim().is_empty() {
-            return Ok(entries);
-        }
-
-        // Tokenize and create an OR query for FTS5 to be forgiving
-        let fts_query: String = query
-            .split_whitespace()
-            .map(|w| {
-                // Escape quotes in individual words
-                let escaped = w.replace('"', "");
-                format!("\"{escaped}\"")
-            })
-            .collect::<Vec<_>>()
-            .join(" OR ");
-
-        if let Some(ref cat) = category {
-            let cat_str = category_to_string(cat);
-            let mut stmt = conn.prepare(
-                "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at
-                 FROM memories_fts f
-                 JOIN memories m ON m.rowid = f.rowid
-                 WHERE memories_fts MATCH ?1 AND m.category = ?2
-                 ORDER BY bm25(memories_fts)
-                 LIMIT ?3",
-            )?;
-            let rows = stmt.query_map(params![&fts_query, &cat_str, limit as i64], row_to_entry)?;
-            for row in rows {
-                entries.push(row?);
-            }
-        } else {
-            let mut stmt = conn.prepare(
-                "SELECT m.id, m.key, m.content, m.category, m.session_id, m.created_at, m.updated_at
-                 FROM memories_fts f
-                 JOIN memories m ON m.rowid = f.rowid
-                 WHERE memories_fts MATCH ?1
-                 ORDER BY bm25(memories_fts)
-                 LIMIT ?2",
-            )?;
-            let rows = stmt.query_map(params![&fts_query, limit as i64], row_to_entry)?;
-            for row in rows {
-                entries.push(row?);
-            }
-        }
-
-        Ok(entries)
+        self.recall_with_mode(query, MatchMode::AnyWord, category, limit)
+            .await
     }
 
     async fn get(
@@ -465,33 +821,65 @@ impl Memory for SqliteMemory {
         category: MemoryCategory,
         key: &str,
     ) -> anyhow::Result<Option<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let cat_str = category_to_string(&category);
-        let result = conn
-            .query_row(
-                "SELECT id, key, content, category, session_id, created_at, updated_at \
-                 FROM memories WHERE key = ?1 AND category = ?2",
-                params![key, &cat_str],
-                row_to_entry,
-            )
-            .ok();
-        Ok(result)
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            let cat_str = category_to_string(&category);
+            let result = conn
+                .query_row(
+                    "SELECT id, key, content, category, session_id, created_at, updated_at \
+                     FROM memories WHERE key = ?1 AND category = ?2 AND session_id IS NULL",
+                    params![key, &cat_str],
+                    row_to_entry,
+                )
+                .ok();
+            Ok(result)
+        })
+        .await
     }
 
     async fn forget(&self, category: MemoryCategory, key: &str) -> anyhow::Result<bool> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let cat_str = category_to_string(&category);
-        let affected = conn.execute(
-            "DELETE FROM memories WHERE key = ?1 AND category = ?2",
-            params![key, &cat_str],
-        )?;
-        Ok(affected > 0)
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            let cat_str = category_to_string(&category);
+            let affected = conn.execute(
+                "DELETE FROM memories WHERE key = ?1 AND category = ?2 AND session_id IS NULL",
+                params![key, &cat_str],
+            )?;
+            Ok(affected > 0)
+        })
+        .await
     }
 
     async fn health_check(&self) -> anyhow::Result<bool> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let result: i64 = conn.query_row("SELECT 1", [], |row| row.get(0))?;
-        Ok(result == 1)
+        self.with_conn(move |conn| {
+            let result: i64 = conn.query_row("SELECT 1", [], |row| row.get(0))?;
+            Ok(result == 1)
+        })
+        .await
+    }
+
+    async fn list(
+        &self,
+        category: Option<MemoryCategory>,
+        offset: usize,
+        limit: usize,
+        order: ListOrder,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.with_conn(move |conn| list_page(conn, category.as_ref(), None, offset, limit, order))
+            .await
+    }
+
+    async fn count(&self, category: Option<MemoryCategory>) -> anyhow::Result<usize> {
+        self.with_conn(move |conn| {
+            let cat_str = category.as_ref().map(category_to_string);
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM memories WHERE ?1 IS NULL OR category = ?1",
+                params![cat_str],
+                |row| row.get(0),
+            )?;
+            Ok(count as usize)
+        })
+        .await
     }
 }
 
@@ -522,6 +910,38 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn slow_query_does_not_block_runtime() {
+        // `#[tokio::test]` runs on one thread.  The query below is held
+        // until the health check has finished, so if it ran in place the
+        // health check could only run after the hold gave up.
+        let busy = Arc::new(SqliteMemory::in_memory().unwrap());
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let slow = tokio::spawn({
+            let busy = Arc::clone(&busy);
+            async move {
+                busy.with_conn(move |conn| {
+                    let _ = started_tx.send(());
+                    release_rx
+                        .recv_timeout(std::time::Duration::from_secs(30))
+                        .map_err(|_| anyhow::anyhow!("query held the runtime thread"))?;
+                    let rows: i64 =
+                        conn.query_row("SELECT count(*) FROM memories", [], |row| row.get(0))?;
+                    Ok(rows)
+                })
+                .await
+            }
+        });
+        started_rx.await.unwrap();
+
+        let other = SqliteMemory::in_memory().unwrap();
+        assert!(other.health_check().await.unwrap());
+        assert!(!slow.is_finished());
+        release_tx.send(()).unwrap();
+        assert_eq!(slow.await.unwrap().unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn store_and_recall() {
         let mem = SqliteMemory::in_memory().unwrap();
@@ -626,6 +1046,327 @@ mod tests {
         assert!(results.iter().any(|e| e.key == "rust"));
     }
 
+    #[tokio::test]
+    async fn store_batch_upserts_in_order() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        mem.store(MemoryCategory::Core, "lang", "C").await.unwrap();
+
+        let stored = mem
+            .store_batch(&[
+                (MemoryCategory::Core, "lang".into(), "Rust".into()),
+                (MemoryCategory::Daily, "todo".into(), "ship it".into()),
+                (MemoryCategory::Daily, "todo".into(), "ship it today".into()),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 3);
+        assert_eq!(stored[2].id, stored[1].id);
+        assert_eq!(mem.count(None).await.unwrap(), 2);
+        let lang = mem
+            .get(MemoryCategory::Core, "lang")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lang.content, "Rust");
+        let todo = mem
+            .get(MemoryCategory::Daily, "todo")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(todo.content, "ship it today");
+    }
+
+    #[tokio::test]
+    async fn export_then_import_restores_searchable_entries() {
+        let source = SqliteMemory::in_memory().unwrap();
+        let batch: Vec<_> = (0..1200)
+            .map(|i| {
+                (
+                    MemoryCategory::Conversation,
+                    format!("k{i}"),
+                    format!("note {i} about widget{}", i % 3),
+                )
+            })
+            .collect();
+        source.store_batch(&batch).await.unwrap();
+
+        let mut dump = Vec::new();
+        assert_eq!(source.export_jsonl(&mut dump).await.unwrap(), 1200);
+        assert_eq!(dump.iter().filter(|&&b| b == b'\n').count(), 1200);
+
+        let restored = SqliteMemory::in_memory().unwrap();
+        restored
+            .store(MemoryCategory::Conversation, "k7", "stale")
+            .await
+            .unwrap();
+        assert_eq!(restored.import_jsonl(dump.as_slice()).await.unwrap(), 1200);
+        assert_eq!(restored.count(None).await.unwrap(), 1200);
+        let hits = restored.recall("widget2", None, 1000).await.unwrap();
+        assert_eq!(hits.len(), 400);
+
+        let original = source
+            .get(MemoryCategory::Conversation, "k42")
+            .await
+            .unwrap()
+            .unwrap();
+        let copy = restored
+            .get(MemoryCategory::Conversation, "k42")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.id, original.id);
+        assert_eq!(copy.created_at, original.created_at);
+        let k7 = restored
+            .get(MemoryCategory::Conversation, "k7")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(k7.content, "note 7 about widget1");
+    }
+
+    #[tokio::test]
+    async fn import_rejects_malformed_lines() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        let err = mem
+            .import_jsonl("\nnot json\n".as_bytes())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+        assert_eq!(mem.count(None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn session_entries_do_not_clobber_global_ones() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        mem.store(MemoryCategory::Core, "goal", "global goal")
+            .await
+            .unwrap();
+        let scoped = mem
+            .store_in_session("s1", MemoryCategory::Core, "goal", "session goal")
+            .await
+            .unwrap();
+        assert_eq!(scoped.session_id.as_deref(), Some("s1"));
+        mem.store_in_session("s1", MemoryCategory::Core, "goal", "session goal v2")
+            .await
+            .unwrap();
+        mem.store_in_session("s2", MemoryCategory::Core, "goal", "other goal")
+            .await
+            .unwrap();
+        assert_eq!(mem.count(None).await.unwrap(), 3);
+
+        let global = mem
+            .get(MemoryCategory::Core, "goal")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(global.content, "global goal");
+        assert_eq!(global.session_id, None);
+
+        let in_s1 = mem.recall_in_session("s1", "goal", None, 10).await.unwrap();
+        assert_eq!(in_s1.len(), 1);
+        assert_eq!(in_s1[0].id, scoped.id);
+        assert_eq!(in_s1[0].content, "session goal v2");
+        assert_eq!(in_s1[0].session_id.as_deref(), Some("s1"));
+        let listed = mem
+            .list_in_session("s2", None, 0, 10, ListOrder::UpdatedAt)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].content, "other goal");
+        assert_eq!(mem.recall("goal", None, 10).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn forget_session_removes_only_that_session() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        mem.store(MemoryCategory::Core, "a", "global")
+            .await
+            .unwrap();
+        for key in ["a", "b"] {
+            mem.store_in_session("s1", MemoryCategory::Daily, key, "scratch")
+                .await
+                .unwrap();
+        }
+        mem.store_in_session("s2", MemoryCategory::Daily, "a", "keep")
+            .await
+            .unwrap();
+
+        assert_eq!(mem.forget_session("s1").await.unwrap(), 2);
+        assert_eq!(mem.forget_session("s1").await.unwrap(), 0);
+        assert_eq!(mem.count(None).await.unwrap(), 2);
+        assert!(mem.get(MemoryCategory::Core, "a").await.unwrap().is_some());
+        assert_eq!(
+            mem.recall_in_session("s2", "keep", None, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn recall_match_modes() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        for (key, content) in [
+            ("phrase", "the borrow checker rejects this"),
+            ("scattered", "a checker that will borrow nothing"),
+            ("one", "borrow a cup of sugar"),
+            ("other", "a spell checker"),
+        ] {
+            mem.store(MemoryCategory::Core, key, content).await.unwrap();
+        }
+
+        let keys = |mode| {
+            let mem = &mem;
+            async move {
+                let mut keys: Vec<String> = mem
+                    .recall_with_mode("borrow checker", mode, None, 10)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|e| e.key)
+                    .collect();
+                keys.sort();
+                keys
+            }
+        };
+        assert_eq!(
+            keys(MatchMode::AnyWord).await,
+            ["one", "other", "phrase", "scattered"]
+        );
+        assert_eq!(keys(MatchMode::AllWords).await, ["phrase", "scattered"]);
+        assert_eq!(keys(MatchMode::Phrase).await, ["phrase"]);
+    }
+
+    #[tokio::test]
+    async fn recall_escapes_fts_syntax() {
+        let mem = SqliteMemory::in_memory().unwrap();
+        mem.store(MemoryCategory::Core, "q", "he said \"NOT\" twice (loudly)")
+            .await
+            .unwrap();
+
+        for mode in [MatchMode::AnyWord, MatchMode::AllWords, MatchMode::Phrase] {
+            for query in ["said \"NOT", "NOT AND (loudly", "said*", "^twice", "\"\""] {
+                mem.recall_with_mode(query, mode, None, 10)
+                    .await
+                    .unwrap_or_else(|e| panic!("{query:?} in {mode:?}: {e}"));
+            }
+        }
+        let hits = mem
+            .recall_with_mode("said \"NOT\"", MatchMode::Phrase, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn recall_stream_pages_in_ranked_order() {
+        use futures_util::TryStreamExt;
+
+        let mem = SqliteMemory::in_memory().unwrap();
+        // More mentions of "alpha" rank higher, so the order is not rowid order.
+        for i in 0..25 {
+            let content = format!("{} entry {i}", "alpha ".repeat(1 + i % 4));
+            mem.store(MemoryCategory::Daily, &format!("d{i:02}"), &content)
+                .await
+                .unwrap();
+        }
+        mem.store(MemoryCategory::Core, "c", "alpha core")
+            .await
+            .unwrap();
+
+        let batches: Vec<Vec<MemoryEntry>> = mem
+            .recall_stream("alpha", Some(MemoryCategory::Daily), 10)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            [10, 10, 5]
+        );
+        let streamed: Vec<String> = batches.into_iter().flatten().map(|e| e.key).collect();
+        let recalled: Vec<String> = mem
+            .recall("alpha", Some(MemoryCategory::Daily), 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(streamed, recalled);
+
+        let all: Vec<_> = mem
+            .recall_stream("alpha", None, 7)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all.into_iter().flatten().count(), 26);
+        let none: Vec<_> = mem
+            .recall_stream("  ", None, 7)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    }
+
+    /// 25 daily entries `d00`..`d24` stored in order, plus 5 core ones.
+    async fn listing_fixture() -> SqliteMemory {
+        let mem = SqliteMemory::in_memory().unwrap();
+        for i in 0..25 {
+            mem.store(MemoryCategory::Daily, &format!("d{i:02}"), "entry")
+                .await
+                .unwrap();
+        }
+        for i in 0..5 {
+            mem.store(MemoryCategory::Core, &format!("c{i}"), "entry")
+                .await
+                .unwrap();
+        }
+        mem
+    }
+
+    #[tokio::test]
+    async fn list_paginates_by_category() {
+        let mem = listing_fixture().await;
+        let daily = Some(MemoryCategory::Daily);
+        assert_eq!(mem.count(daily.clone()).await.unwrap(), 25);
+        assert_eq!(mem.count(None).await.unwrap(), 30);
+
+        let mut keys = Vec::new();
+        for offset in [0, 10, 20] {
+            let page = mem
+                .list(daily.clone(), offset, 10, ListOrder::CreatedAt)
+                .await
+                .unwrap();
+            assert_eq!(page.len(), if offset == 20 { 5 } else { 10 });
+            assert!(page.iter().all(|e| e.category == MemoryCategory::Daily));
+            keys.extend(page.into_iter().map(|e| e.key));
+        }
+        let expected: Vec<String> = (0..25).rev().map(|i| format!("d{i:02}")).collect();
+        assert_eq!(keys, expected);
+
+        let past_end = mem.list(daily, 25, 10, ListOrder::CreatedAt).await.unwrap();
+        assert!(past_end.is_empty());
+    }
+
+    #[tokio::test]
+    async fn list_order_is_stable_and_follows_updates() {
+        let mem = listing_fixture().await;
+        let first = mem.list(None, 0, 30, ListOrder::UpdatedAt).await.unwrap();
+        let again = mem.list(None, 0, 30, ListOrder::UpdatedAt).await.unwrap();
+        let keys = |entries: &[MemoryEntry]| -> Vec<String> {
+            entries.iter().map(|e| e.key.clone()).collect()
+        };
+        assert_eq!(keys(&first), keys(&again));
+
+        mem.store(MemoryCategory::Daily, "d00", "edited")
+            .await
+            .unwrap();
+        let by_update = mem.list(None, 0, 1, ListOrder::UpdatedAt).await.unwrap();
+        assert_eq!(by_update[0].key, "d00");
+        let by_creation = mem.list(None, 0, 1, ListOrder::CreatedAt).await.unwrap();
+        assert_eq!(by_creation[0].key, "c4");
+    }
+
     #[tokio::test]
     async fn health_check_succeeds() {
         let mem = SqliteMemory::in_memory().unwrap();
@@ -4,13 +4,59 @@
 //! persists node information across restarts using SQLite with WAL mode,
 //! following the same pattern as [`crate::sqlite_memory::SqliteMemory`].
 
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+
+use crate::registry::{
+    CapabilityRequirement, DiscoveryFilter, Endpoint, NodeInfo, NodePatch, NodeRegistry, NodeRole,
+    SortBy, TrustTier,
+};
+use crate::schema::{self, Migration};
+
+/// Schema history of the registry.
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create nodes",
+        "CREATE TABLE IF NOT EXISTS nodes (
+            node_id      TEXT PRIMARY KEY,
+            role         TEXT NOT NULL,
+            trust_tier   TEXT NOT NULL,
+            endpoints    TEXT NOT NULL,
+            capabilities TEXT NOT NULL,
+            last_seen    TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_nodes_role ON nodes(role);
+        CREATE INDEX IF NOT EXISTS idx_nodes_last_seen ON nodes(last_seen);",
+    ),
+    Migration::hook(2, "add and backfill nodes.metadata", backfill_metadata),
+];
+
+/// Version of the registry schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+/// Give every node a metadata object.  Databases created before migrations
+/// existed already have the column, older ones do not.
+fn backfill_metadata(tx: &Transaction<'_>) -> rusqlite::Result<()> {
+    schema::add_column_if_missing(tx, "nodes", "metadata", "TEXT NOT NULL DEFAULT '{}'")?;
+    tx.execute(
+        "UPDATE nodes SET metadata = '{}'
+         WHERE json_valid(metadata) = 0 OR json_type(metadata) != 'object'",
+        [],
+    )?;
+    Ok(())
+}
 
-use crate::registry::{DiscoveryFilter, Endpoint, NodeInfo, NodeRegistry, NodeRole, TrustTier};
+/// Default on-disk location of the registry (`~/.ygn/registry.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/registry.db")
+}
 
 // ---------------------------------------------------------------------------
 // SqliteRegistry
@@ -18,7 +64,7 @@ use crate::registry::{DiscoveryFilter, Endpoint, NodeInfo, NodeRegistry, NodeRol
 
 /// Persistent registry backed by SQLite.
 pub struct SqliteRegistry {
-    conn: Mutex<Connection>,
+    conn: Arc<Mutex<Connection>>,
 }
 
 impl std::fmt::Debug for SqliteRegistry {
@@ -30,85 +76,80 @@ impl std::fmt::Debug for SqliteRegistry {
 impl SqliteRegistry {
     /// Create a new registry. Pass `":memory:"` for testing.
     pub fn new(path: &str) -> anyhow::Result<Self> {
-        let conn = Connection::open(path)?;
+        let mut conn = Connection::open(path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS nodes (
-                node_id      TEXT PRIMARY KEY,
-                role         TEXT NOT NULL,
-                trust_tier   TEXT NOT NULL,
-                endpoints    TEXT NOT NULL,
-                capabilities TEXT NOT NULL,
-                last_seen    TEXT NOT NULL,
-                metadata     TEXT NOT NULL DEFAULT '{}'
-            );
-            CREATE INDEX IF NOT EXISTS idx_nodes_role ON nodes(role);
-            CREATE INDEX IF NOT EXISTS idx_nodes_last_seen ON nodes(last_seen);",
-        )?;
+        // Other handles on the same file may hold the write lock briefly
+        // (see `update`); wait for it rather than failing with SQLITE_BUSY.
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
         Ok(Self {
-            conn: Mutex::new(conn),
+            conn: Arc::new(Mutex::new(conn)),
         })
     }
 
-    /// Remove nodes whose last_seen is older than max_staleness_seconds.
-    /// Returns the number of evicted nodes.
-    pub async fn evict_stale(&self, max_staleness_seconds: u64) -> anyhow::Result<usize> {
-        let cutoff = Utc::now() - chrono::Duration::seconds(max_staleness_seconds as i64);
-        let cutoff_str = cutoff.to_rfc3339();
-        let conn = self.conn.lock().unwrap();
-        let count = conn.execute(
-            "DELETE FROM nodes WHERE last_seen < ?1",
-            rusqlite::params![cutoff_str],
-        )?;
-        Ok(count)
+    /// Run blocking database work on Tokio's blocking pool, so a slow query
+    /// never stalls an async worker thread.
+    async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> anyhow::Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+            f(&mut conn)
+        })
+        .await?
     }
 
     /// Merge remote nodes into this registry.
     /// Accepts nodes only if they are newer (by last_seen) than existing entries.
     /// Returns (accepted_count, rejected_count).
     pub async fn merge_nodes(&self, nodes: &[NodeInfo]) -> anyhow::Result<(usize, usize)> {
-        let mut accepted = 0;
-        let mut rejected = 0;
-        let conn = self.conn.lock().unwrap();
-
-        for node in nodes {
-            // Check if node already exists
-            let existing_last_seen: Option<String> = conn
-                .query_row(
-                    "SELECT last_seen FROM nodes WHERE node_id = ?1",
-                    rusqlite::params![node.node_id],
-                    |row| row.get(0),
-                )
-                .ok();
-
-            let should_accept = match existing_last_seen {
-                None => true, // New node, accept
-                Some(existing) => {
-                    // Accept if incoming is newer
-                    node.last_seen.to_rfc3339() > existing
+        let nodes = nodes.to_vec();
+        self.with_conn(move |conn| {
+            let mut accepted = 0;
+            let mut rejected = 0;
+            for node in nodes {
+                // Check if node already exists
+                let existing_last_seen: Option<String> = conn
+                    .query_row(
+                        "SELECT last_seen FROM nodes WHERE node_id = ?1",
+                        rusqlite::params![node.node_id],
+                        |row| row.get(0),
+                    )
+                    .ok();
+
+                let should_accept = match existing_last_seen {
+                    None => true, // New node, accept
+                    Some(existing) => {
+                        // Accept if incoming is newer
+                        node.last_seen.to_rfc3339() > existing
+                    }
+                };
+
+                if should_accept {
+                    // INSERT OR REPLACE (same as register)
+                    let endpoints_json = serde_json::to_string(&node.endpoints)?;
+                    let capabilities_json = serde_json::to_string(&node.capabilities)?;
+                    let last_seen_str = node.last_seen.to_rfc3339();
+                    let metadata_str = node.metadata.to_string();
+                    let role_str = role_to_str(&node.role);
+                    let trust_str = trust_to_str(&node.trust_tier);
+
+                    conn.execute(
+                        "INSERT OR REPLACE INTO nodes (node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        rusqlite::params![node.node_id, role_str, trust_str, endpoints_json, capabilities_json, last_seen_str, metadata_str],
+                    )?;
+                    accepted += 1;
+                } else {
+                    rejected += 1;
                 }
-            };
-
-            if should_accept {
-                // INSERT OR REPLACE (same as register)
-                let endpoints_json = serde_json::to_string(&node.endpoints)?;
-                let capabilities_json = serde_json::to_string(&node.capabilities)?;
-                let last_seen_str = node.last_seen.to_rfc3339();
-                let metadata_str = node.metadata.to_string();
-                let role_str = role_to_str(&node.role);
-                let trust_str = trust_to_str(&node.trust_tier);
-
-                conn.execute(
-                    "INSERT OR REPLACE INTO nodes (node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                    rusqlite::params![node.node_id, role_str, trust_str, endpoints_json, capabilities_json, last_seen_str, metadata_str],
-                )?;
-                accepted += 1;
-            } else {
-                rejected += 1;
             }
-        }
 
-        Ok((accepted, rejected))
+            Ok((accepted, rejected))
+        })
+        .await
     }
 }
 
@@ -194,112 +235,203 @@ fn row_to_node(row: &rusqlite::Row<'_>) -> rusqlite::Result<NodeInfo> {
 #[async_trait]
 impl NodeRegistry for SqliteRegistry {
     async fn register(&self, node: NodeInfo) -> anyhow::Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let role = role_to_str(&node.role);
-        let trust = trust_to_str(&node.trust_tier);
-        let endpoints = serde_json::to_string(&node.endpoints)?;
-        let capabilities = serde_json::to_string(&node.capabilities)?;
-        let last_seen = node.last_seen.to_rfc3339();
-        let metadata = serde_json::to_string(&node.metadata)?;
-
-        conn.execute(
-            "INSERT OR REPLACE INTO nodes (node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![
-                &node.node_id,
-                role,
-                trust,
-                &endpoints,
-                &capabilities,
-                &last_seen,
-                &metadata,
-            ],
-        )?;
-        Ok(())
+        self.with_conn(move |conn| {
+            let role = role_to_str(&node.role);
+            let trust = trust_to_str(&node.trust_tier);
+            let endpoints = serde_json::to_string(&node.endpoints)?;
+            let capabilities = serde_json::to_string(&node.capabilities)?;
+            let last_seen = node.last_seen.to_rfc3339();
+            let metadata = serde_json::to_string(&node.metadata)?;
+
+            conn.execute(
+                "INSERT OR REPLACE INTO nodes (node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    &node.node_id,
+                    role,
+                    trust,
+                    &endpoints,
+                    &capabilities,
+                    &last_seen,
+                    &metadata,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
     }
 
     async fn deregister(&self, node_id: &str) -> anyhow::Result<bool> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let affected = conn.execute("DELETE FROM nodes WHERE node_id = ?1", params![node_id])?;
-        Ok(affected > 0)
+        let node_id = node_id.to_string();
+        self.with_conn(move |conn| {
+            let affected =
+                conn.execute("DELETE FROM nodes WHERE node_id = ?1", params![node_id])?;
+            Ok(affected > 0)
+        })
+        .await
     }
 
     async fn discover(&self, filter: DiscoveryFilter) -> anyhow::Result<Vec<NodeInfo>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        self.with_conn(move |conn| {
+            // Build dynamic WHERE clause
+            let mut clauses: Vec<String> = Vec::new();
+            let mut param_values: Vec<String> = Vec::new();
+
+            if let Some(ref role) = filter.role {
+                clauses.push(format!("role = ?{}", param_values.len() + 1));
+                param_values.push(role_to_str(role).to_string());
+            }
+            if let Some(ref tier) = filter.trust_tier {
+                clauses.push(format!("trust_tier = ?{}", param_values.len() + 1));
+                param_values.push(trust_to_str(tier).to_string());
+            }
+            let requirement = filter
+                .capability
+                .as_deref()
+                .map(CapabilityRequirement::parse);
+            if let Some(ref req) = requirement {
+                // Coarse JSON array prefilter via LIKE — e.g. capabilities LIKE '%"echo%'
+                // also admits "echo@1.2.0"; versions are checked below in Rust.
+                clauses.push(format!("capabilities LIKE ?{}", param_values.len() + 1));
+                param_values.push(format!("%\"{}%", req.name));
+            }
+            if let Some(max_secs) = filter.max_staleness_seconds {
+                let cutoff = Utc::now() - chrono::Duration::seconds(max_secs as i64);
+                clauses.push(format!("last_seen >= ?{}", param_values.len() + 1));
+                param_values.push(cutoff.to_rfc3339());
+            }
 
-        // Build dynamic WHERE clause
-        let mut clauses: Vec<String> = Vec::new();
-        let mut param_values: Vec<String> = Vec::new();
+            let where_clause = if clauses.is_empty() {
+                String::new()
+            } else {
+                format!(" WHERE {}", clauses.join(" AND "))
+            };
 
-        if let Some(ref role) = filter.role {
-            clauses.push(format!("role = ?{}", param_values.len() + 1));
-            param_values.push(role_to_str(role).to_string());
-        }
-        if let Some(ref tier) = filter.trust_tier {
-            clauses.push(format!("trust_tier = ?{}", param_values.len() + 1));
-            param_values.push(trust_to_str(tier).to_string());
-        }
-        if let Some(ref cap) = filter.capability {
-            // JSON array contains check via LIKE — e.g. capabilities LIKE '%"echo"%'
-            clauses.push(format!("capabilities LIKE ?{}", param_values.len() + 1));
-            param_values.push(format!("%\"{cap}\"%"));
-        }
-        if let Some(max_secs) = filter.max_staleness_seconds {
-            let cutoff = Utc::now() - chrono::Duration::seconds(max_secs as i64);
-            clauses.push(format!("last_seen >= ?{}", param_values.len() + 1));
-            param_values.push(cutoff.to_rfc3339());
-        }
+            let order_clause = match filter.effective_sort() {
+                Some(SortBy::LastSeenDesc) => " ORDER BY last_seen DESC, node_id",
+                Some(SortBy::NodeId) => " ORDER BY node_id",
+                None => "",
+            };
 
-        let where_clause = if clauses.is_empty() {
-            String::new()
-        } else {
-            format!(" WHERE {}", clauses.join(" AND "))
-        };
+            // Pagination must follow the capability post-filter, so it moves to
+            // Rust when one is present.
+            let mut page_clause = String::new();
+            if requirement.is_none() && (filter.limit.is_some() || filter.offset.is_some()) {
+                // SQLite needs a LIMIT for OFFSET; -1 means unbounded.
+                let limit = filter.limit.map_or(-1, |l| l as i64);
+                page_clause = format!(" LIMIT {limit} OFFSET {}", filter.offset.unwrap_or(0));
+            }
 
-        let sql = format!(
-            "SELECT node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata FROM nodes{where_clause}"
-        );
+            let sql = format!(
+                "SELECT node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata FROM nodes{where_clause}{order_clause}{page_clause}"
+            );
 
-        let mut stmt = conn.prepare(&sql)?;
+            let mut stmt = conn.prepare(&sql)?;
 
-        // Build params dynamically — rusqlite needs &dyn ToSql references
-        let param_refs: Vec<&dyn rusqlite::types::ToSql> = param_values
-            .iter()
-            .map(|v| v as &dyn rusqlite::types::ToSql)
-            .collect();
+            // Build params dynamically — rusqlite needs &dyn ToSql references
+            let param_refs: Vec<&dyn rusqlite::types::ToSql> = param_values
+                .iter()
+                .map(|v| v as &dyn rusqlite::types::ToSql)
+                .collect();
 
-        let rows = stmt.query_map(param_refs.as_slice(), row_to_node)?;
+            let rows = stmt.query_map(param_refs.as_slice(), row_to_node)?;
 
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row?);
-        }
-        Ok(results)
+            let mut results = Vec::new();
+            for row in rows {
+                let node = row?;
+                if let Some(ref req) = requirement {
+                    if !req.is_satisfied_by_any(&node.capabilities) {
+                        continue;
+                    }
+                }
+                results.push(node);
+            }
+            if requirement.is_some() {
+                results = filter.paginate(results);
+            }
+            Ok(results)
+        })
+        .await
     }
 
     async fn heartbeat(&self, node_id: &str) -> anyhow::Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let now = Utc::now().to_rfc3339();
-        let affected = conn.execute(
-            "UPDATE nodes SET last_seen = ?1 WHERE node_id = ?2",
-            params![&now, node_id],
-        )?;
-        if affected == 0 {
-            return Err(anyhow::anyhow!("Node not found: {node_id}"));
-        }
-        Ok(())
+        let node_id = node_id.to_string();
+        self.with_conn(move |conn| {
+            let now = Utc::now().to_rfc3339();
+            let affected = conn.execute(
+                "UPDATE nodes SET last_seen = ?1 WHERE node_id = ?2",
+                params![&now, node_id],
+            )?;
+            if affected == 0 {
+                return Err(anyhow::anyhow!("Node not found: {node_id}"));
+            }
+            Ok(())
+        })
+        .await
     }
 
     async fn get(&self, node_id: &str) -> anyhow::Result<Option<NodeInfo>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
-        let result = conn
-            .query_row(
-                "SELECT node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata FROM nodes WHERE node_id = ?1",
-                params![node_id],
-                row_to_node,
-            )
-            .ok();
-        Ok(result)
+        let node_id = node_id.to_string();
+        self.with_conn(move |conn| {
+            let result = conn
+                .query_row(
+                    "SELECT node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata FROM nodes WHERE node_id = ?1",
+                    params![node_id],
+                    row_to_node,
+                )
+                .ok();
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Read, merge and write back inside one `BEGIN IMMEDIATE` transaction,
+    /// so concurrent updates — from this handle or another connection to the
+    /// same database file — cannot overwrite each other's changes.
+    async fn update(&self, node_id: &str, patch: NodePatch) -> anyhow::Result<NodeInfo> {
+        let node_id = node_id.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let mut node = tx
+                .query_row(
+                    "SELECT node_id, role, trust_tier, endpoints, capabilities, last_seen, metadata FROM nodes WHERE node_id = ?1",
+                    params![node_id],
+                    row_to_node,
+                )
+                .map_err(|e| match e {
+                    rusqlite::Error::QueryReturnedNoRows => {
+                        anyhow::anyhow!("Node not found: {node_id}")
+                    }
+                    other => other.into(),
+                })?;
+
+            patch.apply(&mut node);
+            tx.execute(
+                "UPDATE nodes SET capabilities = ?1, last_seen = ?2, metadata = ?3 WHERE node_id = ?4",
+                params![
+                    serde_json::to_string(&node.capabilities)?,
+                    node.last_seen.to_rfc3339(),
+                    serde_json::to_string(&node.metadata)?,
+                    node_id,
+                ],
+            )?;
+            tx.commit()?;
+            Ok(node)
+        })
+        .await
+    }
+
+    async fn evict_stale(&self, max_staleness_seconds: u64) -> anyhow::Result<usize> {
+        let cutoff = Utc::now() - chrono::Duration::seconds(max_staleness_seconds as i64);
+        let cutoff_str = cutoff.to_rfc3339();
+        self.with_conn(move |conn| {
+            let count = conn.execute(
+                "DELETE FROM nodes WHERE last_seen < ?1",
+                rusqlite::params![cutoff_str],
+            )?;
+            Ok(count)
+        })
+        .await
     }
 }
 
@@ -310,6 +442,7 @@ impl NodeRegistry for SqliteRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::HeartbeatUpdate;
 
     fn sample_node(id: &str) -> NodeInfo {
         NodeInfo {
@@ -357,6 +490,7 @@ mod tests {
             trust_tier: None,
             capability: None,
             max_staleness_seconds: None,
+            ..Default::default()
         };
         let results = reg.discover(filter).await.unwrap();
         assert_eq!(results.len(), 1);
@@ -407,6 +541,44 @@ mod tests {
         assert_eq!(results[0].node_id, "multi-1");
     }
 
+    #[tokio::test]
+    async fn discover_by_capability_version() {
+        let reg = SqliteRegistry::new(":memory:").unwrap();
+        for (id, cap) in [
+            ("a-old", "echo@1.0.0"),
+            ("b-new", "echo@1.2.0"),
+            ("c-newer", "echo@1.5.0"),
+            ("d-other", "echoes@9.0.0"),
+        ] {
+            let mut node = sample_node(id);
+            node.capabilities = vec![cap.into()];
+            reg.register(node).await.unwrap();
+        }
+
+        let discover = |capability: &str, limit: Option<usize>| {
+            reg.discover(DiscoveryFilter {
+                capability: Some(capability.to_string()),
+                limit,
+                ..Default::default()
+            })
+        };
+        let ids = |nodes: Vec<NodeInfo>| nodes.into_iter().map(|n| n.node_id).collect::<Vec<_>>();
+
+        // Satisfied constraint; pagination applies after the version check.
+        assert_eq!(
+            ids(discover("echo>=1.1", None).await.unwrap()),
+            vec!["b-new", "c-newer"]
+        );
+        assert_eq!(
+            ids(discover("echo>=1.1", Some(1)).await.unwrap()),
+            vec!["b-new"]
+        );
+        // Unsatisfied constraint.
+        assert!(discover("echo>=2.0", None).await.unwrap().is_empty());
+        // Bare name matches every version but not other tools sharing a prefix.
+        assert_eq!(discover("echo", None).await.unwrap().len(), 3);
+    }
+
     #[tokio::test]
     async fn discover_empty_filter_returns_all() {
         let reg = SqliteRegistry::new(":memory:").unwrap();
@@ -418,6 +590,48 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[tokio::test]
+    async fn discover_limit_offset_and_sort() {
+        let reg = SqliteRegistry::new(":memory:").unwrap();
+        for (i, id) in ["node-c", "node-a", "node-d", "node-b"].iter().enumerate() {
+            let mut node = sample_node(id);
+            node.last_seen = Utc::now() - chrono::Duration::seconds(i as i64 * 10);
+            reg.register(node).await.unwrap();
+        }
+        let ids = |nodes: Vec<NodeInfo>| -> Vec<String> {
+            nodes.into_iter().map(|n| n.node_id).collect()
+        };
+
+        let page = reg
+            .discover(DiscoveryFilter {
+                limit: Some(2),
+                offset: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(page), vec!["node-b", "node-c"]);
+
+        let newest = reg
+            .discover(DiscoveryFilter {
+                sort_by: Some(SortBy::LastSeenDesc),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(newest), vec!["node-c", "node-a"]);
+
+        let rest = reg
+            .discover(DiscoveryFilter {
+                offset: Some(3),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(rest), vec!["node-d"]);
+    }
+
     #[tokio::test]
     async fn register_upsert_overwrites() {
         let reg = SqliteRegistry::new(":memory:").unwrap();
@@ -433,6 +647,104 @@ mod tests {
         assert_eq!(found.capabilities, vec!["new".to_string()]);
     }
 
+    #[tokio::test]
+    async fn update_keeps_earlier_capabilities() {
+        let reg = SqliteRegistry::new(":memory:").unwrap();
+        let mut node = sample_node("node-1");
+        node.metadata = serde_json::json!({ "gpu": { "count": 1 } });
+        reg.register(node).await.unwrap();
+
+        reg.update(
+            "node-1",
+            NodePatch {
+                capabilities: vec!["shell".into()],
+                metadata: serde_json::json!({ "gpu": { "model": "a100" } }),
+            },
+        )
+        .await
+        .unwrap();
+
+        let found = reg.get("node-1").await.unwrap().unwrap();
+        assert_eq!(found.capabilities, vec!["echo", "shell"]);
+        assert_eq!(
+            found.metadata,
+            serde_json::json!({ "gpu": { "count": 1, "model": "a100" } })
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_updates_are_not_lost() {
+        let path = std::env::temp_dir().join(format!("ygn-registry-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+        // Two handles on one file, as with two processes sharing a registry.
+        let a = std::sync::Arc::new(SqliteRegistry::new(&path).unwrap());
+        let b = std::sync::Arc::new(SqliteRegistry::new(&path).unwrap());
+        a.register(sample_node("node-1")).await.unwrap();
+
+        let tasks: Vec<_> = (0..50)
+            .map(|i| {
+                let reg = if i % 2 == 0 { a.clone() } else { b.clone() };
+                tokio::spawn(async move {
+                    reg.update(
+                        "node-1",
+                        NodePatch {
+                            capabilities: vec![format!("cap-{i}")],
+                            metadata: serde_json::json!({ format!("k{i}"): i }),
+                        },
+                    )
+                    .await
+                    .unwrap();
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let found = b.get("node-1").await.unwrap().unwrap();
+        assert_eq!(found.capabilities.len(), 51, "{:?}", found.capabilities);
+        assert_eq!(found.metadata.as_object().unwrap().len(), 50);
+
+        drop((a, b));
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path}{suffix}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn update_missing_node_errors() {
+        let reg = SqliteRegistry::new(":memory:").unwrap();
+        let err = reg.update("ghost", NodePatch::default()).await.unwrap_err();
+        assert!(err.to_string().contains("Node not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn heartbeat_with_adds_capabilities_and_refreshes_last_seen() {
+        let reg = SqliteRegistry::new(":memory:").unwrap();
+        let mut node = sample_node("node-1");
+        node.last_seen = Utc::now() - chrono::Duration::seconds(120);
+        let before = node.last_seen;
+        reg.register(node).await.unwrap();
+
+        let update = HeartbeatUpdate {
+            add_capabilities: vec!["gpio".into()],
+            metadata_patch: serde_json::json!({ "load": 0.3 }),
+        };
+        let updated = reg.heartbeat_with("node-1", update).await.unwrap();
+        assert_eq!(updated.capabilities, vec!["echo", "gpio"]);
+
+        let stored = reg.get("node-1").await.unwrap().unwrap();
+        assert_eq!(stored.capabilities, vec!["echo", "gpio"]);
+        assert_eq!(stored.metadata, serde_json::json!({ "load": 0.3 }));
+        assert!(stored.last_seen > before);
+
+        let err = reg
+            .heartbeat_with("ghost", HeartbeatUpdate::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Node not found"), "{err}");
+    }
+
     #[tokio::test]
     async fn evict_stale_nodes() {
         let reg = SqliteRegistry::new(":memory:").unwrap();
@@ -466,6 +778,7 @@ mod tests {
             trust_tier: None,
             capability: None,
             max_staleness_seconds: None,
+            ..Default::default()
         };
         let all = reg.discover(filter).await.unwrap();
         assert_eq!(all.len(), 2);
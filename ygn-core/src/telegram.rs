@@ -232,6 +232,7 @@ mod tests {
             content: "Hello Telegram!".to_string(),
             recipient: None,
             metadata: serde_json::json!({"chat_id": 42}),
+            ..Default::default()
         };
         channel.send(msg).await.unwrap();
 
@@ -3,8 +3,18 @@
 //! Defines the interface for executable tools and a registry to hold them,
 //! based on ZeroClaw's Tool trait architecture.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::metrics::Metrics;
+use crate::tool_history::{ExecutionOrigin, ToolExecution, ToolExecutionLog};
+use crate::tool_limits::{ToolConcurrency, ToolLimiter};
+use crate::tool_output::{ToolOutputLimits, Truncation};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -14,8 +24,273 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
     pub success: bool,
-    pub output: String,
+    /// What the tool produced.  Serialized as `output`, where text stays a
+    /// plain string so existing readers and external tools are unaffected.
+    #[serde(rename = "output")]
+    pub content: ToolContent,
     pub error: Option<String>,
+    /// Set when the content was cut to fit the tool's output limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<Truncation>,
+}
+
+impl ToolResult {
+    /// The content rendered as a string: text as is, JSON serialized, and
+    /// binary as a short description.
+    pub fn output(&self) -> std::borrow::Cow<'_, str> {
+        self.content.render()
+    }
+}
+
+/// The content of a [`ToolResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ContentRepr", into = "ContentRepr")]
+pub enum ToolContent {
+    Text(String),
+    /// Structured output, e.g. a sensor reading.
+    Json(serde_json::Value),
+    /// Raw bytes such as an image, with their MIME type.
+    Binary {
+        data: Vec<u8>,
+        mime_type: String,
+    },
+}
+
+impl ToolContent {
+    /// The content as a string; see [`ToolResult::output`].
+    pub fn render(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            Self::Text(text) => text.as_str().into(),
+            Self::Json(value) => value.to_string().into(),
+            Self::Binary { data, mime_type } => {
+                format!("[{mime_type}, {} bytes]", data.len()).into()
+            }
+        }
+    }
+
+    /// Structured content, serialized.
+    pub fn json(value: &impl Serialize) -> serde_json::Result<Self> {
+        serde_json::to_value(value).map(Self::Json)
+    }
+}
+
+impl Default for ToolContent {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl From<String> for ToolContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for ToolContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+/// Wire form of [`ToolContent`]: text as a bare string, other kinds tagged,
+/// with binary data in base64.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ContentRepr {
+    Text(String),
+    Tagged(TaggedContent),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TaggedContent {
+    Json {
+        value: serde_json::Value,
+    },
+    Binary {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+}
+
+impl From<ToolContent> for ContentRepr {
+    fn from(content: ToolContent) -> Self {
+        use base64::Engine;
+        match content {
+            ToolContent::Text(text) => Self::Text(text),
+            ToolContent::Json(value) => Self::Tagged(TaggedContent::Json { value }),
+            ToolContent::Binary { data, mime_type } => Self::Tagged(TaggedContent::Binary {
+                data: base64::engine::general_purpose::STANDARD.encode(data),
+                mime_type,
+            }),
+        }
+    }
+}
+
+impl TryFrom<ContentRepr> for ToolContent {
+    type Error = base64::DecodeError;
+
+    fn try_from(repr: ContentRepr) -> Result<Self, Self::Error> {
+        use base64::Engine;
+        Ok(match repr {
+            ContentRepr::Text(text) => Self::Text(text),
+            ContentRepr::Tagged(TaggedContent::Json { value }) => Self::Json(value),
+            ContentRepr::Tagged(TaggedContent::Binary { data, mime_type }) => Self::Binary {
+                data: base64::engine::general_purpose::STANDARD.decode(data)?,
+                mime_type,
+            },
+        })
+    }
+}
+
+/// One way the arguments of a call break the tool's parameter schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArgumentViolation {
+    /// JSON pointer to the offending value (empty for the root).
+    pub instance_path: String,
+    /// Expected JSON type, or the schema keyword that failed.
+    pub expected: String,
+    pub message: String,
+}
+
+/// Arguments rejected by [`ToolRegistry::execute`] before the tool ran.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("invalid arguments for tool '{tool}': {}", summary(.violations))]
+pub struct InvalidArguments {
+    pub tool: String,
+    pub violations: Vec<ArgumentViolation>,
+}
+
+fn summary(violations: &[ArgumentViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| {
+            let at = if v.instance_path.is_empty() {
+                "/"
+            } else {
+                &v.instance_path
+            };
+            format!("{at}: {}", v.message)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// A call stopped by its [`CancellationToken`] before the tool finished.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("tool call '{tool}' was cancelled")]
+pub struct ToolCancelled {
+    pub tool: String,
+}
+
+/// A progress update from a long-running tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Work done so far; increases with every update.
+    pub progress: f64,
+    /// Total amount of work, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Severity of a log message a tool sends its caller, lowest first; the
+/// syslog levels MCP uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// Receives `(level, logger, message)` for every log message.
+type LogSink = Arc<dyn Fn(LogLevel, &str, String) + Send + Sync>;
+
+/// Handle a tool reports [`Progress`] through while it runs.
+///
+/// Cheap to clone; every clone reports to the same sink.  The
+/// [`noop`](Self::noop) reporter discards updates, so tools can report
+/// unconditionally.  A reporter can also carry log messages to the caller;
+/// see [`with_logger`](Self::with_logger).
+#[derive(Clone)]
+pub struct ProgressReporter {
+    sink: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+    logger: Option<LogSink>,
+}
+
+impl ProgressReporter {
+    /// Deliver every update to `sink`.
+    pub fn new(sink: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Some(Arc::new(sink)),
+            logger: None,
+        }
+    }
+
+    /// A reporter that discards updates.
+    pub fn noop() -> Self {
+        Self {
+            sink: None,
+            logger: None,
+        }
+    }
+
+    /// Deliver every [`log`](Self::log) message to `logger`, whether or not
+    /// progress updates go anywhere.
+    pub fn with_logger(
+        mut self,
+        logger: impl Fn(LogLevel, &str, String) + Send + Sync + 'static,
+    ) -> Self {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
+    /// Send `message` from `logger` to the caller at `level`; discarded
+    /// without a logger.
+    pub fn log(&self, level: LogLevel, logger: &str, message: impl Into<String>) {
+        if let Some(sink) = &self.logger {
+            sink(level, logger, message.into());
+        }
+    }
+
+    /// Whether updates go anywhere; lets tools skip building messages.
+    pub fn is_active(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Report `progress` out of `total`, with an optional message.
+    pub fn report(&self, progress: f64, total: Option<f64>, message: Option<String>) {
+        if let Some(sink) = &self.sink {
+            sink(Progress {
+                progress,
+                total,
+                message,
+            });
+        }
+    }
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::noop()
+    }
+}
+
+impl std::fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("active", &self.is_active())
+            .field("logging", &self.logger.is_some())
+            .finish()
+    }
 }
 
 /// Metadata describing a tool for discovery by providers.
@@ -45,6 +320,49 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with the given JSON arguments.
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult>;
 
+    /// Execute the tool, reporting progress through `progress` as it runs.
+    ///
+    /// The default ignores `progress` and calls [`execute`](Self::execute);
+    /// long-running tools override it.
+    async fn execute_with_progress(
+        &self,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<ToolResult> {
+        let _ = progress;
+        self.execute(args).await
+    }
+
+    /// Execute the tool until it finishes or `cancel` fires, whichever
+    /// comes first.
+    ///
+    /// The default drops the [`execute_with_progress`](Self::execute_with_progress)
+    /// future on cancellation and fails with [`ToolCancelled`]; tools that
+    /// hold resources a drop cannot release override it to clean up.
+    async fn execute_cancellable(
+        &self,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+        cancel: &CancellationToken,
+    ) -> anyhow::Result<ToolResult> {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(ToolCancelled {
+                tool: self.name().to_string(),
+            }
+            .into()),
+            result = self.execute_with_progress(args, progress) => result,
+        }
+    }
+
+    /// Whether [`ToolRegistry::execute`] checks arguments against
+    /// [`parameters_schema`](Self::parameters_schema) before calling
+    /// [`execute`](Self::execute).  Tools whose schema is intentionally
+    /// loose, or checked elsewhere, can opt out.
+    fn validates_arguments(&self) -> bool {
+        true
+    }
+
     /// Build a [`ToolSpec`] from this tool's metadata.
     fn spec(&self) -> ToolSpec {
         ToolSpec {
@@ -96,8 +414,81 @@ impl Tool for EchoTool {
 
         Ok(ToolResult {
             success: true,
-            output: input,
+            content: ToolContent::Text(input),
+            error: None,
+            truncated: None,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CountdownTool (demo of progress reporting)
+// ---------------------------------------------------------------------------
+
+/// A deliberately slow tool that waits `steps` times `interval_ms`,
+/// reporting progress after each step.  Demonstrates progress
+/// notifications for long-running calls.
+#[derive(Debug, Clone, Default)]
+pub struct CountdownTool;
+
+#[async_trait]
+impl Tool for CountdownTool {
+    fn name(&self) -> &str {
+        "countdown"
+    }
+
+    fn description(&self) -> &str {
+        "Waits for a number of steps, reporting progress after each one"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "description": "Number of steps (default 3)"
+                },
+                "interval_ms": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 10000,
+                    "description": "Milliseconds per step (default 200)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        self.execute_with_progress(args, &ProgressReporter::noop())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<ToolResult> {
+        let steps = args.get("steps").and_then(|v| v.as_u64()).unwrap_or(3);
+        let interval = args
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(200);
+        for step in 1..=steps {
+            tokio::time::sleep(std::time::Duration::from_millis(interval)).await;
+            progress.report(
+                step as f64,
+                Some(steps as f64),
+                Some(format!("step {step} of {steps}")),
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            content: ToolContent::Text(format!("countdown finished after {steps} steps")),
             error: None,
+            truncated: None,
         })
     }
 }
@@ -106,17 +497,63 @@ impl Tool for EchoTool {
 // ToolRegistry
 // ---------------------------------------------------------------------------
 
+/// Separates a namespace from a tool name, as in `fs/read_file`.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Why [`ToolRegistry::resolve`] found no single tool for a name.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ToolLookupError {
+    #[error("Tool not found: {0}")]
+    NotFound(String),
+    #[error("ambiguous tool name '{name}': matches {}", .candidates.join(", "))]
+    Ambiguous {
+        name: String,
+        candidates: Vec<String>,
+    },
+}
+
+/// A tool and the fully qualified name it is registered under.
+struct Registered {
+    name: String,
+    tool: Box<dyn Tool>,
+}
+
+/// The part of a qualified name after its last namespace separator.
+fn short_name(name: &str) -> &str {
+    name.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or(name)
+}
+
 /// Holds a collection of tools and provides lookup by name.
+///
+/// Tools may be registered under a namespace (`node-abc/read_file`) so that
+/// same-named tools from different sources can coexist. Lookups accept the
+/// fully qualified name, an [alias](Self::alias), or a short name that only
+/// one tool has.
 #[derive(Default)]
 pub struct ToolRegistry {
-    tools: Vec<Box<dyn Tool>>,
+    tools: Vec<Registered>,
+    /// Alias to fully qualified name.
+    aliases: HashMap<String, String>,
+    /// Records executions made through [`ToolRegistry::execute`].
+    metrics: Option<Arc<Metrics>>,
+    /// Persists executions made through [`ToolRegistry::execute`], tagged
+    /// with the origin of this registry.
+    history: Option<(Arc<ToolExecutionLog>, ExecutionOrigin)>,
+    /// Caps concurrent executions made through [`ToolRegistry::execute`].
+    limiter: Option<Arc<ToolLimiter>>,
+    /// Bounds the output of executions made through
+    /// [`ToolRegistry::execute`].
+    output_limits: Option<Arc<ToolOutputLimits>>,
+    /// Correlation id the executions recorded in `history` are tagged with.
+    request_id: Option<String>,
 }
 
 impl std::fmt::Debug for ToolRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let names: Vec<&str> = self.tools.iter().map(|t| t.name()).collect();
+        let names: Vec<&str> = self.tools.iter().map(|t| t.name.as_str()).collect();
         f.debug_struct("ToolRegistry")
             .field("tools", &names)
+            .field("aliases", &self.aliases)
             .finish()
     }
 }
@@ -127,19 +564,204 @@ impl ToolRegistry {
         Self::default()
     }
 
-    /// Register a tool.
+    /// Register a tool under its own name.
     pub fn register(&mut self, tool: Box<dyn Tool>) {
-        self.tools.push(tool);
+        let name = tool.name().to_string();
+        self.tools.push(Registered { name, tool });
+    }
+
+    /// Register a tool as `<namespace>/<name>`.
+    pub fn register_in(&mut self, namespace: &str, tool: Box<dyn Tool>) {
+        let name = format!("{namespace}{NAMESPACE_SEPARATOR}{}", tool.name());
+        self.tools.push(Registered { name, tool });
+    }
+
+    /// Let `from` stand for the tool `to` resolves to now.  Fails if `to`
+    /// does not resolve, or if `from` is already a registered tool name.
+    pub fn alias(&mut self, from: &str, to: &str) -> anyhow::Result<()> {
+        if self.is_registered(from) {
+            anyhow::bail!("cannot alias '{from}': a tool is registered under that name");
+        }
+        let target = self.resolve(to)?.to_string();
+        self.aliases.insert(from.to_string(), target);
+        Ok(())
+    }
+
+    /// The fully qualified name `name` refers to: an exact registered
+    /// name, then an alias, then a short name unique in the registry.
+    pub fn resolve(&self, name: &str) -> Result<&str, ToolLookupError> {
+        if let Some(t) = self.tools.iter().find(|t| t.name == name) {
+            return Ok(&t.name);
+        }
+        if let Some(target) = self.aliases.get(name) {
+            if let Some(t) = self.tools.iter().find(|t| &t.name == target) {
+                return Ok(&t.name);
+            }
+        }
+        let mut candidates: Vec<&str> = self
+            .tools
+            .iter()
+            .filter(|t| short_name(&t.name) == name)
+            .map(|t| t.name.as_str())
+            .collect();
+        match candidates.len() {
+            0 => Err(ToolLookupError::NotFound(name.to_string())),
+            1 => Ok(candidates[0]),
+            _ => {
+                candidates.sort_unstable();
+                Err(ToolLookupError::Ambiguous {
+                    name: name.to_string(),
+                    candidates: candidates.into_iter().map(str::to_string).collect(),
+                })
+            }
+        }
+    }
+
+    /// Remove the tool `name` resolves to, returning it if there was one.
+    /// Aliases pointing at it are dropped.
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn Tool>> {
+        let qualified = self.resolve(name).ok()?.to_string();
+        let index = self.tools.iter().position(|t| t.name == qualified)?;
+        self.aliases.retain(|_, target| *target != qualified);
+        Some(self.tools.remove(index).tool)
+    }
+
+    /// Whether `name` resolves to a registered tool.
+    pub fn contains(&self, name: &str) -> bool {
+        self.resolve(name).is_ok()
     }
 
-    /// Get a tool by name.
+    /// Whether a tool is registered under exactly this qualified name.
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t.name == name)
+    }
+
+    /// Get the tool `name` resolves to.
     pub fn get(&self, name: &str) -> Option<&dyn Tool> {
-        self.tools.iter().find(|t| t.name() == name).map(|t| &**t)
+        let qualified = self.resolve(name).ok()?;
+        self.tools
+            .iter()
+            .find(|t| t.name == qualified)
+            .map(|t| &*t.tool)
+    }
+
+    /// Record executions made through [`execute`](Self::execute) in `metrics`.
+    pub fn set_metrics(&mut self, metrics: Arc<Metrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Store executions made through [`execute`](Self::execute) in `log`,
+    /// attributed to `origin`.
+    pub fn set_history(&mut self, log: Arc<ToolExecutionLog>, origin: ExecutionOrigin) {
+        self.history = Some((log, origin));
+    }
+
+    /// Tag executions recorded in the history with `request_id`, the
+    /// correlation id of the request making them.
+    pub fn set_request_id(&mut self, request_id: Option<String>) {
+        self.request_id = request_id;
+    }
+
+    /// Limit concurrent executions made through [`execute`](Self::execute)
+    /// with `limiter`.
+    pub fn set_limiter(&mut self, limiter: Arc<ToolLimiter>) {
+        self.limiter = Some(limiter);
+    }
+
+    /// Fit the output of executions made through [`execute`](Self::execute)
+    /// to `limits`, before it is recorded in the history.
+    pub fn set_output_limits(&mut self, limits: Arc<ToolOutputLimits>) {
+        self.output_limits = Some(limits);
+    }
+
+    /// Current load of the tool `name` resolves to, when the attached
+    /// limiter caps it.
+    pub fn concurrency(&self, name: &str) -> Option<ToolConcurrency> {
+        let name = self.resolve(name).ok()?;
+        self.limiter.as_ref()?.concurrency(name)
+    }
+
+    /// Execute the named tool, recording the outcome in the attached
+    /// metrics and history under its qualified name.  Errors with
+    /// [`ToolLookupError`] if `name` does not resolve to one tool, with
+    /// [`InvalidArguments`] if `args` do not match the tool's schema, with
+    /// [`ToolBusy`](crate::tool_limits::ToolBusy) if the tool stayed at its
+    /// concurrency limit for the whole queue timeout, or with
+    /// [`OutputTooLarge`](crate::tool_output::OutputTooLarge) if it returned
+    /// binary output over its output limit.
+    pub async fn execute(&self, name: &str, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        self.execute_with_progress(name, args, &ProgressReporter::noop())
+            .await
     }
 
-    /// List all registered tool specs.
+    /// Like [`execute`](Self::execute), passing `progress` to the tool's
+    /// [`Tool::execute_with_progress`].
+    pub async fn execute_with_progress(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+    ) -> anyhow::Result<ToolResult> {
+        self.execute_cancellable(name, args, progress, &CancellationToken::new())
+            .await
+    }
+
+    /// Like [`execute_with_progress`](Self::execute_with_progress), but the
+    /// call fails with [`ToolCancelled`] as soon as `cancel` fires.
+    pub async fn execute_cancellable(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+        progress: &ProgressReporter,
+        cancel: &CancellationToken,
+    ) -> anyhow::Result<ToolResult> {
+        let name = self.resolve(name)?;
+        let tool = self.get(name).expect("resolved tool is registered");
+        if tool.validates_arguments() {
+            validate_arguments(tool, &args)?;
+        }
+        let _permit = match &self.limiter {
+            Some(limiter) => limiter.acquire(name).await?,
+            None => None,
+        };
+        let _in_flight = self.metrics.as_ref().map(|m| m.tool_started());
+        let started = Instant::now();
+        let arguments = self.history.as_ref().map(|_| args.clone());
+        let mut result = tool.execute_cancellable(args, progress, cancel).await;
+        let elapsed = started.elapsed();
+        if let Some(limits) = &self.output_limits {
+            result = result.and_then(|r| Ok(limits.apply(name, r)?));
+        }
+        if let Some(metrics) = &self.metrics {
+            let success = result.as_ref().is_ok_and(|r| r.success);
+            metrics.record_tool_execution(name, success, elapsed);
+        }
+        if let (Some((log, origin)), Some(arguments)) = (&self.history, arguments) {
+            let mut execution = ToolExecution::new(name, arguments, &result, elapsed, *origin);
+            if let Some(request_id) = &self.request_id {
+                execution = execution.with_request_id(request_id);
+            }
+            if let Err(e) = log.record(&execution) {
+                tracing::warn!(
+                    error = %e,
+                    tool = name,
+                    request_id = self.request_id.as_deref(),
+                    "failed to record tool execution"
+                );
+            }
+        }
+        result
+    }
+
+    /// List all registered tool specs, named by their qualified names.
     pub fn list(&self) -> Vec<ToolSpec> {
-        self.tools.iter().map(|t| t.spec()).collect()
+        self.tools
+            .iter()
+            .map(|t| ToolSpec {
+                name: t.name.clone(),
+                ..t.tool.spec()
+            })
+            .collect()
     }
 
     /// Number of registered tools.
@@ -153,6 +775,58 @@ impl ToolRegistry {
     }
 }
 
+/// Check `args` against the tool's parameter schema.  A schema that does
+/// not compile is not enforced.
+fn validate_arguments(tool: &dyn Tool, args: &serde_json::Value) -> Result<(), InvalidArguments> {
+    use jsonschema::error::{TypeKind, ValidationErrorKind};
+
+    let schema = tool.parameters_schema();
+    let validator = match jsonschema::validator_for(&schema) {
+        Ok(validator) => validator,
+        Err(e) => {
+            tracing::debug!(tool = tool.name(), error = %e, "tool schema not enforced");
+            return Ok(());
+        }
+    };
+    let violations: Vec<ArgumentViolation> = validator
+        .iter_errors(args)
+        .map(|error| {
+            let expected = match &error.kind {
+                ValidationErrorKind::Type {
+                    kind: TypeKind::Single(t),
+                } => t.to_string(),
+                ValidationErrorKind::Type {
+                    kind: TypeKind::Multiple(types),
+                } => types
+                    .into_iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                _ => error
+                    .schema_path
+                    .to_string()
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+            ArgumentViolation {
+                instance_path: error.instance_path.to_string(),
+                expected,
+                message: error.to_string(),
+            }
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidArguments {
+            tool: tool.name().to_string(),
+            violations,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -169,7 +843,7 @@ mod tests {
             .await
             .unwrap();
         assert!(result.success);
-        assert_eq!(result.output, "hello world");
+        assert_eq!(result.output(), "hello world");
         assert!(result.error.is_none());
     }
 
@@ -178,7 +852,7 @@ mod tests {
         let tool = EchoTool;
         let result = tool.execute(serde_json::json!({})).await.unwrap();
         assert!(result.success);
-        assert_eq!(result.output, "");
+        assert_eq!(result.output(), "");
     }
 
     #[test]
@@ -189,6 +863,51 @@ mod tests {
         assert!(!spec.description.is_empty());
     }
 
+    #[tokio::test]
+    async fn countdown_reports_every_step() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let progress = ProgressReporter::new(move |p| sink.lock().unwrap().push(p));
+        let result = CountdownTool
+            .execute_with_progress(serde_json::json!({"steps": 2, "interval_ms": 1}), &progress)
+            .await
+            .unwrap();
+        assert_eq!(result.output(), "countdown finished after 2 steps");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[1].progress, 2.0);
+        assert_eq!(seen[1].total, Some(2.0));
+        assert_eq!(seen[1].message.as_deref(), Some("step 2 of 2"));
+    }
+
+    #[tokio::test]
+    async fn cancelled_execution_stops_early() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(CountdownTool));
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+        let started = std::time::Instant::now();
+        let err = registry
+            .execute_cancellable(
+                "countdown",
+                serde_json::json!({"steps": 100, "interval_ms": 50}),
+                &ProgressReporter::noop(),
+                &cancel,
+            )
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+        assert_eq!(
+            err.downcast_ref::<ToolCancelled>().unwrap().tool,
+            "countdown"
+        );
+    }
+
     #[test]
     fn registry_register_and_get() {
         let mut registry = ToolRegistry::new();
@@ -218,16 +937,344 @@ mod tests {
         assert!(registry.get("nonexistent").is_none());
     }
 
+    #[test]
+    fn registry_unregister_removes_tool() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+
+        assert!(registry.unregister("nonexistent").is_none());
+        let removed = registry.unregister("echo").unwrap();
+        assert_eq!(removed.name(), "echo");
+        assert!(registry.is_empty());
+    }
+
+    /// Echoes under a configurable name, to simulate same-named tools from
+    /// different sources.
+    struct NamedEcho(&'static str);
+
+    #[async_trait]
+    impl Tool for NamedEcho {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "Named echo"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            Ok(ToolResult {
+                success: true,
+                content: self.0.into(),
+                error: None,
+                truncated: None,
+            })
+        }
+    }
+
+    fn colliding_registry() -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        registry.register_in("fs", Box::new(NamedEcho("read_file")));
+        registry.register_in("node-abc", Box::new(NamedEcho("read_file")));
+        registry.register_in("fs", Box::new(NamedEcho("write_file")));
+        registry
+    }
+
+    #[tokio::test]
+    async fn namespaced_tools_coexist_under_qualified_names() {
+        let registry = colliding_registry();
+        let names: Vec<String> = registry.list().into_iter().map(|s| s.name).collect();
+        assert_eq!(
+            names,
+            ["fs/read_file", "node-abc/read_file", "fs/write_file"]
+        );
+        assert_eq!(registry.list()[0].description, "Named echo");
+
+        assert_eq!(
+            registry.resolve("node-abc/read_file").unwrap(),
+            "node-abc/read_file"
+        );
+        // A short name held by one tool is enough.
+        assert_eq!(registry.resolve("write_file").unwrap(), "fs/write_file");
+        let result = registry
+            .execute("write_file", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result.output(), "write_file");
+        assert!(registry.is_registered("fs/write_file"));
+        assert!(!registry.is_registered("write_file"));
+    }
+
+    #[tokio::test]
+    async fn ambiguous_short_name_lists_candidates() {
+        let registry = colliding_registry();
+        let err = registry.resolve("read_file").unwrap_err();
+        assert_eq!(
+            err,
+            ToolLookupError::Ambiguous {
+                name: "read_file".to_string(),
+                candidates: vec!["fs/read_file".into(), "node-abc/read_file".into()],
+            }
+        );
+        assert!(registry.get("read_file").is_none());
+
+        let err = registry
+            .execute("read_file", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ambiguous tool name 'read_file': matches fs/read_file, node-abc/read_file"
+        );
+        assert_eq!(
+            registry.resolve("missing").unwrap_err().to_string(),
+            "Tool not found: missing"
+        );
+    }
+
+    #[tokio::test]
+    async fn aliases_resolve_to_their_target() {
+        let mut registry = colliding_registry();
+        registry.alias("remote_read", "node-abc/read_file").unwrap();
+        assert_eq!(
+            registry.resolve("remote_read").unwrap(),
+            "node-abc/read_file"
+        );
+        let result = registry
+            .execute("remote_read", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result.output(), "read_file");
+
+        assert!(registry.alias("fs/write_file", "fs/read_file").is_err());
+        assert!(registry.alias("x", "read_file").is_err());
+
+        registry.unregister("remote_read").unwrap();
+        assert!(!registry.contains("remote_read"));
+        assert_eq!(registry.resolve("read_file").unwrap(), "fs/read_file");
+    }
+
     #[test]
     fn tool_result_serialization() {
         let result = ToolResult {
             success: true,
-            output: "ok".to_string(),
+            content: "ok".into(),
             error: None,
+            truncated: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         let round: ToolResult = serde_json::from_str(&json).unwrap();
         assert!(round.success);
-        assert_eq!(round.output, "ok");
+        assert_eq!(round.output(), "ok");
+        // Text stays a plain `output` string on the wire.
+        assert!(json.contains(r#""output":"ok""#), "{json}");
+    }
+
+    #[test]
+    fn structured_and_binary_content_round_trip() {
+        for content in [
+            ToolContent::Json(serde_json::json!({ "temperature": 21.5 })),
+            ToolContent::Binary {
+                data: vec![0, 159, 255],
+                mime_type: "application/octet-stream".into(),
+            },
+        ] {
+            let result = ToolResult {
+                success: true,
+                content: content.clone(),
+                error: None,
+                truncated: None,
+            };
+            let json = serde_json::to_value(&result).unwrap();
+            let round: ToolResult = serde_json::from_value(json).unwrap();
+            assert_eq!(round.content, content);
+        }
+        let binary: ToolResult = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "output": { "type": "binary", "data": "AJ//", "mimeType": "image/png" },
+            "error": null
+        }))
+        .unwrap();
+        assert_eq!(binary.output(), "[image/png, 3 bytes]");
+    }
+
+    /// Counts calls; its schema demands an integer `n`.
+    struct CountingTool {
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        validate: bool,
+    }
+
+    #[async_trait]
+    impl Tool for CountingTool {
+        fn name(&self) -> &str {
+            "count"
+        }
+        fn description(&self) -> &str {
+            "Counts calls"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": { "n": { "type": "integer" } },
+                "required": ["n"]
+            })
+        }
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ToolResult {
+                success: true,
+                content: ToolContent::default(),
+                error: None,
+                truncated: None,
+            })
+        }
+        fn validates_arguments(&self) -> bool {
+            self.validate
+        }
+    }
+
+    #[tokio::test]
+    async fn hardware_call_with_string_speed_is_rejected() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(crate::hardware::HardwareTool::with_seed(7)));
+
+        let bad = serde_json::json!({
+            "action": { "type": "drive", "direction": "forward", "speed": "fast" }
+        });
+        let err = registry.execute("hardware", bad).await.unwrap_err();
+        let invalid = err.downcast_ref::<InvalidArguments>().unwrap();
+        assert_eq!(invalid.tool, "hardware");
+        assert_eq!(invalid.violations.len(), 1);
+        assert_eq!(invalid.violations[0].instance_path, "/action/speed");
+        assert_eq!(invalid.violations[0].expected, "number");
+
+        let good = serde_json::json!({
+            "action": { "type": "drive", "direction": "forward", "speed": 0.5 }
+        });
+        assert!(registry.execute("hardware", good).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments_never_reach_the_tool() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(CountingTool {
+            calls: calls.clone(),
+            validate: true,
+        }));
+        let err = registry
+            .execute("count", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        let invalid = err.downcast_ref::<InvalidArguments>().unwrap();
+        assert_eq!(invalid.violations[0].instance_path, "");
+        assert_eq!(invalid.violations[0].expected, "required");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        registry
+            .execute("count", serde_json::json!({ "n": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn tools_can_opt_out_of_validation() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(CountingTool {
+            calls: calls.clone(),
+            validate: false,
+        }));
+        registry
+            .execute("count", serde_json::json!({ "n": "not a number" }))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    /// Sleeps for `delay`, tracking the most calls it saw running at once.
+    struct SlowTool {
+        delay: std::time::Duration,
+        running: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "slow"
+        }
+        fn description(&self) -> &str {
+            "Sleeps"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            use std::sync::atomic::Ordering::SeqCst;
+            let now = self.running.fetch_add(1, SeqCst) + 1;
+            self.peak.fetch_max(now, SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.running.fetch_sub(1, SeqCst);
+            Ok(ToolResult {
+                success: true,
+                content: ToolContent::default(),
+                error: None,
+                truncated: None,
+            })
+        }
+    }
+
+    fn limited_slow_registry(
+        delay_ms: u64,
+        queue_timeout_ms: u64,
+    ) -> (ToolRegistry, Arc<std::sync::atomic::AtomicUsize>) {
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(SlowTool {
+            delay: std::time::Duration::from_millis(delay_ms),
+            running: Arc::default(),
+            peak: peak.clone(),
+        }));
+        registry.set_limiter(Arc::new(ToolLimiter::new(
+            crate::tool_limits::ToolLimitsConfig {
+                limits: [("slow".to_string(), 1)].into(),
+                queue_timeout_ms,
+                max_queued: None,
+            },
+        )));
+        (registry, peak)
+    }
+
+    #[tokio::test]
+    async fn limit_of_one_serializes_concurrent_calls() {
+        let (registry, peak) = limited_slow_registry(50, 5_000);
+        let started = Instant::now();
+        let calls = (0..3).map(|_| registry.execute("slow", serde_json::json!({})));
+        for result in futures_util::future::join_all(calls).await {
+            assert!(result.unwrap().success);
+        }
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(started.elapsed() >= std::time::Duration::from_millis(150));
+        assert_eq!(registry.concurrency("slow").unwrap().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn queued_call_fails_busy_after_timeout() {
+        let (registry, _) = limited_slow_registry(500, 20);
+        let (first, second) = tokio::join!(
+            registry.execute("slow", serde_json::json!({})),
+            registry.execute("slow", serde_json::json!({})),
+        );
+        assert!(first.unwrap().success);
+        let err = second.unwrap_err();
+        let busy = err.downcast_ref::<crate::tool_limits::ToolBusy>().unwrap();
+        assert_eq!(busy.tool, "slow");
+        assert_eq!(busy.limit, 1);
     }
 }
@@ -0,0 +1,450 @@
+//! Tool execution history and replay.
+//!
+//! A [`ToolExecutionLog`] stores every tool invocation made through a
+//! [`ToolRegistry`] that has the log attached, along with where the call came
+//! from.  Stored calls can be replayed against the current registry with
+//! [`ToolExecutionLog::replay`]; the policy engine is consulted again before
+//! anything runs.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::policy::{PolicyAction, PolicyEngine};
+use crate::schema::{self, Migration};
+use crate::tool::{ToolRegistry, ToolResult};
+
+/// Schema history of the `tool_executions` table.
+const MIGRATIONS: &[Migration] = &[
+    Migration::sql(
+        1,
+        "create tool_executions",
+        "CREATE TABLE IF NOT EXISTS tool_executions (
+        id          TEXT PRIMARY KEY,
+        tool_name   TEXT NOT NULL,
+        arguments   TEXT NOT NULL,
+        result      TEXT NOT NULL,
+        success     INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        timestamp   TEXT NOT NULL,
+        origin      TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_tool_executions_tool
+        ON tool_executions(tool_name, timestamp);",
+    ),
+    Migration::sql(
+        2,
+        "add tool_executions.request_id",
+        "ALTER TABLE tool_executions ADD COLUMN request_id TEXT;",
+    ),
+];
+
+/// Version of the `tool_executions` schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Which entry point invoked a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionOrigin {
+    Mcp,
+    Skill,
+    Cli,
+    /// A skill run by the [`Scheduler`](crate::scheduler::Scheduler).
+    Schedule,
+}
+
+impl ExecutionOrigin {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionOrigin::Mcp => "mcp",
+            ExecutionOrigin::Skill => "skill",
+            ExecutionOrigin::Cli => "cli",
+            ExecutionOrigin::Schedule => "schedule",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "mcp" => ExecutionOrigin::Mcp,
+            "skill" => ExecutionOrigin::Skill,
+            "schedule" => ExecutionOrigin::Schedule,
+            _ => ExecutionOrigin::Cli,
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutionOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One recorded tool invocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolExecution {
+    pub id: String,
+    pub tool_name: String,
+    pub arguments: Value,
+    /// The [`ToolResult`] as JSON; execution errors are stored in the same
+    /// shape with `success: false`.
+    pub result: Value,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub timestamp: DateTime<Utc>,
+    pub origin: ExecutionOrigin,
+    /// Correlation id of the request that made the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ToolExecution {
+    /// Build a record for a finished call.
+    pub fn new(
+        tool_name: &str,
+        arguments: Value,
+        result: &anyhow::Result<ToolResult>,
+        elapsed: Duration,
+        origin: ExecutionOrigin,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            result: result_json(result),
+            success: result.as_ref().is_ok_and(|r| r.success),
+            duration_ms: elapsed.as_millis() as u64,
+            timestamp: Utc::now(),
+            origin,
+            request_id: None,
+        }
+    }
+
+    /// Attribute this call to the request with correlation id `request_id`.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+/// A stored call re-run against the current registry.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub original: ToolExecution,
+    /// Result of the new run, in the same shape as [`ToolExecution::result`].
+    pub result: Value,
+    pub success: bool,
+}
+
+impl Replay {
+    /// Whether the new run produced exactly the stored result.
+    pub fn is_identical(&self) -> bool {
+        self.original.result == self.result
+    }
+
+    /// Line diff of the stored result against the new one.
+    pub fn diff(&self) -> String {
+        diff_lines(&pretty(&self.original.result), &pretty(&self.result))
+    }
+}
+
+/// Default on-disk location of the history (`~/.ygn/tool_history.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/tool_history.db")
+}
+
+// ---------------------------------------------------------------------------
+// ToolExecutionLog
+// ---------------------------------------------------------------------------
+
+/// Persistent log of tool executions backed by SQLite.
+pub struct ToolExecutionLog {
+    conn: Mutex<Connection>,
+}
+
+impl std::fmt::Debug for ToolExecutionLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolExecutionLog").finish_non_exhaustive()
+    }
+}
+
+impl ToolExecutionLog {
+    /// Open (or create) a file-based history.
+    pub fn new(path: &str) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Create an in-memory history (useful for testing).
+    pub fn in_memory() -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Store one execution.
+    pub fn record(&self, execution: &ToolExecution) -> anyhow::Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        conn.execute(
+            "INSERT INTO tool_executions
+                (id, tool_name, arguments, result, success, duration_ms, timestamp, origin,
+                 request_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                execution.id,
+                execution.tool_name,
+                execution.arguments.to_string(),
+                execution.result.to_string(),
+                execution.success,
+                execution.duration_ms as i64,
+                execution.timestamp.to_rfc3339(),
+                execution.origin.as_str(),
+                execution.request_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Look up an execution by id.
+    pub fn get(&self, id: &str) -> anyhow::Result<Option<ToolExecution>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        Ok(conn
+            .query_row(
+                "SELECT id, tool_name, arguments, result, success, duration_ms, timestamp, origin,
+                        request_id
+                 FROM tool_executions WHERE id = ?1",
+                params![id],
+                row_to_execution,
+            )
+            .optional()?)
+    }
+
+    /// Most recent executions first, optionally only those of `tool`.
+    pub fn list(&self, tool: Option<&str>, limit: usize) -> anyhow::Result<Vec<ToolExecution>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let mut stmt = conn.prepare(
+            "SELECT id, tool_name, arguments, result, success, duration_ms, timestamp, origin,
+                    request_id
+             FROM tool_executions
+             WHERE ?1 IS NULL OR tool_name = ?1
+             ORDER BY timestamp DESC LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![tool, limit as i64], row_to_execution)?
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Re-run the stored execution `id` against `registry`.
+    ///
+    /// The call is evaluated by `policy` first and refused unless it is
+    /// allowed.  If `registry` has a history attached, the new run is
+    /// recorded like any other execution.
+    pub async fn replay(
+        &self,
+        id: &str,
+        registry: &ToolRegistry,
+        policy: &PolicyEngine,
+    ) -> anyhow::Result<Replay> {
+        let original = self
+            .get(id)?
+            .ok_or_else(|| anyhow::anyhow!("no tool execution with id {id}"))?;
+        let decision = policy.evaluate(&original.tool_name, &original.arguments);
+        if decision.action != PolicyAction::Allow {
+            anyhow::bail!(
+                "replay refused by policy ({:?}): {}",
+                decision.action,
+                decision.reason
+            );
+        }
+        let result = registry
+            .execute(&original.tool_name, original.arguments.clone())
+            .await;
+        Ok(Replay {
+            success: result.as_ref().is_ok_and(|r| r.success),
+            result: result_json(&result),
+            original,
+        })
+    }
+}
+
+fn row_to_execution(row: &rusqlite::Row<'_>) -> rusqlite::Result<ToolExecution> {
+    let arguments: String = row.get(2)?;
+    let result: String = row.get(3)?;
+    let timestamp: String = row.get(6)?;
+    let origin: String = row.get(7)?;
+    Ok(ToolExecution {
+        id: row.get(0)?,
+        tool_name: row.get(1)?,
+        arguments: serde_json::from_str(&arguments).unwrap_or(Value::Null),
+        result: serde_json::from_str(&result).unwrap_or(Value::Null),
+        success: row.get(4)?,
+        duration_ms: row.get::<_, i64>(5)? as u64,
+        timestamp: DateTime::parse_from_rfc3339(&timestamp)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_default(),
+        origin: ExecutionOrigin::parse(&origin),
+        request_id: row.get(8)?,
+    })
+}
+
+fn result_json(result: &anyhow::Result<ToolResult>) -> Value {
+    match result {
+        Ok(r) => serde_json::to_value(r).unwrap_or(Value::Null),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "output": "",
+            "error": e.to_string(),
+        }),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Line diff of `old` against `new`: unchanged lines are prefixed with two
+/// spaces, removed lines with `- ` and added lines with `+ `.
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    // Longest common subsequence lengths of the suffixes a[i..], b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push_str(&format!("  {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", a[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::McpServer;
+    use crate::policy::PolicyConfig;
+    use crate::tool::EchoTool;
+    use std::sync::Arc;
+
+    fn registry_with_log(origin: ExecutionOrigin) -> (ToolRegistry, Arc<ToolExecutionLog>) {
+        let log = Arc::new(ToolExecutionLog::in_memory().unwrap());
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+        registry.set_history(log.clone(), origin);
+        (registry, log)
+    }
+
+    #[test]
+    fn mcp_calls_are_recorded() {
+        let (registry, log) = registry_with_log(ExecutionOrigin::Mcp);
+        let server = McpServer::new(registry);
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+        server.handle_message(req).unwrap();
+
+        let history = log.list(None, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        let entry = &history[0];
+        assert_eq!(entry.tool_name, "echo");
+        assert_eq!(entry.origin, ExecutionOrigin::Mcp);
+        assert_eq!(entry.arguments, serde_json::json!({ "input": "hi" }));
+        assert_eq!(entry.result["output"], "hi");
+        assert!(entry.success);
+
+        let fetched = log.get(&entry.id).unwrap().unwrap();
+        assert_eq!(fetched.timestamp, entry.timestamp);
+        assert!(entry.request_id.is_some());
+        assert_eq!(fetched.request_id, entry.request_id);
+        assert!(log.list(Some("other"), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mcp_calls_carry_the_given_request_id() {
+        let (registry, log) = registry_with_log(ExecutionOrigin::Mcp);
+        let server = McpServer::new(registry).with_request_id("req-7");
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo","arguments":{"input":"hi"}}}"#;
+        server.handle_message(req).unwrap();
+
+        let history = log.list(None, 10).unwrap();
+        assert_eq!(history[0].request_id.as_deref(), Some("req-7"));
+    }
+
+    #[tokio::test]
+    async fn replaying_echo_is_identical() {
+        let (registry, log) = registry_with_log(ExecutionOrigin::Cli);
+        registry
+            .execute("echo", serde_json::json!({ "input": "same" }))
+            .await
+            .unwrap();
+        let id = log.list(Some("echo"), 1).unwrap()[0].id.clone();
+
+        let policy = PolicyEngine::from_config(PolicyConfig::default());
+        let replay = log.replay(&id, &registry, &policy).await.unwrap();
+        assert!(replay.success);
+        assert!(replay.is_identical());
+        assert!(replay.diff().lines().all(|l| l.starts_with("  ")));
+        // The replay itself is recorded too.
+        assert_eq!(log.list(None, 10).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn replay_rechecks_policy() {
+        let (registry, log) = registry_with_log(ExecutionOrigin::Cli);
+        registry
+            .execute("echo", serde_json::json!({ "input": "x" }))
+            .await
+            .unwrap();
+        let id = log.list(None, 1).unwrap()[0].id.clone();
+
+        let policy = PolicyEngine::from_config(PolicyConfig {
+            denied_tools: vec!["echo".into()],
+            ..Default::default()
+        });
+        let err = log.replay(&id, &registry, &policy).await.unwrap_err();
+        assert!(err.to_string().contains("refused by policy"));
+        assert_eq!(log.list(None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc");
+        assert_eq!(diff, "  a\n- b\n+ x\n  c\n");
+    }
+}
@@ -0,0 +1,328 @@
+//! Per-tool concurrency limits.
+//!
+//! A [`ToolLimiter`] caps how many executions of a tool run at once.  Calls
+//! beyond the cap wait in a queue for up to the configured timeout, then fail
+//! with [`ToolBusy`] instead of piling up.  Attach one to a
+//! [`ToolRegistry`](crate::tool::ToolRegistry) with
+//! [`set_limiter`](crate::tool::ToolRegistry::set_limiter); registries that
+//! share a limiter share its slots, so the caps hold node-wide.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::tool::NAMESPACE_SEPARATOR;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Tool concurrency settings in the node config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct ToolLimitsConfig {
+    /// Maximum concurrent executions by tool name, e.g. `hardware = 1`.  A
+    /// short name also covers the namespaced tools of that name.  Tools
+    /// without an entry are unlimited.
+    pub limits: BTreeMap<String, usize>,
+    /// Milliseconds a call waits for a free slot before failing as busy.
+    pub queue_timeout_ms: u64,
+    /// Calls that may wait for one tool at a time; further calls fail
+    /// immediately.  Unbounded when unset.
+    pub max_queued: Option<usize>,
+}
+
+impl Default for ToolLimitsConfig {
+    fn default() -> Self {
+        Self {
+            limits: BTreeMap::new(),
+            queue_timeout_ms: 30_000,
+            max_queued: None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A call turned away because its tool stayed at its concurrency limit.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("tool '{tool}' is busy: {limit} concurrent call(s) allowed, {queued} waiting")]
+pub struct ToolBusy {
+    pub tool: String,
+    pub limit: usize,
+    /// Calls that were waiting for the tool when this one gave up.
+    pub queued: usize,
+}
+
+/// Current load of a limited tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConcurrency {
+    pub limit: usize,
+    pub in_flight: usize,
+    pub queued: usize,
+}
+
+#[derive(Debug)]
+struct Slots {
+    limit: usize,
+    semaphore: Semaphore,
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+}
+
+/// Holds one of a tool's slots until dropped.
+#[derive(Debug)]
+pub struct ToolPermit<'a> {
+    _permit: SemaphorePermit<'a>,
+    in_flight: &'a AtomicUsize,
+}
+
+impl Drop for ToolPermit<'_> {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a call as queued until dropped, including when the waiting
+/// future is cancelled.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ToolLimiter
+// ---------------------------------------------------------------------------
+
+/// Concurrency slots for the tools named in a [`ToolLimitsConfig`].
+#[derive(Debug)]
+pub struct ToolLimiter {
+    /// Slots keyed by the name used in the config.
+    slots: HashMap<String, Slots>,
+    queue_timeout: Duration,
+    max_queued: Option<usize>,
+}
+
+impl Default for ToolLimiter {
+    fn default() -> Self {
+        Self::new(ToolLimitsConfig::default())
+    }
+}
+
+impl ToolLimiter {
+    pub fn new(config: ToolLimitsConfig) -> Self {
+        let slots = config
+            .limits
+            .into_iter()
+            .map(|(tool, limit)| {
+                let limit = limit.max(1);
+                let slots = Slots {
+                    limit,
+                    semaphore: Semaphore::new(limit),
+                    in_flight: AtomicUsize::new(0),
+                    queued: AtomicUsize::new(0),
+                };
+                (tool, slots)
+            })
+            .collect();
+        Self {
+            slots,
+            queue_timeout: Duration::from_millis(config.queue_timeout_ms),
+            max_queued: config.max_queued,
+        }
+    }
+
+    /// The slots governing `tool`: an entry for its qualified name, else
+    /// one for its short name.
+    fn slots_for(&self, tool: &str) -> Option<(&str, &Slots)> {
+        let short = tool.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or(tool);
+        self.slots
+            .get_key_value(tool)
+            .or_else(|| self.slots.get_key_value(short))
+            .map(|(name, slots)| (name.as_str(), slots))
+    }
+
+    /// Take a slot for one execution of `tool`, waiting up to the queue
+    /// timeout when all are in use.  `None` when `tool` is unlimited.
+    pub async fn acquire(&self, tool: &str) -> Result<Option<ToolPermit<'_>>, ToolBusy> {
+        let Some((name, slots)) = self.slots_for(tool) else {
+            return Ok(None);
+        };
+        let permit = match slots.semaphore.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let queued = slots.queued.fetch_add(1, Ordering::Relaxed) + 1;
+                let _queued = Queued(&slots.queued);
+                let busy = || ToolBusy {
+                    tool: name.to_string(),
+                    limit: slots.limit,
+                    queued: slots.queued.load(Ordering::Relaxed) - 1,
+                };
+                if self.max_queued.is_some_and(|max| queued > max) {
+                    return Err(busy());
+                }
+                match tokio::time::timeout(self.queue_timeout, slots.semaphore.acquire()).await {
+                    Ok(Ok(permit)) => permit,
+                    _ => return Err(busy()),
+                }
+            }
+        };
+        slots.in_flight.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(ToolPermit {
+            _permit: permit,
+            in_flight: &slots.in_flight,
+        }))
+    }
+
+    /// Current load of `tool`, when it is limited.
+    pub fn concurrency(&self, tool: &str) -> Option<ToolConcurrency> {
+        self.slots_for(tool).map(|(_, slots)| ToolConcurrency {
+            limit: slots.limit,
+            in_flight: slots.in_flight.load(Ordering::Relaxed),
+            queued: slots.queued.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Load of every limited tool, by configured name.
+    pub fn snapshot(&self) -> BTreeMap<String, ToolConcurrency> {
+        self.slots
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.concurrency(name)?)))
+            .collect()
+    }
+
+    /// Append per-tool limit, in-flight and queued gauges in the
+    /// Prometheus text format.
+    pub fn render(&self, out: &mut String) {
+        let snapshot = self.snapshot();
+        if snapshot.is_empty() {
+            return;
+        }
+        let mut gauge = |name: &str, help: &str, value: fn(&ToolConcurrency) -> usize| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+            for (tool, concurrency) in &snapshot {
+                let _ = writeln!(out, "{name}{{tool=\"{tool}\"}} {}", value(concurrency));
+            }
+        };
+        gauge(
+            "ygn_tool_concurrency_limit",
+            "Maximum concurrent executions of a limited tool.",
+            |c| c.limit,
+        );
+        gauge(
+            "ygn_tool_in_flight",
+            "Executions of a limited tool currently running.",
+            |c| c.in_flight,
+        );
+        gauge(
+            "ygn_tool_queued",
+            "Calls waiting for a slot of a limited tool.",
+            |c| c.queued,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    fn limiter(limits: &[(&str, usize)], queue_timeout_ms: u64) -> ToolLimiter {
+        ToolLimiter::new(ToolLimitsConfig {
+            limits: limits.iter().map(|(t, l)| (t.to_string(), *l)).collect(),
+            queue_timeout_ms,
+            max_queued: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn unlimited_tools_get_no_permit() {
+        let limiter = limiter(&[("hardware", 1)], 10);
+        assert!(limiter.acquire("echo").await.unwrap().is_none());
+        assert!(limiter.concurrency("echo").is_none());
+    }
+
+    #[tokio::test]
+    async fn permits_count_in_flight_until_dropped() {
+        let limiter = limiter(&[("shell", 2)], 10);
+        let first = limiter.acquire("shell").await.unwrap();
+        let second = limiter.acquire("node-a/shell").await.unwrap();
+        assert_eq!(
+            limiter.concurrency("shell"),
+            Some(ToolConcurrency {
+                limit: 2,
+                in_flight: 2,
+                queued: 0
+            })
+        );
+        drop((first, second));
+        assert_eq!(limiter.concurrency("shell").unwrap().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn full_tool_is_busy_after_queue_timeout() {
+        let limiter = limiter(&[("hardware", 1)], 30);
+        let _held = limiter.acquire("hardware").await.unwrap();
+        let started = Instant::now();
+        let err = limiter.acquire("hardware").await.unwrap_err();
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert_eq!(err.tool, "hardware");
+        assert_eq!(err.limit, 1);
+        assert_eq!(limiter.concurrency("hardware").unwrap().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn queued_call_runs_when_a_slot_frees() {
+        let limiter = Arc::new(limiter(&[("hardware", 1)], 5_000));
+        let held = limiter.acquire("hardware").await.unwrap();
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("hardware").await.map(|p| p.is_some()) }
+        });
+        while limiter.concurrency("hardware").unwrap().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+        drop(held);
+        assert!(waiter.await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_immediately() {
+        let limiter = ToolLimiter::new(ToolLimitsConfig {
+            limits: [("hardware".to_string(), 1)].into(),
+            queue_timeout_ms: 60_000,
+            max_queued: Some(0),
+        });
+        let _held = limiter.acquire("hardware").await.unwrap();
+        let started = Instant::now();
+        assert!(limiter.acquire("hardware").await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn renders_gauges_per_limited_tool() {
+        let limiter = limiter(&[("hardware", 1), ("shell", 2)], 10);
+        let _held = limiter.acquire("shell").await.unwrap();
+        let mut out = String::new();
+        limiter.render(&mut out);
+        assert!(out.contains("# TYPE ygn_tool_in_flight gauge\n"));
+        assert!(out.contains("ygn_tool_concurrency_limit{tool=\"hardware\"} 1\n"));
+        assert!(out.contains("ygn_tool_in_flight{tool=\"shell\"} 1\n"));
+        assert!(out.contains("ygn_tool_queued{tool=\"shell\"} 0\n"));
+
+        let mut empty = String::new();
+        ToolLimiter::default().render(&mut empty);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,311 @@
+//! Per-tool output size limits.
+//!
+//! A [`ToolOutputLimits`] bounds what a tool call may return.  Text and JSON
+//! output over the limit is cut at the limit and ends with a marker giving
+//! the original size; binary output over the limit is rejected with
+//! [`OutputTooLarge`].  With a spill directory configured, the full output
+//! of a truncated call is first written to a file there, and the marker
+//! names it.  Attach one to a [`ToolRegistry`](crate::tool::ToolRegistry)
+//! with [`set_output_limits`](crate::tool::ToolRegistry::set_output_limits).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::sandbox::{AccessKind, AccessRequest, ProcessSandbox, SandboxProfile};
+use crate::tool::{ToolContent, ToolResult, NAMESPACE_SEPARATOR};
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Tool output settings in the node config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct ToolOutputConfig {
+    /// Largest output in bytes any tool may return.
+    pub max_bytes: usize,
+    /// Limits overriding `max_bytes` by tool name, e.g. `read_file =
+    /// 65536`.  A short name also covers the namespaced tools of that name.
+    pub limits: BTreeMap<String, usize>,
+    /// Directory the full output of truncated calls is written to.  Not
+    /// kept when unset.
+    pub spill_dir: Option<PathBuf>,
+}
+
+impl Default for ToolOutputConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1024 * 1024,
+            limits: BTreeMap::new(),
+            spill_dir: None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Binary output turned away for exceeding its tool's limit.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("tool '{tool}' returned {bytes} bytes of binary output, over its {limit} byte limit")]
+pub struct OutputTooLarge {
+    pub tool: String,
+    pub bytes: usize,
+    pub limit: usize,
+}
+
+/// How a [`ToolResult`]'s content was cut to fit its tool's limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Truncation {
+    /// Size of the full output in bytes.
+    pub original_bytes: usize,
+    /// Bytes of the output kept, before the marker.
+    pub kept_bytes: usize,
+    /// File holding the full output, when it was spilled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spill_path: Option<PathBuf>,
+}
+
+impl Truncation {
+    /// The line appended to truncated output.
+    pub fn marker(&self) -> String {
+        let mut marker = format!(
+            "[output truncated at byte {} of {}",
+            self.kept_bytes, self.original_bytes
+        );
+        if let Some(path) = &self.spill_path {
+            marker.push_str(&format!("; full output in {}", path.display()));
+        }
+        marker.push(']');
+        marker
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ToolOutputLimits
+// ---------------------------------------------------------------------------
+
+/// Output limits for the tools named in a [`ToolOutputConfig`].
+#[derive(Debug, Clone)]
+pub struct ToolOutputLimits {
+    max_bytes: usize,
+    limits: BTreeMap<String, usize>,
+    /// Confines spill files to the spill directory.
+    spill: Option<ProcessSandbox>,
+    spill_dir: Option<PathBuf>,
+}
+
+impl Default for ToolOutputLimits {
+    fn default() -> Self {
+        Self::new(ToolOutputConfig::default())
+    }
+}
+
+impl ToolOutputLimits {
+    pub fn new(config: ToolOutputConfig) -> Self {
+        let spill = config.spill_dir.as_ref().map(|dir| {
+            let mut sandbox = ProcessSandbox::new(SandboxProfile::ScratchFs);
+            sandbox.set_scratch_dir(dir.clone());
+            sandbox
+        });
+        Self {
+            max_bytes: config.max_bytes,
+            limits: config.limits,
+            spill,
+            spill_dir: config.spill_dir,
+        }
+    }
+
+    /// The limit for `tool`: an entry for its qualified name, else one for
+    /// its short name, else the global limit.
+    pub fn limit_for(&self, tool: &str) -> usize {
+        let short = tool.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or(tool);
+        self.limits
+            .get(tool)
+            .or_else(|| self.limits.get(short))
+            .copied()
+            .unwrap_or(self.max_bytes)
+    }
+
+    /// Fit `result`, returned by `tool`, to the tool's limit.  Failed
+    /// results pass through unchanged.
+    pub fn apply(&self, tool: &str, mut result: ToolResult) -> Result<ToolResult, OutputTooLarge> {
+        if !result.success {
+            return Ok(result);
+        }
+        let limit = self.limit_for(tool);
+        let full = match &mut result.content {
+            ToolContent::Binary { data, .. } if data.len() > limit => {
+                return Err(OutputTooLarge {
+                    tool: tool.to_string(),
+                    bytes: data.len(),
+                    limit,
+                });
+            }
+            ToolContent::Binary { .. } => return Ok(result),
+            ToolContent::Text(text) if text.len() <= limit => return Ok(result),
+            ToolContent::Text(text) => std::mem::take(text),
+            ToolContent::Json(value) => {
+                let json = value.to_string();
+                if json.len() <= limit {
+                    return Ok(result);
+                }
+                json
+            }
+        };
+
+        let kept = floor_char_boundary(&full, limit);
+        let truncation = Truncation {
+            original_bytes: full.len(),
+            kept_bytes: kept,
+            spill_path: self.spill(tool, &full),
+        };
+        let mut text = full;
+        text.truncate(kept);
+        text.push('\n');
+        text.push_str(&truncation.marker());
+        result.content = ToolContent::Text(text);
+        result.truncated = Some(truncation);
+        Ok(result)
+    }
+
+    /// Write `output` to a new file in the spill directory, returning its
+    /// path.  `None` when spilling is off or the write fails.
+    fn spill(&self, tool: &str, output: &str) -> Option<PathBuf> {
+        let (sandbox, dir) = (self.spill.as_ref()?, self.spill_dir.as_ref()?);
+        let name: String = tool
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = dir.join(format!("{name}-{}.out", uuid::Uuid::new_v4()));
+        let access = sandbox.check_access(&AccessRequest {
+            kind: AccessKind::FileWrite,
+            target: path.display().to_string(),
+        });
+        if !access.allowed {
+            tracing::warn!(tool, reason = %access.reason, "tool output not spilled");
+            return None;
+        }
+        match write_spill(dir, &path, output) {
+            Ok(()) => Some(path),
+            Err(e) => {
+                tracing::warn!(tool, error = %e, "failed to spill tool output");
+                None
+            }
+        }
+    }
+}
+
+fn write_spill(dir: &Path, path: &Path, output: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, output)
+}
+
+/// The largest index `<= index` that falls on a char boundary of `s`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    (0..=index)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(content: ToolContent) -> ToolResult {
+        ToolResult {
+            success: true,
+            content,
+            error: None,
+            truncated: None,
+        }
+    }
+
+    fn limits(max_bytes: usize) -> ToolOutputLimits {
+        ToolOutputLimits::new(ToolOutputConfig {
+            max_bytes,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn output_within_the_limit_is_untouched() {
+        let result = limits(8).apply("echo", ok("12345678".into())).unwrap();
+        assert_eq!(result.content, ToolContent::Text("12345678".into()));
+        assert!(result.truncated.is_none());
+    }
+
+    #[test]
+    fn long_text_is_cut_on_a_char_boundary() {
+        let result = limits(5).apply("echo", ok("abcdé€".into())).unwrap();
+        let truncation = result.truncated.unwrap();
+        assert_eq!(truncation.kept_bytes, 4);
+        assert_eq!(truncation.original_bytes, "abcdé€".len());
+        assert_eq!(
+            result.content,
+            ToolContent::Text("abcd\n[output truncated at byte 4 of 9]".into())
+        );
+    }
+
+    #[test]
+    fn json_over_the_limit_becomes_truncated_text() {
+        let value = serde_json::json!({ "readings": [1, 2, 3, 4, 5, 6] });
+        let result = limits(10)
+            .apply("sensor", ok(ToolContent::Json(value)))
+            .unwrap();
+        let ToolContent::Text(text) = &result.content else {
+            panic!("expected text, got {:?}", result.content);
+        };
+        assert!(text.starts_with("{\"readings"), "{text}");
+        assert_eq!(result.truncated.unwrap().kept_bytes, 10);
+    }
+
+    #[test]
+    fn binary_over_the_limit_is_rejected() {
+        let content = ToolContent::Binary {
+            data: vec![0; 16],
+            mime_type: "image/png".into(),
+        };
+        let err = limits(8).apply("camera", ok(content)).unwrap_err();
+        assert_eq!(
+            err,
+            OutputTooLarge {
+                tool: "camera".into(),
+                bytes: 16,
+                limit: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn per_tool_limits_override_the_default() {
+        let limits = ToolOutputLimits::new(ToolOutputConfig {
+            max_bytes: 4,
+            limits: [("read_file".to_string(), 100)].into(),
+            spill_dir: None,
+        });
+        assert_eq!(limits.limit_for("read_file"), 100);
+        assert_eq!(limits.limit_for("fs/read_file"), 100);
+        assert_eq!(limits.limit_for("echo"), 4);
+    }
+
+    #[test]
+    fn failed_results_are_not_limited() {
+        let mut failed = ok("x".repeat(100).into());
+        failed.success = false;
+        let result = limits(8).apply("echo", failed).unwrap();
+        assert!(result.truncated.is_none());
+    }
+}
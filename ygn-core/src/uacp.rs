@@ -8,6 +8,13 @@
 //! [1B verb][4B message_id][8B timestamp][2B sender_len][sender_bytes][4B payload_len][payload_bytes]
 //! ```
 //! Total header overhead: 19 bytes + sender_len + payload_len
+//!
+//! A reply sets the high bit of the verb byte ([`REPLY_FLAG`]) and appends
+//! `[4B in_reply_to]`, the `message_id` it answers.  Frames without the flag
+//! are unchanged.
+//!
+//! [`UacpDecoder`] reassembles frames from a byte stream; the TCP transport
+//! lives in [`crate::uacp_transport`].
 
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -37,6 +44,31 @@ impl UacpVerb {
             other => anyhow::bail!("invalid uACP verb byte: 0x{other:02x}"),
         }
     }
+
+    /// The verb's name as written in the spec, e.g. `"PING"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ping => "PING",
+            Self::Tell => "TELL",
+            Self::Ask => "ASK",
+            Self::Observe => "OBSERVE",
+        }
+    }
+}
+
+impl std::str::FromStr for UacpVerb {
+    type Err = anyhow::Error;
+
+    /// Parse a verb name, ignoring case.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "PING" => Ok(Self::Ping),
+            "TELL" => Ok(Self::Tell),
+            "ASK" => Ok(Self::Ask),
+            "OBSERVE" => Ok(Self::Observe),
+            _ => anyhow::bail!("unknown uACP verb '{s}'"),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -51,6 +83,9 @@ pub struct UacpMessage {
     pub sender_id: String,
     pub payload: Vec<u8>,
     pub timestamp: u64,
+    /// `message_id` of the message this one answers.
+    #[serde(default)]
+    pub in_reply_to: Option<u32>,
 }
 
 /// Global atomic counter for generating unique message IDs.
@@ -65,6 +100,7 @@ impl UacpMessage {
             sender_id: sender.to_string(),
             payload: Vec::new(),
             timestamp: now_millis(),
+            in_reply_to: None,
         }
     }
 
@@ -76,6 +112,7 @@ impl UacpMessage {
             sender_id: sender.to_string(),
             payload: payload.to_vec(),
             timestamp: now_millis(),
+            in_reply_to: None,
         }
     }
 
@@ -87,6 +124,7 @@ impl UacpMessage {
             sender_id: sender.to_string(),
             payload: payload.to_vec(),
             timestamp: now_millis(),
+            in_reply_to: None,
         }
     }
 
@@ -98,6 +136,24 @@ impl UacpMessage {
             sender_id: sender.to_string(),
             payload: payload.to_vec(),
             timestamp: now_millis(),
+            in_reply_to: None,
+        }
+    }
+
+    /// Create a reply to this message: a PING answers a PING, anything
+    /// else is answered with a TELL.
+    pub fn reply(&self, sender: &str, payload: &[u8]) -> Self {
+        let verb = match self.verb {
+            UacpVerb::Ping => UacpVerb::Ping,
+            _ => UacpVerb::Tell,
+        };
+        Self {
+            verb,
+            message_id: MSG_ID_COUNTER.fetch_add(1, Ordering::Relaxed),
+            sender_id: sender.to_string(),
+            payload: payload.to_vec(),
+            timestamp: now_millis(),
+            in_reply_to: Some(self.message_id),
         }
     }
 }
@@ -117,6 +173,12 @@ fn now_millis() -> u64 {
 /// Minimum wire size: 1 (verb) + 4 (msg_id) + 8 (ts) + 2 (sender_len) + 4 (payload_len) = 19.
 const MIN_HEADER_SIZE: usize = 19;
 
+/// Verb-byte bit marking a reply, which carries a trailing `in_reply_to`.
+pub const REPLY_FLAG: u8 = 0x80;
+
+/// Size of the trailing `in_reply_to` field of a reply.
+const REPLY_FIELD_SIZE: usize = 4;
+
 /// Encodes and decodes `UacpMessage` values to/from the compact binary wire format.
 #[derive(Debug, Clone, Default)]
 pub struct UacpCodec;
@@ -128,16 +190,24 @@ impl UacpCodec {
         let sender_len = sender_bytes.len() as u16;
         let payload_len = msg.payload.len() as u32;
 
-        let total = MIN_HEADER_SIZE + sender_bytes.len() + msg.payload.len();
+        let total = MIN_HEADER_SIZE + sender_bytes.len() + msg.payload.len() + REPLY_FIELD_SIZE;
         let mut buf = Vec::with_capacity(total);
 
-        buf.push(msg.verb as u8);
+        let flag = if msg.in_reply_to.is_some() {
+            REPLY_FLAG
+        } else {
+            0
+        };
+        buf.push(msg.verb as u8 | flag);
         buf.extend_from_slice(&msg.message_id.to_be_bytes());
         buf.extend_from_slice(&msg.timestamp.to_be_bytes());
         buf.extend_from_slice(&sender_len.to_be_bytes());
         buf.extend_from_slice(sender_bytes);
         buf.extend_from_slice(&payload_len.to_be_bytes());
         buf.extend_from_slice(&msg.payload);
+        if let Some(in_reply_to) = msg.in_reply_to {
+            buf.extend_from_slice(&in_reply_to.to_be_bytes());
+        }
 
         buf
     }
@@ -155,7 +225,8 @@ impl UacpCodec {
         let mut pos = 0;
 
         // verb
-        let verb = UacpVerb::from_byte(data[pos])?;
+        let verb = UacpVerb::from_byte(data[pos] & !REPLY_FLAG)?;
+        let is_reply = data[pos] & REPLY_FLAG != 0;
         pos += 1;
 
         // message_id
@@ -194,6 +265,17 @@ impl UacpCodec {
             );
         }
         let payload = data[pos..pos + payload_len].to_vec();
+        pos += payload_len;
+
+        // in_reply_to
+        let in_reply_to = if is_reply {
+            if pos + REPLY_FIELD_SIZE > data.len() {
+                anyhow::bail!("uACP frame truncated: missing in_reply_to");
+            }
+            Some(u32::from_be_bytes(data[pos..pos + 4].try_into()?))
+        } else {
+            None
+        };
 
         Ok(UacpMessage {
             verb,
@@ -201,9 +283,29 @@ impl UacpCodec {
             sender_id,
             payload,
             timestamp,
+            in_reply_to,
         })
     }
 
+    /// Size of the frame at the start of `data`, or `None` if more bytes
+    /// are needed to tell.
+    pub fn frame_len(data: &[u8]) -> Option<usize> {
+        if data.len() < MIN_HEADER_SIZE {
+            return None;
+        }
+        // 15 = 1 verb + 4 msg_id + 8 ts + 2 sender_len
+        let sender_len = u16::from_be_bytes([data[13], data[14]]) as usize;
+        let pl_off = 15 + sender_len;
+        let len_bytes: [u8; 4] = data.get(pl_off..pl_off + 4)?.try_into().ok()?;
+        let payload_len = u32::from_be_bytes(len_bytes) as usize;
+        let reply_len = if data[0] & REPLY_FLAG != 0 {
+            REPLY_FIELD_SIZE
+        } else {
+            0
+        };
+        Some(MIN_HEADER_SIZE + sender_len + payload_len + reply_len)
+    }
+
     /// Encode multiple messages into a single buffer (concatenated frames).
     pub fn encode_batch(msgs: &[UacpMessage]) -> Vec<u8> {
         let mut buf = Vec::new();
@@ -219,39 +321,201 @@ impl UacpCodec {
         let mut pos = 0;
 
         while pos < data.len() {
-            // We need to figure out message length by peeking at the header.
-            if pos + MIN_HEADER_SIZE > data.len() {
-                anyhow::bail!(
-                    "uACP batch: trailing {} bytes too short for a header",
-                    data.len() - pos
-                );
-            }
+            let rest = &data[pos..];
+            let frame_len = match Self::frame_len(rest) {
+                Some(len) if len <= rest.len() => len,
+                _ => anyhow::bail!(
+                    "uACP batch: trailing {} bytes are not a complete frame",
+                    rest.len()
+                ),
+            };
+            msgs.push(Self::decode(&rest[..frame_len])?);
+            pos += frame_len;
+        }
 
-            // Parse sender_len to compute full frame size.
-            let sender_len = u16::from_be_bytes(data[pos + 13..pos + 15].try_into()?) as usize;
+        Ok(msgs)
+    }
+}
 
-            // payload_len field sits right after sender bytes:
-            // pos + 15 + sender_len  (15 = 1 verb + 4 msg_id + 8 ts + 2 sender_len)
-            let pl_off = pos + 15 + sender_len;
-            if pl_off + 4 > data.len() {
-                anyhow::bail!("uACP batch: frame truncated at payload_len");
-            }
-            let payload_len = u32::from_be_bytes(data[pl_off..pl_off + 4].try_into()?) as usize;
+// ---------------------------------------------------------------------------
+// Streaming decoder
+// ---------------------------------------------------------------------------
 
-            let frame_len = MIN_HEADER_SIZE + sender_len + payload_len;
-            if pos + frame_len > data.len() {
-                anyhow::bail!("uACP batch: frame overflows buffer");
-            }
+/// Largest frame [`UacpDecoder`] accepts by default (16 MiB).
+pub const DEFAULT_MAX_FRAME: usize = 16 * 1024 * 1024;
 
-            let msg = Self::decode(&data[pos..pos + frame_len])?;
-            msgs.push(msg);
-            pos += frame_len;
+/// Reassembles messages from bytes that arrive in arbitrary chunks, as
+/// read from a socket.
+#[derive(Debug)]
+pub struct UacpDecoder {
+    buf: Vec<u8>,
+    max_frame: usize,
+}
+
+impl Default for UacpDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UacpDecoder {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            max_frame: DEFAULT_MAX_FRAME,
         }
+    }
 
-        Ok(msgs)
+    /// Reject frames larger than `max_frame` bytes instead of buffering
+    /// them.
+    pub fn with_max_frame(mut self, max_frame: usize) -> Self {
+        self.max_frame = max_frame;
+        self
+    }
+
+    /// Append bytes read from the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Take the next complete message, or `None` until more bytes arrive.
+    /// An error means the stream is corrupt and should be closed.
+    pub fn next_message(&mut self) -> anyhow::Result<Option<UacpMessage>> {
+        let Some(frame_len) = UacpCodec::frame_len(&self.buf) else {
+            return Ok(None);
+        };
+        if frame_len > self.max_frame {
+            anyhow::bail!(
+                "uACP frame of {frame_len} bytes exceeds the {} byte limit",
+                self.max_frame
+            );
+        }
+        if self.buf.len() < frame_len {
+            return Ok(None);
+        }
+        let msg = UacpCodec::decode(&self.buf[..frame_len]);
+        self.buf.drain(..frame_len);
+        msg.map(Some)
+    }
+
+    /// Bytes received that do not yet form a complete message.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON bridge
+// ---------------------------------------------------------------------------
+
+impl UacpMessage {
+    /// Render the message as readable JSON for debugging: the verb by name
+    /// and the payload as UTF-8 text when valid, else base64, with
+    /// `payload_encoding` saying which.
+    pub fn to_json(&self) -> serde_json::Value {
+        use base64::Engine;
+        let (payload, encoding) = match std::str::from_utf8(&self.payload) {
+            Ok(text) => (text.to_string(), "utf8"),
+            Err(_) => (
+                base64::engine::general_purpose::STANDARD.encode(&self.payload),
+                "base64",
+            ),
+        };
+        let mut value = serde_json::json!({
+            "verb": self.verb.as_str(),
+            "message_id": self.message_id,
+            "sender_id": self.sender_id,
+            "timestamp": self.timestamp,
+            "payload": payload,
+            "payload_encoding": encoding,
+        });
+        if let Some(in_reply_to) = self.in_reply_to {
+            value["in_reply_to"] = in_reply_to.into();
+        }
+        value
+    }
+
+    /// Build a message from the JSON form of [`to_json`](Self::to_json).
+    /// `payload` and `payload_encoding` may be omitted (an empty UTF-8
+    /// payload).
+    pub fn from_json(value: &serde_json::Value) -> anyhow::Result<Self> {
+        use base64::Engine;
+        let field = |name: &str| {
+            value
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("uACP JSON is missing '{name}'"))
+        };
+        let verb = field("verb")?
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("uACP JSON 'verb' must be a string"))?
+            .parse()?;
+        let message_id = field("message_id")?
+            .as_u64()
+            .and_then(|id| u32::try_from(id).ok())
+            .ok_or_else(|| anyhow::anyhow!("uACP JSON 'message_id' must be a u32"))?;
+        let sender_id = field("sender_id")?
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("uACP JSON 'sender_id' must be a string"))?
+            .to_string();
+        let timestamp = field("timestamp")?
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("uACP JSON 'timestamp' must be a u64"))?;
+        let text = value
+            .get("payload")
+            .map(|p| {
+                p.as_str()
+                    .ok_or_else(|| anyhow::anyhow!("uACP JSON 'payload' must be a string"))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let payload = match value
+            .get("payload_encoding")
+            .and_then(|e| e.as_str())
+            .unwrap_or("utf8")
+        {
+            "utf8" => text.as_bytes().to_vec(),
+            "base64" => base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|e| anyhow::anyhow!("uACP JSON payload is not valid base64: {e}"))?,
+            other => anyhow::bail!("unknown uACP payload_encoding '{other}'"),
+        };
+        let in_reply_to = match value.get("in_reply_to") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(id) => Some(
+                id.as_u64()
+                    .and_then(|id| u32::try_from(id).ok())
+                    .ok_or_else(|| anyhow::anyhow!("uACP JSON 'in_reply_to' must be a u32"))?,
+            ),
+        };
+        Ok(Self {
+            verb,
+            message_id,
+            sender_id,
+            payload,
+            timestamp,
+            in_reply_to,
+        })
     }
 }
 
+/// Bytes of frames written as text: hex (optionally `0x`-prefixed, spaces
+/// allowed) when the text is only hex digits, else standard base64.
+pub fn frame_bytes_from_text(text: &str) -> anyhow::Result<Vec<u8>> {
+    use base64::Engine;
+    let compact: String = text.split_whitespace().collect();
+    let hex = compact.strip_prefix("0x").unwrap_or(&compact);
+    if !hex.is_empty() && hex.len().is_multiple_of(2) && hex.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return (0..hex.len())
+            .step_by(2)
+            .map(|i| Ok(u8::from_str_radix(&hex[i..i + 2], 16)?))
+            .collect();
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(&compact)
+        .map_err(|e| anyhow::anyhow!("uACP frame is neither hex nor base64: {e}"))
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -272,6 +536,7 @@ mod tests {
             sender_id: "test-agent".to_string(),
             payload: payload.to_vec(),
             timestamp: 1_700_000_000_000,
+            in_reply_to: None,
         }
     }
 
@@ -391,6 +656,7 @@ mod tests {
                 sender_id: format!("agent-{i}"),
                 payload: format!("payload-{i}").into_bytes(),
                 timestamp: 1_700_000_000_000 + u64::from(i),
+                in_reply_to: None,
             })
             .collect();
         let encoded = UacpCodec::encode_batch(&msgs);
@@ -411,6 +677,7 @@ mod tests {
             sender_id: "node-1".to_string(),
             payload: Vec::new(),
             timestamp: 1_700_000_000_000,
+            in_reply_to: None,
         };
         let encoded = UacpCodec::encode(&msg);
         let hex = encoded
@@ -431,4 +698,119 @@ mod tests {
         assert!(decoded.payload.is_empty());
         assert_eq!(decoded.timestamp, 1_700_000_000_000);
     }
+
+    #[test]
+    fn reply_roundtrip_keeps_correlation() {
+        let ask = make_test_message(UacpVerb::Ask, b"question?");
+        let reply = ask.reply("responder", b"answer");
+        assert_eq!(reply.verb, UacpVerb::Tell);
+        assert_eq!(reply.in_reply_to, Some(ask.message_id));
+
+        let encoded = UacpCodec::encode(&reply);
+        assert_eq!(encoded[0], UacpVerb::Tell as u8 | REPLY_FLAG);
+        assert_eq!(UacpCodec::frame_len(&encoded), Some(encoded.len()));
+        let decoded = UacpCodec::decode(&encoded).unwrap();
+        assert_eq!(decoded.in_reply_to, Some(ask.message_id));
+        assert_eq!(decoded.payload, b"answer");
+
+        let pong = make_test_message(UacpVerb::Ping, &[]).reply("responder", &[]);
+        assert_eq!(pong.verb, UacpVerb::Ping);
+
+        let truncated = &encoded[..encoded.len() - 1];
+        let err = UacpCodec::decode(truncated).unwrap_err().to_string();
+        assert!(err.contains("in_reply_to"), "{err}");
+    }
+
+    #[test]
+    fn streaming_decoder_reassembles_split_frames() {
+        let ask = make_test_message(UacpVerb::Ask, b"q");
+        let msgs = vec![
+            make_test_message(UacpVerb::Ping, &[]),
+            ask.reply("responder", b"a"),
+            make_test_message(UacpVerb::Tell, b"data"),
+        ];
+        let bytes = UacpCodec::encode_batch(&msgs);
+
+        let mut decoder = UacpDecoder::new();
+        let mut decoded = Vec::new();
+        for byte in &bytes {
+            decoder.push(std::slice::from_ref(byte));
+            while let Some(msg) = decoder.next_message().unwrap() {
+                decoded.push(msg);
+            }
+        }
+        assert_eq!(decoder.buffered(), 0);
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[1].in_reply_to, Some(1));
+        assert_eq!(decoded[2].payload, b"data");
+    }
+
+    #[test]
+    fn streaming_decoder_rejects_oversized_frames() {
+        let bytes = UacpCodec::encode(&make_test_message(UacpVerb::Tell, &[0; 64]));
+        let mut decoder = UacpDecoder::new().with_max_frame(32);
+        decoder.push(&bytes[..30]);
+        let err = decoder.next_message().unwrap_err().to_string();
+        assert!(err.contains("exceeds the 32 byte limit"), "{err}");
+    }
+
+    #[test]
+    fn decode_batch_reports_incomplete_trailing_frame() {
+        let mut bytes = UacpCodec::encode(&make_test_message(UacpVerb::Tell, b"data"));
+        bytes.extend_from_slice(&[0x02, 0x00]);
+        let err = UacpCodec::decode_batch(&bytes).unwrap_err().to_string();
+        assert!(err.contains("trailing 2 bytes"), "{err}");
+    }
+
+    #[test]
+    fn json_roundtrip_reencodes_identically() {
+        let ask = make_test_message(UacpVerb::Ask, b"question?");
+        for msg in [
+            ask.clone(),
+            ask.reply("responder", b"answer"),
+            make_test_message(UacpVerb::Observe, &[0xFF, 0x00, 0x80]),
+        ] {
+            let json = msg.to_json();
+            let back = UacpMessage::from_json(&json).unwrap();
+            assert_eq!(UacpCodec::encode(&back), UacpCodec::encode(&msg), "{json}");
+        }
+    }
+
+    #[test]
+    fn json_renders_verb_names_and_readable_payloads() {
+        let text = make_test_message(UacpVerb::Tell, b"hello").to_json();
+        assert_eq!(text["verb"], "TELL");
+        assert_eq!(text["payload"], "hello");
+        assert_eq!(text["payload_encoding"], "utf8");
+        assert!(text.get("in_reply_to").is_none());
+
+        let binary = make_test_message(UacpVerb::Tell, &[0xFF, 0xFE]).to_json();
+        assert_eq!(binary["payload"], "//4=");
+        assert_eq!(binary["payload_encoding"], "base64");
+
+        let err = UacpMessage::from_json(&serde_json::json!({ "verb": "SHOUT" })).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown uACP verb 'SHOUT'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn frame_text_accepts_hex_and_base64() {
+        use base64::Engine;
+        let expected = UacpCodec::decode(&frame_bytes_from_text(INTEROP_HEX).unwrap()).unwrap();
+        assert_eq!(expected.sender_id, "node-1");
+
+        let spaced = format!("0x{} {}", &INTEROP_HEX[..10], &INTEROP_HEX[10..]);
+        assert_eq!(
+            frame_bytes_from_text(&spaced).unwrap(),
+            UacpCodec::encode(&expected)
+        );
+        let b64 = base64::engine::general_purpose::STANDARD.encode(UacpCodec::encode(&expected));
+        assert_eq!(
+            frame_bytes_from_text(&b64).unwrap(),
+            UacpCodec::encode(&expected)
+        );
+        assert!(frame_bytes_from_text("not a frame!").is_err());
+    }
 }
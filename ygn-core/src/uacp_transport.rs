@@ -0,0 +1,223 @@
+//! uACP over TCP.
+//!
+//! Frames are written back to back on the stream and reassembled with
+//! [`UacpDecoder`].  [`UacpServer`] hands every incoming message to a
+//! handler and writes back the reply it returns, if any; [`UacpClient`]
+//! sends messages and matches replies to its ASKs by `in_reply_to`.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::uacp::{UacpCodec, UacpDecoder, UacpMessage};
+
+/// How long [`UacpClient::ask`] waits for a reply by default.
+pub const DEFAULT_ASK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Read `stream` until it closes, passing each decoded message to
+/// `on_message`.  Fails on a corrupt frame.
+async fn read_messages(
+    mut stream: OwnedReadHalf,
+    mut on_message: impl FnMut(UacpMessage),
+) -> anyhow::Result<()> {
+    let mut decoder = UacpDecoder::new();
+    let mut chunk = vec![0u8; 8192];
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        decoder.push(&chunk[..n]);
+        while let Some(msg) = decoder.next_message()? {
+            on_message(msg);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+/// Accepts uACP connections on a TCP listener.
+pub struct UacpServer {
+    listener: TcpListener,
+}
+
+impl UacpServer {
+    pub async fn bind(addr: impl ToSocketAddrs) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .context("binding uACP listener")?;
+        Ok(Self { listener })
+    }
+
+    pub fn local_addr(&self) -> anyhow::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept connections until the task is dropped, calling `handler` for
+    /// every message received.  A message the handler returns is sent back
+    /// on the same connection, with `in_reply_to` set to the request's id
+    /// if the handler left it unset.  Messages on one connection are
+    /// handled concurrently, so replies may arrive out of order.
+    pub async fn serve<F, Fut>(self, handler: F) -> anyhow::Result<()>
+    where
+        F: Fn(UacpMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<UacpMessage>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        loop {
+            let (stream, peer) = self.listener.accept().await?;
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(stream, handler).await {
+                    tracing::debug!(%peer, error = %e, "uACP connection closed");
+                }
+            });
+        }
+    }
+}
+
+async fn serve_connection<F, Fut>(stream: TcpStream, handler: Arc<F>) -> anyhow::Result<()>
+where
+    F: Fn(UacpMessage) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<UacpMessage>> + Send + 'static,
+{
+    let (reader, writer) = stream.into_split();
+    let (replies, outgoing) = mpsc::unbounded_channel();
+    let writer_task = tokio::spawn(write_frames(writer, outgoing));
+
+    let result = read_messages(reader, |msg| {
+        let handler = handler.clone();
+        let replies = replies.clone();
+        tokio::spawn(async move {
+            let request_id = msg.message_id;
+            if let Some(mut reply) = handler(msg).await {
+                reply.in_reply_to.get_or_insert(request_id);
+                let _ = replies.send(UacpCodec::encode(&reply));
+            }
+        });
+    })
+    .await;
+
+    // Let handlers still running finish writing their replies.
+    drop(replies);
+    let _ = writer_task.await;
+    result
+}
+
+async fn write_frames(mut writer: OwnedWriteHalf, mut frames: mpsc::UnboundedReceiver<Vec<u8>>) {
+    while let Some(frame) = frames.recv().await {
+        if writer.write_all(&frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+type Pending = Arc<Mutex<HashMap<u32, oneshot::Sender<UacpMessage>>>>;
+
+/// A connection to a [`UacpServer`].
+pub struct UacpClient {
+    writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    pending: Pending,
+    reader: JoinHandle<()>,
+    timeout: Duration,
+}
+
+impl UacpClient {
+    pub async fn connect(addr: impl ToSocketAddrs) -> anyhow::Result<Self> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .context("connecting to uACP server")?;
+        let (reader, writer) = stream.into_split();
+        let pending: Pending = Arc::default();
+
+        let waiting = pending.clone();
+        let reader = tokio::spawn(async move {
+            let result = read_messages(reader, |msg| {
+                let Some(id) = msg.in_reply_to else {
+                    tracing::debug!(
+                        message_id = msg.message_id,
+                        "ignoring unsolicited uACP message"
+                    );
+                    return;
+                };
+                if let Some(tx) = waiting.lock().unwrap().remove(&id) {
+                    let _ = tx.send(msg);
+                }
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::debug!(error = %e, "uACP client connection failed");
+            }
+            // Fail every outstanding ask.
+            waiting.lock().unwrap().clear();
+        });
+
+        Ok(Self {
+            writer: tokio::sync::Mutex::new(writer),
+            pending,
+            reader,
+            timeout: DEFAULT_ASK_TIMEOUT,
+        })
+    }
+
+    /// How long [`ask`](Self::ask) waits for a reply.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a message without waiting for a reply.
+    pub async fn send(&self, msg: &UacpMessage) -> anyhow::Result<()> {
+        let frame = UacpCodec::encode(msg);
+        self.writer
+            .lock()
+            .await
+            .write_all(&frame)
+            .await
+            .context("sending uACP message")
+    }
+
+    /// Send a message and wait for the reply whose `in_reply_to` is its
+    /// `message_id`.
+    pub async fn ask(&self, msg: &UacpMessage) -> anyhow::Result<UacpMessage> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(msg.message_id, tx);
+        if let Err(e) = self.send(msg).await {
+            self.pending.lock().unwrap().remove(&msg.message_id);
+            return Err(e);
+        }
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => anyhow::bail!("uACP connection closed before a reply arrived"),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&msg.message_id);
+                anyhow::bail!(
+                    "no uACP reply to message {} within {:?}",
+                    msg.message_id,
+                    self.timeout
+                )
+            }
+        }
+    }
+}
+
+impl Drop for UacpClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
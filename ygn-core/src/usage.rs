@@ -0,0 +1,516 @@
+//! Token usage accounting and cost tracking.
+//!
+//! Provides a [`UsageTracker`] that accumulates prompt/completion tokens per
+//! provider, model, and session, estimates cost from per-1k-token pricing,
+//! and persists everything to SQLite so totals survive restarts.  An optional
+//! daily cost limit makes chat requests fail fast once the budget is spent.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{ChatRequest, ChatResponse, Provider, TokenUsage};
+use crate::schema::{self, Migration};
+
+/// Schema history of the `usage` table.
+const MIGRATIONS: &[Migration] = &[Migration::sql(
+    1,
+    "create usage",
+    "CREATE TABLE IF NOT EXISTS usage (
+        id                INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider          TEXT NOT NULL,
+        model             TEXT NOT NULL,
+        session_id        TEXT,
+        prompt_tokens     INTEGER NOT NULL,
+        completion_tokens INTEGER NOT NULL,
+        cost              REAL NOT NULL,
+        day               TEXT NOT NULL,
+        created_at        TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_usage_day ON usage(day);
+    CREATE INDEX IF NOT EXISTS idx_usage_session ON usage(session_id);",
+)];
+
+/// Version of the `usage` schema this build creates.
+pub const SCHEMA_VERSION: u32 = schema::latest(MIGRATIONS);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Share of the prompt price charged for prompt cache reads when
+/// [`ModelPricing::cache_read_per_1k`] is unset.
+pub const DEFAULT_CACHE_READ_FACTOR: f64 = 0.1;
+
+/// Share of the prompt price charged for prompt cache writes when
+/// [`ModelPricing::cache_write_per_1k`] is unset.
+pub const DEFAULT_CACHE_WRITE_FACTOR: f64 = 1.25;
+
+/// Price per 1,000 tokens for a provider or model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ModelPricing {
+    /// Cost per 1k prompt (input) tokens.
+    pub prompt_per_1k: f64,
+    /// Cost per 1k completion (output) tokens.
+    pub completion_per_1k: f64,
+    /// Cost per 1k input tokens read from a prompt cache.  Defaults to a
+    /// tenth of `prompt_per_1k`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_per_1k: Option<f64>,
+    /// Cost per 1k input tokens written to a prompt cache.  Defaults to
+    /// 1.25 times `prompt_per_1k`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_per_1k: Option<f64>,
+}
+
+/// Pricing table and budget for usage tracking.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Pricing keyed by model name or provider name. Model names take
+    /// precedence; unknown models are priced at zero.
+    pub pricing: HashMap<String, ModelPricing>,
+    /// Maximum estimated cost per UTC day. `None` means unlimited.
+    pub daily_cost_limit: Option<f64>,
+}
+
+/// Accumulated token counts and estimated cost.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_cost: f64,
+}
+
+/// Usage totals for a single UTC day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// Day in `YYYY-MM-DD` format.
+    pub date: String,
+    pub totals: UsageTotals,
+}
+
+/// Default on-disk location of the usage database (`~/.ygn/usage.db`).
+pub fn default_db_path() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.ygn/usage.db")
+}
+
+// ---------------------------------------------------------------------------
+// UsageTracker
+// ---------------------------------------------------------------------------
+
+/// Persistent token usage tracker backed by SQLite.
+pub struct UsageTracker {
+    conn: Mutex<Connection>,
+    config: UsageConfig,
+}
+
+impl std::fmt::Debug for UsageTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsageTracker")
+            .field("daily_cost_limit", &self.config.daily_cost_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl UsageTracker {
+    /// Open (or create) a file-based usage store.
+    pub fn new(path: &str, config: UsageConfig) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open(path)?, config)
+    }
+
+    /// Create an in-memory usage store (useful for testing).
+    pub fn in_memory(config: UsageConfig) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, config)
+    }
+
+    /// Open the store at [`default_db_path`], falling back to an in-memory
+    /// one, which still enforces the budget, if it is unusable.
+    pub fn open_default(config: UsageConfig) -> Self {
+        let path = default_db_path();
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        Self::open_or_in_memory(&path, config)
+    }
+
+    /// Open the store at `path`, falling back to an in-memory one like
+    /// [`open_default`](Self::open_default).
+    pub fn open_or_in_memory(path: &str, config: UsageConfig) -> Self {
+        match Self::new(path, config.clone()) {
+            Ok(tracker) => tracker,
+            Err(e) => {
+                tracing::warn!(error = %e, path = %path, "usage store unavailable; tracking in memory");
+                Self::in_memory(config).expect("in-memory SQLite")
+            }
+        }
+    }
+
+    fn with_connection(mut conn: Connection, config: UsageConfig) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
+        schema::migrate(&mut conn, MIGRATIONS)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config,
+        })
+    }
+
+    /// The active pricing/budget configuration.
+    pub fn config(&self) -> &UsageConfig {
+        &self.config
+    }
+
+    /// Estimate the cost of `usage` for the given provider and model,
+    /// including prompt cache reads and writes at their own rates.
+    pub fn estimate_cost(&self, provider: &str, model: &str, usage: &TokenUsage) -> f64 {
+        let pricing = self
+            .config
+            .pricing
+            .get(model)
+            .or_else(|| self.config.pricing.get(provider))
+            .copied()
+            .unwrap_or_default();
+        let cache_read_per_1k = pricing
+            .cache_read_per_1k
+            .unwrap_or(pricing.prompt_per_1k * DEFAULT_CACHE_READ_FACTOR);
+        let cache_write_per_1k = pricing
+            .cache_write_per_1k
+            .unwrap_or(pricing.prompt_per_1k * DEFAULT_CACHE_WRITE_FACTOR);
+        let cost = |tokens: u32, per_1k: f64| (tokens as f64 / 1000.0) * per_1k;
+        cost(usage.prompt_tokens, pricing.prompt_per_1k)
+            + cost(usage.completion_tokens, pricing.completion_per_1k)
+            + cost(usage.cache_read_tokens.unwrap_or(0), cache_read_per_1k)
+            + cost(usage.cache_creation_tokens.unwrap_or(0), cache_write_per_1k)
+    }
+
+    /// Record token usage for a single provider call. Returns the estimated
+    /// cost of the call.
+    pub fn record(
+        &self,
+        provider: &str,
+        model: &str,
+        session_id: Option<&str>,
+        usage: &TokenUsage,
+    ) -> anyhow::Result<f64> {
+        let cost = self.estimate_cost(provider, model, usage);
+        let now = Utc::now();
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        conn.execute(
+            "INSERT INTO usage (provider, model, session_id, prompt_tokens, completion_tokens, cost, day, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                provider,
+                model,
+                session_id,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                cost,
+                now.format("%Y-%m-%d").to_string(),
+                now.to_rfc3339(),
+            ],
+        )?;
+        Ok(cost)
+    }
+
+    /// Totals across all recorded usage.
+    pub fn totals(&self) -> anyhow::Result<UsageTotals> {
+        self.query_totals("", &[])
+    }
+
+    /// Totals for a single session.
+    pub fn session_totals(&self, session_id: &str) -> anyhow::Result<UsageTotals> {
+        self.query_totals(" WHERE session_id = ?1", &[session_id])
+    }
+
+    /// Totals for the current UTC day.
+    pub fn today_totals(&self) -> anyhow::Result<UsageTotals> {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        self.query_totals(" WHERE day = ?1", &[&today])
+    }
+
+    /// Totals grouped by provider name.
+    pub fn totals_by_provider(&self) -> anyhow::Result<Vec<(String, UsageTotals)>> {
+        self.query_grouped("provider")
+    }
+
+    /// Totals grouped by model name.
+    pub fn totals_by_model(&self) -> anyhow::Result<Vec<(String, UsageTotals)>> {
+        self.query_grouped("model")
+    }
+
+    /// Per-day totals, oldest first.
+    pub fn daily_breakdown(&self) -> anyhow::Result<Vec<DailyUsage>> {
+        Ok(self
+            .query_grouped("day")?
+            .into_iter()
+            .map(|(date, totals)| DailyUsage { date, totals })
+            .collect())
+    }
+
+    /// Fail if today's estimated cost has reached the configured daily limit.
+    pub fn check_budget(&self) -> anyhow::Result<()> {
+        if let Some(limit) = self.config.daily_cost_limit {
+            let spent = self.today_totals()?.estimated_cost;
+            if spent >= limit {
+                anyhow::bail!(
+                    "daily cost budget exceeded: spent {spent:.4} of {limit:.4} limit; \
+                     refusing to call provider"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a chat request through `provider`, enforcing the daily budget
+    /// before the call and recording token usage after it.
+    pub async fn chat(
+        &self,
+        provider: &dyn Provider,
+        request: ChatRequest,
+        session_id: Option<&str>,
+    ) -> anyhow::Result<ChatResponse> {
+        self.check_budget()?;
+        let model = request.model.clone();
+        let response = provider.chat(request).await?;
+        if let Some(ref usage) = response.usage {
+            self.record(provider.name(), &model, session_id, usage)?;
+        }
+        Ok(response)
+    }
+
+    // -- query helpers -----------------------------------------------------
+
+    fn query_totals(&self, where_clause: &str, args: &[&str]) -> anyhow::Result<UsageTotals> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let sql = format!(
+            "SELECT COUNT(*), COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0), \
+             COALESCE(SUM(cost), 0.0) FROM usage{where_clause}"
+        );
+        let totals = conn.query_row(&sql, rusqlite::params_from_iter(args.iter()), |row| {
+            Ok(UsageTotals {
+                requests: row.get::<_, i64>(0)? as u64,
+                prompt_tokens: row.get::<_, i64>(1)? as u64,
+                completion_tokens: row.get::<_, i64>(2)? as u64,
+                estimated_cost: row.get(3)?,
+            })
+        })?;
+        Ok(totals)
+    }
+
+    fn query_grouped(&self, column: &str) -> anyhow::Result<Vec<(String, UsageTotals)>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("{e}"))?;
+        let sql = format!(
+            "SELECT {column}, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(cost) \
+             FROM usage GROUP BY {column} ORDER BY {column}"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                UsageTotals {
+                    requests: row.get::<_, i64>(1)? as u64,
+                    prompt_tokens: row.get::<_, i64>(2)? as u64,
+                    completion_tokens: row.get::<_, i64>(3)? as u64,
+                    estimated_cost: row.get(4)?,
+                },
+            ))
+        })?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{ChatMessage, ChatRole, StubProvider};
+
+    fn priced_config() -> UsageConfig {
+        let mut pricing = HashMap::new();
+        pricing.insert(
+            "claude-sonnet".to_string(),
+            ModelPricing {
+                prompt_per_1k: 3.0,
+                completion_per_1k: 15.0,
+                ..Default::default()
+            },
+        );
+        pricing.insert(
+            "openai".to_string(),
+            ModelPricing {
+                prompt_per_1k: 1.0,
+                completion_per_1k: 2.0,
+                ..Default::default()
+            },
+        );
+        UsageConfig {
+            pricing,
+            daily_cost_limit: None,
+        }
+    }
+
+    fn usage(prompt: u32, completion: u32) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            ..Default::default()
+        }
+    }
+
+    fn sample_request() -> ChatRequest {
+        ChatRequest {
+            model: "stub".to_string(),
+            messages: vec![ChatMessage {
+                role: ChatRole::User,
+                content: "Hi".into(),
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            }],
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            stop: None,
+            top_p: None,
+            seed: None,
+            candidate_count: None,
+        }
+    }
+
+    #[test]
+    fn cost_uses_model_then_provider_pricing() {
+        let tracker = UsageTracker::in_memory(priced_config()).unwrap();
+        let cost = tracker.estimate_cost("claude", "claude-sonnet", &usage(1000, 1000));
+        assert!((cost - 18.0).abs() < 1e-9);
+        let cost = tracker.estimate_cost("openai", "gpt-4o", &usage(2000, 500));
+        assert!((cost - 3.0).abs() < 1e-9);
+        let cost = tracker.estimate_cost("ollama", "llama3", &usage(5000, 5000));
+        assert_eq!(cost, 0.0);
+    }
+
+    #[test]
+    fn cache_tokens_are_priced_at_cache_rates() {
+        let tracker = UsageTracker::in_memory(priced_config()).unwrap();
+        let cached = TokenUsage {
+            prompt_tokens: 1000,
+            completion_tokens: 0,
+            cache_creation_tokens: Some(1000),
+            cache_read_tokens: Some(10_000),
+        };
+        // 3.0 full price, 3.75 to write the cache, 10 x 0.3 to read it.
+        let cost = tracker.estimate_cost("claude", "claude-sonnet", &cached);
+        assert!((cost - 9.75).abs() < 1e-9, "{cost}");
+
+        let mut config = priced_config();
+        let pricing = config.pricing.get_mut("claude-sonnet").unwrap();
+        pricing.cache_read_per_1k = Some(0.5);
+        pricing.cache_write_per_1k = Some(0.0);
+        let tracker = UsageTracker::in_memory(config).unwrap();
+        let cost = tracker.estimate_cost("claude", "claude-sonnet", &cached);
+        assert!((cost - 8.0).abs() < 1e-9, "{cost}");
+    }
+
+    #[test]
+    fn accumulates_by_provider_model_and_session() {
+        let tracker = UsageTracker::in_memory(priced_config()).unwrap();
+        tracker
+            .record("claude", "claude-sonnet", Some("s1"), &usage(100, 50))
+            .unwrap();
+        tracker
+            .record("claude", "claude-sonnet", Some("s2"), &usage(200, 100))
+            .unwrap();
+        tracker
+            .record("openai", "gpt-4o", Some("s1"), &usage(10, 5))
+            .unwrap();
+
+        let totals = tracker.totals().unwrap();
+        assert_eq!(totals.requests, 3);
+        assert_eq!(totals.prompt_tokens, 310);
+        assert_eq!(totals.completion_tokens, 155);
+
+        let s1 = tracker.session_totals("s1").unwrap();
+        assert_eq!(s1.requests, 2);
+        assert_eq!(s1.prompt_tokens, 110);
+
+        let by_provider = tracker.totals_by_provider().unwrap();
+        assert_eq!(by_provider.len(), 2);
+        assert_eq!(by_provider[0].0, "claude");
+        assert_eq!(by_provider[0].1.prompt_tokens, 300);
+
+        let by_model = tracker.totals_by_model().unwrap();
+        assert_eq!(by_model.len(), 2);
+
+        let daily = tracker.daily_breakdown().unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].totals.requests, 3);
+    }
+
+    #[test]
+    fn persists_across_reopen() {
+        let path = std::env::temp_dir().join(format!("ygn-usage-{}.db", uuid::Uuid::new_v4()));
+        let path_str = path.to_str().unwrap();
+        {
+            let tracker = UsageTracker::new(path_str, priced_config()).unwrap();
+            tracker
+                .record("claude", "claude-sonnet", None, &usage(1000, 0))
+                .unwrap();
+        }
+        let reopened = UsageTracker::new(path_str, priced_config()).unwrap();
+        let totals = reopened.totals().unwrap();
+        assert_eq!(totals.requests, 1);
+        assert_eq!(totals.prompt_tokens, 1000);
+        assert!((totals.estimated_cost - 3.0).abs() < 1e-9);
+        drop(reopened);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn chat_records_usage() {
+        let tracker = UsageTracker::in_memory(UsageConfig::default()).unwrap();
+        let provider = StubProvider::default();
+        tracker
+            .chat(&provider, sample_request(), Some("sess"))
+            .await
+            .unwrap();
+        assert_eq!(tracker.session_totals("sess").unwrap().requests, 1);
+        assert_eq!(tracker.totals_by_provider().unwrap()[0].0, "stub");
+    }
+
+    #[tokio::test]
+    async fn budget_cutoff_fails_fast() {
+        let mut config = priced_config();
+        config.daily_cost_limit = Some(10.0);
+        let tracker = UsageTracker::in_memory(config).unwrap();
+
+        // Under budget: the call goes through.
+        tracker
+            .record("claude", "claude-sonnet", None, &usage(1000, 0))
+            .unwrap();
+        assert!(tracker.check_budget().is_ok());
+
+        // Push past the limit: 3.0 + 15.0 > 10.0.
+        tracker
+            .record("claude", "claude-sonnet", None, &usage(0, 1000))
+            .unwrap();
+        let err = tracker
+            .chat(&StubProvider::default(), sample_request(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("budget exceeded"));
+        // The rejected call must not have been recorded.
+        assert_eq!(tracker.totals().unwrap().requests, 2);
+    }
+}
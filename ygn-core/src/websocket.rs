@@ -0,0 +1,385 @@
+//! Local WebSocket channel.
+//!
+//! Provides a Channel implementation for browser UIs and local testing.
+//! The gateway mounts it at `GET /ws`: each text frame from a client becomes
+//! a [`ChannelMessage`], and [`Channel::send`] pushes text frames back to one
+//! client (by connection id) or broadcasts to all of them.
+//!
+//! Both directions are bounded. Inbound frames that arrive while
+//! [`INBOUND_CAPACITY`] messages are already waiting for [`Channel::listen`]
+//! are dropped, and a client whose outbound queue reaches
+//! [`OUTBOUND_CAPACITY`] is disconnected rather than buffered without limit.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+
+use crate::channel::{Channel, ChannelMessage, SendMessage};
+
+/// Query parameter carrying a client's connection id.
+pub const CLIENT_ID_QUERY: &str = "client_id";
+
+/// Header carrying a client's connection id, used when the query parameter
+/// is absent.
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Received messages buffered for [`Channel::listen`] before new frames are
+/// dropped.
+pub const INBOUND_CAPACITY: usize = 256;
+
+/// Messages queued for one client before it is considered too slow and
+/// disconnected.
+pub const OUTBOUND_CAPACITY: usize = 64;
+
+/// A connected client: the queue feeding its socket, tagged with a sequence
+/// number so a reconnect under the same id is not removed by the old socket.
+struct Client {
+    seq: u64,
+    outbound: mpsc::Sender<String>,
+}
+
+struct Inner {
+    clients: Mutex<HashMap<String, Client>>,
+    next_seq: AtomicU64,
+    inbound_tx: mpsc::Sender<ChannelMessage>,
+    inbound_rx: tokio::sync::Mutex<mpsc::Receiver<ChannelMessage>>,
+}
+
+// ---------------------------------------------------------------------------
+// WebSocketChannel
+// ---------------------------------------------------------------------------
+
+/// A `Channel` whose peers are WebSocket clients of the gateway.
+///
+/// Cloning is cheap and every clone shares the same connections, so the
+/// gateway can accept sockets while the runtime listens and sends.
+#[derive(Clone)]
+pub struct WebSocketChannel {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for WebSocketChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketChannel")
+            .field("clients", &self.connected_clients())
+            .finish()
+    }
+}
+
+impl Default for WebSocketChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebSocketChannel {
+    /// Create a channel with no connected clients.
+    pub fn new() -> Self {
+        let (inbound_tx, inbound_rx) = mpsc::channel(INBOUND_CAPACITY);
+        Self {
+            inner: Arc::new(Inner {
+                clients: Mutex::new(HashMap::new()),
+                next_seq: AtomicU64::new(0),
+                inbound_tx,
+                inbound_rx: tokio::sync::Mutex::new(inbound_rx),
+            }),
+        }
+    }
+
+    /// Ids of the currently connected clients, sorted.
+    pub fn connected_clients(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .inner
+            .clients
+            .lock()
+            .map(|c| c.keys().cloned().collect())
+            .unwrap_or_default();
+        ids.sort();
+        ids
+    }
+
+    /// Resolve a connection id from the `client_id` query parameter, then
+    /// the `x-client-id` header, falling back to a random UUID.
+    pub fn client_id(query: &HashMap<String, String>, headers: &HeaderMap) -> String {
+        query
+            .get(CLIENT_ID_QUERY)
+            .cloned()
+            .or_else(|| {
+                headers
+                    .get(CLIENT_ID_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            })
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Complete a WebSocket upgrade and serve the socket as `client_id`.
+    ///
+    /// An id that is already connected is rejected with `409 Conflict`, so
+    /// one client cannot take over another's replies.
+    pub fn accept(&self, ws: WebSocketUpgrade, client_id: String) -> Response {
+        if self.connected_clients().contains(&client_id) {
+            tracing::warn!(client_id = %client_id, "rejecting duplicate websocket client id");
+            return (
+                StatusCode::CONFLICT,
+                format!("WebSocket client already connected: {client_id}"),
+            )
+                .into_response();
+        }
+        let channel = self.clone();
+        ws.on_upgrade(move |socket| async move { channel.serve(socket, client_id).await })
+    }
+
+    /// Register a client, unless its id is already taken. Returns the
+    /// registration's sequence number.
+    fn register(&self, client_id: &str, outbound: mpsc::Sender<String>) -> Option<u64> {
+        let mut clients = self.inner.clients.lock().ok()?;
+        if clients.contains_key(client_id) {
+            return None;
+        }
+        let seq = self.inner.next_seq.fetch_add(1, Ordering::Relaxed);
+        clients.insert(client_id.to_string(), Client { seq, outbound });
+        Some(seq)
+    }
+
+    /// Queue a received message for [`Channel::listen`], dropping it if the
+    /// queue is full. Returns whether it was queued.
+    fn push_inbound(&self, message: ChannelMessage) -> bool {
+        match self.inner.inbound_tx.try_send(message) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(message)) => {
+                tracing::warn!(client_id = %message.sender, "websocket inbound queue full; dropping message");
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
+    async fn serve(self, socket: WebSocket, client_id: String) {
+        let (outbound, mut queue) = mpsc::channel(OUTBOUND_CAPACITY);
+        // Re-checked here because two upgrades for one id can race past
+        // `accept`.
+        let Some(seq) = self.register(&client_id, outbound) else {
+            tracing::warn!(client_id = %client_id, "closing duplicate websocket client id");
+            return;
+        };
+        tracing::debug!(client_id = %client_id, "websocket client connected");
+
+        let (mut sink, mut stream) = socket.split();
+        loop {
+            tokio::select! {
+                out = queue.recv() => {
+                    let Some(text) = out else { break };
+                    if sink.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                frame = stream.next() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        self.push_inbound(ChannelMessage {
+                            channel: "websocket".to_string(),
+                            sender: client_id.clone(),
+                            content: text.to_string(),
+                            timestamp: Utc::now(),
+                            metadata: serde_json::json!({ "connection_id": client_id }),
+                        });
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    // Pings are answered by the protocol layer; binary
+                    // frames are not part of this channel.
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+
+        if let Ok(mut clients) = self.inner.clients.lock() {
+            if clients.get(&client_id).is_some_and(|c| c.seq == seq) {
+                clients.remove(&client_id);
+            }
+        }
+        tracing::debug!(client_id = %client_id, "websocket client disconnected");
+    }
+}
+
+#[async_trait]
+impl Channel for WebSocketChannel {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    /// Deliver to `recipient` when set (erroring if it is not connected),
+    /// otherwise broadcast to every connected client.
+    ///
+    /// A client whose outbound queue is full is disconnected; a targeted
+    /// send to it errors.
+    async fn send(&self, message: SendMessage) -> anyhow::Result<()> {
+        let mut clients = self
+            .inner
+            .clients
+            .lock()
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        let targets: Vec<String> = match message.recipient {
+            Some(ref id) if clients.contains_key(id) => vec![id.clone()],
+            Some(ref id) => anyhow::bail!("WebSocket client not connected: {id}"),
+            None => clients.keys().cloned().collect(),
+        };
+
+        for id in targets {
+            let error = match clients[&id].outbound.try_send(message.content.clone()) {
+                Ok(()) => continue,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!(client_id = %id, "websocket client too slow; disconnecting");
+                    format!("WebSocket client {id} disconnected: outbound queue full")
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    format!("WebSocket client not connected: {id}")
+                }
+            };
+            // Dropping the sender ends the client's serve loop.
+            clients.remove(&id);
+            if message.recipient.is_some() {
+                anyhow::bail!(error);
+            }
+        }
+        Ok(())
+    }
+
+    async fn listen(&self) -> anyhow::Result<Option<ChannelMessage>> {
+        Ok(self.inner.inbound_rx.lock().await.recv().await)
+    }
+
+    async fn health_check(&self) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_id_prefers_query_then_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_ID_HEADER, "from-header".parse().unwrap());
+        let query = HashMap::from([(CLIENT_ID_QUERY.to_string(), "from-query".to_string())]);
+
+        assert_eq!(WebSocketChannel::client_id(&query, &headers), "from-query");
+        assert_eq!(
+            WebSocketChannel::client_id(&HashMap::new(), &headers),
+            "from-header"
+        );
+        let generated = WebSocketChannel::client_id(&HashMap::new(), &HeaderMap::new());
+        assert!(uuid::Uuid::parse_str(&generated).is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_to_unknown_client_errors() {
+        let ch = WebSocketChannel::new();
+        let err = ch
+            .send(SendMessage {
+                content: "hi".to_string(),
+                recipient: Some("ghost".to_string()),
+                metadata: serde_json::json!({}),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ghost"));
+    }
+
+    fn text(content: &str, recipient: Option<&str>) -> SendMessage {
+        SendMessage {
+            content: content.to_string(),
+            recipient: recipient.map(String::from),
+            metadata: serde_json::json!({}),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_client_is_disconnected_on_overflow() {
+        let ch = WebSocketChannel::new();
+        let (slow_tx, _slow_queue) = mpsc::channel(OUTBOUND_CAPACITY);
+        let (fast_tx, mut fast_queue) = mpsc::channel(OUTBOUND_CAPACITY);
+        ch.register("slow", slow_tx).unwrap();
+        ch.register("fast", fast_tx).unwrap();
+
+        for i in 0..OUTBOUND_CAPACITY {
+            ch.send(text(&i.to_string(), None)).await.unwrap();
+            fast_queue.recv().await.unwrap();
+        }
+        assert_eq!(ch.connected_clients(), vec!["fast", "slow"]);
+
+        // The next broadcast overflows the slow client only.
+        ch.send(text("more", None)).await.unwrap();
+        assert_eq!(ch.connected_clients(), vec!["fast"]);
+        assert_eq!(fast_queue.recv().await.unwrap(), "more");
+    }
+
+    #[tokio::test]
+    async fn targeted_send_to_full_client_errors() {
+        let ch = WebSocketChannel::new();
+        let (tx, _queue) = mpsc::channel(1);
+        ch.register("slow", tx).unwrap();
+        ch.send(text("one", Some("slow"))).await.unwrap();
+        let err = ch.send(text("two", Some("slow"))).await.unwrap_err();
+        assert!(err.to_string().contains("outbound queue full"), "{err}");
+        assert!(ch.connected_clients().is_empty());
+    }
+
+    #[test]
+    fn duplicate_client_id_is_not_registered() {
+        let ch = WebSocketChannel::new();
+        let (first, _q1) = mpsc::channel(1);
+        let (second, _q2) = mpsc::channel(1);
+        assert!(ch.register("alice", first).is_some());
+        assert!(ch.register("alice", second).is_none());
+    }
+
+    #[tokio::test]
+    async fn inbound_overflow_drops_messages() {
+        let ch = WebSocketChannel::new();
+        let message = |content: &str| ChannelMessage {
+            channel: "websocket".to_string(),
+            sender: "alice".to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            metadata: serde_json::json!({}),
+        };
+        for i in 0..INBOUND_CAPACITY {
+            assert!(ch.push_inbound(message(&i.to_string())));
+        }
+        assert!(!ch.push_inbound(message("dropped")));
+
+        assert_eq!(ch.listen().await.unwrap().unwrap().content, "0");
+        assert!(ch.push_inbound(message("fits again")));
+    }
+
+    #[tokio::test]
+    async fn broadcast_with_no_clients_is_ok() {
+        let ch = WebSocketChannel::new();
+        assert_eq!(ch.name(), "websocket");
+        assert!(ch.connected_clients().is_empty());
+        ch.send(SendMessage {
+            content: "hi".to_string(),
+            recipient: None,
+            metadata: serde_json::json!({}),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+//! One gateway delegates a task to another over A2A with `ask_agent`.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use ygn_core::a2a_client::{A2aClient, AskAgentTool};
+use ygn_core::auth::ApiKeyAuth;
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState};
+use ygn_core::sandbox::{ProcessSandbox, SandboxProfile};
+use ygn_core::tool::{EchoTool, ToolRegistry};
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-a2a-client-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+fn echo_and_ask_agent() -> ToolRegistry {
+    let mut sandbox = ProcessSandbox::new(SandboxProfile::Net);
+    sandbox.allow_domain("127.0.0.1");
+    let mut tools = ToolRegistry::new();
+    tools.register(Box::new(EchoTool));
+    tools.register(Box::new(AskAgentTool::new(Box::new(sandbox))));
+    tools
+}
+
+/// Serve a gateway offering `tools` over MCP and return its base URL.
+async fn serve(tools: fn() -> ToolRegistry) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = build_router_with_state(AppState {
+        auth: Arc::new(ApiKeyAuth::default()),
+        mcp_tools: tools,
+        ..test_state()
+    });
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn gateway_asks_peer_gateway_and_gets_artifact() {
+    let peer = serve(ToolRegistry::new).await;
+    let asker = serve(echo_and_ask_agent).await;
+
+    let response: Value = reqwest::Client::new()
+        .post(format!("{asker}/mcp"))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {
+                "name": "ask_agent",
+                "arguments": { "url": peer, "message": "summarize the audit log" }
+            }
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let text = response["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap_or_else(|| panic!("{response}"));
+    let output: Value = serde_json::from_str(text).unwrap();
+    assert_eq!(output["agent"], "Y-GN");
+    assert_eq!(output["status"], "completed");
+    assert_eq!(output["result"], "Processed: summarize the audit log");
+
+    // The task is recorded on the peer.
+    let client = A2aClient::new(&peer).unwrap();
+    let card = client.fetch_card().await.unwrap();
+    let task_id = output["task_id"].as_str().unwrap();
+    let task = client.get_task(&card, task_id).await.unwrap();
+    assert_eq!(task.message, "summarize the audit log");
+}
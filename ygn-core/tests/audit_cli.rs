@@ -0,0 +1,96 @@
+//! CLI tests for `ygn-core audit export`.
+
+use assert_cmd::Command;
+use serde_json::json;
+use ygn_core::audit::{AuditEntry, AuditEventType, AuditLog};
+
+/// An audit file with a granted `echo` call and a denied `shell` call an
+/// hour later.
+fn audit_file(dir: &std::path::Path) -> std::path::PathBuf {
+    let entry = |time: &str, event_type, tool: &str, decision: &str, risk: &str| AuditEntry {
+        timestamp: time.parse().unwrap(),
+        ..AuditEntry::now(
+            event_type,
+            tool,
+            decision,
+            risk,
+            json!({ "reason": "cmd=rm | blocked" }),
+        )
+    };
+    let mut log = AuditLog::new();
+    log.record(entry(
+        "2026-03-01T11:00:00Z",
+        AuditEventType::AccessGranted,
+        "echo",
+        "Allow",
+        "Low",
+    ));
+    log.record(entry(
+        "2026-03-01T12:00:00Z",
+        AuditEventType::AccessDenied,
+        "shell",
+        "Deny",
+        "High",
+    ));
+    let path = dir.join("audit.jsonl");
+    log.export_jsonl(std::fs::File::create(&path).unwrap())
+        .unwrap();
+    path
+}
+
+#[test]
+fn exports_filtered_cef_to_a_file() {
+    let dir = std::env::temp_dir().join(format!("ygn-audit-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = audit_file(&dir);
+    let out = dir.join("audit.cef");
+
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .arg("audit")
+        .arg("export")
+        .args(["--format", "cef", "--since", "2026-03-01T11:30:00Z"])
+        .arg("--log")
+        .arg(&log)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+
+    let cef = std::fs::read_to_string(&out).unwrap();
+    assert_eq!(
+        cef,
+        concat!(
+            "CEF:0|Y-GN|ygn-core|",
+            env!("CARGO_PKG_VERSION"),
+            "|AccessDenied|Deny|8|rt=1772366400000 act=Deny cs1Label=tool cs1=shell ",
+            "reason=cmd\\=rm | blocked\n"
+        )
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn exports_jsonl_to_stdout_by_event_type() {
+    let dir = std::env::temp_dir().join(format!("ygn-audit-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = audit_file(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .args(["audit", "export", "--event-type", "access_granted", "--log"])
+        .arg(&log)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let exported = AuditLog::read_jsonl(output.as_slice()).unwrap();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported.entries()[0].tool_name, "echo");
+
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .args(["audit", "export", "--since", "yesterday", "--log"])
+        .arg(&log)
+        .assert()
+        .failure();
+    std::fs::remove_dir_all(&dir).ok();
+}
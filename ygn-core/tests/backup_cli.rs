@@ -0,0 +1,61 @@
+//! CLI tests for `ygn-core backup create` and `backup restore`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use ygn_core::memory::{Memory, MemoryCategory};
+use ygn_core::sqlite_memory::SqliteMemory;
+
+fn ygn(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env("HOME", home).env_remove("USERPROFILE");
+    cmd
+}
+
+#[tokio::test]
+async fn backup_and_restore_memory() {
+    let home = std::env::temp_dir().join(format!("ygn-backup-cli-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(home.join(".ygn")).unwrap();
+    let db = home.join(".ygn/memory.db");
+    let out = home.join("snapshots");
+
+    let memory = SqliteMemory::new(db.to_str().unwrap()).unwrap();
+    memory
+        .store(MemoryCategory::Core, "motto", "before")
+        .await
+        .unwrap();
+
+    ygn(&home)
+        .args(["backup", "create", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Backed up 1 stores"))
+        .stdout(predicate::str::contains("- memory -> memory-"));
+    assert!(out.join("manifest.json").is_file());
+
+    memory
+        .store(MemoryCategory::Core, "motto", "after")
+        .await
+        .unwrap();
+    drop(memory);
+
+    ygn(&home)
+        .args(["backup", "restore", "--only", "memory", "--from"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 stores: memory"));
+
+    let memory = SqliteMemory::new(db.to_str().unwrap()).unwrap();
+    let entry = memory.get(MemoryCategory::Core, "motto").await.unwrap();
+    assert_eq!(entry.unwrap().content, "before");
+
+    ygn(&home)
+        .args(["backup", "restore", "--only", "nope", "--from"])
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no 'nope' store"));
+
+    let _ = std::fs::remove_dir_all(&home);
+}
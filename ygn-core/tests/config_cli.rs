@@ -0,0 +1,115 @@
+//! CLI tests for `ygn-core config`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/config")
+        .join(name)
+}
+
+#[test]
+fn good_config_validates() {
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .args(["config", "validate"])
+        .arg(fixture("good.toml"))
+        .assert()
+        .success()
+        .stdout("OK\n");
+}
+
+#[test]
+fn bad_trust_tier_is_reported_by_pointer() {
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .args(["config", "validate"])
+        .arg(fixture("bad_trust_tier.toml"))
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("/trust_tier: "))
+        .stdout(predicate::str::contains("/auth/keys/0/trust_tier: "))
+        .stdout(predicate::str::contains("OK").not());
+}
+
+#[test]
+fn semantic_errors_are_reported_as_configuration_errors() {
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env_remove("YGN_GATEWAY_BIND")
+        .args(["config", "validate"])
+        .arg(fixture("bad_semantics.toml"))
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "ConfigurationError: /gateway_bind: invalid gateway_bind 'localhost': expected host:port",
+        ))
+        .stdout(predicate::str::contains(
+            "ConfigurationError: /policy_file: policy file ",
+        ))
+        .stderr(predicate::str::contains(
+            "hint: Review and correct the configuration file",
+        ));
+}
+
+#[test]
+fn schema_output_is_deterministic() {
+    let run = || {
+        Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+            .args(["config", "schema"])
+            .output()
+            .unwrap()
+            .stdout
+    };
+    let first = run();
+    assert!(!first.is_empty());
+    assert_eq!(first, run());
+}
+
+fn temp_config(text: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ygn-config-cli-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("node.toml");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn config_flag_selects_the_config_file() {
+    let path = temp_config("node_role = \"core\"\ntrust_tier = \"untrusted\"\n");
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env_remove("YGN_NODE_ROLE")
+        .env_remove("YGN_TRUST_TIER")
+        .arg("--config")
+        .arg(&path)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("node_role: core"))
+        .stdout(predicate::str::contains("trust_tier: untrusted"));
+}
+
+#[test]
+fn env_overrides_the_config_file() {
+    let path = temp_config("node_role = \"core\"\n");
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env("YGN_NODE_ROLE", "brain-proxy")
+        .args(["status", "--config"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("node_role: brain-proxy"));
+}
+
+#[test]
+fn invalid_node_role_in_config_flag_fails() {
+    let path = temp_config("node_role = \"satellite\"\n");
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env_remove("YGN_NODE_ROLE")
+        .arg("--config")
+        .arg(&path)
+        .arg("status")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid node_role 'satellite': expected one of edge, core, brain, brain-proxy",
+        ));
+}
@@ -0,0 +1,39 @@
+-- A memory database as created before schema migrations existed.
+CREATE TABLE memories (
+    id         TEXT PRIMARY KEY,
+    key        TEXT NOT NULL,
+    content    TEXT NOT NULL,
+    category   TEXT NOT NULL,
+    session_id TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    embedding  BLOB
+);
+
+CREATE VIRTUAL TABLE memories_fts
+    USING fts5(key, content, content=memories, content_rowid=rowid);
+
+CREATE TRIGGER memories_ai AFTER INSERT ON memories BEGIN
+    INSERT INTO memories_fts(rowid, key, content)
+    VALUES (new.rowid, new.key, new.content);
+END;
+
+CREATE TRIGGER memories_ad AFTER DELETE ON memories BEGIN
+    INSERT INTO memories_fts(memories_fts, rowid, key, content)
+    VALUES ('delete', old.rowid, old.key, old.content);
+END;
+
+CREATE TRIGGER memories_au AFTER UPDATE ON memories BEGIN
+    INSERT INTO memories_fts(memories_fts, rowid, key, content)
+    VALUES ('delete', old.rowid, old.key, old.content);
+    INSERT INTO memories_fts(rowid, key, content)
+    VALUES (new.rowid, new.key, new.content);
+END;
+
+INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at)
+VALUES ('m-1', 'language', 'Rust is the language of the data plane', 'core', NULL,
+        '2025-01-01T00:00:00+00:00', '2025-01-02T00:00:00+00:00');
+
+INSERT INTO memories (id, key, content, category, session_id, created_at, updated_at)
+VALUES ('m-2', 'standup', 'Ship the gateway', 'daily', '',
+        '2025-01-03T00:00:00+00:00', '2025-01-03T00:00:00+00:00');
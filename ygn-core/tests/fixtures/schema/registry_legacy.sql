@@ -0,0 +1,14 @@
+-- A registry database from before nodes carried metadata.
+CREATE TABLE nodes (
+    node_id      TEXT PRIMARY KEY,
+    role         TEXT NOT NULL,
+    trust_tier   TEXT NOT NULL,
+    endpoints    TEXT NOT NULL,
+    capabilities TEXT NOT NULL,
+    last_seen    TEXT NOT NULL
+);
+CREATE INDEX idx_nodes_role ON nodes(role);
+CREATE INDEX idx_nodes_last_seen ON nodes(last_seen);
+
+INSERT INTO nodes (node_id, role, trust_tier, endpoints, capabilities, last_seen)
+VALUES ('edge-1', 'edge', 'trusted', '[]', '["echo"]', '2025-01-01T00:00:00+00:00');
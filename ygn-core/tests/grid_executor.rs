@@ -0,0 +1,152 @@
+//! The grid executor routes tool calls to gateways advertising the tool.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+use ygn_core::auth::ApiKeyAuth;
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState};
+use ygn_core::grid::{GridExecutor, RemoteExecuteTool};
+use ygn_core::hardware::HardwareTool;
+use ygn_core::registry::{Endpoint, InMemoryRegistry, NodeInfo, NodeRegistry, NodeRole, TrustTier};
+use ygn_core::tool::{EchoTool, Tool, ToolRegistry};
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-grid-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+fn echo_only() -> ToolRegistry {
+    let mut tools = ToolRegistry::new();
+    tools.register(Box::new(EchoTool));
+    tools
+}
+
+fn echo_and_hardware() -> ToolRegistry {
+    let mut tools = echo_only();
+    tools.register(Box::new(HardwareTool::with_seed(7)));
+    tools
+}
+
+/// A gateway serving `tools`, and the token that shuts it down.
+struct Node {
+    info: NodeInfo,
+    shutdown: CancellationToken,
+    join: tokio::task::JoinHandle<()>,
+}
+
+async fn serve(node_id: &str, tools: fn() -> ToolRegistry) -> Node {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let state = AppState {
+        auth: Arc::new(ApiKeyAuth::default()),
+        mcp_tools: tools,
+        ..test_state()
+    };
+    let shutdown = state.shutdown.clone();
+    let app = build_router_with_state(state);
+    let token = shutdown.clone();
+    let join = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(token.cancelled_owned())
+            .await
+            .unwrap();
+    });
+    let info = NodeInfo {
+        node_id: node_id.to_string(),
+        role: NodeRole::Core,
+        endpoints: vec![Endpoint {
+            protocol: "http".into(),
+            address,
+        }],
+        trust_tier: TrustTier::Trusted,
+        capabilities: tools().list().into_iter().map(|spec| spec.name).collect(),
+        last_seen: chrono::Utc::now(),
+        metadata: json!({}),
+    };
+    Node {
+        info,
+        shutdown,
+        join,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn routes_by_capability_and_fails_over() {
+    let a = serve("node-a", echo_only).await;
+    let b = serve("node-b", echo_and_hardware).await;
+    let registry = Arc::new(InMemoryRegistry::new());
+    registry.register(a.info.clone()).await.unwrap();
+    registry.register(b.info.clone()).await.unwrap();
+    let executor = GridExecutor::new(registry);
+
+    // Only node-b has the hardware tool.
+    for _ in 0..2 {
+        let run = executor
+            .execute(
+                "hardware",
+                json!({ "action": { "type": "sense", "sensor_type": "light" } }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.node_id, "node-b");
+        assert!(run.result.success, "{:?}", run.result.error);
+    }
+
+    // Both have echo; round-robin alternates between them.
+    let first = executor
+        .execute("echo", json!({ "input": "one" }))
+        .await
+        .unwrap();
+    let second = executor
+        .execute("echo", json!({ "input": "two" }))
+        .await
+        .unwrap();
+    assert_eq!(first.result.output(), "one");
+    assert_ne!(first.node_id, second.node_id);
+
+    // With node-a gone, every echo call lands on node-b.
+    a.shutdown.cancel();
+    a.join.await.unwrap();
+    for input in ["three", "four"] {
+        let run = executor
+            .execute("echo", json!({ "input": input }))
+            .await
+            .unwrap();
+        assert_eq!(run.node_id, "node-b");
+        assert_eq!(run.result.output(), input);
+    }
+
+    b.shutdown.cancel();
+    b.join.await.unwrap();
+    let err = executor
+        .execute("echo", json!({ "input": "five" }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("are unreachable"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn remote_execute_tool_reports_the_node() {
+    let b = serve("node-b", echo_and_hardware).await;
+    let registry = Arc::new(InMemoryRegistry::new());
+    registry.register(b.info.clone()).await.unwrap();
+    let tool = RemoteExecuteTool::new(Arc::new(GridExecutor::new(registry)));
+
+    let result = tool
+        .execute(json!({ "tool": "echo", "arguments": { "input": "hi" } }))
+        .await
+        .unwrap();
+    assert!(result.success);
+    let output: serde_json::Value = serde_json::from_str(&result.output()).unwrap();
+    assert_eq!(output, json!({ "node_id": "node-b", "output": "hi" }));
+
+    let err = tool
+        .execute(json!({ "tool": "shell", "arguments": {} }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no trusted node advertises tool 'shell'");
+}
@@ -0,0 +1,114 @@
+//! CLI tests for `ygn-core init`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ygn() -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env_remove("YGN_NODE_ROLE")
+        .env_remove("YGN_TRUST_TIER")
+        .env_remove("YGN_GATEWAY_BIND");
+    cmd
+}
+
+fn temp_config() -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("ygn-init-cli-{}", uuid::Uuid::new_v4()))
+        .join("config.toml")
+}
+
+fn init(path: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    ygn()
+        .arg("--config")
+        .arg(path)
+        .args(["init", "--non-interactive"])
+        .args(args)
+        .assert()
+}
+
+fn validate(path: &std::path::Path) {
+    ygn()
+        .args(["config", "validate"])
+        .arg(path)
+        .assert()
+        .success()
+        .stdout("OK\n");
+}
+
+#[test]
+fn each_profile_writes_a_valid_config() {
+    let cases: [(&str, &[&str], &[&str]); 3] = [
+        (
+            "edge",
+            &["--registry-url", "http://10.0.0.5:3000"],
+            &[
+                "node_role = \"edge\"",
+                "trust_tier = \"untrusted\"",
+                "simulated = true",
+                "remote_url = \"http://10.0.0.5:3000\"",
+            ],
+        ),
+        (
+            "brain",
+            &["--providers", "anthropic,ollama"],
+            &[
+                "node_role = \"brain\"",
+                "simulated = false",
+                "export ANTHROPIC_API_KEY",
+                "ollama: no key needed",
+            ],
+        ),
+        (
+            "core",
+            &["--bind", "10.0.0.7:3000"],
+            &[
+                "node_role = \"core\"",
+                "trust_tier = \"trusted\"",
+                "gateway_bind = \"10.0.0.7:3000\"",
+            ],
+        ),
+    ];
+    for (profile, args, expected) in cases {
+        let path = temp_config();
+        let mut full = vec!["--profile", profile];
+        full.extend_from_slice(args);
+        init(&path, &full)
+            .success()
+            .stdout(predicate::str::contains(format!("Wrote {profile} config")));
+
+        validate(&path);
+        let text = std::fs::read_to_string(&path).unwrap();
+        for line in expected {
+            assert!(text.contains(line), "{profile}: missing {line}\n{text}");
+        }
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
+
+#[test]
+fn missing_required_values_fail_without_prompting() {
+    let path = temp_config();
+    init(&path, &["--profile", "edge"])
+        .failure()
+        .stderr(predicate::str::contains("--registry-url"));
+    init(&path, &["--profile", "brain"])
+        .failure()
+        .stderr(predicate::str::contains("--providers"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn existing_config_needs_force() {
+    let path = temp_config();
+    init(&path, &["--profile", "core"]).success();
+    std::fs::write(&path, "# hand-edited\n").unwrap();
+
+    init(&path, &["--profile", "core"])
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "# hand-edited\n");
+
+    init(&path, &["--profile", "core", "--force"]).success();
+    validate(&path);
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
@@ -0,0 +1,103 @@
+//! Integration tests for MCP client mode: a second `ygn-core mcp` process
+//! acts as the external server and its tools are imported over stdio.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use ygn_core::mcp_client::{import_configured_tools, McpClient, McpServerConfig};
+use ygn_core::tool::{EchoTool, Tool, ToolRegistry};
+
+fn child_config() -> McpServerConfig {
+    McpServerConfig {
+        command: env!("CARGO_BIN_EXE_ygn-core").to_string(),
+        args: vec!["mcp".to_string()],
+        timeout_secs: 10,
+    }
+}
+
+fn ygn_core_server() -> Arc<McpClient> {
+    Arc::new(McpClient::new("ygn-child", child_config()))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn imported_echo_tool_forwards_calls() {
+    let client = ygn_core_server();
+    let tools = client.import_tools().await.unwrap();
+    let echo = tools
+        .iter()
+        .find(|t| t.name() == "ygn-child/echo")
+        .expect("echo tool imported");
+    assert_eq!(echo.parameters_schema()["type"], "object");
+
+    let result = echo
+        .execute(json!({ "input": "over the wire" }))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert_eq!(result.output(), "over the wire");
+
+    client.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_remote_tool_is_an_error() {
+    let client = ygn_core_server();
+    let result = client.call_tool("does_not_exist", json!({})).await;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("ygn-child"), "{err}");
+    client.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restarts_child_after_it_dies() {
+    let client = ygn_core_server();
+    client.connect().await.unwrap();
+    let first_pid = client.pid().await.expect("child running");
+
+    std::process::Command::new("kill")
+        .args(["-9", &first_pid.to_string()])
+        .status()
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let result = client
+        .call_tool("echo", json!({ "input": "again" }))
+        .await
+        .unwrap();
+    assert_eq!(result.output(), "again");
+    let second_pid = client.pid().await.expect("child restarted");
+    assert_ne!(first_pid, second_pid);
+
+    client.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn imported_tools_do_not_shadow_local_tools() {
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let servers = [("ygn-child".to_string(), child_config())].into();
+
+    let clients = import_configured_tools(&servers, &mut registry).await;
+    let names: Vec<String> = registry.list().into_iter().map(|s| s.name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "echo",
+            "ygn-child/echo",
+            "ygn-child/hardware",
+            "ygn-child/run_skill"
+        ]
+    );
+    assert_eq!(
+        registry.get("echo").unwrap().description(),
+        EchoTool.description()
+    );
+
+    // Importing the same server again registers nothing new.
+    let again = import_configured_tools(&servers, &mut registry).await;
+    assert_eq!(registry.len(), 4);
+
+    for client in clients.iter().chain(&again) {
+        client.shutdown().await;
+    }
+}
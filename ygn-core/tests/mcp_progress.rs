@@ -0,0 +1,97 @@
+//! Progress over MCP stdio: `ygn-core mcp --demo-tools` serves the slow
+//! `countdown` tool, whose progress notifications precede its result and
+//! which `notifications/cancelled` stops early.
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+#[test]
+fn countdown_progress_precedes_the_result() {
+    let home = std::env::temp_dir().join(format!("ygn-progress-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {
+            "name": "countdown",
+            "arguments": { "steps": 3, "interval_ms": 10 },
+            "_meta": { "progressToken": "count-1" }
+        }
+    });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env("HOME", &home)
+        .env_remove("USERPROFILE")
+        .args(["mcp", "--demo-tools"])
+        .write_stdin(format!("{call}\n"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&home).ok();
+    assert!(output.status.success());
+
+    let messages: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let result = messages
+        .iter()
+        .position(|m| m["id"] == 1)
+        .expect("tools/call result");
+    assert_eq!(
+        messages[result]["result"]["content"][0]["text"],
+        "countdown finished after 3 steps"
+    );
+    let progress: Vec<&Value> = messages[..result]
+        .iter()
+        .filter(|m| m["method"] == "notifications/progress")
+        .collect();
+    assert_eq!(progress.len(), 3);
+    assert!(progress
+        .iter()
+        .all(|p| p["params"]["progressToken"] == "count-1"));
+    assert_eq!(progress[2]["params"]["progress"], 3.0);
+    assert_eq!(progress[2]["params"]["total"], 3.0);
+}
+
+#[test]
+fn cancelled_countdown_fails_early() {
+    let home = std::env::temp_dir().join(format!("ygn-cancel-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {
+            "name": "countdown",
+            "arguments": { "steps": 100, "interval_ms": 100 }
+        }
+    });
+    let cancel = json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 1, "reason": "user abort" }
+    });
+
+    let started = std::time::Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env("HOME", &home)
+        .env_remove("USERPROFILE")
+        .args(["mcp", "--demo-tools"])
+        .write_stdin(format!("{call}\n{cancel}\n"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&home).ok();
+    assert!(output.status.success());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let messages: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(messages.len(), 1, "{messages:?}");
+    assert_eq!(messages[0]["id"], 1);
+    assert_eq!(messages[0]["error"]["code"], -32800);
+}
@@ -0,0 +1,113 @@
+//! A brain-proxy gateway relays `POST /mcp` to a core node's gateway.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use ygn_core::auth::ApiKeyAuth;
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState};
+use ygn_core::mcp_proxy::McpProxy;
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-mcp-proxy-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+/// Serve a gateway with `proxy` attached and return its base URL.
+async fn serve(proxy: Option<Arc<McpProxy>>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = build_router_with_state(AppState {
+        auth: Arc::new(ApiKeyAuth::default()),
+        mcp_proxy: proxy,
+        ..test_state()
+    });
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+async fn rpc(base: &str, method: &str, params: Value) -> Value {
+    reqwest::Client::new()
+        .post(format!("{base}/mcp"))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+fn names(list: &Value) -> Vec<&str> {
+    list["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn proxy_gateway_forwards_to_core_gateway() {
+    let core = serve(None).await;
+    let proxy = McpProxy::new(&core).unwrap().with_node_id("core");
+    let edge = serve(Some(Arc::new(proxy))).await;
+
+    let list = rpc(&edge, "tools/list", json!({})).await;
+    assert_eq!(
+        names(&list),
+        vec!["echo", "hardware", "core/echo", "core/hardware"]
+    );
+
+    let local = rpc(
+        &edge,
+        "tools/call",
+        json!({ "name": "echo", "arguments": { "input": "local" } }),
+    )
+    .await;
+    assert_eq!(local["result"]["content"][0]["text"], "local");
+
+    let remote = rpc(
+        &edge,
+        "tools/call",
+        json!({ "name": "core/echo", "arguments": { "input": "relayed" } }),
+    )
+    .await;
+    assert_eq!(remote["result"]["content"][0]["text"], "relayed");
+
+    // The call reached the core node.
+    let metrics = reqwest::get(format!("{core}/metrics"))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        metrics.contains(r#"ygn_mcp_requests_total{method="tools/call"} 1"#),
+        "{metrics}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unreachable_core_keeps_local_tools() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let proxy = McpProxy::new(&format!("http://127.0.0.1:{port}")).unwrap();
+    let edge = serve(Some(Arc::new(proxy))).await;
+
+    let list = rpc(&edge, "tools/list", json!({})).await;
+    assert_eq!(names(&list), vec!["echo", "hardware"]);
+
+    let remote = rpc(
+        &edge,
+        "tools/call",
+        json!({ "name": "remote_only", "arguments": {} }),
+    )
+    .await;
+    assert_eq!(remote["error"]["code"], -32004);
+}
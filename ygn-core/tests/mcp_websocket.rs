@@ -0,0 +1,333 @@
+//! MCP over `GET /mcp/ws`: a gateway is served on an ephemeral port and a
+//! real WebSocket client exchanges JSON-RPC frames with it.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use ygn_core::auth::ApiKeyAuth;
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState, REQUEST_ID_HEADER};
+use ygn_core::policy::{PolicyAction, PolicyConfig, ToolOverride};
+use ygn_core::tool::{Tool, ToolRegistry, ToolResult};
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-mcp-ws-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+type Client =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn connect(state: AppState) -> Client {
+    connect_with_request_id(state, None).await
+}
+
+/// Like [`connect`], sending `request_id` as the upgrade's `x-request-id`.
+async fn connect_with_request_id(state: AppState, request_id: Option<&str>) -> Client {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/mcp/ws", listener.local_addr().unwrap());
+    let app = build_router_with_state(AppState {
+        auth: Arc::new(ApiKeyAuth::default()),
+        ..state
+    });
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    let mut request = url.into_client_request().unwrap();
+    if let Some(id) = request_id {
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, id.parse().unwrap());
+    }
+    let (client, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+    client
+}
+
+async fn send(client: &mut Client, message: Value) {
+    client
+        .send(Message::Text(message.to_string().into()))
+        .await
+        .unwrap();
+}
+
+async fn next_json(client: &mut Client) -> Value {
+    let frame = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("timed out waiting for frame")
+        .unwrap()
+        .unwrap();
+    match frame {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("unexpected frame: {other:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tools_list_and_calls_share_one_socket() {
+    let mut client = connect(test_state()).await;
+
+    send(
+        &mut client,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+    )
+    .await;
+    let list = next_json(&mut client).await;
+    assert_eq!(list["id"], 1);
+    let names: Vec<&str> = list["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"echo"), "{names:?}");
+
+    // Two messages in one frame, answered in order.
+    let call = |id: u64, input: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "echo", "arguments": { "input": input } }
+        })
+    };
+    client
+        .send(Message::Text(
+            format!("{}\n{}", call(2, "first"), call(3, "second")).into(),
+        ))
+        .await
+        .unwrap();
+    for (id, text) in [(2, "first"), (3, "second")] {
+        let response = next_json(&mut client).await;
+        assert_eq!(response["id"], id);
+        assert_eq!(response["result"]["content"][0]["text"], text);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn upgrade_request_id_reaches_audit_entries() {
+    let path = std::env::temp_dir().join(format!("ygn-ws-audit-{}.jsonl", uuid::Uuid::new_v4()));
+    let mut client = connect_with_request_id(
+        AppState {
+            audit_file: Some(path.clone()),
+            ..test_state()
+        },
+        Some("ws-req-7"),
+    )
+    .await;
+
+    send(
+        &mut client,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "echo", "arguments": { "input": "hi" } }
+        }),
+    )
+    .await;
+    assert_eq!(next_json(&mut client).await["id"], 1);
+    client.close(None).await.unwrap();
+
+    // The audit log is appended once the connection's server finishes.
+    let mut text = String::new();
+    for _ in 0..500 {
+        text = std::fs::read_to_string(&path).unwrap_or_default();
+        if !text.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    std::fs::remove_file(&path).ok();
+    let entries: Vec<Value> = text
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert!(!entries.is_empty());
+    assert!(
+        entries.iter().all(|e| e["request_id"] == "ws-req-7"),
+        "{entries:?}"
+    );
+}
+
+#[cfg(unix)]
+fn shell_tools() -> ToolRegistry {
+    use ygn_core::sandbox::{ProcessSandbox, SandboxProfile};
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(ygn_core::shell::ShellTool::new(Box::new(
+        ProcessSandbox::new(SandboxProfile::Net),
+    ))));
+    registry
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn progress_notifications_arrive_on_the_socket() {
+    let mut client = connect(AppState {
+        mcp_tools: shell_tools,
+        // Shell calls need approval by default.
+        policy: PolicyConfig {
+            tool_overrides: BTreeMap::from([(
+                "shell".to_string(),
+                ToolOverride {
+                    action: PolicyAction::Allow,
+                    risk_level: None,
+                },
+            )]),
+            ..PolicyConfig::default()
+        },
+        ..test_state()
+    })
+    .await;
+
+    send(
+        &mut client,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {
+                "name": "shell",
+                "arguments": { "command": "printf", "args": ["a\\nb\\n"] },
+                "_meta": { "progressToken": "p1" }
+            }
+        }),
+    )
+    .await;
+    for progress in 1..=2 {
+        let notification = next_json(&mut client).await;
+        assert_eq!(notification["method"], "notifications/progress");
+        assert_eq!(notification["params"]["progressToken"], "p1");
+        assert_eq!(notification["params"]["progress"], progress as f64);
+    }
+    let response = next_json(&mut client).await;
+    assert_eq!(response["id"], 7);
+}
+
+// -- disconnects --------------------------------------------------------------
+
+static HANG_STARTED: AtomicBool = AtomicBool::new(false);
+static HANG_CANCELLED: AtomicBool = AtomicBool::new(false);
+static COUNT_RUNS: AtomicUsize = AtomicUsize::new(0);
+static HANG_TOOLS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets its flag when dropped, i.e. when the call holding it is abandoned.
+struct SetOnDrop(&'static AtomicBool);
+
+impl Drop for SetOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Runs until cancelled; counts how often the registry holding it is
+/// dropped, which happens once the connection's server has finished.
+struct HangTool;
+
+impl Drop for HangTool {
+    fn drop(&mut self) {
+        HANG_TOOLS_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for HangTool {
+    fn name(&self) -> &str {
+        "hang"
+    }
+
+    fn description(&self) -> &str {
+        "Never finishes"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    async fn execute(&self, _args: Value) -> anyhow::Result<ToolResult> {
+        let _cancelled = SetOnDrop(&HANG_CANCELLED);
+        HANG_STARTED.store(true, Ordering::SeqCst);
+        std::future::pending().await
+    }
+}
+
+/// Counts its runs.
+struct CountTool;
+
+#[async_trait::async_trait]
+impl Tool for CountTool {
+    fn name(&self) -> &str {
+        "count"
+    }
+
+    fn description(&self) -> &str {
+        "Counts calls"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    async fn execute(&self, _args: Value) -> anyhow::Result<ToolResult> {
+        COUNT_RUNS.fetch_add(1, Ordering::SeqCst);
+        anyhow::bail!("counted")
+    }
+}
+
+fn hang_tools() -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(HangTool));
+    registry.register(Box::new(CountTool));
+    registry
+}
+
+async fn wait_for(what: &str, condition: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timed out waiting for {what}"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn closing_the_socket_cancels_calls_and_drops_the_backlog() {
+    let mut client = connect(AppState {
+        mcp_tools: hang_tools,
+        ..test_state()
+    })
+    .await;
+
+    let call = |id: u64, name: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": {} }
+        })
+        .to_string()
+    };
+    let mut frame = call(1, "hang");
+    for id in 2..=5 {
+        frame = format!("{frame}\n{}", call(id, "count"));
+    }
+    client.send(Message::Text(frame.into())).await.unwrap();
+    wait_for("the hanging call to start", || {
+        HANG_STARTED.load(Ordering::SeqCst)
+    })
+    .await;
+
+    client.close(None).await.unwrap();
+    wait_for("the connection's server to finish", || {
+        HANG_TOOLS_DROPPED.load(Ordering::SeqCst) > 0
+    })
+    .await;
+    assert!(HANG_CANCELLED.load(Ordering::SeqCst));
+    assert_eq!(COUNT_RUNS.load(Ordering::SeqCst), 0);
+}
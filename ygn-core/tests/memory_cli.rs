@@ -0,0 +1,53 @@
+//! CLI tests for `ygn-core memory list`.
+
+use assert_cmd::Command;
+use ygn_core::memory::{Memory, MemoryCategory};
+use ygn_core::sqlite_memory::SqliteMemory;
+
+fn ygn(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env("HOME", home).env_remove("USERPROFILE");
+    cmd
+}
+
+#[tokio::test]
+async fn lists_a_page_of_one_category() {
+    let home = std::env::temp_dir().join(format!("ygn-memory-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(home.join(".ygn")).unwrap();
+    let db = home.join(".ygn/memory.db");
+    {
+        let memory = SqliteMemory::new(db.to_str().unwrap()).unwrap();
+        for i in 0..25 {
+            memory
+                .store(MemoryCategory::Daily, &format!("day-{i:02}"), "summary")
+                .await
+                .unwrap();
+        }
+        memory
+            .store(MemoryCategory::Core, "name", "ygn")
+            .await
+            .unwrap();
+    }
+
+    let output = ygn(&home)
+        .args(["memory", "list", "--category", "daily", "--limit", "20"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Memory entries (20 of 25):"), "{stdout}");
+    assert!(stdout.contains("day-24: summary"), "{stdout}");
+    assert!(!stdout.contains("day-04"), "{stdout}");
+    assert!(!stdout.contains("[core]"), "{stdout}");
+
+    let output = ygn(&home)
+        .args(["memory", "list", "--offset", "25", "--order", "created_at"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Memory entries (1 of 26):"), "{stdout}");
+    assert!(stdout.contains("[daily]"), "{stdout}");
+    assert!(stdout.contains("day-00"), "{stdout}");
+
+    let _ = std::fs::remove_dir_all(&home);
+}
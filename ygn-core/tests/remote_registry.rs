@@ -0,0 +1,161 @@
+//! Integration tests for `RemoteRegistry`: a gateway router is served on an
+//! ephemeral port and driven entirely through the `NodeRegistry` trait.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState};
+use ygn_core::registry::{
+    DiscoveryFilter, Endpoint, NodeInfo, NodeRegistry, NodeRole, SortBy, TrustTier,
+};
+use ygn_core::remote_registry::RemoteRegistry;
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-remote-registry-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+async fn spawn_gateway() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = build_router_with_state(test_state());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+fn node(id: &str, role: NodeRole, caps: &[&str]) -> NodeInfo {
+    NodeInfo {
+        node_id: id.to_string(),
+        role,
+        endpoints: vec![Endpoint {
+            protocol: "http".to_string(),
+            address: "127.0.0.1:3000".to_string(),
+        }],
+        trust_tier: TrustTier::Trusted,
+        capabilities: caps.iter().map(|c| c.to_string()).collect(),
+        last_seen: Utc::now(),
+        metadata: serde_json::json!({ "zone": "lab" }),
+    }
+}
+
+#[tokio::test]
+async fn remote_registry_round_trip() {
+    let reg = RemoteRegistry::new(spawn_gateway().await);
+
+    reg.register(node("brain-1", NodeRole::Brain, &["plan"]))
+        .await
+        .unwrap();
+    reg.register(node("edge-1", NodeRole::Edge, &["echo", "gpio"]))
+        .await
+        .unwrap();
+
+    let fetched = reg.get("edge-1").await.unwrap().unwrap();
+    assert_eq!(fetched.capabilities, vec!["echo", "gpio"]);
+    assert_eq!(fetched.metadata["zone"], "lab");
+    assert_eq!(fetched.endpoints[0].address, "127.0.0.1:3000");
+    assert!(reg.get("missing").await.unwrap().is_none());
+
+    let all = reg.discover(DiscoveryFilter::default()).await.unwrap();
+    assert_eq!(all.len(), 2);
+
+    let brains = reg
+        .discover(DiscoveryFilter {
+            role: Some(NodeRole::Brain),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(brains.len(), 1);
+    assert_eq!(brains[0].node_id, "brain-1");
+
+    let gpio = reg
+        .discover(DiscoveryFilter {
+            capability: Some("gpio".to_string()),
+            max_staleness_seconds: Some(60),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(gpio.len(), 1);
+    assert_eq!(gpio[0].node_id, "edge-1");
+
+    let page = reg
+        .discover(DiscoveryFilter {
+            sort_by: Some(SortBy::NodeId),
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].node_id, "edge-1");
+
+    reg.heartbeat("edge-1").await.unwrap();
+
+    assert!(reg.deregister("edge-1").await.unwrap());
+    assert!(!reg.deregister("edge-1").await.unwrap());
+}
+
+#[tokio::test]
+async fn heartbeat_for_unknown_node_maps_to_not_found() {
+    let reg = RemoteRegistry::new(spawn_gateway().await);
+    let err = reg.heartbeat("ghost").await.unwrap_err().to_string();
+    assert_eq!(err, "Node not found: ghost");
+}
+
+#[tokio::test]
+async fn connection_errors_are_retried_then_reported() {
+    // Reserve a port, then close it so connections are refused.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let reg = RemoteRegistry::new(url).with_retries(2);
+    let start = std::time::Instant::now();
+    let err = reg.get("n1").await.unwrap_err().to_string();
+    // Two backoffs: 100ms + 200ms.
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    assert!(err.contains("failed"), "{err}");
+}
+
+#[tokio::test]
+async fn retry_succeeds_once_server_comes_up() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+        let app = build_router_with_state(test_state());
+        axum::serve(listener, app).await
+    });
+
+    let reg = RemoteRegistry::new(format!("http://{addr}")).with_retries(3);
+    assert!(reg.get("n1").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn unresponsive_server_times_out() {
+    // Accepts connections but never answers.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let reg = RemoteRegistry::new(url).with_timeout(Duration::from_millis(200));
+    let err = reg
+        .discover(DiscoveryFilter::default())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("timed out"), "{err}");
+}
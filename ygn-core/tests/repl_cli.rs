@@ -0,0 +1,30 @@
+//! CLI test for `ygn-core repl`: piped input runs as a script of REPL
+//! lines.
+
+use assert_cmd::Command;
+
+#[test]
+fn piped_lines_run_as_repl_commands() {
+    let home = std::env::temp_dir().join(format!("ygn-repl-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .env("HOME", &home)
+        .env_remove("USERPROFILE")
+        .arg("repl")
+        .write_stdin("/tools\n/call echo {\n  \"input\": \"from a script\"\n}\n/skills run health-check\n/exit\n/tools\n")
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&home).ok();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("from a script"), "{stdout}");
+    assert!(stdout.contains("step 0 echo: ok health-ok"), "{stdout}");
+    // /tools ran once: the line after /exit is never read.
+    assert_eq!(
+        stdout.matches("Echoes the provided input").count(),
+        1,
+        "{stdout}"
+    );
+}
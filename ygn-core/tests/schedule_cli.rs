@@ -0,0 +1,87 @@
+//! CLI tests for `ygn-core schedule`: entries persist across invocations
+//! and can be toggled and removed.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ygn(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env("HOME", home).env_remove("USERPROFILE");
+    cmd
+}
+
+#[test]
+fn schedule_add_list_enable_remove() {
+    let home = std::env::temp_dir().join(format!("ygn-schedule-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    ygn(&home)
+        .args([
+            "schedule",
+            "add",
+            "health",
+            "--skill",
+            "health-check",
+            "--every",
+            "5m",
+            "--jitter",
+            "10s",
+            "--remember",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Scheduled 'health' to run skill 'health-check' every 300000ms",
+        ));
+    ygn(&home)
+        .args(["schedule", "add", "nightly", "--skill", "health-check"])
+        .args(["--cron", "0 3 * * *", "--disabled"])
+        .assert()
+        .success();
+
+    ygn(&home)
+        .args(["schedule", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Schedules (2)"))
+        .stdout(predicate::str::contains(
+            "health [enabled] skill health-check every 300000ms, jitter 10000ms, never run",
+        ))
+        .stdout(predicate::str::contains("nightly [disabled]"));
+
+    ygn(&home)
+        .args(["schedule", "enable", "nightly"])
+        .assert()
+        .success();
+    ygn(&home)
+        .args(["schedule", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nightly [enabled]"));
+
+    ygn(&home)
+        .args(["schedule", "remove", "health"])
+        .assert()
+        .success();
+    ygn(&home)
+        .args(["schedule", "remove", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no schedule named 'health'"));
+
+    // Unknown skills and bad cron expressions are refused.
+    ygn(&home)
+        .args([
+            "schedule", "add", "x", "--skill", "missing", "--every", "1m",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown skill 'missing'"));
+    ygn(&home)
+        .args(["schedule", "add", "x", "--skill", "health-check"])
+        .args(["--cron", "61 * * * *"])
+        .assert()
+        .failure();
+
+    std::fs::remove_dir_all(&home).ok();
+}
@@ -0,0 +1,124 @@
+//! Opening databases created with an older schema migrates them to the
+//! current version, and reopening them is a no-op.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use ygn_core::memory::{Memory, MemoryCategory};
+use ygn_core::registry::NodeRegistry;
+use ygn_core::schema;
+use ygn_core::sqlite_memory::{self, SqliteMemory};
+use ygn_core::sqlite_registry::{self, SqliteRegistry};
+
+/// Create a database file at a fresh temp path from a SQL fixture.
+fn legacy_db(fixture: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("ygn-schema-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = dir.join("legacy.db");
+    Connection::open(&db)
+        .unwrap()
+        .execute_batch(fixture)
+        .unwrap();
+    (dir, db)
+}
+
+fn applied_migrations(db: &Path) -> i64 {
+    Connection::open(db)
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+}
+
+fn has_column(db: &Path, table: &str, column: &str) -> bool {
+    Connection::open(db)
+        .unwrap()
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+            [table, column],
+            |row| row.get(0),
+        )
+        .unwrap()
+}
+
+#[tokio::test]
+async fn legacy_memory_database_is_migrated() {
+    let (dir, db) = legacy_db(include_str!("fixtures/schema/memory_legacy.sql"));
+    let path = db.to_str().unwrap();
+    assert!(!has_column(&db, "memories", "expires_at"));
+
+    let memory = SqliteMemory::new(path).unwrap();
+    let entry = memory.get(MemoryCategory::Core, "language").await.unwrap();
+    assert_eq!(
+        entry.unwrap().content,
+        "Rust is the language of the data plane"
+    );
+    let hits = memory.recall("data plane", None, 5).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    // Older builds could store a blank session id; it now means global.
+    let standup = memory.get(MemoryCategory::Daily, "standup").await.unwrap();
+    assert_eq!(standup.unwrap().session_id, None);
+    drop(memory);
+
+    let conn = Connection::open(&db).unwrap();
+    assert_eq!(
+        schema::version(&conn).unwrap(),
+        Some(sqlite_memory::SCHEMA_VERSION)
+    );
+    assert!(has_column(&db, "memories", "expires_at"));
+    let applied = applied_migrations(&db);
+
+    // Reopening applies nothing further.
+    SqliteMemory::new(path).unwrap();
+    assert_eq!(applied_migrations(&db), applied);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn legacy_registry_database_is_migrated() {
+    let (dir, db) = legacy_db(include_str!("fixtures/schema/registry_legacy.sql"));
+    let path = db.to_str().unwrap();
+    assert!(!has_column(&db, "nodes", "metadata"));
+
+    let registry = SqliteRegistry::new(path).unwrap();
+    let node = registry.get("edge-1").await.unwrap().unwrap();
+    assert_eq!(node.capabilities, vec!["echo".to_string()]);
+    assert_eq!(node.metadata, serde_json::json!({}));
+    drop(registry);
+
+    let conn = Connection::open(&db).unwrap();
+    assert_eq!(
+        schema::version(&conn).unwrap(),
+        Some(sqlite_registry::SCHEMA_VERSION)
+    );
+    let applied = applied_migrations(&db);
+
+    SqliteRegistry::new(path).unwrap();
+    assert_eq!(applied_migrations(&db), applied);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn database_ahead_of_build_is_refused() {
+    let (dir, db) = legacy_db(include_str!("fixtures/schema/memory_legacy.sql"));
+    let path = db.to_str().unwrap();
+    SqliteMemory::new(path).unwrap();
+    Connection::open(&db)
+        .unwrap()
+        .execute(
+            "INSERT INTO schema_migrations (version, description, applied_at) VALUES (?1, 'from the future', '')",
+            [sqlite_memory::SCHEMA_VERSION + 1],
+        )
+        .unwrap();
+
+    let err = SqliteMemory::new(path).unwrap_err();
+    assert!(
+        err.to_string().contains("newer than this build supports"),
+        "{err:#}"
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,91 @@
+//! CLI tests for `ygn-core skills`: an imported manifest must survive into
+//! later invocations.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ygn(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env("HOME", home).env_remove("USERPROFILE");
+    cmd
+}
+
+#[test]
+fn imported_skill_is_listed() {
+    let home = std::env::temp_dir().join(format!("ygn-skills-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+    let manifest = home.join("greet.yaml");
+    std::fs::write(
+        &manifest,
+        "manifest_version: 1\nname: greet\nversion: 0.2.0\nsteps:\n  - tool_name: echo\n    arguments: { input: hi }\n",
+    )
+    .unwrap();
+
+    ygn(&home)
+        .args(["skills", "import"])
+        .arg(&manifest)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported skill 'greet' v0.2.0"));
+    assert!(home.join(".ygn/skills/greet.yaml").exists());
+
+    ygn(&home)
+        .args(["skills", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Registered skills (2)"))
+        .stdout(predicate::str::contains("greet v0.2.0"));
+
+    // Importing over a bundled skill is refused.
+    let clash = home.join("clash.yaml");
+    std::fs::write(
+        &clash,
+        "manifest_version: 1\nname: health-check\nversion: '9'\nsteps: []\n",
+    )
+    .unwrap();
+    ygn(&home)
+        .args(["skills", "import"])
+        .arg(&clash)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("conflicts with a built-in skill"));
+
+    std::fs::remove_dir_all(&home).unwrap();
+}
+
+#[test]
+fn dry_run_prints_plan_and_fails_on_denied_steps() {
+    let home = std::env::temp_dir().join(format!("ygn-skills-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(home.join(".ygn")).unwrap();
+
+    ygn(&home)
+        .args(["skills", "run", "health-check", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Plan for skill 'health-check'"))
+        .stdout(predicate::str::contains("Allow"));
+
+    std::fs::write(
+        home.join(".ygn/config.toml"),
+        "[policy]\ndenied_tools = [\"echo\"]\n",
+    )
+    .unwrap();
+    ygn(&home)
+        .args(["skills", "run", "health-check", "--dry-run"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("DENIED"))
+        .stdout(predicate::str::contains("is on the deny list"));
+    ygn(&home)
+        .args(["skills", "run", "health-check", "--dry-run", "--json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("\"denied_steps\""));
+    ygn(&home)
+        .args(["skills", "run", "health-check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("denied or unknown steps"));
+
+    std::fs::remove_dir_all(&home).unwrap();
+}
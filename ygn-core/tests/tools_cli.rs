@@ -0,0 +1,63 @@
+//! CLI tests for `ygn-core tools history` and `tools replay`: a call made
+//! over MCP stdio is recorded and can be replayed later.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ygn(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_ygn-core"));
+    cmd.env("HOME", home).env_remove("USERPROFILE");
+    cmd
+}
+
+#[test]
+fn mcp_call_is_listed_and_replayed() {
+    let home = std::env::temp_dir().join(format!("ygn-tools-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    ygn(&home)
+        .arg("mcp")
+        .write_stdin(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo","arguments":{"input":"replay me"}}}"#
+                .to_string()
+                + "\n",
+        )
+        .assert()
+        .success();
+
+    let output = ygn(&home)
+        .args(["tools", "history", "--tool", "echo"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Tool executions (1):"), "{stdout}");
+    assert!(stdout.contains("[mcp] echo ok"), "{stdout}");
+    let id = stdout
+        .lines()
+        .nth(1)
+        .unwrap()
+        .split_whitespace()
+        .next()
+        .unwrap();
+
+    ygn(&home)
+        .args(["tools", "replay", id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("output identical"));
+
+    // The replay is recorded as a CLI execution.
+    ygn(&home)
+        .args(["tools", "history", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[cli] echo ok"));
+
+    ygn(&home)
+        .args(["tools", "replay", "no-such-id"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no tool execution with id"));
+
+    std::fs::remove_dir_all(&home).ok();
+}
@@ -0,0 +1,40 @@
+//! CLI tests for `ygn-core uacp decode`.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use ygn_core::uacp::{UacpCodec, UacpMessage};
+
+fn decode(stdin: impl Into<Vec<u8>>) -> assert_cmd::assert::Assert {
+    Command::new(env!("CARGO_BIN_EXE_ygn-core"))
+        .args(["uacp", "decode"])
+        .write_stdin(stdin)
+        .assert()
+}
+
+#[test]
+fn decodes_hex_and_base64_frames_to_json() {
+    use base64::Engine;
+    let tell = UacpMessage::tell("edge-1", b"temp=21");
+    let b64 = base64::engine::general_purpose::STANDARD.encode(UacpCodec::encode(&tell));
+    let input = format!("010000002a0000018bcfe5680000066e6f64652d3100000000\n\n{b64}\n");
+
+    let output = decode(input).success().get_output().stdout.clone();
+    let stream = serde_json::Deserializer::from_slice(&output).into_iter::<serde_json::Value>();
+    let messages: Vec<serde_json::Value> = stream.map(Result::unwrap).collect();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["verb"], "PING");
+    assert_eq!(messages[0]["message_id"], 42);
+    assert_eq!(messages[0]["sender_id"], "node-1");
+    assert_eq!(messages[1]["verb"], "TELL");
+    assert_eq!(messages[1]["payload"], "temp=21");
+
+    let back = UacpMessage::from_json(&messages[1]).unwrap();
+    assert_eq!(UacpCodec::encode(&back), UacpCodec::encode(&tell));
+}
+
+#[test]
+fn garbage_input_fails() {
+    decode("not a frame!\n")
+        .failure()
+        .stderr(predicate::str::contains("neither hex nor base64"));
+}
@@ -0,0 +1,70 @@
+//! uACP client/server over a loopback TCP socket.
+
+use std::time::Duration;
+
+use ygn_core::uacp::{UacpMessage, UacpVerb};
+use ygn_core::uacp_transport::{UacpClient, UacpServer};
+
+async fn spawn_server() -> std::net::SocketAddr {
+    let server = UacpServer::bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.serve(|msg: UacpMessage| async move {
+        match msg.verb {
+            UacpVerb::Ping => Some(msg.reply("server", &[])),
+            UacpVerb::Ask => {
+                // Answer slower questions later, so replies cross.
+                let delay: u64 = std::str::from_utf8(&msg.payload).unwrap().parse().unwrap();
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Some(UacpMessage::tell("server", &msg.payload))
+            }
+            _ => None,
+        }
+    }));
+    addr
+}
+
+#[tokio::test]
+async fn ping_gets_a_reply() {
+    let addr = spawn_server().await;
+    let client = UacpClient::connect(addr).await.unwrap();
+
+    let ping = UacpMessage::ping("client");
+    let pong = client.ask(&ping).await.unwrap();
+    assert_eq!(pong.verb, UacpVerb::Ping);
+    assert_eq!(pong.sender_id, "server");
+    assert_eq!(pong.in_reply_to, Some(ping.message_id));
+}
+
+#[tokio::test]
+async fn concurrent_asks_are_matched_by_in_reply_to() {
+    let addr = spawn_server().await;
+    let client = UacpClient::connect(addr).await.unwrap();
+
+    let slow = UacpMessage::ask("client", b"200");
+    let fast = UacpMessage::ask("client", b"10");
+    let (slow_reply, fast_reply) = tokio::join!(client.ask(&slow), client.ask(&fast));
+    let (slow_reply, fast_reply) = (slow_reply.unwrap(), fast_reply.unwrap());
+    assert_eq!(slow_reply.in_reply_to, Some(slow.message_id));
+    assert_eq!(slow_reply.payload, b"200");
+    assert_eq!(fast_reply.in_reply_to, Some(fast.message_id));
+    assert_eq!(fast_reply.payload, b"10");
+}
+
+#[tokio::test]
+async fn unanswered_ask_times_out() {
+    let addr = spawn_server().await;
+    let client = UacpClient::connect(addr)
+        .await
+        .unwrap()
+        .with_timeout(Duration::from_millis(100));
+
+    client
+        .send(&UacpMessage::tell("client", b"fire and forget"))
+        .await
+        .unwrap();
+    let err = client
+        .ask(&UacpMessage::observe("client", b"{}"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no uACP reply"), "{err}");
+}
@@ -0,0 +1,148 @@
+//! Integration tests for `WebSocketChannel`: a gateway router is served on an
+//! ephemeral port and real WebSocket clients connect to `/ws`.
+
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use ygn_core::channel::{Channel, SendMessage};
+use ygn_core::config::NodeConfig;
+use ygn_core::gateway::{build_router_with_state, AppState};
+use ygn_core::websocket::WebSocketChannel;
+
+/// Gateway state over the default config, keeping its stores in a fresh
+/// temporary directory rather than `~/.ygn`.
+fn test_state() -> AppState {
+    let dir = std::env::temp_dir().join(format!("ygn-ws-channel-{}", uuid::Uuid::new_v4()));
+    AppState::new(NodeConfig::default(), &dir)
+}
+
+type Client =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn spawn_gateway() -> (String, WebSocketChannel) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+    let state = test_state();
+    let channel = state.ws_channel.clone();
+    let app = build_router_with_state(state);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (url, channel)
+}
+
+/// Connect and wait until the gateway has registered the client.
+async fn connect(
+    channel: &WebSocketChannel,
+    request: impl IntoClientRequest + Unpin,
+    id: &str,
+) -> Client {
+    let (client, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+    wait_for(|| channel.connected_clients().iter().any(|c| c == id)).await;
+    client
+}
+
+async fn wait_for(cond: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if cond() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("condition not reached");
+}
+
+async fn next_text(client: &mut Client) -> String {
+    let frame = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("timed out waiting for frame")
+        .unwrap()
+        .unwrap();
+    match frame {
+        Message::Text(text) => text.to_string(),
+        other => panic!("unexpected frame: {other:?}"),
+    }
+}
+
+fn reply(content: &str, recipient: Option<&str>) -> SendMessage {
+    SendMessage {
+        content: content.to_string(),
+        recipient: recipient.map(String::from),
+        metadata: serde_json::json!({}),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn websocket_message_round_trip() {
+    let (url, channel) = spawn_gateway().await;
+    let mut alice = connect(&channel, format!("{url}?client_id=alice"), "alice").await;
+
+    alice.send(Message::Text("ping".into())).await.unwrap();
+    let inbound = tokio::time::timeout(Duration::from_secs(5), channel.listen())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(inbound.channel, "websocket");
+    assert_eq!(inbound.sender, "alice");
+    assert_eq!(inbound.content, "ping");
+    assert_eq!(inbound.metadata["connection_id"], "alice");
+
+    // The runtime echoes back through the channel.
+    channel
+        .send(reply(
+            &format!("echo: {}", inbound.content),
+            Some(&inbound.sender),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(next_text(&mut alice).await, "echo: ping");
+}
+
+#[tokio::test]
+async fn websocket_targeted_broadcast_and_cleanup() {
+    let (url, channel) = spawn_gateway().await;
+    let mut alice = connect(&channel, format!("{url}?client_id=alice"), "alice").await;
+
+    let mut request = url.as_str().into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("x-client-id", "bob".parse().unwrap());
+    let mut bob = connect(&channel, request, "bob").await;
+    assert_eq!(channel.connected_clients(), vec!["alice", "bob"]);
+
+    channel.send(reply("for bob", Some("bob"))).await.unwrap();
+    channel.send(reply("for all", None)).await.unwrap();
+    assert_eq!(next_text(&mut bob).await, "for bob");
+    assert_eq!(next_text(&mut bob).await, "for all");
+    // Alice never saw the targeted message.
+    assert_eq!(next_text(&mut alice).await, "for all");
+
+    bob.close(None).await.unwrap();
+    wait_for(|| channel.connected_clients() == vec!["alice"]).await;
+    assert!(channel.send(reply("gone", Some("bob"))).await.is_err());
+}
+
+#[tokio::test]
+async fn websocket_rejects_duplicate_client_id() {
+    let (url, channel) = spawn_gateway().await;
+    let mut alice = connect(&channel, format!("{url}?client_id=alice"), "alice").await;
+
+    let err = tokio_tungstenite::connect_async(format!("{url}?client_id=alice"))
+        .await
+        .unwrap_err();
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), 409);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // The original connection still receives its replies.
+    channel
+        .send(reply("still yours", Some("alice")))
+        .await
+        .unwrap();
+    assert_eq!(next_text(&mut alice).await, "still yours");
+}